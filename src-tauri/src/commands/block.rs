@@ -1,20 +1,39 @@
-use chrono::Utc;
+use chrono::{Local, Utc};
 use rusqlite::OptionalExtension;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use uuid::Uuid;
 
-use crate::commands::workspace::open_workspace_db;
+use crate::commands::journal::reading_order_by;
+use crate::commands::page::save_page_frontmatter;
+use crate::commands::search::quote_fts_literal;
+use crate::commands::workspace::{
+    load_auto_repair_on_read, load_indent_style, load_sanitization_rules, open_workspace_db,
+};
+use crate::error::OxinotError;
 use crate::models::block::{
     Block, BlockType, CreateBlockRequest, MoveBlockRequest, UpdateBlockRequest,
 };
-use crate::services::wiki_link_index;
+use crate::services::block_history::{self, InverseOperation};
+use crate::services::page_path_service::{self, PathMatchType};
+use crate::services::{markdown_to_blocks, wiki_link_index};
+use crate::utils::code_language;
+use crate::utils::csv::parse_csv;
 use crate::utils::fractional_index;
+use crate::utils::markdown::{
+    apply_sanitization_rules, markdown_to_block_tree, normalize_marker_layout, strip_id_markers,
+    subtree_to_plain_markdown, BlockPreviewNode, IndentStyle, SanitizationRules,
+    CHECKED_METADATA_KEY,
+};
+use crate::utils::metadata_schema::{
+    format_validation_errors, MetadataSchema, MetadataValidationError,
+};
 use crate::utils::page_sync::{
-    sync_page_to_markdown, sync_page_to_markdown_after_create, sync_page_to_markdown_after_delete,
-    sync_page_to_markdown_after_move, sync_page_to_markdown_after_update,
+    self, sync_page_to_markdown, sync_page_to_markdown_after_create,
+    sync_page_to_markdown_after_delete, sync_page_to_markdown_after_merge,
+    sync_page_to_markdown_after_move, sync_page_to_markdown_after_update, SyncStrategy,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,15 +73,117 @@ pub struct MoveResult {
     /// Siblings that had their order_weight changed due to rebalancing
     /// (empty if no rebalancing occurred)
     pub affected_siblings: Vec<Block>,
+    /// How the page file was updated, only while sync strategy debugging is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_strategy: Option<SyncStrategy>,
+}
+
+/// A block returned by a mutation. Serializes as the plain block, plus
+/// `sync_strategy` while sync strategy debugging is on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedBlock {
+    #[serde(flatten)]
+    pub block: Block,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_strategy: Option<SyncStrategy>,
+}
+
+impl std::ops::Deref for SyncedBlock {
+    type Target = Block;
+
+    fn deref(&self) -> &Block {
+        &self.block
+    }
+}
+
+/// Result of a find/replace scoped to one block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceInBlockResult {
+    /// The block after replacement (unchanged when nothing matched)
+    pub block: Block,
+    /// Number of matches replaced
+    pub replacements: usize,
+    /// How the page file was updated, only while sync strategy debugging is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_strategy: Option<SyncStrategy>,
+}
+
+/// Where `preview_insert_position` predicts a new block would land
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertPositionPreview {
+    pub page_id: String,
+    pub parent_id: Option<String>,
+    /// Nesting level of the new block (root blocks are 0)
+    pub depth: usize,
+    /// Weight of the sibling the new block would follow
+    pub before_weight: Option<f64>,
+    /// Weight of the sibling the new block would precede
+    pub after_weight: Option<f64>,
+    /// Weight the new block would get
+    pub order_weight: f64,
+    /// Order key the new block would get; `None` if the siblings need rebalancing first
+    pub order_key: Option<String>,
+}
+
+/// One block in the shallow tree returned by `get_page_outline`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineEntry {
+    pub id: String,
+    pub parent_id: Option<String>,
+    /// First line of the content, cut to `OUTLINE_CONTENT_MAX_CHARS`
+    pub content: String,
+    pub block_type: BlockType,
+    /// Nesting level (root blocks are 0)
+    pub depth: usize,
+    /// Direct children, whether or not they are within the outline's depth
+    pub child_count: usize,
+    pub has_children: bool,
+}
+
+/// An alias declared by more than one block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasConflict {
+    pub alias: String,
+    /// In creation order; the first one is what `[[alias]]` resolves to
+    pub block_ids: Vec<String>,
+}
+
+/// A `((uuid))` reference whose target block no longer exists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenBlockEmbed {
+    pub from_block_id: String,
+    pub from_page_id: String,
+    pub page_title: String,
+    /// Path of the containing page (`page_paths`), for navigation
+    pub page_path: Option<String>,
+    pub missing_block_id: String,
 }
 
+/// One sibling's position as reported by `debug_order_weights`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiblingOrderWeight {
+    pub block_id: String,
+    pub order_weight: f64,
+    /// The string key that actually orders the siblings
+    pub order_key: String,
+    /// The key fails `fractional_index::is_valid_key`, so nothing can be inserted next to it
+    pub invalid_key: bool,
+    /// The key does not sort strictly after the previous sibling's key
+    pub out_of_order: bool,
+}
 
 /// Helper: load a single block from DB, or return None.
 fn get_block_by_id_opt(conn: &Connection, id: &str) -> Result<Option<Block>, String> {
     let block_opt = conn
         .query_row(
             "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
          FROM blocks WHERE id = ?",
             [id],
             |row| {
@@ -72,6 +193,7 @@ fn get_block_by_id_opt(conn: &Connection, id: &str) -> Result<Option<Block>, Str
                     parent_id: row.get(2)?,
                     content: row.get(3)?,
                     order_weight: row.get(4)?,
+                    order_key: row.get(10)?,
                     is_collapsed: row.get::<_, i32>(5)? != 0,
                     block_type: parse_block_type(row.get::<_, String>(6)?),
                     language: row.get(7)?,
@@ -101,16 +223,16 @@ fn get_block_with_ancestors(
 ) -> Result<Option<BlockWithPath>, String> {
     let sql = r#"
 WITH RECURSIVE
-anc(id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, depth) AS (
-    SELECT id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, 0
+anc(id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, order_key, depth) AS (
+    SELECT id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, order_key, 0
     FROM blocks
     WHERE id = ?1
     UNION ALL
-    SELECT b.id, b.page_id, b.parent_id, b.content, b.order_weight, b.is_collapsed, b.block_type, b.language, b.created_at, b.updated_at, anc.depth + 1
+    SELECT b.id, b.page_id, b.parent_id, b.content, b.order_weight, b.is_collapsed, b.block_type, b.language, b.created_at, b.updated_at, b.order_key, anc.depth + 1
     FROM blocks b
     JOIN anc ON anc.parent_id = b.id
 )
-SELECT id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, depth
+SELECT id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, order_key, depth
 FROM anc
 ORDER BY depth DESC
 "#;
@@ -125,6 +247,7 @@ ORDER BY depth DESC
                     parent_id: row.get(2)?,
                     content: row.get(3)?,
                     order_weight: row.get(4)?,
+                    order_key: row.get(10)?,
                     is_collapsed: row.get::<_, i32>(5)? != 0,
                     block_type: parse_block_type(row.get::<_, String>(6)?),
                     language: row.get(7)?,
@@ -132,7 +255,7 @@ ORDER BY depth DESC
                     updated_at: row.get(9)?,
                     metadata: HashMap::new(),
                 },
-                row.get::<_, i64>(10)?,
+                row.get::<_, i64>(11)?,
             ))
         })
         .map_err(|e| e.to_string())?
@@ -166,21 +289,37 @@ pub struct BlockSearchResult {
     pub page_path: String,
     pub block_path: String,
     pub full_path: String,
+    /// HTML-escaped content around the match, with matches wrapped in `<mark>`...`</mark>`
+    pub snippet: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchBlocksRequest {
     pub query: String,
     pub limit: Option<i64>,
+    /// Tokens of context kept on each side of a match in `snippet` (default 8)
+    pub snippet_context: Option<u32>,
 }
 
 /// Resolve a block by a breadcrumb-like path within a page:
 /// Example input: ["X", "Y"] resolves the child block named "X" under root,
 /// then child "Y" under "X", matching by exact content equality (trimmed).
+/// A segment with no exact match falls back to Unicode NFC comparison, then (with
+/// `case_insensitive`) to comparison ignoring case.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolveBlockPathRequest {
     pub page_id: String,
     pub segments: Vec<String>,
+    #[serde(default)]
+    pub case_insensitive: bool,
+}
+
+/// Block found by `resolve_block_path`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedBlockPath {
+    pub block_id: String,
+    /// Loosest match needed by any segment of the path
+    pub match_type: PathMatchType,
 }
 
 //
@@ -218,7 +357,7 @@ pub async fn get_blocks(
         .join(",");
     let sql = format!(
         "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
          FROM blocks WHERE id IN ({})",
         placeholders
     );
@@ -235,6 +374,7 @@ pub async fn get_blocks(
                     parent_id: row.get(2)?,
                     content: row.get(3)?,
                     order_weight: row.get(4)?,
+                    order_key: row.get(10)?,
                     is_collapsed: row.get::<_, i32>(5)? != 0,
                     block_type: parse_block_type(row.get::<_, String>(6)?),
                     language: row.get(7)?,
@@ -270,24 +410,47 @@ pub async fn get_block_ancestors(
 }
 
 /// Get the subtree blocks for embedding (root block + all descendants).
-/// Returns blocks in unspecified order; caller can group by parent/order_weight.
+/// Returns blocks in unspecified order; caller can group by parent and sort with `Block::cmp_position`.
 #[tauri::command]
 pub async fn get_block_subtree(
     workspace_path: String,
     request: GetBlockSubtreeRequest,
 ) -> Result<Vec<Block>, String> {
     let conn = open_workspace_db(&workspace_path)?;
+    load_block_subtree(&conn, &request.block_id, request.max_depth)
+}
+
+/// Serialize a block and all of its descendants to clean markdown for the clipboard:
+/// no `ID::` or metadata lines, indentation re-based so the block starts at depth 0
+#[tauri::command]
+pub async fn copy_block_subtree_to_clipboard_markdown(
+    workspace_path: String,
+    block_id: String,
+) -> Result<String, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let blocks = load_block_subtree(&conn, &block_id, None)?;
+    Ok(subtree_to_plain_markdown(
+        &blocks,
+        &block_id,
+        load_indent_style(&workspace_path),
+    ))
+}
 
+/// Load a block and its descendants down to `max_depth` (root = depth 0), with metadata
+pub(crate) fn load_block_subtree(
+    conn: &Connection,
+    block_id: &str,
+    max_depth: Option<i64>,
+) -> Result<Vec<Block>, String> {
     // First, ensure the root exists (and capture page_id so we can scope recursion if needed).
-    let root = get_block_by_id_opt(&conn, &request.block_id)?
-        .ok_or_else(|| "Block not found".to_string())?;
+    let root = get_block_by_id_opt(conn, block_id)?.ok_or_else(|| "Block not found".to_string())?;
 
-    let max_depth = request.max_depth.unwrap_or(1000).clamp(0, 10_000);
+    let max_depth = max_depth.unwrap_or(1000).clamp(0, 10_000);
 
     let sql = r#"
 WITH RECURSIVE descendants AS (
     SELECT
-        id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at,
+        id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, order_key,
         0 as depth
     FROM blocks
     WHERE id = ?1
@@ -295,13 +458,13 @@ WITH RECURSIVE descendants AS (
     UNION ALL
 
     SELECT
-        b.id, b.page_id, b.parent_id, b.content, b.order_weight, b.is_collapsed, b.block_type, b.language, b.created_at, b.updated_at,
+        b.id, b.page_id, b.parent_id, b.content, b.order_weight, b.is_collapsed, b.block_type, b.language, b.created_at, b.updated_at, b.order_key,
         d.depth + 1
     FROM blocks b
     JOIN descendants d ON b.parent_id = d.id
     WHERE d.depth < ?2
 )
-SELECT id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at
+SELECT id, page_id, parent_id, content, order_weight, is_collapsed, block_type, language, created_at, updated_at, order_key
 FROM descendants
 "#;
 
@@ -314,6 +477,7 @@ FROM descendants
                 parent_id: row.get(2)?,
                 content: row.get(3)?,
                 order_weight: row.get(4)?,
+                order_key: row.get(10)?,
                 is_collapsed: row.get::<_, i32>(5)? != 0,
                 block_type: parse_block_type(row.get::<_, String>(6)?),
                 language: row.get(7)?,
@@ -328,7 +492,7 @@ FROM descendants
 
     // Load metadata for all blocks in a single query
     let block_ids: Vec<String> = blocks.iter().map(|b| b.id.clone()).collect();
-    let metadata_map = load_blocks_metadata(&conn, &block_ids)?;
+    let metadata_map = load_blocks_metadata(conn, &block_ids)?;
     for block in &mut blocks {
         block.metadata = metadata_map.get(&block.id).cloned().unwrap_or_default();
     }
@@ -356,50 +520,21 @@ pub async fn get_page_blocks(
         return Ok(Vec::new());
     }
 
-    // Try to query blocks, and repair if constraint violation occurs
-    loop {
-        // Collect blocks in a separate scope to drop statement before retry
-        let query_result = query_blocks_for_page(&conn, &page_id);
+    let mut blocks = query_page_blocks_checked(
+        &mut conn,
+        &page_id,
+        load_auto_repair_on_read(&workspace_path),
+        "get_page_blocks",
+    )?;
 
-        match query_result {
-            Ok(mut blocks) => {
-                // Load metadata for all blocks in a single query
-                let block_ids: Vec<String> = blocks.iter().map(|b| b.id.clone()).collect();
-                let metadata_map = load_blocks_metadata(&conn, &block_ids)?;
-                for block in &mut blocks {
-                    block.metadata = metadata_map.get(&block.id).cloned().unwrap_or_default();
-                }
-
-                return Ok(blocks);
-            }
-            Err(e) => {
-                if e.contains("FOREIGN KEY constraint") {
-                    eprintln!(
-                        "[get_page_blocks] FOREIGN KEY constraint failed for page {}: {}",
-                        page_id, e
-                    );
-                    eprintln!("[get_page_blocks] Attempting database repair...");
-                    // Perform inline database repair
-                    match perform_db_repair(&mut conn) {
-                        Ok(()) => {
-                            eprintln!("[get_page_blocks] Database repair completed successfully, retrying query");
-                            // Retry the loop
-                            continue;
-                        }
-                        Err(repair_err) => {
-                            eprintln!("[get_page_blocks] Database repair failed: {}", repair_err);
-                            return Err(format!(
-                                "FOREIGN KEY constraint failed and repair unsuccessful: {}",
-                                e
-                            ));
-                        }
-                    }
-                } else {
-                    return Err(e);
-                }
-            }
-        }
+    // Load metadata for all blocks in a single query
+    let block_ids: Vec<String> = blocks.iter().map(|b| b.id.clone()).collect();
+    let metadata_map = load_blocks_metadata(&conn, &block_ids)?;
+    for block in &mut blocks {
+        block.metadata = metadata_map.get(&block.id).cloned().unwrap_or_default();
     }
+
+    Ok(blocks)
 }
 
 /// Optimized page loading: get blocks without metadata for faster initial render
@@ -422,42 +557,14 @@ pub async fn get_page_blocks_fast(
         return Ok(Vec::new());
     }
 
-    // Try to query blocks without metadata, and repair if constraint violation occurs
-    loop {
-        let query_result = query_blocks_for_page(&conn, &page_id);
-
-        match query_result {
-            Ok(blocks) => {
-                // Return blocks WITHOUT metadata for fast initial load
-                // Metadata will be loaded separately via get_page_blocks_metadata
-                return Ok(blocks);
-            }
-            Err(e) => {
-                if e.contains("FOREIGN KEY constraint") {
-                    eprintln!(
-                        "[get_page_blocks_fast] FOREIGN KEY constraint failed for page {}: {}",
-                        page_id, e
-                    );
-                    eprintln!("[get_page_blocks_fast] Attempting database repair...");
-                    match perform_db_repair(&mut conn) {
-                        Ok(()) => {
-                            eprintln!("[get_page_blocks_fast] Database repair completed successfully, retrying query");
-                            continue;
-                        }
-                        Err(repair_err) => {
-                            eprintln!("[get_page_blocks_fast] Database repair failed: {}", repair_err);
-                            return Err(format!(
-                                "FOREIGN KEY constraint failed and repair unsuccessful: {}",
-                                e
-                            ));
-                        }
-                    }
-                } else {
-                    return Err(e);
-                }
-            }
-        }
-    }
+    // Return blocks WITHOUT metadata for fast initial load
+    // Metadata will be loaded separately via get_page_blocks_metadata
+    query_page_blocks_checked(
+        &mut conn,
+        &page_id,
+        load_auto_repair_on_read(&workspace_path),
+        "get_page_blocks_fast",
+    )
 }
 
 /// Load metadata for multiple blocks - called asynchronously after initial page load
@@ -499,10 +606,10 @@ pub async fn get_page_blocks_root(
     let mut stmt = conn
         .prepare(
             "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
              FROM blocks
              WHERE page_id = ? AND parent_id IS NULL
-             ORDER BY order_weight",
+             ORDER BY order_key",
         )
         .map_err(|e| e.to_string())?;
 
@@ -514,6 +621,7 @@ pub async fn get_page_blocks_root(
                 parent_id: row.get(2)?,
                 content: row.get(3)?,
                 order_weight: row.get(4)?,
+                order_key: row.get(10)?,
                 is_collapsed: row.get::<_, i32>(5)? != 0,
                 block_type: parse_block_type(row.get::<_, String>(6)?),
                 language: row.get(7)?,
@@ -549,10 +657,10 @@ pub async fn get_page_blocks_children(
 
     let sql = format!(
         "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
          FROM blocks
          WHERE parent_id IN ({})
-         ORDER BY parent_id, order_weight",
+         ORDER BY parent_id, order_key",
         placeholders
     );
 
@@ -568,6 +676,7 @@ pub async fn get_page_blocks_children(
                     parent_id: row.get(2)?,
                     content: row.get(3)?,
                     order_weight: row.get(4)?,
+                    order_key: row.get(10)?,
                     is_collapsed: row.get::<_, i32>(5)? != 0,
                     block_type: parse_block_type(row.get::<_, String>(6)?),
                     language: row.get(7)?,
@@ -584,6 +693,89 @@ pub async fn get_page_blocks_children(
     Ok(blocks)
 }
 
+/// Outline entries keep at most this many characters of the block's first line
+const OUTLINE_CONTENT_MAX_CHARS: usize = 80;
+
+/// Lightweight table of contents: the top `max_depth` levels (default 2) of a page,
+/// flattened in document order, without metadata or deeper descendants.
+#[tauri::command]
+pub async fn get_page_outline(
+    workspace_path: String,
+    page_id: String,
+    max_depth: Option<usize>,
+) -> Result<Vec<OutlineEntry>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_page_outline(&conn, &page_id, max_depth.unwrap_or(2))
+}
+
+fn load_page_outline(
+    conn: &Connection,
+    page_id: &str,
+    max_depth: usize,
+) -> Result<Vec<OutlineEntry>, String> {
+    if max_depth == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "WITH RECURSIVE outline(id, parent_id, content, block_type, order_key, depth) AS (
+                SELECT id, parent_id, content, block_type, order_key, 0
+                FROM blocks
+                WHERE page_id = ?1 AND parent_id IS NULL
+                UNION ALL
+                SELECT b.id, b.parent_id, b.content, b.block_type, b.order_key, o.depth + 1
+                FROM blocks b
+                JOIN outline o ON b.parent_id = o.id
+                WHERE o.depth + 1 < ?2
+             )
+             SELECT o.id, o.parent_id, o.content, COALESCE(o.block_type, 'bullet'), o.depth,
+                    (SELECT COUNT(*) FROM blocks c WHERE c.parent_id = o.id)
+             FROM outline o
+             ORDER BY o.depth, o.order_key",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let entries = stmt
+        .query_map(params![page_id, max_depth as i64], |row| {
+            let content: String = row.get(2)?;
+            let child_count = row.get::<_, i64>(5)? as usize;
+            Ok(OutlineEntry {
+                id: row.get(0)?,
+                parent_id: row.get(1)?,
+                content: outline_text(&content),
+                block_type: parse_block_type(row.get::<_, String>(3)?),
+                depth: row.get::<_, i64>(4)? as usize,
+                child_count,
+                has_children: child_count > 0,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Rows arrive level by level; reorder depth-first so each entry follows its parent
+    Ok(reading_order_by(entries, |entry| {
+        (entry.id.clone(), entry.parent_id.clone())
+    }))
+}
+
+/// First non-empty line of a block, cut at a character boundary
+fn outline_text(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("");
+    if line.chars().count() > OUTLINE_CONTENT_MAX_CHARS {
+        let mut cut: String = line.chars().take(OUTLINE_CONTENT_MAX_CHARS).collect();
+        cut.push('…');
+        cut
+    } else {
+        line.to_string()
+    }
+}
+
 /// Load page blocks with metadata and children hierarchy in a single database round-trip.
 /// Combines three IPC calls into one for significantly better performance.
 #[tauri::command]
@@ -620,69 +812,89 @@ fn get_page_blocks_complete_impl(
         });
     }
 
-    loop {
-        let query_result = query_blocks_for_page(&conn, page_id);
+    let all_blocks = query_page_blocks_checked(
+        &mut conn,
+        page_id,
+        load_auto_repair_on_read(workspace_path),
+        "get_page_blocks_complete",
+    )?;
+    let block_ids: Vec<String> = all_blocks.iter().map(|b| b.id.clone()).collect();
+    let mut metadata_map = load_blocks_metadata(&conn, &block_ids)?;
 
-        match query_result {
-            Ok(all_blocks) => {
-                let block_ids: Vec<String> = all_blocks.iter().map(|b| b.id.clone()).collect();
-                let mut metadata_map = load_blocks_metadata(&conn, &block_ids)?;
+    let mut root_blocks = Vec::new();
 
-                let mut root_blocks = Vec::new();
+    for mut block in all_blocks {
+        block.metadata = metadata_map.remove(&block.id).unwrap_or_default();
 
-                for mut block in all_blocks {
-                    block.metadata = metadata_map
-                        .remove(&block.id)
-                        .unwrap_or_default();
+        // Include ALL blocks (root and nested) in root_blocks
+        // The frontend's normalizeBlocks() will handle the hierarchy correctly
+        root_blocks.push(block);
+    }
 
-                    // Include ALL blocks (root and nested) in root_blocks
-                    // The frontend's normalizeBlocks() will handle the hierarchy correctly
-                    root_blocks.push(block);
-                }
+    Ok(crate::models::block::PageBlocksComplete {
+        root_blocks,
+        children_by_parent: HashMap::new(),
+        metadata: metadata_map,
+    })
+}
 
-                return Ok(crate::models::block::PageBlocksComplete {
-                    root_blocks,
-                    children_by_parent: HashMap::new(),
-                    metadata: metadata_map,
-                });
-            }
-            Err(e) => {
-                if e.contains("FOREIGN KEY constraint") {
-                    eprintln!(
-                        "[get_page_blocks_complete] FOREIGN KEY constraint failed for page {}: {}",
-                        page_id, e
-                    );
-                    eprintln!("[get_page_blocks_complete] Attempting database repair...");
-                    match perform_db_repair(&mut conn) {
-                        Ok(()) => {
-                            eprintln!("[get_page_blocks_complete] Database repair completed successfully, retrying query");
-                            continue;
-                        }
-                        Err(repair_err) => {
-                            eprintln!("[get_page_blocks_complete] Database repair failed: {}", repair_err);
-                            return Err(format!(
-                                "FOREIGN KEY constraint failed and repair unsuccessful: {}",
-                                e
-                            ));
-                        }
-                    }
-                } else {
-                    return Err(e);
-                }
-            }
+/// Preview how markdown would be parsed into blocks, without writing to DB or disk
+#[tauri::command]
+pub fn parse_markdown_preview(
+    workspace_path: String,
+    markdown: String,
+) -> Result<Vec<BlockPreviewNode>, String> {
+    Ok(markdown_to_block_tree(
+        &markdown,
+        load_indent_style(&workspace_path),
+    ))
+}
+
+/// Guess a code snippet's language, as done for new code blocks without one
+#[tauri::command]
+pub fn detect_code_language(code: String) -> Option<String> {
+    code_language::detect_code_language(&code).map(str::to_string)
+}
+
+/// Language to store for a block: the given one, else a guess for code blocks
+fn resolve_block_language(
+    block_type: &BlockType,
+    language: Option<String>,
+    content: &str,
+) -> Option<String> {
+    match language {
+        Some(language) => Some(language),
+        None if matches!(block_type, BlockType::Code) => {
+            code_language::detect_code_language(content).map(str::to_string)
         }
+        None => None,
+    }
+}
+
+/// Language to store after an update. Detection runs only when the block becomes a code
+/// block, so editing an existing code block never re-guesses its language.
+fn updated_block_language(
+    was_code: bool,
+    block_type: &BlockType,
+    language: Option<String>,
+    content: &str,
+) -> Option<String> {
+    if was_code {
+        language
+    } else {
+        resolve_block_language(block_type, language, content)
     }
 }
 
 /// Helper function to query blocks for a page (avoids lifetime issues)
-fn query_blocks_for_page(conn: &Connection, page_id: &str) -> Result<Vec<Block>, String> {
+pub(crate) fn query_blocks_for_page(conn: &Connection, page_id: &str) -> Result<Vec<Block>, String> {
     let mut stmt = conn
         .prepare(
             "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
             FROM blocks
             WHERE page_id = ?
-            ORDER BY parent_id NULLS FIRST, order_weight",
+            ORDER BY parent_id NULLS FIRST, order_key",
         )
         .map_err(|e| e.to_string())?;
 
@@ -694,6 +906,7 @@ fn query_blocks_for_page(conn: &Connection, page_id: &str) -> Result<Vec<Block>,
                 parent_id: row.get(2)?,
                 content: row.get(3)?,
                 order_weight: row.get(4)?,
+                order_key: row.get(10)?,
                 is_collapsed: row.get::<_, i32>(5)? != 0,
                 block_type: parse_block_type(row.get::<_, String>(6)?),
                 language: row.get(7)?,
@@ -708,6 +921,61 @@ fn query_blocks_for_page(conn: &Connection, page_id: &str) -> Result<Vec<Block>,
         .map_err(|e| e.to_string())
 }
 
+/// Query a page's blocks, handling a foreign key error as corruption.
+/// With `auto_repair` the block repair runs once and the query is retried; otherwise
+/// the read fails with a corruption error and nothing is deleted, leaving the user to
+/// inspect it (`validate_workspace_integrity`) and run `repair_db` explicitly.
+fn query_page_blocks_checked(
+    conn: &mut Connection,
+    page_id: &str,
+    auto_repair: bool,
+    caller: &str,
+) -> Result<Vec<Block>, String> {
+    query_page_blocks_checked_with(conn, page_id, auto_repair, caller, &query_blocks_for_page)
+}
+
+type BlockQuery<'a> = dyn Fn(&Connection, &str) -> Result<Vec<Block>, String> + 'a;
+
+/// `query_page_blocks_checked` reading the blocks with `query`
+fn query_page_blocks_checked_with(
+    conn: &mut Connection,
+    page_id: &str,
+    auto_repair: bool,
+    caller: &str,
+    query: &BlockQuery<'_>,
+) -> Result<Vec<Block>, String> {
+    let e = match query(conn, page_id) {
+        Err(e) if e.contains("FOREIGN KEY constraint") => e,
+        result => return result,
+    };
+    eprintln!(
+        "[{}] FOREIGN KEY constraint failed for page {}: {}",
+        caller, page_id, e
+    );
+
+    if !auto_repair {
+        return Err(OxinotError::corruption(format!(
+            "FOREIGN KEY constraint failed for page {}; run repair_db to fix it: {}",
+            page_id, e
+        ))
+        .to_string());
+    }
+
+    eprintln!("[{}] Attempting database repair...", caller);
+    if let Err(repair_err) = perform_db_repair(conn) {
+        eprintln!("[{}] Database repair failed: {}", caller, repair_err);
+        return Err(format!(
+            "FOREIGN KEY constraint failed and repair unsuccessful: {}",
+            e
+        ));
+    }
+    eprintln!(
+        "[{}] Database repair completed successfully, retrying query",
+        caller
+    );
+    query(conn, page_id)
+}
+
 /// Helper function to repair database integrity
 fn perform_db_repair(conn: &mut Connection) -> Result<(), String> {
     eprintln!("[perform_db_repair] Starting database repair...");
@@ -839,6 +1107,9 @@ fn perform_db_repair(conn: &mut Connection) -> Result<(), String> {
         );
     }
 
+    // Snapshots may reference the blocks removed or re-parented above
+    block_history::clear_history(&tx)?;
+
     tx.commit()
         .map_err(|e| format!("Failed to commit repair transaction: {}", e))?;
 
@@ -846,33 +1117,11 @@ fn perform_db_repair(conn: &mut Connection) -> Result<(), String> {
     Ok(())
 }
 
-/// Search blocks across the whole workspace DB by content substring.
-/// Returns breadcrumb-like paths (page path + block path) for completion/navigation.
-///
-/// NOTE:
-/// - This uses `LIKE` matching for now. You can later replace with FTS.
-/// - Depth/path are computed via recursive CTEs.
-/// - Path segments are derived from block content (trimmed, newlines collapsed).
-#[tauri::command]
-pub async fn search_blocks(
-    workspace_path: String,
-    request: SearchBlocksRequest,
-) -> Result<Vec<BlockSearchResult>, String> {
-    let conn = open_workspace_db(&workspace_path)?;
-
-    let q = request.query.trim();
-    if q.is_empty() {
-        return Ok(vec![]);
-    }
-    let limit = request.limit.unwrap_or(50).clamp(1, 200);
-    let like = format!("%{}%", q);
-
-    // Recursive CTE to:
-    // - build page path as "A/B/C" by walking pages.parent_id
-    // - build block path as "X/Y" by walking blocks.parent_id within a page
-    //
-    // We compute paths only for matching blocks, but use CTEs to get ancestor chains.
-    let sql = r#"
+/// Recursive CTEs giving every page's title path (`page_chain.path`, "A/B/C") and every
+/// block's content path within its page (`block_chain.path`, "X/Y"). `norm_blocks` holds
+/// block content trimmed with newlines collapsed, which is what path segments use.
+/// Append a SELECT joining them to build `full_path` (page path + block path).
+pub(crate) const BLOCK_PATH_CTES: &str = r#"
 WITH RECURSIVE
 page_chain(id, title, parent_id, path) AS (
     SELECT p.id, p.title, p.parent_id, p.title as path
@@ -917,7 +1166,39 @@ block_chain(id, page_id, parent_id, content, depth, path) AS (
     JOIN block_chain bc ON bc.id = nb.parent_id
     WHERE nb.page_id = bc.page_id
 )
-SELECT
+"#;
+
+/// Search blocks across the whole workspace DB by content substring.
+/// Returns breadcrumb-like paths (page path + block path) for completion/navigation.
+///
+/// NOTE:
+/// - This uses `LIKE` matching for now. You can later replace with FTS.
+/// - Depth/path are computed via recursive CTEs.
+/// - Path segments are derived from block content (trimmed, newlines collapsed).
+#[tauri::command]
+pub async fn search_blocks(
+    workspace_path: String,
+    request: SearchBlocksRequest,
+) -> Result<Vec<BlockSearchResult>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+
+    let q = request.query.trim();
+    if q.is_empty() {
+        return Ok(vec![]);
+    }
+    let limit = request.limit.unwrap_or(50).clamp(1, 200);
+    let snippet_context = request.snippet_context.unwrap_or(DEFAULT_SNIPPET_CONTEXT);
+    let like = format!("%{}%", q);
+
+    // Recursive CTE to:
+    // - build page path as "A/B/C" by walking pages.parent_id
+    // - build block path as "X/Y" by walking blocks.parent_id within a page
+    //
+    // We compute paths only for matching blocks, but use CTEs to get ancestor chains.
+    let sql = format!(
+        "{}{}",
+        BLOCK_PATH_CTES,
+        r#"SELECT
     nb.id,
     nb.page_id,
     nb.parent_id,
@@ -935,10 +1216,11 @@ LEFT JOIN page_chain pc ON pc.id = nb.page_id
 WHERE nb.content LIKE ?1
 ORDER BY LENGTH(nb.content) ASC
 LIMIT ?2
-"#;
+"#
+    );
 
-    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
-    let rows = stmt
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let mut rows = stmt
         .query_map(params![like, limit], |row| {
             Ok(BlockSearchResult {
                 id: row.get(0)?,
@@ -949,27 +1231,132 @@ LIMIT ?2
                 page_path: row.get(5)?,
                 block_path: row.get(6)?,
                 full_path: row.get(7)?,
+                snippet: String::new(),
             })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
+    for row in &mut rows {
+        row.snippet = block_match_snippet(&conn, &row.id, &row.content, q, snippet_context);
+    }
+
     Ok(rows)
 }
 
-/// Resolve block path segments within a page by exact content match at each level.
+const DEFAULT_SNIPPET_CONTEXT: u32 = 8;
+/// FTS5 `snippet()` accepts at most 64 tokens
+const MAX_SNIPPET_TOKENS: u32 = 64;
+// Placeholder highlight delimiters handed to `snippet()`. The snippet is HTML-escaped
+// before they become `<mark>` tags, so a literal "<mark>" in content stays text.
+const SNIPPET_OPEN: char = '\u{2}';
+const SNIPPET_CLOSE: char = '\u{3}';
+
+/// Highlighted snippet of `content` around `query`, built with FTS5 `snippet()`.
+/// Content that fits in the window, or that the FTS index cannot match (e.g. queries
+/// shorter than a trigram), is returned whole with every match highlighted.
+fn block_match_snippet(
+    conn: &Connection,
+    block_id: &str,
+    content: &str,
+    query: &str,
+    context: u32,
+) -> String {
+    let tokens = (context.saturating_mul(2) + 1).min(MAX_SNIPPET_TOKENS);
+    if content.chars().count() <= tokens as usize {
+        return highlight_all_matches(content, query);
+    }
+
+    let snippet: Option<String> = conn
+        .query_row(
+            "SELECT snippet(blocks_fts, 2, ?1, ?2, '…', ?3)
+             FROM blocks_fts
+             WHERE blocks_fts MATCH ?4 AND block_id = ?5",
+            params![
+                SNIPPET_OPEN.to_string(),
+                SNIPPET_CLOSE.to_string(),
+                tokens,
+                quote_fts_literal(query),
+                block_id
+            ],
+            |row| row.get(0),
+        )
+        .ok();
+
+    match snippet {
+        Some(snippet) if snippet.contains(SNIPPET_OPEN) => {
+            let mut out = String::with_capacity(snippet.len());
+            for c in snippet.chars() {
+                match c {
+                    SNIPPET_OPEN => out.push_str("<mark>"),
+                    SNIPPET_CLOSE => out.push_str("</mark>"),
+                    _ => push_html_escaped(&mut out, c),
+                }
+            }
+            out
+        }
+        _ => highlight_all_matches(content, query),
+    }
+}
+
+/// HTML-escape `content`, wrapping every ASCII-case-insensitive occurrence of `query`
+/// (the same matching `LIKE` uses) in `<mark>`...`</mark>`
+fn highlight_all_matches(content: &str, query: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut i = 0;
+    while i < content.len() {
+        let end = i + query.len();
+        if !query.is_empty()
+            && content.is_char_boundary(end)
+            && content[i..end].eq_ignore_ascii_case(query)
+        {
+            out.push_str("<mark>");
+            for c in content[i..end].chars() {
+                push_html_escaped(&mut out, c);
+            }
+            out.push_str("</mark>");
+            i = end;
+            continue;
+        }
+        let c = content[i..].chars().next().unwrap_or_default();
+        push_html_escaped(&mut out, c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+fn push_html_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        _ => out.push(c),
+    }
+}
+
+/// Resolve block path segments within a page by exact content match at each level,
+/// falling back to normalized matching (see `ResolveBlockPathRequest`).
 /// Assumes uniqueness per parent is enforced at the editor level (per your design).
 #[tauri::command]
 pub async fn resolve_block_path(
     workspace_path: String,
     request: ResolveBlockPathRequest,
-) -> Result<Option<String>, String> {
+) -> Result<Option<ResolvedBlockPath>, String> {
     let conn = open_workspace_db(&workspace_path)?;
+    resolve_block_path_segments(&conn, &request)
+}
 
+fn resolve_block_path_segments(
+    conn: &Connection,
+    request: &ResolveBlockPathRequest,
+) -> Result<Option<ResolvedBlockPath>, String> {
     let mut current_parent: Option<String> = None;
+    let mut path_match = PathMatchType::Exact;
 
-    for seg in request.segments {
+    for seg in &request.segments {
         let seg = seg.trim();
         if seg.is_empty() {
             return Ok(None);
@@ -993,54 +1380,255 @@ pub async fn resolve_block_path(
             .optional()
             .map_err(|e| e.to_string())?;
 
-        let Some(id) = found else {
-            return Ok(None);
+        let (id, match_type) = match found {
+            Some(id) => (id, PathMatchType::Exact),
+            None => {
+                let fallback = match_block_segment(
+                    conn,
+                    &request.page_id,
+                    current_parent.as_deref(),
+                    &normalized,
+                    request.case_insensitive,
+                )?;
+                let Some(found) = fallback else {
+                    return Ok(None);
+                };
+                found
+            }
         };
 
+        path_match = path_match.max(match_type);
         current_parent = Some(id);
     }
 
-    Ok(current_parent)
+    Ok(current_parent.map(|block_id| ResolvedBlockPath {
+        block_id,
+        match_type: path_match,
+    }))
 }
 
-/// Create a new block
-#[tauri::command]
-pub async fn create_block(
-    app: tauri::AppHandle,
-    workspace_path: String,
-    request: CreateBlockRequest,
-) -> Result<Block, String> {
-    let conn = open_workspace_db(&workspace_path)?;
-    let conn_mutex = Mutex::new(conn);
-
-    // Calculate order_weight
-    let order_weight = {
-        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-        calculate_new_order_weight(
-            &conn,
-            &request.page_id,
-            request.parent_id.as_deref(),
-            request.after_block_id.as_deref(),
-        )?.0  // Extract just the order_weight, ignore rebalance flag
-    };
-
-    let id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
-    let block_type = request.block_type.unwrap_or_default();
-    let content = request.content.unwrap_or_default();
+/// Compare a path segment against the children of `parent_id` by NFC form, then
+/// (with `case_insensitive`) ignoring case. Children are tried in outline order.
+fn match_block_segment(
+    conn: &Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
+    segment: &str,
+    case_insensitive: bool,
+) -> Result<Option<(String, PathMatchType)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, REPLACE(REPLACE(content, CHAR(10), ' '), CHAR(13), ' ')
+             FROM blocks
+             WHERE page_id = ?1 AND parent_id IS ?2
+             ORDER BY order_key",
+        )
+        .map_err(|e| e.to_string())?;
+    let children: Vec<(String, String)> = stmt
+        .query_map(params![page_id, parent_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut modes = vec![(false, PathMatchType::Normalized)];
+    if case_insensitive {
+        modes.push((true, PathMatchType::CaseInsensitive));
+    }
+    for (fold_case, match_type) in modes {
+        let target = page_path_service::match_key(segment, fold_case);
+        if let Some((id, _)) = children
+            .iter()
+            .find(|(_, content)| page_path_service::match_key(content, fold_case) == target)
+        {
+            return Ok(Some((id.clone(), match_type)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Find the block declaring `alias:: <alias>` anywhere in the workspace.
+/// Aliases are not unique; the earliest created block wins (see `get_alias_conflicts`).
+#[tauri::command]
+pub async fn resolve_block_alias(
+    workspace_path: String,
+    alias: String,
+) -> Result<Option<String>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    wiki_link_index::resolve_block_alias(&conn, &alias).map_err(|e| e.to_string())
+}
+
+/// List aliases declared by several blocks, so they can be renamed apart
+#[tauri::command]
+pub async fn get_alias_conflicts(workspace_path: String) -> Result<Vec<AliasConflict>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_alias_conflicts(&conn)
+}
+
+fn find_alias_conflicts(conn: &Connection) -> Result<Vec<AliasConflict>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT TRIM(m.value), b.id
+             FROM block_metadata m
+             JOIN blocks b ON b.id = m.block_id
+             WHERE m.key = ?1
+               AND TRIM(m.value) IN (
+                   SELECT TRIM(value) FROM block_metadata
+                   WHERE key = ?1
+                   GROUP BY TRIM(value)
+                   HAVING COUNT(*) > 1
+               )
+             ORDER BY TRIM(m.value), b.created_at, b.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([wiki_link_index::ALIAS_METADATA_KEY], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut conflicts: Vec<AliasConflict> = Vec::new();
+    for (alias, block_id) in rows {
+        match conflicts.last_mut() {
+            Some(conflict) if conflict.alias == alias => conflict.block_ids.push(block_id),
+            _ => conflicts.push(AliasConflict {
+                alias,
+                block_ids: vec![block_id],
+            }),
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Find `((uuid))` references to blocks that no longer exist, the block-ref
+/// counterpart of `get_broken_links`. A block referencing the same missing ID
+/// several times is reported once.
+#[tauri::command]
+pub async fn find_broken_block_embeds(
+    workspace_path: String,
+) -> Result<Vec<BrokenBlockEmbed>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_broken_block_embeds(&conn)
+}
+
+fn load_broken_block_embeds(conn: &Connection) -> Result<Vec<BrokenBlockEmbed>, String> {
+    // Only UUID-shaped refs: other `((...))` text is not a block reference
+    let ref_re = regex::Regex::new(
+        r"\(\(([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})\)\)",
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.page_id, p.title, pp.path_text, b.content
+             FROM blocks b
+             JOIN pages p ON p.id = b.page_id
+             LEFT JOIN page_paths pp ON pp.page_id = b.page_id
+             WHERE instr(b.content, '((') > 0 AND COALESCE(p.is_deleted, 0) = 0
+             ORDER BY p.title, b.page_id, b.order_key",
+        )
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut exists_stmt = conn
+        .prepare("SELECT 1 FROM blocks WHERE id = ?")
+        .map_err(|e| e.to_string())?;
+    let mut known: HashMap<String, bool> = HashMap::new();
+    let mut broken = Vec::new();
+
+    for (block_id, page_id, page_title, page_path, content) in candidates {
+        let mut reported = HashSet::new();
+        for caps in ref_re.captures_iter(&content) {
+            let target = caps[1].to_string();
+            let exists = match known.get(&target) {
+                Some(exists) => *exists,
+                None => {
+                    let exists = exists_stmt.exists([&target]).map_err(|e| e.to_string())?;
+                    known.insert(target.clone(), exists);
+                    exists
+                }
+            };
+            if exists || !reported.insert(target.clone()) {
+                continue;
+            }
+
+            broken.push(BrokenBlockEmbed {
+                from_block_id: block_id.clone(),
+                from_page_id: page_id.clone(),
+                page_title: page_title.clone(),
+                page_path: page_path.clone(),
+                missing_block_id: target,
+            });
+        }
+    }
+
+    Ok(broken)
+}
+
+/// Create a new block
+#[tauri::command]
+pub async fn create_block(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    request: CreateBlockRequest,
+) -> Result<SyncedBlock, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    // Calculate order_weight and order_key
+    let (order_weight, order_key) = {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let (order_weight, order_key, _) = calculate_new_order_weight(
+            &conn,
+            &request.page_id,
+            request.parent_id.as_deref(),
+            request.after_block_id.as_deref(),
+        )?;
+        (order_weight, order_key)
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let block_type = request.block_type.unwrap_or_default();
+    let content = apply_sanitization_rules(
+        &request.content.unwrap_or_default(),
+        &load_sanitization_rules(&workspace_path),
+        &block_type,
+    );
+    let language = resolve_block_language(&block_type, None, &content);
 
     {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.execute(
-            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, block_type, created_at, updated_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key, block_type, language, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 &id,
                 &request.page_id,
                 &request.parent_id,
                 &content,
                 order_weight,
+                &order_key,
                 block_type_to_string(&block_type),
+                &language,
                 &now,
                 &now
             ],
@@ -1049,6 +1637,16 @@ pub async fn create_block(
 
         // Index block in FTS5
         index_block_fts(&conn, &id, &request.page_id, &content)?;
+
+        block_history::record_operation(
+            &conn,
+            "create_block",
+            &request.page_id,
+            &InverseOperation {
+                restore: Vec::new(),
+                remove: vec![id.clone()],
+            },
+        )?;
     }
 
     let created_block = {
@@ -1057,7 +1655,7 @@ pub async fn create_block(
     };
 
     // Sync to markdown file (allow targeted patching for this create)
-    sync_page_to_markdown_after_create(
+    let strategy = sync_page_to_markdown_after_create(
         &conn_mutex,
         &workspace_path,
         &created_block.page_id,
@@ -1078,9 +1676,12 @@ pub async fn create_block(
     }
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &created_block.page_id);
 
-    Ok(created_block)
+    Ok(SyncedBlock {
+        block: created_block,
+        sync_strategy: page_sync::debug_sync_strategy(strategy),
+    })
 }
 
 /// Update a block
@@ -1089,7 +1690,7 @@ pub async fn update_block(
     app: tauri::AppHandle,
     workspace_path: String,
     request: UpdateBlockRequest,
-) -> Result<Block, String> {
+) -> Result<SyncedBlock, String> {
     let conn = open_workspace_db(&workspace_path)?;
     let conn_mutex = Mutex::new(conn);
     let now = Utc::now().to_rfc3339();
@@ -1099,14 +1700,35 @@ pub async fn update_block(
         get_block_by_id(&conn, &request.id)?
     };
 
-    let new_content = request.content.unwrap_or(block.content);
     let new_collapsed = request.is_collapsed.unwrap_or(block.is_collapsed);
+    let was_code = matches!(block.block_type, BlockType::Code);
     let new_block_type = request.block_type.unwrap_or(block.block_type);
-    let new_language = request.language.or(block.language);
+    let new_content = match request.content {
+        Some(content) => apply_sanitization_rules(
+            &content,
+            &load_sanitization_rules(&workspace_path),
+            &new_block_type,
+        ),
+        None => block.content,
+    };
+    let new_language = updated_block_language(
+        was_code,
+        &new_block_type,
+        request.language.or(block.language),
+        &new_content,
+    );
+    let schema = match request.metadata {
+        Some(_) => MetadataSchema::load(std::path::Path::new(&workspace_path))?,
+        None => MetadataSchema::default(),
+    };
 
     {
-        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-        conn.execute(
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        // One transaction, so a metadata schema error leaves the block untouched
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let restore = block_history::snapshot_blocks(&tx, &[&request.id])?;
+
+        tx.execute(
             "UPDATE blocks SET content = ?, is_collapsed = ?, block_type = ?, language = ?, updated_at = ? WHERE id = ?",
             params![
                 &new_content,
@@ -1120,15 +1742,31 @@ pub async fn update_block(
         .map_err(|e| e.to_string())?;
 
         // Update FTS5 index with new content
-        index_block_fts(&conn, &request.id, &block.page_id, &new_content)?;
+        index_block_fts(&tx, &request.id, &block.page_id, &new_content)?;
 
         // Extract and update TODO status from content prefix
-        update_todo_status_metadata(&conn, &request.id, &new_content)?;
+        update_todo_status_metadata(&tx, &request.id, &new_content)?;
 
         // Update metadata if provided
         if let Some(metadata) = &request.metadata {
-            save_block_metadata(&conn, &request.id, metadata)?;
+            save_block_metadata(&tx, &request.id, metadata, &schema)?;
+        }
+
+        if let Some(checked) = request.checked {
+            update_checked_metadata(&tx, &request.id, checked)?;
         }
+
+        block_history::record_operation(
+            &tx,
+            "update_block",
+            &block.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
+
+        tx.commit().map_err(|e| e.to_string())?;
     }
 
     let updated_block = {
@@ -1137,7 +1775,7 @@ pub async fn update_block(
     };
 
     // Sync to markdown file (allow targeted patching for this update)
-    sync_page_to_markdown_after_update(
+    let strategy = sync_page_to_markdown_after_update(
         &conn_mutex,
         &workspace_path,
         &updated_block.page_id,
@@ -1158,9 +1796,128 @@ pub async fn update_block(
     }
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &updated_block.page_id);
 
-    Ok(updated_block)
+    Ok(SyncedBlock {
+        block: updated_block,
+        sync_strategy: page_sync::debug_sync_strategy(strategy),
+    })
+}
+
+/// Find/replace within a single block's content.
+///
+/// With `regex`, `find` is a regular expression and `replacement` may refer to
+/// capture groups (`$1`, `${name}`). `all` replaces every match, otherwise only the
+/// first. Text outside the matches is kept byte-for-byte.
+#[tauri::command]
+pub async fn replace_in_block(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    block_id: String,
+    find: String,
+    replacement: String,
+    regex: bool,
+    all: bool,
+) -> Result<ReplaceInBlockResult, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    let (block, replacements) = {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let rules = load_sanitization_rules(&workspace_path);
+        replace_in_block_content(&conn, &block_id, &find, &replacement, regex, all, &rules)?
+    };
+
+    if replacements == 0 {
+        return Ok(ReplaceInBlockResult {
+            block,
+            replacements,
+            sync_strategy: None,
+        });
+    }
+
+    let strategy =
+        sync_page_to_markdown_after_update(&conn_mutex, &workspace_path, &block.page_id, &block.id)
+            .await?;
+
+    {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        wiki_link_index::index_block_links(&conn, &block.id, &block.content, &block.page_id)
+            .map_err(|e| e.to_string())?;
+    }
+
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &block.page_id);
+
+    Ok(ReplaceInBlockResult {
+        block,
+        replacements,
+        sync_strategy: page_sync::debug_sync_strategy(strategy),
+    })
+}
+
+/// DB side of `replace_in_block`: rewrite the content and refresh FTS and TODO status.
+/// Returns the block and how many matches were replaced.
+fn replace_in_block_content(
+    conn: &Connection,
+    block_id: &str,
+    find: &str,
+    replacement: &str,
+    regex: bool,
+    all: bool,
+    rules: &SanitizationRules,
+) -> Result<(Block, usize), String> {
+    if find.is_empty() {
+        return Err("Search text must not be empty".to_string());
+    }
+
+    let block = get_block_by_id(conn, block_id)?;
+    let limit = if all { 0 } else { 1 };
+
+    let (new_content, replacements) = if regex {
+        let re = regex::Regex::new(find).map_err(|e| format!("Invalid regex: {}", e))?;
+        let mut count = re.find_iter(&block.content).count();
+        if !all {
+            count = count.min(1);
+        }
+        let replaced = re.replacen(&block.content, limit, replacement).into_owned();
+        (replaced, count)
+    } else {
+        let mut count = block.content.matches(find).count();
+        if !all {
+            count = count.min(1);
+        }
+        let replaced = if all {
+            block.content.replace(find, replacement)
+        } else {
+            block.content.replacen(find, replacement, 1)
+        };
+        (replaced, count)
+    };
+
+    if replacements == 0 {
+        return Ok((block, 0));
+    }
+    let new_content = apply_sanitization_rules(&new_content, rules, &block.block_type);
+
+    let restore = block_history::snapshot_blocks(conn, &[block_id])?;
+    conn.execute(
+        "UPDATE blocks SET content = ?, updated_at = ? WHERE id = ?",
+        params![&new_content, Utc::now().to_rfc3339(), block_id],
+    )
+    .map_err(|e| e.to_string())?;
+    index_block_fts(conn, block_id, &block.page_id, &new_content)?;
+    update_todo_status_metadata(conn, block_id, &new_content)?;
+    block_history::record_operation(
+        conn,
+        "replace_in_block",
+        &block.page_id,
+        &InverseOperation {
+            restore,
+            remove: Vec::new(),
+        },
+    )?;
+
+    Ok((get_block_by_id(conn, block_id)?, replacements))
 }
 
 /// Delete a block (and all descendants)
@@ -1201,7 +1958,7 @@ pub async fn delete_block(
     let children: Vec<String> = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         let mut stmt = conn
-            .prepare("SELECT id FROM blocks WHERE parent_id = ? ORDER BY order_weight")
+            .prepare("SELECT id FROM blocks WHERE parent_id = ? ORDER BY order_key")
             .map_err(|e| e.to_string())?;
 
         let results: Vec<String> = stmt
@@ -1217,6 +1974,22 @@ pub async fn delete_block(
     {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
 
+        // Journal the block and the children it is about to promote, parent first
+        let mut snapshot_ids = vec![block_id.as_str()];
+        if !is_last_block {
+            snapshot_ids.extend(children.iter().map(String::as_str));
+        }
+        let restore = block_history::snapshot_blocks(&conn, &snapshot_ids)?;
+        block_history::record_operation(
+            &conn,
+            "delete_block",
+            &page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
+
         // If this is the only block in the page, clear content instead of deleting
         if is_last_block {
             conn.execute(
@@ -1261,7 +2034,7 @@ pub async fn delete_block(
     }
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
 
     // Return only the deleted block ID (not descendants since they're preserved)
     Ok(vec![block_id])
@@ -1278,7 +2051,7 @@ pub async fn move_block(
     app: tauri::AppHandle,
     workspace_path: String,
     request: MoveBlockRequest,
-) -> Result<Block, String> {
+) -> Result<SyncedBlock, String> {
     let conn = open_workspace_db(&workspace_path)?;
     let conn_mutex = Mutex::new(conn);
 
@@ -1287,26 +2060,39 @@ pub async fn move_block(
         get_block_by_id(&conn, &request.id)?
     };
 
-    // Calculate new order_weight
-    let new_order = {
+    // Calculate new order_weight and order_key
+    let (new_order, new_key) = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-        calculate_new_order_weight(
+        let (new_order, new_key, _) = calculate_new_order_weight(
             &conn,
             &block.page_id,
             request.new_parent_id.as_deref(),
             request.after_block_id.as_deref(),
-        )?.0  // Extract just the order_weight, ignore rebalance flag
+        )?;
+        (new_order, new_key)
     };
 
     let now = Utc::now().to_rfc3339();
 
     {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let restore = block_history::snapshot_blocks(&conn, &[&request.id])?;
+
         conn.execute(
-            "UPDATE blocks SET parent_id = ?, order_weight = ?, updated_at = ? WHERE id = ?",
-            params![&request.new_parent_id, new_order, &now, &request.id],
+            "UPDATE blocks SET parent_id = ?, order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+            params![&request.new_parent_id, new_order, &new_key, &now, &request.id],
         )
         .map_err(|e| e.to_string())?;
+
+        block_history::record_operation(
+            &conn,
+            "move_block",
+            &block.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
     }
 
     let moved_block = {
@@ -1315,7 +2101,7 @@ pub async fn move_block(
     };
 
     // Sync to markdown file
-    sync_page_to_markdown_after_move(
+    let strategy = sync_page_to_markdown_after_move(
         &conn_mutex,
         &workspace_path,
         &moved_block.page_id,
@@ -1324,9 +2110,12 @@ pub async fn move_block(
     .await?;
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &moved_block.page_id);
 
-    Ok(moved_block)
+    Ok(SyncedBlock {
+        block: moved_block,
+        sync_strategy: page_sync::debug_sync_strategy(strategy),
+    })
 }
 
 /// Indent a block (make it a child of previous sibling)
@@ -1355,7 +2144,7 @@ pub async fn indent_block(
     let last_child_id: Option<String> = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT id FROM blocks WHERE page_id = ? AND parent_id = ? ORDER BY order_weight DESC LIMIT 1",
+            "SELECT id FROM blocks WHERE page_id = ? AND parent_id = ? ORDER BY order_key DESC LIMIT 1",
             params![&block.page_id, &prev_sibling.id],
             |row| row.get(0),
         )
@@ -1363,8 +2152,8 @@ pub async fn indent_block(
     };
 
     // Calculate new order_weight as child of previous sibling, after its last child
-    // Returns (new_order, did_rebalance)
-    let (new_order, did_rebalance) = {
+    // Returns (new_order, new_key, did_rebalance)
+    let (new_order, new_key, did_rebalance) = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         calculate_new_order_weight(
             &conn,
@@ -1378,11 +2167,23 @@ pub async fn indent_block(
 
     {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let restore = block_history::snapshot_blocks(&conn, &[&block_id])?;
+
         conn.execute(
-            "UPDATE blocks SET parent_id = ?, order_weight = ?, updated_at = ? WHERE id = ?",
-            params![&prev_sibling.id, new_order, &now, &block_id],
+            "UPDATE blocks SET parent_id = ?, order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+            params![&prev_sibling.id, new_order, &new_key, &now, &block_id],
         )
         .map_err(|e| e.to_string())?;
+
+        block_history::record_operation(
+            &conn,
+            "indent_block",
+            &block.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
     }
 
     let updated_block = {
@@ -1402,7 +2203,7 @@ pub async fn indent_block(
     };
 
     // Sync to markdown file
-    sync_page_to_markdown_after_move(
+    let strategy = sync_page_to_markdown_after_move(
         &conn_mutex,
         &workspace_path,
         &updated_block.page_id,
@@ -1411,11 +2212,12 @@ pub async fn indent_block(
     .await?;
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &updated_block.page_id);
 
     Ok(MoveResult {
         moved_block: updated_block,
         affected_siblings,
+        sync_strategy: page_sync::debug_sync_strategy(strategy),
     })
 }
 
@@ -1445,8 +2247,8 @@ pub async fn outdent_block(
     };
 
     // Calculate new order_weight as sibling of parent
-    // Returns (new_order, did_rebalance)
-    let (new_order, did_rebalance) = {
+    // Returns (new_order, new_key, did_rebalance)
+    let (new_order, new_key, did_rebalance) = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         calculate_new_order_weight(
             &conn,
@@ -1460,11 +2262,23 @@ pub async fn outdent_block(
 
     {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let restore = block_history::snapshot_blocks(&conn, &[&block_id])?;
+
         conn.execute(
-            "UPDATE blocks SET parent_id = ?, order_weight = ?, updated_at = ? WHERE id = ?",
-            params![&parent.parent_id, new_order, &now, &block_id],
+            "UPDATE blocks SET parent_id = ?, order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+            params![&parent.parent_id, new_order, &new_key, &now, &block_id],
         )
         .map_err(|e| e.to_string())?;
+
+        block_history::record_operation(
+            &conn,
+            "outdent_block",
+            &block.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
     }
 
     let updated_block = {
@@ -1484,7 +2298,7 @@ pub async fn outdent_block(
     };
 
     // Sync to markdown file
-    sync_page_to_markdown_after_move(
+    let strategy = sync_page_to_markdown_after_move(
         &conn_mutex,
         &workspace_path,
         &updated_block.page_id,
@@ -1493,11 +2307,12 @@ pub async fn outdent_block(
     .await?;
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &updated_block.page_id);
 
     Ok(MoveResult {
         moved_block: updated_block,
         affected_siblings,
+        sync_strategy: page_sync::debug_sync_strategy(strategy),
     })
 }
 
@@ -1520,11 +2335,23 @@ pub async fn toggle_collapse(
 
     {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let restore = block_history::snapshot_blocks(&conn, &[&block_id])?;
+
         conn.execute(
             "UPDATE blocks SET is_collapsed = ?, updated_at = ? WHERE id = ?",
             params![(!block.is_collapsed) as i32, &now, &block_id],
         )
         .map_err(|e| e.to_string())?;
+
+        block_history::record_operation(
+            &conn,
+            "toggle_collapse",
+            &block.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
     }
 
     let updated_block = {
@@ -1537,7 +2364,7 @@ pub async fn toggle_collapse(
     sync_page_to_markdown(&conn_mutex, &workspace_path, &updated_block.page_id).await?;
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &updated_block.page_id);
 
     Ok(updated_block)
 }
@@ -1581,22 +2408,23 @@ pub async fn merge_blocks(
         // 3. Move all children of current block to target block
         // They should be appended to the end of target block's children
 
-        // Get existing children of target block to find last order_weight
-        let last_child_weight: Option<f64> = tx
+        // Get existing children of target block to find the last position
+        let last_child: Option<SiblingPosition> = tx
             .query_row(
-                "SELECT MAX(order_weight) FROM blocks WHERE parent_id = ?",
+                "SELECT order_weight, order_key FROM blocks WHERE parent_id = ?
+                 ORDER BY order_key DESC LIMIT 1",
                 params![&target_block.id],
-                |row| row.get(0),
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
-            .ok()
-            .flatten();
+            .optional()
+            .map_err(|e| e.to_string())?;
 
         // Get children of current block - use explicit loop to avoid statement lifetime issues
         let mut children_rows: Vec<(String, f64)> = Vec::new();
         {
             let mut stmt = tx
                 .prepare(
-                    "SELECT id, order_weight FROM blocks WHERE parent_id = ? ORDER BY order_weight",
+                    "SELECT id, order_weight FROM blocks WHERE parent_id = ? ORDER BY order_key",
                 )
                 .map_err(|e| e.to_string())?;
 
@@ -1609,18 +2437,33 @@ pub async fn merge_blocks(
             }
         }
 
+        // Journal the target, the merged block and its children before touching them
+        let mut snapshot_ids = vec![target_block.id.as_str(), block_id.as_str()];
+        snapshot_ids.extend(children_rows.iter().map(|(id, _)| id.as_str()));
+        let restore = block_history::snapshot_blocks(&tx, &snapshot_ids)?;
+        block_history::record_operation(
+            &tx,
+            "merge_blocks",
+            &block.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
+
         let now = Utc::now().to_rfc3339();
 
         // Reparent each child
-        let mut last_weight = last_child_weight;
-        for (child_id, _) in children_rows {
+        let (mut last_weight, last_key) = last_child.unzip();
+        let new_keys = sibling_keys_between(last_key.as_deref(), None, children_rows.len())?;
+        for ((child_id, _), new_key) in children_rows.into_iter().zip(new_keys) {
             // Calculate new weight (append to end)
             let new_weight = fractional_index::calculate_middle(last_weight, None);
             last_weight = Some(new_weight);
 
             tx.execute(
-                "UPDATE blocks SET parent_id = ?, order_weight = ?, updated_at = ? WHERE id = ?",
-                params![&target_block.id, new_weight, &now, &child_id],
+                "UPDATE blocks SET parent_id = ?, order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+                params![&target_block.id, new_weight, &new_key, &now, &child_id],
             )
             .map_err(|e| e.to_string())?;
 
@@ -1645,10 +2488,17 @@ pub async fn merge_blocks(
             .map_err(|e| format!("Failed to commit merge transaction: {}", e))?;
     }
 
-    // 6. Sync to markdown (Full rewrite to be safe for complex structural changes)
+    // 6. Sync to markdown (incremental merge patch, full rewrite if anything is ambiguous)
     // Transaction is released, safe to await async operations now
     let conn_mutex = Mutex::new(open_workspace_db(&workspace_path)?);
-    sync_page_to_markdown(&conn_mutex, &workspace_path, &block.page_id).await?;
+    sync_page_to_markdown_after_merge(
+        &conn_mutex,
+        &workspace_path,
+        &block.page_id,
+        &target_block.id,
+        &block_id,
+    )
+    .await?;
 
     // Return all changed blocks (merged block + moved children)
     let mut changed_blocks = Vec::new();
@@ -1679,91 +2529,668 @@ pub async fn merge_blocks(
     }
 
     // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &block.page_id);
 
     Ok(changed_blocks)
 }
 
-// ============ Helper Functions ============
-
-fn calculate_new_order_weight(
-    conn: &Connection,
-    page_id: &str,
-    parent_id: Option<&str>,
-    after_block_id: Option<&str>,
-) -> Result<(f64, bool), String> {
-    let (before, after) = get_neighbor_weights(conn, page_id, parent_id, after_block_id)?;
-
-    // Check rebalancing
-    let needs_rebalance = match (before, after) {
-        (Some(b), Some(a)) => fractional_index::needs_rebalancing(b, a),
-        (None, Some(a)) => a < 1e-5, // Heuristic: if first item is very close to 0
-        (Some(b), None) => b > 1e15, // Heuristic: if last item is huge (near f64 mantissa limit)
-        (None, None) => false,
-    };
+/// Revert the most recent create/update/delete/move/merge recorded in the
+/// block history, then rewrite the affected page file.
+#[tauri::command]
+pub async fn undo_last_operation(
+    app: tauri::AppHandle,
+    workspace_path: String,
+) -> Result<block_history::UndoResult, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let result = block_history::undo_last(&mut conn)?;
 
-    if needs_rebalance {
-        println!(
-            "[calculate_new_order_weight] Rebalancing siblings for page {} parent {:?}",
-            page_id, parent_id
-        );
-        rebalance_siblings(conn, page_id, parent_id)?;
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &result.page_id).await?;
 
-        // Re-fetch
-        let (before_new, after_new) =
-            get_neighbor_weights(conn, page_id, parent_id, after_block_id)?;
-        return Ok((fractional_index::calculate_middle(before_new, after_new), true));
-    }
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
 
-    Ok((fractional_index::calculate_middle(before, after), false))
+    Ok(result)
 }
 
-fn get_neighbor_weights(
-    conn: &Connection,
-    page_id: &str,
-    parent_id: Option<&str>,
-    after_block_id: Option<&str>,
-) -> Result<(Option<f64>, Option<f64>), String> {
-    match after_block_id {
-        Some(after_id) => {
-            let after_block = get_block_by_id(conn, after_id)?;
+/// Swap the positions of two sibling blocks (exchange their order weights).
+/// Both blocks must belong to the same page and share the same parent.
+#[tauri::command]
+pub async fn swap_blocks(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    block_id_a: String,
+    block_id_b: String,
+) -> Result<Vec<Block>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let (block_a, block_b) = swap_sibling_order(&mut conn, &block_id_a, &block_id_b)?;
 
-            // Find next sibling after the target block
-            let next_sibling: Option<f64> = conn
-                .query_row(
-                    "SELECT order_weight FROM blocks
-                     WHERE page_id = ? AND parent_id IS ? AND order_weight > ?
-                     ORDER BY order_weight LIMIT 1",
-                    params![page_id, parent_id, after_block.order_weight],
-                    |row| row.get(0),
-                )
-                .ok();
+    // Two whole subtrees trade places, so rewrite the page file
+    let page_id = block_a.page_id.clone();
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
 
-            Ok((Some(after_block.order_weight), next_sibling))
-        }
-        None => {
-            // If no after_block_id is provided, we insert at the BEGINNING of the siblings list.
-            let first_order: Option<f64> = conn
-                .query_row(
-                    "SELECT MIN(order_weight) FROM blocks WHERE page_id = ? AND parent_id IS ?",
-                    params![page_id, parent_id],
-                    |row| row.get(0),
-                )
-                .ok()
-                .flatten();
+    // Emit workspace changed event for git monitoring
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
 
-            Ok((None, first_order))
-        }
-    }
+    Ok(vec![block_a, block_b])
 }
 
-fn rebalance_siblings(
-    conn: &Connection,
-    page_id: &str,
-    parent_id: Option<&str>,
+/// Exchange the order weights of two siblings in a single transaction.
+/// Returns both blocks as they are after the swap.
+fn swap_sibling_order(
+    conn: &mut Connection,
+    block_id_a: &str,
+    block_id_b: &str,
+) -> Result<(Block, Block), String> {
+    if block_id_a == block_id_b {
+        return Err("Cannot swap a block with itself".to_string());
+    }
+
+    let block_a = get_block_by_id(conn, block_id_a)?;
+    let block_b = get_block_by_id(conn, block_id_b)?;
+
+    if block_a.page_id != block_b.page_id {
+        return Err("Cannot swap blocks from different pages".to_string());
+    }
+    if block_a.parent_id != block_b.parent_id {
+        return Err("Cannot swap blocks with different parents".to_string());
+    }
+
+    let now = Utc::now().to_rfc3339();
+    {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start swap transaction: {}", e))?;
+        let restore = block_history::snapshot_blocks(&tx, &[block_id_a, block_id_b])?;
+
+        tx.execute(
+            "UPDATE blocks SET order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+            params![block_b.order_weight, &block_b.order_key, &now, &block_a.id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE blocks SET order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+            params![block_a.order_weight, &block_a.order_key, &now, &block_b.id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        block_history::record_operation(
+            &tx,
+            "swap_blocks",
+            &block_a.page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit swap transaction: {}", e))?;
+    }
+
+    Ok((
+        get_block_by_id(conn, block_id_a)?,
+        get_block_by_id(conn, block_id_b)?,
+    ))
+}
+
+/// Put the children of `parent_id` (root blocks when None) on a page in the order of
+/// `ordered_block_ids`, which must list every one of them exactly once. All weights
+/// are reassigned evenly in one transaction and the page is synced once.
+#[tauri::command]
+pub async fn reorder_siblings(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+    parent_id: Option<String>,
+    ordered_block_ids: Vec<String>,
+) -> Result<Vec<Block>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let siblings = apply_sibling_order(
+        &mut conn,
+        &page_id,
+        parent_id.as_deref(),
+        &ordered_block_ids,
+    )?;
+
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
+
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(siblings)
+}
+
+/// Reassign the order weights of a sibling list. Returns the siblings in their new order.
+fn apply_sibling_order(
+    conn: &mut Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
+    ordered_block_ids: &[String],
+) -> Result<Vec<Block>, String> {
+    for block_id in ordered_block_ids {
+        let block = get_block_by_id(conn, block_id)?;
+        if block.page_id != page_id {
+            return Err(format!("Block {} is not on page {}", block_id, page_id));
+        }
+        if block.parent_id.as_deref() != parent_id {
+            return Err(format!("Block {} has a different parent", block_id));
+        }
+    }
+
+    let unique: HashSet<&str> = ordered_block_ids.iter().map(String::as_str).collect();
+    if unique.len() != ordered_block_ids.len() {
+        return Err("Each block may only be listed once".to_string());
+    }
+    let sibling_count = get_siblings_as_blocks(conn, page_id, parent_id)?.len();
+    if sibling_count != ordered_block_ids.len() {
+        return Err(format!(
+            "Expected all {} siblings, got {}",
+            sibling_count,
+            ordered_block_ids.len()
+        ));
+    }
+
+    let weights = fractional_index::rebalance_order_weights(ordered_block_ids.len());
+    let now = Utc::now().to_rfc3339();
+    {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start reorder transaction: {}", e))?;
+        let ids: Vec<&str> = ordered_block_ids.iter().map(String::as_str).collect();
+        let restore = block_history::snapshot_blocks(&tx, &ids)?;
+        // Keys are derived again from the fresh weights
+        for (block_id, weight) in ordered_block_ids.iter().zip(weights) {
+            tx.execute(
+                "UPDATE blocks SET order_weight = ?, order_key = NULL, updated_at = ? WHERE id = ?",
+                params![weight, &now, block_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        block_history::record_operation(
+            &tx,
+            "reorder_siblings",
+            page_id,
+            &InverseOperation {
+                restore,
+                remove: Vec::new(),
+            },
+        )?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit reorder transaction: {}", e))?;
+    }
+
+    get_siblings_as_blocks(conn, page_id, parent_id)
+}
+
+/// Merge a block into its parent: append its content to the parent's content,
+/// promote its children to the parent, and delete it. The promoted children
+/// take the merged block's former position among the parent's children.
+#[tauri::command]
+pub async fn merge_into_parent(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    block_id: String,
+) -> Result<Vec<Block>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let (page_id, parent_id, promoted_ids) = merge_block_into_parent(&mut conn, &block_id)?;
+
+    // Full rewrite: the merged block's subtree was restructured
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
+
+    let conn = conn_mutex.into_inner().map_err(|e| e.to_string())?;
+    let parent = get_block_by_id(&conn, &parent_id)?;
+
+    deindex_block_fts(&conn, &block_id)?;
+    index_block_fts(&conn, &parent.id, &parent.page_id, &parent.content)?;
+    wiki_link_index::index_block_links(&conn, &parent.id, &parent.content, &parent.page_id)
+        .map_err(|e| e.to_string())?;
+
+    let mut changed_blocks = vec![parent];
+    for child_id in promoted_ids {
+        changed_blocks.push(get_block_by_id(&conn, &child_id)?);
+    }
+
+    // Emit workspace changed event for git monitoring
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(changed_blocks)
+}
+
+/// Turn reporting of page-file sync strategies in mutation responses on or off (for
+/// debugging patch fallbacks)
+#[tauri::command]
+pub fn set_sync_strategy_debug(enabled: bool) -> Result<(), String> {
+    page_sync::set_sync_strategy_debug(enabled);
+    Ok(())
+}
+
+/// Predict where `create_block` would put a new block relative to `after_block_id`
+/// without inserting anything. With `as_sibling` the block follows it under the same
+/// parent; otherwise it becomes its first child.
+#[tauri::command]
+pub fn preview_insert_position(
+    workspace_path: String,
+    after_block_id: String,
+    as_sibling: bool,
+) -> Result<InsertPositionPreview, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    compute_insert_position(&conn, &after_block_id, as_sibling)
+}
+
+fn compute_insert_position(
+    conn: &Connection,
+    after_block_id: &str,
+    as_sibling: bool,
+) -> Result<InsertPositionPreview, String> {
+    let anchor = get_block_with_ancestors(conn, after_block_id)?
+        .ok_or_else(|| "Block not found".to_string())?;
+    let anchor_depth = anchor.ancestor_ids.len() - 1;
+    let block = anchor.block;
+
+    // Mirrors create_block: a sibling goes right after the anchor, a child goes first
+    let (parent_id, depth, (before, after)) = if as_sibling {
+        let neighbors = get_neighbor_positions(
+            conn,
+            &block.page_id,
+            block.parent_id.as_deref(),
+            Some(&block.id),
+        )?;
+        (block.parent_id.clone(), anchor_depth, neighbors)
+    } else {
+        let neighbors = get_neighbor_positions(conn, &block.page_id, Some(&block.id), None)?;
+        (Some(block.id.clone()), anchor_depth + 1, neighbors)
+    };
+
+    Ok(InsertPositionPreview {
+        page_id: block.page_id,
+        parent_id,
+        depth,
+        before_weight: before.as_ref().map(|(weight, _)| *weight),
+        after_weight: after.as_ref().map(|(weight, _)| *weight),
+        order_weight: position_weight_between(&before, &after),
+        order_key: position_key_between(&before, &after),
+    })
+}
+
+/// Ordered siblings under `parent_id` (root blocks when `None`) with their order keys,
+/// flagging invalid and out-of-order keys, for inspecting sibling ordering issues.
+#[tauri::command]
+pub fn debug_order_weights(
+    workspace_path: String,
+    page_id: String,
+    parent_id: Option<String>,
+) -> Result<Vec<SiblingOrderWeight>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_sibling_order_weights(&conn, &page_id, parent_id.as_deref())
+}
+
+/// Group sibling blocks under a new parent block with `parent_content`.
+/// The new block takes the position of the earliest selected block and the
+/// selection becomes its children, keeping their order. All blocks must share a parent.
+/// Returns the new parent followed by the regrouped children.
+#[tauri::command]
+pub async fn group_blocks_under_new_parent(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    block_ids: Vec<String>,
+    parent_content: String,
+) -> Result<Vec<Block>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let (page_id, group_id, child_ids) =
+        group_blocks_under_parent(&mut conn, &block_ids, &parent_content)?;
+
+    // Full rewrite: the selected subtrees were re-nested
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
+
+    let conn = conn_mutex.into_inner().map_err(|e| e.to_string())?;
+    let group = get_block_by_id(&conn, &group_id)?;
+
+    index_block_fts(&conn, &group.id, &group.page_id, &group.content)?;
+    wiki_link_index::index_block_links(&conn, &group.id, &group.content, &group.page_id)
+        .map_err(|e| e.to_string())?;
+
+    let mut changed_blocks = vec![group];
+    for child_id in child_ids {
+        changed_blocks.push(get_block_by_id(&conn, &child_id)?);
+    }
+
+    // Emit workspace changed event for git monitoring
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(changed_blocks)
+}
+
+/// Perform the DB side of `group_blocks_under_new_parent` in one transaction.
+/// Returns (page_id, new parent id, child ids in order).
+fn group_blocks_under_parent(
+    conn: &mut Connection,
+    block_ids: &[String],
+    parent_content: &str,
+) -> Result<(String, String, Vec<String>), String> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for id in block_ids {
+        if !blocks.iter().any(|b| &b.id == id) {
+            blocks.push(get_block_by_id(conn, id)?);
+        }
+    }
+    let Some(first) = blocks.first() else {
+        return Err("No blocks selected to group".to_string());
+    };
+    let (page_id, parent_id) = (first.page_id.clone(), first.parent_id.clone());
+    if blocks
+        .iter()
+        .any(|b| b.page_id != page_id || b.parent_id != parent_id)
+    {
+        return Err("Cannot group blocks: selected blocks must be siblings".to_string());
+    }
+    blocks.sort_by(|a, b| a.cmp_position(b));
+
+    let group_id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let child_ids: Vec<String> = blocks.iter().map(|b| b.id.clone()).collect();
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start group transaction: {}", e))?;
+    let ids: Vec<&str> = child_ids.iter().map(String::as_str).collect();
+    let restore = block_history::snapshot_blocks(&tx, &ids)?;
+
+    tx.execute(
+        "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key, block_type, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            &group_id,
+            &page_id,
+            &parent_id,
+            parent_content,
+            blocks[0].order_weight,
+            &blocks[0].order_key,
+            block_type_to_string(&BlockType::Bullet),
+            &now,
+            &now
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let weights = fractional_index::rebalance_order_weights(child_ids.len());
+    for (child_id, weight) in child_ids.iter().zip(weights) {
+        tx.execute(
+            "UPDATE blocks SET parent_id = ?, order_weight = ?, order_key = NULL, updated_at = ? WHERE id = ?",
+            params![&group_id, weight, &now, child_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    block_history::record_operation(
+        &tx,
+        "group_blocks",
+        &page_id,
+        &InverseOperation {
+            restore,
+            remove: vec![group_id.clone()],
+        },
+    )?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit group transaction: {}", e))?;
+
+    Ok((page_id, group_id, child_ids))
+}
+
+/// Perform the DB side of `merge_into_parent` in one transaction.
+/// Returns (page_id, parent_id, promoted child ids in order).
+fn merge_block_into_parent(
+    conn: &mut Connection,
+    block_id: &str,
+) -> Result<(String, String, Vec<String>), String> {
+    let block = get_block_by_id(conn, block_id)?;
+    let parent_id = block
+        .parent_id
+        .clone()
+        .ok_or_else(|| "Cannot merge into parent: block is at root level".to_string())?;
+    let parent = get_block_by_id(conn, &parent_id)?;
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start merge transaction: {}", e))?;
+
+    // Promoted children fill the gap between the merged block and its next sibling
+    let next_sibling: Option<SiblingPosition> = tx
+        .query_row(
+            "SELECT order_weight, order_key FROM blocks
+             WHERE page_id = ? AND parent_id = ? AND order_key > ?
+             ORDER BY order_key LIMIT 1",
+            params![&block.page_id, &parent_id, &block.order_key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (next_sibling_weight, next_sibling_key) = next_sibling.unzip();
+
+    let child_ids: Vec<String> = {
+        let mut stmt = tx
+            .prepare("SELECT id FROM blocks WHERE parent_id = ? ORDER BY order_key")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([block_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+
+    let weights = fractional_index::calculate_between(
+        Some(block.order_weight),
+        next_sibling_weight,
+        child_ids.len(),
+    );
+    let keys = sibling_keys_between(
+        Some(&block.order_key),
+        next_sibling_key.as_deref(),
+        child_ids.len(),
+    )?;
+
+    // Parents before children, so undo re-inserts the merged block before its children
+    let mut snapshot_ids: Vec<&str> = vec![&parent_id, block_id];
+    snapshot_ids.extend(child_ids.iter().map(String::as_str));
+    let restore = block_history::snapshot_blocks(&tx, &snapshot_ids)?;
+
+    let now = Utc::now().to_rfc3339();
+
+    for ((child_id, weight), key) in child_ids.iter().zip(weights).zip(keys) {
+        tx.execute(
+            "UPDATE blocks SET parent_id = ?, order_weight = ?, order_key = ?, updated_at = ? WHERE id = ?",
+            params![&parent_id, weight, &key, &now, child_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let new_content = format!("{}{}", parent.content, block.content);
+    tx.execute(
+        "UPDATE blocks SET content = ?, updated_at = ? WHERE id = ?",
+        params![&new_content, &now, &parent_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.execute("DELETE FROM blocks WHERE id = ?", [block_id])
+        .map_err(|e| e.to_string())?;
+
+    block_history::record_operation(
+        &tx,
+        "merge_into_parent",
+        &block.page_id,
+        &InverseOperation {
+            restore,
+            remove: Vec::new(),
+        },
+    )?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit merge transaction: {}", e))?;
+
+    Ok((block.page_id, parent_id, child_ids))
+}
+
+// ============ Helper Functions ============
+
+/// Weight and order key for a block placed after `after_block_id` (first when
+/// `None`) among the siblings under `parent_id`. The flag reports whether the
+/// siblings had to be renumbered first, which only happens when their keys are
+/// not usable (duplicated or written outside `fractional_index`).
+fn calculate_new_order_weight(
+    conn: &Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
+    after_block_id: Option<&str>,
+) -> Result<(f64, String, bool), String> {
+    let (before, after) = get_neighbor_positions(conn, page_id, parent_id, after_block_id)?;
+    if let Some(order_key) = position_key_between(&before, &after) {
+        return Ok((position_weight_between(&before, &after), order_key, false));
+    }
+
+    println!(
+        "[calculate_new_order_weight] Rebalancing siblings for page {} parent {:?}",
+        page_id, parent_id
+    );
+    rebalance_siblings(conn, page_id, parent_id)?;
+
+    // Re-fetch
+    let (before, after) = get_neighbor_positions(conn, page_id, parent_id, after_block_id)?;
+    let order_key = position_key_between(&before, &after)
+        .ok_or_else(|| "Failed to order block among its siblings".to_string())?;
+    Ok((position_weight_between(&before, &after), order_key, true))
+}
+
+/// A sibling's (order_weight, order_key)
+type SiblingPosition = (f64, String);
+
+fn position_key_between(
+    before: &Option<SiblingPosition>,
+    after: &Option<SiblingPosition>,
+) -> Option<String> {
+    fractional_index::key_between(
+        before.as_ref().map(|(_, key)| key.as_str()),
+        after.as_ref().map(|(_, key)| key.as_str()),
+    )
+}
+
+fn position_weight_between(
+    before: &Option<SiblingPosition>,
+    after: &Option<SiblingPosition>,
+) -> f64 {
+    fractional_index::calculate_middle(
+        before.as_ref().map(|(weight, _)| *weight),
+        after.as_ref().map(|(weight, _)| *weight),
+    )
+}
+
+/// `count` ascending order keys between two siblings' keys
+pub(crate) fn sibling_keys_between(
+    before: Option<&str>,
+    after: Option<&str>,
+    count: usize,
+) -> Result<Vec<String>, String> {
+    fractional_index::keys_between(before, after, count)
+        .ok_or_else(|| format!("Invalid sibling order keys {:?} and {:?}", before, after))
+}
+
+fn get_neighbor_positions(
+    conn: &Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
+    after_block_id: Option<&str>,
+) -> Result<(Option<SiblingPosition>, Option<SiblingPosition>), String> {
+    match after_block_id {
+        Some(after_id) => {
+            let after_block = get_block_by_id(conn, after_id)?;
+
+            // Find next sibling after the target block
+            let next_sibling: Option<SiblingPosition> = conn
+                .query_row(
+                    "SELECT order_weight, order_key FROM blocks
+                     WHERE page_id = ? AND parent_id IS ? AND order_key > ?
+                     ORDER BY order_key LIMIT 1",
+                    params![page_id, parent_id, &after_block.order_key],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+
+            Ok((
+                Some((after_block.order_weight, after_block.order_key)),
+                next_sibling,
+            ))
+        }
+        None => {
+            // If no after_block_id is provided, we insert at the BEGINNING of the siblings list.
+            let first: Option<SiblingPosition> = conn
+                .query_row(
+                    "SELECT order_weight, order_key FROM blocks
+                     WHERE page_id = ? AND parent_id IS ?
+                     ORDER BY order_key LIMIT 1",
+                    params![page_id, parent_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+
+            Ok((None, first))
+        }
+    }
+}
+
+fn load_sibling_order_weights(
+    conn: &Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
+) -> Result<Vec<SiblingOrderWeight>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, order_weight, order_key FROM blocks
+             WHERE page_id = ? AND parent_id IS ?
+             ORDER BY order_key, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![page_id, parent_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut siblings: Vec<SiblingOrderWeight> = Vec::with_capacity(rows.len());
+    for (block_id, order_weight, order_key) in rows {
+        let out_of_order = siblings
+            .last()
+            .is_some_and(|before| before.order_key >= order_key);
+        siblings.push(SiblingOrderWeight {
+            block_id,
+            order_weight,
+            invalid_key: !fractional_index::is_valid_key(&order_key),
+            order_key,
+            out_of_order,
+        });
+    }
+
+    Ok(siblings)
+}
+
+fn rebalance_siblings(
+    conn: &Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
 ) -> Result<(), String> {
     let mut stmt = conn
-        .prepare("SELECT id FROM blocks WHERE page_id = ? AND parent_id IS ? ORDER BY order_weight")
+        .prepare(
+            "SELECT id FROM blocks WHERE page_id = ? AND parent_id IS ? ORDER BY order_key, id",
+        )
         .map_err(|e| e.to_string())?;
 
     let sibling_ids: Vec<String> = stmt
@@ -1775,18 +3202,19 @@ fn rebalance_siblings(
     let new_weights = fractional_index::rebalance_order_weights(sibling_ids.len());
     let now = Utc::now().to_rfc3339();
 
+    // Clearing the keys lets them be derived again from the fresh weights
     for (i, id) in sibling_ids.iter().enumerate() {
         conn.execute(
-            "UPDATE blocks SET order_weight = ?, updated_at = ? WHERE id = ?",
+            "UPDATE blocks SET order_weight = ?, order_key = NULL, updated_at = ? WHERE id = ?",
             params![new_weights[i], &now, id],
         )
         .map_err(|e| e.to_string())?;
     }
 
-    Ok(())
+    // Older snapshots of these siblings carry positions from before the rescale
+    block_history::clear_page_history(conn, page_id)
 }
 
-
 /// Fetch all sibling blocks under a given parent
 fn get_siblings_as_blocks(
     conn: &Connection,
@@ -1796,8 +3224,8 @@ fn get_siblings_as_blocks(
     let mut stmt = conn
         .prepare(
             "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
-             FROM blocks WHERE page_id = ? AND parent_id IS ? ORDER BY order_weight",
+                is_collapsed, block_type, language, created_at, updated_at, order_key
+             FROM blocks WHERE page_id = ? AND parent_id IS ? ORDER BY order_key",
         )
         .map_err(|e| e.to_string())?;
 
@@ -1809,6 +3237,7 @@ fn get_siblings_as_blocks(
                 parent_id: row.get(2)?,
                 content: row.get(3)?,
                 order_weight: row.get(4)?,
+                order_key: row.get(10)?,
                 is_collapsed: row.get::<_, i32>(5)? != 0,
                 block_type: crate::models::block::string_to_block_type(&row.get::<_, String>(6)?),
                 language: row.get(7)?,
@@ -1845,7 +3274,7 @@ fn load_block_metadata(
 }
 
 /// Load metadata for multiple blocks in a single query
-fn load_blocks_metadata(
+pub(crate) fn load_blocks_metadata(
     conn: &Connection,
     block_ids: &[String],
 ) -> Result<HashMap<String, HashMap<String, String>>, String> {
@@ -1937,18 +3366,66 @@ fn update_todo_status_metadata(
     Ok(())
 }
 
-/// Save metadata for a block to the database
+/// Store a task bullet's checkbox state, which serializes as "- [ ]" / "- [x]"
+fn update_checked_metadata(conn: &Connection, block_id: &str, checked: bool) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM block_metadata WHERE block_id = ? AND key = ?",
+        params![block_id, CHECKED_METADATA_KEY],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let metadata_id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, ?, ?)",
+        params![
+            &metadata_id,
+            block_id,
+            CHECKED_METADATA_KEY,
+            checked.to_string()
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Check block metadata against the workspace's metadata schema without saving it.
+///
+/// Returns one entry per offending key (empty when everything fits), so the UI can
+/// highlight the fields to fix.
+#[tauri::command]
+pub fn validate_block_metadata(
+    workspace_path: String,
+    metadata: HashMap<String, String>,
+) -> Result<Vec<MetadataValidationError>, String> {
+    let schema = MetadataSchema::load(std::path::Path::new(&workspace_path))?;
+    Ok(schema
+        .apply(&metadata, Local::now().date_naive())
+        .err()
+        .unwrap_or_default())
+}
+
+/// Save metadata for a block to the database.
+///
+/// Values of keys declared in `schema` are normalized to their type first; if any
+/// don't fit, nothing is saved. Markdown read from page files is imported with an
+/// empty schema, since the file is the source of truth.
 fn save_block_metadata(
     conn: &Connection,
     block_id: &str,
     metadata: &HashMap<String, String>,
+    schema: &MetadataSchema,
 ) -> Result<(), String> {
+    let metadata = schema
+        .apply(metadata, Local::now().date_naive())
+        .map_err(|errors| format_validation_errors(&errors))?;
+
     // Delete existing metadata for this block
     conn.execute("DELETE FROM block_metadata WHERE block_id = ?", [block_id])
         .map_err(|e| e.to_string())?;
 
     // Insert new metadata
-    for (key, value) in metadata {
+    for (key, value) in &metadata {
         let metadata_id = Uuid::new_v4().to_string();
         conn.execute(
             "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, ?, ?)",
@@ -1960,11 +3437,140 @@ fn save_block_metadata(
     Ok(())
 }
 
+/// Outcome of replacing a page's blocks from markdown
+pub(crate) struct PageBlocksImport {
+    pub blocks: Vec<Block>,
+    /// Blocks of the page that no longer exist after the import
+    pub removed_block_ids: Vec<String>,
+}
+
+/// Apply the workspace's sanitization rules to parsed blocks before they are stored
+fn sanitize_blocks(blocks: &mut [Block], rules: &SanitizationRules) {
+    for block in blocks {
+        block.content = apply_sanitization_rules(&block.content, rules, &block.block_type);
+    }
+}
+
+/// Replace a page's blocks with those parsed from its markdown, in one transaction.
+/// Block content is stored with `rules` applied.
+///
+/// Blocks are upserted by ID (so references into the page survive), blocks no longer
+/// present in the markdown are removed, and block metadata and page frontmatter are
+/// replaced with what the file carries. An embedded ID that already belongs to a block on another page is given a
+/// fresh ID instead of stealing that block. FTS and wiki-link indexes are refreshed for
+/// every parsed block.
+pub(crate) fn import_page_blocks_from_markdown(
+    conn: &mut Connection,
+    page_id: &str,
+    markdown: &str,
+    indent: IndentStyle,
+    rules: &SanitizationRules,
+) -> Result<PageBlocksImport, String> {
+    let mut blocks = markdown_to_blocks(markdown, page_id, indent);
+    sanitize_blocks(&mut blocks, rules);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut remapped: HashMap<String, String> = HashMap::new();
+    for block in &blocks {
+        let owner: Option<String> = tx
+            .query_row(
+                "SELECT page_id FROM blocks WHERE id = ?",
+                [&block.id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if owner.is_some_and(|owner| owner != page_id) {
+            remapped.insert(block.id.clone(), Uuid::new_v4().to_string());
+        }
+    }
+    for block in &mut blocks {
+        if let Some(new_id) = remapped.get(&block.id) {
+            block.id = new_id.clone();
+        }
+        if let Some(new_parent) = block.parent_id.as_ref().and_then(|p| remapped.get(p)) {
+            block.parent_id = Some(new_parent.clone());
+        }
+    }
+
+    let new_ids: HashSet<&str> = blocks.iter().map(|b| b.id.as_str()).collect();
+
+    let existing_ids: Vec<String> = {
+        let mut stmt = tx
+            .prepare("SELECT id FROM blocks WHERE page_id = ?")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map([page_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+
+    let removed_block_ids: Vec<String> = existing_ids
+        .into_iter()
+        .filter(|id| !new_ids.contains(id.as_str()))
+        .collect();
+    for block_id in &removed_block_ids {
+        tx.execute("DELETE FROM blocks WHERE id = ?", [block_id])
+            .map_err(|e| e.to_string())?;
+        deindex_block_fts(&tx, block_id)?;
+    }
+
+    for block in &blocks {
+        tx.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key,
+                                 is_collapsed, block_type, language, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, NULLIF(?, ''), ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                page_id = excluded.page_id,
+                parent_id = excluded.parent_id,
+                content = excluded.content,
+                order_weight = excluded.order_weight,
+                order_key = excluded.order_key,
+                block_type = excluded.block_type,
+                language = excluded.language,
+                updated_at = excluded.updated_at",
+            params![
+                &block.id,
+                page_id,
+                &block.parent_id,
+                &block.content,
+                block.order_weight,
+                &block.order_key,
+                block.is_collapsed as i32,
+                block_type_to_string(&block.block_type),
+                &block.language,
+                &block.created_at,
+                &block.updated_at,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        save_block_metadata(&tx, &block.id, &block.metadata, &MetadataSchema::default())?;
+        update_todo_status_metadata(&tx, &block.id, &block.content)?;
+        index_block_fts(&tx, &block.id, page_id, &block.content)?;
+        wiki_link_index::index_block_links(&tx, &block.id, &block.content, page_id)
+            .map_err(|e| e.to_string())?;
+    }
+    save_page_frontmatter(&tx, page_id, markdown)?;
+    // The file replaced the page's blocks; journaled snapshots would restore over it
+    block_history::clear_page_history(&tx, page_id)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(PageBlocksImport {
+        blocks,
+        removed_block_ids,
+    })
+}
+
 fn get_block_by_id(conn: &Connection, id: &str) -> Result<Block, String> {
     let mut block = conn
         .query_row(
             "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
          FROM blocks WHERE id = ?",
             [id],
             |row| {
@@ -1974,6 +3580,7 @@ fn get_block_by_id(conn: &Connection, id: &str) -> Result<Block, String> {
                     parent_id: row.get(2)?,
                     content: row.get(3)?,
                     order_weight: row.get(4)?,
+                    order_key: row.get(10)?,
                     is_collapsed: row.get::<_, i32>(5)? != 0,
                     block_type: parse_block_type(row.get::<_, String>(6)?),
                     language: row.get(7)?,
@@ -2019,16 +3626,16 @@ fn find_previous_sibling(conn: &Connection, block: &Block) -> Result<Block, Stri
     let mut stmt = conn
         .prepare(
             "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
+                is_collapsed, block_type, language, created_at, updated_at, order_key
              FROM blocks
-             WHERE page_id = ? AND parent_id IS ? AND order_weight < ?
-             ORDER BY order_weight DESC
+             WHERE page_id = ? AND parent_id IS ? AND order_key < ?
+             ORDER BY order_key DESC
              LIMIT 1",
         )
         .map_err(|e| e.to_string())?;
 
     stmt.query_row(
-        params![&block.page_id, &block.parent_id, block.order_weight],
+        params![&block.page_id, &block.parent_id, &block.order_key],
         |row| {
             Ok(Block {
                 id: row.get(0)?,
@@ -2036,6 +3643,7 @@ fn find_previous_sibling(conn: &Connection, block: &Block) -> Result<Block, Stri
                 parent_id: row.get(2)?,
                 content: row.get(3)?,
                 order_weight: row.get(4)?,
+                order_key: row.get(10)?,
                 is_collapsed: row.get::<_, i32>(5)? != 0,
                 block_type: parse_block_type(row.get::<_, String>(6)?),
                 language: row.get(7)?,
@@ -2052,6 +3660,7 @@ pub fn parse_block_type(s: String) -> BlockType {
     match s.to_lowercase().as_str() {
         "code" => BlockType::Code,
         "fence" => BlockType::Fence,
+        "heading" => BlockType::Heading,
         _ => BlockType::Bullet,
     }
 }
@@ -2063,6 +3672,7 @@ pub fn block_type_to_string(bt: &BlockType) -> String {
         BlockType::Fence => "fence".to_string(),
         BlockType::AiPrompt => "ai-prompt".to_string(),
         BlockType::AiResponse => "ai-response".to_string(),
+        BlockType::Heading => "heading".to_string(),
     }
 }
 
@@ -2073,8 +3683,11 @@ pub fn index_block_fts(
     page_id: &str,
     content: &str,
 ) -> Result<(), String> {
+    // FTS5 tables have no unique key on block_id, so OR REPLACE never replaces:
+    // drop the old row first or the block ends up indexed twice.
+    deindex_block_fts(conn, block_id)?;
     conn.execute(
-        "INSERT OR REPLACE INTO blocks_fts (block_id, page_id, content, anchor_id, path_text)
+        "INSERT INTO blocks_fts (block_id, page_id, content, anchor_id, path_text)
          VALUES (?, ?, ?, ?, ?)",
         params![block_id, page_id, content, block_id, ""],
     )
@@ -2101,90 +3714,366 @@ pub struct CreateBlocksBatchResponse {
     pub created_count: usize,
 }
 
+/// Create a batch of blocks on one page, in one transaction.
+///
+/// If any block fails to insert the whole batch is rolled back, so neither the DB nor
+/// the page file changes. The page is synced only after the batch commits.
 #[tauri::command]
 pub async fn create_blocks_batch(
     app: tauri::AppHandle,
     workspace_path: String,
     request: CreateBlocksBatchRequest,
 ) -> Result<CreateBlocksBatchResponse, String> {
-    let conn = open_workspace_db(&workspace_path)?;
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let rules = load_sanitization_rules(&workspace_path);
+    let created_blocks = insert_blocks_batch(&mut conn, &request.page_id, request.blocks, &rules)?;
+
     let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &request.page_id).await?;
+
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &request.page_id);
+
+    Ok(CreateBlocksBatchResponse {
+        created_count: created_blocks.len(),
+        blocks: created_blocks,
+    })
+}
+
+/// Insert `requests` on `page_id`, each after the previous one. Commits only if every
+/// insert succeeds; an error drops the transaction, rolling back the whole batch.
+fn insert_blocks_batch(
+    conn: &mut Connection,
+    page_id: &str,
+    requests: Vec<CreateBlockRequest>,
+    rules: &SanitizationRules,
+) -> Result<Vec<Block>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut created_blocks = Vec::new();
+    let mut last_block_id: Option<String> = None;
+
+    for block_request in requests {
+        let (order_weight, order_key, _) = calculate_new_order_weight(
+            &tx,
+            page_id,
+            block_request.parent_id.as_deref(),
+            last_block_id.as_deref(),
+        )?;
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        let block_type = block_request.block_type.unwrap_or_default();
+        let content = apply_sanitization_rules(
+            &block_request.content.unwrap_or_default(),
+            rules,
+            &block_type,
+        );
+        let language = resolve_block_language(&block_type, None, &content);
+
+        tx.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key, block_type, language, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                &id,
+                page_id,
+                &block_request.parent_id,
+                &content,
+                order_weight,
+                &order_key,
+                block_type_to_string(&block_type),
+                &language,
+                &now,
+                &now
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        index_block_fts(&tx, &id, page_id, &content)?;
+
+        let created_block = get_block_by_id(&tx, &id)?;
+        wiki_link_index::index_block_links(
+            &tx,
+            &created_block.id,
+            &created_block.content,
+            &created_block.page_id,
+        )
+        .map_err(|e| e.to_string())?;
+
+        last_block_id = Some(created_block.id.clone());
+        created_blocks.push(created_block);
+    }
+
+    block_history::record_operation(
+        &tx,
+        "create_blocks_batch",
+        page_id,
+        &InverseOperation {
+            restore: Vec::new(),
+            remove: created_blocks.iter().map(|b| b.id.clone()).collect(),
+        },
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(created_blocks)
+}
+
+/// Import CSV rows as blocks appended to the end of a page.
+///
+/// Each row becomes one root block whose content is the `title_column` value; the
+/// other columns become `key::value` metadata named after their headers (empty cells
+/// are skipped). All rows are inserted in one transaction, then the page is synced.
+#[tauri::command]
+pub async fn import_csv_as_blocks(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+    csv: String,
+    title_column: String,
+) -> Result<Vec<Block>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let schema = MetadataSchema::load(std::path::Path::new(&workspace_path))?;
+    let rules = load_sanitization_rules(&workspace_path);
+    let blocks = insert_csv_blocks(&mut conn, &page_id, &csv, &title_column, &schema, &rules)?;
+
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
+
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(blocks)
+}
+
+fn insert_csv_blocks(
+    conn: &mut Connection,
+    page_id: &str,
+    csv: &str,
+    title_column: &str,
+    schema: &MetadataSchema,
+    rules: &SanitizationRules,
+) -> Result<Vec<Block>, String> {
+    let mut rows = parse_csv(csv)?.into_iter();
+    let headers: Vec<String> = rows
+        .next()
+        .ok_or_else(|| "CSV is empty".to_string())?
+        .into_iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let title_index = headers
+        .iter()
+        .position(|h| h == title_column)
+        .ok_or_else(|| format!("Column '{}' not found in CSV header", title_column))?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let last_root: Option<SiblingPosition> = tx
+        .query_row(
+            "SELECT order_weight, order_key FROM blocks WHERE page_id = ? AND parent_id IS NULL
+             ORDER BY order_key DESC LIMIT 1",
+            [page_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (last_weight, last_key) = last_root.unzip();
+    let mut order_weight = last_weight.unwrap_or(0.0);
+    let keys = sibling_keys_between(last_key.as_deref(), None, rows.len())?;
+
+    let mut ids = Vec::new();
+    for (row, order_key) in rows.zip(keys) {
+        let content = row
+            .get(title_index)
+            .map(|v| apply_sanitization_rules(v.trim(), rules, &BlockType::Bullet))
+            .unwrap_or_default();
+
+        // Metadata lives on a single markdown line, so fold any embedded newlines
+        let metadata: HashMap<String, String> = headers
+            .iter()
+            .zip(row.iter())
+            .enumerate()
+            .filter(|(i, (key, _))| *i != title_index && !key.is_empty())
+            .map(|(_, (key, value))| {
+                (
+                    key.clone(),
+                    value.split_whitespace().collect::<Vec<_>>().join(" "),
+                )
+            })
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        order_weight += 1.0;
+
+        tx.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key, block_type, created_at, updated_at)
+             VALUES (?, ?, NULL, ?, ?, ?, 'bullet', ?, ?)",
+            params![&id, page_id, &content, order_weight, &order_key, &now, &now],
+        )
+        .map_err(|e| e.to_string())?;
+
+        save_block_metadata(&tx, &id, &metadata, schema)?;
+        index_block_fts(&tx, &id, page_id, &content)?;
+        wiki_link_index::index_block_links(&tx, &id, &content, page_id)
+            .map_err(|e| e.to_string())?;
+
+        ids.push(id);
+    }
+
+    block_history::record_operation(
+        &tx,
+        "import_csv",
+        page_id,
+        &InverseOperation {
+            restore: Vec::new(),
+            remove: ids.clone(),
+        },
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    ids.iter().map(|id| get_block_by_id(conn, id)).collect()
+}
+
+/// Paste a markdown outline as blocks under `parent_id`, after `after_block_id` (or
+/// first among its siblings when None).
+///
+/// The pasted hierarchy keeps its shape, with its top-level blocks becoming children
+/// of `parent_id`. Every pasted block gets a fresh ID: `ID::` lines in the text are
+/// dropped, so pasting never takes over an existing block. All blocks are inserted in
+/// one transaction and the page is synced once afterwards.
+#[tauri::command]
+pub async fn paste_markdown_as_blocks(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+    parent_id: Option<String>,
+    after_block_id: Option<String>,
+    markdown: String,
+) -> Result<Vec<Block>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let blocks = insert_pasted_markdown(
+        &mut conn,
+        &page_id,
+        parent_id.as_deref(),
+        after_block_id.as_deref(),
+        &markdown,
+        load_indent_style(&workspace_path),
+        &load_sanitization_rules(&workspace_path),
+    )?;
+    if blocks.is_empty() {
+        return Ok(blocks);
+    }
 
-    let mut created_blocks = Vec::new();
-    let mut last_block_id: Option<String> = None;
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
 
-    for mut block_request in request.blocks {
-        block_request.page_id = request.page_id.clone();
-
-        let order_weight = {
-            let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-            calculate_new_order_weight(
-                &conn,
-                &block_request.page_id,
-                block_request.parent_id.as_deref(),
-                last_block_id.as_deref(),
-            )?.0  // Extract just the order_weight, ignore rebalance flag
-        };
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
 
-        let id = Uuid::new_v4().to_string();
-        let now = Utc::now().to_rfc3339();
-        let block_type = block_request.block_type.unwrap_or_default();
-        let content = block_request.content.unwrap_or_default();
+    Ok(blocks)
+}
 
-        {
-            let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-            conn.execute(
-                "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, block_type, created_at, updated_at)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                params![
-                    &id,
-                    &block_request.page_id,
-                    &block_request.parent_id,
-                    &content,
-                    order_weight,
-                    block_type_to_string(&block_type),
-                    &now,
-                    &now
-                ],
-            )
-            .map_err(|e| e.to_string())?;
+fn insert_pasted_markdown(
+    conn: &mut Connection,
+    page_id: &str,
+    parent_id: Option<&str>,
+    after_block_id: Option<&str>,
+    markdown: &str,
+    indent: IndentStyle,
+    rules: &SanitizationRules,
+) -> Result<Vec<Block>, String> {
+    let cleaned = normalize_marker_layout(&strip_id_markers(markdown), indent);
+    let mut blocks = markdown_to_blocks(&cleaned, page_id, indent);
+    sanitize_blocks(&mut blocks, rules);
+    if blocks.is_empty() {
+        return Ok(blocks);
+    }
 
-            index_block_fts(&conn, &id, &block_request.page_id, &content)?;
+    if let Some(parent_id) = parent_id {
+        let parent = get_block_by_id(conn, parent_id)?;
+        if parent.page_id != page_id {
+            return Err("Parent block belongs to a different page".to_string());
         }
+    }
 
-        let created_block = {
-            let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-            get_block_by_id(&conn, &id)?
-        };
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
 
-        {
-            let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-            wiki_link_index::index_block_links(
-                &conn,
-                &created_block.id,
-                &created_block.content,
-                &created_block.page_id,
-            )
-            .map_err(|e| e.to_string())?;
+    // Fresh IDs throughout, in case the parser kept any
+    let remapped: HashMap<String, String> = blocks
+        .iter()
+        .map(|block| (block.id.clone(), Uuid::new_v4().to_string()))
+        .collect();
+    let (before, after) = get_neighbor_positions(&tx, page_id, parent_id, after_block_id)?;
+    let root_count = blocks.iter().filter(|b| b.parent_id.is_none()).count();
+    let mut root_weights = fractional_index::calculate_between(
+        before.as_ref().map(|(weight, _)| *weight),
+        after.as_ref().map(|(weight, _)| *weight),
+        root_count,
+    )
+    .into_iter();
+    let mut root_keys = sibling_keys_between(
+        before.as_ref().map(|(_, key)| key.as_str()),
+        after.as_ref().map(|(_, key)| key.as_str()),
+        root_count,
+    )?
+    .into_iter();
+    // Pasted children keep their parsed weights, from which their keys are derived
+    for block in &mut blocks {
+        block.id = remapped[&block.id].clone();
+        match block.parent_id.take() {
+            Some(pasted_parent) => block.parent_id = Some(remapped[&pasted_parent].clone()),
+            None => {
+                block.parent_id = parent_id.map(str::to_string);
+                block.order_weight = root_weights.next().unwrap_or(block.order_weight);
+                block.order_key = root_keys.next().unwrap_or_default();
+            }
         }
+    }
 
-        last_block_id = Some(created_block.id.clone());
-        created_blocks.push(created_block);
+    let now = Utc::now().to_rfc3339();
+    for block in &blocks {
+        tx.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key,
+                                 is_collapsed, block_type, language, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, NULLIF(?, ''), ?, ?, ?, ?, ?)",
+            params![
+                &block.id,
+                page_id,
+                &block.parent_id,
+                &block.content,
+                block.order_weight,
+                &block.order_key,
+                block.is_collapsed as i32,
+                block_type_to_string(&block.block_type),
+                &block.language,
+                &now,
+                &now,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        save_block_metadata(&tx, &block.id, &block.metadata, &MetadataSchema::default())?;
+        update_todo_status_metadata(&tx, &block.id, &block.content)?;
+        index_block_fts(&tx, &block.id, page_id, &block.content)?;
+        wiki_link_index::index_block_links(&tx, &block.id, &block.content, page_id)
+            .map_err(|e| e.to_string())?;
     }
 
-    sync_page_to_markdown(
-        &conn_mutex,
-        &workspace_path,
-        &request.page_id,
-    )
-    .await?;
+    block_history::record_operation(
+        &tx,
+        "paste_markdown",
+        page_id,
+        &InverseOperation {
+            restore: Vec::new(),
+            remove: blocks.iter().map(|b| b.id.clone()).collect(),
+        },
+    )?;
 
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    tx.commit().map_err(|e| e.to_string())?;
 
-    Ok(CreateBlocksBatchResponse {
-        created_count: created_blocks.len(),
-        blocks: created_blocks,
-    })
+    blocks
+        .iter()
+        .map(|b| get_block_by_id(conn, &b.id))
+        .collect()
 }
 
 #[cfg(test)]
@@ -2665,6 +4554,7 @@ mod tests {
                         parent_id: r.get(2)?,
                         content: r.get(3)?,
                         order_weight: r.get(4)?,
+                        order_key: String::new(),
                         is_collapsed: r.get::<_, i32>(5)? != 0,
                         block_type: parse_block_type(r.get::<_, String>(6)?),
                         language: r.get(7)?,
@@ -2690,4 +4580,834 @@ mod tests {
             fs::remove_dir_all(&temp_dir).unwrap();
         });
     }
+
+    #[test]
+    fn test_swap_sibling_order() {
+        tauri::async_runtime::block_on(async {
+            let temp_dir = std::env::temp_dir().join(format!("oxinot_test_{}", Uuid::new_v4()));
+            fs::create_dir_all(&temp_dir).unwrap();
+            let path_str = temp_dir.to_string_lossy().to_string();
+
+            let mut conn = open_workspace_db(&path_str).unwrap();
+            let page_file = "SwapPage.md";
+            conn.execute(
+                "INSERT INTO pages (id, title, file_path) VALUES ('page1', 'SwapPage', ?)",
+                params![page_file],
+            )
+            .unwrap();
+            for (id, content, weight) in [("a", "First", 1.0), ("b", "Second", 2.0), ("c", "Third", 3.0)] {
+                conn.execute(
+                    "INSERT INTO blocks (id, page_id, content, order_weight) VALUES (?, 'page1', ?, ?)",
+                    params![id, content, weight],
+                )
+                .unwrap();
+            }
+
+            let (a, b) = swap_sibling_order(&mut conn, "a", "b").unwrap();
+            assert_eq!(a.order_weight, 2.0);
+            assert_eq!(b.order_weight, 1.0);
+
+            let order: Vec<String> = conn
+                .prepare("SELECT id FROM blocks WHERE page_id = 'page1' ORDER BY order_key")
+                .unwrap()
+                .query_map([], |r| r.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(order, vec!["b", "a", "c"]);
+
+            let conn_mutex = Mutex::new(conn);
+            sync_page_to_markdown(&conn_mutex, &path_str, "page1").await.unwrap();
+            let content = fs::read_to_string(temp_dir.join(page_file)).unwrap();
+            let first = content.find("- First").unwrap();
+            let second = content.find("- Second").unwrap();
+            let third = content.find("- Third").unwrap();
+            assert!(second < first && first < third);
+
+            // Blocks under different parents cannot be swapped
+            let mut conn = conn_mutex.into_inner().unwrap();
+            conn.execute("UPDATE blocks SET parent_id = 'a' WHERE id = 'c'", [])
+                .unwrap();
+            assert!(swap_sibling_order(&mut conn, "a", "c").is_err());
+
+            fs::remove_dir_all(&temp_dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_merge_block_into_parent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute("INSERT INTO pages (id, title) VALUES ('page1', 'Page')", [])
+            .unwrap();
+        for (id, parent, content, weight) in [
+            ("p", None, "Parent", 1.0),
+            ("x", Some("p"), " absorbed", 1.0),
+            ("s", Some("p"), "Sibling", 2.0),
+            ("c1", Some("x"), "Child 1", 1.0),
+            ("c2", Some("x"), "Child 2", 2.0),
+        ] {
+            conn.execute(
+                "INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES (?, 'page1', ?, ?, ?)",
+                params![id, parent, content, weight],
+            )
+            .unwrap();
+        }
+
+        let (_, parent_id, promoted) = merge_block_into_parent(&mut conn, "x").unwrap();
+        assert_eq!(parent_id, "p");
+        assert_eq!(promoted, vec!["c1", "c2"]);
+
+        let parent = get_block_by_id(&conn, "p").unwrap();
+        assert_eq!(parent.content, "Parent absorbed");
+        assert!(get_block_by_id(&conn, "x").is_err());
+
+        let order: Vec<String> = conn
+            .prepare("SELECT id FROM blocks WHERE parent_id = 'p' ORDER BY order_key")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(order, vec!["c1", "c2", "s"]);
+
+        // Root blocks have no parent to merge into
+        assert!(merge_block_into_parent(&mut conn, "p").is_err());
+    }
+
+    #[test]
+    fn test_create_blocks_batch_rolls_back_on_failure() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+             INSERT INTO pages (id, title, file_path) VALUES ('p', 'Page', 'Page.md');",
+        )
+        .unwrap();
+
+        let request = |parent_id: Option<&str>, content: &str| CreateBlockRequest {
+            page_id: "p".to_string(),
+            parent_id: parent_id.map(|p| p.to_string()),
+            after_block_id: None,
+            content: Some(content.to_string()),
+            block_type: None,
+        };
+
+        // The third block points at a parent that does not exist
+        let err = insert_blocks_batch(
+            &mut conn,
+            "p",
+            vec![
+                request(None, "first"),
+                request(None, "second"),
+                request(Some("missing-parent"), "third"),
+                request(None, "fourth"),
+            ],
+            &SanitizationRules::default(),
+        )
+        .unwrap_err();
+        assert!(err.contains("FOREIGN KEY"), "{}", err);
+
+        let block_count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM blocks", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(block_count, 0);
+        let fts_count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM blocks_fts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(fts_count, 0);
+
+        let created = insert_blocks_batch(
+            &mut conn,
+            "p",
+            vec![request(None, "first"), request(None, "second")],
+            &SanitizationRules::default(),
+        )
+        .unwrap();
+        assert_eq!(created.len(), 2);
+        assert!(created[0].order_weight < created[1].order_weight);
+    }
+
+    #[test]
+    fn test_new_code_blocks_get_detected_language() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('p', 'Page', 'Page.md')",
+            [],
+        )
+        .unwrap();
+
+        let request = |block_type: BlockType, content: &str| CreateBlockRequest {
+            page_id: "p".to_string(),
+            parent_id: None,
+            after_block_id: None,
+            content: Some(content.to_string()),
+            block_type: Some(block_type),
+        };
+        let python = "def main():\n    print('hi')";
+
+        let created = insert_blocks_batch(
+            &mut conn,
+            "p",
+            vec![
+                request(BlockType::Code, python),
+                request(BlockType::Code, "just some text"),
+                request(BlockType::Bullet, python),
+            ],
+            &SanitizationRules::default(),
+        )
+        .unwrap();
+
+        assert_eq!(created[0].language.as_deref(), Some("python"));
+        assert_eq!(created[1].language, None);
+        assert_eq!(created[2].language, None);
+
+        // An explicit language is never overridden
+        let kept = resolve_block_language(&BlockType::Code, Some("text".to_string()), python);
+        assert_eq!(kept.as_deref(), Some("text"));
+
+        // Updates detect only when a block turns into a code block
+        let converted = updated_block_language(false, &BlockType::Code, None, python);
+        assert_eq!(converted.as_deref(), Some("python"));
+        let edited = updated_block_language(true, &BlockType::Code, None, python);
+        assert_eq!(edited, None);
+    }
+
+    #[test]
+    fn test_import_csv_as_blocks() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('movies', 'Movies', 'Movies.md')",
+            [],
+        )
+        .unwrap();
+
+        let csv = "title,year,rating\nHeat,1995,8.3\n\"Crouching Tiger, Hidden Dragon\",2000,7.9\n";
+        let blocks = insert_csv_blocks(
+            &mut conn,
+            "movies",
+            csv,
+            "title",
+            &MetadataSchema::default(),
+            &SanitizationRules::default(),
+        )
+        .unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].content, "Heat");
+        assert_eq!(blocks[0].metadata.get("year").map(String::as_str), Some("1995"));
+        assert_eq!(blocks[0].metadata.get("rating").map(String::as_str), Some("8.3"));
+        assert!(!blocks[0].metadata.contains_key("title"));
+        assert_eq!(blocks[1].content, "Crouching Tiger, Hidden Dragon");
+        assert_eq!(blocks[1].metadata.get("year").map(String::as_str), Some("2000"));
+        assert_eq!(blocks[1].metadata.get("rating").map(String::as_str), Some("7.9"));
+        assert!(blocks[0].order_weight < blocks[1].order_weight);
+
+        let missing_column = insert_csv_blocks(
+            &mut conn,
+            "movies",
+            csv,
+            "name",
+            &MetadataSchema::default(),
+            &SanitizationRules::default(),
+        );
+        assert!(missing_column.is_err());
+    }
+
+    #[test]
+    fn test_csv_import_applies_metadata_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('movies', 'Movies', 'Movies.md')",
+            [],
+        )
+        .unwrap();
+        let schema: MetadataSchema =
+            serde_json::from_str(r#"{"keys": {"seen": "boolean", "year": "number"}}"#).unwrap();
+        let rules = SanitizationRules::default();
+
+        let csv = "title,seen,year\nHeat,Yes,1995\n";
+        let blocks = insert_csv_blocks(&mut conn, "movies", csv, "title", &schema, &rules).unwrap();
+        let seen = blocks[0].metadata.get("seen").map(String::as_str);
+        assert_eq!(seen, Some("true"));
+
+        let csv = "title,seen,year\nAlien,no,nineteen\n";
+        let err =
+            insert_csv_blocks(&mut conn, "movies", csv, "title", &schema, &rules).unwrap_err();
+        assert!(err.contains("year expects a number"));
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM blocks WHERE page_id = 'movies'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_paste_markdown_as_blocks_rebases_under_target() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('parent', 'p1', NULL, 'Parent', 1.0),
+                ('first', 'p1', 'parent', 'First', 1.0),
+                ('last', 'p1', 'parent', 'Last', 2.0);",
+        )
+        .unwrap();
+
+        let markdown = "- Pasted\n  ID::first\n  - Nested\n- Second root\n";
+        let blocks = insert_pasted_markdown(
+            &mut conn,
+            "p1",
+            Some("parent"),
+            Some("first"),
+            markdown,
+            IndentStyle::default(),
+            &SanitizationRules::default(),
+        )
+        .unwrap();
+
+        assert_eq!(blocks.len(), 3);
+        let pasted = blocks.iter().find(|b| b.content == "Pasted").unwrap();
+        let nested = blocks.iter().find(|b| b.content == "Nested").unwrap();
+        let second = blocks.iter().find(|b| b.content == "Second root").unwrap();
+        assert_ne!(pasted.id, "first");
+        assert_eq!(pasted.parent_id.as_deref(), Some("parent"));
+        assert_eq!(nested.parent_id.as_deref(), Some(pasted.id.as_str()));
+        assert_eq!(second.parent_id.as_deref(), Some("parent"));
+        assert!(1.0 < pasted.order_weight && pasted.order_weight < second.order_weight);
+        assert!(second.order_weight < 2.0);
+
+        // The existing block keyed by the pasted ID marker is untouched
+        let existing = get_block_by_id(&conn, "first").unwrap();
+        assert_eq!(existing.content, "First");
+        assert_eq!(existing.parent_id.as_deref(), Some("parent"));
+    }
+
+    #[test]
+    fn test_apply_sibling_order() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page'), ('p2', 'Other');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('parent', 'p1', NULL, 'Parent', 1.0),
+                ('a', 'p1', 'parent', 'A', 1.0),
+                ('b', 'p1', 'parent', 'B', 1.5),
+                ('c', 'p1', 'parent', 'C', 1.75),
+                ('root', 'p1', NULL, 'Root', 2.0),
+                ('elsewhere', 'p2', NULL, 'Elsewhere', 1.0);",
+        )
+        .unwrap();
+        let ids = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let siblings =
+            apply_sibling_order(&mut conn, "p1", Some("parent"), &ids(&["c", "a", "b"])).unwrap();
+        let order: Vec<(&str, f64)> = siblings
+            .iter()
+            .map(|b| (b.id.as_str(), b.order_weight))
+            .collect();
+        assert_eq!(order, vec![("c", 1.0), ("a", 2.0), ("b", 3.0)]);
+
+        let reorder = |conn: &mut Connection, list: &[&str]| {
+            apply_sibling_order(conn, "p1", Some("parent"), &ids(list))
+        };
+        assert!(reorder(&mut conn, &["c", "a", "root"]).is_err());
+        assert!(reorder(&mut conn, &["c", "a", "elsewhere"]).is_err());
+        assert!(reorder(&mut conn, &["c", "a"]).is_err());
+        assert!(reorder(&mut conn, &["c", "a", "a"]).is_err());
+        assert!(reorder(&mut conn, &["c", "a", "missing"]).is_err());
+    }
+
+    #[test]
+    fn test_paste_and_import_apply_sanitization_rules() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute("INSERT INTO pages (id, title) VALUES ('p1', 'Page')", [])
+            .unwrap();
+        let rules = SanitizationRules {
+            trim_trailing_whitespace: true,
+            normalize_smart_quotes: true,
+            collapse_blank_lines: false,
+        };
+
+        let pasted = insert_pasted_markdown(
+            &mut conn,
+            "p1",
+            None,
+            None,
+            "- \u{201c}Quoted\u{201d}   \n```\nkeep\u{2019}s   \n```\n",
+            IndentStyle::default(),
+            &rules,
+        )
+        .unwrap();
+        assert_eq!(pasted[0].content, "\"Quoted\"");
+        assert_eq!(pasted[1].content, "keep\u{2019}s   ");
+
+        import_page_blocks_from_markdown(
+            &mut conn,
+            "p1",
+            "- It\u{2019}s here  \n",
+            IndentStyle::default(),
+            &rules,
+        )
+        .unwrap();
+        let stored: String = conn
+            .query_row("SELECT content FROM blocks WHERE page_id = 'p1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(stored, "It's here");
+    }
+
+    fn replace_test_conn(content: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute("INSERT INTO pages (id, title) VALUES ('p1', 'Page')", [])
+            .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', ?, 1.0)",
+            [content],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_replace_in_block_first_vs_all() {
+        let conn = replace_test_conn("café latte, café mocha, café");
+        let rules = SanitizationRules::default();
+
+        let (block, count) =
+            replace_in_block_content(&conn, "b1", "café", "tea", false, false, &rules).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(block.content, "tea latte, café mocha, café");
+
+        let (block, count) =
+            replace_in_block_content(&conn, "b1", "café", "tea", false, true, &rules).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(block.content, "tea latte, tea mocha, tea");
+
+        let (block, count) =
+            replace_in_block_content(&conn, "b1", "missing", "x", false, true, &rules).unwrap();
+        assert_eq!(count, 0);
+        assert_eq!(block.content, "tea latte, tea mocha, tea");
+
+        assert!(replace_in_block_content(&conn, "b1", "", "x", false, true, &rules).is_err());
+    }
+
+    #[test]
+    fn test_replace_in_block_regex_capture_group() {
+        let conn = replace_test_conn("due 2024-01-05, moved to 2024-02-10 [[Plan]]");
+        let rules = SanitizationRules::default();
+
+        let (block, count) = replace_in_block_content(
+            &conn,
+            "b1",
+            r"(\d{4})-(\d{2})-(\d{2})",
+            "$3/$2/$1",
+            true,
+            true,
+            &rules,
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            block.content,
+            "due 05/01/2024, moved to 10/02/2024 [[Plan]]"
+        );
+
+        let (block, count) =
+            replace_in_block_content(&conn, "b1", r"(\d+)/", "<$1>", true, false, &rules).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            block.content,
+            "due <05>01/2024, moved to 10/02/2024 [[Plan]]"
+        );
+
+        assert!(replace_in_block_content(&conn, "b1", "(", "x", true, true, &rules).is_err());
+    }
+
+    #[test]
+    fn test_debug_order_weights_reports_key_state() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight, order_key) VALUES ('a', 'p1', 'A', 1.0, 'V');
+             INSERT INTO blocks (id, page_id, content, order_weight, order_key) VALUES ('c', 'p1', 'C', 3.0, 'X');
+             INSERT INTO blocks (id, page_id, content, order_weight, order_key) VALUES ('b', 'p1', 'B', 2.0, 'W');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('a1', 'p1', 'a', 'A1', 1.0);",
+        )
+        .unwrap();
+        // A duplicate of `b`'s key and a key ending in the zero digit, as left by hand edits
+        conn.execute_batch(
+            "INSERT INTO blocks (id, page_id, content, order_weight, order_key) VALUES ('b2', 'p1', 'B2', 2.5, 'W');
+             INSERT INTO blocks (id, page_id, content, order_weight, order_key) VALUES ('d', 'p1', 'D', 4.0, 'Y0');",
+        )
+        .unwrap();
+
+        let siblings = load_sibling_order_weights(&conn, "p1", None).unwrap();
+        let ids: Vec<&str> = siblings.iter().map(|s| s.block_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "b2", "c", "d"]);
+        let keys: Vec<&str> = siblings.iter().map(|s| s.order_key.as_str()).collect();
+        assert_eq!(keys, vec!["V", "W", "W", "X", "Y0"]);
+
+        let out_of_order: Vec<bool> = siblings.iter().map(|s| s.out_of_order).collect();
+        assert_eq!(out_of_order, vec![false, false, true, false, false]);
+        let invalid: Vec<bool> = siblings.iter().map(|s| s.invalid_key).collect();
+        assert_eq!(invalid, vec![false, false, false, false, true]);
+
+        let children = load_sibling_order_weights(&conn, "p1", Some("a")).unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].block_id, "a1");
+    }
+
+    #[test]
+    fn test_repeated_inserts_at_one_spot_never_rebalance() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('a', 'p1', 'A', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b', 'p1', 'B', 2.0);",
+        )
+        .unwrap();
+
+        // Each insert lands right after `a`, halving the same gap; f64 weights run
+        // out of room after about fifty of these
+        let mut expected = vec!["b".to_string()];
+        for i in 0..200 {
+            let (weight, key, rebalanced) =
+                calculate_new_order_weight(&conn, "p1", None, Some("a")).unwrap();
+            assert!(!rebalanced);
+            let id = format!("n{}", i);
+            conn.execute(
+                "INSERT INTO blocks (id, page_id, content, order_weight, order_key)
+                 VALUES (?, 'p1', '', ?, ?)",
+                params![&id, weight, &key],
+            )
+            .unwrap();
+            expected.insert(0, id);
+        }
+        expected.insert(0, "a".to_string());
+
+        let ids: Vec<String> = get_siblings_as_blocks(&conn, "p1", None)
+            .unwrap()
+            .into_iter()
+            .map(|block| block.id)
+            .collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_group_blocks_under_new_parent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('before', 'p1', 'Before', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('x', 'p1', 'X', 2.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('y', 'p1', 'Y', 3.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('z', 'p1', 'Z', 4.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('after', 'p1', 'After', 5.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('x1', 'p1', 'x', 'X child', 1.0);",
+        )
+        .unwrap();
+
+        let selection = vec!["y".to_string(), "x".to_string(), "z".to_string()];
+        let (page_id, group_id, child_ids) =
+            group_blocks_under_parent(&mut conn, &selection, "Group").unwrap();
+        assert_eq!(page_id, "p1");
+        assert_eq!(child_ids, vec!["x", "y", "z"]);
+
+        let ids_under = |parent: Option<&str>| -> Vec<String> {
+            conn.prepare(
+                "SELECT id FROM blocks WHERE page_id = 'p1' AND parent_id IS ? ORDER BY order_key",
+            )
+            .unwrap()
+            .query_map([parent], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+        };
+        assert_eq!(ids_under(None), vec!["before", group_id.as_str(), "after"]);
+        assert_eq!(ids_under(Some(&group_id)), vec!["x", "y", "z"]);
+        assert_eq!(ids_under(Some("x")), vec!["x1"]);
+
+        let group = get_block_by_id(&conn, &group_id).unwrap();
+        assert_eq!(group.content, "Group");
+        assert_eq!(group.parent_id, None);
+
+        // Non-siblings are rejected
+        let mixed = vec!["before".to_string(), "x1".to_string()];
+        assert!(group_blocks_under_parent(&mut conn, &mixed, "Nope").is_err());
+        assert!(group_blocks_under_parent(&mut conn, &[], "Nope").is_err());
+    }
+
+    #[test]
+    fn test_preview_sibling_insert_after_last_child_stays_sibling() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('parent', 'p1', 'Parent', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('next', 'p1', 'Next', 2.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('c1', 'p1', 'parent', 'First', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('c2', 'p1', 'parent', 'Last', 2.0);",
+        )
+        .unwrap();
+
+        // Splitting at the end of the last child must not nest under it
+        let preview = compute_insert_position(&conn, "c2", true).unwrap();
+        assert_eq!(preview.page_id, "p1");
+        assert_eq!(preview.parent_id.as_deref(), Some("parent"));
+        assert_eq!(preview.depth, 1);
+        assert_eq!(preview.before_weight, Some(2.0));
+        assert_eq!(preview.after_weight, None);
+        assert!(preview.order_weight > 2.0);
+
+        let between = compute_insert_position(&conn, "c1", true).unwrap();
+        assert_eq!(between.parent_id.as_deref(), Some("parent"));
+        assert_eq!(
+            (between.before_weight, between.after_weight),
+            (Some(1.0), Some(2.0))
+        );
+        assert_eq!(between.order_weight, 1.5);
+
+        let root = compute_insert_position(&conn, "parent", true).unwrap();
+        assert_eq!(root.parent_id, None);
+        assert_eq!(root.depth, 0);
+        assert_eq!(root.after_weight, Some(2.0));
+
+        let child = compute_insert_position(&conn, "c2", false).unwrap();
+        assert_eq!(child.parent_id.as_deref(), Some("c2"));
+        assert_eq!(child.depth, 2);
+        assert_eq!((child.before_weight, child.after_weight), (None, None));
+
+        // Nothing was inserted
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM blocks", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 4);
+        assert!(compute_insert_position(&conn, "missing", true).is_err());
+    }
+
+    #[test]
+    fn test_resolve_block_path_segments_normalized_fallback() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p', 'P');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('root', 'p', NULL, 'Re\u{301}view', 1.0),
+                ('child', 'p', 'root', 'Next Steps', 1.0);",
+        )
+        .unwrap();
+        let request = |segments: &[&str], case_insensitive: bool| ResolveBlockPathRequest {
+            page_id: "p".to_string(),
+            segments: segments.iter().map(|s| s.to_string()).collect(),
+            case_insensitive,
+        };
+
+        let exact = resolve_block_path_segments(&conn, &request(&["Re\u{301}view"], false))
+            .unwrap()
+            .unwrap();
+        assert_eq!(exact.block_id, "root");
+        assert_eq!(exact.match_type, PathMatchType::Exact);
+
+        let composed = request(&["R\u{e9}view", "Next Steps"], false);
+        let composed = resolve_block_path_segments(&conn, &composed)
+            .unwrap()
+            .unwrap();
+        assert_eq!(composed.block_id, "child");
+        assert_eq!(composed.match_type, PathMatchType::Normalized);
+
+        let cased = request(&["R\u{e9}view", "next steps"], false);
+        assert!(resolve_block_path_segments(&conn, &cased)
+            .unwrap()
+            .is_none());
+        let folded = request(&["R\u{e9}view", "next steps"], true);
+        let folded = resolve_block_path_segments(&conn, &folded)
+            .unwrap()
+            .unwrap();
+        assert_eq!(folded.block_id, "child");
+        assert_eq!(folded.match_type, PathMatchType::CaseInsensitive);
+    }
+
+    #[test]
+    fn test_block_match_snippet_marks_matches_and_escapes_content() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        let long = format!(
+            "{} the <mark>needle</mark> sits here {}",
+            "lorem ".repeat(20),
+            "ipsum ".repeat(20)
+        );
+        conn.execute(
+            "INSERT INTO blocks_fts (block_id, page_id, content) VALUES ('b1', 'p1', ?)",
+            [&long],
+        )
+        .unwrap();
+
+        let snippet = block_match_snippet(&conn, "b1", &long, "needle", 8);
+        assert!(snippet.contains("&lt;mark&gt;<mark>needle</mark>&lt;/mark&gt;"));
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.len() < long.len());
+
+        // Short content comes back whole, with every match marked
+        assert_eq!(
+            block_match_snippet(&conn, "b2", "a & b & c", "&", 8),
+            "a <mark>&amp;</mark> b <mark>&amp;</mark> c"
+        );
+    }
+
+    #[test]
+    fn test_page_outline_is_shallow_and_in_document_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b', 'p1', 'Second', 2.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('a', 'p1', 'First\nmore', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('a2', 'p1', 'a', 'A two', 2.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('a1', 'p1', 'a', 'A one', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('a1x', 'p1', 'a1', 'Deep', 1.0);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('b1', 'p1', 'b', ?, 1.0)",
+            ["x".repeat(OUTLINE_CONTENT_MAX_CHARS + 10)],
+        )
+        .unwrap();
+
+        let outline = load_page_outline(&conn, "p1", 2).unwrap();
+        let ids: Vec<&str> = outline.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "a1", "a2", "b", "b1"]);
+        assert_eq!(
+            outline.iter().map(|e| e.depth).collect::<Vec<_>>(),
+            vec![0, 1, 1, 0, 1]
+        );
+
+        assert_eq!(outline[0].content, "First");
+        assert_eq!(outline[0].child_count, 2);
+        // a1's child is beyond the outline but still counted
+        assert!(outline[1].has_children);
+        assert_eq!(outline[1].child_count, 1);
+        assert!(!outline[2].has_children);
+        assert_eq!(
+            outline[4].content.chars().count(),
+            OUTLINE_CONTENT_MAX_CHARS + 1
+        );
+
+        let roots = load_page_outline(&conn, "p1", 1).unwrap();
+        assert_eq!(roots.len(), 2);
+        assert!(load_page_outline(&conn, "p1", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_alias_conflicts_lists_blocks_in_creation_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight, created_at)
+                 VALUES ('b', 'p1', 'B', 1.0, '2024-02-01T00:00:00Z');
+             INSERT INTO blocks (id, page_id, content, order_weight, created_at)
+                 VALUES ('a', 'p1', 'A', 2.0, '2024-01-01T00:00:00Z');
+             INSERT INTO blocks (id, page_id, content, order_weight, created_at)
+                 VALUES ('c', 'p1', 'C', 3.0, '2024-03-01T00:00:00Z');
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m1', 'b', 'alias', 'dup');
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m2', 'a', 'alias', 'dup');
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m3', 'c', 'alias', 'solo');",
+        )
+        .unwrap();
+
+        let conflicts = find_alias_conflicts(&conn).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].alias, "dup");
+        assert_eq!(conflicts[0].block_ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_find_broken_block_embeds_reports_missing_targets() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        let live = "11111111-1111-4111-8111-111111111111";
+        let gone = "22222222-2222-4222-8222-222222222222";
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Notes');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('p1', 'Work/Notes');",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES (?, 'p1', 'Target', 1.0)",
+            [live],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('src', 'p1', ?, 2.0)",
+            [format!(
+                "(({})) and (({})) twice (({})) ((not a uuid))",
+                live, gone, gone
+            )],
+        )
+        .unwrap();
+
+        let broken = load_broken_block_embeds(&conn).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].from_block_id, "src");
+        assert_eq!(broken[0].missing_block_id, gone);
+        assert_eq!(broken[0].page_title, "Notes");
+        assert_eq!(broken[0].page_path.as_deref(), Some("Work/Notes"));
+    }
+
+    #[test]
+    fn test_query_page_blocks_checked_reports_corruption_without_repair() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'kept', 1.0);
+             PRAGMA foreign_keys = OFF;
+             INSERT INTO blocks (id, page_id, content, order_weight)
+                VALUES ('orphan', 'gone', 'orphan', 1.0);
+             PRAGMA foreign_keys = ON;",
+        )
+        .unwrap();
+
+        // The first read fails the way a foreign key violation surfaces
+        let calls = std::cell::Cell::new(0);
+        let query = |conn: &Connection, page_id: &str| {
+            calls.set(calls.get() + 1);
+            if calls.get() == 1 {
+                Err("FOREIGN KEY constraint failed".to_string())
+            } else {
+                query_blocks_for_page(conn, page_id)
+            }
+        };
+        let block_count = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM blocks", [], |row| row.get(0))
+                .unwrap()
+        };
+
+        let err =
+            query_page_blocks_checked_with(&mut conn, "p1", false, "test", &query).unwrap_err();
+        assert!(OxinotError::is_corruption(&err));
+        assert!(err.contains("run repair_db"));
+        assert_eq!(calls.get(), 1);
+        assert_eq!(block_count(&conn), 2);
+
+        calls.set(0);
+        let blocks = query_page_blocks_checked_with(&mut conn, "p1", true, "test", &query).unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(block_count(&conn), 1);
+    }
 }
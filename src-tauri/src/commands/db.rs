@@ -1,7 +1,21 @@
-use crate::commands::workspace::open_workspace_db;
+use crate::commands::block::{deindex_block_fts, import_page_blocks_from_markdown};
+use crate::commands::workspace::{
+    is_ignored_sync_entry, load_indent_style, open_workspace_db, record_last_optimized,
+    syncable_markdown_path,
+};
 use crate::error::OxinotError;
-use crate::services::FtsService;
+use crate::services::{block_history, FtsService};
+use crate::utils::markdown::SanitizationRules;
+use crate::utils::page_sync::{render_page_file, sync_page_to_markdown, update_page_file_metadata};
+use crate::utils::sync_ignore::SyncIgnore;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
 /// Vacuum the database to reclaim unused space.
 /// This rebuilds the database file, repacking it into a minimal amount of disk space.
@@ -256,6 +270,11 @@ pub fn repair_db(workspace_path: String) -> Result<String, String> {
         .map_err(|e| e.to_string())?;
     }
 
+    if !report.is_empty() {
+        // Snapshots may reference the blocks removed or re-parented above
+        block_history::clear_history(&tx)?;
+    }
+
     tx.commit()
         .map_err(|e| format!("Failed to commit transaction: {}", e))?;
 
@@ -269,6 +288,165 @@ pub fn repair_db(workspace_path: String) -> Result<String, String> {
     Ok(report)
 }
 
+/// Number of sample IDs kept per integrity category
+const INTEGRITY_SAMPLE_LIMIT: usize = 10;
+
+/// One category of problems found by `validate_workspace_integrity`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub count: usize,
+    /// Up to `INTEGRITY_SAMPLE_LIMIT` IDs (or relative paths for files) for inspection
+    pub sample_ids: Vec<String>,
+}
+
+impl IntegrityIssue {
+    fn from_ids(ids: Vec<String>) -> Self {
+        Self {
+            count: ids.len(),
+            sample_ids: ids.into_iter().take(INTEGRITY_SAMPLE_LIMIT).collect(),
+        }
+    }
+}
+
+/// Read-only report of the problems `repair_db` and sync would act on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// Blocks whose page or parent block does not exist
+    pub orphan_blocks: IntegrityIssue,
+    /// Blocks whose parent block belongs to a different page
+    pub cross_page_parents: IntegrityIssue,
+    /// Wiki links whose source page/block or resolved target page does not exist
+    pub dangling_wiki_links: IntegrityIssue,
+    /// Live pages whose markdown file is missing on disk
+    pub pages_missing_files: IntegrityIssue,
+    /// Markdown files sync would visit that have no page row (workspace-relative paths)
+    pub files_without_page: IntegrityIssue,
+}
+
+/// Report integrity problems without changing anything, so they can be inspected
+/// before running `repair_db` or a full sync
+#[tauri::command]
+pub fn validate_workspace_integrity(workspace_path: String) -> Result<IntegrityReport, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    check_workspace_integrity(&conn, Path::new(&workspace_path))
+}
+
+fn check_workspace_integrity(
+    conn: &Connection,
+    workspace_root: &Path,
+) -> Result<IntegrityReport, String> {
+    let orphan_blocks = query_ids(
+        conn,
+        "SELECT id FROM blocks
+         WHERE page_id NOT IN (SELECT id FROM pages)
+            OR (parent_id IS NOT NULL AND parent_id NOT IN (SELECT id FROM blocks))
+         ORDER BY id",
+    )?;
+    let cross_page_parents = query_ids(
+        conn,
+        "SELECT b.id FROM blocks b
+         JOIN blocks parent ON parent.id = b.parent_id
+         WHERE parent.page_id != b.page_id
+         ORDER BY b.id",
+    )?;
+    let dangling_wiki_links = query_ids(
+        conn,
+        "SELECT id FROM wiki_links
+         WHERE from_page_id NOT IN (SELECT id FROM pages)
+            OR from_block_id NOT IN (SELECT id FROM blocks)
+            OR (to_page_id IS NOT NULL AND to_page_id NOT IN (SELECT id FROM pages))
+         ORDER BY id",
+    )?;
+
+    let pages: Vec<(String, String)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, file_path FROM pages
+                 WHERE file_path IS NOT NULL AND is_deleted = 0
+                 ORDER BY file_path",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+    let pages_missing_files = pages
+        .iter()
+        .filter(|(_, file_path)| !workspace_root.join(file_path).exists())
+        .map(|(page_id, _)| page_id.clone())
+        .collect();
+
+    // Soft-deleted pages still own their path, so their files are not "without page"
+    let known_paths: HashSet<String> = query_ids(
+        conn,
+        "SELECT file_path FROM pages WHERE file_path IS NOT NULL",
+    )?
+    .into_iter()
+    .collect();
+    let ignore = SyncIgnore::load(workspace_root);
+    let mut files = Vec::new();
+    collect_syncable_markdown(workspace_root, workspace_root, &ignore, &mut files)?;
+    files.sort();
+    let files_without_page = files
+        .into_iter()
+        .filter(|rel_path| !known_paths.contains(rel_path))
+        .collect();
+
+    Ok(IntegrityReport {
+        orphan_blocks: IntegrityIssue::from_ids(orphan_blocks),
+        cross_page_parents: IntegrityIssue::from_ids(cross_page_parents),
+        dangling_wiki_links: IntegrityIssue::from_ids(dangling_wiki_links),
+        pages_missing_files: IntegrityIssue::from_ids(pages_missing_files),
+        files_without_page: IntegrityIssue::from_ids(files_without_page),
+    })
+}
+
+fn query_ids(conn: &Connection, sql: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Collect the workspace-relative paths of every markdown file sync would visit
+fn collect_syncable_markdown(
+    workspace_root: &Path,
+    dir: &Path,
+    ignore: &SyncIgnore,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Error reading directory {}: {}", dir.display(), e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() {
+            let skipped = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(is_ignored_sync_entry)
+                .unwrap_or(false);
+            if !skipped {
+                collect_syncable_markdown(workspace_root, &path, ignore, files)?;
+            }
+        } else if let Some(rel_path) = syncable_markdown_path(workspace_root, &path, ignore) {
+            files.push(rel_path);
+        }
+    }
+    Ok(())
+}
+
 /// FTS5 index statistics
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FtsIndexStats {
@@ -353,6 +531,184 @@ pub fn optimize_fts_index(workspace_path: String) -> Result<String, String> {
     Ok("FTS5 index optimized successfully.".to_string())
 }
 
+/// How often the auto-optimize scheduler wakes up to check whether a run is due
+const AUTO_OPTIMIZE_TICK: Duration = Duration::from_secs(30);
+
+/// A due optimization waits until no write has been reported for this long
+const AUTO_OPTIMIZE_IDLE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+struct OptimizeSchedule {
+    interval: Duration,
+    idle: Duration,
+    tick: Duration,
+}
+
+struct AutoOptimizer {
+    stop: AtomicBool,
+    /// Last time a mutation was reported for the workspace
+    last_write: Mutex<Option<Instant>>,
+}
+
+static AUTO_OPTIMIZERS: OnceLock<Mutex<HashMap<String, Arc<AutoOptimizer>>>> = OnceLock::new();
+
+fn auto_optimizers() -> &'static Mutex<HashMap<String, Arc<AutoOptimizer>>> {
+    AUTO_OPTIMIZERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Periodically run `ANALYZE` and FTS5 `optimize` for a workspace.
+///
+/// A run becomes due every `interval_hours` and starts once the workspace has been
+/// idle for a while; it is skipped (and retried) if another connection is mid-write.
+/// Each successful run is recorded as `last_optimized_at` in workspace settings.
+/// Enabling again replaces the previous schedule.
+#[tauri::command]
+pub fn enable_auto_optimize(workspace_path: String, interval_hours: u64) -> Result<(), String> {
+    if interval_hours == 0 {
+        return Err("interval_hours must be at least 1".to_string());
+    }
+    // Fail early on an unusable workspace rather than in the background thread
+    open_workspace_db(&workspace_path)?;
+
+    start_auto_optimize(
+        workspace_path,
+        OptimizeSchedule {
+            interval: Duration::from_secs(interval_hours * 3600),
+            idle: AUTO_OPTIMIZE_IDLE,
+            tick: AUTO_OPTIMIZE_TICK,
+        },
+    )
+}
+
+/// Stop periodic optimization for a workspace. Returns false if it was not enabled.
+#[tauri::command]
+pub fn disable_auto_optimize(workspace_path: String) -> Result<bool, String> {
+    let mut optimizers = auto_optimizers().lock().map_err(|e| e.to_string())?;
+    match optimizers.remove(&workspace_path) {
+        Some(optimizer) => {
+            optimizer.stop.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Stop every auto-optimize schedule (used when the workspace is closed)
+pub fn stop_all_auto_optimize() {
+    if let Ok(mut optimizers) = auto_optimizers().lock() {
+        for (_, optimizer) in optimizers.drain() {
+            optimizer.stop.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Report a mutation so a pending optimization waits for the workspace to go idle
+pub fn note_workspace_write(workspace_path: &str) {
+    let Ok(optimizers) = auto_optimizers().lock() else {
+        return;
+    };
+    if let Some(optimizer) = optimizers.get(workspace_path) {
+        if let Ok(mut last_write) = optimizer.last_write.lock() {
+            *last_write = Some(Instant::now());
+        }
+    }
+}
+
+fn start_auto_optimize(workspace_path: String, schedule: OptimizeSchedule) -> Result<(), String> {
+    let optimizer = Arc::new(AutoOptimizer {
+        stop: AtomicBool::new(false),
+        last_write: Mutex::new(None),
+    });
+
+    {
+        let mut optimizers = auto_optimizers().lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = optimizers.insert(workspace_path.clone(), optimizer.clone()) {
+            previous.stop.store(true, Ordering::SeqCst);
+        }
+    }
+
+    std::thread::spawn(move || run_auto_optimize_loop(&workspace_path, &optimizer, schedule));
+    Ok(())
+}
+
+fn run_auto_optimize_loop(
+    workspace_path: &str,
+    optimizer: &AutoOptimizer,
+    schedule: OptimizeSchedule,
+) {
+    let mut last_run = Instant::now();
+
+    loop {
+        std::thread::sleep(schedule.tick);
+        if optimizer.stop.load(Ordering::SeqCst) {
+            return;
+        }
+        if last_run.elapsed() < schedule.interval {
+            continue;
+        }
+        let recently_written = optimizer
+            .last_write
+            .lock()
+            .map(|last_write| last_write.is_some_and(|at| at.elapsed() < schedule.idle))
+            .unwrap_or(false);
+        if recently_written {
+            continue;
+        }
+
+        let result = open_workspace_db(workspace_path).and_then(|conn| optimize_if_idle(&conn));
+        match result {
+            Ok(true) => {
+                last_run = Instant::now();
+                if let Err(e) = record_last_optimized(workspace_path) {
+                    eprintln!("[auto_optimize] Failed to record optimization time: {}", e);
+                }
+            }
+            // A write is in progress; try again on the next tick
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!(
+                    "[auto_optimize] Optimization failed for {}: {}",
+                    workspace_path, e
+                );
+                last_run = Instant::now();
+            }
+        }
+    }
+}
+
+/// Run `ANALYZE` and FTS5 `optimize` in one write transaction.
+/// Returns false without doing anything if another connection holds the write lock.
+fn optimize_if_idle(conn: &Connection) -> Result<bool, String> {
+    conn.busy_timeout(Duration::ZERO)
+        .map_err(|e| e.to_string())?;
+
+    match conn.execute_batch("BEGIN IMMEDIATE") {
+        Ok(()) => {}
+        Err(rusqlite::Error::SqliteFailure(err, _))
+            if err.code == rusqlite::ErrorCode::DatabaseBusy =>
+        {
+            return Ok(false);
+        }
+        Err(e) => return Err(e.to_string()),
+    }
+
+    let result = conn.execute_batch(
+        "ANALYZE;
+         INSERT INTO blocks_fts(blocks_fts, rank) VALUES('optimize', 0);",
+    );
+    match result {
+        Ok(()) => conn
+            .execute_batch("COMMIT")
+            .map_err(|e| format!("Failed to commit optimization: {}", e))?,
+        Err(e) => {
+            let _ = conn.execute_batch("ROLLBACK");
+            return Err(format!("Failed to optimize database: {}", e));
+        }
+    }
+
+    Ok(true)
+}
+
 /// Rebuild FTS5 index for a specific page
 /// Useful when a page has many block updates
 #[tauri::command]
@@ -366,3 +722,1212 @@ pub fn rebuild_page_fts_index(workspace_path: String, page_id: String) -> Result
         count
     ))
 }
+
+/// A metadata value that looks like JSON but does not parse
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MalformedMetadata {
+    pub metadata_id: String,
+    pub block_id: String,
+    pub page_id: String,
+    pub page_path: Option<String>,
+    pub key: String,
+    pub value: String,
+    pub error: String,
+}
+
+/// How `fix_metadata_json` should treat malformed values
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum MetadataJsonFix {
+    /// Store the raw text as a JSON string literal so typed consumers can parse it
+    #[serde(rename = "quote")]
+    Quote,
+    /// Leave the value untouched and list the offending keys in a `jsonError` metadata entry
+    #[serde(rename = "flag")]
+    Flag,
+}
+
+/// Metadata key used to flag blocks carrying malformed JSON values
+const JSON_ERROR_METADATA_KEY: &str = "jsonError";
+
+/// Scan block metadata for values that look like JSON objects/arrays but fail to parse
+#[tauri::command]
+pub fn validate_metadata_json(workspace_path: String) -> Result<Vec<MalformedMetadata>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_malformed_metadata(&conn)
+}
+
+/// Repair malformed JSON metadata values and resync the affected pages.
+/// Returns the number of metadata values that were fixed or flagged.
+#[tauri::command]
+pub async fn fix_metadata_json(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    strategy: MetadataJsonFix,
+) -> Result<usize, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let malformed = find_malformed_metadata(&conn)?;
+    if malformed.is_empty() {
+        return Ok(0);
+    }
+
+    apply_metadata_json_fix(&mut conn, &malformed, strategy)?;
+
+    // Metadata is mirrored in markdown, so rewrite every touched page
+    let mut page_ids: Vec<&str> = malformed.iter().map(|m| m.page_id.as_str()).collect();
+    page_ids.sort();
+    page_ids.dedup();
+
+    let conn_mutex = Mutex::new(conn);
+    for page_id in page_ids {
+        sync_page_to_markdown(&conn_mutex, &workspace_path, page_id).await?;
+    }
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(malformed.len())
+}
+
+fn find_malformed_metadata(conn: &Connection) -> Result<Vec<MalformedMetadata>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.block_id, b.page_id, p.file_path, m.key, m.value
+             FROM block_metadata m
+             JOIN blocks b ON b.id = m.block_id
+             JOIN pages p ON p.id = b.page_id
+             WHERE p.is_deleted = 0
+               AND (ltrim(m.value) LIKE '{%' OR ltrim(m.value) LIKE '[%')
+             ORDER BY p.file_path, b.order_key, m.key",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(MalformedMetadata {
+                metadata_id: row.get(0)?,
+                block_id: row.get(1)?,
+                page_id: row.get(2)?,
+                page_path: row.get(3)?,
+                key: row.get(4)?,
+                value: row.get(5)?,
+                error: String::new(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut malformed = Vec::new();
+    for row in rows {
+        let mut entry = row.map_err(|e| e.to_string())?;
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&entry.value) {
+            entry.error = e.to_string();
+            malformed.push(entry);
+        }
+    }
+
+    Ok(malformed)
+}
+
+fn apply_metadata_json_fix(
+    conn: &mut Connection,
+    malformed: &[MalformedMetadata],
+    strategy: MetadataJsonFix,
+) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    match strategy {
+        MetadataJsonFix::Quote => {
+            for entry in malformed {
+                let quoted = serde_json::to_string(&entry.value).map_err(|e| e.to_string())?;
+                tx.execute(
+                    "UPDATE block_metadata SET value = ? WHERE id = ?",
+                    params![quoted, &entry.metadata_id],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        MetadataJsonFix::Flag => {
+            let mut keys_by_block: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            for entry in malformed {
+                keys_by_block
+                    .entry(entry.block_id.as_str())
+                    .or_default()
+                    .push(entry.key.as_str());
+            }
+
+            for (block_id, keys) in keys_by_block {
+                tx.execute(
+                    "DELETE FROM block_metadata WHERE block_id = ? AND key = ?",
+                    params![block_id, JSON_ERROR_METADATA_KEY],
+                )
+                .map_err(|e| e.to_string())?;
+                tx.execute(
+                    "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, ?, ?)",
+                    params![
+                        Uuid::new_v4().to_string(),
+                        block_id,
+                        JSON_ERROR_METADATA_KEY,
+                        keys.join(",")
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    let page_ids: HashSet<&str> = malformed.iter().map(|m| m.page_id.as_str()).collect();
+    for page_id in page_ids {
+        block_history::clear_page_history(&tx, page_id)?;
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))
+}
+
+/// Size comparison between a page file on disk and its serialized DB blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageSizeDivergence {
+    pub page_id: String,
+    pub title: String,
+    pub file_path: String,
+    /// Size of the file on disk, or the last recorded size if the file is missing
+    pub file_size: Option<i64>,
+    /// Byte length the serializer would produce for the page's current DB blocks
+    pub serialized_size: i64,
+    /// `file_size - serialized_size`; zero means the sizes agree
+    pub divergence: i64,
+}
+
+/// Compare each page's on-disk size with what the serializer would write for it.
+/// A cheap drift signal: nonzero divergence means the file and DB disagree.
+/// Results are sorted by absolute divergence, largest first.
+#[tauri::command]
+pub fn get_page_size_divergence(workspace_path: String) -> Result<Vec<PageSizeDivergence>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    compute_page_size_divergence(&conn, Path::new(&workspace_path))
+}
+
+fn compute_page_size_divergence(
+    conn: &Connection,
+    workspace_root: &Path,
+) -> Result<Vec<PageSizeDivergence>, String> {
+    let pages: Vec<(String, String, String, Option<i64>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, title, file_path, file_size FROM pages
+                 WHERE file_path IS NOT NULL AND is_deleted = 0",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+
+    let workspace_path = workspace_root.to_string_lossy();
+    let mut results = Vec::with_capacity(pages.len());
+    for (page_id, title, file_path, recorded_size) in pages {
+        let full_path = workspace_root.join(&file_path);
+        let file_size = std::fs::metadata(&full_path)
+            .ok()
+            .map(|m| m.len() as i64)
+            .or(recorded_size);
+        // Render through the sync path, so frontmatter and the trailing newline match
+        let existing = std::fs::read_to_string(&full_path).ok();
+        let serialized_size =
+            render_page_file(conn, &workspace_path, &page_id, existing.as_deref())?.len() as i64;
+
+        results.push(PageSizeDivergence {
+            page_id,
+            title,
+            file_path,
+            file_size,
+            serialized_size,
+            divergence: file_size.unwrap_or(0) - serialized_size,
+        });
+    }
+
+    results.sort_by(|a, b| {
+        b.divergence
+            .abs()
+            .cmp(&a.divergence.abs())
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+
+    Ok(results)
+}
+
+/// A trailing empty root block found (or removed) by `trim_trailing_empty_blocks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrailingEmptyBlock {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_path: Option<String>,
+}
+
+/// Remove empty, childless root blocks from the end of every page.
+/// A page whose only content is empty blocks keeps its first one.
+/// With `dry_run`, nothing is changed and the blocks that would be removed are returned.
+#[tauri::command]
+pub async fn trim_trailing_empty_blocks(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    dry_run: bool,
+) -> Result<Vec<TrailingEmptyBlock>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let trailing = find_trailing_empty_blocks(&conn)?;
+    if dry_run || trailing.is_empty() {
+        return Ok(trailing);
+    }
+
+    delete_trailing_empty_blocks(&mut conn, &trailing)?;
+
+    let mut page_ids: Vec<&str> = trailing.iter().map(|t| t.page_id.as_str()).collect();
+    page_ids.sort();
+    page_ids.dedup();
+
+    let conn_mutex = Mutex::new(conn);
+    for page_id in page_ids {
+        sync_page_to_markdown(&conn_mutex, &workspace_path, page_id).await?;
+    }
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(trailing)
+}
+
+fn find_trailing_empty_blocks(conn: &Connection) -> Result<Vec<TrailingEmptyBlock>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.page_id, p.file_path, trim(b.content) = '',
+                    EXISTS (SELECT 1 FROM blocks c WHERE c.parent_id = b.id)
+             FROM blocks b
+             JOIN pages p ON p.id = b.page_id
+             WHERE b.parent_id IS NULL AND p.is_deleted = 0
+             ORDER BY b.page_id, b.order_key DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                TrailingEmptyBlock {
+                    block_id: row.get(0)?,
+                    page_id: row.get(1)?,
+                    page_path: row.get(2)?,
+                },
+                row.get::<_, bool>(3)? && !row.get::<_, bool>(4)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Rows are grouped by page, last root block first
+    let mut trailing = Vec::new();
+    let mut current_page: Option<String> = None;
+    let mut run: Vec<TrailingEmptyBlock> = Vec::new();
+    let mut run_open = false;
+    let mut page_has_content = false;
+
+    let mut flush = |run: &mut Vec<TrailingEmptyBlock>, page_has_content: bool| {
+        if !page_has_content {
+            // Only empty blocks: keep the first one (the last in this reversed run)
+            run.pop();
+        }
+        trailing.append(run);
+    };
+
+    for (block, is_empty) in rows {
+        if current_page.as_deref() != Some(block.page_id.as_str()) {
+            if current_page.is_some() {
+                flush(&mut run, page_has_content);
+            }
+            current_page = Some(block.page_id.clone());
+            run_open = true;
+            page_has_content = false;
+        }
+
+        if run_open && is_empty {
+            run.push(block);
+        } else {
+            run_open = false;
+            page_has_content = true;
+        }
+    }
+    if current_page.is_some() {
+        flush(&mut run, page_has_content);
+    }
+
+    Ok(trailing)
+}
+
+fn delete_trailing_empty_blocks(
+    conn: &mut Connection,
+    trailing: &[TrailingEmptyBlock],
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for entry in trailing {
+        tx.execute("DELETE FROM blocks WHERE id = ?", [&entry.block_id])
+            .map_err(|e| e.to_string())?;
+        deindex_block_fts(&tx, &entry.block_id)?;
+        block_history::clear_page_history(&tx, &entry.page_id)?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Page rows sharing one file path, as left behind by concurrent syncs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicatePageGroup {
+    pub file_path: String,
+    /// The oldest row, which survives the merge
+    pub kept_page_id: String,
+    pub removed_page_ids: Vec<String>,
+}
+
+/// Merge page rows that share a file path into the oldest one.
+/// Child pages and backlinks are moved to the kept page, the other rows are deleted,
+/// the kept page is re-imported from its file, and the unique path index is installed.
+/// With `dry_run`, nothing is changed and the duplicate groups are returned.
+#[tauri::command]
+pub async fn dedupe_pages_by_path(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    dry_run: bool,
+) -> Result<Vec<DuplicatePageGroup>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let groups = find_duplicate_pages(&conn)?;
+    if dry_run || groups.is_empty() {
+        return Ok(groups);
+    }
+
+    merge_duplicate_pages(&mut conn, &groups)?;
+
+    // A removed row may have held blocks the kept one lacked; the file is authoritative
+    let indent = load_indent_style(&workspace_path);
+    let conn_mutex = Mutex::new(conn);
+    for group in &groups {
+        let full_path = Path::new(&workspace_path).join(&group.file_path);
+        if !full_path.is_file() {
+            continue;
+        }
+        let content = tokio::fs::read_to_string(&full_path)
+            .await
+            .map_err(|e| format!("Failed to read page file: {}", e))?;
+        {
+            let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+            // Re-reads the kept file as it is, so no sanitization
+            import_page_blocks_from_markdown(
+                &mut conn,
+                &group.kept_page_id,
+                &content,
+                indent,
+                &SanitizationRules::default(),
+            )?;
+        }
+        update_page_file_metadata(&conn_mutex, &full_path, &group.kept_page_id).await?;
+    }
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(groups)
+}
+
+fn find_duplicate_pages(conn: &Connection) -> Result<Vec<DuplicatePageGroup>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_path FROM pages
+             WHERE is_deleted = 0 AND file_path IN (
+                 SELECT file_path FROM pages
+                 WHERE is_deleted = 0 AND file_path IS NOT NULL
+                 GROUP BY file_path HAVING COUNT(*) > 1
+             )
+             ORDER BY file_path, created_at, rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut groups: Vec<DuplicatePageGroup> = Vec::new();
+    for (page_id, file_path) in rows {
+        match groups.last_mut() {
+            Some(group) if group.file_path == file_path => group.removed_page_ids.push(page_id),
+            _ => groups.push(DuplicatePageGroup {
+                file_path,
+                kept_page_id: page_id,
+                removed_page_ids: Vec::new(),
+            }),
+        }
+    }
+
+    Ok(groups)
+}
+
+fn merge_duplicate_pages(
+    conn: &mut Connection,
+    groups: &[DuplicatePageGroup],
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for group in groups {
+        for removed in &group.removed_page_ids {
+            tx.execute(
+                "UPDATE pages SET parent_id = ? WHERE parent_id = ?",
+                params![group.kept_page_id, removed],
+            )
+            .map_err(|e| e.to_string())?;
+            tx.execute(
+                "UPDATE wiki_links SET to_page_id = ? WHERE to_page_id = ?",
+                params![group.kept_page_id, removed],
+            )
+            .map_err(|e| e.to_string())?;
+
+            let block_ids: Vec<String> = tx
+                .prepare("SELECT id FROM blocks WHERE page_id = ?")
+                .and_then(|mut stmt| {
+                    stmt.query_map([removed], |row| row.get(0))?
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(|e| e.to_string())?;
+            for block_id in &block_ids {
+                deindex_block_fts(&tx, block_id)?;
+            }
+
+            tx.execute("DELETE FROM pages WHERE id = ?", [removed])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    crate::db::schema::ensure_unique_page_paths(conn).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// A page whose file is missing on disk while its blocks remain in the DB
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhantomBlockPage {
+    pub page_id: String,
+    pub title: String,
+    pub file_path: String,
+    pub block_ids: Vec<String>,
+}
+
+/// How `fix_phantom_blocks` should resolve a missing page file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum PhantomBlockFix {
+    /// Write the file again from the page's DB blocks
+    #[serde(rename = "recreate")]
+    Recreate,
+    /// Delete the orphaned blocks, leaving the page empty
+    #[serde(rename = "remove")]
+    Remove,
+}
+
+/// Find blocks whose page file no longer exists (e.g. after a failed delete or a crash).
+/// Pages with an existing but empty file are not reported.
+#[tauri::command]
+pub fn find_phantom_blocks(workspace_path: String) -> Result<Vec<PhantomBlockPage>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_phantom_block_pages(&conn, Path::new(&workspace_path))
+}
+
+/// Recreate or remove the files/blocks reported by `find_phantom_blocks`.
+/// Returns the pages that were fixed.
+#[tauri::command]
+pub async fn fix_phantom_blocks(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    strategy: PhantomBlockFix,
+) -> Result<Vec<PhantomBlockPage>, String> {
+    let mut conn = open_workspace_db(&workspace_path)?;
+    let phantoms = find_phantom_block_pages(&conn, Path::new(&workspace_path))?;
+    if phantoms.is_empty() {
+        return Ok(phantoms);
+    }
+
+    match strategy {
+        PhantomBlockFix::Recreate => {
+            let conn_mutex = Mutex::new(conn);
+            recreate_phantom_files(&conn_mutex, &workspace_path, &phantoms).await?;
+        }
+        PhantomBlockFix::Remove => remove_phantom_blocks(&mut conn, &phantoms)?,
+    }
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(phantoms)
+}
+
+fn find_phantom_block_pages(
+    conn: &Connection,
+    workspace_root: &Path,
+) -> Result<Vec<PhantomBlockPage>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, p.file_path, b.id
+             FROM pages p
+             JOIN blocks b ON b.page_id = p.id
+             WHERE p.file_path IS NOT NULL AND p.is_deleted = 0 AND p.is_directory = 0
+             ORDER BY p.file_path, b.order_key",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut phantoms: Vec<PhantomBlockPage> = Vec::new();
+    let mut present: Option<String> = None;
+    for (page_id, title, file_path, block_id) in rows {
+        if present.as_deref() == Some(page_id.as_str()) {
+            continue;
+        }
+        match phantoms.last_mut() {
+            Some(page) if page.page_id == page_id => page.block_ids.push(block_id),
+            _ => {
+                if workspace_root.join(&file_path).exists() {
+                    present = Some(page_id);
+                    continue;
+                }
+                phantoms.push(PhantomBlockPage {
+                    page_id,
+                    title,
+                    file_path,
+                    block_ids: vec![block_id],
+                });
+            }
+        }
+    }
+
+    Ok(phantoms)
+}
+
+async fn recreate_phantom_files(
+    conn_mutex: &Mutex<Connection>,
+    workspace_path: &str,
+    phantoms: &[PhantomBlockPage],
+) -> Result<(), String> {
+    for page in phantoms {
+        sync_page_to_markdown(conn_mutex, workspace_path, &page.page_id).await?;
+    }
+    Ok(())
+}
+
+fn remove_phantom_blocks(
+    conn: &mut Connection,
+    phantoms: &[PhantomBlockPage],
+) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for page in phantoms {
+        for block_id in &page.block_ids {
+            deindex_block_fts(&tx, block_id)?;
+        }
+        tx.execute("DELETE FROM blocks WHERE page_id = ?", [&page.page_id])
+            .map_err(|e| e.to_string())?;
+        block_history::clear_page_history(&tx, &page.page_id)?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Rows returned by `run_readonly_query` at most; the rest are dropped
+const MAX_READONLY_QUERY_ROWS: usize = 10_000;
+
+/// Result of an ad-hoc read-only query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadonlyQueryResult {
+    pub columns: Vec<String>,
+    /// One JSON value per column; blobs are arrays of bytes
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// More than `MAX_READONLY_QUERY_ROWS` rows matched
+    pub truncated: bool,
+}
+
+/// Run a single user-supplied `SELECT` (or `WITH ... SELECT`) against the workspace DB.
+/// Anything that could write, or more than one statement, is rejected before it runs,
+/// and the connection is switched to `query_only` as a second guard.
+#[tauri::command]
+pub fn run_readonly_query(
+    workspace_path: String,
+    sql: String,
+) -> Result<ReadonlyQueryResult, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    execute_readonly_query(&conn, &sql)
+}
+
+fn execute_readonly_query(conn: &Connection, sql: &str) -> Result<ReadonlyQueryResult, String> {
+    let keyword = leading_sql_keyword(sql).to_ascii_uppercase();
+    if keyword != "SELECT" && keyword != "WITH" {
+        return Err("Only SELECT queries are allowed".to_string());
+    }
+
+    let mut batch = rusqlite::Batch::new(conn, sql);
+    let mut stmt = batch
+        .next()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Query is empty".to_string())?;
+    if batch.next().map_err(|e| e.to_string())?.is_some() {
+        return Err("Only a single statement is allowed".to_string());
+    }
+    if !stmt.readonly() {
+        return Err("Only read-only queries are allowed".to_string());
+    }
+
+    conn.execute_batch("PRAGMA query_only = ON; BEGIN DEFERRED")
+        .map_err(|e| e.to_string())?;
+    let result = collect_query_rows(&mut stmt);
+    drop(stmt);
+    conn.execute_batch("ROLLBACK; PRAGMA query_only = OFF")
+        .map_err(|e| e.to_string())?;
+
+    result
+}
+
+fn collect_query_rows(stmt: &mut rusqlite::Statement) -> Result<ReadonlyQueryResult, String> {
+    use rusqlite::types::ValueRef;
+
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = Vec::new();
+    let mut truncated = false;
+
+    let mut cursor = stmt.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = cursor.next().map_err(|e| e.to_string())? {
+        if rows.len() == MAX_READONLY_QUERY_ROWS {
+            truncated = true;
+            break;
+        }
+        let mut values = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            let value = match row.get_ref(i).map_err(|e| e.to_string())? {
+                ValueRef::Null => serde_json::Value::Null,
+                ValueRef::Integer(n) => serde_json::Value::from(n),
+                ValueRef::Real(f) => serde_json::Number::from_f64(f)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null),
+                ValueRef::Text(t) => serde_json::Value::from(String::from_utf8_lossy(t)),
+                ValueRef::Blob(b) => serde_json::Value::from(b.to_vec()),
+            };
+            values.push(value);
+        }
+        rows.push(values);
+    }
+
+    Ok(ReadonlyQueryResult {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+/// First SQL keyword, skipping whitespace and comments
+fn leading_sql_keyword(sql: &str) -> &str {
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_start();
+        if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment.split_once('\n').map(|(_, r)| r).unwrap_or("");
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map(|(_, r)| r).unwrap_or("");
+        } else {
+            break;
+        }
+    }
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    &rest[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::markdown::IndentStyle;
+    use crate::utils::page_sync::render_page_markdown;
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('page1', 'Cast', 'Cast.md')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('block1', 'page1', 'Movie', 1.0)",
+            [],
+        )
+        .unwrap();
+        for (id, key, value) in [
+            ("m1", "cast", r#"{"a":"#),
+            ("m2", "crew", r#"{"director":"x"}"#),
+            ("m3", "note", "plain text"),
+        ] {
+            conn.execute(
+                "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, 'block1', ?, ?)",
+                params![id, key, value],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn test_find_malformed_metadata() {
+        let conn = create_test_db();
+
+        let malformed = find_malformed_metadata(&conn).unwrap();
+
+        assert_eq!(malformed.len(), 1);
+        assert_eq!(malformed[0].block_id, "block1");
+        assert_eq!(malformed[0].key, "cast");
+        assert_eq!(malformed[0].value, r#"{"a":"#);
+        assert_eq!(malformed[0].page_path.as_deref(), Some("Cast.md"));
+        assert!(!malformed[0].error.is_empty());
+    }
+
+    #[test]
+    fn test_fix_metadata_json_strategies() {
+        let mut conn = create_test_db();
+        let malformed = find_malformed_metadata(&conn).unwrap();
+        apply_metadata_json_fix(&mut conn, &malformed, MetadataJsonFix::Flag).unwrap();
+
+        let flag: String = conn
+            .query_row(
+                "SELECT value FROM block_metadata WHERE block_id = 'block1' AND key = 'jsonError'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(flag, "cast");
+
+        apply_metadata_json_fix(&mut conn, &malformed, MetadataJsonFix::Quote).unwrap();
+        let quoted: String = conn
+            .query_row(
+                "SELECT value FROM block_metadata WHERE id = 'm1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(quoted, r#""{\"a\":""#);
+        assert!(find_malformed_metadata(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_page_size_divergence() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_size_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('page2', 'Plain', 'Plain.md')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('block2', 'page2', 'Hello', 1.0)",
+            [],
+        )
+        .unwrap();
+
+        // Plain.md matches the DB exactly; Cast.md was edited externally
+        std::fs::write(
+            temp_dir.join("Plain.md"),
+            render_page_markdown(&conn, "page2", IndentStyle::default()).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(temp_dir.join("Cast.md"), "- Movie\n").unwrap();
+
+        let results = compute_page_size_divergence(&conn, &temp_dir).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let cast = results.iter().find(|r| r.page_id == "page1").unwrap();
+        assert_ne!(cast.divergence, 0);
+        assert_eq!(results[0].page_id, "page1");
+
+        let plain = results.iter().find(|r| r.page_id == "page2").unwrap();
+        assert_eq!(plain.divergence, 0);
+        assert_eq!(plain.file_size, Some(plain.serialized_size));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_page_size_divergence_counts_frontmatter() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_size_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let conn = create_test_db();
+        std::fs::write(
+            temp_dir.join("Cast.md"),
+            "---\ntags: film\n---\n- Outdated\n  ID::block1\n",
+        )
+        .unwrap();
+
+        // A full rewrite keeps the frontmatter and applies the trailing-newline policy
+        let conn_mutex = Mutex::new(conn);
+        tauri::async_runtime::block_on(sync_page_to_markdown(&conn_mutex, &path_str, "page1"))
+            .unwrap();
+        let written = std::fs::read_to_string(temp_dir.join("Cast.md")).unwrap();
+        assert!(written.starts_with("---\ntags: film\n---\n"));
+
+        let conn = conn_mutex.into_inner().unwrap();
+        let results = compute_page_size_divergence(&conn, &temp_dir).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file_size, Some(written.len() as i64));
+        assert_eq!(results[0].divergence, 0);
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_trim_trailing_empty_blocks() {
+        let mut conn = create_test_db();
+        conn.execute_batch(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('empty1', 'page1', '', 2.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('empty2', 'page1', '  ', 3.0);
+             INSERT INTO pages (id, title, file_path) VALUES ('page2', 'Blank', 'Blank.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('only', 'page2', '', 1.0);",
+        )
+        .unwrap();
+
+        let trailing = find_trailing_empty_blocks(&conn).unwrap();
+        let mut ids: Vec<&str> = trailing.iter().map(|t| t.block_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["empty1", "empty2"]);
+
+        delete_trailing_empty_blocks(&mut conn, &trailing).unwrap();
+
+        let remaining: Vec<String> = conn
+            .prepare("SELECT id FROM blocks ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(remaining, vec!["block1", "only"]);
+        assert!(find_trailing_empty_blocks(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_optimize_skips_during_active_write() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_optimize_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let writer = open_workspace_db(&path_str).unwrap();
+        let optimizer = open_workspace_db(&path_str).unwrap();
+
+        writer.execute_batch("BEGIN IMMEDIATE").unwrap();
+        writer
+            .execute(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'P', 'P.md')",
+                [],
+            )
+            .unwrap();
+        assert!(!optimize_if_idle(&optimizer).unwrap());
+
+        writer.execute_batch("COMMIT").unwrap();
+        assert!(optimize_if_idle(&optimizer).unwrap());
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_auto_optimize_runs_on_schedule() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_auto_optimize_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+        open_workspace_db(&path_str).unwrap();
+
+        start_auto_optimize(
+            path_str.clone(),
+            OptimizeSchedule {
+                interval: Duration::from_millis(50),
+                idle: Duration::from_millis(10),
+                tick: Duration::from_millis(10),
+            },
+        )
+        .unwrap();
+
+        let settings_path = temp_dir.join(".oxinot").join("settings.json");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut last_optimized = None;
+        while Instant::now() < deadline && last_optimized.is_none() {
+            std::thread::sleep(Duration::from_millis(20));
+            last_optimized = std::fs::read_to_string(&settings_path)
+                .ok()
+                .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+                .and_then(|v| v["last_optimized_at"].as_str().map(str::to_string));
+        }
+
+        assert!(disable_auto_optimize(path_str).unwrap());
+        assert!(last_optimized.is_some());
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_dedupe_pages_by_path_merges_references() {
+        let mut conn = create_test_db();
+        // Simulate a database written before the unique path index existed
+        conn.execute_batch(
+            "DROP INDEX idx_pages_file_path_unique;
+             INSERT INTO pages (id, title, file_path, created_at) VALUES ('dup-old', 'Notes', 'Notes.md', '2024-01-01');
+             INSERT INTO pages (id, title, file_path, created_at) VALUES ('dup-new', 'Notes', 'Notes.md', '2024-01-02');
+             INSERT INTO pages (id, title, parent_id, file_path) VALUES ('child', 'Child', 'dup-new', 'Notes/Child.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('old-block', 'dup-old', 'Kept', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('new-block', 'dup-new', 'Dropped', 1.0);
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+                 VALUES ('l1', 'page1', 'block1', 'dup-new', 'page_link', 'notes', 'Notes');",
+        )
+        .unwrap();
+
+        let groups = find_duplicate_pages(&conn).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].file_path, "Notes.md");
+        assert_eq!(groups[0].kept_page_id, "dup-old");
+        assert_eq!(groups[0].removed_page_ids, vec!["dup-new"]);
+
+        merge_duplicate_pages(&mut conn, &groups).unwrap();
+
+        let notes: Vec<String> = conn
+            .prepare("SELECT id FROM pages WHERE file_path = 'Notes.md'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(notes, vec!["dup-old"]);
+
+        let child_parent: String = conn
+            .query_row(
+                "SELECT parent_id FROM pages WHERE id = 'child'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(child_parent, "dup-old");
+        let link_target: String = conn
+            .query_row(
+                "SELECT to_page_id FROM wiki_links WHERE id = 'l1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(link_target, "dup-old");
+        let dropped: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM blocks WHERE id = 'new-block'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(dropped, 0);
+
+        // The unique index is back in place
+        assert!(find_duplicate_pages(&conn).unwrap().is_empty());
+        assert!(conn
+            .execute(
+                "INSERT INTO pages (id, title, file_path) VALUES ('again', 'Notes', 'Notes.md')",
+                [],
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_phantom_blocks_reported_and_recreated() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_phantom_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let mut conn = create_test_db();
+        conn.execute_batch(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('block2', 'page1', 'Sequel', 2.0);
+             INSERT INTO pages (id, title, file_path) VALUES ('page2', 'Empty', 'Empty.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('blank', 'page2', '', 1.0);",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.join("Cast.md"),
+            render_page_markdown(&conn, "page1", IndentStyle::default()).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(temp_dir.join("Empty.md"), "").unwrap();
+        assert!(find_phantom_block_pages(&conn, &temp_dir)
+            .unwrap()
+            .is_empty());
+
+        // Deleted out of band
+        std::fs::remove_file(temp_dir.join("Cast.md")).unwrap();
+
+        let phantoms = find_phantom_block_pages(&conn, &temp_dir).unwrap();
+        assert_eq!(phantoms.len(), 1);
+        assert_eq!(phantoms[0].page_id, "page1");
+        assert_eq!(phantoms[0].file_path, "Cast.md");
+        assert_eq!(phantoms[0].block_ids, vec!["block1", "block2"]);
+
+        let conn_mutex = Mutex::new(conn);
+        tauri::async_runtime::block_on(recreate_phantom_files(&conn_mutex, &path_str, &phantoms))
+            .unwrap();
+        let restored = std::fs::read_to_string(temp_dir.join("Cast.md")).unwrap();
+        assert!(restored.contains("- Movie"));
+        assert!(restored.contains("- Sequel"));
+
+        conn = conn_mutex.into_inner().unwrap();
+        assert!(find_phantom_block_pages(&conn, &temp_dir)
+            .unwrap()
+            .is_empty());
+
+        // The remove option drops the blocks instead
+        std::fs::remove_file(temp_dir.join("Cast.md")).unwrap();
+        let phantoms = find_phantom_block_pages(&conn, &temp_dir).unwrap();
+        remove_phantom_blocks(&mut conn, &phantoms).unwrap();
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM blocks WHERE page_id = 'page1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 0);
+        assert!(find_phantom_block_pages(&conn, &temp_dir)
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_readonly_query_returns_rows() {
+        let conn = create_test_db();
+
+        let result = execute_readonly_query(
+            &conn,
+            "-- blocks with their page\n
+             SELECT b.id, p.title, b.order_weight, NULL AS missing
+             FROM blocks b JOIN pages p ON p.id = b.page_id;",
+        )
+        .unwrap();
+        assert_eq!(
+            result.columns,
+            vec!["id", "title", "order_weight", "missing"]
+        );
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(
+            result.rows[0],
+            vec![
+                serde_json::json!("block1"),
+                serde_json::json!("Cast"),
+                serde_json::json!(1.0),
+                serde_json::Value::Null,
+            ]
+        );
+        assert!(!result.truncated);
+
+        let counted = execute_readonly_query(
+            &conn,
+            "WITH keys AS (SELECT key FROM block_metadata) SELECT COUNT(*) FROM keys",
+        )
+        .unwrap();
+        assert_eq!(counted.rows[0][0], serde_json::json!(3));
+    }
+
+    #[test]
+    fn test_readonly_query_rejects_writes() {
+        let conn = create_test_db();
+
+        for sql in [
+            "INSERT INTO pages (id, title) VALUES ('x', 'X')",
+            "UPDATE blocks SET content = 'changed'",
+            "DROP TABLE blocks",
+            "/* sneaky */ DELETE FROM blocks",
+            "SELECT 1; DELETE FROM blocks",
+            "WITH x AS (SELECT 1) DELETE FROM blocks",
+            "PRAGMA query_only = OFF",
+            "",
+        ] {
+            assert!(execute_readonly_query(&conn, sql).is_err(), "{}", sql);
+        }
+
+        let content: String = conn
+            .query_row(
+                "SELECT content FROM blocks WHERE id = 'block1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(content, "Movie");
+        let pages: i64 = conn
+            .query_row("SELECT COUNT(*) FROM pages", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(pages, 1);
+
+        // Writes through the same connection still work afterwards
+        conn.execute("UPDATE blocks SET content = 'Film' WHERE id = 'block1'", [])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_check_workspace_integrity_reports_without_changes() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_integrity_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(temp_dir.join(".oxinot")).unwrap();
+        std::fs::write(temp_dir.join("Cast.md"), "- Movie\n").unwrap();
+        std::fs::write(temp_dir.join("Stray.md"), "- Stray\n").unwrap();
+        std::fs::write(temp_dir.join(".oxinot").join("Hidden.md"), "- x\n").unwrap();
+
+        let conn = create_test_db();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('page2', 'Gone', 'Gone.md');
+             INSERT INTO blocks (id, page_id, content, order_weight)
+                 VALUES ('block2', 'page2', 'Other', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                 VALUES ('cross', 'page1', 'block2', 'Child', 2.0);
+             PRAGMA foreign_keys = OFF;
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                 VALUES ('orphan', 'page1', 'missing', 'Lost', 3.0);
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+                 VALUES ('link1', 'page1', 'block1', 'nowhere', 'page_link', 'Nowhere', 'Nowhere');
+             PRAGMA foreign_keys = ON;",
+        )
+        .unwrap();
+
+        let report = check_workspace_integrity(&conn, &temp_dir).unwrap();
+        assert_eq!(report.orphan_blocks.sample_ids, vec!["orphan"]);
+        assert_eq!(report.cross_page_parents.sample_ids, vec!["cross"]);
+        assert_eq!(report.dangling_wiki_links.sample_ids, vec!["link1"]);
+        assert_eq!(report.pages_missing_files.sample_ids, vec!["page2"]);
+        assert_eq!(report.files_without_page.sample_ids, vec!["Stray.md"]);
+        assert_eq!(report.files_without_page.count, 1);
+
+        let blocks: i64 = conn
+            .query_row("SELECT COUNT(*) FROM blocks", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(blocks, 4);
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
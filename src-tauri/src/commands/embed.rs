@@ -0,0 +1,222 @@
+use rusqlite::Connection;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+use crate::commands::block::load_block_subtree;
+use crate::commands::workspace::open_workspace_db;
+use crate::models::block::Block;
+
+/// Cached renderings kept before the cache is reset
+const MAX_EMBED_CACHE_ENTRIES: usize = 512;
+
+/// (workspace path, block id, max depth)
+type EmbedCacheKey = (String, String, i64);
+
+struct CachedEmbed {
+    structure_hash: u64,
+    blocks: Vec<Block>,
+}
+
+static EMBED_CACHE: OnceLock<Mutex<HashMap<EmbedCacheKey, CachedEmbed>>> = OnceLock::new();
+
+fn embed_cache() -> &'static Mutex<HashMap<EmbedCacheKey, CachedEmbed>> {
+    EMBED_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Resolve an embedded block to its subtree (root first, then descendants down to
+/// `max_depth`). Renderings are cached until the target page's structure changes.
+#[tauri::command]
+pub async fn resolve_embed(
+    workspace_path: String,
+    block_id: String,
+    max_depth: Option<i64>,
+) -> Result<Vec<Block>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    resolve_embed_cached(&conn, &workspace_path, &block_id, max_depth).map(|(blocks, _)| blocks)
+}
+
+/// Resolve several embeds at once. Targets that no longer exist are left out.
+#[tauri::command]
+pub async fn resolve_embeds_batch(
+    workspace_path: String,
+    block_ids: Vec<String>,
+    max_depth: Option<i64>,
+) -> Result<HashMap<String, Vec<Block>>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+
+    let mut resolved = HashMap::new();
+    for block_id in block_ids {
+        if resolved.contains_key(&block_id) {
+            continue;
+        }
+        match resolve_embed_cached(&conn, &workspace_path, &block_id, max_depth) {
+            Ok((blocks, _)) => {
+                resolved.insert(block_id, blocks);
+            }
+            Err(e) => eprintln!("[resolve_embeds_batch] Skipping {}: {}", block_id, e),
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Drop all cached embed renderings for a workspace (for debugging)
+#[tauri::command]
+pub fn clear_embed_cache(workspace_path: String) -> Result<usize, String> {
+    let mut cache = embed_cache().lock().map_err(|e| e.to_string())?;
+    let before = cache.len();
+    cache.retain(|(workspace, _, _), _| *workspace != workspace_path);
+    Ok(before - cache.len())
+}
+
+/// Returns the rendering and whether it came from the cache
+fn resolve_embed_cached(
+    conn: &Connection,
+    workspace_path: &str,
+    block_id: &str,
+    max_depth: Option<i64>,
+) -> Result<(Vec<Block>, bool), String> {
+    let page_id: String = conn
+        .query_row(
+            "SELECT page_id FROM blocks WHERE id = ?",
+            [block_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| "Block not found".to_string())?;
+    let structure_hash = page_structure_hash(conn, &page_id)?;
+    let key: EmbedCacheKey = (
+        workspace_path.to_string(),
+        block_id.to_string(),
+        max_depth.unwrap_or(-1),
+    );
+
+    {
+        let cache = embed_cache().lock().map_err(|e| e.to_string())?;
+        if let Some(cached) = cache.get(&key) {
+            if cached.structure_hash == structure_hash {
+                return Ok((cached.blocks.clone(), true));
+            }
+        }
+    }
+
+    let blocks = load_block_subtree(conn, block_id, max_depth)?;
+
+    let mut cache = embed_cache().lock().map_err(|e| e.to_string())?;
+    if cache.len() >= MAX_EMBED_CACHE_ENTRIES && !cache.contains_key(&key) {
+        cache.clear();
+    }
+    cache.insert(
+        key,
+        CachedEmbed {
+            structure_hash,
+            blocks: blocks.clone(),
+        },
+    );
+
+    Ok((blocks, false))
+}
+
+/// Hash of everything about a page's blocks that can change a rendering:
+/// tree shape, order, collapse state, content, modification times and metadata
+fn page_structure_hash(conn: &Connection, page_id: &str) -> Result<u64, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, parent_id, order_weight, is_collapsed, content, updated_at
+             FROM blocks WHERE page_id = ? ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([page_id]).map_err(|e| e.to_string())?;
+
+    let mut hasher = DefaultHasher::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        row.get::<_, String>(0)
+            .map_err(|e| e.to_string())?
+            .hash(&mut hasher);
+        row.get::<_, Option<String>>(1)
+            .map_err(|e| e.to_string())?
+            .hash(&mut hasher);
+        row.get::<_, f64>(2)
+            .map_err(|e| e.to_string())?
+            .to_bits()
+            .hash(&mut hasher);
+        row.get::<_, i32>(3)
+            .map_err(|e| e.to_string())?
+            .hash(&mut hasher);
+        row.get::<_, String>(4)
+            .map_err(|e| e.to_string())?
+            .hash(&mut hasher);
+        row.get::<_, Option<String>>(5)
+            .map_err(|e| e.to_string())?
+            .hash(&mut hasher);
+    }
+
+    // Metadata edits don't touch the block row
+    let mut metadata_stmt = conn
+        .prepare(
+            "SELECT m.block_id, m.key, m.value FROM block_metadata m
+             JOIN blocks b ON b.id = m.block_id
+             WHERE b.page_id = ? ORDER BY m.block_id, m.key",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = metadata_stmt.query([page_id]).map_err(|e| e.to_string())?;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        for column in 0..3 {
+            row.get::<_, String>(column)
+                .map_err(|e| e.to_string())?
+                .hash(&mut hasher);
+        }
+    }
+
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_embed_uses_cache_until_target_changes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('x', 'p1', 'Embedded', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('x1', 'p1', 'x', 'Child', 1.0);",
+        )
+        .unwrap();
+        let workspace = format!("test-embed-{}", uuid::Uuid::new_v4());
+
+        let (first, cached) = resolve_embed_cached(&conn, &workspace, "x", None).unwrap();
+        assert!(!cached);
+        assert_eq!(first.len(), 2);
+
+        let (second, cached) = resolve_embed_cached(&conn, &workspace, "x", None).unwrap();
+        assert!(cached);
+        assert_eq!(second.len(), 2);
+
+        conn.execute(
+            "UPDATE blocks SET content = 'Edited child', updated_at = '2030-01-01T00:00:00Z' WHERE id = 'x1'",
+            [],
+        )
+        .unwrap();
+        let (third, cached) = resolve_embed_cached(&conn, &workspace, "x", None).unwrap();
+        assert!(!cached);
+        assert!(third.iter().any(|b| b.content == "Edited child"));
+
+        conn.execute(
+            "INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m1', 'x1', 'status', 'done')",
+            [],
+        )
+        .unwrap();
+        let (fourth, cached) = resolve_embed_cached(&conn, &workspace, "x", None).unwrap();
+        assert!(!cached);
+        let child = fourth.iter().find(|b| b.id == "x1").unwrap();
+        assert_eq!(child.metadata.get("status"), Some(&"done".to_string()));
+
+        assert_eq!(clear_embed_cache(workspace.clone()).unwrap(), 1);
+        let (_, cached) = resolve_embed_cached(&conn, &workspace, "x", None).unwrap();
+        assert!(!cached);
+    }
+}
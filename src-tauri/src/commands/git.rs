@@ -19,6 +19,75 @@ pub struct GitCommitResult {
     pub commit_hash: Option<String>,
 }
 
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct GitLogOptions {
+    pub limit: Option<usize>,
+    pub skip: Option<usize>,
+    pub file_path: Option<String>,
+    pub author: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitLogEntry {
+    pub sha: String,
+    pub author: String,
+    /// Commit time as unix seconds
+    pub timestamp: i64,
+    pub message: String,
+    pub changed_files: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitStashEntry {
+    /// Position in the stash list; 0 is the most recent
+    pub index: usize,
+    /// `stash@{N}` reference
+    pub reference: String,
+    pub message: String,
+    /// Stash time as unix seconds
+    pub timestamp: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitStashPopResult {
+    pub success: bool,
+    pub message: String,
+    /// Files left with conflict markers; the stash is kept when this is non-empty
+    pub conflicted_files: Vec<String>,
+}
+
+/// What a `git_pull` did to the local branch
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub enum GitPullOutcome {
+    UpToDate,
+    FastForward,
+    Merged,
+    /// The merge stopped with conflict markers left in `conflicted_files`
+    Conflicts,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitPullResult {
+    pub outcome: GitPullOutcome,
+    pub message: String,
+    pub conflicted_files: Vec<String>,
+}
+
+/// Separates commit records in `git log` output (ASCII record separator)
+const LOG_RECORD_SEPARATOR: char = '\u{1e}';
+
+/// Upper bound on the text returned by `git_diff`; larger diffs are cut off
+const MAX_DIFF_BYTES: usize = 512 * 1024;
+
+/// `git` in `path` with untranslated output, for commands whose output is parsed
+fn git_command(path: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.current_dir(path).env("LC_ALL", "C");
+    command
+}
+
 /// Initialize a git repository in the workspace
 #[command]
 pub async fn git_init(workspace_path: String) -> Result<bool, String> {
@@ -349,9 +418,16 @@ pub async fn git_push(workspace_path: String) -> Result<String, String> {
     Ok("Pushed successfully".to_string())
 }
 
-/// Pull changes from remote
+/// Pull changes from remote, merging them into the current branch.
+///
+/// When the branch moved the workspace is reindexed so the database reflects the
+/// pulled files. A conflicting merge is not an error: the conflict markers are left
+/// in the files, which are listed in the result, and nothing is reindexed.
 #[command]
-pub async fn git_pull(workspace_path: String) -> Result<String, String> {
+pub async fn git_pull(
+    app: tauri::AppHandle,
+    workspace_path: String,
+) -> Result<GitPullResult, String> {
     if workspace_path.is_empty() { return Err("workspace_path must not be empty".to_string()); }
     let path = Path::new(&workspace_path);
 
@@ -359,26 +435,133 @@ pub async fn git_pull(workspace_path: String) -> Result<String, String> {
         return Err("Not a git repository".to_string());
     }
 
+    let result = pull_and_merge(path).await?;
+
+    if matches!(
+        result.outcome,
+        GitPullOutcome::FastForward | GitPullOutcome::Merged
+    ) {
+        let sync_path = workspace_path.clone();
+        tokio::task::spawn_blocking(move || {
+            crate::commands::workspace::sync_workspace_with_progress(&sync_path, None, &mut |_| {})
+        })
+        .await
+        .map_err(|e| format!("Pulled, but reindexing failed: {}", e))?
+        .map_err(|e| format!("Pulled, but reindexing failed: {}", e))?;
+        crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    }
+
+    Ok(result)
+}
+
+/// Run `git pull --no-rebase` and classify the result by comparing HEAD before and after
+async fn pull_and_merge(path: &Path) -> Result<GitPullResult, String> {
+    let head_before = rev_parse_head(path).await;
+
     let output = Command::new("git")
-        .args(["pull"])
+        .args(["pull", "--no-rebase"])
         .current_dir(path)
         .output()
         .await
         .map_err(|e| format!("Failed to pull: {}", e))?;
 
+    if !output.status.success() {
+        let conflicted_files = list_conflicted_files(path).await?;
+        if conflicted_files.is_empty() {
+            return Err(format!(
+                "Pull failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        return Ok(GitPullResult {
+            outcome: GitPullOutcome::Conflicts,
+            message: format!(
+                "Merge stopped with conflicts in {} file(s)",
+                conflicted_files.len()
+            ),
+            conflicted_files,
+        });
+    }
+
+    let head_after = rev_parse_head(path).await;
+    let (outcome, message) = if head_after == head_before {
+        (GitPullOutcome::UpToDate, "Already up to date")
+    } else if is_merge_commit(path).await {
+        (GitPullOutcome::Merged, "Merged remote changes")
+    } else {
+        (GitPullOutcome::FastForward, "Fast-forwarded")
+    };
+
+    Ok(GitPullResult {
+        outcome,
+        message: message.to_string(),
+        conflicted_files: Vec::new(),
+    })
+}
+
+/// Current HEAD commit, or None on an unborn branch
+async fn rev_parse_head(path: &Path) -> Option<String> {
+    rev_parse(path, "HEAD").await
+}
+
+/// Commit `reference` points at, or None when it doesn't exist
+async fn rev_parse(path: &Path, reference: &str) -> Option<String> {
+    let output = git_command(path)
+        .args(["rev-parse", "--verify", "-q", reference])
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether HEAD has more than one parent
+async fn is_merge_commit(path: &Path) -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--verify", "-q", "HEAD^2"])
+        .current_dir(path)
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Whether a merge is in progress or any file still has unresolved conflicts, as left
+/// by a conflicting `git_pull` or `git_stash_pop`
+pub(crate) async fn has_unresolved_merge(path: &Path) -> Result<bool, String> {
+    if path.join(".git").join("MERGE_HEAD").exists() {
+        return Ok(true);
+    }
+    Ok(!list_conflicted_files(path).await?.is_empty())
+}
+
+/// Files with unresolved merge conflicts
+async fn list_conflicted_files(path: &Path) -> Result<Vec<String>, String> {
+    let output = git_command(path)
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list conflicts: {}", e))?;
     if !output.status.success() {
         return Err(format!(
-            "Pull failed: {}",
+            "Failed to list conflicts: {}",
             String::from_utf8_lossy(&output.stderr)
         ));
     }
-
-    Ok("Pulled successfully".to_string())
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.to_string())
+        .collect())
 }
 
-/// Get git log (recent commits)
+/// Get git log with pagination, per-file history, and author/date filters
 #[command]
-pub async fn git_log(workspace_path: String, limit: Option<usize>) -> Result<Vec<String>, String> {
+pub async fn git_log(
+    workspace_path: String,
+    options: Option<GitLogOptions>,
+) -> Result<Vec<GitLogEntry>, String> {
     if workspace_path.is_empty() { return Err("workspace_path must not be empty".to_string()); }
     let path = Path::new(&workspace_path);
 
@@ -386,23 +569,479 @@ pub async fn git_log(workspace_path: String, limit: Option<usize>) -> Result<Vec
         return Err("Not a git repository".to_string());
     }
 
-    let limit_str = limit.unwrap_or(20).to_string();
-    let output = Command::new("git")
-        .args(["log", "--oneline", &format!("- বিধ{}", limit_str)])
-        .current_dir(path)
+    // A freshly initialized repo without commits has no history yet
+    if rev_parse_head(path).await.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let options = options.unwrap_or_default();
+    let mut args = vec![
+        "log".to_string(),
+        format!("--max-count={}", options.limit.unwrap_or(20)),
+        format!("--skip={}", options.skip.unwrap_or(0)),
+        "--pretty=format:%x1e%H%x1f%an%x1f%at%x1f%s".to_string(),
+        "--name-only".to_string(),
+    ];
+    if let Some(author) = options.author.as_deref().filter(|a| !a.is_empty()) {
+        args.push(format!("--author={}", author));
+    }
+    if let Some(since) = options.since.as_deref().filter(|s| !s.is_empty()) {
+        args.push(format!("--since={}", since));
+    }
+    if let Some(until) = options.until.as_deref().filter(|u| !u.is_empty()) {
+        args.push(format!("--until={}", until));
+    }
+    if let Some(file_path) = options.file_path.as_deref().filter(|f| !f.is_empty()) {
+        // List every file of the matching commits, not just the filtered one
+        args.push("--full-diff".to_string());
+        args.push("--".to_string());
+        args.push(file_path.to_string());
+    }
+
+    let output = git_command(path)
+        .args(&args)
         .output()
         .await
         .map_err(|e| format!("Failed to get log: {}", e))?;
 
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Git log failed: {}", stderr));
+    }
+
+    Ok(parse_git_log_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Stash local changes, including untracked files, so a pull can proceed.
+/// Returns false when there was nothing to stash.
+#[command]
+pub async fn git_stash(workspace_path: String, message: Option<String>) -> Result<bool, String> {
+    if workspace_path.is_empty() { return Err("workspace_path must not be empty".to_string()); }
+    let path = Path::new(&workspace_path);
+
+    if !path.join(".git").exists() {
+        return Err("Not a git repository".to_string());
+    }
+
+    let mut args = vec!["stash", "push", "--include-untracked"];
+    if let Some(message) = message.as_deref().filter(|m| !m.is_empty()) {
+        args.extend(["-m", message]);
+    }
+
+    // `git stash push` succeeds without stashing on a clean tree; tell the two apart by
+    // whether refs/stash moved
+    let stash_before = rev_parse(path, "refs/stash").await;
+    let output = git_command(path)
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to stash: {}", e))?;
+
     if !output.status.success() {
         return Err(format!(
-            "Git log failed: {}",
+            "Stash failed: {}",
             String::from_utf8_lossy(&output.stderr)
         ));
     }
 
-    let log_text = String::from_utf8_lossy(&output.stdout);
-    let commits: Vec<String> = log_text.lines().map(|line| line.to_string()).collect();
+    Ok(rev_parse(path, "refs/stash").await != stash_before)
+}
+
+/// List stashed changes, most recent first
+#[command]
+pub async fn git_stash_list(workspace_path: String) -> Result<Vec<GitStashEntry>, String> {
+    if workspace_path.is_empty() { return Err("workspace_path must not be empty".to_string()); }
+    let path = Path::new(&workspace_path);
+
+    if !path.join(".git").exists() {
+        return Err("Not a git repository".to_string());
+    }
+
+    let output = git_command(path)
+        .args(["stash", "list", "--format=%gd%x1f%ct%x1f%gs"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list stashes: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Stash list failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_git_stash_list(&stdout))
+}
+
+/// Re-apply the most recent stash and drop it.
+/// On conflicts the stash is kept and the conflicted files are reported instead of an error.
+#[command]
+pub async fn git_stash_pop(workspace_path: String) -> Result<GitStashPopResult, String> {
+    if workspace_path.is_empty() { return Err("workspace_path must not be empty".to_string()); }
+    let path = Path::new(&workspace_path);
+
+    if !path.join(".git").exists() {
+        return Err("Not a git repository".to_string());
+    }
+
+    let output = git_command(path)
+        .args(["stash", "pop"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to pop stash: {}", e))?;
+
+    if output.status.success() {
+        return Ok(GitStashPopResult {
+            success: true,
+            message: "Stash applied successfully".to_string(),
+            conflicted_files: Vec::new(),
+        });
+    }
+
+    let conflicted_files = list_conflicted_files(path).await?;
+
+    if conflicted_files.is_empty() {
+        return Err(format!(
+            "Stash pop failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(GitStashPopResult {
+        success: false,
+        message: format!(
+            "Stash applied with conflicts in {} file(s); the stash was kept",
+            conflicted_files.len()
+        ),
+        conflicted_files,
+    })
+}
+
+/// Parse `git stash list` output in the `%gd%x1f%ct%x1f%gs` format
+fn parse_git_stash_list(text: &str) -> Vec<GitStashEntry> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let mut fields = line.split('\u{1f}');
+            let reference = fields.next().unwrap_or_default().to_string();
+            let timestamp = fields
+                .next()
+                .and_then(|t| t.trim().parse().ok())
+                .unwrap_or(0);
+            let message = fields.next().unwrap_or_default().to_string();
+            GitStashEntry {
+                index,
+                reference,
+                message,
+                timestamp,
+            }
+        })
+        .collect()
+}
 
-    Ok(commits)
-}
\ No newline at end of file
+/// Unified diff of uncommitted changes (staged, unstaged and untracked files) against
+/// HEAD, optionally limited to one workspace-relative file.
+///
+/// Binary files show up as a "Binary files ... differ" line. Output beyond
+/// `MAX_DIFF_BYTES` is dropped at a line boundary and a truncation note is appended.
+#[command]
+pub async fn git_diff(workspace_path: String, file_path: Option<String>) -> Result<String, String> {
+    if workspace_path.is_empty() { return Err("workspace_path must not be empty".to_string()); }
+    let path = Path::new(&workspace_path);
+
+    if !path.join(".git").exists() {
+        return Err("Not a git repository".to_string());
+    }
+
+    let file_path = file_path.as_deref().filter(|f| !f.is_empty());
+    diff_working_tree(path, file_path, MAX_DIFF_BYTES)
+}
+
+fn diff_working_tree(
+    path: &Path,
+    file_path: Option<&str>,
+    max_bytes: usize,
+) -> Result<String, String> {
+    let repo =
+        git2::Repository::open(path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    // No HEAD tree yet in a repo without commits: everything is new
+    let head_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+
+    let mut options = git2::DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    if let Some(file_path) = file_path {
+        options.pathspec(file_path).disable_pathspec_match(true);
+    }
+
+    let diff = repo
+        .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))
+        .map_err(|e| format!("Failed to compute diff: {}", e))?;
+
+    let mut output = String::new();
+    let mut truncated = false;
+    let printed = diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        let content = String::from_utf8_lossy(line.content());
+        let origin = match line.origin() {
+            origin @ ('+' | '-' | ' ') => Some(origin),
+            _ => None,
+        };
+        let len = content.len() + usize::from(origin.is_some());
+        if output.len() + len > max_bytes {
+            truncated = true;
+            return false;
+        }
+        output.extend(origin);
+        output.push_str(&content);
+        true
+    });
+    if let Err(e) = printed {
+        if !truncated {
+            return Err(format!("Failed to print diff: {}", e));
+        }
+    }
+
+    if truncated {
+        output.push_str(&format!(
+            "\n[diff truncated after {} KB; view the full diff with git]\n",
+            max_bytes / 1024
+        ));
+    }
+    Ok(output)
+}
+
+/// Parse `git log` output produced with a record-separated pretty format
+/// followed by the `--name-only` file list of each commit
+fn parse_git_log_output(text: &str) -> Vec<GitLogEntry> {
+    text.split(LOG_RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.split('\u{1f}');
+            let sha = fields.next()?.trim().to_string();
+            if sha.is_empty() {
+                return None;
+            }
+            let author = fields.next().unwrap_or_default().to_string();
+            let timestamp = fields
+                .next()
+                .and_then(|t| t.trim().parse().ok())
+                .unwrap_or(0);
+            let message = fields.next().unwrap_or_default().to_string();
+            let changed_files = lines.filter(|l| !l.trim().is_empty()).count();
+
+            Some(GitLogEntry {
+                sha,
+                author,
+                timestamp,
+                message,
+                changed_files,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command as StdCommand;
+    use uuid::Uuid;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = StdCommand::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("failed to run git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn commit_file(dir: &Path, file: &str, content: &str, author: &str, message: &str) {
+        std::fs::write(dir.join(file), content).unwrap();
+        git(dir, &["add", file]);
+        git(
+            dir,
+            &[
+                "-c",
+                &format!("user.name={}", author),
+                "-c",
+                &format!("user.email={}@example.com", author.to_lowercase()),
+                "commit",
+                "-q",
+                "-m",
+                message,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_git_log_pagination_file_and_author_filters() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_git_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        git(&temp_dir, &["init", "-q"]);
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        // No commits yet: an empty history rather than an error
+        let empty = tauri::async_runtime::block_on(git_log(path_str.clone(), None)).unwrap();
+        assert!(empty.is_empty());
+
+        commit_file(&temp_dir, "A.md", "- one\n", "Alice", "c1");
+        commit_file(&temp_dir, "B.md", "- two\n", "Bob", "c2");
+        commit_file(&temp_dir, "A.md", "- three\n", "Alice", "c3");
+        commit_file(&temp_dir, "C.md", "- four\n", "Bob", "c4");
+        commit_file(&temp_dir, "A.md", "- five\n", "Bob", "c5");
+        std::fs::write(temp_dir.join("B.md"), "- six\n").unwrap();
+        git(&temp_dir, &["add", "B.md"]);
+        commit_file(&temp_dir, "A.md", "- six\n", "Bob", "c6");
+
+        let messages = |entries: Vec<GitLogEntry>| {
+            entries.into_iter().map(|e| e.message).collect::<Vec<_>>()
+        };
+
+        tauri::async_runtime::block_on(async {
+            let page = git_log(
+                path_str.clone(),
+                Some(GitLogOptions {
+                    limit: Some(2),
+                    skip: Some(1),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+            assert!(page.iter().all(|e| e.sha.len() == 40 && e.changed_files == 1));
+            assert_eq!(messages(page), vec!["c5", "c4"]);
+
+            let file_history = git_log(
+                path_str.clone(),
+                Some(GitLogOptions {
+                    file_path: Some("A.md".to_string()),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+            // The file filter selects commits but still counts all of their files
+            assert_eq!(file_history[0].changed_files, 2);
+            assert!(file_history[1..].iter().all(|e| e.changed_files == 1));
+            assert_eq!(messages(file_history), vec!["c6", "c5", "c3", "c1"]);
+
+            let by_alice = git_log(
+                path_str.clone(),
+                Some(GitLogOptions {
+                    author: Some("Alice".to_string()),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+            assert!(by_alice.iter().all(|e| e.author == "Alice"));
+            assert_eq!(messages(by_alice), vec!["c3", "c1"]);
+        });
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_stash_list_and_pop_with_conflict() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_stash_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        git(&temp_dir, &["init", "-q"]);
+        git(&temp_dir, &["config", "user.name", "Alice"]);
+        git(&temp_dir, &["config", "user.email", "alice@example.com"]);
+        commit_file(&temp_dir, "A.md", "- one\n", "Alice", "c1");
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        tauri::async_runtime::block_on(async {
+            assert!(!git_stash(path_str.clone(), None).await.unwrap());
+
+            std::fs::write(temp_dir.join("A.md"), "- local edit\n").unwrap();
+            assert!(git_stash(path_str.clone(), Some("before pull".to_string()))
+                .await
+                .unwrap());
+            let restored = std::fs::read_to_string(temp_dir.join("A.md")).unwrap();
+            assert_eq!(restored, "- one\n");
+
+            let stashes = git_stash_list(path_str.clone()).await.unwrap();
+            assert_eq!(stashes.len(), 1);
+            assert_eq!(stashes[0].reference, "stash@{0}");
+            assert!(stashes[0].message.contains("before pull"));
+            assert!(stashes[0].timestamp > 0);
+
+            // What a pull would bring in, touching the same line
+            commit_file(&temp_dir, "A.md", "- remote edit\n", "Bob", "c2");
+
+            let popped = git_stash_pop(path_str.clone()).await.unwrap();
+            assert!(!popped.success);
+            assert_eq!(popped.conflicted_files, vec!["A.md"]);
+            assert_eq!(git_stash_list(path_str.clone()).await.unwrap().len(), 1);
+        });
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_diff_covers_changes_binary_files_and_cap() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_diff_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        git(&temp_dir, &["init", "-q"]);
+        commit_file(&temp_dir, "A.md", "- one\n- two\n", "Alice", "c1");
+
+        std::fs::write(temp_dir.join("A.md"), "- one\n- 2\n").unwrap();
+        std::fs::write(temp_dir.join("New.md"), "- fresh\n").unwrap();
+        std::fs::write(temp_dir.join("image.png"), [0u8, 159, 146, 150, 0, 1]).unwrap();
+
+        let diff = diff_working_tree(&temp_dir, None, MAX_DIFF_BYTES).unwrap();
+        assert!(diff.contains("diff --git a/A.md b/A.md\n"));
+        assert!(diff.contains("-- two\n+- 2\n"));
+        assert!(diff.contains("+- fresh\n"));
+        assert!(diff.contains("Binary files"));
+
+        let scoped = diff_working_tree(&temp_dir, Some("New.md"), MAX_DIFF_BYTES).unwrap();
+        assert!(scoped.contains("New.md") && !scoped.contains("A.md"));
+
+        let capped = diff_working_tree(&temp_dir, None, 64).unwrap();
+        assert!(capped.contains("[diff truncated"));
+        assert!(!capped.contains("+- fresh"));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_git_pull_reports_outcomes_and_leaves_conflicts() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_pull_{}", Uuid::new_v4()));
+        let origin = temp_dir.join("origin");
+        let clone = temp_dir.join("clone");
+        std::fs::create_dir_all(&origin).unwrap();
+        git(&origin, &["init", "-q"]);
+        commit_file(&origin, "A.md", "- one\n", "Alice", "c1");
+        git(&temp_dir, &["clone", "-q", "origin", "clone"]);
+        git(&clone, &["config", "user.name", "Bob"]);
+        git(&clone, &["config", "user.email", "bob@example.com"]);
+
+        let pull = |dir: &Path| tauri::async_runtime::block_on(pull_and_merge(dir)).unwrap();
+
+        assert_eq!(pull(&clone).outcome, GitPullOutcome::UpToDate);
+
+        commit_file(&origin, "B.md", "- two\n", "Alice", "c2");
+        assert_eq!(pull(&clone).outcome, GitPullOutcome::FastForward);
+        assert!(clone.join("B.md").exists());
+
+        commit_file(&origin, "C.md", "- three\n", "Alice", "c3");
+        commit_file(&clone, "D.md", "- four\n", "Bob", "c4");
+        assert_eq!(pull(&clone).outcome, GitPullOutcome::Merged);
+
+        commit_file(&origin, "A.md", "- from origin\n", "Alice", "c5");
+        commit_file(&clone, "A.md", "- from clone\n", "Bob", "c6");
+        let result = pull(&clone);
+        assert_eq!(result.outcome, GitPullOutcome::Conflicts);
+        assert_eq!(result.conflicted_files, vec!["A.md"]);
+        let content = std::fs::read_to_string(clone.join("A.md")).unwrap();
+        assert!(content.contains("<<<<<<<") && content.contains(">>>>>>>"));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
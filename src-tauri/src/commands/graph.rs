@@ -1,10 +1,21 @@
 use crate::commands::workspace::open_workspace_db;
-use crate::models::graph::{GraphData, GraphEdge, GraphNode};
-use rusqlite::params;
+use crate::models::graph::{ClusterMode, GraphData, GraphEdge, GraphNode, LocalGraphData};
+use crate::utils::graph_cluster::assign_clusters;
+use rusqlite::{params, Connection};
 use std::collections::{HashMap, HashSet};
 
+/// Hop limit for `get_local_graph`; deeper neighborhoods approach the global graph
+const MAX_LOCAL_GRAPH_DEPTH: u32 = 3;
+/// Default node cap for `get_local_graph` so hub pages cannot pull in the whole workspace
+const DEFAULT_LOCAL_GRAPH_NODES: usize = 150;
+
+/// Page link graph. With `cluster` each node also gets a `cluster_id`, computed here
+/// so large workspaces need no clustering pass in the frontend.
 #[tauri::command]
-pub async fn get_graph_data(workspace_path: String) -> Result<GraphData, String> {
+pub async fn get_graph_data(
+    workspace_path: String,
+    cluster: Option<ClusterMode>,
+) -> Result<GraphData, String> {
     let conn = open_workspace_db(&workspace_path)?;
 
     // Fetch all pages
@@ -32,47 +43,18 @@ pub async fn get_graph_data(workspace_path: String) -> Result<GraphData, String>
             node_type: "page".to_string(),
             page_id: page_id.clone(),
             block_id: None,
+            cluster_id: None,
         });
     }
 
-    // Fetch wiki links and create edges
-    let mut stmt = conn
-        .prepare(
-            r#"
-        SELECT DISTINCT w.from_page_id, w.to_page_id, w.link_type, w.is_embed
-        FROM wiki_links w
-        WHERE w.to_page_id IS NOT NULL
-        ORDER BY w.from_page_id, w.to_page_id
-        "#,
-        )
-        .map_err(|e| e.to_string())?;
-
-    let mut edges = Vec::new();
-    let edge_rows = stmt
-        .query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,         // from_page_id
-                row.get::<_, Option<String>>(1)?, // to_page_id
-                row.get::<_, String>(2)?,         // link_type
-                row.get::<_, i32>(3)? != 0,       // is_embed
-            ))
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+    // Only create edges between existing pages
+    let edges: Vec<GraphEdge> = load_page_edges(&conn)?
+        .into_iter()
+        .filter(|edge| page_ids.contains(&edge.source) && page_ids.contains(&edge.target))
+        .collect();
 
-    for (from_page_id, to_page_id, link_type, is_embed) in edge_rows {
-        if let Some(to_id) = to_page_id {
-            // Only create edges between existing pages
-            if page_ids.contains(&to_id) {
-                edges.push(GraphEdge {
-                    source: from_page_id,
-                    target: to_id,
-                    relation_type: link_type,
-                    is_embed,
-                });
-            }
-        }
+    if let Some(mode) = cluster {
+        assign_clusters(&mut nodes, &edges, mode);
     }
 
     Ok(GraphData { nodes, edges })
@@ -174,48 +156,241 @@ pub async fn get_page_graph_data(
             node_type: "page".to_string(),
             page_id: id,
             block_id: None,
+            cluster_id: None,
         });
     }
 
-    // Fetch edges between all connected pages
+    // Only include edges where both pages are in our connected set
+    let edges: Vec<GraphEdge> = load_page_edges(&conn)?
+        .into_iter()
+        .filter(|edge| all_pages.contains(&edge.source) && all_pages.contains(&edge.target))
+        .collect();
+
+    Ok(GraphData { nodes, edges })
+}
+
+/// Pages within `depth` hops (1-3, default 2) of `page_id`, following links in both
+/// directions. At most `max_nodes` pages are returned, nearest first.
+#[tauri::command]
+pub async fn get_local_graph(
+    workspace_path: String,
+    page_id: String,
+    depth: Option<u32>,
+    max_nodes: Option<usize>,
+) -> Result<LocalGraphData, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    build_local_graph(
+        &conn,
+        &page_id,
+        depth.unwrap_or(2),
+        max_nodes.unwrap_or(DEFAULT_LOCAL_GRAPH_NODES),
+    )
+}
+
+fn build_local_graph(
+    conn: &Connection,
+    page_id: &str,
+    depth: u32,
+    max_nodes: usize,
+) -> Result<LocalGraphData, String> {
+    let depth = depth.clamp(1, MAX_LOCAL_GRAPH_DEPTH);
+    let max_nodes = max_nodes.max(1);
+
+    // Shortest hop count per page; one extra row tells us whether the cap was hit
     let mut stmt = conn
         .prepare(
             r#"
-        SELECT DISTINCT w.from_page_id, w.to_page_id, w.link_type, w.is_embed
-        FROM wiki_links w
-        WHERE w.to_page_id IS NOT NULL
+        WITH RECURSIVE reach(id, hop) AS (
+            SELECT ?1, 0
+            UNION
+            SELECT w.to_page_id, r.hop + 1
+            FROM reach r JOIN wiki_links w ON w.from_page_id = r.id
+            WHERE r.hop < ?2 AND w.to_page_id IS NOT NULL
+            UNION
+            SELECT w.from_page_id, r.hop + 1
+            FROM reach r JOIN wiki_links w ON w.to_page_id = r.id
+            WHERE r.hop < ?2
+        )
+        SELECT p.id, p.title, MIN(r.hop) AS hop
+        FROM reach r
+        JOIN pages p ON p.id = r.id
+        GROUP BY p.id
+        ORDER BY hop, p.title, p.id
+        LIMIT ?3
         "#,
         )
         .map_err(|e| e.to_string())?;
 
-    let mut edges = Vec::new();
+    let mut pages = stmt
+        .query_map(params![page_id, depth, max_nodes as i64 + 1], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    if pages.is_empty() {
+        return Err(format!("Page not found: {}", page_id));
+    }
+    let truncated = pages.len() > max_nodes;
+    pages.truncate(max_nodes);
 
-    let edge_rows = stmt
+    let page_ids: HashSet<&str> = pages.iter().map(|(id, _)| id.as_str()).collect();
+    let edges: Vec<GraphEdge> = load_page_edges(conn)?
+        .into_iter()
+        .filter(|edge| {
+            page_ids.contains(edge.source.as_str()) && page_ids.contains(edge.target.as_str())
+        })
+        .collect();
+
+    let nodes = pages
+        .into_iter()
+        .map(|(id, title)| GraphNode {
+            id: id.clone(),
+            label: title,
+            node_type: "page".to_string(),
+            page_id: id,
+            block_id: None,
+            cluster_id: None,
+        })
+        .collect();
+
+    Ok(LocalGraphData {
+        nodes,
+        edges,
+        truncated,
+    })
+}
+
+/// One edge per linked page pair (self-links excluded), weighted by the number of
+/// distinct blocks linking from `source` to `target`. Plain links take precedence over
+/// embeds for `relation_type`, and `is_embed` holds only when every link embeds.
+fn load_page_edges(conn: &Connection) -> Result<Vec<GraphEdge>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+        SELECT w.from_page_id, w.to_page_id,
+               COALESCE(MIN(CASE WHEN w.is_embed = 0 THEN w.link_type END), MIN(w.link_type)),
+               MIN(w.is_embed),
+               COUNT(DISTINCT w.from_block_id)
+        FROM wiki_links w
+        WHERE w.to_page_id IS NOT NULL AND w.to_page_id != w.from_page_id
+        GROUP BY w.from_page_id, w.to_page_id
+        ORDER BY w.from_page_id, w.to_page_id
+        "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut edges = stmt
         .query_map([], |row| {
-            Ok((
-                row.get::<_, String>(0)?,         // from_page_id
-                row.get::<_, Option<String>>(1)?, // to_page_id
-                row.get::<_, String>(2)?,         // link_type
-                row.get::<_, i32>(3)? != 0,       // is_embed
-            ))
+            Ok(GraphEdge {
+                source: row.get(0)?,
+                target: row.get(1)?,
+                relation_type: row.get(2)?,
+                is_embed: row.get::<_, i32>(3)? != 0,
+                weight: row.get::<_, i64>(4)? as usize,
+                is_bidirectional: false,
+            })
         })
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
-    for (from_page_id, to_page_id, link_type, is_embed) in edge_rows {
-        if let Some(to_id) = to_page_id {
-            // Only include edges where both pages are in our connected set
-            if all_pages.contains(&from_page_id) && all_pages.contains(&to_id) {
-                edges.push(GraphEdge {
-                    source: from_page_id,
-                    target: to_id,
-                    relation_type: link_type,
-                    is_embed,
-                });
-            }
-        }
+    let pairs: HashSet<(String, String)> = edges
+        .iter()
+        .map(|edge| (edge.source.clone(), edge.target.clone()))
+        .collect();
+    for edge in &mut edges {
+        edge.is_bidirectional = pairs.contains(&(edge.target.clone(), edge.source.clone()));
     }
 
-    Ok(GraphData { nodes, edges })
+    Ok(edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_page_edges_weights_and_direction() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('a', 'A'), ('b', 'B'), ('c', 'C');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'a', '', 1.0), ('a2', 'a', '', 2.0), ('b1', 'b', '', 1.0);
+             INSERT INTO wiki_links
+                (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target, is_embed)
+             VALUES
+                ('l1', 'a', 'a1', 'b', 'page_link', 'B', 'B', 0),
+                ('l2', 'a', 'a1', 'b', 'page_link', 'B', 'B|again', 0),
+                ('l3', 'a', 'a2', 'b', 'embed_page', 'B', 'B', 1),
+                ('l4', 'a', 'a2', 'c', 'page_link', 'C', 'C', 0),
+                ('l5', 'a', 'a2', 'a', 'page_link', 'A', 'A', 0),
+                ('l6', 'b', 'b1', 'a', 'page_link', 'A', 'A', 0);",
+        )
+        .unwrap();
+
+        let edges = load_page_edges(&conn).unwrap();
+        let summary: Vec<(&str, &str, usize, bool)> = edges
+            .iter()
+            .map(|e| {
+                (
+                    e.source.as_str(),
+                    e.target.as_str(),
+                    e.weight,
+                    e.is_bidirectional,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", "b", 2, true),
+                ("a", "c", 1, false),
+                ("b", "a", 1, true),
+            ]
+        );
+        assert_eq!(edges[0].relation_type, "page_link");
+        assert!(!edges[0].is_embed);
+    }
+
+    #[test]
+    fn test_build_local_graph_expands_hops_and_caps_nodes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        // Chain a -> b <- c -> d, plus e unconnected
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES
+                ('a', 'A'), ('b', 'B'), ('c', 'C'), ('d', 'D'), ('e', 'E');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'a', '', 1.0), ('c1', 'c', '', 1.0);
+             INSERT INTO wiki_links
+                (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+             VALUES
+                ('l1', 'a', 'a1', 'b', 'page_link', 'B', 'B'),
+                ('l2', 'c', 'c1', 'b', 'page_link', 'B', 'B'),
+                ('l3', 'c', 'c1', 'd', 'page_link', 'D', 'D');",
+        )
+        .unwrap();
+
+        let ids = |graph: &LocalGraphData| -> Vec<String> {
+            graph.nodes.iter().map(|n| n.id.clone()).collect()
+        };
+
+        let one_hop = build_local_graph(&conn, "a", 1, 10).unwrap();
+        assert_eq!(ids(&one_hop), vec!["a", "b"]);
+        assert_eq!(one_hop.edges.len(), 1);
+        assert!(!one_hop.truncated);
+
+        let all = build_local_graph(&conn, "a", 3, 10).unwrap();
+        assert_eq!(ids(&all), vec!["a", "b", "c", "d"]);
+        assert_eq!(all.edges.len(), 3);
+
+        let capped = build_local_graph(&conn, "a", 3, 3).unwrap();
+        assert_eq!(ids(&capped), vec!["a", "b", "c"]);
+        assert!(capped.truncated);
+
+        assert!(build_local_graph(&conn, "missing", 2, 10).is_err());
+    }
 }
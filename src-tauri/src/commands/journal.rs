@@ -0,0 +1,227 @@
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::commands::block::query_blocks_for_page;
+use crate::commands::workspace::open_workspace_db;
+use crate::models::block::Block;
+
+/// Default base folder for daily notes, matching the frontend's `dailyNotesPath`
+pub(crate) const DEFAULT_DAILY_NOTES_PATH: &str = "Daily";
+
+/// One daily note's blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalDay {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub page_id: String,
+    pub page_path: String,
+    /// In reading order: each block followed by its descendants
+    pub blocks: Vec<Block>,
+}
+
+/// Blocks of the daily notes dated `from` through `to` (inclusive, `YYYY-MM-DD`),
+/// oldest day first.
+///
+/// Daily notes follow the frontend's path template
+/// `{daily_notes_path}/{YYYY}/{MM}/{YYYY-MM-DD}`; `daily_notes_path` defaults to `Daily`.
+#[tauri::command]
+pub fn get_journal_entries(
+    workspace_path: String,
+    from: String,
+    to: String,
+    daily_notes_path: Option<String>,
+) -> Result<Vec<JournalDay>, String> {
+    let from = parse_journal_date(&from)?;
+    let to = parse_journal_date(&to)?;
+    let conn = open_workspace_db(&workspace_path)?;
+    let base = daily_notes_path.unwrap_or_else(|| DEFAULT_DAILY_NOTES_PATH.to_string());
+
+    collect_journal_entries(&conn, &base, from, to)
+}
+
+pub(crate) fn parse_journal_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}' (expected YYYY-MM-DD): {}", date, e))
+}
+
+fn collect_journal_entries(
+    conn: &Connection,
+    base: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<JournalDay>, String> {
+    if from > to {
+        return Ok(Vec::new());
+    }
+    let base = base.trim().trim_matches('/');
+    let prefix = if base.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", base)
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT pp.page_id, pp.path_text
+             FROM page_paths pp
+             JOIN pages p ON p.id = pp.page_id
+             WHERE p.is_deleted = 0 AND p.is_directory = 0 AND pp.path_text LIKE ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map([format!("{}%", prefix)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut days = Vec::new();
+    for (page_id, page_path) in candidates {
+        let Some(date) = journal_date_of(&page_path, &prefix) else {
+            continue;
+        };
+        if date < from || date > to {
+            continue;
+        }
+
+        let blocks = reading_order(query_blocks_for_page(conn, &page_id)?);
+        days.push(JournalDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            page_id,
+            page_path,
+            blocks,
+        });
+    }
+
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(days)
+}
+
+/// The date of a daily-note path, if it matches `{prefix}{YYYY}/{MM}/{YYYY-MM-DD}` exactly
+fn journal_date_of(page_path: &str, prefix: &str) -> Option<NaiveDate> {
+    let rest = page_path.strip_prefix(prefix)?;
+    let date = NaiveDate::parse_from_str(rest.rsplit('/').next()?, "%Y-%m-%d").ok()?;
+    let expected = format!("{}/{}", date.format("%Y/%m"), date.format("%Y-%m-%d"));
+    (rest == expected).then_some(date)
+}
+
+/// Page titles from the workspace root down to the daily note of `date`:
+/// the segments of `base`, then `YYYY`, `MM` and `YYYY-MM-DD`
+pub(crate) fn daily_note_titles(base: &str, date: NaiveDate) -> Vec<String> {
+    base.split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .chain([
+            date.format("%Y").to_string(),
+            date.format("%m").to_string(),
+            date.format("%Y-%m-%d").to_string(),
+        ])
+        .collect()
+}
+
+/// Order blocks depth-first by `order_weight`, so children follow their parent
+fn reading_order(mut blocks: Vec<Block>) -> Vec<Block> {
+    blocks.sort_by(|a, b| a.cmp_position(b));
+    reading_order_by(blocks, |block| (block.id.clone(), block.parent_id.clone()))
+}
+
+/// Order tree nodes depth-first, so children follow their parent. `link` gives a node's
+/// id and parent id; siblings keep their order in `nodes`.
+pub(crate) fn reading_order_by<T>(
+    nodes: Vec<T>,
+    link: impl Fn(&T) -> (String, Option<String>),
+) -> Vec<T> {
+    let mut children: HashMap<Option<String>, Vec<(String, T)>> = HashMap::new();
+    for node in nodes {
+        let (id, parent_id) = link(&node);
+        children.entry(parent_id).or_default().push((id, node));
+    }
+
+    let mut ordered = Vec::new();
+    let mut stack = children.remove(&None).unwrap_or_default();
+    stack.reverse();
+    while let Some((id, node)) = stack.pop() {
+        if let Some(mut kids) = children.remove(&Some(id)) {
+            kids.reverse();
+            stack.extend(kids);
+        }
+        ordered.push(node);
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_note_titles_match_journal_paths() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let titles = daily_note_titles("/Notes/Daily/", date);
+        assert_eq!(titles, vec!["Notes", "Daily", "2024", "06", "2024-06-01"]);
+        assert_eq!(
+            journal_date_of(&titles.join("/"), "Notes/Daily/"),
+            Some(date)
+        );
+
+        assert_eq!(
+            daily_note_titles("", date),
+            vec!["2024", "06", "2024-06-01"]
+        );
+    }
+
+    #[test]
+    fn test_journal_entries_grouped_by_date_in_reading_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES
+                ('d1', '2025-01-09', 'Daily/2025/01/2025-01-09.md'),
+                ('d2', '2025-01-10', 'Daily/2025/01/2025-01-10.md'),
+                ('d3', '2025-02-01', 'Daily/2025/02/2025-02-01.md'),
+                ('x', '2025-01-10', 'Notes/2025-01-10.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES
+                ('d1', 'Daily/2025/01/2025-01-09'),
+                ('d2', 'Daily/2025/01/2025-01-10'),
+                ('d3', 'Daily/2025/02/2025-02-01'),
+                ('x', 'Notes/2025-01-10');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('d1-a', 'd1', NULL, 'Thursday', 1.0),
+                ('d2-b', 'd2', NULL, 'Second', 2.0),
+                ('d2-a', 'd2', NULL, 'First', 1.0),
+                ('d2-a1', 'd2', 'd2-a', 'First child', 1.0),
+                ('d3-a', 'd3', NULL, 'February', 1.0),
+                ('x-a', 'x', NULL, 'Not a daily note', 1.0);",
+        )
+        .unwrap();
+
+        let days = collect_journal_entries(
+            &conn,
+            "Daily",
+            parse_journal_date("2025-01-09").unwrap(),
+            parse_journal_date("2025-01-31").unwrap(),
+        )
+        .unwrap();
+
+        let dates: Vec<&str> = days.iter().map(|d| d.date.as_str()).collect();
+        assert_eq!(dates, vec!["2025-01-09", "2025-01-10"]);
+        assert_eq!(days[0].blocks.len(), 1);
+        let second_day: Vec<&str> = days[1].blocks.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(second_day, vec!["d2-a", "d2-a1", "d2-b"]);
+
+        let all = collect_journal_entries(
+            &conn,
+            "Daily",
+            parse_journal_date("2025-01-01").unwrap(),
+            parse_journal_date("2025-12-31").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2].page_id, "d3");
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, Once, OnceLock};
+use std::time::{Duration, Instant};
+
+use tauri::ipc::Channel;
+use uuid::Uuid;
+
+use crate::commands::query::{run_query_macro, QueryResult};
+use crate::commands::workspace::open_workspace_db;
+use crate::services::query_service::{self, matches_path_pattern};
+
+/// Maximum number of live queries that may be registered at once
+const MAX_LIVE_QUERIES: usize = 32;
+
+/// Mutations arriving within this window are coalesced into one re-run
+const LIVE_QUERY_DEBOUNCE: Duration = Duration::from_millis(150);
+
+type ResultSink = Arc<dyn Fn(&QueryResult) -> Result<(), String> + Send + Sync>;
+
+struct LiveQuery {
+    workspace_path: String,
+    query_string: String,
+    /// FROM patterns of the query; empty if the query could not be parsed
+    from_paths: Vec<String>,
+    sink: ResultSink,
+    /// Serialized form of the last result sent, to skip unchanged re-runs
+    last_sent: Option<String>,
+}
+
+static LIVE_QUERIES: OnceLock<Mutex<HashMap<String, LiveQuery>>> = OnceLock::new();
+
+fn live_queries() -> &'static Mutex<HashMap<String, LiveQuery>> {
+    LIVE_QUERIES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Re-runs waiting out their debounce window, by token, with the time each is due
+struct RerunQueue {
+    due: Mutex<HashMap<String, Instant>>,
+    wake: Condvar,
+}
+
+static RERUN_QUEUE: OnceLock<RerunQueue> = OnceLock::new();
+static RERUN_WORKER: Once = Once::new();
+
+fn rerun_queue() -> &'static RerunQueue {
+    RERUN_QUEUE.get_or_init(|| RerunQueue {
+        due: Mutex::new(HashMap::new()),
+        wake: Condvar::new(),
+    })
+}
+
+/// Register a query whose results are re-sent on `channel` whenever a change could
+/// affect them. The initial result set is sent immediately.
+/// Returns a token for `unregister_live_query`.
+#[tauri::command]
+pub fn register_live_query(
+    workspace_path: String,
+    query: String,
+    channel: Channel<QueryResult>,
+) -> Result<String, String> {
+    let sink: ResultSink = Arc::new(move |result: &QueryResult| {
+        channel.send(result.clone()).map_err(|e| e.to_string())
+    });
+    register_live_query_with_sink(workspace_path, query, sink)
+}
+
+/// Stop delivering updates for a live query
+#[tauri::command]
+pub fn unregister_live_query(token: String) -> Result<bool, String> {
+    let mut queries = live_queries().lock().map_err(|e| e.to_string())?;
+    Ok(queries.remove(&token).is_some())
+}
+
+fn register_live_query_with_sink(
+    workspace_path: String,
+    query: String,
+    sink: ResultSink,
+) -> Result<String, String> {
+    {
+        let queries = live_queries().lock().map_err(|e| e.to_string())?;
+        if queries.len() >= MAX_LIVE_QUERIES {
+            return Err(format!(
+                "Too many live queries (limit {}); unregister one first",
+                MAX_LIVE_QUERIES
+            ));
+        }
+    }
+
+    let initial = run_query_macro(&workspace_path, &query)?;
+    sink(&initial)?;
+
+    let from_paths = query_service::parse_query_macro(&query)
+        .map(|m| m.query_filter.from.paths)
+        .unwrap_or_default();
+
+    let token = Uuid::new_v4().to_string();
+    let mut queries = live_queries().lock().map_err(|e| e.to_string())?;
+    if queries.len() >= MAX_LIVE_QUERIES {
+        return Err(format!(
+            "Too many live queries (limit {}); unregister one first",
+            MAX_LIVE_QUERIES
+        ));
+    }
+    queries.insert(
+        token.clone(),
+        LiveQuery {
+            workspace_path,
+            query_string: query,
+            from_paths,
+            sink,
+            last_sent: serde_json::to_string(&initial).ok(),
+        },
+    );
+
+    Ok(token)
+}
+
+/// Schedule a debounced re-run of the live queries a change could affect.
+///
+/// With `page_id`, only queries whose FROM patterns match that page's path are
+/// re-run; without it, every live query of the workspace is.
+pub fn notify_live_queries(workspace_path: &str, page_id: Option<&str>) {
+    {
+        let Ok(queries) = live_queries().lock() else {
+            return;
+        };
+        if !queries.values().any(|q| q.workspace_path == workspace_path) {
+            return;
+        }
+    }
+
+    let page_path = page_id.and_then(|id| lookup_page_path(workspace_path, id));
+
+    let Ok(mut queries) = live_queries().lock() else {
+        return;
+    };
+    for (token, live) in queries.iter_mut() {
+        if live.workspace_path != workspace_path {
+            continue;
+        }
+        let affected = match &page_path {
+            Some(path) => {
+                live.from_paths.is_empty()
+                    || live
+                        .from_paths
+                        .iter()
+                        .any(|pattern| matches_path_pattern(pattern, path))
+            }
+            // A page we cannot resolve (e.g. just deleted) may still have been in the result set
+            None => true,
+        };
+        if !affected {
+            continue;
+        }
+
+        schedule_rerun(token);
+    }
+}
+
+/// Push `token`'s re-run back to a full debounce window from now
+fn schedule_rerun(token: &str) {
+    RERUN_WORKER.call_once(|| {
+        std::thread::spawn(run_rerun_worker);
+    });
+
+    let queue = rerun_queue();
+    let Ok(mut due) = queue.due.lock() else {
+        return;
+    };
+    due.insert(token.to_string(), Instant::now() + LIVE_QUERY_DEBOUNCE);
+    queue.wake.notify_one();
+}
+
+/// The single thread that runs live query re-runs once their debounce window passes
+fn run_rerun_worker() {
+    let queue = rerun_queue();
+    let Ok(mut due) = queue.due.lock() else {
+        return;
+    };
+    loop {
+        let now = Instant::now();
+        let ready: Vec<String> = due
+            .iter()
+            .filter(|(_, at)| **at <= now)
+            .map(|(token, _)| token.clone())
+            .collect();
+
+        if !ready.is_empty() {
+            for token in &ready {
+                due.remove(token);
+            }
+            // Queries run unlocked so notifications keep coming in meanwhile
+            drop(due);
+            for token in &ready {
+                rerun_live_query(token);
+            }
+            due = match queue.due.lock() {
+                Ok(due) => due,
+                Err(_) => return,
+            };
+            continue;
+        }
+
+        let waited = match due.values().min().copied() {
+            Some(next) => queue
+                .wake
+                .wait_timeout(due, next - now)
+                .ok()
+                .map(|(due, _)| due),
+            None => queue.wake.wait(due).ok(),
+        };
+        let Some(waited) = waited else {
+            return;
+        };
+        due = waited;
+    }
+}
+
+fn lookup_page_path(workspace_path: &str, page_id: &str) -> Option<String> {
+    let conn = open_workspace_db(workspace_path).ok()?;
+    conn.query_row(
+        "SELECT path_text FROM page_paths WHERE page_id = ?",
+        [page_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+fn rerun_live_query(token: &str) {
+    // Snapshot what we need so the registry is not locked while the query runs
+    let (workspace_path, query_string, sink) = {
+        let Ok(queries) = live_queries().lock() else {
+            return;
+        };
+        match queries.get(token) {
+            Some(live) => (
+                live.workspace_path.clone(),
+                live.query_string.clone(),
+                live.sink.clone(),
+            ),
+            // Unregistered while the re-run was pending
+            None => return,
+        }
+    };
+
+    let result = match run_query_macro(&workspace_path, &query_string) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("[live_query] Failed to re-run live query {}: {}", token, e);
+            return;
+        }
+    };
+    let serialized = serde_json::to_string(&result).ok();
+
+    let Ok(mut queries) = live_queries().lock() else {
+        return;
+    };
+    let Some(live) = queries.get_mut(token) else {
+        return;
+    };
+    if serialized.is_some() && live.last_sent == serialized {
+        return;
+    }
+
+    if let Err(e) = sink(&result) {
+        // The receiving side is gone; stop tracking this query
+        eprintln!("[live_query] Dropping live query {}: {}", token, e);
+        queries.remove(token);
+        return;
+    }
+    live.last_sent = serialized;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_live_query_receives_updated_results() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_live_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('notes', 'Notes', 'Notes.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('notes', 'Notes');
+             INSERT INTO pages (id, title, file_path) VALUES ('other', 'Other', 'Other.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('other', 'Other');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'notes', 'first', 1.0);",
+        )
+        .unwrap();
+
+        let (tx, rx) = mpsc::channel::<QueryResult>();
+        let tx = Mutex::new(tx);
+        let sink: ResultSink = Arc::new(move |result: &QueryResult| {
+            tx.lock()
+                .unwrap()
+                .send(result.clone())
+                .map_err(|e| e.to_string())
+        });
+
+        let token = register_live_query_with_sink(
+            path_str.clone(),
+            "QUERY: FROM [Notes]".to_string(),
+            sink,
+        )
+        .unwrap();
+
+        let initial = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(initial.total_count, 1);
+
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b2', 'notes', 'second', 2.0)",
+            [],
+        )
+        .unwrap();
+
+        // A change on an unrelated page does not re-run the query, even though a re-run
+        // would now find a new result
+        notify_live_queries(&path_str, Some("other"));
+        assert!(matches!(
+            rx.recv_timeout(LIVE_QUERY_DEBOUNCE * 4),
+            Err(mpsc::RecvTimeoutError::Timeout)
+        ));
+
+        // A burst of notifications is coalesced into a single re-run
+        for _ in 0..5 {
+            notify_live_queries(&path_str, Some("notes"));
+        }
+
+        let updated = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(updated.total_count, 2);
+        assert!(updated.blocks.iter().any(|b| b.block.content == "second"));
+        assert!(matches!(
+            rx.recv_timeout(LIVE_QUERY_DEBOUNCE * 4),
+            Err(mpsc::RecvTimeoutError::Timeout)
+        ));
+
+        assert!(unregister_live_query(token).unwrap());
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
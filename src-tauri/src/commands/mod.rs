@@ -1,11 +1,16 @@
 pub mod block;
 pub mod db;
+pub mod embed;
 pub mod git;
 pub mod graph;
+pub mod journal;
+pub mod live_query;
 pub mod page;
 pub mod query;
 pub mod search;
+pub mod stats;
 pub mod todo;
+pub mod trash;
 pub mod wiki_link;
 pub mod workspace;
 
@@ -1,20 +1,70 @@
-use chrono::Utc;
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use uuid::Uuid;
 
-use crate::commands::workspace::open_workspace_db;
+use crate::commands::block::{
+    import_page_blocks_from_markdown, index_block_fts, query_blocks_for_page, sibling_keys_between,
+};
+use crate::commands::journal::{daily_note_titles, parse_journal_date, DEFAULT_DAILY_NOTES_PATH};
+use crate::commands::trash::move_page_to_trash;
+use crate::commands::workspace::{
+    load_file_naming, load_indent_style, load_sanitization_rules, open_workspace_db,
+    sync_workspace_with_progress,
+};
+use crate::config::{METADATA_DIR_NAME, TEMPLATES_DIR_NAME};
+use crate::models::block::Block;
 use crate::models::page::{CreatePageRequest, MovePageRequest, Page, UpdatePageRequest};
-use crate::services::file_sync::FileSyncService;
-use crate::utils::page_sync::sync_page_to_markdown;
+use crate::services::file_sync::{FileNaming, FileSyncService};
+use crate::services::block_history;
+use crate::services::page_path_service;
+use crate::services::wiki_link_index;
+use crate::services::wiki_link_parser::{rewrite_link_targets, rewrite_link_targets_with};
+use crate::utils::events::emit_page_changed;
+use crate::utils::fractional_index;
+use crate::utils::html::{blocks_to_html, page_anchor_key};
+use crate::utils::markdown::{
+    blocks_to_plain_markdown, frontmatter_value, normalize_marker_layout, parse_frontmatter,
+    plain_text, prepend_frontmatter, render_frontmatter, set_frontmatter_value, split_frontmatter,
+    strip_id_markers, IndentStyle, SanitizationRules,
+};
+use crate::utils::mermaid::blocks_to_mermaid_mindmap;
+use crate::utils::page_sync::{sync_page_to_markdown, update_page_file_metadata};
+use crate::utils::path::{ensure_rename_target_free, normalize_page_path};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetPageRequest {
     pub page_id: String,
 }
 
+/// Size figures for one page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageStats {
+    pub page_id: String,
+    pub block_count: usize,
+    /// Words in block content, not counting code, link markup or block references
+    pub word_count: usize,
+    /// Deepest nesting level (root blocks are 0)
+    pub max_depth: usize,
+    /// Latest `updated_at` of the page or any of its blocks, as RFC3339 in UTC
+    pub last_modified: Option<String>,
+}
+
+/// `PageStats` summed over every page of the workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspacePageStats {
+    pub page_count: usize,
+    pub block_count: usize,
+    pub word_count: usize,
+    /// Deepest nesting level on any page
+    pub max_depth: usize,
+    pub last_modified: Option<String>,
+}
+
 /// Create a new page
 #[tauri::command]
 pub async fn create_page(
@@ -22,12 +72,24 @@ pub async fn create_page(
     workspace_path: String,
     request: CreatePageRequest,
 ) -> Result<Page, String> {
-    let conn = open_workspace_db(&workspace_path)?;
+    let new_page = create_page_internal(&workspace_path, request).await?;
+
+    // Emit workspace changed event for git monitoring
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(new_page)
+}
+
+async fn create_page_internal(
+    workspace_path: &str,
+    request: CreatePageRequest,
+) -> Result<Page, String> {
+    let conn = open_workspace_db(workspace_path)?;
     let conn_mutex = Mutex::new(conn);
 
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
-    let file_sync = FileSyncService::new(&workspace_path);
+    let file_sync = FileSyncService::new(workspace_path);
 
     // 1. Check parent page validity (Read-only transaction)
     if let Some(parent_id) = &request.parent_id {
@@ -73,10 +135,12 @@ pub async fn create_page(
             .transaction()
             .map_err(|e| format!("Failed to start transaction: {}", e))?;
 
-        let res = tx.execute(
-            "INSERT INTO pages (id, title, parent_id, file_path, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)",
-            params![&id, &request.title, &request.parent_id, &rel_path, &now, &now],
-        );
+        let res = tx
+            .execute(
+                "INSERT INTO pages (id, title, parent_id, file_path, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)",
+                params![&id, &request.title, &request.parent_id, &rel_path, &now, &now],
+            )
+            .and_then(|_| page_path_service::update_page_path(&tx, &id, &rel_path));
 
         match res {
             Ok(_) => {
@@ -98,12 +162,7 @@ pub async fn create_page(
     }
 
     // Re-query to get full page object
-    let new_page = get_page_internal(&conn_mutex, &id)?;
-
-    // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
-
-    Ok(new_page)
+    get_page_internal(&conn_mutex, &id)
 }
 
 /// Get all pages
@@ -140,16 +199,373 @@ pub async fn get_pages(workspace_path: String) -> Result<Vec<Page>, String> {
     Ok(pages)
 }
 
+/// Pages whose frontmatter sets `key` to `value`, ordered by title. A list-valued key
+/// matches when any of its items equals `value`.
+#[tauri::command]
+pub async fn get_pages_by_metadata(
+    workspace_path: String,
+    key: String,
+    value: String,
+) -> Result<Vec<Page>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_pages_by_metadata(&conn, &key, &value)
+}
+
+fn find_pages_by_metadata(conn: &Connection, key: &str, value: &str) -> Result<Vec<Page>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, p.parent_id, p.file_path, p.is_directory, p.file_mtime,
+                    p.file_size, p.created_at, p.updated_at
+             FROM pages p
+             JOIN page_metadata m ON m.page_id = p.id
+             WHERE p.is_deleted = 0
+               AND m.key = ?1
+               AND (m.value = ?2
+                    OR EXISTS (
+                        SELECT 1 FROM json_each(
+                            CASE WHEN json_valid(m.value) AND json_type(m.value) = 'array'
+                                 THEN m.value ELSE '[]' END
+                        )
+                        WHERE json_each.value = ?2
+                    ))
+             ORDER BY p.title",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let pages = stmt
+        .query_map(params![key, value], |row| {
+            Ok(Page {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                parent_id: row.get(2)?,
+                file_path: row.get(3)?,
+                is_directory: row.get::<_, i32>(4)? != 0,
+                file_mtime: row.get(5)?,
+                file_size: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(pages)
+}
+
+/// The page's stored frontmatter metadata as a frontmatter block, in file order
+pub(crate) fn load_page_frontmatter(
+    conn: &Connection,
+    page_id: &str,
+) -> Result<Option<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM page_metadata WHERE page_id = ? ORDER BY rowid")
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map([page_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<(String, String)>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(render_frontmatter(&entries))
+}
+
+/// Replace a page's stored frontmatter metadata with what `markdown` carries
+pub(crate) fn save_page_frontmatter(
+    conn: &Connection,
+    page_id: &str,
+    markdown: &str,
+) -> Result<(), String> {
+    conn.execute("DELETE FROM page_metadata WHERE page_id = ?", [page_id])
+        .map_err(|e| e.to_string())?;
+
+    let Some(frontmatter) = split_frontmatter(markdown).0 else {
+        return Ok(());
+    };
+    for (key, value) in parse_frontmatter(frontmatter) {
+        conn.execute(
+            "INSERT OR REPLACE INTO page_metadata (page_id, key, value) VALUES (?, ?, ?)",
+            params![page_id, key, value],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Longest preview returned by `get_recent_pages`, in characters
+const RECENT_PAGE_PREVIEW_CHARS: usize = 120;
+
+/// A recently edited page with a plain-text preview of its first non-empty block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentPage {
+    #[serde(flatten)]
+    pub page: Page,
+    pub preview: String,
+}
+
+/// Most recently edited pages, newest first, for a "recently edited" list.
+/// Soft-deleted pages are skipped, as are directory notes with nothing beyond their title.
+#[tauri::command]
+pub async fn get_recent_pages(
+    workspace_path: String,
+    limit: usize,
+) -> Result<Vec<RecentPage>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_recent_pages(&conn, limit)
+}
+
+fn load_recent_pages(conn: &Connection, limit: usize) -> Result<Vec<RecentPage>, String> {
+    // Editing a block doesn't touch its page row, so a page counts as edited when any of
+    // its blocks was. Timestamps are compared through julianday() since rows carry both
+    // `CURRENT_TIMESTAMP` and RFC 3339 values.
+    let mut page_stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, p.parent_id, p.file_path, p.is_directory, p.file_mtime,
+                    p.file_size, p.created_at, p.updated_at
+             FROM pages p
+             LEFT JOIN (
+                SELECT page_id, MAX(julianday(updated_at)) AS edited
+                FROM blocks GROUP BY page_id
+             ) b ON b.page_id = p.id
+             WHERE p.is_deleted = 0
+             ORDER BY MAX(COALESCE(julianday(p.updated_at), 0), COALESCE(b.edited, 0)) DESC,
+                      p.title",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut block_stmt = conn
+        .prepare(
+            "SELECT content FROM blocks
+             WHERE page_id = ?
+             ORDER BY parent_id IS NOT NULL, order_key",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let pages = page_stmt
+        .query_map([], |row| {
+            Ok(Page {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                parent_id: row.get(2)?,
+                file_path: row.get(3)?,
+                is_directory: row.get::<_, i32>(4)? != 0,
+                file_mtime: row.get(5)?,
+                file_size: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut recent = Vec::new();
+    for page in pages {
+        if recent.len() >= limit {
+            break;
+        }
+        let page = page.map_err(|e| e.to_string())?;
+
+        let contents = block_stmt
+            .query_map([&page.id], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        let mut preview = String::new();
+        for content in contents {
+            let text = preview_text(&content.map_err(|e| e.to_string())?);
+            // An auto-created folder note only repeats the directory name
+            if text.is_empty() || (page.is_directory && text.eq_ignore_ascii_case(&page.title)) {
+                continue;
+            }
+            preview = text;
+            break;
+        }
+
+        if page.is_directory && preview.is_empty() {
+            continue;
+        }
+        recent.push(RecentPage { page, preview });
+    }
+
+    Ok(recent)
+}
+
+/// Block content as a reader sees it (`plain_text`), folded onto one line and cut to
+/// `RECENT_PAGE_PREVIEW_CHARS`
+fn preview_text(content: &str) -> String {
+    let folded = plain_text(content)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if folded.chars().count() > RECENT_PAGE_PREVIEW_CHARS {
+        format!(
+            "{}...",
+            folded
+                .chars()
+                .take(RECENT_PAGE_PREVIEW_CHARS)
+                .collect::<String>()
+        )
+    } else {
+        folded
+    }
+}
+
+// Fuzzy title scoring, in the spirit of fzf: every matched character scores, matches at
+// word starts and consecutive runs score extra, and gaps between matches cost points.
+const FUZZY_MATCH_SCORE: i64 = 16;
+const FUZZY_BOUNDARY_BONUS: i64 = 8;
+const FUZZY_CONSECUTIVE_BONUS: i64 = 4;
+const FUZZY_GAP_START_PENALTY: i64 = 3;
+const FUZZY_GAP_EXTENSION_PENALTY: i64 = 1;
+
+/// A page whose title fuzzily matches a quick-switcher query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickSwitchHit {
+    #[serde(flatten)]
+    pub page: Page,
+    pub score: i64,
+    /// Character positions in the title that matched the query, for highlighting
+    pub match_indices: Vec<usize>,
+}
+
+/// Pages whose title contains the query's characters in order (case-insensitive,
+/// whitespace in the query ignored), best match first and more recently edited
+/// first among equal scores. An empty query lists the most recently edited pages.
+#[tauri::command]
+pub async fn quick_switch_search(
+    workspace_path: String,
+    query: String,
+    limit: usize,
+) -> Result<Vec<QuickSwitchHit>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_quick_switch_hits(&conn, &query, limit)
+}
+
+fn find_quick_switch_hits(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<QuickSwitchHit>, String> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, parent_id, file_path, is_directory, file_mtime, file_size, created_at, updated_at
+             FROM pages
+             WHERE is_deleted = 0
+             ORDER BY updated_at DESC, title",
+        )
+        .map_err(|e| e.to_string())?;
+    let pages = stmt
+        .query_map([], |row| {
+            Ok(Page {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                parent_id: row.get(2)?,
+                file_path: row.get(3)?,
+                is_directory: row.get::<_, i32>(4)? != 0,
+                file_mtime: row.get(5)?,
+                file_size: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut hits = Vec::new();
+    for page in pages {
+        let page = page.map_err(|e| e.to_string())?;
+        if query.is_empty() {
+            hits.push(QuickSwitchHit {
+                page,
+                score: 0,
+                match_indices: Vec::new(),
+            });
+            continue;
+        }
+        if let Some((score, match_indices)) = fuzzy_match(&page.title, &query) {
+            hits.push(QuickSwitchHit {
+                page,
+                score,
+                match_indices,
+            });
+        }
+    }
+
+    // Stable sort: pages arrive newest first, so equal scores stay in recency order
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Score `title` against a non-empty `query`, returning the matched character
+/// positions, or `None` when the query is not a subsequence of the title. The match
+/// used is the tightest one ending where the first complete match ends.
+fn fuzzy_match(title: &str, query: &[char]) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = title.chars().collect();
+    let same = |a: char, b: char| a.to_lowercase().eq(b.to_lowercase());
+
+    let mut matched = 0;
+    let mut end = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if same(c, query[matched]) {
+            matched += 1;
+            if matched == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    // Walk back from the end so later occurrences win and the window is as short as
+    // possible ("ab" in "a_xab" matches the final "ab", not "a_x..b")
+    let mut positions = Vec::with_capacity(query.len());
+    let mut remaining = query.len();
+    for i in (0..=end).rev() {
+        if same(chars[i], query[remaining - 1]) {
+            positions.push(i);
+            remaining -= 1;
+            if remaining == 0 {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    for &pos in &positions {
+        score += FUZZY_MATCH_SCORE;
+        let at_boundary = pos == 0 || {
+            let before = chars[pos - 1];
+            !before.is_alphanumeric() || (before.is_lowercase() && chars[pos].is_uppercase())
+        };
+        if at_boundary {
+            score += FUZZY_BOUNDARY_BONUS;
+        }
+        match previous {
+            Some(prev) if pos == prev + 1 => score += FUZZY_CONSECUTIVE_BONUS,
+            Some(prev) => {
+                score -=
+                    FUZZY_GAP_START_PENALTY + (pos - prev - 2) as i64 * FUZZY_GAP_EXTENSION_PENALTY
+            }
+            None => {}
+        }
+        previous = Some(pos);
+    }
+
+    Some((score, positions))
+}
+
 /// Update page title
 #[tauri::command]
 pub async fn update_page_title(
     app: tauri::AppHandle,
     workspace_path: String,
     request: UpdatePageRequest,
-) -> Result<Page, String> {
+) -> Result<PageTitleUpdate, String> {
     let conn = open_workspace_db(&workspace_path)?;
     let conn_mutex = Mutex::new(conn);
     let now = Utc::now().to_rfc3339();
+    let mut touched_page_ids = Vec::new();
 
     if let Some(title) = &request.title {
         // Rename file first
@@ -158,19 +574,25 @@ pub async fn update_page_title(
             .rename_page_file(&conn_mutex, &request.id, title)
             .await?;
 
-        // Update DB
-        {
-            let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-            conn.execute(
-                "UPDATE pages SET title = ?, file_path = ?, updated_at = ? WHERE id = ?",
-                params![title, new_file_path, now, request.id],
-            )
-            .map_err(|e| e.to_string())?;
-        }
+        // Update DB and the links pointing at the page
+        let rewrite = {
+            let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            let naming = load_file_naming(&workspace_path);
+            let rewrite =
+                record_title_rename(&tx, &request.id, title, &new_file_path, naming, &now)?;
+            tx.commit().map_err(|e| e.to_string())?;
+            rewrite
+        };
 
         // Re-write file content to update title inside the file (if header is used)
         // Or just ensure sync
         sync_page_to_markdown(&conn_mutex, &workspace_path, &request.id).await?;
+        for page_id in &rewrite.touched_page_ids {
+            sync_page_to_markdown(&conn_mutex, &workspace_path, page_id).await?;
+            emit_page_changed(&app, &workspace_path, page_id);
+        }
+        touched_page_ids = rewrite.touched_page_ids;
     } else {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.execute(
@@ -183,7 +605,45 @@ pub async fn update_page_title(
     // Emit workspace changed event for git monitoring
     crate::utils::events::emit_workspace_changed(&app, &workspace_path);
 
-    get_page_internal(&conn_mutex, &request.id)
+    Ok(PageTitleUpdate {
+        page: get_page_internal(&conn_mutex, &request.id)?,
+        touched_page_ids,
+    })
+}
+
+/// A renamed page plus the pages whose links to it were rewritten
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageTitleUpdate {
+    #[serde(flatten)]
+    pub page: Page,
+    pub touched_page_ids: Vec<String>,
+}
+
+/// Point the page row at its renamed file and rewrite every link to it
+fn record_title_rename(
+    conn: &Connection,
+    page_id: &str,
+    title: &str,
+    new_file_path: &str,
+    naming: FileNaming,
+    now: &str,
+) -> Result<WikiLinkRewriteResult, String> {
+    conn.execute(
+        "UPDATE pages SET title = ?, file_path = ?, updated_at = ? WHERE id = ?",
+        params![title, new_file_path, now, page_id],
+    )
+    .map_err(|e| e.to_string())?;
+    page_path_service::update_page_path(conn, page_id, new_file_path)
+        .map_err(|e| e.to_string())?;
+
+    // Slug-named pages are linked by title, not by file name
+    let new_stem = file_stem_of(new_file_path);
+    let link_name = match naming {
+        FileNaming::Title => new_stem.as_str(),
+        FileNaming::Slug => title,
+    };
+    rewrite_inbound_links(conn, page_id, link_name, now)
 }
 
 /// Delete a page
@@ -201,45 +661,1632 @@ pub async fn delete_page(
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.query_row(
             "SELECT COUNT(*) FROM pages WHERE parent_id = ?",
-            [&page_id],
+            [&page_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?
+    };
+
+    if children_count > 0 {
+        return Err("Cannot delete page with children".to_string());
+    }
+
+    // Move the file to the trash (restorable with `restore_from_trash`), then delete
+    // from DB (Cascade will handle blocks, but we do it explicitly to be safe)
+    {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        move_page_to_trash(&conn, &workspace_path, &page_id)?;
+        conn.execute("DELETE FROM pages WHERE id = ?", [&page_id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    // Emit workspace changed event for git monitoring
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(page_id)
+}
+
+/// Pages left soft-deleted (`is_deleted = 1`) by a path deletion whose filesystem
+/// step failed, most recently deleted first
+#[tauri::command]
+pub async fn list_soft_deleted_pages(workspace_path: String) -> Result<Vec<Page>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, parent_id, file_path, is_directory, file_mtime, file_size, created_at, updated_at
+             FROM pages
+             WHERE is_deleted = 1
+             ORDER BY updated_at DESC, title",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let pages = stmt
+        .query_map([], |row| {
+            Ok(Page {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                parent_id: row.get(2)?,
+                file_path: row.get(3)?,
+                is_directory: row.get::<_, i32>(4)? != 0,
+                file_mtime: row.get(5)?,
+                file_size: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(pages)
+}
+
+/// Bring back a soft-deleted page: clear its deleted flag and rewrite its markdown
+/// file from the blocks still in the DB. Refused when a file already exists at the
+/// page's path.
+#[tauri::command]
+pub async fn restore_soft_deleted_page(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+) -> Result<Page, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        undelete_page(&conn, &workspace_path, &page_id)?;
+    }
+
+    if let Err(e) = sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await {
+        // Without its file the page would vanish on the next sync; keep it in the trash
+        let conn = conn_mutex.lock().map_err(|err| err.to_string())?;
+        conn.execute("UPDATE pages SET is_deleted = 1 WHERE id = ?", [&page_id])
+            .map_err(|err| err.to_string())?;
+        return Err(format!("Failed to restore page file: {}", e));
+    }
+
+    let page = get_page_internal(&conn_mutex, &page_id)?;
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(page)
+}
+
+/// Clear the deleted flag of a soft-deleted page after checking its file path is free
+fn undelete_page(conn: &Connection, workspace_path: &str, page_id: &str) -> Result<(), String> {
+    let (file_path, is_deleted): (Option<String>, bool) = conn
+        .query_row(
+            "SELECT file_path, is_deleted FROM pages WHERE id = ?",
+            [page_id],
+            |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Page not found: {}", page_id))?;
+
+    if !is_deleted {
+        return Err("Page is not deleted".to_string());
+    }
+    let file_path = file_path.ok_or_else(|| "Page has no file path to restore".to_string())?;
+    let full_path = std::path::Path::new(workspace_path).join(&file_path);
+    if full_path.exists() {
+        return Err(format!("A file already exists at {}", file_path));
+    }
+
+    conn.execute(
+        "UPDATE pages SET is_deleted = 0, updated_at = ? WHERE id = ?",
+        params![Utc::now().to_rfc3339(), page_id],
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            format!("Another page already uses {}", file_path)
+        }
+        e => e.to_string(),
+    })?;
+    Ok(())
+}
+
+/// Duplicate a page as a template: a new page titled `new_title`, next to the original,
+/// holding a copy of every block under fresh IDs
+#[tauri::command]
+pub async fn duplicate_page(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+    new_title: String,
+) -> Result<Page, String> {
+    let new_title = new_title.trim();
+    if new_title.is_empty() {
+        return Err("Page title cannot be empty".to_string());
+    }
+
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    let source = get_page_internal(&conn_mutex, &page_id)?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let file_sync = FileSyncService::new(&workspace_path);
+
+    let (abs_path, rel_path) = file_sync
+        .prepare_new_page_file(&conn_mutex, source.parent_id.as_deref(), new_title)
+        .await
+        .map_err(|e| format!("Failed to create page file: {}", e))?;
+
+    // file_mtime/file_size are left unset; the markdown sync below records the new file's.
+    // If this fails, the created file is removed again
+    let insert_result = {
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        conn.transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))
+            .and_then(|tx| {
+                tx.execute(
+                    "INSERT INTO pages (id, title, parent_id, file_path, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)",
+                    params![&id, new_title, &source.parent_id, &rel_path, &now, &now],
+                )
+                .and_then(|_| page_path_service::update_page_path(&tx, &id, &rel_path))
+                .map_err(|e| format!("Failed to insert page: {}", e))?;
+                copy_page_blocks(&tx, &page_id, &id)?;
+                tx.commit()
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))
+            })
+    };
+
+    if let Err(e) = insert_result {
+        if abs_path.exists() {
+            let _ = tokio::fs::remove_file(&abs_path).await;
+        }
+        return Err(e);
+    }
+
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &id).await?;
+
+    let new_page = get_page_internal(&conn_mutex, &id)?;
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(new_page)
+}
+
+/// Names of the page templates in `.oxinot/templates/` (their `.md` file stems), sorted
+#[tauri::command]
+pub async fn list_templates(workspace_path: String) -> Result<Vec<String>, String> {
+    let dir = templates_dir(&workspace_path);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read templates directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Create a page titled `title` whose blocks come from the template `template_name`.
+///
+/// `{{title}}`, `{{date}}` (YYYY-MM-DD) and `{{time}}` (HH:MM) are substituted first.
+/// `ID::` markers in the template are dropped so every block gets a fresh ID, while
+/// `key::value` metadata lines are kept as block metadata. The template's frontmatter
+/// becomes the page's, alongside any keys the new page file already had.
+#[tauri::command]
+pub async fn create_page_from_template(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    title: String,
+    template_name: String,
+    parent_id: Option<String>,
+) -> Result<Page, String> {
+    let page =
+        create_page_from_template_internal(&workspace_path, &title, &template_name, parent_id)
+            .await?;
+
+    emit_page_changed(&app, &workspace_path, &page.id);
+
+    Ok(page)
+}
+
+async fn create_page_from_template_internal(
+    workspace_path: &str,
+    title: &str,
+    template_name: &str,
+    parent_id: Option<String>,
+) -> Result<Page, String> {
+    if template_name.is_empty()
+        || template_name.starts_with('.')
+        || template_name.contains(['/', '\\'])
+    {
+        return Err(format!("Invalid template name: {}", template_name));
+    }
+    let template_path = templates_dir(workspace_path).join(format!("{}.md", template_name));
+    let template = std::fs::read_to_string(&template_path)
+        .map_err(|e| format!("Failed to read template {}: {}", template_name, e))?;
+
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err("Page title cannot be empty".to_string());
+    }
+    let indent = load_indent_style(workspace_path);
+    let now = Local::now();
+    let rendered = render_template(
+        &template,
+        &title,
+        &now.format("%Y-%m-%d").to_string(),
+        &now.format("%H:%M").to_string(),
+    );
+    let markdown = normalize_marker_layout(&strip_id_markers(&rendered), indent);
+
+    let page = create_page_internal(
+        workspace_path,
+        CreatePageRequest {
+            title,
+            parent_id,
+            file_path: None,
+        },
+    )
+    .await?;
+
+    // Either the page ends up filled from the template or it is removed again
+    fill_page_from_template(workspace_path, &page, &rendered, &markdown, indent).await
+}
+
+/// Import the rendered template into the freshly created `page`. On failure the page
+/// row, its search entries and its file are deleted, so no half-filled page is left.
+async fn fill_page_from_template(
+    workspace_path: &str,
+    page: &Page,
+    rendered: &str,
+    markdown: &str,
+    indent: IndentStyle,
+) -> Result<Page, String> {
+    let conn = open_workspace_db(workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    let filled = async {
+        {
+            let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+            let rules = load_sanitization_rules(workspace_path);
+            import_page_blocks_from_markdown(&mut conn, &page.id, markdown, indent, &rules)?;
+            if let (Some(frontmatter), Some(file_path)) =
+                (split_frontmatter(rendered).0, page.file_path.as_deref())
+            {
+                // The full rewrite below keeps the file's frontmatter, so put the template's there
+                let page_file = std::path::Path::new(workspace_path).join(file_path);
+                let existing = std::fs::read_to_string(&page_file).unwrap_or_default();
+                let (existing_frontmatter, body) = split_frontmatter(&existing);
+                let mut content = prepend_frontmatter(Some(frontmatter), body);
+                let existing_entries = existing_frontmatter.map(parse_frontmatter);
+                for (key, value) in existing_entries.unwrap_or_default() {
+                    if frontmatter_value(&content, &key).is_none() {
+                        content = set_frontmatter_value(&content, &key, &value);
+                    }
+                }
+                std::fs::write(&page_file, &content)
+                    .map_err(|e| format!("Failed to write page frontmatter: {}", e))?;
+                save_page_frontmatter(&conn, &page.id, &content)?;
+            }
+        }
+        sync_page_to_markdown(&conn_mutex, workspace_path, &page.id).await?;
+        get_page_internal(&conn_mutex, &page.id)
+    }
+    .await;
+
+    if filled.is_err() {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM blocks_fts WHERE page_id = ?", [&page.id])
+            .and_then(|_| conn.execute("DELETE FROM pages WHERE id = ?", [&page.id]))
+            .map_err(|e| e.to_string())?;
+        if let Some(file_path) = page.file_path.as_deref() {
+            let _ = std::fs::remove_file(std::path::Path::new(workspace_path).join(file_path));
+        }
+    }
+    filled
+}
+
+/// Template applied to new daily notes when `.oxinot/templates/daily.md` exists
+const DAILY_TEMPLATE_NAME: &str = "daily";
+
+/// The daily note for `date` (`YYYY-MM-DD`), created if it does not exist yet.
+///
+/// Daily notes live at `{daily_notes_path}/{YYYY}/{MM}/{YYYY-MM-DD}` like in
+/// `get_journal_entries`; missing folder pages on the way are created as directories.
+/// A new note starts from the `daily` template when there is one. Calling it again
+/// for the same date returns the existing note, and a note file that exists on disk
+/// but was never indexed is picked up by a sync instead of failing.
+#[tauri::command]
+pub async fn get_or_create_daily_note(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    date: String,
+    daily_notes_path: Option<String>,
+) -> Result<Page, String> {
+    let (page, changed) =
+        get_or_create_daily_note_internal(&workspace_path, &date, daily_notes_path).await?;
+    if changed {
+        crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    }
+    Ok(page)
+}
+
+/// The daily note, plus whether any page on the way had to be created or converted
+async fn get_or_create_daily_note_internal(
+    workspace_path: &str,
+    date: &str,
+    daily_notes_path: Option<String>,
+) -> Result<(Page, bool), String> {
+    let date = parse_journal_date(date)?;
+    let base = daily_notes_path.unwrap_or_else(|| DEFAULT_DAILY_NOTES_PATH.to_string());
+    let titles = daily_note_titles(&base, date);
+    let has_template = templates_dir(workspace_path)
+        .join(format!("{}.md", DAILY_TEMPLATE_NAME))
+        .is_file();
+
+    let mut changed = false;
+    let mut parent_id: Option<String> = None;
+    for (i, title) in titles.iter().enumerate() {
+        let is_note = i == titles.len() - 1;
+        let existing = find_or_index_child_page(workspace_path, parent_id.as_deref(), title)?;
+
+        let page = match existing {
+            Some(page) => page,
+            None if is_note && has_template => {
+                changed = true;
+                create_page_from_template_internal(
+                    workspace_path,
+                    title,
+                    DAILY_TEMPLATE_NAME,
+                    parent_id.clone(),
+                )
+                .await?
+            }
+            None => {
+                changed = true;
+                let request = CreatePageRequest {
+                    title: title.clone(),
+                    parent_id: parent_id.clone(),
+                    file_path: None,
+                };
+                create_page_internal(workspace_path, request).await?
+            }
+        };
+
+        if is_note {
+            return Ok((page, changed));
+        }
+        let page = if page.is_directory {
+            page
+        } else {
+            changed = true;
+            convert_page_to_directory_internal(workspace_path, &page.id).await?
+        };
+        parent_id = Some(page.id);
+    }
+
+    Err("Daily note path is empty".to_string())
+}
+
+/// The live page titled `title` under `parent_id` (None: the workspace root). When the
+/// DB has none but a matching file is on disk, the workspace is synced and looked up
+/// again, so files created outside the app are reused instead of colliding.
+fn find_or_index_child_page(
+    workspace_path: &str,
+    parent_id: Option<&str>,
+    title: &str,
+) -> Result<Option<Page>, String> {
+    let find = || -> Result<Option<Page>, String> {
+        let conn = open_workspace_db(workspace_path)?;
+        let page_id: Option<String> = conn
+            .query_row(
+                "SELECT id FROM pages WHERE parent_id IS ? AND title = ? AND is_deleted = 0",
+                params![parent_id, title],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        page_id
+            .map(|id| get_page_internal(&Mutex::new(conn), &id))
+            .transpose()
+    };
+
+    if let Some(page) = find()? {
+        return Ok(Some(page));
+    }
+
+    let parent_dir = match parent_id {
+        Some(parent_id) => {
+            let conn = Mutex::new(open_workspace_db(workspace_path)?);
+            let parent = get_page_internal(&conn, parent_id)?;
+            let parent_file = parent.file_path.unwrap_or_default();
+            let parent_file = std::path::Path::new(workspace_path).join(parent_file);
+            match parent_file.parent() {
+                Some(dir) => dir.to_path_buf(),
+                None => return Ok(None),
+            }
+        }
+        None => std::path::PathBuf::from(workspace_path),
+    };
+    let name = load_file_naming(workspace_path).file_stem(title);
+    let on_disk = parent_dir.join(format!("{}.md", name)).exists()
+        || parent_dir.join(&name).join(format!("{}.md", name)).exists();
+    if !on_disk {
+        return Ok(None);
+    }
+
+    sync_workspace_with_progress(workspace_path, None, &mut |_| {})?;
+    find()
+}
+
+fn templates_dir(workspace_path: &str) -> std::path::PathBuf {
+    std::path::Path::new(workspace_path)
+        .join(METADATA_DIR_NAME)
+        .join(TEMPLATES_DIR_NAME)
+}
+
+/// Substitute the `{{title}}`, `{{date}}` and `{{time}}` placeholders; any other
+/// `{{...}}` is left as written
+fn render_template(template: &str, title: &str, date: &str, time: &str) -> String {
+    template
+        .replace("{{title}}", title)
+        .replace("{{date}}", date)
+        .replace("{{time}}", time)
+}
+
+/// Copy every block of `source_page_id` into `new_page_id` under fresh IDs, keeping the
+/// hierarchy, order weights, types and metadata. `((id))` refs between blocks of the page
+/// are pointed at the copies. FTS and wiki-link indexes are filled for the new blocks.
+/// Returns the number of blocks copied.
+fn copy_page_blocks(
+    conn: &Connection,
+    source_page_id: &str,
+    new_page_id: &str,
+) -> Result<usize, String> {
+    // Parents before children, so each copy's parent already exists
+    let source_blocks: Vec<(String, Option<String>, String)> = {
+        let mut stmt = conn
+            .prepare(
+                "WITH RECURSIVE tree(id, depth) AS (
+                    SELECT id, 0 FROM blocks WHERE page_id = ?1 AND parent_id IS NULL
+                    UNION ALL
+                    SELECT b.id, t.depth + 1 FROM blocks b JOIN tree t ON b.parent_id = t.id
+                    WHERE b.page_id = ?1
+                 )
+                 SELECT b.id, b.parent_id, b.content
+                 FROM tree t JOIN blocks b ON b.id = t.id
+                 ORDER BY t.depth, b.order_key",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([source_page_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    let new_ids: HashMap<&str, String> = source_blocks
+        .iter()
+        .map(|(id, _, _)| (id.as_str(), Uuid::new_v4().to_string()))
+        .collect();
+    let now = Utc::now().to_rfc3339();
+
+    for (old_id, old_parent_id, content) in &source_blocks {
+        let new_id = &new_ids[old_id.as_str()];
+        let new_parent_id = old_parent_id
+            .as_deref()
+            .and_then(|parent| new_ids.get(parent));
+
+        let mut new_content = content.clone();
+        for (from, to) in &new_ids {
+            let old_ref = format!("(({}))", from);
+            if new_content.contains(&old_ref) {
+                new_content = new_content.replace(&old_ref, &format!("(({}))", to));
+            }
+        }
+
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight, order_key,
+                                 is_collapsed, block_type, language, created_at, updated_at)
+             SELECT ?1, ?2, ?3, ?4, order_weight, order_key, is_collapsed, block_type, language, ?5, ?5
+             FROM blocks WHERE id = ?6",
+            params![
+                new_id,
+                new_page_id,
+                new_parent_id,
+                &new_content,
+                &now,
+                old_id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        let metadata: Vec<(String, String)> = {
+            let mut stmt = conn
+                .prepare("SELECT key, value FROM block_metadata WHERE block_id = ?")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([old_id], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            rows
+        };
+        for (key, value) in metadata {
+            conn.execute(
+                "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, ?, ?)",
+                params![Uuid::new_v4().to_string(), new_id, key, value],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        index_block_fts(conn, new_id, new_page_id, &new_content)?;
+        wiki_link_index::index_block_links(conn, new_id, &new_content, new_page_id)
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(source_blocks.len())
+}
+
+/// Get a single page
+#[tauri::command]
+pub async fn get_page(
+    workspace_path: String,
+    request: GetPageRequest,
+) -> Result<Option<Page>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    match get_page_internal(&conn_mutex, &request.page_id) {
+        Ok(page) => Ok(Some(page)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Export a page's block tree as a Mermaid mindmap diagram
+#[tauri::command]
+pub async fn export_page_mermaid(
+    workspace_path: String,
+    page_id: String,
+) -> Result<String, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    let page = get_page_internal(&conn_mutex, &page_id)?;
+
+    let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+    let blocks = query_blocks_for_page(&conn, &page_id)?;
+
+    Ok(blocks_to_mermaid_mindmap(&page.title, &blocks))
+}
+
+/// Export a page as shareable markdown without the hidden `ID::` and metadata lines.
+/// With `frontmatter`, the page's block metadata becomes a YAML frontmatter block.
+#[tauri::command]
+pub async fn export_page_to_plain_markdown(
+    workspace_path: String,
+    page_id: String,
+    frontmatter: Option<bool>,
+) -> Result<String, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    get_page_internal(&conn_mutex, &page_id)?;
+
+    let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+    let blocks = query_blocks_for_page(&conn, &page_id)?;
+
+    Ok(blocks_to_plain_markdown(
+        &blocks,
+        load_indent_style(&workspace_path),
+        frontmatter.unwrap_or(false),
+    ))
+}
+
+/// Export a page as a standalone HTML document of nested lists.
+/// Wiki links back to the exported page resolve to its in-document anchor.
+#[tauri::command]
+pub async fn export_page_to_html(
+    workspace_path: String,
+    page_id: String,
+) -> Result<String, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    let page = get_page_internal(&conn_mutex, &page_id)?;
+
+    let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+    let blocks = query_blocks_for_page(&conn, &page_id)?;
+
+    let anchor = format!("page-{}", page.id);
+    let mut page_anchors = HashMap::new();
+    page_anchors.insert(page_anchor_key(&page.title), anchor.clone());
+    if let Some(file_path) = &page.file_path {
+        page_anchors.insert(page_anchor_key(file_path), anchor.clone());
+    }
+
+    Ok(blocks_to_html(&page.title, &anchor, &blocks, &page_anchors))
+}
+
+/// Block count, word count, nesting depth and last-modified time of a page
+#[tauri::command]
+pub async fn get_page_stats(workspace_path: String, page_id: String) -> Result<PageStats, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_page_stats(&conn, Some(&page_id))?
+        .pop()
+        .ok_or_else(|| format!("Page not found: {}", page_id))
+}
+
+/// `get_page_stats` aggregated over all pages of the workspace
+#[tauri::command]
+pub async fn get_workspace_page_stats(
+    workspace_path: String,
+) -> Result<WorkspacePageStats, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let pages = load_page_stats(&conn, None)?;
+
+    let mut totals = WorkspacePageStats {
+        page_count: pages.len(),
+        ..Default::default()
+    };
+    for page in pages {
+        totals.block_count += page.block_count;
+        totals.word_count += page.word_count;
+        totals.max_depth = totals.max_depth.max(page.max_depth);
+        totals.last_modified = totals.last_modified.max(page.last_modified);
+    }
+    Ok(totals)
+}
+
+/// Stats for one page, or for every live page when `page_id` is None (ordered by title)
+fn load_page_stats(conn: &Connection, page_id: Option<&str>) -> Result<Vec<PageStats>, String> {
+    let code_re = regex::Regex::new(r"(?s)```.*?```|`[^`]*`").map_err(|e| e.to_string())?;
+
+    let mut stats: Vec<PageStats> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, updated_at FROM pages
+                 WHERE is_deleted = 0 AND (?1 IS NULL OR id = ?1)
+                 ORDER BY title",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([page_id], |row| {
+                Ok(PageStats {
+                    page_id: row.get(0)?,
+                    block_count: 0,
+                    word_count: 0,
+                    max_depth: 0,
+                    last_modified: row.get(1)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    // Compared as times: stored values mix RFC3339 offsets and SQLite's default format
+    let mut latest: Vec<Option<DateTime<Utc>>> = stats
+        .iter()
+        .map(|page| page.last_modified.as_deref().and_then(parse_timestamp))
+        .collect();
+    let index: HashMap<String, usize> = stats
+        .iter()
+        .enumerate()
+        .map(|(i, page)| (page.page_id.clone(), i))
+        .collect();
+
+    // (page_id, parent_id) per block, for depths once every block is known
+    let mut parents: HashMap<String, (String, Option<String>)> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare(
+                "SELECT b.id, b.page_id, b.parent_id, b.content, b.block_type, b.updated_at
+                 FROM blocks b JOIN pages p ON p.id = b.page_id
+                 WHERE p.is_deleted = 0 AND (?1 IS NULL OR b.page_id = ?1)",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut rows = stmt.query([page_id]).map_err(|e| e.to_string())?;
+
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let id: String = row.get(0).map_err(|e| e.to_string())?;
+            let block_page_id: String = row.get(1).map_err(|e| e.to_string())?;
+            let parent_id: Option<String> = row.get(2).map_err(|e| e.to_string())?;
+            let content: String = row.get(3).map_err(|e| e.to_string())?;
+            let block_type: Option<String> = row.get(4).map_err(|e| e.to_string())?;
+            let updated_at: Option<String> = row.get(5).map_err(|e| e.to_string())?;
+
+            let Some(&i) = index.get(&block_page_id) else {
+                continue;
+            };
+            let page = &mut stats[i];
+            page.block_count += 1;
+            if !matches!(block_type.as_deref(), Some("code") | Some("fence")) {
+                page.word_count += count_words(&content, &code_re);
+            }
+            latest[i] = latest[i].max(updated_at.as_deref().and_then(parse_timestamp));
+            parents.insert(id, (block_page_id, parent_id));
+        }
+    }
+
+    // Walk up each block's parents; the step cap guards against a corrupt cycle
+    for (page_id, parent_id) in parents.values() {
+        let mut depth = 0;
+        let mut current = parent_id.clone();
+        while let Some(parent) = current {
+            if depth >= parents.len() {
+                break;
+            }
+            depth += 1;
+            current = parents.get(&parent).and_then(|(_, p)| p.clone());
+        }
+        let page = &mut stats[index[page_id]];
+        page.max_depth = page.max_depth.max(depth);
+    }
+
+    for (page, latest) in stats.iter_mut().zip(latest) {
+        page.last_modified = latest.map(|t| t.to_rfc3339());
+    }
+    Ok(stats)
+}
+
+/// Words in block content as a reader sees it (`plain_text`), with code spans dropped
+fn count_words(content: &str, code_re: &regex::Regex) -> usize {
+    plain_text(&code_re.replace_all(content, " "))
+        .split_whitespace()
+        .filter(|word| word.chars().any(|c| c.is_alphanumeric()))
+        .count()
+}
+
+/// Parse an RFC3339 timestamp, or SQLite's `CURRENT_TIMESTAMP` format (UTC)
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|t| t.and_utc()))
+        .ok()
+}
+
+/// Rewrite a page file so every bullet's hidden lines follow the canonical layout
+/// (`ID::` marker first, then metadata, at body indent), then re-import the page's
+/// blocks so metadata that was previously dropped reaches the database.
+/// Returns false if the file was already normalized.
+#[tauri::command]
+pub async fn normalize_block_marker_layout(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+) -> Result<bool, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+    let page = get_page_internal(&conn_mutex, &page_id)?;
+    if page.is_directory {
+        return Err(format!("Page {} is a directory", page_id));
+    }
+    let rel_path = page
+        .file_path
+        .ok_or_else(|| format!("Page {} has no file", page_id))?;
+    let full_path = std::path::Path::new(&workspace_path).join(&rel_path);
+
+    let content = tokio::fs::read_to_string(&full_path)
+        .await
+        .map_err(|e| format!("Failed to read page file: {}", e))?;
+    let indent = load_indent_style(&workspace_path);
+    let normalized = normalize_marker_layout(&content, indent);
+    if normalized == content {
+        return Ok(false);
+    }
+
+    tokio::fs::write(&full_path, &normalized)
+        .await
+        .map_err(|e| format!("Failed to write page file: {}", e))?;
+
+    {
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        // Only the marker layout changes; the content stays as the file has it
+        import_page_blocks_from_markdown(
+            &mut conn,
+            &page_id,
+            &normalized,
+            indent,
+            &SanitizationRules::default(),
+        )?;
+    }
+    update_page_file_metadata(&conn_mutex, &full_path, &page_id).await?;
+
+    emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(true)
+}
+
+/// A reference from another page to a block removed by `replace_page_content`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenBlockRef {
+    pub from_block_id: String,
+    pub from_page_id: String,
+    pub to_block_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacePageContentResult {
+    pub blocks: Vec<Block>,
+    pub broken_refs: Vec<BrokenBlockRef>,
+}
+
+/// Replace all blocks of a page with the block tree parsed from `markdown`.
+///
+/// The page row and file path are kept, so links to the page still resolve. `ID::`
+/// markers in the markdown are preserved; bullets without one get fresh IDs. Blocks
+/// that disappear are reported with any references other pages hold to them.
+#[tauri::command]
+pub async fn replace_page_content(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+    markdown: String,
+) -> Result<ReplacePageContentResult, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    let result =
+        replace_page_content_internal(&conn_mutex, &workspace_path, &page_id, &markdown).await?;
+
+    emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(result)
+}
+
+async fn replace_page_content_internal(
+    conn_mutex: &Mutex<Connection>,
+    workspace_path: &str,
+    page_id: &str,
+    markdown: &str,
+) -> Result<ReplacePageContentResult, String> {
+    let page = get_page_internal(conn_mutex, page_id)?;
+    if page.is_directory {
+        return Err(format!("Page {} is a directory", page_id));
+    }
+
+    let (import, broken_refs) = {
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let import = import_page_blocks_from_markdown(
+            &mut conn,
+            page_id,
+            markdown,
+            load_indent_style(workspace_path),
+            &load_sanitization_rules(workspace_path),
+        )?;
+        let broken_refs = find_inbound_block_refs(&conn, page_id, &import.removed_block_ids)?;
+        (import, broken_refs)
+    };
+
+    sync_page_to_markdown(conn_mutex, workspace_path, page_id).await?;
+
+    Ok(ReplacePageContentResult {
+        blocks: import.blocks,
+        broken_refs,
+    })
+}
+
+/// Find `((block-id))` refs and `[[Page#^block-id]]` links on other pages pointing at `block_ids`
+fn find_inbound_block_refs(
+    conn: &Connection,
+    page_id: &str,
+    block_ids: &[String],
+) -> Result<Vec<BrokenBlockRef>, String> {
+    let mut broken = Vec::new();
+
+    for block_id in block_ids {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, page_id FROM blocks
+                 WHERE page_id != ?1 AND instr(content, '((' || ?2 || '))') > 0
+                 UNION
+                 SELECT from_block_id, from_page_id FROM wiki_links
+                 WHERE from_page_id != ?1 AND block_ref = ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let refs = stmt
+            .query_map(params![page_id, block_id], |row| {
+                Ok(BrokenBlockRef {
+                    from_block_id: row.get(0)?,
+                    from_page_id: row.get(1)?,
+                    to_block_id: block_id.clone(),
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        broken.extend(refs);
+    }
+
+    Ok(broken)
+}
+
+/// Which side wins when a page's title and filename disagree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ReconcilePreference {
+    /// Rename the file after the title, rewriting links that pointed at the old name
+    #[serde(rename = "title")]
+    Title,
+    /// Set the title from the filename
+    #[serde(rename = "filename")]
+    Filename,
+}
+
+/// A page whose title does not match its file name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleFilenameMismatch {
+    pub page_id: String,
+    pub title: String,
+    pub file_path: String,
+    /// File name without the `.md` extension
+    pub file_stem: String,
+}
+
+/// Make a page's title and file name agree again
+#[tauri::command]
+pub async fn reconcile_title_filename(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    page_id: String,
+    prefer: ReconcilePreference,
+) -> Result<Page, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    let page = reconcile_page_internal(&conn_mutex, &workspace_path, &page_id, prefer).await?;
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(page)
+}
+
+/// Report every page whose title and file name disagree.
+/// Unless `dry_run`, each file page is then renamed after its title;
+/// directory pages are only reported.
+#[tauri::command]
+pub async fn reconcile_all(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    dry_run: bool,
+) -> Result<Vec<TitleFilenameMismatch>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let mismatches = find_title_filename_mismatches(&conn, load_file_naming(&workspace_path))?;
+    if dry_run || mismatches.is_empty() {
+        return Ok(mismatches);
+    }
+
+    let directory_pages: HashSet<String> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM pages WHERE is_directory = 1")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        ids
+    };
+
+    let conn_mutex = Mutex::new(conn);
+    for mismatch in &mismatches {
+        if directory_pages.contains(&mismatch.page_id) {
+            continue;
+        }
+        reconcile_page_internal(
+            &conn_mutex,
+            &workspace_path,
+            &mismatch.page_id,
+            ReconcilePreference::Title,
+        )
+        .await?;
+    }
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(mismatches)
+}
+
+fn find_title_filename_mismatches(
+    conn: &Connection,
+    naming: FileNaming,
+) -> Result<Vec<TitleFilenameMismatch>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, file_path FROM pages
+             WHERE file_path IS NOT NULL AND is_deleted = 0
+             ORDER BY file_path",
+        )
+        .map_err(|e| e.to_string())?;
+    let pages = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(pages
+        .into_iter()
+        .filter_map(|(page_id, title, file_path)| {
+            let file_stem = file_stem_of(&file_path);
+            (!naming.matches_stem(&file_stem, &title)).then_some(TitleFilenameMismatch {
+                page_id,
+                title,
+                file_path,
+                file_stem,
+            })
+        })
+        .collect())
+}
+
+fn file_stem_of(file_path: &str) -> String {
+    std::path::Path::new(file_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+async fn reconcile_page_internal(
+    conn_mutex: &Mutex<Connection>,
+    workspace_path: &str,
+    page_id: &str,
+    prefer: ReconcilePreference,
+) -> Result<Page, String> {
+    let page = get_page_internal(conn_mutex, page_id)?;
+    let Some(file_path) = page.file_path.clone() else {
+        return Err(format!("Page {} has no file", page_id));
+    };
+    let file_stem = file_stem_of(&file_path);
+    let naming = load_file_naming(workspace_path);
+    if naming.matches_stem(&file_stem, &page.title) {
+        return Ok(page);
+    }
+    let new_stem = naming.file_stem(&page.title);
+
+    let now = Utc::now().to_rfc3339();
+
+    match prefer {
+        ReconcilePreference::Filename => {
+            let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+            conn.execute(
+                "UPDATE pages SET title = ?, updated_at = ? WHERE id = ?",
+                params![file_stem, now, page_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        ReconcilePreference::Title => {
+            if page.is_directory {
+                return Err(format!(
+                    "Page {} is a directory page; rename it instead so child paths follow",
+                    page_id
+                ));
+            }
+            let target = std::path::Path::new(workspace_path)
+                .join(&file_path)
+                .with_file_name(format!("{}.md", new_stem));
+            // Slug names pick a free suffix instead of failing
+            if naming == FileNaming::Title {
+                let source = std::path::Path::new(workspace_path).join(&file_path);
+                ensure_rename_target_free(&source, &target)?;
+            }
+
+            let new_file_path = FileSyncService::new(workspace_path)
+                .rename_page_file(conn_mutex, page_id, &page.title)
+                .await?;
+
+            let affected_pages = {
+                let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+                let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+                tx.execute(
+                    "UPDATE pages SET file_path = ?, updated_at = ? WHERE id = ?",
+                    params![new_file_path, now, page_id],
+                )
+                .map_err(|e| e.to_string())?;
+                page_path_service::update_page_path(&tx, page_id, &new_file_path)
+                    .map_err(|e| e.to_string())?;
+
+                // Slug-named pages are linked by title, not by file name
+                let link_name = match naming {
+                    FileNaming::Title => new_stem.as_str(),
+                    FileNaming::Slug => page.title.as_str(),
+                };
+                let rewrite = rewrite_inbound_links(&tx, page_id, link_name, &now)?;
+                tx.commit().map_err(|e| e.to_string())?;
+                rewrite.touched_page_ids
+            };
+
+            for affected_page in affected_pages {
+                sync_page_to_markdown(conn_mutex, workspace_path, &affected_page).await?;
+            }
+        }
+    }
+
+    get_page_internal(conn_mutex, page_id)
+}
+
+/// Outcome of rewriting the links that point at a renamed page
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WikiLinkRewriteResult {
+    /// Number of blocks whose content was rewritten
+    pub updated_count: usize,
+    /// Pages containing those blocks; the only pages that need re-rendering
+    pub touched_page_ids: Vec<String>,
+}
+
+/// After a page file was renamed on disk, point the page row at its new path and
+/// rewrite every link to it. Paths may be absolute or workspace-relative.
+/// Directory renames are left to the next sync for the child pages' paths.
+#[tauri::command]
+pub async fn rewrite_wiki_links_for_page_path_change(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    from_path: String,
+    to_path: String,
+) -> Result<WikiLinkRewriteResult, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    let result = {
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let result = rewrite_links_for_path_change(&tx, &workspace_path, &from_path, &to_path)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        result
+    };
+
+    for page_id in &result.touched_page_ids {
+        sync_page_to_markdown(&conn_mutex, &workspace_path, page_id).await?;
+        emit_page_changed(&app, &workspace_path, page_id);
+    }
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(result)
+}
+
+fn rewrite_links_for_path_change(
+    conn: &Connection,
+    workspace_path: &str,
+    from_path: &str,
+    to_path: &str,
+) -> Result<WikiLinkRewriteResult, String> {
+    let from_rel = workspace_relative_path(workspace_path, from_path);
+    let to_rel = workspace_relative_path(workspace_path, to_path);
+
+    // The row may already carry the new path if a sync ran after the rename
+    let page_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM pages WHERE file_path IN (?1, ?2) AND is_deleted = 0
+             ORDER BY file_path = ?1 DESC LIMIT 1",
+            params![from_rel, to_rel],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(page_id) = page_id else {
+        return Ok(WikiLinkRewriteResult::default());
+    };
+
+    let new_stem = file_stem_of(&to_rel);
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE pages SET title = ?, file_path = ?, updated_at = ? WHERE id = ?",
+        params![new_stem, to_rel, now, page_id],
+    )
+    .map_err(|e| e.to_string())?;
+    page_path_service::update_page_path(conn, &page_id, &to_rel).map_err(|e| e.to_string())?;
+
+    rewrite_inbound_links(conn, &page_id, &new_stem, &now)
+}
+
+/// Record a file-tree rename in the DB; the caller renames on disk before committing.
+///
+/// For a file, `from_path`/`to_path` are the page file. For a page directory they are
+/// the directory: its folder note (`Dir/Dir.md`, renamed along with it) becomes the
+/// renamed page, and every page below it is pointed at its new path with links to it
+/// rewritten.
+pub(crate) fn record_path_rename(
+    conn: &Connection,
+    workspace_path: &str,
+    from_path: &str,
+    to_path: &str,
+    is_directory: bool,
+) -> Result<WikiLinkRewriteResult, String> {
+    if !is_directory {
+        return rewrite_links_for_path_change(conn, workspace_path, from_path, to_path);
+    }
+
+    let from_dir = workspace_relative_path(workspace_path, from_path);
+    let to_dir = workspace_relative_path(workspace_path, to_path);
+    let mut result = rewrite_links_for_path_change(
+        conn,
+        workspace_path,
+        &format!("{}/{}.md", from_dir, file_stem_of(&from_dir)),
+        &format!("{}/{}.md", to_dir, file_stem_of(&to_dir)),
+    )?;
+
+    let moved = move_page_paths(conn, &format!("{}/", from_dir), &format!("{}/", to_dir))?;
+    merge_rewrite_results(&mut result, moved);
+
+    Ok(result)
+}
+
+/// Record a file-tree move in the DB; the caller moves it on disk before committing.
+/// The page file (or page directory, with everything in it) at `from_path` now lives
+/// at `to_path`. The moved page is re-parented under the folder page of its new
+/// directory (none at the workspace root), and path-qualified links to every moved
+/// page are rewritten; titles don't change.
+pub(crate) fn record_path_move(
+    conn: &Connection,
+    workspace_path: &str,
+    from_path: &str,
+    to_path: &str,
+    is_directory: bool,
+) -> Result<WikiLinkRewriteResult, String> {
+    let from_rel = workspace_relative_path(workspace_path, from_path);
+    let to_rel = workspace_relative_path(workspace_path, to_path);
+
+    let (moved_page_path, result) = if is_directory {
+        let result = move_page_paths(conn, &format!("{}/", from_rel), &format!("{}/", to_rel))?;
+        (format!("{}/{}.md", to_rel, file_stem_of(&to_rel)), result)
+    } else {
+        (to_rel.clone(), move_page_paths(conn, &from_rel, &to_rel)?)
+    };
+
+    let new_dir = std::path::Path::new(&to_rel)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let parent_id: Option<String> = if new_dir.is_empty() {
+        None
+    } else {
+        conn.query_row(
+            "SELECT id FROM pages WHERE file_path = ? AND is_deleted = 0",
+            [format!("{}/{}.md", new_dir, file_stem_of(&new_dir))],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+    };
+    conn.execute(
+        "UPDATE pages SET parent_id = ? WHERE file_path = ? AND is_deleted = 0",
+        params![parent_id, moved_page_path],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+/// Point pages whose file path starts with `from_prefix` (a directory with a trailing
+/// `/`, or a single page file) at the same path under `to_prefix`, rewriting
+/// path-qualified links (`[[Old/Child]]`) to them for the new location
+fn move_page_paths(
+    conn: &Connection,
+    from_prefix: &str,
+    to_prefix: &str,
+) -> Result<WikiLinkRewriteResult, String> {
+    let moved: Vec<(String, String)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, file_path FROM pages
+                 WHERE is_deleted = 0 AND substr(file_path, 1, ?1) = ?2
+                   AND (file_path = ?2 OR substr(?2, -1) = '/')",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![from_prefix.chars().count(), from_prefix], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let mut result = WikiLinkRewriteResult::default();
+    for (page_id, file_path) in moved {
+        let new_path = format!("{}{}", to_prefix, &file_path[from_prefix.len()..]);
+        conn.execute(
+            "UPDATE pages SET file_path = ?, updated_at = ? WHERE id = ?",
+            params![new_path, now, page_id],
+        )
+        .map_err(|e| e.to_string())?;
+        page_path_service::update_page_path(conn, &page_id, &new_path)
+            .map_err(|e| e.to_string())?;
+
+        // A folder note (`Dir/Dir.md`) is linked by its directory path
+        let mut link_path = normalize_page_path(&new_path);
+        if let Some((dir, stem)) = link_path.rsplit_once('/') {
+            if dir.rsplit('/').next() == Some(stem) {
+                link_path = dir.to_string();
+            }
+        }
+        let rewrite = rewrite_inbound_links_with(conn, &page_id, &now, &|content, targets| {
+            rewrite_link_targets_with(content, targets, |target| {
+                moved_link_target(target, &link_path)
+            })
+        })?;
+        merge_rewrite_results(&mut result, rewrite);
+    }
+
+    Ok(result)
+}
+
+fn merge_rewrite_results(result: &mut WikiLinkRewriteResult, other: WikiLinkRewriteResult) {
+    result.updated_count += other.updated_count;
+    for page_id in other.touched_page_ids {
+        if !result.touched_page_ids.contains(&page_id) {
+            result.touched_page_ids.push(page_id);
+        }
+    }
+    result.touched_page_ids.sort();
+}
+
+/// A link target pointing at a moved page: as many trailing segments of the page's
+/// new path as the link spelled out, so `[[Child]]` stays as is and `[[Old/Child]]`
+/// becomes `[[New/Child]]`
+fn moved_link_target(target: &str, new_path: &str) -> String {
+    let depth = target.split('/').count();
+    let segments: Vec<&str> = new_path.split('/').collect();
+    segments[segments.len().saturating_sub(depth)..].join("/")
+}
+
+fn workspace_relative_path(workspace_path: &str, path: &str) -> String {
+    let path = std::path::Path::new(path);
+    path.strip_prefix(workspace_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+/// Point every link that resolved to `page_id` at its new file name
+fn rewrite_inbound_links(
+    conn: &Connection,
+    page_id: &str,
+    new_stem: &str,
+    now: &str,
+) -> Result<WikiLinkRewriteResult, String> {
+    rewrite_inbound_links_with(conn, page_id, now, &|content, targets| {
+        rewrite_link_targets(content, targets, new_stem)
+    })
+}
+
+/// Rewrite the blocks linking to `page_id` with `rewrite`, which gets a block's content
+/// and the raw targets in it that resolved to the page
+fn rewrite_inbound_links_with(
+    conn: &Connection,
+    page_id: &str,
+    now: &str,
+    rewrite: &dyn Fn(&str, &HashSet<String>) -> String,
+) -> Result<WikiLinkRewriteResult, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.from_block_id, w.from_page_id, w.raw_target, b.content
+             FROM wiki_links w JOIN blocks b ON b.id = w.from_block_id
+             WHERE w.to_page_id = ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([page_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut targets_by_block: HashMap<String, (String, String, HashSet<String>)> = HashMap::new();
+    for (block_id, from_page_id, raw_target, content) in rows {
+        targets_by_block
+            .entry(block_id)
+            .or_insert_with(|| (from_page_id, content, HashSet::new()))
+            .2
+            .insert(raw_target);
+    }
+
+    let mut result = WikiLinkRewriteResult::default();
+    for (block_id, (from_page_id, content, raw_targets)) in targets_by_block {
+        let rewritten = rewrite(&content, &raw_targets);
+        if rewritten == content {
+            continue;
+        }
+
+        conn.execute(
+            "UPDATE blocks SET content = ?, updated_at = ? WHERE id = ?",
+            params![rewritten, now, block_id],
+        )
+        .map_err(|e| e.to_string())?;
+        index_block_fts(conn, &block_id, &from_page_id, &rewritten)?;
+        wiki_link_index::index_block_links(conn, &block_id, &rewritten, &from_page_id)
+            .map_err(|e| e.to_string())?;
+
+        result.updated_count += 1;
+        if !result.touched_page_ids.contains(&from_page_id) {
+            result.touched_page_ids.push(from_page_id);
+        }
+    }
+    result.touched_page_ids.sort();
+    for touched_page_id in &result.touched_page_ids {
+        block_history::clear_page_history(conn, touched_page_id)?;
+    }
+
+    Ok(result)
+}
+
+/// Combine two pages: append every root block of `source_page_id` (with its subtree)
+/// after the root blocks of `target_page_id`, point links to the source at the target,
+/// then delete the source page and its file. Block IDs are kept, so `((block))`
+/// references and embeds into the moved blocks keep working.
+#[tauri::command]
+pub async fn merge_pages(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    source_page_id: String,
+    target_page_id: String,
+) -> Result<Page, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let conn_mutex = Mutex::new(conn);
+
+    let rewrite = {
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let rewrite = merge_page_blocks(&tx, &workspace_path, &source_page_id, &target_page_id)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        rewrite
+    };
+
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &target_page_id).await?;
+    emit_page_changed(&app, &workspace_path, &target_page_id);
+    for page_id in &rewrite.touched_page_ids {
+        if page_id == &target_page_id {
+            continue;
+        }
+        sync_page_to_markdown(&conn_mutex, &workspace_path, page_id).await?;
+        emit_page_changed(&app, &workspace_path, page_id);
+    }
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    get_page_internal(&conn_mutex, &target_page_id)
+}
+
+/// Database half of `merge_pages`. The source page's file goes to the trash, like a
+/// deleted page's. Returns the links rewritten to the target.
+fn merge_page_blocks(
+    conn: &Connection,
+    workspace_path: &str,
+    source_page_id: &str,
+    target_page_id: &str,
+) -> Result<WikiLinkRewriteResult, String> {
+    if source_page_id == target_page_id {
+        return Err("Cannot merge a page into itself".to_string());
+    }
+    let load = |page_id: &str| {
+        conn.query_row(
+            "SELECT title, file_path, is_directory FROM pages WHERE id = ? AND is_deleted = 0",
+            [page_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, i32>(2)? != 0,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Page not found: {}", page_id))
+    };
+    let (_, _, source_is_directory) = load(source_page_id)?;
+    let (target_title, target_file, _) = load(target_page_id)?;
+
+    let child_pages: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pages WHERE parent_id = ?",
+            [source_page_id],
             |row| row.get(0),
         )
+        .map_err(|e| e.to_string())?;
+    if source_is_directory || child_pages > 0 {
+        return Err("Cannot merge a page with children".to_string());
+    }
+
+    // Snapshot the source while it still has its blocks
+    move_page_to_trash(conn, workspace_path, source_page_id)?;
+
+    let (last_weight, last_key): (Option<f64>, Option<String>) = conn
+        .query_row(
+            "SELECT order_weight, order_key FROM blocks WHERE page_id = ? AND parent_id IS NULL
+             ORDER BY order_key DESC LIMIT 1",
+            [target_page_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
         .map_err(|e| e.to_string())?
+        .unzip();
+    let source_roots: Vec<String> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM blocks WHERE page_id = ? AND parent_id IS NULL
+                 ORDER BY order_key",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([source_page_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
     };
 
-    if children_count > 0 {
-        return Err("Cannot delete page with children".to_string());
+    let now = Utc::now().to_rfc3339();
+    let weights = fractional_index::calculate_between(last_weight, None, source_roots.len());
+    let keys = sibling_keys_between(last_key.as_deref(), None, source_roots.len())?;
+    for ((block_id, weight), key) in source_roots.iter().zip(weights).zip(keys) {
+        conn.execute(
+            "UPDATE blocks SET order_weight = ?, order_key = ? WHERE id = ?",
+            params![weight, &key, block_id],
+        )
+        .map_err(|e| e.to_string())?;
     }
+    conn.execute(
+        "UPDATE blocks SET page_id = ?1, updated_at = ?3 WHERE page_id = ?2",
+        params![target_page_id, source_page_id, now],
+    )
+    .map_err(|e| e.to_string())?;
 
-    // Delete file
-    let file_sync = FileSyncService::new(&workspace_path);
-    file_sync.delete_page_file(&conn_mutex, &page_id).await?;
-
-    // Delete from DB (Cascade will handle blocks, but we do it explicitly to be safe)
-    {
-        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-        conn.execute("DELETE FROM pages WHERE id = ?", [&page_id])
+    // Derived rows keyed by page follow the moved blocks
+    for sql in [
+        "UPDATE blocks_fts SET page_id = ?1 WHERE page_id = ?2",
+        "UPDATE block_paths SET page_id = ?1 WHERE page_id = ?2",
+        "UPDATE block_tags SET page_id = ?1 WHERE page_id = ?2",
+        "UPDATE sync_changed_blocks SET page_id = ?1 WHERE page_id = ?2",
+        "UPDATE wiki_links SET from_page_id = ?1 WHERE from_page_id = ?2",
+    ] {
+        conn.execute(sql, params![target_page_id, source_page_id])
             .map_err(|e| e.to_string())?;
     }
 
-    // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+    // The full path, so a basename shared with other pages cannot send links elsewhere
+    let target_path: Option<String> = conn
+        .query_row(
+            "SELECT path_text FROM page_paths WHERE page_id = ?",
+            [target_page_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let target_link = target_path
+        .or_else(|| target_file.as_deref().map(file_stem_of))
+        .unwrap_or(target_title);
+    let mut rewrite = rewrite_inbound_links(conn, source_page_id, &target_link, &now)?;
+    rewrite.touched_page_ids.retain(|id| id != source_page_id);
+    block_history::clear_page_history(conn, source_page_id)?;
+    block_history::clear_page_history(conn, target_page_id)?;
 
-    Ok(page_id)
-}
+    conn.execute("DELETE FROM pages WHERE id = ?", [source_page_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE pages SET updated_at = ? WHERE id = ?",
+        params![now, target_page_id],
+    )
+    .map_err(|e| e.to_string())?;
 
-/// Get a single page
-#[tauri::command]
-pub async fn get_page(
-    workspace_path: String,
-    request: GetPageRequest,
-) -> Result<Option<Page>, String> {
-    let conn = open_workspace_db(&workspace_path)?;
-    let conn_mutex = Mutex::new(conn);
-    match get_page_internal(&conn_mutex, &request.page_id) {
-        Ok(page) => Ok(Some(page)),
-        Err(_) => Ok(None),
-    }
+    Ok(rewrite)
 }
 
 // Internal helper to get page
@@ -272,11 +2319,39 @@ pub struct PageTreeItem {
     pub page: Page,
     pub children: Vec<PageTreeItem>,
     pub depth: i32,
+    /// Whether the page has child pages, even when `children` was cut off by a depth limit
+    pub has_children: bool,
 }
 
+/// Page hierarchy. `max_depth` stops descending below that depth (0 = root pages only);
+/// `lazy` returns just the root level, to be expanded with `get_page_tree_children`.
 #[tauri::command]
-pub async fn get_page_tree(workspace_path: String) -> Result<Vec<PageTreeItem>, String> {
+pub async fn get_page_tree(
+    workspace_path: String,
+    max_depth: Option<i32>,
+    lazy: Option<bool>,
+) -> Result<Vec<PageTreeItem>, String> {
     let pages = get_pages(workspace_path).await?;
+    let max_depth = if lazy.unwrap_or(false) {
+        Some(0)
+    } else {
+        max_depth
+    };
+    Ok(build_page_tree(pages, max_depth))
+}
+
+/// Progressive loading: direct child pages of `parent_id`, without their own children.
+/// Mirrors `get_page_blocks_children` for the page hierarchy.
+#[tauri::command]
+pub async fn get_page_tree_children(
+    workspace_path: String,
+    parent_id: String,
+) -> Result<Vec<PageTreeItem>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_page_tree_children(&conn, &parent_id)
+}
+
+fn build_page_tree(pages: Vec<Page>, max_depth: Option<i32>) -> Vec<PageTreeItem> {
     let mut tree: Vec<PageTreeItem> = Vec::new();
     let mut page_map: HashMap<String, Vec<Page>> = HashMap::new();
 
@@ -289,31 +2364,94 @@ pub async fn get_page_tree(workspace_path: String) -> Result<Vec<PageTreeItem>,
     // Build tree
     if let Some(root_pages) = page_map.get("root") {
         for page in root_pages {
-            tree.push(build_tree_recursive(page.clone(), &page_map, 0));
+            tree.push(build_tree_recursive(page.clone(), &page_map, 0, max_depth));
         }
     }
 
-    Ok(tree)
+    tree
 }
 
 fn build_tree_recursive(
     page: Page,
     page_map: &HashMap<String, Vec<Page>>,
     depth: i32,
+    max_depth: Option<i32>,
 ) -> PageTreeItem {
+    let child_pages = page_map.get(&page.id);
+    let has_children = child_pages.is_some_and(|children| !children.is_empty());
     let mut children: Vec<PageTreeItem> = Vec::new();
-    if let Some(child_pages) = page_map.get(&page.id) {
-        for child in child_pages {
-            children.push(build_tree_recursive(child.clone(), page_map, depth + 1));
+    if max_depth.map_or(true, |max| depth < max) {
+        for child in child_pages.into_iter().flatten() {
+            children.push(build_tree_recursive(
+                child.clone(),
+                page_map,
+                depth + 1,
+                max_depth,
+            ));
         }
     }
     PageTreeItem {
         page,
         children,
         depth,
+        has_children,
     }
 }
 
+fn load_page_tree_children(
+    conn: &Connection,
+    parent_id: &str,
+) -> Result<Vec<PageTreeItem>, String> {
+    // Depth of the children: one more than the number of ancestors of the parent
+    let depth: i32 = conn
+        .query_row(
+            "WITH RECURSIVE ancestors(id, parent_id) AS (
+                 SELECT id, parent_id FROM pages WHERE id = ?
+                 UNION
+                 SELECT p.id, p.parent_id FROM pages p JOIN ancestors a ON p.id = a.parent_id
+             )
+             SELECT COUNT(*) FROM ancestors",
+            [parent_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, parent_id, file_path, is_directory, file_mtime, file_size, created_at, updated_at,
+                EXISTS (SELECT 1 FROM pages c WHERE c.parent_id = pages.id AND c.is_deleted = 0)
+             FROM pages
+             WHERE parent_id = ? AND is_deleted = 0
+             ORDER BY title",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let items = stmt
+        .query_map([parent_id], |row| {
+            Ok(PageTreeItem {
+                page: Page {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    file_path: row.get(3)?,
+                    is_directory: row.get::<_, i32>(4)? != 0,
+                    file_mtime: row.get(5)?,
+                    file_size: row.get(6)?,
+                    created_at: row.get(7)?,
+                    updated_at: row.get(8)?,
+                },
+                children: Vec::new(),
+                depth,
+                has_children: row.get::<_, i32>(9)? != 0,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(items)
+}
+
 /// Convert a page to a directory (folder)
 #[tauri::command]
 pub async fn convert_page_to_directory(
@@ -321,13 +2459,25 @@ pub async fn convert_page_to_directory(
     workspace_path: String,
     page_id: String,
 ) -> Result<Page, String> {
-    let conn = open_workspace_db(&workspace_path)?;
+    let page = convert_page_to_directory_internal(&workspace_path, &page_id).await?;
+
+    // Emit workspace changed event for git monitoring
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(page)
+}
+
+async fn convert_page_to_directory_internal(
+    workspace_path: &str,
+    page_id: &str,
+) -> Result<Page, String> {
+    let conn = open_workspace_db(workspace_path)?;
     let conn_mutex = Mutex::new(conn);
-    let file_sync = FileSyncService::new(&workspace_path);
+    let file_sync = FileSyncService::new(workspace_path);
 
     // Convert file to directory structure
     let new_path = file_sync
-        .convert_page_to_directory(&conn_mutex, &page_id)
+        .convert_page_to_directory(&conn_mutex, page_id)
         .await?;
 
     // Update DB
@@ -340,10 +2490,7 @@ pub async fn convert_page_to_directory(
         .map_err(|e| e.to_string())?;
     }
 
-    // Emit workspace changed event for git monitoring
-    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
-
-    get_page_internal(&conn_mutex, &page_id)
+    get_page_internal(&conn_mutex, page_id)
 }
 
 /// Move a page to a new parent
@@ -486,3 +2633,845 @@ pub async fn reindex_page_markdown(workspace_path: String, page_id: String) -> R
     let conn_mutex = Mutex::new(conn);
     sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undelete_page_refuses_existing_file() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_undelete_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let workspace = temp_dir.to_string_lossy().to_string();
+
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path, is_deleted) VALUES
+                ('gone', 'Gone', 'Gone.md', 1),
+                ('kept', 'Kept', 'Kept.md', 1),
+                ('live', 'Live', 'Live.md', 0);",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.join("Kept.md"), "- still here\n").unwrap();
+
+        undelete_page(&conn, &workspace, "gone").unwrap();
+        let is_deleted: i32 = conn
+            .query_row(
+                "SELECT is_deleted FROM pages WHERE id = 'gone'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(is_deleted, 0);
+
+        let err = undelete_page(&conn, &workspace, "kept").unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+        assert!(undelete_page(&conn, &workspace, "live").is_err());
+        assert!(undelete_page(&conn, &workspace, "missing").is_err());
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_get_pages_by_frontmatter_metadata() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('a', 'Alpha', 'Alpha.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('b', 'Beta', 'Beta.md');
+             INSERT INTO pages (id, title, file_path, is_deleted) VALUES ('c', 'Gone', 'Gone.md', 1);",
+        )
+        .unwrap();
+        let frontmatter = "---\nstatus: draft\ntags: [work, home]\n---\n- body\n";
+        for page_id in ["a", "b", "c"] {
+            save_page_frontmatter(&conn, page_id, frontmatter).unwrap();
+        }
+        save_page_frontmatter(&conn, "b", "---\nstatus: done\n---\n").unwrap();
+
+        let titles = |key: &str, value: &str| -> Vec<String> {
+            find_pages_by_metadata(&conn, key, value)
+                .unwrap()
+                .into_iter()
+                .map(|page| page.title)
+                .collect()
+        };
+        assert_eq!(titles("status", "draft"), vec!["Alpha"]);
+        assert_eq!(titles("status", "done"), vec!["Beta"]);
+        assert_eq!(titles("tags", "home"), vec!["Alpha"]);
+        assert!(titles("tags", "wor").is_empty());
+
+        // A file without frontmatter clears the page's metadata
+        save_page_frontmatter(&conn, "a", "- body\n").unwrap();
+        assert!(titles("status", "draft").is_empty());
+    }
+
+    #[test]
+    fn test_page_stats_count_words_depth_and_blocks() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, updated_at) VALUES ('p1', 'Notes', '2024-01-01 00:00:00');
+             INSERT INTO pages (id, title, updated_at) VALUES ('p2', 'Other', '2024-01-01 00:00:00');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight, updated_at)
+                 VALUES ('a', 'p1', NULL, 'Read [[Books/Dune|the novel]] soon', 1.0, '2024-03-01T10:00:00+09:00');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight, updated_at)
+                 VALUES ('b', 'p1', 'a', 'see ((abc)) and **`let x = 1;`** here', 1.0, '2024-03-01 05:00:00');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight, updated_at)
+                 VALUES ('c', 'p1', 'b', '- [[Plain Link]] -', 1.0, '2024-01-15 00:00:00');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight, block_type, updated_at)
+                 VALUES ('d', 'p1', NULL, 'fn main() {}', 2.0, 'code', '2024-01-01 00:00:00');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                 VALUES ('e', 'p2', NULL, 'one two', 1.0);",
+        )
+        .unwrap();
+
+        let stats = load_page_stats(&conn, Some("p1")).unwrap();
+        assert_eq!(stats.len(), 1);
+        let page = &stats[0];
+        assert_eq!(page.block_count, 4);
+        // "Read the novel soon" + "see and here" + "Plain Link"; code block not counted
+        assert_eq!(page.word_count, 9);
+        assert_eq!(page.max_depth, 2);
+        // 05:00 UTC is later than 10:00+09:00, whatever the strings say
+        assert_eq!(
+            page.last_modified.as_deref(),
+            Some("2024-03-01T05:00:00+00:00")
+        );
+
+        let all = load_page_stats(&conn, None).unwrap();
+        assert_eq!(
+            all.iter().map(|p| p.page_id.as_str()).collect::<Vec<_>>(),
+            vec!["p1", "p2"]
+        );
+        assert_eq!(all[1].word_count, 2);
+        assert!(load_page_stats(&conn, Some("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replace_page_content_keeps_page_and_reports_broken_refs() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_replace_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('notes', 'Notes', 'Notes.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('other', 'Other', 'Other.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('old-1', 'notes', 'old one', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('keep', 'notes', 'kept', 2.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('ref', 'other', 'see ((old-1))', 1.0);",
+        )
+        .unwrap();
+        let conn_mutex = Mutex::new(conn);
+
+        let markdown =
+            "- Parent\n  - Child\n    ID::child-1\n    status::done\n- Kept edited\n  ID::keep\n";
+        let result = tauri::async_runtime::block_on(replace_page_content_internal(
+            &conn_mutex,
+            &path_str,
+            "notes",
+            markdown,
+        ))
+        .unwrap();
+
+        let conn = conn_mutex.lock().unwrap();
+        let blocks = query_blocks_for_page(&conn, "notes").unwrap();
+        assert_eq!(blocks.len(), 3);
+        let parent = blocks.iter().find(|b| b.content == "Parent").unwrap();
+        let child = blocks.iter().find(|b| b.id == "child-1").unwrap();
+        assert_eq!(parent.parent_id, None);
+        assert_eq!(child.parent_id.as_deref(), Some(parent.id.as_str()));
+        let status: String = conn
+            .query_row(
+                "SELECT value FROM block_metadata WHERE block_id = 'child-1' AND key = 'status'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(status, "done");
+        let kept = blocks.iter().find(|b| b.id == "keep").unwrap();
+        assert_eq!(kept.content, "Kept edited");
+
+        let (title, file_path): (String, String) = conn
+            .query_row(
+                "SELECT title, file_path FROM pages WHERE id = 'notes'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "Notes");
+        assert_eq!(file_path, "Notes.md");
+
+        assert_eq!(result.broken_refs.len(), 1);
+        assert_eq!(result.broken_refs[0].from_block_id, "ref");
+        assert_eq!(result.broken_refs[0].to_block_id, "old-1");
+
+        let on_disk = std::fs::read_to_string(temp_dir.join("Notes.md")).unwrap();
+        assert!(on_disk.contains("- Kept edited"));
+        assert!(on_disk.contains("ID::keep"));
+
+        drop(conn);
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_reconcile_title_filename_prefers_title() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_reconcile_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('target', 'My Page', 'my-page.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('target', 'my-page');
+             INSERT INTO pages (id, title, file_path) VALUES ('other', 'Other', 'Other.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('other', 'Other');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('t1', 'target', 'hello', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('link', 'other', 'see [[my-page|it]]', 1.0);",
+        )
+        .unwrap();
+        wiki_link_index::index_block_links(&conn, "link", "see [[my-page|it]]", "other").unwrap();
+        std::fs::write(temp_dir.join("my-page.md"), "- hello\n  ID::t1\n").unwrap();
+
+        let mismatches = find_title_filename_mismatches(&conn, FileNaming::Title).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].page_id, "target");
+        assert_eq!(mismatches[0].file_stem, "my-page");
+
+        let conn_mutex = Mutex::new(conn);
+        let page = tauri::async_runtime::block_on(reconcile_page_internal(
+            &conn_mutex,
+            &path_str,
+            "target",
+            ReconcilePreference::Title,
+        ))
+        .unwrap();
+
+        assert_eq!(page.file_path.as_deref(), Some("My Page.md"));
+        assert!(temp_dir.join("My Page.md").exists());
+        assert!(!temp_dir.join("my-page.md").exists());
+
+        let conn = conn_mutex.lock().unwrap();
+        let (content, path_text, to_page_id): (String, String, Option<String>) = conn
+            .query_row(
+                "SELECT b.content, pp.path_text, w.to_page_id
+                 FROM blocks b, page_paths pp, wiki_links w
+                 WHERE b.id = 'link' AND pp.page_id = 'target' AND w.from_block_id = 'link'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(content, "see [[My Page|it]]");
+        assert_eq!(path_text, "My Page");
+        assert_eq!(to_page_id.as_deref(), Some("target"));
+        let mismatches = find_title_filename_mismatches(&conn, FileNaming::Title).unwrap();
+        assert!(mismatches.is_empty());
+
+        let other_md = std::fs::read_to_string(temp_dir.join("Other.md")).unwrap();
+        assert!(other_md.contains("[[My Page|it]]"));
+
+        drop(conn);
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_rewrite_links_for_path_change_reports_touched_pages() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('target', 'Target', 'Target.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('target', 'Target');
+             INSERT INTO pages (id, title, file_path) VALUES ('a', 'A', 'A.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('b', 'B', 'B.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('c', 'C', 'C.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'a', 'See [[Target]]', 1.0),
+                ('a2', 'a', 'And [[Target|the target]]', 2.0),
+                ('b1', 'b', 'Also [[Target#Intro]]', 1.0),
+                ('c1', 'c', 'Unrelated [[Elsewhere]]', 1.0);",
+        )
+        .unwrap();
+        for (block_id, page_id, content) in [
+            ("a1", "a", "See [[Target]]"),
+            ("a2", "a", "And [[Target|the target]]"),
+            ("b1", "b", "Also [[Target#Intro]]"),
+            ("c1", "c", "Unrelated [[Elsewhere]]"),
+        ] {
+            wiki_link_index::index_block_links(&conn, block_id, content, page_id).unwrap();
+        }
+
+        let result =
+            rewrite_links_for_path_change(&conn, "/ws", "/ws/Target.md", "/ws/Renamed.md").unwrap();
+
+        assert_eq!(result.updated_count, 3);
+        assert_eq!(result.touched_page_ids, vec!["a", "b"]);
+
+        let b1: String = conn
+            .query_row("SELECT content FROM blocks WHERE id = 'b1'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(b1, "Also [[Renamed#Intro]]");
+        let file_path: String = conn
+            .query_row(
+                "SELECT file_path FROM pages WHERE id = 'target'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(file_path, "Renamed.md");
+    }
+
+    #[test]
+    fn test_title_rename_reports_touched_pages() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('target', 'Target', 'Target.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('target', 'Target');
+             INSERT INTO pages (id, title, file_path) VALUES ('a', 'A', 'A.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('b', 'B', 'B.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('c', 'C', 'C.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'a', 'See [[Target]]', 1.0),
+                ('b1', 'b', 'Also [[Target|it]]', 1.0),
+                ('c1', 'c', 'Unrelated [[Elsewhere]]', 1.0);",
+        )
+        .unwrap();
+        for (block_id, page_id, content) in [
+            ("a1", "a", "See [[Target]]"),
+            ("b1", "b", "Also [[Target|it]]"),
+            ("c1", "c", "Unrelated [[Elsewhere]]"),
+        ] {
+            wiki_link_index::index_block_links(&conn, block_id, content, page_id).unwrap();
+        }
+
+        let result = record_title_rename(
+            &conn,
+            "target",
+            "Renamed",
+            "Renamed.md",
+            FileNaming::Title,
+            "2024-01-01T00:00:00Z",
+        )
+        .unwrap();
+
+        assert_eq!(result.updated_count, 2);
+        assert_eq!(result.touched_page_ids, vec!["a", "b"]);
+        let (title, b1): (String, String) = conn
+            .query_row(
+                "SELECT p.title, b.content FROM pages p, blocks b
+                 WHERE p.id = 'target' AND b.id = 'b1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(title, "Renamed");
+        assert_eq!(b1, "Also [[Renamed|it]]");
+    }
+
+    #[test]
+    fn test_record_directory_rename_moves_child_pages() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path, is_directory)
+                VALUES ('dir', 'Old', 'Notes/Old/Old.md', 1);
+             INSERT INTO pages (id, title, file_path) VALUES ('child', 'Child', 'Notes/Old/Child.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('other', 'Other', 'Notes/Older.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('a', 'A', 'A.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'a', 'See [[Old]] and [[Notes/Old/Child]]', 1.0);",
+        )
+        .unwrap();
+        for (page_id, path) in [
+            ("dir", "Notes/Old/Old.md"),
+            ("child", "Notes/Old/Child.md"),
+            ("other", "Notes/Older.md"),
+        ] {
+            page_path_service::update_page_path(&conn, page_id, path).unwrap();
+        }
+        let content = "See [[Old]] and [[Notes/Old/Child]]";
+        wiki_link_index::index_block_links(&conn, "a1", content, "a").unwrap();
+
+        let result = record_path_rename(&conn, "/ws", "Notes/Old", "Notes/New", true).unwrap();
+        assert_eq!(result.touched_page_ids, vec!["a"]);
+
+        let path_of = |id: &str| -> String {
+            conn.query_row("SELECT file_path FROM pages WHERE id = ?", [id], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        assert_eq!(path_of("dir"), "Notes/New/New.md");
+        assert_eq!(path_of("child"), "Notes/New/Child.md");
+        assert_eq!(path_of("other"), "Notes/Older.md");
+
+        let a1: String = conn
+            .query_row("SELECT content FROM blocks WHERE id = 'a1'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(a1, "See [[New]] and [[Notes/New/Child]]");
+    }
+
+    #[test]
+    fn test_record_path_move_sets_parent_and_rewrites_links() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path, is_directory)
+                VALUES ('proj', 'Projects', 'Projects/Projects.md', 1);
+             INSERT INTO pages (id, title, file_path, is_directory)
+                VALUES ('dir', 'Old', 'Notes/Old/Old.md', 1);
+             INSERT INTO pages (id, title, file_path) VALUES ('child', 'Child', 'Notes/Old/Child.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('idea', 'Idea', 'Notes/Idea.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('a', 'A', 'A.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'a', 'See [[Notes/Idea]] and [[Notes/Old/Child]]', 1.0);",
+        )
+        .unwrap();
+        for (page_id, path) in [
+            ("proj", "Projects/Projects.md"),
+            ("dir", "Notes/Old/Old.md"),
+            ("child", "Notes/Old/Child.md"),
+            ("idea", "Notes/Idea.md"),
+        ] {
+            page_path_service::update_page_path(&conn, page_id, path).unwrap();
+        }
+        let content = "See [[Notes/Idea]] and [[Notes/Old/Child]]";
+        wiki_link_index::index_block_links(&conn, "a1", content, "a").unwrap();
+
+        let moved = record_path_move(&conn, "/ws", "Notes/Idea.md", "Projects/Idea.md", false);
+        assert_eq!(moved.unwrap().touched_page_ids, vec!["a"]);
+        let moved = record_path_move(&conn, "/ws", "Notes/Old", "Projects/Old", true);
+        assert_eq!(moved.unwrap().touched_page_ids, vec!["a"]);
+
+        let page = |id: &str| -> (String, Option<String>) {
+            conn.query_row(
+                "SELECT file_path, parent_id FROM pages WHERE id = ?",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            page("idea"),
+            ("Projects/Idea.md".into(), Some("proj".into()))
+        );
+        assert_eq!(
+            page("dir"),
+            ("Projects/Old/Old.md".into(), Some("proj".into()))
+        );
+        assert_eq!(page("child").0, "Projects/Old/Child.md");
+
+        let a1: String = conn
+            .query_row("SELECT content FROM blocks WHERE id = 'a1'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(a1, "See [[Projects/Idea]] and [[Projects/Old/Child]]");
+
+        record_path_move(&conn, "/ws", "Projects/Idea.md", "Idea.md", false).unwrap();
+        assert_eq!(page("idea"), ("Idea.md".into(), None));
+    }
+
+    #[test]
+    fn test_copy_page_blocks_remaps_ids_and_refs() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('src', 'Template', 'Template.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('copy', 'Copy', 'Copy.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('other', 'Other', 'Other.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('outside', 'other', 'elsewhere', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight, block_type, language)
+                VALUES ('root', 'src', 'Agenda', 1.0, 'heading', '2');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                VALUES ('child-b', 'src', 'root', 'see ((child-a)) and ((outside))', 2.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                VALUES ('child-a', 'src', 'root', 'first', 1.0);
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m1', 'child-a', 'status', 'todo');",
+        )
+        .unwrap();
+
+        assert_eq!(copy_page_blocks(&conn, "src", "copy").unwrap(), 3);
+
+        let copied = query_blocks_for_page(&conn, "copy").unwrap();
+        assert_eq!(copied.len(), 3);
+        assert!(copied
+            .iter()
+            .all(|b| !["root", "child-a", "child-b"].contains(&b.id.as_str())));
+        let root = copied.iter().find(|b| b.content == "Agenda").unwrap();
+        assert_eq!(root.parent_id, None);
+        assert_eq!(root.language.as_deref(), Some("2"));
+        let first = copied.iter().find(|b| b.content == "first").unwrap();
+        assert_eq!(first.parent_id.as_deref(), Some(root.id.as_str()));
+        assert_eq!(first.order_weight, 1.0);
+        let second = copied
+            .iter()
+            .find(|b| b.parent_id.as_deref() == Some(root.id.as_str()) && b.id != first.id)
+            .unwrap();
+        assert_eq!(second.order_weight, 2.0);
+        assert_eq!(
+            second.content,
+            format!("see (({})) and ((outside))", first.id)
+        );
+
+        let (block_type, status, fts): (String, String, i64) = conn
+            .query_row(
+                "SELECT (SELECT block_type FROM blocks WHERE id = ?1),
+                        (SELECT value FROM block_metadata WHERE block_id = ?2 AND key = 'status'),
+                        (SELECT COUNT(*) FROM blocks_fts WHERE page_id = 'copy')",
+                params![&root.id, &first.id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(block_type, "heading");
+        assert_eq!(status, "todo");
+        assert_eq!(fts, 3);
+        assert_eq!(query_blocks_for_page(&conn, "src").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_template_blocks_get_fresh_ids_and_keep_metadata() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch("INSERT INTO pages (id, title) VALUES ('p1', 'Standup');")
+            .unwrap();
+
+        let template = "- Meeting: {{title}} on {{date}} {{unknown}}\n  ID::template-root\n  \
+                        status::open\n  - Attendees\n    ID::template-child\n- Notes\n  \
+                        owner::{{title}}\n";
+        let indent = crate::utils::markdown::IndentStyle::default();
+        let rendered = render_template(template, "Standup", "2024-05-01", "09:30");
+        let markdown = normalize_marker_layout(&strip_id_markers(&rendered), indent);
+        import_page_blocks_from_markdown(
+            &mut conn,
+            "p1",
+            &markdown,
+            indent,
+            &SanitizationRules::default(),
+        )
+        .unwrap();
+
+        let blocks = query_blocks_for_page(&conn, "p1").unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert!(blocks.iter().all(|b| !b.id.starts_with("template-")));
+
+        let root = blocks.iter().find(|b| b.parent_id.is_none()).unwrap();
+        assert_eq!(root.content, "Meeting: Standup on 2024-05-01 {{unknown}}");
+        let metadata: Vec<(String, String, String)> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT b.content, m.key, m.value FROM block_metadata m
+                     JOIN blocks b ON b.id = m.block_id ORDER BY m.key",
+                )
+                .unwrap();
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            rows
+        };
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(
+            metadata[0],
+            ("Notes".into(), "owner".into(), "Standup".into())
+        );
+        assert_eq!(
+            metadata[1],
+            (root.content.clone(), "status".into(), "open".into())
+        );
+    }
+
+    #[test]
+    fn test_failed_template_fill_removes_page() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_template_{}", Uuid::new_v4()));
+        // The page "file" is a directory, so writing the template's frontmatter fails
+        std::fs::create_dir_all(temp_dir.join("Standup.md")).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Standup', 'Standup.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('p1', 'Standup');",
+        )
+        .unwrap();
+        let page = get_page_internal(&Mutex::new(conn), "p1").unwrap();
+
+        let rendered = "---\ntags: meeting\n---\n- Agenda\n";
+        let indent = IndentStyle::default();
+        let result = tauri::async_runtime::block_on(fill_page_from_template(
+            &path_str,
+            &page,
+            rendered,
+            &normalize_marker_layout(rendered, indent),
+            indent,
+        ));
+        assert!(result.is_err());
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        let (pages, blocks, fts): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM pages), (SELECT COUNT(*) FROM blocks),
+                        (SELECT COUNT(*) FROM blocks_fts)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((pages, blocks, fts), (0, 0, 0));
+
+        drop(conn);
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_get_or_create_daily_note_is_idempotent() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_daily_{}", Uuid::new_v4()));
+        let templates = templates_dir(&temp_dir.to_string_lossy());
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(templates.join("daily.md"), "- Plan for {{title}}\n").unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let note = |date: &str| {
+            tauri::async_runtime::block_on(get_or_create_daily_note_internal(
+                &path_str,
+                date,
+                Some("Journal".to_string()),
+            ))
+            .unwrap()
+        };
+        let (first, created) = note("2024-05-01");
+        let (second, changed) = note("2024-05-01");
+        assert!(created);
+        assert!(!changed);
+        assert_eq!(first.id, second.id);
+
+        let note_files: Vec<_> = walkdir::WalkDir::new(&temp_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name() == "2024-05-01.md")
+            .collect();
+        assert_eq!(note_files.len(), 1);
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        let contents: Vec<String> = query_blocks_for_page(&conn, &first.id)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.content)
+            .collect();
+        assert_eq!(contents, vec!["Plan for 2024-05-01"]);
+        let on_disk = std::fs::read_to_string(note_files[0].path()).unwrap();
+        assert_eq!(on_disk.matches("Plan for").count(), 1);
+
+        drop(conn);
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_quick_switch_ranks_fuzzy_title_matches() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, is_deleted, updated_at) VALUES
+                ('mn', 'Meeting Notes', 0, '2024-01-01 00:00:00'),
+                ('mn2', 'Meeting Notes', 0, '2024-02-01 00:00:00'),
+                ('loose', 'my mountain', 0, '2024-03-01 00:00:00'),
+                ('gone', 'Meeting Notes', 1, '2024-04-01 00:00:00'),
+                ('other', 'Groceries', 0, '2024-05-01 00:00:00');",
+        )
+        .unwrap();
+
+        let hits = find_quick_switch_hits(&conn, "mt n", 10).unwrap();
+        let ids: Vec<&str> = hits.iter().map(|h| h.page.id.as_str()).collect();
+        assert_eq!(ids, vec!["mn2", "mn", "loose"]);
+        assert_eq!(hits[0].match_indices, vec![0, 3, 5]);
+        assert!(hits[0].score > hits[2].score);
+
+        let ids: Vec<String> = find_quick_switch_hits(&conn, "", 2)
+            .unwrap()
+            .into_iter()
+            .map(|h| h.page.id)
+            .collect();
+        assert_eq!(ids, vec!["other", "loose"]);
+
+        let tight = fuzzy_match("a_xab", &['a', 'b']).unwrap();
+        assert_eq!(tight.1, vec![3, 4]);
+        assert!(fuzzy_match("Groceries", &['z']).is_none());
+    }
+
+    #[test]
+    fn test_recent_pages_order_preview_and_exclusions() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, is_directory, is_deleted, updated_at) VALUES
+                ('old', 'Old', 0, 0, '2024-01-01 00:00:00'),
+                ('new', 'New', 0, 0, '2024-03-01 00:00:00'),
+                ('gone', 'Gone', 0, 1, '2024-04-01 00:00:00'),
+                ('dir', 'Dir', 1, 0, '2024-05-01 00:00:00'),
+                ('notes', 'Notes', 1, 0, '2024-02-01 00:00:00');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('n0', 'new', NULL, '', 1.0),
+                ('n1', 'new', NULL, '## **Plan** for [[Projects/Q2|Q2]] `soon`
+status:: draft', 2.0),
+                ('n2', 'new', 'n1', 'child text', 1.0),
+                ('o1', 'old', NULL, 'See [docs](https://example.com) ((abc))', 1.0),
+                ('g1', 'gone', NULL, 'deleted', 1.0),
+                ('d1', 'dir', NULL, 'Dir', 1.0),
+                ('t1', 'notes', NULL, 'Notes', 1.0),
+                ('t2', 'notes', NULL, 'Folder overview', 2.0);
+             UPDATE blocks SET updated_at = '2000-01-01 00:00:00';",
+        )
+        .unwrap();
+
+        let recent = load_recent_pages(&conn, 10).unwrap();
+        let ids: Vec<&str> = recent.iter().map(|r| r.page.id.as_str()).collect();
+        assert_eq!(ids, vec!["new", "notes", "old"]);
+        assert_eq!(recent[0].preview, "Plan for Q2 soon");
+        assert_eq!(recent[1].preview, "Folder overview");
+        assert_eq!(recent[2].preview, "See docs");
+
+        assert_eq!(load_recent_pages(&conn, 1).unwrap().len(), 1);
+
+        // A block edit (stored as RFC 3339) makes its page the most recent
+        conn.execute(
+            "UPDATE blocks SET updated_at = '2024-06-01T08:30:00.123+00:00' WHERE id = 'o1'",
+            [],
+        )
+        .unwrap();
+        let ids: Vec<String> = load_recent_pages(&conn, 10)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.page.id)
+            .collect();
+        assert_eq!(ids, vec!["old", "new", "notes"]);
+    }
+
+    #[test]
+    fn test_page_tree_depth_limit_and_lazy_children() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, parent_id, is_deleted) VALUES
+                ('a', 'A', NULL, 0),
+                ('b', 'B', 'a', 0),
+                ('c', 'C', 'b', 0),
+                ('d', 'D', 'a', 1),
+                ('e', 'E', NULL, 0);",
+        )
+        .unwrap();
+        let page = |id: &str, parent: Option<&str>| Page {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            parent_id: parent.map(str::to_string),
+            file_path: None,
+            is_directory: false,
+            file_mtime: None,
+            file_size: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let pages = vec![
+            page("a", None),
+            page("b", Some("a")),
+            page("c", Some("b")),
+            page("e", None),
+        ];
+
+        let full = build_page_tree(pages.clone(), None);
+        assert_eq!(full[0].children[0].children[0].page.id, "c");
+        assert!(!full[1].has_children);
+
+        let shallow = build_page_tree(pages, Some(0));
+        assert!(shallow[0].children.is_empty());
+        assert!(shallow[0].has_children);
+
+        let children = load_page_tree_children(&conn, "a").unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].page.id, "b");
+        assert_eq!(children[0].depth, 1);
+        assert!(children[0].has_children);
+
+        let grandchildren = load_page_tree_children(&conn, "b").unwrap();
+        assert_eq!(grandchildren[0].depth, 2);
+        assert!(!grandchildren[0].has_children);
+    }
+
+    #[test]
+    fn test_merge_page_blocks_moves_subtrees_and_rewrites_links() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_merge_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let workspace = temp_dir.to_string_lossy().to_string();
+        std::fs::write(temp_dir.join("Draft.md"), "- First\n  ID::s1\n").unwrap();
+        let conn = open_workspace_db(&workspace).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES
+                ('src', 'Draft', 'Draft.md'),
+                ('dst', 'Final', 'Work/Final.md'),
+                ('other', 'Other', 'Other.md');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('d1', 'dst', NULL, 'Existing', 1.0),
+                ('s1', 'src', NULL, 'First', 1.0),
+                ('s1a', 'src', 's1', 'Nested', 1.0),
+                ('s2', 'src', NULL, 'Second', 2.0),
+                ('o1', 'other', NULL, 'See [[Draft]] and ((s1a))', 1.0);",
+        )
+        .unwrap();
+        page_path_service::update_page_path(&conn, "src", "Draft.md").unwrap();
+        page_path_service::update_page_path(&conn, "dst", "Work/Final.md").unwrap();
+        wiki_link_index::index_block_links(&conn, "o1", "See [[Draft]] and ((s1a))", "other")
+            .unwrap();
+
+        let rewrite = merge_page_blocks(&conn, &workspace, "src", "dst").unwrap();
+        assert_eq!(rewrite.touched_page_ids, vec!["other"]);
+        assert!(!temp_dir.join("Draft.md").exists());
+
+        let blocks = query_blocks_for_page(&conn, "dst").unwrap();
+        let roots: Vec<&str> = blocks
+            .iter()
+            .filter(|b| b.parent_id.is_none())
+            .map(|b| b.id.as_str())
+            .collect();
+        assert_eq!(roots, vec!["d1", "s1", "s2"]);
+        let nested = blocks.iter().find(|b| b.id == "s1a").unwrap();
+        assert_eq!(nested.parent_id.as_deref(), Some("s1"));
+
+        let (content, to_page): (String, Option<String>) = conn
+            .query_row(
+                "SELECT b.content, w.to_page_id FROM blocks b
+                 JOIN wiki_links w ON w.from_block_id = b.id WHERE b.id = 'o1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(content, "See [[Work/Final]] and ((s1a))");
+        assert_eq!(to_page.as_deref(), Some("dst"));
+
+        let source_left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM pages WHERE id = 'src'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(source_left, 0);
+        assert!(merge_page_blocks(&conn, &workspace, "dst", "dst").is_err());
+
+        // The source file went to the trash rather than being deleted
+        let trash = temp_dir
+            .join(METADATA_DIR_NAME)
+            .join(crate::config::TRASH_DIR_NAME);
+        let trashed = std::fs::read_dir(&trash)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.path().join("Draft.md").exists());
+        assert!(trashed);
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+}
@@ -13,11 +13,14 @@ pub struct QueryResultBlock {
     pub page_path: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
     pub blocks: Vec<QueryResultBlock>,
     pub total_count: usize,
     pub error: Option<String>,
+    /// Set for aggregate queries, which return this instead of blocks;
+    /// `total_count` is then the number of blocks aggregated
+    pub aggregate: Option<AggregateResult>,
 }
 
 /// Execute a query macro and return matching blocks
@@ -25,36 +28,61 @@ pub struct QueryResult {
 pub async fn execute_query_macro(
     workspace_path: String,
     query_string: String,
+) -> Result<QueryResult, String> {
+    run_query_macro(&workspace_path, &query_string)
+}
+
+/// Parse and run a query macro against the workspace DB.
+/// Parse and execution errors are reported in `QueryResult::error`.
+pub(crate) fn run_query_macro(
+    workspace_path: &str,
+    query_string: &str,
 ) -> Result<QueryResult, String> {
     // Parse the query macro
-    let query_macro = match query_service::parse_query_macro(&query_string) {
+    let query_macro = match query_service::parse_query_macro(query_string) {
         Ok(macro_obj) => macro_obj,
         Err(e) => {
             return Ok(QueryResult {
                 blocks: vec![],
                 total_count: 0,
                 error: Some(e.message),
+                aggregate: None,
             })
         }
     };
 
     // Open database connection
-    let conn = open_workspace_db(&workspace_path).map_err(|e| format!("Database error: {}", e))?;
+    let conn = open_workspace_db(workspace_path).map_err(|e| format!("Database error: {}", e))?;
 
     // Execute query
-    match execute_query(&conn, &workspace_path, query_macro) {
+    let aggregate = query_macro.query_filter.aggregate.clone();
+    match execute_query(&conn, workspace_path, query_macro) {
         Ok(blocks) => {
             let total_count = blocks.len();
-            Ok(QueryResult {
-                blocks,
-                total_count,
-                error: None,
-            })
+            match aggregate {
+                Some(aggregate) => {
+                    let metadata: Vec<&HashMap<String, String>> =
+                        blocks.iter().map(|b| &b.block.metadata).collect();
+                    Ok(QueryResult {
+                        blocks: vec![],
+                        total_count,
+                        error: None,
+                        aggregate: Some(query_service::aggregate_metadata(&aggregate, &metadata)),
+                    })
+                }
+                None => Ok(QueryResult {
+                    blocks,
+                    total_count,
+                    error: None,
+                    aggregate: None,
+                }),
+            }
         }
         Err(e) => Ok(QueryResult {
             blocks: vec![],
             total_count: 0,
             error: Some(e),
+            aggregate: None,
         }),
     }
 }
@@ -87,7 +115,7 @@ fn execute_query(
     sql.push_str(
         "SELECT b.id, b.page_id, b.parent_id, b.content, b.order_weight,
                 b.is_collapsed, b.block_type, b.language, b.created_at, b.updated_at,
-                COALESCE(pp.path_text, '') "
+                COALESCE(pp.path_text, ''), b.order_key "
     );
 
     if filter.depth.is_some() {
@@ -153,14 +181,40 @@ fn execute_query(
             SortType::Cba => sql.push_str(" ORDER BY b.content DESC"),
             SortType::Numeric123 => sql.push_str(" ORDER BY b.created_at ASC"),
             SortType::Numeric321 => sql.push_str(" ORDER BY b.created_at DESC"),
+            SortType::By { key, descending } => {
+                let column = match key {
+                    SortKey::CreatedAt => "b.created_at",
+                    SortKey::UpdatedAt => "b.updated_at",
+                    // Sorted after metadata is loaded; keep a stable base order
+                    SortKey::Metadata(_) => "b.created_at",
+                };
+                let direction = if *descending && !matches!(key, SortKey::Metadata(_)) {
+                    "DESC"
+                } else {
+                    "ASC"
+                };
+                sql.push_str(&format!(" ORDER BY {} {}", column, direction));
+            }
         }
     } else {
         // Default sort if none specified
         sql.push_str(" ORDER BY b.created_at");
     }
 
-    // 6. LIMIT
-    if let Some(limit) = filter.limit {
+    // A metadata sort happens after the rows are loaded, so the limit has to wait too
+    let metadata_sort = match &filter.sort {
+        Some(SortType::By {
+            key: SortKey::Metadata(key),
+            descending,
+        }) => Some((key.as_str(), *descending)),
+        _ => None,
+    };
+
+    // 6. LIMIT (aggregates always cover every matching block)
+    let sql_limit = filter
+        .limit
+        .filter(|_| metadata_sort.is_none() && filter.aggregate.is_none());
+    if let Some(limit) = sql_limit {
         sql.push_str(" LIMIT ?");
         params.push(Box::new(limit));
     }
@@ -179,6 +233,7 @@ fn execute_query(
                 parent_id: row.get(2)?,
                 content: row.get(3)?,
                 order_weight: row.get(4)?,
+                order_key: row.get(11)?,
                 is_collapsed: row.get::<_, i32>(5)? != 0,
                 block_type: parse_block_type(row.get::<_, String>(6)?),
                 language: row.get(7)?,
@@ -242,6 +297,20 @@ fn execute_query(
         }
     }
 
+    // 8. Metadata SORT, then its deferred LIMIT
+    if let Some((key, descending)) = metadata_sort {
+        results.sort_by(|a, b| {
+            query_service::compare_sort_values(
+                a.block.metadata.get(key).map(String::as_str),
+                b.block.metadata.get(key).map(String::as_str),
+                descending,
+            )
+        });
+        if let Some(limit) = filter.limit.filter(|_| filter.aggregate.is_none()) {
+            results.truncate(limit as usize);
+        }
+    }
+
     Ok(results)
 }
 
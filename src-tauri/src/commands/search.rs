@@ -1,6 +1,10 @@
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
+use crate::commands::block::load_block_subtree;
 use crate::commands::workspace::open_workspace_db;
+use crate::services::wiki_link_parser::parse_tags;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -11,6 +15,8 @@ pub struct SearchResult {
     pub content: String,
     pub snippet: String, // Highlighted snippet with match
     pub rank: f64,       // Relevance score
+    /// BM25 relevance of a block match, higher is better; 0 for page title matches
+    pub score: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,6 +83,7 @@ pub fn search_content_with_options(
                 content: title,
                 snippet,
                 rank: 100.0, // Page title matches are high priority
+                score: 0.0,
             })
         })
         .map_err(|e| e.to_string())?;
@@ -87,57 +94,603 @@ pub fn search_content_with_options(
         }
     }
 
-    // 2. Search in block content using FTS5 with ranking
+    // 2. Search in block content using FTS5, ranked by BM25
     let fts_query = build_fts_query(
         &query,
         options.use_phrase_search,
         options.use_boolean_operators,
     );
 
+    // Input FTS5 cannot parse (stray operators, unbalanced parentheses, "c++") fails at
+    // query time; retry it as one literal phrase instead of surfacing a SQL logic error.
+    let block_results = match search_blocks_fts(&conn, &fts_query, &query, options.limit) {
+        Ok(block_results) => block_results,
+        Err(_) => search_blocks_fts(&conn, &quote_fts_literal(&query), &query, options.limit)
+            .map_err(|e| e.to_string())?,
+    };
+    results.extend(block_results);
+
+    Ok(results)
+}
+
+/// Run an FTS5 block search ordered by `bm25()`; `query` is the raw input, used for snippets
+fn search_blocks_fts(
+    conn: &Connection,
+    fts_query: &str,
+    query: &str,
+    limit: u32,
+) -> rusqlite::Result<Vec<SearchResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.id, b.page_id, b.content, p.title, bm25(blocks_fts) AS score
+         FROM blocks_fts
+         JOIN blocks b ON blocks_fts.block_id = b.id
+         JOIN pages p ON b.page_id = p.id
+         WHERE blocks_fts MATCH ?1
+         AND p.is_deleted = 0
+         ORDER BY score, p.title COLLATE NOCASE, b.order_key
+         LIMIT ?2",
+    )?;
+
+    let rows = stmt.query_map(rusqlite::params![fts_query, limit], |row| {
+        let content: String = row.get(2)?;
+        let bm25: f64 = row.get(4)?;
+
+        // Create snippet with highlighted match
+        let snippet = create_snippet(&content, query);
+
+        Ok(SearchResult {
+            id: row.get(0)?,
+            page_id: row.get(1)?,
+            page_title: row.get(3)?,
+            result_type: "block".to_string(),
+            content,
+            snippet,
+            rank: bm25,
+            // bm25() is negative, with better matches further below zero
+            score: -bm25,
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// Quote the whole input as a single FTS5 string, so every character is literal
+pub(crate) fn quote_fts_literal(query: &str) -> String {
+    format!("\"{}\"", query.trim().replace('"', "\"\""))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagBlockHit {
+    pub block_id: String,
+    /// The tag as matched in this block (a descendant tag when descendants are included)
+    pub tag: String,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagPageGroup {
+    pub page_id: String,
+    pub page_title: String,
+    pub page_path: Option<String>,
+    pub blocks: Vec<TagBlockHit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagView {
+    pub tag: String,
+    pub total_count: usize,
+    pub pages: Vec<TagPageGroup>,
+}
+
+/// All blocks carrying `#tag`, grouped by page.
+///
+/// Tags match case-insensitively; a leading `#` on `tag` is ignored. With
+/// `include_descendants`, nested tags count too (`#proj` includes `#proj/sub`).
+#[tauri::command]
+pub fn get_tag_view(
+    workspace_path: String,
+    tag: String,
+    include_descendants: bool,
+) -> Result<TagView, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    build_tag_view(&conn, &tag, include_descendants)
+}
+
+fn build_tag_view(
+    conn: &Connection,
+    tag: &str,
+    include_descendants: bool,
+) -> Result<TagView, String> {
+    let wanted = tag.trim().trim_start_matches('#').trim().to_lowercase();
+    if wanted.is_empty() {
+        return Err("Tag must not be empty".to_string());
+    }
+    let prefix = format!("{}/", wanted);
+
     let mut stmt = conn
         .prepare(
-            "SELECT b.id, b.page_id, b.content, p.title, b.order_weight,
-                    rank
-             FROM blocks_fts fts
-             JOIN blocks b ON fts.block_id = b.id
+            "SELECT b.id, b.page_id, b.content, p.title, pp.path_text
+             FROM blocks b
              JOIN pages p ON b.page_id = p.id
-             WHERE blocks_fts MATCH ?1
-             AND p.is_deleted = 0
-             ORDER BY rank, p.title COLLATE NOCASE, b.order_weight
-             LIMIT ?2",
+             LEFT JOIN page_paths pp ON pp.page_id = p.id
+             WHERE b.content LIKE '%#%' AND p.is_deleted = 0
+             ORDER BY p.title COLLATE NOCASE, p.id, b.order_key",
         )
         .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
 
-    let block_results = stmt
-        .query_map([&fts_query, &options.limit.to_string()], |row| {
-            let id: String = row.get(0)?;
-            let page_id: String = row.get(1)?;
-            let content: String = row.get(2)?;
-            let page_title: String = row.get(3)?;
-            let rank: f64 = row.get(5)?;
+    let mut pages: Vec<TagPageGroup> = Vec::new();
+    let mut total_count = 0;
 
-            // Create snippet with highlighted match
-            let snippet = create_snippet(&content, &query);
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let content: String = row.get(2).map_err(|e| e.to_string())?;
+        let matched = parse_tags(&content).into_iter().find(|found| {
+            let lower = found.to_lowercase();
+            lower == wanted || (include_descendants && lower.starts_with(&prefix))
+        });
+        let Some(matched) = matched else {
+            continue;
+        };
 
-            Ok(SearchResult {
-                id,
+        let block_id: String = row.get(0).map_err(|e| e.to_string())?;
+        let page_id: String = row.get(1).map_err(|e| e.to_string())?;
+        let hit = TagBlockHit {
+            block_id,
+            tag: matched,
+            snippet: block_snippet(&content),
+        };
+        total_count += 1;
+
+        match pages.last_mut() {
+            Some(group) if group.page_id == page_id => group.blocks.push(hit),
+            _ => pages.push(TagPageGroup {
                 page_id,
-                page_title,
-                result_type: "block".to_string(),
-                content,
-                snippet,
-                rank,
+                page_title: row.get(3).map_err(|e| e.to_string())?,
+                page_path: row.get(4).map_err(|e| e.to_string())?,
+                blocks: vec![hit],
+            }),
+        }
+    }
+
+    Ok(TagView {
+        tag: wanted,
+        total_count,
+        pages,
+    })
+}
+
+/// A tag and the number of blocks carrying it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// A block found through the tag index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggedBlock {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_title: String,
+    /// The indexed (lowercased) tag that matched
+    pub tag: String,
+    pub snippet: String,
+}
+
+/// Every tag used in the workspace with its block count, most used first.
+/// Nested tags are listed under their full path (`project/alpha`).
+#[tauri::command]
+pub fn get_all_tags(workspace_path: String) -> Result<Vec<TagCount>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    collect_tag_counts(&conn)
+}
+
+fn collect_tag_counts(conn: &Connection) -> Result<Vec<TagCount>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.tag, COUNT(*) FROM block_tags t
+             JOIN pages p ON p.id = t.page_id
+             WHERE p.is_deleted = 0
+             GROUP BY t.tag
+             ORDER BY COUNT(*) DESC, t.tag",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TagCount {
+                tag: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
             })
         })
         .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
 
-    for result in block_results {
-        if let Ok(r) = result {
-            results.push(r);
+/// Blocks tagged with `tag` or any tag nested under it (`project` also finds
+/// `#project/alpha`). Matching is case-insensitive and a leading `#` is ignored.
+#[tauri::command]
+pub fn get_blocks_by_tag(workspace_path: String, tag: String) -> Result<Vec<TaggedBlock>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_blocks_by_tag(&conn, &tag)
+}
+
+fn find_blocks_by_tag(conn: &Connection, tag: &str) -> Result<Vec<TaggedBlock>, String> {
+    let wanted = tag.trim().trim_start_matches('#').trim().to_lowercase();
+    if wanted.is_empty() {
+        return Err("Tag must not be empty".to_string());
+    }
+    let prefix = format!("{}/", wanted);
+
+    // substr() rather than LIKE: tags may contain '_', a LIKE wildcard
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.block_id, t.page_id, p.title, t.tag, b.content
+             FROM block_tags t
+             JOIN blocks b ON b.id = t.block_id
+             JOIN pages p ON p.id = t.page_id
+             WHERE p.is_deleted = 0
+               AND (t.tag = :tag OR substr(t.tag, 1, length(:prefix)) = :prefix)
+             ORDER BY p.title COLLATE NOCASE, p.id, b.order_key, t.tag",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::named_params! { ":tag": wanted, ":prefix": prefix },
+            |row| {
+                let content: String = row.get(4)?;
+                Ok(TaggedBlock {
+                    block_id: row.get(0)?,
+                    page_id: row.get(1)?,
+                    page_title: row.get(2)?,
+                    tag: row.get(3)?,
+                    snippet: block_snippet(&content),
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    // A block tagged both `#proj` and `#proj/sub` is listed once
+    let mut seen = std::collections::HashSet::new();
+    let mut blocks = Vec::new();
+    for block in rows {
+        let block = block.map_err(|e| e.to_string())?;
+        if seen.insert(block.block_id.clone()) {
+            blocks.push(block);
         }
     }
+    Ok(blocks)
+}
 
-    Ok(results)
+/// First line-folded stretch of a block, cut at a character boundary
+fn block_snippet(content: &str) -> String {
+    let max_len = 100;
+    let folded = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if folded.chars().count() > max_len {
+        format!("{}...", folded.chars().take(max_len).collect::<String>())
+    } else {
+        folded
+    }
+}
+
+/// Upper bound on `search_with_context` hits; each one costs extra lookups
+const MAX_CONTEXT_SEARCH_RESULTS: usize = 50;
+
+/// A matching block with its surrounding outline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextSearchHit {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub snippet: String,
+    pub parent_id: Option<String>,
+    pub parent_content: Option<String>,
+    pub first_child_id: Option<String>,
+    pub first_child_snippet: Option<String>,
+}
+
+/// Blocks containing `query` (case-insensitive), each with its parent's content and
+/// its first child, so a hit can be read in place. At most 50 hits (default 20).
+#[tauri::command]
+pub fn search_with_context(
+    workspace_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<ContextSearchHit>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    collect_context_hits(&conn, &query, limit)
+}
+
+/// Escape LIKE wildcards so the query matches literally under `ESCAPE '\'`
+fn escape_like(query: &str) -> String {
+    query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn collect_context_hits(
+    conn: &Connection,
+    query: &str,
+    limit: Option<usize>,
+) -> Result<Vec<ContextSearchHit>, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+    let limit = limit.unwrap_or(20).clamp(1, MAX_CONTEXT_SEARCH_RESULTS);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.page_id, p.title, b.content, b.parent_id, parent.content
+             FROM blocks b
+             JOIN pages p ON b.page_id = p.id
+             LEFT JOIN blocks parent ON parent.id = b.parent_id
+             WHERE b.content LIKE ?1 ESCAPE '\\' AND p.is_deleted = 0
+             ORDER BY LENGTH(b.content), p.title COLLATE NOCASE, b.order_key
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![format!("%{}%", escape_like(query)), limit as i64],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut hits = Vec::with_capacity(rows.len());
+    for (block_id, page_id, page_title, content, parent_id, parent_content) in rows {
+        let first_child = load_block_subtree(conn, &block_id, Some(1))?
+            .into_iter()
+            .filter(|b| b.parent_id.as_deref() == Some(block_id.as_str()))
+            .min_by(|a, b| a.cmp_position(b));
+
+        hits.push(ContextSearchHit {
+            snippet: block_snippet(&content),
+            block_id,
+            page_id,
+            page_title,
+            parent_id,
+            parent_content,
+            first_child_snippet: first_child.as_ref().map(|c| block_snippet(&c.content)),
+            first_child_id: first_child.map(|c| c.id),
+        });
+    }
+
+    Ok(hits)
+}
+
+/// Comparison `search_by_metadata` applies to a metadata value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataOp {
+    /// Exact match
+    Eq,
+    /// Case-insensitive substring
+    Contains,
+    /// Numeric comparisons; values that are not numbers never match
+    Gt,
+    Lt,
+}
+
+/// A block whose metadata matched, with the page it is on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataSearchHit {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub page_path: Option<String>,
+    pub key: String,
+    pub value: String,
+    pub snippet: String,
+}
+
+/// Blocks whose `key::value` metadata satisfies `value <op> value_filter`, e.g.
+/// `rating` `gt` `4`. For `gt`/`lt` both sides are compared as numbers and blocks
+/// with a non-numeric value are left out.
+#[tauri::command]
+pub fn search_by_metadata(
+    workspace_path: String,
+    key: String,
+    value_filter: String,
+    op: MetadataOp,
+) -> Result<Vec<MetadataSearchHit>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    collect_metadata_hits(&conn, &key, &value_filter, op)
+}
+
+fn collect_metadata_hits(
+    conn: &Connection,
+    key: &str,
+    value_filter: &str,
+    op: MetadataOp,
+) -> Result<Vec<MetadataSearchHit>, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Metadata key must not be empty".to_string());
+    }
+    let value_filter = value_filter.trim();
+    let number_filter = match op {
+        MetadataOp::Gt | MetadataOp::Lt => Some(
+            value_filter
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' is not a number", value_filter))?,
+        ),
+        MetadataOp::Eq | MetadataOp::Contains => None,
+    };
+    let lower_filter = value_filter.to_lowercase();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.page_id, p.title, pp.path_text, m.value, b.content
+             FROM block_metadata m
+             JOIN blocks b ON b.id = m.block_id
+             JOIN pages p ON b.page_id = p.id
+             LEFT JOIN page_paths pp ON pp.page_id = p.id
+             WHERE m.key = ? AND p.is_deleted = 0
+             ORDER BY p.title COLLATE NOCASE, p.id, b.order_key",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([key], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let hits = rows
+        .into_iter()
+        .filter(|(_, _, _, _, value, _)| {
+            let value = value.trim();
+            match op {
+                MetadataOp::Eq => value == value_filter,
+                MetadataOp::Contains => value.to_lowercase().contains(&lower_filter),
+                MetadataOp::Gt | MetadataOp::Lt => {
+                    let (Ok(number), Some(filter)) = (value.parse::<f64>(), number_filter) else {
+                        return false;
+                    };
+                    if op == MetadataOp::Gt {
+                        number > filter
+                    } else {
+                        number < filter
+                    }
+                }
+            }
+        })
+        .map(
+            |(block_id, page_id, page_title, page_path, value, content)| MetadataSearchHit {
+                block_id,
+                page_id,
+                page_title,
+                page_path,
+                key: key.to_string(),
+                value,
+                snippet: block_snippet(&content),
+            },
+        )
+        .collect();
+
+    Ok(hits)
+}
+
+/// Default number of entries returned by `get_search_history`
+const DEFAULT_SEARCH_HISTORY_LIMIT: usize = 20;
+/// Oldest entries beyond this many are dropped whenever a search is recorded
+const MAX_SEARCH_HISTORY_ENTRIES: usize = 200;
+
+/// A past search, most recent first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHistoryEntry {
+    pub query: String,
+    pub result_count: i64,
+    pub searched_at: String,
+}
+
+/// Remember a search the user actually ran; repeating a query moves it back to the top.
+/// Search commands never call this themselves, since autocomplete and AI tools share them.
+#[tauri::command]
+pub fn record_search_history(
+    workspace_path: String,
+    query: String,
+    result_count: usize,
+) -> Result<(), String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    record_search(&conn, &query, result_count);
+    Ok(())
+}
+
+/// A failed write is logged and otherwise ignored so history never breaks the search itself
+pub(crate) fn record_search(conn: &Connection, query: &str, result_count: usize) {
+    let query = query.trim();
+    if query.is_empty() {
+        return;
+    }
+    let searched_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let result = conn
+        .execute(
+            "INSERT INTO search_history (query, result_count, searched_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(query) DO UPDATE SET
+                result_count = excluded.result_count,
+                searched_at = excluded.searched_at",
+            params![query, result_count as i64, searched_at],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM search_history WHERE query NOT IN (
+                    SELECT query FROM search_history ORDER BY searched_at DESC LIMIT ?1
+                 )",
+                [MAX_SEARCH_HISTORY_ENTRIES as i64],
+            )
+        });
+    if let Err(e) = result {
+        log::warn!("[search] Failed to record search history: {}", e);
+    }
+}
+
+/// Recent searches in this workspace, newest first (default 20)
+#[tauri::command]
+pub fn get_search_history(
+    workspace_path: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHistoryEntry>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    load_search_history(&conn, limit.unwrap_or(DEFAULT_SEARCH_HISTORY_LIMIT))
+}
+
+#[tauri::command]
+pub fn clear_search_history(workspace_path: String) -> Result<(), String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    conn.execute("DELETE FROM search_history", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn load_search_history(conn: &Connection, limit: usize) -> Result<Vec<SearchHistoryEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT query, result_count, searched_at FROM search_history
+             ORDER BY searched_at DESC LIMIT ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = stmt
+        .query_map([limit as i64], |row| {
+            Ok(SearchHistoryEntry {
+                query: row.get(0)?,
+                result_count: row.get(1)?,
+                searched_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(entries)
 }
 
 /// Build FTS5 query from user input
@@ -169,8 +722,12 @@ fn build_fts_query(query: &str, use_phrase_search: bool, use_boolean_operators:
     }
 
     if use_phrase_search {
-        // Wrap single query in quotes for phrase matching
-        format!("\"{}\"", query)
+        // Wrap single query in quotes for phrase matching; a trailing `*` stays
+        // outside the quotes so it is still a prefix query
+        match query.strip_suffix('*') {
+            Some(prefix) if !prefix.is_empty() => format!("\"{}\"*", prefix),
+            _ => format!("\"{}\"", query),
+        }
     } else {
         // Use simple contains search
         query.to_string()
@@ -328,6 +885,17 @@ mod tests {
         assert_eq!(query, "hello");
     }
 
+    #[test]
+    fn test_build_fts_query_prefix() {
+        let query = build_fts_query("hel*", true, true);
+        assert_eq!(query, "\"hel\"*");
+    }
+
+    #[test]
+    fn test_quote_fts_literal_escapes_quotes() {
+        assert_eq!(quote_fts_literal(" say \"hi\" "), "\"say \"\"hi\"\"\"");
+    }
+
     #[test]
     fn test_validate_fts_query_removes_invalid_chars() {
         let query = validate_fts_query("hello@world#123");
@@ -372,4 +940,261 @@ mod tests {
         let snippet = create_snippet(text, "fox");
         assert!(snippet.contains("**fox**"));
     }
+
+    #[test]
+    fn test_tag_view_groups_by_page_and_includes_descendants() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Alpha'), ('p2', 'Beta');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('p1', 'Alpha'), ('p2', 'Work/Beta');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'p1', 'Kickoff #proj', 1.0),
+                ('a2', 'p1', 'Unrelated #project', 2.0),
+                ('a3', 'p1', 'Subtask #proj/sub', 3.0),
+                ('b1', 'p2', 'Design notes #Proj/sub', 1.0),
+                ('b2', 'p2', 'Code `#proj` only', 2.0);",
+        )
+        .unwrap();
+
+        let exact = build_tag_view(&conn, "#proj", false).unwrap();
+        assert_eq!(exact.total_count, 1);
+        assert_eq!(exact.pages.len(), 1);
+        assert_eq!(exact.pages[0].page_title, "Alpha");
+        assert_eq!(exact.pages[0].blocks[0].block_id, "a1");
+
+        let nested = build_tag_view(&conn, "proj", true).unwrap();
+        assert_eq!(nested.total_count, 3);
+        assert_eq!(nested.pages.len(), 2);
+        let alpha: Vec<&str> = nested.pages[0]
+            .blocks
+            .iter()
+            .map(|b| b.block_id.as_str())
+            .collect();
+        assert_eq!(alpha, vec!["a1", "a3"]);
+        assert_eq!(nested.pages[1].page_path.as_deref(), Some("Work/Beta"));
+        assert_eq!(nested.pages[1].blocks[0].tag, "Proj/sub");
+    }
+
+    #[test]
+    fn test_tag_index_counts_and_prefix_lookup() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Alpha'), ('p2', 'Beta');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('a1', 'p1', 'Kickoff #Project', 1.0),
+                ('a2', 'p1', 'Plan #project/alpha and #project', 2.0),
+                ('a3', 'p1', 'Code `#project` only, issue#12', 3.0),
+                ('b1', 'p2', 'Other #projects #my_tag', 1.0);",
+        )
+        .unwrap();
+        crate::services::wiki_link_index::reindex_all_tags(&conn).unwrap();
+
+        let counts: Vec<(String, usize)> = collect_tag_counts(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.tag, t.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("project".to_string(), 2),
+                ("my_tag".to_string(), 1),
+                ("project/alpha".to_string(), 1),
+                ("projects".to_string(), 1),
+            ]
+        );
+
+        let blocks: Vec<String> = find_blocks_by_tag(&conn, "#PROJECT")
+            .unwrap()
+            .into_iter()
+            .map(|b| b.block_id)
+            .collect();
+        assert_eq!(blocks, vec!["a1", "a2"]);
+
+        let nested = find_blocks_by_tag(&conn, "project/alpha").unwrap();
+        assert_eq!(nested.len(), 1);
+        assert_eq!(nested[0].page_title, "Alpha");
+        assert_eq!(
+            find_blocks_by_tag(&conn, "my_tag").unwrap()[0].block_id,
+            "b1"
+        );
+        assert!(find_blocks_by_tag(&conn, "myxtag").unwrap().is_empty());
+
+        // Editing a block replaces its tags
+        crate::services::wiki_link_index::index_block_links(&conn, "a1", "No tags", "p1").unwrap();
+        assert_eq!(find_blocks_by_tag(&conn, "project").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search_with_context_includes_parent_and_child() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Trip');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('day', 'p1', 'Day one', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('hit', 'p1', 'day', 'Visit the Louvre', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('later', 'p1', 'hit', 'Buy tickets\nonline', 2.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('first', 'p1', 'hit', 'Open at 9', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('top', 'p1', 'Louvre map', 2.0);",
+        )
+        .unwrap();
+
+        let hits = collect_context_hits(&conn, "louvre", None).unwrap();
+        assert_eq!(hits.len(), 2);
+
+        let nested = hits.iter().find(|h| h.block_id == "hit").unwrap();
+        assert_eq!(nested.page_title, "Trip");
+        assert_eq!(nested.snippet, "Visit the Louvre");
+        assert_eq!(nested.parent_id.as_deref(), Some("day"));
+        assert_eq!(nested.parent_content.as_deref(), Some("Day one"));
+        assert_eq!(nested.first_child_id.as_deref(), Some("first"));
+        assert_eq!(nested.first_child_snippet.as_deref(), Some("Open at 9"));
+
+        let root = hits.iter().find(|h| h.block_id == "top").unwrap();
+        assert_eq!(root.parent_content, None);
+        assert_eq!(root.first_child_id, None);
+
+        assert_eq!(
+            collect_context_hits(&conn, "louvre", Some(1))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(collect_context_hits(&conn, "  ", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_with_context_matches_wildcards_literally() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Notes');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('pct', 'p1', 'Grew 50% this year', 1.0),
+                ('num', 'p1', 'Grew 500 units', 2.0),
+                ('snake', 'p1', 'call my_var', 3.0),
+                ('word', 'p1', 'call myXvar', 4.0),
+                ('path', 'p1', 'C:\\temp', 5.0);",
+        )
+        .unwrap();
+
+        let ids = |query: &str| -> Vec<String> {
+            collect_context_hits(&conn, query, None)
+                .unwrap()
+                .into_iter()
+                .map(|h| h.block_id)
+                .collect()
+        };
+        assert_eq!(ids("50%"), vec!["pct"]);
+        assert_eq!(ids("my_var"), vec!["snake"]);
+        assert_eq!(ids("C:\\temp"), vec!["path"]);
+    }
+
+    #[test]
+    fn test_search_blocks_fts_ranks_by_bm25_and_falls_back_to_literal() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Notes');
+             INSERT INTO blocks (id, page_id, content, order_weight)
+                VALUES ('weak', 'p1', 'a long block that mentions rust only once among many other words', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight)
+                VALUES ('strong', 'p1', 'rust rust rust', 2.0);
+             INSERT INTO blocks (id, page_id, content, order_weight)
+                VALUES ('cpp', 'p1', 'learning c++ (again)', 3.0);
+             INSERT INTO blocks_fts (block_id, page_id, content) SELECT id, page_id, content FROM blocks;",
+        )
+        .unwrap();
+
+        let hits =
+            search_blocks_fts(&conn, &build_fts_query("rus*", true, true), "rus", 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].id, "strong");
+        assert!(hits[0].score > hits[1].score);
+        assert!(hits[1].score > 0.0);
+
+        let raw = build_fts_query("c++ (again", true, true);
+        assert!(search_blocks_fts(&conn, &raw, "c++ (again", 10).is_err());
+        let hits =
+            search_blocks_fts(&conn, &quote_fts_literal("c++ (again"), "c++ (again", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "cpp");
+    }
+
+    #[test]
+    fn test_search_by_metadata_ops_skip_non_numeric_values() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Movies');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('p1', 'Reviews/Movies');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('heat', 'p1', 'Heat', 1.0),
+                ('cats', 'p1', 'Cats', 2.0),
+                ('dune', 'p1', 'Dune', 3.0),
+                ('alien', 'p1', 'Alien', 4.0);
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES
+                ('m1', 'heat', 'rating', '5'),
+                ('m2', 'cats', 'rating', '1.5'),
+                ('m3', 'dune', 'rating', 'unrated'),
+                ('m4', 'alien', 'rating', '4.5'),
+                ('m5', 'heat', 'genre', 'Crime Drama');",
+        )
+        .unwrap();
+
+        let ids = |op: MetadataOp, key: &str, filter: &str| -> Vec<String> {
+            collect_metadata_hits(&conn, key, filter, op)
+                .unwrap()
+                .into_iter()
+                .map(|hit| hit.block_id)
+                .collect()
+        };
+
+        assert_eq!(ids(MetadataOp::Gt, "rating", "4"), vec!["heat", "alien"]);
+        assert_eq!(ids(MetadataOp::Lt, "rating", "4"), vec!["cats"]);
+        assert_eq!(ids(MetadataOp::Eq, "rating", "unrated"), vec!["dune"]);
+        assert_eq!(ids(MetadataOp::Contains, "genre", "drama"), vec!["heat"]);
+
+        let hits = collect_metadata_hits(&conn, "rating", "4.9", MetadataOp::Gt).unwrap();
+        assert_eq!(hits[0].page_path.as_deref(), Some("Reviews/Movies"));
+        assert_eq!(hits[0].value, "5");
+
+        assert!(collect_metadata_hits(&conn, "rating", "high", MetadataOp::Gt).is_err());
+    }
+
+    #[test]
+    fn test_search_history_dedupes_and_orders_by_recency() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+
+        for (query, count) in [("rust", 3), ("   ", 0), ("notes", 1), (" rust ", 5)] {
+            record_search(&conn, query, count);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let history = load_search_history(&conn, 10).unwrap();
+        let queries: Vec<(&str, i64)> = history
+            .iter()
+            .map(|entry| (entry.query.as_str(), entry.result_count))
+            .collect();
+        assert_eq!(queries, vec![("rust", 5), ("notes", 1)]);
+        assert_eq!(load_search_history(&conn, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search_history_is_capped() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+
+        for i in 0..MAX_SEARCH_HISTORY_ENTRIES + 5 {
+            record_search(&conn, &format!("query {}", i), 1);
+        }
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM search_history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, MAX_SEARCH_HISTORY_ENTRIES as i64);
+    }
 }
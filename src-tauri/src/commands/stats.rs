@@ -0,0 +1,418 @@
+use regex::Regex;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::commands::workspace::open_workspace_db;
+use crate::services::wiki_link_parser::parse_tags;
+use crate::utils::markdown::plain_text;
+use crate::utils::path::{validate_no_path_traversal, validate_workspace_containment};
+
+/// Common English words left out of the word summary when `exclude_stopwords` is set
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be",
+    "been", "before", "but", "by", "can", "could", "did", "do", "does", "for", "from", "had",
+    "has", "have", "he", "her", "here", "him", "his", "how", "i", "if", "in", "into", "is", "it",
+    "its", "just", "me", "more", "my", "no", "not", "now", "of", "on", "one", "only", "or",
+    "other", "our", "out", "over", "she", "so", "some", "such", "than", "that", "the", "their",
+    "them", "then", "there", "these", "they", "this", "those", "to", "too", "up", "us", "very",
+    "was", "we", "were", "what", "when", "where", "which", "who", "why", "will", "with", "would",
+    "you", "your",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TermFrequency {
+    pub term: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordFrequencySummary {
+    pub words: Vec<TermFrequency>,
+    pub tags: Vec<TermFrequency>,
+    /// Number of words counted after filtering, before truncating to `top_n`
+    pub total_words: usize,
+}
+
+/// Most frequent words and tags across all block content, for a word/tag cloud.
+///
+/// Only pages that are not deleted count. Words are lowercased and split on Unicode
+/// word boundaries. URLs, inline code and tags are stripped and the rest is reduced to
+/// its plain text (links count as their shown text) before counting words; tags
+/// (`#tag`, `#[[multi word]]`) are counted separately.
+#[tauri::command]
+pub fn get_word_frequency(
+    workspace_path: String,
+    top_n: usize,
+    min_length: usize,
+    exclude_stopwords: bool,
+) -> Result<WordFrequencySummary, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    compute_word_frequency(&conn, top_n, min_length, exclude_stopwords)
+}
+
+fn compute_word_frequency(
+    conn: &Connection,
+    top_n: usize,
+    min_length: usize,
+    exclude_stopwords: bool,
+) -> Result<WordFrequencySummary, String> {
+    let tag_re =
+        Regex::new(r"(?:^|[^\w&/])#(?:\[\[([^\]]+)\]\]|([\w/-]+))").map_err(|e| e.to_string())?;
+    let noise_re = Regex::new(r"https?://\S+|\(\([^)]*\)\)|`[^`]*`").map_err(|e| e.to_string())?;
+
+    let mut word_counts: HashMap<String, usize> = HashMap::new();
+    let mut tag_counts: HashMap<String, usize> = HashMap::new();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.content FROM blocks b
+             JOIN pages p ON p.id = b.page_id
+             WHERE p.is_deleted = 0",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let content: String = row.get(0).map_err(|e| e.to_string())?;
+
+        let without_noise = noise_re.replace_all(&content, " ");
+        for caps in tag_re.captures_iter(&without_noise) {
+            if let Some(tag) = caps.get(1).or_else(|| caps.get(2)) {
+                *tag_counts
+                    .entry(tag.as_str().trim().to_lowercase())
+                    .or_insert(0) += 1;
+            }
+        }
+
+        let text = plain_text(&tag_re.replace_all(&without_noise, " "));
+        for word in text.unicode_words() {
+            let word = word.to_lowercase();
+            if word.chars().count() < min_length || word.chars().all(|c| c.is_numeric()) {
+                continue;
+            }
+            if exclude_stopwords && STOPWORDS.contains(&word.as_str()) {
+                continue;
+            }
+            *word_counts.entry(word).or_insert(0) += 1;
+        }
+    }
+
+    let total_words = word_counts.values().sum();
+
+    Ok(WordFrequencySummary {
+        words: top_terms(word_counts, top_n),
+        tags: top_terms(tag_counts, top_n),
+        total_words,
+    })
+}
+
+/// Sort by descending count (ties alphabetically) and keep the first `top_n`
+fn top_terms(counts: HashMap<String, usize>, top_n: usize) -> Vec<TermFrequency> {
+    let mut terms: Vec<TermFrequency> = counts
+        .into_iter()
+        .map(|(term, count)| TermFrequency { term, count })
+        .collect();
+    terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+    terms.truncate(top_n);
+    terms
+}
+
+/// One page's structured data in the metadata index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageMetadataEntry {
+    pub page_id: String,
+    pub title: String,
+    /// The YAML frontmatter of the page file
+    pub frontmatter: BTreeMap<String, String>,
+    /// Page properties: the metadata of the page's first root block
+    pub properties: BTreeMap<String, String>,
+    /// Every metadata key used by any block of the page
+    pub metadata_keys: Vec<String>,
+    /// Lowercased, deduplicated tags used anywhere on the page
+    pub tags: Vec<String>,
+}
+
+/// Write a JSON catalog of every page's structured data to `dest_path`, keyed by the
+/// page's file path relative to the workspace. `dest_path` is relative to the
+/// workspace and must stay inside it. Returns the number of pages written.
+#[tauri::command]
+pub async fn export_metadata_index(
+    workspace_path: String,
+    dest_path: String,
+) -> Result<usize, String> {
+    let index = {
+        let conn = open_workspace_db(&workspace_path)?;
+        build_metadata_index(&conn)?
+    };
+
+    let dest = resolve_export_dest(&workspace_path, &dest_path)?;
+    let json = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    tokio::fs::write(&dest, json)
+        .await
+        .map_err(|e| format!("Failed to write metadata index: {}", e))?;
+
+    Ok(index.len())
+}
+
+/// Resolve a workspace-relative destination that may not exist yet. Its nearest
+/// existing ancestor must resolve (symlinks included) inside the workspace.
+fn resolve_export_dest(workspace_path: &str, dest_path: &str) -> Result<PathBuf, String> {
+    validate_no_path_traversal(dest_path, "dest_path")?;
+
+    let workspace = Path::new(workspace_path);
+    let mut existing = Path::new(dest_path);
+    while !workspace.join(existing).exists() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => break,
+        }
+    }
+    validate_workspace_containment(workspace_path, &existing.to_string_lossy())?;
+
+    Ok(workspace.join(dest_path))
+}
+
+fn build_metadata_index(conn: &Connection) -> Result<BTreeMap<String, PageMetadataEntry>, String> {
+    let mut index = BTreeMap::new();
+
+    let mut page_stmt = conn
+        .prepare(
+            "SELECT id, title, file_path FROM pages
+             WHERE is_deleted = 0 AND is_directory = 0 AND file_path IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+    let pages = page_stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut first_block_stmt = conn
+        .prepare(
+            "SELECT id FROM blocks WHERE page_id = ? AND parent_id IS NULL
+             ORDER BY order_key LIMIT 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut frontmatter_stmt = conn
+        .prepare("SELECT key, value FROM page_metadata WHERE page_id = ?")
+        .map_err(|e| e.to_string())?;
+    let mut properties_stmt = conn
+        .prepare("SELECT key, value FROM block_metadata WHERE block_id = ?")
+        .map_err(|e| e.to_string())?;
+    let mut keys_stmt = conn
+        .prepare(
+            "SELECT DISTINCT m.key FROM block_metadata m
+             JOIN blocks b ON m.block_id = b.id
+             WHERE b.page_id = ? ORDER BY m.key",
+        )
+        .map_err(|e| e.to_string())?;
+    let mut content_stmt = conn
+        .prepare("SELECT content FROM blocks WHERE page_id = ?")
+        .map_err(|e| e.to_string())?;
+
+    for (page_id, title, file_path) in pages {
+        let first_block: Option<String> = first_block_stmt
+            .query_row([&page_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        let frontmatter = frontmatter_stmt
+            .query_map([&page_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<BTreeMap<String, String>, _>>()
+            .map_err(|e| e.to_string())?;
+        let properties = match first_block {
+            Some(block_id) => properties_stmt
+                .query_map([&block_id], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<BTreeMap<String, String>, _>>()
+                .map_err(|e| e.to_string())?,
+            None => BTreeMap::new(),
+        };
+
+        let metadata_keys = keys_stmt
+            .query_map([&page_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut tags = BTreeSet::new();
+        let mut rows = content_stmt.query([&page_id]).map_err(|e| e.to_string())?;
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let content: String = row.get(0).map_err(|e| e.to_string())?;
+            tags.extend(parse_tags(&content).into_iter().map(|t| t.to_lowercase()));
+        }
+
+        index.insert(
+            file_path,
+            PageMetadataEntry {
+                page_id,
+                title,
+                frontmatter,
+                properties,
+                metadata_keys,
+                tags: tags.into_iter().collect(),
+            },
+        );
+    }
+
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_frequency_top_word_excludes_stopwords_and_short_words() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('p1', 'Page');
+             INSERT INTO pages (id, title, is_deleted) VALUES ('gone', 'Gone', 1);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('b1', 'p1', 'The Rust compiler and the rust book #rust', 1.0),
+                ('b2', 'p1', 'Rust is fast; see [[Rust Guide|guide]] and https://example.com/rust', 2.0),
+                ('b3', 'p1', 'Über café, über alles #[[Reading List]] #rust\nstatus::draft', 3.0),
+                ('b4', 'p1', 'Can''t stop [[Zettelkasten]]', 4.0),
+                ('d1', 'gone', 'rust rust rust trashed', 1.0);",
+        )
+        .unwrap();
+
+        let summary = compute_word_frequency(&conn, 3, 3, true).unwrap();
+
+        assert_eq!(
+            summary.words[0],
+            TermFrequency {
+                term: "rust".to_string(),
+                count: 3
+            }
+        );
+        assert_eq!(
+            summary.words[1],
+            TermFrequency {
+                term: "über".to_string(),
+                count: 2
+            }
+        );
+        assert!(summary
+            .words
+            .iter()
+            .all(|w| w.term != "the" && w.term != "and"));
+        assert!(summary.words.iter().all(|w| w.term.chars().count() >= 3));
+
+        assert_eq!(
+            summary.tags,
+            vec![
+                TermFrequency {
+                    term: "rust".to_string(),
+                    count: 2
+                },
+                TermFrequency {
+                    term: "reading list".to_string(),
+                    count: 1
+                },
+            ]
+        );
+
+        let with_stopwords = compute_word_frequency(&conn, 20, 3, false).unwrap();
+        assert!(with_stopwords.words.iter().any(|w| w.term == "the"));
+        let terms: Vec<&str> = with_stopwords
+            .words
+            .iter()
+            .map(|w| w.term.as_str())
+            .collect();
+        // Link syntax is gone, the shown text counts, metadata and deleted pages don't
+        assert!(terms.contains(&"guide"));
+        assert!(terms.contains(&"zettelkasten"));
+        assert!(terms.contains(&"can't"));
+        assert!(!terms
+            .iter()
+            .any(|t| t.contains('[') || *t == "status" || *t == "draft"));
+        assert!(!terms.contains(&"trashed"));
+    }
+
+    #[test]
+    fn test_metadata_index_lists_frontmatter_and_tags_per_page() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES
+                ('p1', 'Inception', 'Movies/Inception.md'),
+                ('p2', 'Plain', 'Plain.md');
+             INSERT INTO pages (id, title, file_path, is_directory) VALUES ('d1', 'Movies', 'Movies', 1);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES
+                ('b1', 'p1', NULL, 'Properties', 1.0),
+                ('b2', 'p1', NULL, 'Seen it #Movie #sci-fi', 2.0),
+                ('b3', 'p1', 'b2', 'Again #movie', 1.0),
+                ('c1', 'p2', NULL, 'Nothing here', 1.0);
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES
+                ('m1', 'b1', 'year', '2010'),
+                ('m2', 'b1', 'director', 'Christopher Nolan'),
+                ('m3', 'b3', 'rating', '5');
+             INSERT INTO page_metadata (page_id, key, value) VALUES ('p1', 'genre', 'sci-fi');",
+        )
+        .unwrap();
+
+        let index = build_metadata_index(&conn).unwrap();
+        assert_eq!(index.len(), 2);
+
+        let movie = &index["Movies/Inception.md"];
+        assert_eq!(movie.title, "Inception");
+        assert_eq!(movie.properties.get("year"), Some(&"2010".to_string()));
+        assert_eq!(
+            movie.properties.get("director"),
+            Some(&"Christopher Nolan".to_string())
+        );
+        assert!(!movie.properties.contains_key("rating"));
+        assert_eq!(movie.frontmatter.len(), 1);
+        assert_eq!(movie.frontmatter.get("genre"), Some(&"sci-fi".to_string()));
+        assert_eq!(movie.metadata_keys, vec!["director", "rating", "year"]);
+        assert_eq!(movie.tags, vec!["movie", "sci-fi"]);
+
+        let plain = &index["Plain.md"];
+        assert!(plain.frontmatter.is_empty());
+        assert!(plain.properties.is_empty());
+        assert!(plain.tags.is_empty());
+    }
+
+    #[test]
+    fn test_export_dest_must_stay_inside_workspace() {
+        let dir = std::env::temp_dir().join(format!("oxinot_test_export_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let workspace = dir.to_string_lossy().to_string();
+
+        assert_eq!(
+            resolve_export_dest(&workspace, "exports/index.json").unwrap(),
+            dir.join("exports/index.json")
+        );
+        assert!(resolve_export_dest(&workspace, "../index.json").is_err());
+        assert!(resolve_export_dest(&workspace, "/tmp/index.json").is_err());
+
+        #[cfg(unix)]
+        {
+            let outside = std::env::temp_dir().join(format!("oxinot_out_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, dir.join("link")).unwrap();
+            assert!(resolve_export_dest(&workspace, "link/index.json").is_err());
+            std::fs::remove_dir_all(&outside).unwrap();
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,15 @@
-use chrono::Utc;
+use chrono::{Local, NaiveDate, Utc};
+use rusqlite::{params, params_from_iter, Connection};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
 
 use crate::commands::workspace::open_workspace_db;
+use crate::error::OxinotError;
+use crate::services::block_history::{self, InverseOperation};
+use crate::utils::natural_date::parse_natural_date;
+use crate::utils::page_sync::sync_page_to_markdown;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TodoFilter {
@@ -178,3 +186,476 @@ pub async fn query_todos(
 
     Ok(results)
 }
+
+/// Metadata keys that place a task on the agenda
+const AGENDA_DATE_KEYS: [&str; 3] = ["due", "scheduled", "deadline"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgendaItem {
+    pub block_id: String,
+    pub content: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub page_path: Option<String>,
+    pub status: String,
+    /// Normalized `YYYY-MM-DD` date the task is bucketed by
+    pub date: String,
+    /// Metadata key the date came from (`due`, `scheduled` or `deadline`)
+    pub date_key: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAgenda {
+    pub overdue: Vec<AgendaItem>,
+    pub today: Vec<AgendaItem>,
+    pub upcoming: Vec<AgendaItem>,
+}
+
+/// Get open tasks dated within `from..=to`, bucketed relative to local today
+#[tauri::command]
+pub async fn get_task_agenda(
+    workspace_path: String,
+    from: String,
+    to: String,
+) -> Result<TaskAgenda, String> {
+    let from = parse_agenda_date(&from).ok_or_else(|| format!("Invalid from date: {}", from))?;
+    let to = parse_agenda_date(&to).ok_or_else(|| format!("Invalid to date: {}", to))?;
+    if from > to {
+        return Err("from date must not be after to date".to_string());
+    }
+
+    let conn = open_workspace_db(&workspace_path)?;
+    build_task_agenda(&conn, from, to, Local::now().date_naive())
+}
+
+fn build_task_agenda(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    today: NaiveDate,
+) -> Result<TaskAgenda, String> {
+    let key_placeholders = AGENDA_DATE_KEYS.map(|_| "?").join(",");
+    let sql = format!(
+        r#"
+        SELECT b.id, b.content, b.page_id, p.title, p.file_path,
+               bm_status.value, bm_date.key, bm_date.value
+        FROM block_metadata bm_status
+        JOIN blocks b ON b.id = bm_status.block_id
+        JOIN pages p ON p.id = b.page_id
+        JOIN block_metadata bm_date
+            ON bm_date.block_id = b.id AND bm_date.key IN ({})
+        WHERE bm_status.key = 'todoStatus'
+          AND bm_status.value NOT IN ('done', 'canceled')
+          AND p.is_deleted = 0
+        "#,
+        key_placeholders
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(AGENDA_DATE_KEYS), |row| {
+            Ok((
+                AgendaItem {
+                    block_id: row.get(0)?,
+                    content: row.get(1)?,
+                    page_id: row.get(2)?,
+                    page_title: row.get(3)?,
+                    page_path: row.get(4)?,
+                    status: row.get(5)?,
+                    date: String::new(),
+                    date_key: row.get(6)?,
+                },
+                row.get::<_, String>(7)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // A task may carry several date keys; it is listed once under its earliest date
+    let mut earliest: HashMap<String, (NaiveDate, AgendaItem)> = HashMap::new();
+    for (item, raw_date) in rows {
+        let Some(date) = parse_agenda_date(&raw_date) else {
+            continue;
+        };
+        match earliest.get(&item.block_id) {
+            Some((existing, _)) if *existing <= date => {}
+            _ => {
+                earliest.insert(item.block_id.clone(), (date, item));
+            }
+        }
+    }
+
+    let mut entries: Vec<(NaiveDate, AgendaItem)> = earliest
+        .into_values()
+        .filter(|(date, _)| *date >= from && *date <= to)
+        .collect();
+    entries.sort_by(|(a_date, a), (b_date, b)| {
+        a_date
+            .cmp(b_date)
+            .then_with(|| a.page_title.cmp(&b.page_title))
+            .then_with(|| a.block_id.cmp(&b.block_id))
+    });
+
+    let mut agenda = TaskAgenda::default();
+    for (date, mut item) in entries {
+        item.date = date.format("%Y-%m-%d").to_string();
+        if date < today {
+            agenda.overdue.push(item);
+        } else if date == today {
+            agenda.today.push(item);
+        } else {
+            agenda.upcoming.push(item);
+        }
+    }
+
+    Ok(agenda)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledBlock {
+    pub block_id: String,
+    pub content: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub page_path: Option<String>,
+    /// Normalized `YYYY-MM-DD` date the block is scheduled for
+    pub date: String,
+}
+
+/// Get blocks scheduled within `from..=to`, earliest first. A block is scheduled by
+/// `SCHEDULED::YYYY-MM-DD` or `@YYYY-MM-DD` in its content, or by `scheduled::`
+/// metadata (`set_block_schedule`); with several dates the earliest counts.
+#[tauri::command]
+pub async fn get_scheduled_blocks(
+    workspace_path: String,
+    from: String,
+    to: String,
+) -> Result<Vec<ScheduledBlock>, String> {
+    let from = parse_agenda_date(&from).ok_or_else(|| format!("Invalid from date: {}", from))?;
+    let to = parse_agenda_date(&to).ok_or_else(|| format!("Invalid to date: {}", to))?;
+    if from > to {
+        return Err("from date must not be after to date".to_string());
+    }
+
+    let conn = open_workspace_db(&workspace_path)?;
+    load_scheduled_blocks(&conn, from, to)
+}
+
+fn load_scheduled_blocks(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<ScheduledBlock>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT b.id, b.content, b.page_id, p.title, p.file_path, s.value
+            FROM (
+                SELECT block_id, value FROM block_metadata WHERE key = 'scheduled'
+                UNION ALL
+                SELECT block_id, scheduled_date FROM block_schedule
+            ) s
+            JOIN blocks b ON b.id = s.block_id
+            JOIN pages p ON p.id = b.page_id
+            WHERE p.is_deleted = 0
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                ScheduledBlock {
+                    block_id: row.get(0)?,
+                    content: row.get(1)?,
+                    page_id: row.get(2)?,
+                    page_title: row.get(3)?,
+                    page_path: row.get(4)?,
+                    date: String::new(),
+                },
+                row.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut earliest: HashMap<String, (NaiveDate, ScheduledBlock)> = HashMap::new();
+    for (block, raw_date) in rows {
+        let Some(date) = parse_agenda_date(&raw_date) else {
+            continue;
+        };
+        match earliest.get(&block.block_id) {
+            Some((existing, _)) if *existing <= date => {}
+            _ => {
+                earliest.insert(block.block_id.clone(), (date, block));
+            }
+        }
+    }
+
+    let mut entries: Vec<(NaiveDate, ScheduledBlock)> = earliest
+        .into_values()
+        .filter(|(date, _)| *date >= from && *date <= to)
+        .collect();
+    entries.sort_by(|(a_date, a), (b_date, b)| {
+        a_date
+            .cmp(b_date)
+            .then_with(|| a.page_title.cmp(&b.page_title))
+            .then_with(|| a.block_id.cmp(&b.block_id))
+    });
+
+    Ok(entries
+        .into_iter()
+        .map(|(date, mut block)| {
+            block.date = date.format("%Y-%m-%d").to_string();
+            block
+        })
+        .collect())
+}
+
+/// Schedule a block for a date given in words ("tomorrow", "next monday",
+/// "in 3 days") or as `YYYY-MM-DD`, stored as `scheduled::YYYY-MM-DD`.
+/// Returns the resolved date.
+#[tauri::command]
+pub async fn set_block_schedule(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    block_id: String,
+    natural_date: String,
+) -> Result<String, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let (page_id, date) =
+        store_block_schedule(&conn, &block_id, &natural_date, Local::now().date_naive())?;
+
+    let conn_mutex = Mutex::new(conn);
+    sync_page_to_markdown(&conn_mutex, &workspace_path, &page_id).await?;
+
+    crate::utils::events::emit_page_changed(&app, &workspace_path, &page_id);
+
+    Ok(date)
+}
+
+/// Returns (page_id, `YYYY-MM-DD`)
+fn store_block_schedule(
+    conn: &Connection,
+    block_id: &str,
+    natural_date: &str,
+    today: NaiveDate,
+) -> Result<(String, String), String> {
+    let date = parse_natural_date(natural_date, today)
+        .ok_or_else(|| {
+            OxinotError::validation(format!("Unrecognized date: '{}'", natural_date.trim()))
+        })?
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let page_id: String = conn
+        .query_row(
+            "SELECT page_id FROM blocks WHERE id = ?",
+            [block_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| OxinotError::BlockNotFound(block_id.to_string()).to_string())?;
+
+    let restore = block_history::snapshot_blocks(conn, &[block_id])?;
+    conn.execute(
+        "DELETE FROM block_metadata WHERE block_id = ? AND key = 'scheduled'",
+        [block_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, 'scheduled', ?)",
+        params![Uuid::new_v4().to_string(), block_id, &date],
+    )
+    .map_err(|e| e.to_string())?;
+    block_history::record_operation(
+        conn,
+        "set_block_schedule",
+        &page_id,
+        &InverseOperation {
+            restore,
+            remove: Vec::new(),
+        },
+    )?;
+
+    Ok((page_id, date))
+}
+
+/// Parse a metadata date value such as `2024-03-01`, `2024/03/01`,
+/// `[[2024-03-01]]`, `<2024-03-01 Fri 10:00>` or `2024-03-01T10:00:00Z`
+fn parse_agenda_date(value: &str) -> Option<NaiveDate> {
+    let trimmed = value
+        .trim()
+        .trim_start_matches(['[', '<'])
+        .trim_end_matches([']', '>']);
+    let date_part = trimmed
+        .split(|c: char| c.is_whitespace() || c == 'T')
+        .next()?;
+
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(date_part, fmt).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rusqlite::params;
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('page1', 'Plans', 'Plans.md')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn seed_task(conn: &Connection, id: &str, status: &str, key: &str, date: &str) {
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES (?, 'page1', ?, 1.0)",
+            params![id, format!("TODO {}", id)],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, 'todoStatus', ?)",
+            params![format!("{}-status", id), id, status],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, ?, ?)",
+            params![format!("{}-date", id), id, key, date],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_agenda_date_formats() {
+        let expected = NaiveDate::from_ymd_opt(2024, 3, 1);
+        assert_eq!(parse_agenda_date("2024-03-01"), expected);
+        assert_eq!(parse_agenda_date(" 2024/03/01 "), expected);
+        assert_eq!(parse_agenda_date("[[2024-03-01]]"), expected);
+        assert_eq!(parse_agenda_date("<2024-03-01 Fri 10:00>"), expected);
+        assert_eq!(parse_agenda_date("2024-03-01T10:00:00Z"), expected);
+        assert_eq!(parse_agenda_date("next tuesday"), None);
+        assert_eq!(parse_agenda_date("2024-02-30"), None);
+    }
+
+    #[test]
+    fn test_task_agenda_buckets() {
+        let conn = create_test_db();
+        let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let fmt = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+
+        seed_task(&conn, "yesterday", "todo", "due", &fmt(today - Duration::days(1)));
+        seed_task(&conn, "today", "doing", "scheduled", &fmt(today));
+        seed_task(&conn, "next-week", "todo", "due", &fmt(today + Duration::days(7)));
+        seed_task(&conn, "finished", "done", "due", &fmt(today));
+        seed_task(&conn, "far-future", "todo", "due", &fmt(today + Duration::days(60)));
+
+        let agenda = build_task_agenda(
+            &conn,
+            today - Duration::days(7),
+            today + Duration::days(14),
+            today,
+        )
+        .unwrap();
+
+        let ids = |items: &[AgendaItem]| items.iter().map(|i| i.block_id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&agenda.overdue), vec!["yesterday"]);
+        assert_eq!(ids(&agenda.today), vec!["today"]);
+        assert_eq!(ids(&agenda.upcoming), vec!["next-week"]);
+        assert_eq!(agenda.today[0].page_path.as_deref(), Some("Plans.md"));
+        assert_eq!(agenda.upcoming[0].date, fmt(today + Duration::days(7)));
+    }
+
+    #[test]
+    fn test_set_block_schedule() {
+        let conn = create_test_db();
+        seed_task(&conn, "task", "todo", "due", "2024-07-01");
+        let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+        let scheduled = |conn: &Connection| -> Vec<String> {
+            conn.prepare(
+                "SELECT value FROM block_metadata WHERE block_id = 'task' AND key = 'scheduled'",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+        };
+
+        let (page_id, date) = store_block_schedule(&conn, "task", "tomorrow", today).unwrap();
+        assert_eq!(page_id, "page1");
+        assert_eq!(date, "2024-06-16");
+        assert_eq!(scheduled(&conn), vec!["2024-06-16"]);
+
+        let (_, date) = store_block_schedule(&conn, "task", "2024-03-01", today).unwrap();
+        assert_eq!(date, "2024-03-01");
+        assert_eq!(scheduled(&conn), vec!["2024-03-01"]);
+
+        let err = store_block_schedule(&conn, "task", "when pigs fly", today).unwrap_err();
+        assert!(err.starts_with("Validation error"), "{}", err);
+        assert_eq!(scheduled(&conn), vec!["2024-03-01"]);
+
+        assert!(store_block_schedule(&conn, "missing", "tomorrow", today).is_err());
+    }
+
+    #[test]
+    fn test_scheduled_blocks_from_content_and_metadata() {
+        let conn = create_test_db();
+        let date = |d| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        for (id, content) in [
+            ("inline", "Review @2024-06-03"),
+            ("marker", "Ship SCHEDULED::2024-06-01"),
+            ("invalid", "Broken @2024-02-30 and SCHEDULED::someday"),
+            ("later", "Retro @2024-08-01"),
+        ] {
+            conn.execute(
+                "INSERT INTO blocks (id, page_id, content, order_weight) VALUES (?, 'page1', ?, 1.0)",
+                params![id, content],
+            )
+            .unwrap();
+            crate::services::wiki_link_index::index_block_links(&conn, id, content, "page1")
+                .unwrap();
+        }
+        seed_task(&conn, "explicit", "todo", "scheduled", "2024-06-02");
+
+        let blocks = load_scheduled_blocks(&conn, date("2024-06-01"), date("2024-06-30")).unwrap();
+        let found: Vec<(&str, &str)> = blocks
+            .iter()
+            .map(|b| (b.block_id.as_str(), b.date.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("marker", "2024-06-01"),
+                ("explicit", "2024-06-02"),
+                ("inline", "2024-06-03"),
+            ]
+        );
+        assert_eq!(blocks[0].page_title, "Plans");
+
+        // Removing the date from the content unschedules the block
+        crate::services::wiki_link_index::index_block_links(&conn, "inline", "Review", "page1")
+            .unwrap();
+        let blocks = load_scheduled_blocks(&conn, date("2024-06-01"), date("2024-06-30")).unwrap();
+        assert_eq!(blocks.len(), 2);
+
+        // The derived date never becomes user metadata that would be written to the file
+        let leaked: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM block_metadata WHERE block_id IN ('inline', 'marker')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(leaked, 0);
+    }
+}
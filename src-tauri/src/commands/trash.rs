@@ -0,0 +1,393 @@
+//! Trash for deleted pages.
+//!
+//! `delete_page` moves a page's markdown file (or, for a childless folder page, its
+//! whole directory) into `.oxinot/trash/<trash_id>/` instead of removing it, and
+//! records it in `.oxinot/trash/manifest.json` together with a snapshot of the page's
+//! blocks. Entries older than `TRASH_RETENTION_DAYS` are purged whenever the trash is
+//! written or listed.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::commands::block::{load_blocks_metadata, query_blocks_for_page};
+use crate::commands::page::load_page_frontmatter;
+use crate::commands::workspace::{load_indent_style, open_workspace_db, sync_single_file};
+use crate::config::{
+    METADATA_DIR_NAME, TRASH_DIR_NAME, TRASH_MANIFEST_FILENAME, TRASH_RETENTION_DAYS,
+};
+use crate::models::block::Block;
+use crate::utils::markdown::{blocks_to_markdown, prepend_frontmatter, strip_id_markers};
+
+/// A deleted page kept in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub id: String,
+    pub page_id: String,
+    pub title: String,
+    /// Workspace-relative path of the page's markdown file
+    pub file_path: Option<String>,
+    /// Workspace-relative path of what was moved: the file, or a folder page's directory
+    pub original_path: Option<String>,
+    pub is_directory: bool,
+    /// RFC 3339 time of deletion
+    pub deleted_at: String,
+    /// The page's blocks (with metadata) at deletion time
+    pub blocks: Vec<Block>,
+    /// The page's frontmatter block at deletion time
+    #[serde(default)]
+    pub frontmatter: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrashManifest {
+    entries: Vec<TrashEntry>,
+}
+
+/// Deleted pages still in the trash, most recently deleted first
+#[tauri::command]
+pub async fn list_trash(workspace_path: String) -> Result<Vec<TrashEntry>, String> {
+    let mut manifest = load_manifest(&workspace_path)?;
+    if purge_expired(&workspace_path, &mut manifest, Utc::now()) {
+        save_manifest(&workspace_path, &manifest)?;
+    }
+    Ok(manifest.entries.into_iter().rev().collect())
+}
+
+/// Put a trashed page back at its original path and reindex it. Refused when
+/// something already exists there. Returns the restored page's id.
+#[tauri::command]
+pub async fn restore_from_trash(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    trash_id: String,
+) -> Result<String, String> {
+    let page_id = restore_entry(&workspace_path, &trash_id)?;
+
+    crate::utils::events::emit_workspace_changed(&app, &workspace_path);
+
+    Ok(page_id)
+}
+
+/// Permanently delete everything in the trash. Returns the number of entries removed.
+#[tauri::command]
+pub async fn empty_trash(workspace_path: String) -> Result<usize, String> {
+    let manifest = load_manifest(&workspace_path)?;
+    let trash_dir = trash_dir(&workspace_path);
+    if trash_dir.exists() {
+        fs::remove_dir_all(&trash_dir).map_err(|e| format!("Failed to empty trash: {}", e))?;
+    }
+    Ok(manifest.entries.len())
+}
+
+/// Move a page's file into the trash and record it with a snapshot of its blocks.
+/// The page itself is left in the DB for the caller to delete.
+pub(crate) fn move_page_to_trash(
+    conn: &Connection,
+    workspace_path: &str,
+    page_id: &str,
+) -> Result<TrashEntry, String> {
+    let (title, file_path, is_directory): (String, Option<String>, bool) = conn
+        .query_row(
+            "SELECT title, file_path, is_directory FROM pages WHERE id = ?",
+            [page_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i32>(2)? != 0)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Page not found: {}", page_id))?;
+
+    let frontmatter = load_page_frontmatter(conn, page_id)?;
+    let mut blocks = query_blocks_for_page(conn, page_id)?;
+    let block_ids: Vec<String> = blocks.iter().map(|b| b.id.clone()).collect();
+    let mut metadata = load_blocks_metadata(conn, &block_ids)?;
+    for block in &mut blocks {
+        block.metadata = metadata.remove(&block.id).unwrap_or_default();
+    }
+
+    let now = Utc::now();
+    let id = format!(
+        "{}-{}",
+        now.format("%Y%m%dT%H%M%S"),
+        &Uuid::new_v4().simple().to_string()[..8]
+    );
+
+    // A folder page's note lives inside its directory, which goes with it
+    let original_path = file_path.as_deref().map(|rel| {
+        let rel = Path::new(rel);
+        match rel.parent() {
+            Some(dir) if is_directory && dir.file_name() == rel.file_stem() => dir.to_path_buf(),
+            _ => rel.to_path_buf(),
+        }
+    });
+
+    let workspace_root = Path::new(workspace_path);
+    let moved_path = original_path
+        .as_ref()
+        .filter(|rel| workspace_root.join(rel).exists());
+    if let Some(rel) = moved_path {
+        let name = rel
+            .file_name()
+            .ok_or_else(|| format!("Invalid page path: {}", rel.display()))?;
+        let entry_dir = trash_dir(workspace_path).join(&id);
+        fs::create_dir_all(&entry_dir)
+            .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+        fs::rename(workspace_root.join(rel), entry_dir.join(name))
+            .map_err(|e| format!("Failed to move page to trash: {}", e))?;
+    }
+
+    let entry = TrashEntry {
+        id,
+        page_id: page_id.to_string(),
+        title,
+        file_path,
+        original_path: moved_path.map(|rel| rel.to_string_lossy().replace('\\', "/")),
+        is_directory,
+        deleted_at: now.to_rfc3339(),
+        blocks,
+        frontmatter,
+    };
+
+    let mut manifest = load_manifest(workspace_path)?;
+    purge_expired(workspace_path, &mut manifest, now);
+    manifest.entries.push(entry.clone());
+    save_manifest(workspace_path, &manifest)?;
+
+    Ok(entry)
+}
+
+/// Move a trash entry back into the workspace and reindex its page. Without a
+/// trashed file the page is rewritten from the block snapshot.
+fn restore_entry(workspace_path: &str, trash_id: &str) -> Result<String, String> {
+    let mut manifest = load_manifest(workspace_path)?;
+    let index = manifest
+        .entries
+        .iter()
+        .position(|entry| entry.id == trash_id)
+        .ok_or_else(|| format!("Trash entry not found: {}", trash_id))?;
+    let entry = manifest.entries[index].clone();
+    let file_path = entry
+        .file_path
+        .clone()
+        .ok_or_else(|| "Trashed page has no file path to restore".to_string())?;
+
+    let workspace_root = Path::new(workspace_path);
+    let restore_rel = entry.original_path.as_deref().unwrap_or(&file_path);
+    let target = workspace_root.join(restore_rel);
+    if target.exists() {
+        return Err(format!("A file already exists at {}", restore_rel));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+    }
+
+    let entry_dir = trash_dir(workspace_path).join(&entry.id);
+    let trashed = Path::new(restore_rel)
+        .file_name()
+        .map(|name| entry_dir.join(name))
+        .filter(|path| entry.original_path.is_some() && path.exists());
+    match trashed {
+        Some(trashed) => fs::rename(&trashed, &target)
+            .map_err(|e| format!("Failed to restore page from trash: {}", e))?,
+        None => {
+            let markdown = prepend_frontmatter(
+                entry.frontmatter.as_deref(),
+                &blocks_to_markdown(&entry.blocks, load_indent_style(workspace_path)),
+            );
+            let page_file = workspace_root.join(&file_path);
+            if let Some(parent) = page_file.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+            }
+            fs::write(&page_file, markdown)
+                .map_err(|e| format!("Failed to write restored page: {}", e))?;
+        }
+    }
+
+    // A merged page's blocks live on in the page it was merged into; the restored copy
+    // takes fresh IDs instead of colliding with them
+    if block_ids_in_use(workspace_path, &entry.blocks)? {
+        let page_file = workspace_root.join(&file_path);
+        let content = fs::read_to_string(&page_file)
+            .map_err(|e| format!("Failed to read restored page: {}", e))?;
+        fs::write(&page_file, strip_id_markers(&content))
+            .map_err(|e| format!("Failed to write restored page: {}", e))?;
+    }
+
+    // The file is back in place, so the entry is done even if indexing fails below;
+    // the next full sync picks the page up then
+    manifest.entries.remove(index);
+    save_manifest(workspace_path, &manifest)?;
+    if entry_dir.exists() {
+        let _ = fs::remove_dir_all(&entry_dir);
+    }
+
+    let synced = sync_single_file(workspace_path, &file_path)?
+        .ok_or_else(|| format!("Restored page was not indexed: {}", file_path))?;
+    Ok(synced.page_id)
+}
+
+/// Whether any of a trashed page's blocks is still indexed, under another page
+fn block_ids_in_use(workspace_path: &str, blocks: &[Block]) -> Result<bool, String> {
+    let conn = open_workspace_db(workspace_path)?;
+    let mut stmt = conn
+        .prepare("SELECT 1 FROM blocks WHERE id = ?")
+        .map_err(|e| e.to_string())?;
+    for block in blocks {
+        if stmt.exists([&block.id]).map_err(|e| e.to_string())? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Drop entries deleted more than `TRASH_RETENTION_DAYS` before `now`, with their
+/// files. Returns whether anything was removed.
+fn purge_expired(workspace_path: &str, manifest: &mut TrashManifest, now: DateTime<Utc>) -> bool {
+    let cutoff = now - Duration::days(TRASH_RETENTION_DAYS);
+    let before = manifest.entries.len();
+    manifest.entries.retain(|entry| {
+        let expired = DateTime::parse_from_rfc3339(&entry.deleted_at)
+            .map(|deleted_at| deleted_at < cutoff)
+            .unwrap_or(false);
+        if expired {
+            let _ = fs::remove_dir_all(trash_dir(workspace_path).join(&entry.id));
+        }
+        !expired
+    });
+    manifest.entries.len() != before
+}
+
+fn trash_dir(workspace_path: &str) -> PathBuf {
+    Path::new(workspace_path)
+        .join(METADATA_DIR_NAME)
+        .join(TRASH_DIR_NAME)
+}
+
+fn load_manifest(workspace_path: &str) -> Result<TrashManifest, String> {
+    let path = trash_dir(workspace_path).join(TRASH_MANIFEST_FILENAME);
+    match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse trash manifest: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(TrashManifest::default()),
+        Err(e) => Err(format!("Failed to read trash manifest: {}", e)),
+    }
+}
+
+fn save_manifest(workspace_path: &str, manifest: &TrashManifest) -> Result<(), String> {
+    let dir = trash_dir(workspace_path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize trash manifest: {}", e))?;
+    fs::write(dir.join(TRASH_MANIFEST_FILENAME), json)
+        .map_err(|e| format!("Failed to write trash manifest: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::workspace::{open_workspace_db, sync_workspace_with_progress};
+
+    #[test]
+    fn test_trash_round_trip_and_expiry() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_trash_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let workspace = temp_dir.to_string_lossy().to_string();
+        let frontmatter = "---\nstatus: draft\ntags:\n  - a\n  - b\n---\n";
+        std::fs::write(
+            temp_dir.join("Notes.md"),
+            format!("{}- first\n- second\n", frontmatter),
+        )
+        .unwrap();
+        sync_workspace_with_progress(&workspace, None, &mut |_| {}).unwrap();
+
+        let conn = open_workspace_db(&workspace).unwrap();
+        let page_id: String = conn
+            .query_row(
+                "SELECT id FROM pages WHERE file_path = 'Notes.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        let entry = move_page_to_trash(&conn, &workspace, &page_id).unwrap();
+        conn.execute("DELETE FROM pages WHERE id = ?", [&page_id])
+            .unwrap();
+        assert!(!temp_dir.join("Notes.md").exists());
+        assert_eq!(entry.original_path.as_deref(), Some("Notes.md"));
+        assert_eq!(entry.blocks.len(), 2);
+        assert!(trash_dir(&workspace)
+            .join(&entry.id)
+            .join("Notes.md")
+            .exists());
+
+        // Refused while another file occupies the path
+        std::fs::write(temp_dir.join("Notes.md"), "- other\n").unwrap();
+        assert!(restore_entry(&workspace, &entry.id).is_err());
+        std::fs::remove_file(temp_dir.join("Notes.md")).unwrap();
+
+        let restored = restore_entry(&workspace, &entry.id).unwrap();
+        let content = std::fs::read_to_string(temp_dir.join("Notes.md")).unwrap();
+        assert!(content.contains("first") && content.contains("second"));
+        let title: String = conn
+            .query_row("SELECT title FROM pages WHERE id = ?", [&restored], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(title, "Notes");
+        assert!(load_manifest(&workspace).unwrap().entries.is_empty());
+
+        // Blocks that moved to another page while trashed (a merge) are not taken over
+        let merged = move_page_to_trash(&conn, &workspace, &restored).unwrap();
+        conn.execute(
+            "UPDATE pages SET file_path = NULL, title = 'Merged' WHERE id = ?",
+            [&restored],
+        )
+        .unwrap();
+        let copy = restore_entry(&workspace, &merged.id).unwrap();
+        assert_ne!(copy, restored);
+        let moved: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM blocks WHERE page_id = ?",
+                [&restored],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(moved, 2);
+        let copied: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM blocks WHERE page_id = ?",
+                [&copy],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(copied, 2);
+
+        // Without the trashed file the page is rebuilt from the snapshot, frontmatter first
+        let snapshot = move_page_to_trash(&conn, &workspace, &copy).unwrap();
+        assert_eq!(snapshot.frontmatter.as_deref(), Some(frontmatter));
+        conn.execute("DELETE FROM pages WHERE id = ?", [&copy])
+            .unwrap();
+        std::fs::remove_file(trash_dir(&workspace).join(&snapshot.id).join("Notes.md")).unwrap();
+        restore_entry(&workspace, &snapshot.id).unwrap();
+        let content = std::fs::read_to_string(temp_dir.join("Notes.md")).unwrap();
+        assert!(content.starts_with(&format!("{}- first", frontmatter)));
+
+        // Old entries are purged
+        let mut manifest = TrashManifest {
+            entries: vec![TrashEntry {
+                deleted_at: (Utc::now() - Duration::days(TRASH_RETENTION_DAYS + 1)).to_rfc3339(),
+                ..entry
+            }],
+        };
+        assert!(purge_expired(&workspace, &mut manifest, Utc::now()));
+        assert!(manifest.entries.is_empty());
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+}
@@ -1,7 +1,18 @@
+use crate::commands::block::BLOCK_PATH_CTES;
+use crate::commands::journal::DEFAULT_DAILY_NOTES_PATH;
+use crate::commands::search::quote_fts_literal;
 use crate::commands::workspace::open_workspace_db;
-use crate::models::wiki_link::{BacklinkGroup, BacklinkBlock, WikiLink};
-use crate::services::wiki_link_index;
-use rusqlite::params;
+use crate::models::page::Page;
+use crate::models::wiki_link::{
+    BacklinkBlock, BacklinkContext, BacklinkGroup, BlockEmbedder, BlockMention, BlockReferences,
+    EmbeddedBlock, OutboundLink, PageOutboundLinks, ResolvedBlock, ResolvedLink, ResolvedPageLink,
+    WikiLink,
+};
+use crate::services::wiki_link_parser::parse_wiki_links;
+use crate::services::{page_path_service, wiki_link_index};
+use crate::utils::path::normalize_page_path;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
 use std::collections::HashMap;
 
 #[tauri::command]
@@ -57,16 +68,251 @@ pub async fn get_page_backlinks(
     Ok(result)
 }
 
+/// Get every block referencing a page along with its parent block's content and its
+/// breadcrumb path, for rendering backlink previews. A block linking the page several
+/// times is listed once; blocks on soft-deleted pages are skipped.
+#[tauri::command]
+pub async fn get_page_backlinks_with_context(
+    workspace_path: String,
+    page_id: String,
+) -> Result<Vec<BacklinkContext>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_backlinks_with_context(&conn, &page_id)
+}
+
+fn find_backlinks_with_context(
+    conn: &Connection,
+    page_id: &str,
+) -> Result<Vec<BacklinkContext>, String> {
+    let sql = format!(
+        "{}{}",
+        BLOCK_PATH_CTES,
+        r#"SELECT
+    b.id,
+    b.page_id,
+    p.title,
+    b.content,
+    b.parent_id,
+    parent.content,
+    CASE
+        WHEN COALESCE(pc.path, '') = '' THEN COALESCE(bc.path, nb.content)
+        ELSE (pc.path || '/' || COALESCE(bc.path, nb.content))
+    END as full_path
+FROM (SELECT DISTINCT from_block_id FROM wiki_links WHERE to_page_id = ?1) w
+JOIN blocks b ON b.id = w.from_block_id
+JOIN pages p ON p.id = b.page_id
+JOIN norm_blocks nb ON nb.id = b.id
+LEFT JOIN blocks parent ON parent.id = b.parent_id
+LEFT JOIN block_chain bc ON bc.id = b.id
+LEFT JOIN page_chain pc ON pc.id = b.page_id
+WHERE COALESCE(p.is_deleted, 0) = 0
+ORDER BY p.title, b.created_at
+"#
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![page_id], |row| {
+            Ok(BacklinkContext {
+                block_id: row.get(0)?,
+                page_id: row.get(1)?,
+                page_title: row.get(2)?,
+                content: row.get(3)?,
+                parent_id: row.get(4)?,
+                parent_content: row.get(5)?,
+                full_path: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Pages a page links to (with titles) and its broken targets, one entry per target
+/// with the number of links to it. Counterpart of `get_page_backlinks`.
+#[tauri::command]
+pub async fn get_page_outbound_links(
+    workspace_path: String,
+    page_id: String,
+) -> Result<PageOutboundLinks, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_outbound_links(&conn, &page_id)
+}
+
+fn find_outbound_links(conn: &Connection, page_id: &str) -> Result<PageOutboundLinks, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.to_page_id, p.title, p.file_path, MIN(w.target_path), COUNT(*)
+             FROM wiki_links w JOIN pages p ON p.id = w.to_page_id
+             WHERE w.from_page_id = ?1
+             GROUP BY w.to_page_id
+             ORDER BY p.title",
+        )
+        .map_err(|e| e.to_string())?;
+    let resolved = stmt
+        .query_map([page_id], |row| {
+            Ok(OutboundLink {
+                to_page_id: row.get(0)?,
+                page_title: row.get(1)?,
+                file_path: row.get(2)?,
+                target_path: row.get(3)?,
+                count: row.get::<_, i64>(4)? as usize,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Same notion of broken as `get_broken_links`: block aliases are not broken
+    let mut stmt = conn
+        .prepare(
+            "SELECT target_path, COUNT(*) FROM wiki_links
+             WHERE from_page_id = ?1 AND to_page_id IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM block_metadata m
+                   WHERE m.key = ?2 AND TRIM(m.value) = wiki_links.target_path
+               )
+             GROUP BY target_path
+             ORDER BY target_path",
+        )
+        .map_err(|e| e.to_string())?;
+    let broken = stmt
+        .query_map(
+            params![page_id, wiki_link_index::ALIAS_METADATA_KEY],
+            |row| {
+                Ok(OutboundLink {
+                    to_page_id: None,
+                    page_title: None,
+                    file_path: None,
+                    target_path: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as usize,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(PageOutboundLinks { resolved, broken })
+}
+
+/// Blocks referring to a page, for a references panel. `linked` blocks contain a wiki
+/// link to it; `unlinked` blocks on other pages contain its title as a whole word
+/// (case-insensitive) outside any `[[...]]`, without linking it.
+#[tauri::command]
+pub async fn get_block_references(
+    workspace_path: String,
+    page_id: String,
+) -> Result<BlockReferences, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_block_references(&conn, &page_id)
+}
+
+fn find_block_references(conn: &Connection, page_id: &str) -> Result<BlockReferences, String> {
+    let title: String = conn
+        .query_row(
+            "SELECT title FROM pages WHERE id = ?1",
+            params![page_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Page not found: {}", page_id))?;
+
+    let read_mention = |row: &rusqlite::Row| -> rusqlite::Result<BlockMention> {
+        Ok(BlockMention {
+            block_id: row.get(0)?,
+            page_id: row.get(1)?,
+            page_title: row.get(2)?,
+            content: row.get(3)?,
+        })
+    };
+
+    let linked = conn
+        .prepare(
+            "SELECT b.id, b.page_id, p.title, b.content
+             FROM (SELECT DISTINCT from_block_id FROM wiki_links WHERE to_page_id = ?1) w
+             JOIN blocks b ON b.id = w.from_block_id
+             JOIN pages p ON p.id = b.page_id
+             WHERE COALESCE(p.is_deleted, 0) = 0
+             ORDER BY p.title, b.created_at",
+        )
+        .map_err(|e| e.to_string())?
+        .query_map(params![page_id], read_mention)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let title = title.trim();
+    if title.is_empty() {
+        return Ok(BlockReferences {
+            linked,
+            unlinked: Vec::new(),
+        });
+    }
+
+    // Narrow candidates with the trigram index (which needs 3+ characters), then
+    // check word boundaries and link spans on the content itself
+    let (candidate_filter, needle) = if title.chars().count() >= 3 {
+        (
+            "b.id IN (SELECT block_id FROM blocks_fts WHERE blocks_fts MATCH ?1)",
+            format!("content : {}", quote_fts_literal(title)),
+        )
+    } else {
+        ("instr(lower(b.content), lower(?1)) > 0", title.to_string())
+    };
+    let sql = format!(
+        "SELECT b.id, b.page_id, p.title, b.content
+         FROM blocks b
+         JOIN pages p ON p.id = b.page_id
+         WHERE {}
+           AND b.page_id != ?2
+           AND COALESCE(p.is_deleted, 0) = 0
+           AND b.id NOT IN (SELECT from_block_id FROM wiki_links WHERE to_page_id = ?2)
+         ORDER BY p.title, b.created_at",
+        candidate_filter
+    );
+    let candidates = conn
+        .prepare(&sql)
+        .map_err(|e| e.to_string())?
+        .query_map(params![needle, page_id], read_mention)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let link_re = Regex::new(r"\[\[[^\]]*\]\]").map_err(|e| e.to_string())?;
+    let word_re = Regex::new(&format!(r"(?i)(?:^|\W){}(?:$|\W)", regex::escape(title)))
+        .map_err(|e| e.to_string())?;
+    let unlinked = candidates
+        .into_iter()
+        .filter(|mention| word_re.is_match(&link_re.replace_all(&mention.content, " ")))
+        .collect();
+
+    Ok(BlockReferences { linked, unlinked })
+}
+
+/// Links whose target is neither a page nor a block alias (`alias::` metadata).
+/// Aliases are checked here rather than at index time, so an alias declared after
+/// the link was written still counts.
 #[tauri::command]
 pub async fn get_broken_links(workspace_path: String) -> Result<Vec<WikiLink>, String> {
     let conn = open_workspace_db(&workspace_path)?;
-    
+    find_broken_links(&conn)
+}
+
+fn find_broken_links(conn: &Connection) -> Result<Vec<WikiLink>, String> {
     let mut stmt = conn.prepare(
         "SELECT id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target, alias, heading, block_ref, is_embed 
-         FROM wiki_links WHERE to_page_id IS NULL"
+         FROM wiki_links WHERE to_page_id IS NULL
+           AND NOT EXISTS (
+               SELECT 1 FROM block_metadata m
+               WHERE m.key = ?1 AND TRIM(m.value) = wiki_links.target_path
+           )"
     ).map_err(|e| e.to_string())?;
     
-    let links = stmt.query_map([], |row| {
+    let links = stmt.query_map([wiki_link_index::ALIAS_METADATA_KEY], |row| {
         Ok(WikiLink {
             id: row.get(0)?,
             from_page_id: row.get(1)?,
@@ -87,9 +333,522 @@ pub async fn get_broken_links(workspace_path: String) -> Result<Vec<WikiLink>, S
     Ok(links)
 }
 
+/// Pages nothing points at: no `[[link]]` or embed from another page resolves to
+/// them and no other page's `((ref))` targets one of their blocks. Directory notes
+/// are skipped, as are pages under `exclude_prefix` (a page path such as the daily
+/// notes folder, which defaults to `Daily`; pass an empty string to keep everything).
+#[tauri::command]
+pub async fn get_orphan_pages(
+    workspace_path: String,
+    exclude_prefix: Option<String>,
+) -> Result<Vec<Page>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let prefix = exclude_prefix.unwrap_or_else(|| DEFAULT_DAILY_NOTES_PATH.to_string());
+    find_orphan_pages(&conn, &prefix)
+}
+
+fn find_orphan_pages(conn: &Connection, exclude_prefix: &str) -> Result<Vec<Page>, String> {
+    let prefix = exclude_prefix.trim().trim_matches('/');
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, p.parent_id, p.file_path, p.is_directory, p.file_mtime,
+                    p.file_size, p.created_at, p.updated_at, COALESCE(pp.path_text, p.title)
+             FROM pages p
+             LEFT JOIN page_paths pp ON pp.page_id = p.id
+             WHERE p.is_deleted = 0 AND p.is_directory = 0
+               AND NOT EXISTS (
+                   SELECT 1 FROM wiki_links w
+                   WHERE w.to_page_id = p.id AND w.from_page_id != p.id
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM block_refs r
+                   JOIN blocks target ON target.id = r.to_block_id
+                   JOIN blocks source ON source.id = r.from_block_id
+                   WHERE target.page_id = p.id AND source.page_id != p.id
+               )
+             ORDER BY p.title COLLATE NOCASE",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                Page {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    file_path: row.get(3)?,
+                    is_directory: row.get::<_, i32>(4)? != 0,
+                    file_mtime: row.get(5)?,
+                    file_size: row.get(6)?,
+                    created_at: row.get(7)?,
+                    updated_at: row.get(8)?,
+                },
+                row.get::<_, String>(9)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let excluded = |path: &str| {
+        !prefix.is_empty()
+            && path
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+
+    Ok(rows
+        .into_iter()
+        .filter(|(_, path)| !excluded(path))
+        .map(|(page, _)| page)
+        .collect())
+}
+
 #[tauri::command]
 pub async fn reindex_wiki_links(workspace_path: String) -> Result<(), String> {
     let mut conn = open_workspace_db(&workspace_path)?;
     wiki_link_index::reindex_all_links(&mut conn).map_err(|e| e.to_string())?;
     Ok(())
 }
+
+/// Resolve a `[[link]]` target to a page: exact path first, then by Unicode NFC form,
+/// then (with `case_insensitive`) ignoring case. Reports which match succeeded.
+#[tauri::command]
+pub async fn resolve_page_link(
+    workspace_path: String,
+    target: String,
+    case_insensitive: Option<bool>,
+) -> Result<Option<ResolvedPageLink>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let resolved = page_path_service::resolve_page_path(
+        &conn,
+        &normalize_page_path(&target),
+        case_insensitive.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(resolved.map(|(page_id, match_type)| ResolvedPageLink {
+        page_id,
+        match_type,
+    }))
+}
+
+/// Get a block's raw content along with each `[[link]]`/`![[embed]]` in it resolved
+/// to its target page id and file, or marked unresolved
+#[tauri::command]
+pub async fn get_block_resolved(
+    workspace_path: String,
+    block_id: String,
+) -> Result<ResolvedBlock, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    resolve_block_links(&conn, &block_id)
+}
+
+fn resolve_block_links(conn: &Connection, block_id: &str) -> Result<ResolvedBlock, String> {
+    let (page_id, content): (String, String) = conn
+        .query_row(
+            "SELECT page_id, content FROM blocks WHERE id = ?",
+            [block_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Block not found: {}", e))?;
+
+    let mut links = Vec::new();
+    for link in parse_wiki_links(&content) {
+        let to_page_id = wiki_link_index::resolve_link_target(conn, &link.target_path)
+            .map_err(|e| e.to_string())?;
+        let to_file_path = match &to_page_id {
+            Some(id) => conn
+                .query_row("SELECT file_path FROM pages WHERE id = ?", [id], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .map_err(|e| e.to_string())?
+                .flatten(),
+            None => None,
+        };
+
+        links.push(ResolvedLink {
+            raw_target: link.raw_target,
+            target_path: link.target_path,
+            alias: link.alias,
+            heading: link.heading,
+            block_ref: link.block_ref,
+            is_embed: link.is_embed,
+            link_type: link.link_type,
+            to_page_id,
+            to_file_path,
+        });
+    }
+
+    Ok(ResolvedBlock {
+        block_id: block_id.to_string(),
+        page_id,
+        content,
+        links,
+    })
+}
+
+/// List blocks that are transcluded elsewhere, via `((uuid))` references or
+/// `![[page#^anchor]]` embeds, with where each is used. Most-used blocks come first.
+#[tauri::command]
+pub async fn get_embedded_blocks(workspace_path: String) -> Result<Vec<EmbeddedBlock>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_embedded_blocks(&conn)
+}
+
+fn find_embedded_blocks(conn: &Connection) -> Result<Vec<EmbeddedBlock>, String> {
+    // (target block id, embedder block id, embedding kind)
+    let mut uses: Vec<(String, String, &str)> = Vec::new();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT w.block_ref, w.from_block_id FROM wiki_links w
+             JOIN blocks t ON t.id = w.block_ref
+             WHERE w.is_embed = 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let embeds = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    uses.extend(embeds.into_iter().map(|(target, from)| (target, from, "embed")));
+
+    let ref_re = Regex::new(r"\(\(([0-9A-Za-z_-]+)\)\)").map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT id, content FROM blocks WHERE instr(content, '((') > 0")
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (from, content) in candidates {
+        for caps in ref_re.captures_iter(&content) {
+            uses.push((caps[1].to_string(), from.clone(), "block_ref"));
+        }
+    }
+
+    let mut by_target: HashMap<String, Vec<(String, &str)>> = HashMap::new();
+    for (target, from, kind) in uses {
+        let embedders = by_target.entry(target).or_default();
+        if !embedders.iter().any(|(id, k)| *id == from && *k == kind) {
+            embedders.push((from, kind));
+        }
+    }
+
+    let mut result = Vec::new();
+    for (target, embedder_refs) in by_target {
+        let Some((page_id, content)) = conn
+            .query_row(
+                "SELECT page_id, content FROM blocks WHERE id = ?",
+                [&target],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+        else {
+            // `((...))` that does not name an existing block
+            continue;
+        };
+
+        let mut embedders = Vec::with_capacity(embedder_refs.len());
+        for (block_id, kind) in embedder_refs {
+            let (embedder_page_id, page_title): (String, String) = conn
+                .query_row(
+                    "SELECT b.page_id, p.title FROM blocks b JOIN pages p ON p.id = b.page_id
+                     WHERE b.id = ?",
+                    [&block_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(|e| e.to_string())?;
+            embedders.push(BlockEmbedder {
+                block_id,
+                page_id: embedder_page_id,
+                page_title,
+                embed_type: kind.to_string(),
+            });
+        }
+        embedders.sort_by(|a, b| {
+            a.page_title
+                .cmp(&b.page_title)
+                .then_with(|| a.block_id.cmp(&b.block_id))
+        });
+
+        result.push(EmbeddedBlock {
+            block_id: target,
+            page_id,
+            content,
+            embedder_count: embedders.len(),
+            embedders,
+        });
+    }
+
+    result.sort_by(|a, b| {
+        b.embedder_count
+            .cmp(&a.embedder_count)
+            .then_with(|| a.block_id.cmp(&b.block_id))
+    });
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_block_links() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        let content = "See [[Projects/Alpha|the plan]] and ![[Missing Page]]";
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('alpha', 'Alpha', 'Projects/Alpha.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('alpha', 'Projects/Alpha');
+             INSERT INTO pages (id, title, file_path) VALUES ('notes', 'Notes', 'Notes.md');",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'notes', ?, 1.0)",
+            [content],
+        )
+        .unwrap();
+
+        let resolved = resolve_block_links(&conn, "b1").unwrap();
+
+        assert_eq!(resolved.content, content);
+        assert_eq!(resolved.page_id, "notes");
+        assert_eq!(resolved.links.len(), 2);
+
+        let alpha = &resolved.links[0];
+        assert_eq!(alpha.alias.as_deref(), Some("the plan"));
+        assert_eq!(alpha.to_page_id.as_deref(), Some("alpha"));
+        assert_eq!(alpha.to_file_path.as_deref(), Some("Projects/Alpha.md"));
+        assert!(!alpha.is_embed);
+
+        let missing = &resolved.links[1];
+        assert!(missing.is_embed);
+        assert_eq!(missing.to_page_id, None);
+        assert_eq!(missing.to_file_path, None);
+    }
+
+    #[test]
+    fn test_find_embedded_blocks() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('source', 'Source', 'Source.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('a', 'Alpha', 'Alpha.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('b', 'Beta', 'Beta.md');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('x', 'source', 'Shared idea', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('ref', 'a', 'see ((x))', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('emb', 'b', '![[Source#^x]]', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('dangling', 'b', '((missing))', 2.0);
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target, block_ref, is_embed)
+                VALUES ('w1', 'b', 'emb', 'source', 'embed_block', 'Source', 'Source#^x', 'x', 1);",
+        )
+        .unwrap();
+
+        let embedded = find_embedded_blocks(&conn).unwrap();
+
+        assert_eq!(embedded.len(), 1);
+        let x = &embedded[0];
+        assert_eq!(x.block_id, "x");
+        assert_eq!(x.page_id, "source");
+        assert_eq!(x.embedder_count, 2);
+        assert_eq!(x.embedders[0].block_id, "ref");
+        assert_eq!(x.embedders[0].page_title, "Alpha");
+        assert_eq!(x.embedders[0].embed_type, "block_ref");
+        assert_eq!(x.embedders[1].block_id, "emb");
+        assert_eq!(x.embedders[1].page_id, "b");
+        assert_eq!(x.embedders[1].embed_type, "embed");
+    }
+
+    #[test]
+    fn test_find_backlinks_with_context() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('target', 'Target', 'Target.md');
+             INSERT INTO pages (id, title, file_path) VALUES ('notes', 'Notes', 'Notes.md');
+             INSERT INTO pages (id, title, file_path, is_deleted) VALUES ('gone', 'Gone', 'Gone.md', 1);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('parent', 'notes', 'Meeting', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                VALUES ('child', 'notes', 'parent', 'ask [[Target]] about [[Target]]', 1.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('root', 'notes', 'see [[Target]]', 2.0);
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('old', 'gone', '[[Target]]', 1.0);
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+                VALUES ('w1', 'notes', 'child', 'target', 'page_link', 'Target', 'Target');
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+                VALUES ('w2', 'notes', 'child', 'target', 'page_link', 'Target', 'Target');
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+                VALUES ('w3', 'notes', 'root', 'target', 'page_link', 'Target', 'Target');
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type, target_path, raw_target)
+                VALUES ('w4', 'gone', 'old', 'target', 'page_link', 'Target', 'Target');",
+        )
+        .unwrap();
+
+        let mut backlinks = find_backlinks_with_context(&conn, "target").unwrap();
+        backlinks.sort_by(|a, b| a.block_id.cmp(&b.block_id));
+
+        assert_eq!(backlinks.len(), 2);
+        let child = &backlinks[0];
+        assert_eq!(child.block_id, "child");
+        assert_eq!(child.page_title, "Notes");
+        assert_eq!(child.parent_id.as_deref(), Some("parent"));
+        assert_eq!(child.parent_content.as_deref(), Some("Meeting"));
+        assert_eq!(child.full_path, "Notes/Meeting/ask [[Target]] about [[Target]]");
+        let root = &backlinks[1];
+        assert_eq!(root.block_id, "root");
+        assert_eq!(root.parent_content, None);
+        assert_eq!(root.full_path, "Notes/see [[Target]]");
+    }
+
+    #[test]
+    fn test_block_alias_links_are_not_broken() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('notes', 'Notes', 'Notes.md');
+             INSERT INTO blocks (id, page_id, content, order_weight, created_at)
+                 VALUES ('old', 'notes', 'Older', 1.0, '2024-01-01T00:00:00Z');
+             INSERT INTO blocks (id, page_id, content, order_weight, created_at)
+                 VALUES ('new', 'notes', 'Newer', 2.0, '2024-06-01T00:00:00Z');
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m1', 'new', 'alias', 'goal');
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m2', 'old', 'alias', ' goal ');
+             INSERT INTO blocks (id, page_id, content, order_weight)
+                 VALUES ('src', 'notes', 'See [[goal]] and [[nowhere]]', 3.0);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            wiki_link_index::resolve_block_alias(&conn, "goal")
+                .unwrap()
+                .as_deref(),
+            Some("old")
+        );
+
+        wiki_link_index::index_block_links(&conn, "src", "See [[goal]] and [[nowhere]]", "notes")
+            .unwrap();
+        let alias_target: String = conn
+            .query_row(
+                "SELECT to_block_id FROM block_refs WHERE from_block_id = 'src' AND ref_type = 'alias'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(alias_target, "old");
+
+        let broken = find_broken_links(&conn).unwrap();
+        let targets: Vec<&str> = broken.iter().map(|l| l.target_path.as_str()).collect();
+        assert_eq!(targets, vec!["nowhere"]);
+    }
+
+    #[test]
+    fn test_find_orphan_pages_skips_linked_directories_and_daily_notes() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, is_directory) VALUES
+                ('hub', 'Hub', 0), ('linked', 'Linked', 0), ('reffed', 'Reffed', 0),
+                ('lonely', 'Lonely', 0), ('selfish', 'Selfish', 0), ('folder', 'Folder', 1),
+                ('day', '2024-06-01', 0), ('gone', 'Gone', 0);
+             UPDATE pages SET is_deleted = 1 WHERE id = 'gone';
+             INSERT INTO page_paths (page_id, path_text) VALUES
+                ('day', 'Daily/2024/06/2024-06-01'), ('lonely', 'Dailyish/Lonely');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('h1', 'hub', 'See [[Linked]] and ((r1))', 1.0),
+                ('r1', 'reffed', 'Quoted elsewhere', 1.0),
+                ('s1', 'selfish', 'Back to [[Selfish]]', 1.0);
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type,
+                                     target_path, raw_target) VALUES
+                ('w1', 'hub', 'h1', 'linked', 'page_link', 'Linked', 'Linked'),
+                ('w2', 'selfish', 's1', 'selfish', 'page_link', 'Selfish', 'Selfish');
+             INSERT INTO block_refs (id, from_block_id, to_block_id) VALUES ('ref1', 'h1', 'r1');",
+        )
+        .unwrap();
+
+        let ids = |prefix: &str| -> Vec<String> {
+            find_orphan_pages(&conn, prefix)
+                .unwrap()
+                .into_iter()
+                .map(|page| page.id)
+                .collect()
+        };
+
+        assert_eq!(ids("Daily"), vec!["hub", "lonely", "selfish"]);
+        assert_eq!(ids("/Daily/"), vec!["hub", "lonely", "selfish"]);
+        assert_eq!(ids(""), vec!["day", "hub", "lonely", "selfish"]);
+    }
+
+    #[test]
+    fn test_find_outbound_links_dedups_and_lists_broken() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES
+                ('notes', 'Notes', 'Notes.md'),
+                ('alpha', 'Alpha', 'Alpha.md');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('alpha', 'Alpha');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('b1', 'notes', '[[Alpha]] and [[Ghost]]', 1.0),
+                ('b2', 'notes', 'again [[Alpha|A]] and [[nick]]', 2.0),
+                ('b3', 'alpha', 'alias:: nick', 1.0);
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES
+                ('m1', 'b3', 'alias', 'nick');",
+        )
+        .unwrap();
+        wiki_link_index::index_block_links(&conn, "b1", "[[Alpha]] and [[Ghost]]", "notes")
+            .unwrap();
+        wiki_link_index::index_block_links(&conn, "b2", "again [[Alpha|A]] and [[nick]]", "notes")
+            .unwrap();
+
+        let links = find_outbound_links(&conn, "notes").unwrap();
+        assert_eq!(links.resolved.len(), 1);
+        assert_eq!(links.resolved[0].to_page_id.as_deref(), Some("alpha"));
+        assert_eq!(links.resolved[0].page_title.as_deref(), Some("Alpha"));
+        assert_eq!(links.resolved[0].count, 2);
+        assert_eq!(links.broken.len(), 1);
+        assert_eq!(links.broken[0].target_path, "Ghost");
+        assert_eq!(links.broken[0].count, 1);
+
+        let from_alpha = find_outbound_links(&conn, "alpha").unwrap();
+        assert!(from_alpha.resolved.is_empty());
+    }
+
+    #[test]
+    fn test_find_block_references_splits_linked_and_unlinked() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES
+                ('rust', 'Rust'), ('notes', 'Notes'), ('log', 'Log');
+             INSERT INTO blocks (id, page_id, content, order_weight) VALUES
+                ('own', 'rust', 'Rust is a language', 1.0),
+                ('linked', 'notes', 'Learning [[Rust]] and rust', 1.0),
+                ('plain', 'notes', 'Started with rust today', 2.0),
+                ('upper', 'log', 'RUST, again', 1.0),
+                ('inside', 'log', 'See [[Rust book]] later', 2.0),
+                ('partial', 'log', 'Trusty old tools, rusty nails', 3.0);
+             INSERT INTO blocks_fts (block_id, page_id, content)
+                SELECT id, page_id, content FROM blocks;
+             INSERT INTO wiki_links (id, from_page_id, from_block_id, to_page_id, link_type,
+                                     target_path, raw_target) VALUES
+                ('w1', 'notes', 'linked', 'rust', 'page_link', 'Rust', 'Rust');",
+        )
+        .unwrap();
+
+        let refs = find_block_references(&conn, "rust").unwrap();
+        let ids = |mentions: &[BlockMention]| -> Vec<String> {
+            mentions.iter().map(|m| m.block_id.clone()).collect()
+        };
+        assert_eq!(ids(&refs.linked), vec!["linked"]);
+        assert_eq!(ids(&refs.unlinked), vec!["upper", "plain"]);
+        assert_eq!(refs.unlinked[1].page_title, "Notes");
+
+        assert!(find_block_references(&conn, "missing").is_err());
+    }
+}
@@ -1,15 +1,28 @@
-use crate::commands::block::{block_type_to_string, index_block_fts};
-use crate::config::{METADATA_DIR_NAME, SETTINGS_FILENAME, WORKSPACE_DB_FILENAME};
+use crate::commands::block::{block_type_to_string, deindex_block_fts, index_block_fts};
+use crate::commands::page::save_page_frontmatter;
+use crate::config::{
+    DB_BUSY_TIMEOUT_MS, METADATA_DIR_NAME, SETTINGS_FILENAME, WORKSPACE_DB_FILENAME,
+};
 use crate::error::OxinotError;
+use crate::services::block_history;
+use crate::services::file_sync::{is_slug_stem, FileNaming, TITLE_FRONTMATTER_KEY};
 use crate::services::markdown_to_blocks;
 use crate::services::page_path_service;
+use crate::services::wiki_link_index;
+use crate::utils::markdown::{frontmatter_value, IndentStyle, SanitizationRules};
+use crate::utils::page_sync::{normalize_file_trailing_newline, TrailingNewlinePolicy};
+use crate::utils::sync_ignore::SyncIgnore;
 // (removed) WorkspaceSyncService import: sync/reindex paths are unified on filesystem-driven `sync_workspace`
 use chrono::Utc;
-use rusqlite::{named_params, Connection};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tauri::Emitter;
 use uuid::Uuid;
 
 /// Compute workspace-relative path from absolute path.
@@ -49,12 +62,131 @@ pub struct MigrationResult {
     pub blocks: usize,
 }
 
+/// Payload of the `sync-progress` event, sent after each markdown file is synced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncProgress {
+    pub processed: usize,
+    pub total: usize,
+    /// Workspace-relative path of the file just synced
+    pub current_file: String,
+}
+
+/// A page file in a `SyncPlan`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncPlanEntry {
+    /// Workspace-relative path
+    pub file_path: String,
+    /// None for files that have no page yet
+    pub page_id: Option<String>,
+}
+
+/// What `sync_workspace` would change, as reported by `sync_workspace_dry_run`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncPlan {
+    /// Files that would become new pages
+    pub added: Vec<SyncPlanEntry>,
+    /// Pages whose file changed since it was indexed; their blocks would be reindexed
+    pub updated: Vec<SyncPlanEntry>,
+    /// Pages whose file is gone; sync would delete them from the DB
+    pub deleted: Vec<SyncPlanEntry>,
+    /// Folder notes sync would create for directories that lack one
+    /// (also listed in `added`, or in `updated` when the page still exists)
+    pub missing_folder_notes: Vec<String>,
+}
+
+/// A page whose file was changed by another program since the app last wrote or
+/// indexed it, as reported by `detect_external_changes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalChange {
+    pub page_id: String,
+    /// Workspace-relative path
+    pub file_path: String,
+    /// mtime (unix seconds) and size recorded in the DB
+    pub recorded_mtime: i64,
+    pub recorded_size: i64,
+    /// Current mtime and size on disk; None when the file no longer exists
+    pub current_mtime: Option<i64>,
+    pub current_size: Option<i64>,
+}
+
+/// A page file reindexed by `sync_single_file`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedFile {
+    pub page_id: String,
+    /// Workspace-relative path
+    pub file_path: String,
+    /// The file is gone and its page was removed from the DB
+    pub deleted: bool,
+}
+
+/// Counts files as `sync_directory` syncs them and reports each one
+struct SyncProgressTracker<'a> {
+    processed: usize,
+    total: usize,
+    on_progress: &'a mut dyn FnMut(&SyncProgress),
+    /// Checked between files; once set, the walk stops before the next file
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl SyncProgressTracker<'_> {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    fn file_synced(&mut self, rel_path: &str) {
+        self.processed += 1;
+        (self.on_progress)(&SyncProgress {
+            processed: self.processed,
+            total: self.total,
+            current_file: rel_path.to_string(),
+        });
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WorkspaceSettings {
     pub version: String,
     pub workspace_name: String,
     pub created_at: String,
     pub last_opened: String,
+    #[serde(default)]
+    pub sanitization: SanitizationRules,
+    /// When the database was last optimized by the auto-optimize scheduler
+    #[serde(default)]
+    pub last_optimized_at: Option<String>,
+    #[serde(default)]
+    pub trailing_newline: TrailingNewlinePolicy,
+    /// Indentation used when writing and parsing nested blocks in page files
+    #[serde(default)]
+    pub indent: IndentStyle,
+    /// Seconds between autosave commits of a git workspace; None disables them
+    #[serde(default)]
+    pub auto_commit_interval_secs: Option<u64>,
+    /// Push after each autosave commit
+    #[serde(default)]
+    pub auto_commit_push: bool,
+    /// Run the destructive block repair automatically when a page read hits a
+    /// foreign key error; off by default so corruption can be inspected first
+    #[serde(default)]
+    pub auto_repair_on_read: bool,
+    /// How files of new and renamed pages are named after their titles
+    #[serde(default)]
+    pub file_naming: FileNaming,
+}
+
+/// Shortest interval accepted by `set_auto_commit`
+pub const MIN_AUTO_COMMIT_INTERVAL_SECS: u64 = 60;
+
+/// The workspace most recently opened with `sync_workspace`, until `close_workspace`
+static OPEN_WORKSPACE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn open_workspace_slot() -> &'static Mutex<Option<String>> {
+    OPEN_WORKSPACE.get_or_init(|| Mutex::new(None))
+}
+
+/// Path of the workspace currently open in the app, for background tasks
+pub fn current_workspace_path() -> Option<String> {
+    open_workspace_slot().lock().ok()?.clone()
 }
 
 /// Helper function to open workspace-specific DB connection
@@ -74,6 +206,12 @@ pub fn open_workspace_db(workspace_path: &str) -> Result<Connection, String> {
         OxinotError::database(format!("Failed to open workspace database: {}", e)).to_string()
     })?;
 
+    // Wait for a concurrent writer instead of failing immediately
+    conn.busy_timeout(Duration::from_millis(DB_BUSY_TIMEOUT_MS))
+        .map_err(|e| {
+            OxinotError::database(format!("Failed to set busy timeout: {}", e)).to_string()
+        })?;
+
     // Enable foreign keys
     conn.execute("PRAGMA foreign_keys = ON", []).map_err(|e| {
         OxinotError::database(format!("Failed to enable foreign keys: {}", e)).to_string()
@@ -159,6 +297,14 @@ fn init_workspace_settings(workspace_path: &str) -> Result<WorkspaceSettings, St
             workspace_name,
             created_at: now.clone(),
             last_opened: now,
+            sanitization: SanitizationRules::default(),
+            last_optimized_at: None,
+            trailing_newline: TrailingNewlinePolicy::default(),
+            indent: IndentStyle::default(),
+            auto_commit_interval_secs: None,
+            auto_commit_push: false,
+            auto_repair_on_read: false,
+            file_naming: FileNaming::default(),
         };
 
         save_workspace_settings(workspace_path, &settings)?;
@@ -167,6 +313,228 @@ fn init_workspace_settings(workspace_path: &str) -> Result<WorkspaceSettings, St
     }
 }
 
+/// Load the content sanitization rules configured for a workspace.
+///
+/// Missing or unreadable settings fall back to the defaults (all rules off),
+/// so saving a block never fails because of the settings file.
+pub fn load_sanitization_rules(workspace_path: &str) -> SanitizationRules {
+    get_workspace_settings_path(workspace_path)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<WorkspaceSettings>(&content).ok())
+        .map(|settings| settings.sanitization)
+        .unwrap_or_default()
+}
+
+/// Update the content sanitization rules stored in workspace settings
+#[tauri::command]
+pub fn set_sanitization_rules(
+    workspace_path: String,
+    rules: SanitizationRules,
+) -> Result<WorkspaceSettings, String> {
+    let mut settings = init_workspace_settings(&workspace_path)?;
+    settings.sanitization = rules;
+    save_workspace_settings(&workspace_path, &settings)?;
+    Ok(settings)
+}
+
+/// Load the trailing-newline policy for page files; defaults to `Always`
+/// when the settings file is missing or unreadable.
+pub fn load_trailing_newline_policy(workspace_path: &str) -> TrailingNewlinePolicy {
+    get_workspace_settings_path(workspace_path)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<WorkspaceSettings>(&content).ok())
+        .map(|settings| settings.trailing_newline)
+        .unwrap_or_default()
+}
+
+/// Update the trailing-newline policy stored in workspace settings
+#[tauri::command]
+pub fn set_trailing_newline_policy(
+    workspace_path: String,
+    policy: TrailingNewlinePolicy,
+) -> Result<WorkspaceSettings, String> {
+    let mut settings = init_workspace_settings(&workspace_path)?;
+    settings.trailing_newline = policy;
+    save_workspace_settings(&workspace_path, &settings)?;
+    Ok(settings)
+}
+
+/// Load the indent style for page files; defaults to two spaces per level
+/// when the settings file is missing or unreadable.
+pub fn load_indent_style(workspace_path: &str) -> IndentStyle {
+    get_workspace_settings_path(workspace_path)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<WorkspaceSettings>(&content).ok())
+        .map(|settings| settings.indent)
+        .unwrap_or_default()
+}
+
+/// Update the indent style stored in workspace settings.
+/// Existing page files keep their indentation until they are next rewritten, and are
+/// read with the indentation detected in each file (`IndentStyle::detect`).
+#[tauri::command]
+pub fn set_indent_style(
+    workspace_path: String,
+    indent: IndentStyle,
+) -> Result<WorkspaceSettings, String> {
+    if !indent.use_tabs && indent.width == 0 {
+        return Err("Indent width must be at least 1".to_string());
+    }
+    let mut settings = init_workspace_settings(&workspace_path)?;
+    settings.indent = indent;
+    save_workspace_settings(&workspace_path, &settings)?;
+    Ok(settings)
+}
+
+/// Autosave commit settings: (interval in seconds if enabled, push after committing)
+pub fn load_auto_commit_settings(workspace_path: &str) -> (Option<u64>, bool) {
+    get_workspace_settings_path(workspace_path)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<WorkspaceSettings>(&content).ok())
+        .map(|s| (s.auto_commit_interval_secs, s.auto_commit_push))
+        .unwrap_or((None, false))
+}
+
+/// Turn periodic autosave commits on (`interval_secs`) or off (None).
+/// `push` also pushes each autosave commit; it is never done otherwise, and None
+/// keeps the current setting.
+#[tauri::command]
+pub fn set_auto_commit(
+    workspace_path: String,
+    interval_secs: Option<u64>,
+    push: Option<bool>,
+) -> Result<WorkspaceSettings, String> {
+    if interval_secs.is_some_and(|secs| secs < MIN_AUTO_COMMIT_INTERVAL_SECS) {
+        return Err(format!(
+            "Auto-commit interval must be at least {} seconds",
+            MIN_AUTO_COMMIT_INTERVAL_SECS
+        ));
+    }
+    let mut settings = init_workspace_settings(&workspace_path)?;
+    settings.auto_commit_interval_secs = interval_secs;
+    if let Some(push) = push {
+        settings.auto_commit_push = push;
+    }
+    save_workspace_settings(&workspace_path, &settings)?;
+    Ok(settings)
+}
+
+/// Whether page reads may repair the database on a foreign key error
+pub fn load_auto_repair_on_read(workspace_path: &str) -> bool {
+    get_workspace_settings_path(workspace_path)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<WorkspaceSettings>(&content).ok())
+        .map(|s| s.auto_repair_on_read)
+        .unwrap_or(false)
+}
+
+/// Allow (or forbid) page reads to run the block repair on a foreign key error.
+/// When off, reads fail with a corruption error and `repair_db` must be run explicitly.
+#[tauri::command]
+pub fn set_auto_repair_on_read(
+    workspace_path: String,
+    enabled: bool,
+) -> Result<WorkspaceSettings, String> {
+    let mut settings = init_workspace_settings(&workspace_path)?;
+    settings.auto_repair_on_read = enabled;
+    save_workspace_settings(&workspace_path, &settings)?;
+    Ok(settings)
+}
+
+pub fn load_file_naming(workspace_path: &str) -> FileNaming {
+    get_workspace_settings_path(workspace_path)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<WorkspaceSettings>(&content).ok())
+        .map(|s| s.file_naming)
+        .unwrap_or_default()
+}
+
+/// Choose how new and renamed page files are named (`title` or `slug`).
+/// Existing files keep their names until the page is next renamed.
+#[tauri::command]
+pub fn set_file_naming(
+    workspace_path: String,
+    file_naming: FileNaming,
+) -> Result<WorkspaceSettings, String> {
+    let mut settings = init_workspace_settings(&workspace_path)?;
+    settings.file_naming = file_naming;
+    save_workspace_settings(&workspace_path, &settings)?;
+    Ok(settings)
+}
+
+/// Make every page file end as the workspace's trailing-newline policy asks.
+/// Returns the workspace-relative paths of the files that were (or, with `dry_run`,
+/// would be) changed. Under `Preserve` nothing changes.
+#[tauri::command]
+pub fn normalize_trailing_newlines(
+    workspace_path: String,
+    dry_run: bool,
+) -> Result<Vec<String>, String> {
+    let policy = load_trailing_newline_policy(&workspace_path);
+    let conn = open_workspace_db(&workspace_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_path FROM pages
+             WHERE is_deleted = 0 AND is_directory = 0 AND file_path IS NOT NULL
+             ORDER BY file_path",
+        )
+        .map_err(|e| e.to_string())?;
+    let pages: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut changed = Vec::new();
+    for (page_id, rel_path) in pages {
+        let full_path = Path::new(&workspace_path).join(&rel_path);
+        if !full_path.is_file() {
+            continue;
+        }
+        if !normalize_file_trailing_newline(&full_path, policy, dry_run)? {
+            continue;
+        }
+
+        if !dry_run {
+            // Keep incremental sync from treating our own rewrite as an external edit
+            let metadata = fs::metadata(&full_path).map_err(|e| e.to_string())?;
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64);
+            conn.execute(
+                "UPDATE pages SET file_mtime = ?, file_size = ? WHERE id = ?",
+                rusqlite::params![mtime, metadata.len() as i64, page_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        changed.push(rel_path);
+    }
+
+    Ok(changed)
+}
+
+/// Record the current time as the workspace's last optimization
+pub fn record_last_optimized(workspace_path: &str) -> Result<(), String> {
+    let settings_path = get_workspace_settings_path(workspace_path)?;
+    let mut settings = match fs::read_to_string(&settings_path) {
+        Ok(content) => serde_json::from_str::<WorkspaceSettings>(&content).map_err(|e| {
+            OxinotError::settings(format!("Failed to parse settings: {}", e)).to_string()
+        })?,
+        Err(_) => init_workspace_settings(workspace_path)?,
+    };
+    settings.last_optimized_at = Some(Utc::now().to_rfc3339());
+    save_workspace_settings(workspace_path, &settings)
+}
+
 /// Save workspace settings to `.oxinot/settings.json`
 ///
 /// # Errors
@@ -205,9 +573,43 @@ pub fn initialize_workspace(workspace_path: String) -> Result<WorkspaceSettings,
 
 /// Sync workspace: scan all markdown files and sync with database
 /// This is the source of truth - filesystem drives the database
+///
+/// Emits `sync-progress` (`SyncProgress`) after each file and `sync-complete`
+/// (`MigrationResult`) once the sync has finished. Afterwards the workspace is
+/// watched, and files changed outside the app are reindexed as they change.
 #[tauri::command]
-pub fn sync_workspace(workspace_path: String) -> Result<MigrationResult, String> {
-    let conn = open_workspace_db(&workspace_path)?;
+pub fn sync_workspace(
+    app: tauri::AppHandle,
+    workspace_path: String,
+) -> Result<MigrationResult, String> {
+    let result = sync_workspace_with_progress(&workspace_path, None, &mut |progress| {
+        let _ = app.emit("sync-progress", progress);
+    })?;
+
+    let _ = app.emit("sync-complete", &result);
+
+    if let Ok(mut open) = open_workspace_slot().lock() {
+        *open = Some(workspace_path.clone());
+    }
+    if let Err(e) = crate::services::file_watcher::watch_workspace(&workspace_path) {
+        eprintln!("[sync_workspace] File watcher not started: {}", e);
+    }
+
+    Ok(result)
+}
+
+/// Engine behind `sync_workspace`; `on_progress` is called after each markdown file
+/// is synced. The connection is not behind a lock, so the callback may block freely.
+///
+/// When `cancel` is set the walk stops between files. Files synced so far stay
+/// indexed (with their links), orphan cleanup is skipped since the walk is
+/// incomplete, and an `OxinotError::Cancelled` message is returned.
+pub(crate) fn sync_workspace_with_progress(
+    workspace_path: &str,
+    cancel: Option<&AtomicBool>,
+    on_progress: &mut dyn FnMut(&SyncProgress),
+) -> Result<MigrationResult, String> {
+    let conn = open_workspace_db(workspace_path)?;
     let workspace_root = PathBuf::from(&workspace_path);
 
     println!(
@@ -271,20 +673,44 @@ pub fn sync_workspace(workspace_path: String) -> Result<MigrationResult, String>
         existing_pages.len()
     );
 
+    // Forget the previous sync's change set; sync_or_create_file records this run's
+    conn.execute("DELETE FROM sync_changed_blocks", [])
+        .map_err(|e| e.to_string())?;
+
     let mut synced_pages = 0;
     let mut synced_blocks = 0;
 
+    // Parsed once; ignored entries are neither synced nor counted as found, so
+    // pages for newly ignored files are dropped like deleted ones
+    let ignore = SyncIgnore::load(&workspace_root);
+    if !ignore.is_empty() {
+        println!("[sync_workspace] Applying exclusion patterns from .oxinotignore");
+    }
+    // Read once rather than per file; files nested differently are detected as parsed
+    let indent = load_indent_style(workspace_path);
+
+    // Count first so progress events can report a total
+    let mut progress = SyncProgressTracker {
+        processed: 0,
+        total: count_markdown_files(&workspace_root, &workspace_root, &ignore)?,
+        on_progress,
+        cancel,
+    };
+
     // Scan filesystem
     let mut found_files = std::collections::HashSet::new();
     sync_directory(
         &conn,
         &workspace_root,
         &workspace_root,
+        &ignore,
+        indent,
         None,
         &mut existing_pages,
         &mut found_files,
         &mut synced_pages,
         &mut synced_blocks,
+        &mut progress,
     )?;
 
     println!(
@@ -293,9 +719,11 @@ pub fn sync_workspace(workspace_path: String) -> Result<MigrationResult, String>
     );
 
     // Delete pages from DB that no longer exist in filesystem
+    // (unless cancelled: unvisited files are missing from found_files, not from disk)
+    let cancelled = progress.is_cancelled();
     let mut deleted_count = 0;
     for (file_path, page_id) in existing_pages.iter() {
-        if !found_files.contains(file_path) {
+        if !cancelled && !found_files.contains(file_path) {
             println!(
                 "[sync_workspace] DELETING orphaned page from DB: id={}, path={}",
                 page_id, file_path
@@ -309,6 +737,26 @@ pub fn sync_workspace(workspace_path: String) -> Result<MigrationResult, String>
         }
     }
 
+    // Wiki links are indexed only for blocks changed by this sync. This runs after the
+    // scan so that links to pages discovered later in the walk still resolve.
+    let changed_blocks = load_sync_changed_blocks(&conn)?;
+    for (block_id, page_id, content) in &changed_blocks {
+        wiki_link_index::index_block_links(&conn, block_id, content, page_id)
+            .map_err(|e| format!("Failed to index links for block {}: {}", block_id, e))?;
+    }
+
+    if cancelled {
+        println!(
+            "[sync_workspace] Sync cancelled after {} of {} files",
+            progress.processed, progress.total
+        );
+        return Err(OxinotError::cancelled(format!(
+            "sync stopped after {} of {} files",
+            progress.processed, progress.total
+        ))
+        .to_string());
+    }
+
     println!(
         "[sync_workspace] Sync complete: {} pages synced, {} blocks synced, {} pages deleted",
         synced_pages, synced_blocks, deleted_count
@@ -320,78 +768,405 @@ pub fn sync_workspace(workspace_path: String) -> Result<MigrationResult, String>
     })
 }
 
-/// Recursively sync directory with database
-fn sync_directory(
-    conn: &rusqlite::Connection,
+/// Report what `sync_workspace` would add, update and delete, without writing to the
+/// database or creating missing folder notes. Useful before a sync that would drop
+/// pages whose files were moved outside the app.
+#[tauri::command]
+pub fn sync_workspace_dry_run(workspace_path: String) -> Result<SyncPlan, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    plan_workspace_sync(&conn, Path::new(&workspace_path))
+}
+
+/// Read-only counterpart of `sync_workspace_with_progress`: the same walk, and the
+/// same diff against the pages in the DB
+fn plan_workspace_sync(conn: &Connection, workspace_root: &Path) -> Result<SyncPlan, String> {
+    let mut plan = SyncPlan::default();
+
+    // (page_id, file_mtime, file_size) by workspace-relative path
+    let mut existing_pages: HashMap<String, (String, Option<i64>, Option<i64>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, file_path, file_mtime, file_size
+                 FROM pages WHERE file_path IS NOT NULL AND is_deleted = 0",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(1)?,
+                    (row.get(0)?, row.get(2)?, row.get(3)?),
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    // A DB holding absolute paths is wiped and rebuilt by sync, so every page goes
+    let has_absolute = existing_pages
+        .keys()
+        .any(|path| path.starts_with('/') || path.contains(":\\"));
+    if has_absolute {
+        plan.deleted = existing_pages
+            .drain()
+            .map(|(file_path, (page_id, _, _))| SyncPlanEntry {
+                file_path,
+                page_id: Some(page_id),
+            })
+            .collect();
+    }
+
+    let ignore = SyncIgnore::load(workspace_root);
+    let mut found_files = HashSet::new();
+    plan_directory_sync(
+        workspace_root,
+        workspace_root,
+        &ignore,
+        &existing_pages,
+        &mut found_files,
+        &mut plan,
+    )?;
+
+    for (file_path, (page_id, _, _)) in existing_pages {
+        if !found_files.contains(&file_path) {
+            plan.deleted.push(SyncPlanEntry {
+                file_path,
+                page_id: Some(page_id),
+            });
+        }
+    }
+    plan.deleted.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    Ok(plan)
+}
+
+/// Walk `current_dir` as `sync_directory` does, recording each file's planned change
+fn plan_directory_sync(
     workspace_root: &Path,
     current_dir: &Path,
-    parent_page_id: Option<&str>,
-    existing_pages: &mut std::collections::HashMap<String, String>,
-    found_files: &mut std::collections::HashSet<String>,
-    synced_pages: &mut usize,
-    synced_blocks: &mut usize,
+    ignore: &SyncIgnore,
+    existing_pages: &HashMap<String, (String, Option<i64>, Option<i64>)>,
+    found_files: &mut HashSet<String>,
+    plan: &mut SyncPlan,
 ) -> Result<(), String> {
-    let entries = fs::read_dir(current_dir)
-        .map_err(|e| format!("Error reading directory {}: {}", current_dir.display(), e))?;
+    let (dir_entries, file_entries) = read_sync_entries(workspace_root, current_dir, ignore)?;
 
-    let mut items: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-    items.sort_by(|a, b| {
-        let a_name = a.file_name();
-        let b_name = b.file_name();
-        a_name.cmp(&b_name)
-    });
+    for entry in dir_entries {
+        let path = entry.path();
+        let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let folder_note_path = path.join(format!("{}.md", dir_name));
+        let rel_path = compute_rel_path(&folder_note_path, workspace_root)?;
+        found_files.insert(rel_path.clone());
 
-    // Skip .oxinot directory and separate files/dirs up-front
-    let mut file_entries: Vec<std::fs::DirEntry> = Vec::new();
-    let mut dir_entries: Vec<std::fs::DirEntry> = Vec::new();
+        if folder_note_path.exists() {
+            plan_file_sync(&folder_note_path, rel_path, existing_pages, plan)?;
+        } else {
+            plan.missing_folder_notes.push(rel_path.clone());
+            let page_id = existing_pages.get(&rel_path).map(|(id, _, _)| id.clone());
+            let entry = SyncPlanEntry {
+                file_path: rel_path,
+                page_id: page_id.clone(),
+            };
+            // A page whose folder note was deleted is reindexed from the recreated note
+            if page_id.is_some() {
+                plan.updated.push(entry);
+            } else {
+                plan.added.push(entry);
+            }
+        }
 
-    for entry in items {
+        plan_directory_sync(
+            workspace_root,
+            &path,
+            ignore,
+            existing_pages,
+            found_files,
+            plan,
+        )?;
+    }
+
+    for entry in file_entries {
         let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
 
-        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            // Skip .oxinot and common heavy/system directories
-            if name == ".oxinot"
-                || name == ".git"
-                || name == "node_modules"
-                || name == "target"
-                || name == "dist"
-                || name == "build"
-                || name == ".vscode"
-                || name == ".idea"
-                || name == ".DS_Store"
-            {
-                continue;
-            }
+        let rel_path = compute_rel_path(&path, workspace_root)?;
+        found_files.insert(rel_path.clone());
+        if !is_dir_note(&path) {
+            plan_file_sync(&path, rel_path, existing_pages, plan)?;
         }
+    }
 
-        // Use symlink_metadata to avoid following symlinks into potential loops
-        let metadata = entry
-            .metadata()
-            .map_err(|e| format!("Error reading metadata: {}", e))?;
+    Ok(())
+}
 
-        // Also check for symlinks explicitly if we want to skip them
-        let symlink_metadata = entry
-            .path()
-            .symlink_metadata()
-            .map_err(|e| format!("Error reading symlink metadata: {}", e))?;
+/// Classify one file the way `sync_or_create_file` would treat it: a new page, or
+/// a reindex when its mtime or size differs from what the DB recorded
+fn plan_file_sync(
+    file_path: &Path,
+    rel_path: String,
+    existing_pages: &HashMap<String, (String, Option<i64>, Option<i64>)>,
+    plan: &mut SyncPlan,
+) -> Result<(), String> {
+    let Some((page_id, db_mtime, db_size)) = existing_pages.get(&rel_path) else {
+        plan.added.push(SyncPlanEntry {
+            file_path: rel_path,
+            page_id: None,
+        });
+        return Ok(());
+    };
 
-        if symlink_metadata.is_symlink() {
-            println!("[sync_directory] Skipping symlink: {:?}", path);
-            continue;
+    let metadata = fs::metadata(file_path).map_err(|e| e.to_string())?;
+    let mtime = file_mtime_secs(&metadata);
+
+    if *db_mtime != mtime || *db_size != Some(metadata.len() as i64) {
+        plan.updated.push(SyncPlanEntry {
+            file_path: rel_path,
+            page_id: Some(page_id.clone()),
+        });
+    }
+
+    Ok(())
+}
+
+/// List pages whose file no longer matches the mtime/size recorded in the DB.
+///
+/// Block edits patch or rewrite page files assuming the DB reflects the file, so a
+/// file edited by another tool while the app is open would be overwritten by the
+/// next edit. The UI can call this first and offer to reindex those pages instead.
+/// Pages without recorded metadata are not reported.
+#[tauri::command]
+pub fn detect_external_changes(workspace_path: String) -> Result<Vec<ExternalChange>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    find_external_changes(&conn, Path::new(&workspace_path))
+}
+
+fn find_external_changes(
+    conn: &Connection,
+    workspace_root: &Path,
+) -> Result<Vec<ExternalChange>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, file_path, file_mtime, file_size FROM pages
+             WHERE file_path IS NOT NULL AND file_mtime IS NOT NULL AND file_size IS NOT NULL
+               AND is_deleted = 0
+             ORDER BY file_path",
+        )
+        .map_err(|e| e.to_string())?;
+    let pages = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut changes = Vec::new();
+    for (page_id, file_path, recorded_mtime, recorded_size) in pages {
+        let metadata = fs::metadata(workspace_root.join(&file_path)).ok();
+        let current_mtime = metadata.as_ref().and_then(file_mtime_secs);
+        let current_size = metadata.as_ref().map(|m| m.len() as i64);
+
+        if current_mtime != Some(recorded_mtime) || current_size != Some(recorded_size) {
+            changes.push(ExternalChange {
+                page_id,
+                file_path,
+                recorded_mtime,
+                recorded_size,
+                current_mtime,
+                current_size,
+            });
         }
+    }
 
-        if metadata.is_dir() {
-            dir_entries.push(entry);
-        } else if metadata.is_file() {
-            file_entries.push(entry);
+    Ok(changes)
+}
+
+/// Reindex one markdown file after it changed on disk, the way `sync_workspace` would.
+///
+/// Returns None when there is nothing to do: the file's mtime and size match what
+/// the DB recorded (e.g. the app wrote it itself), or a missing file never had a
+/// page. A deleted file's page is removed from the DB. The file's parent folder note
+/// must already be indexed; new directories are picked up by the next full sync.
+pub(crate) fn sync_single_file(
+    workspace_path: &str,
+    rel_path: &str,
+) -> Result<Option<SyncedFile>, String> {
+    let conn = open_workspace_db(workspace_path)?;
+    let workspace_root = PathBuf::from(workspace_path);
+    let file_path = workspace_root.join(rel_path);
+
+    let existing: Option<(String, Option<i64>, Option<i64>)> = conn
+        .query_row(
+            "SELECT id, file_mtime, file_size FROM pages WHERE file_path = ? AND is_deleted = 0",
+            [rel_path],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let Ok(metadata) = fs::metadata(&file_path) else {
+        let Some((page_id, _, _)) = existing else {
+            return Ok(None);
+        };
+        println!(
+            "[sync_single_file] File removed, deleting page: id={}, path={}",
+            page_id, rel_path
+        );
+        conn.execute(
+            "DELETE FROM pages WHERE id = :id",
+            named_params! { ":id": &page_id },
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(Some(SyncedFile {
+            page_id,
+            file_path: rel_path.to_string(),
+            deleted: true,
+        }));
+    };
+
+    if let Some((_, db_mtime, db_size)) = &existing {
+        if *db_mtime == file_mtime_secs(&metadata) && *db_size == Some(metadata.len() as i64) {
+            return Ok(None);
         }
     }
 
+    // A folder note's page hangs off the enclosing directory's note, like any file there
+    let is_directory = is_dir_note(&file_path);
+    let parent_dir = if is_directory {
+        file_path.parent().and_then(|dir| dir.parent())
+    } else {
+        file_path.parent()
+    };
+    let parent_page_id = match parent_dir.filter(|dir| *dir != workspace_root) {
+        Some(dir) => {
+            let dir_name = dir.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let note_rel_path =
+                compute_rel_path(&dir.join(format!("{}.md", dir_name)), &workspace_root)?;
+            let parent_id: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM pages WHERE file_path = ? AND is_deleted = 0",
+                    [&note_rel_path],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            Some(parent_id.ok_or_else(|| {
+                format!(
+                    "Folder note {} is not indexed yet; a full sync is needed for {}",
+                    note_rel_path, rel_path
+                )
+            })?)
+        }
+        None => None,
+    };
+
+    let mut existing_pages: HashMap<String, String> = existing
+        .map(|(page_id, _, _)| (rel_path.to_string(), page_id))
+        .into_iter()
+        .collect();
+    let (mut synced_pages, mut synced_blocks) = (0, 0);
+    let page_id = sync_or_create_file(
+        &conn,
+        &workspace_root,
+        &file_path,
+        load_indent_style(workspace_path),
+        parent_page_id.as_deref(),
+        is_directory,
+        &mut existing_pages,
+        &mut synced_pages,
+        &mut synced_blocks,
+    )?;
+
+    // The other pages are already indexed, so links can be resolved right away
+    let mut stmt = conn
+        .prepare("SELECT id, content FROM blocks WHERE page_id = ?")
+        .map_err(|e| e.to_string())?;
+    let blocks = stmt
+        .query_map([&page_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for (block_id, content) in &blocks {
+        wiki_link_index::index_block_links(&conn, block_id, content, &page_id)
+            .map_err(|e| format!("Failed to index links for block {}: {}", block_id, e))?;
+    }
+
+    Ok(Some(SyncedFile {
+        page_id,
+        file_path: rel_path.to_string(),
+        deleted: false,
+    }))
+}
+
+/// Workspace-relative path of `path` if it is a markdown file that sync would visit:
+/// not inside an ignored directory (`.oxinot`, `node_modules`, ...) and not matched by
+/// `.oxinotignore`
+pub(crate) fn syncable_markdown_path(
+    workspace_root: &Path,
+    path: &Path,
+    ignore: &SyncIgnore,
+) -> Option<String> {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+        return None;
+    }
+    let rel_path = compute_rel_path(path, workspace_root).ok()?;
+
+    let segments: Vec<&str> = rel_path.split('/').collect();
+    if segments.iter().any(|name| is_ignored_sync_entry(name)) {
+        return None;
+    }
+    for end in 1..=segments.len() {
+        let is_dir = end < segments.len();
+        if ignore.is_ignored(&segments[..end].join("/"), is_dir) {
+            return None;
+        }
+    }
+
+    Some(rel_path)
+}
+
+/// File modification time in unix seconds, as stored in `pages.file_mtime`
+fn file_mtime_secs(metadata: &fs::Metadata) -> Option<i64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+/// Recursively sync directory with database
+fn sync_directory(
+    conn: &rusqlite::Connection,
+    workspace_root: &Path,
+    current_dir: &Path,
+    ignore: &SyncIgnore,
+    indent: IndentStyle,
+    parent_page_id: Option<&str>,
+    existing_pages: &mut std::collections::HashMap<String, String>,
+    found_files: &mut std::collections::HashSet<String>,
+    synced_pages: &mut usize,
+    synced_blocks: &mut usize,
+    progress: &mut SyncProgressTracker,
+) -> Result<(), String> {
+    let (dir_entries, file_entries) = read_sync_entries(workspace_root, current_dir, ignore)?;
+
     // (1) Process subdirectories first so we can create directory pages (Dir/Dir.md)
     // and pass the correct parent_id when indexing their contents.
     // IMPORTANT: Every directory MUST have a folder note to serve as its page.
     // If a folder note doesn't exist, we auto-create it to prevent orphaning.
     for entry in dir_entries {
+        if progress.is_cancelled() {
+            return Ok(());
+        }
+
         let path = entry.path();
         let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
         let folder_note_path = path.join(format!("{}.md", dir_name));
@@ -416,28 +1191,37 @@ fn sync_directory(
             conn,
             workspace_root,
             &folder_note_path,
+            indent,
             parent_page_id,
             true,
             existing_pages,
             synced_pages,
             synced_blocks,
         )?;
+        progress.file_synced(&rel_path);
 
         sync_directory(
             conn,
             workspace_root,
             &path,
+            ignore,
+            indent,
             Some(&page_id),
             existing_pages,
             found_files,
             synced_pages,
             synced_blocks,
+            progress,
         )?;
     }
 
     // (2) Process regular markdown files in the current directory.
     // IMPORTANT: never index "directory note" files (Dir/Dir.md) as regular pages.
     for entry in file_entries {
+        if progress.is_cancelled() {
+            return Ok(());
+        }
+
         let path = entry.path();
 
         if let Some(ext) = path.extension() {
@@ -448,15 +1232,7 @@ fn sync_directory(
             continue;
         }
 
-        let is_dir_note = path
-            .parent()
-            .and_then(|p| p.file_name())
-            .and_then(|n| n.to_str())
-            .zip(path.file_stem().and_then(|s| s.to_str()))
-            .map(|(parent_name, stem)| parent_name == stem)
-            .unwrap_or(false);
-
-        if is_dir_note {
+        if is_dir_note(&path) {
             // Store relative path in found_files for directory notes
             let rel_path = compute_rel_path(&path, workspace_root)?;
             println!(
@@ -476,22 +1252,155 @@ fn sync_directory(
             conn,
             workspace_root,
             &path,
+            indent,
             parent_page_id,
             false,
             existing_pages,
             synced_pages,
             synced_blocks,
         )?;
+        progress.file_synced(&rel_path);
     }
 
     Ok(())
 }
 
+/// List the entries of `current_dir` that sync visits, sorted by name and split into
+/// (directories, files). Ignored names, `.oxinotignore` matches and symlinks are left
+/// out, so ignored directories are never walked.
+fn read_sync_entries(
+    workspace_root: &Path,
+    current_dir: &Path,
+    ignore: &SyncIgnore,
+) -> Result<(Vec<std::fs::DirEntry>, Vec<std::fs::DirEntry>), String> {
+    let entries = fs::read_dir(current_dir)
+        .map_err(|e| format!("Error reading directory {}: {}", current_dir.display(), e))?;
+
+    let mut items: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    items.sort_by(|a, b| {
+        let a_name = a.file_name();
+        let b_name = b.file_name();
+        a_name.cmp(&b_name)
+    });
+
+    // Skip .oxinot directory and separate files/dirs up-front
+    let mut file_entries: Vec<std::fs::DirEntry> = Vec::new();
+    let mut dir_entries: Vec<std::fs::DirEntry> = Vec::new();
+
+    for entry in items {
+        let path = entry.path();
+
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            if is_ignored_sync_entry(name) {
+                continue;
+            }
+        }
+
+        // Use symlink_metadata to avoid following symlinks into potential loops
+        let metadata = entry
+            .metadata()
+            .map_err(|e| format!("Error reading metadata: {}", e))?;
+
+        // Also check for symlinks explicitly if we want to skip them
+        let symlink_metadata = entry
+            .path()
+            .symlink_metadata()
+            .map_err(|e| format!("Error reading symlink metadata: {}", e))?;
+
+        if symlink_metadata.is_symlink() {
+            println!("[sync_directory] Skipping symlink: {:?}", path);
+            continue;
+        }
+
+        if ignore.is_ignored(&compute_rel_path(&path, workspace_root)?, metadata.is_dir()) {
+            continue;
+        }
+
+        if metadata.is_dir() {
+            dir_entries.push(entry);
+        } else if metadata.is_file() {
+            file_entries.push(entry);
+        }
+    }
+
+    Ok((dir_entries, file_entries))
+}
+
+/// A directory note (`Dir/Dir.md`) is synced as its directory's page, never as a regular page
+fn is_dir_note(path: &Path) -> bool {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .zip(path.file_stem().and_then(|s| s.to_str()))
+        .map(|(parent_name, stem)| parent_name == stem)
+        .unwrap_or(false)
+}
+
+/// Skip .oxinot and common heavy/system directories
+pub(crate) fn is_ignored_sync_entry(name: &str) -> bool {
+    matches!(
+        name,
+        ".oxinot"
+            | ".git"
+            | "node_modules"
+            | "target"
+            | "dist"
+            | "build"
+            | ".vscode"
+            | ".idea"
+            | ".DS_Store"
+    )
+}
+
+/// Number of files `sync_directory` will sync under `dir`: one folder note per
+/// subdirectory plus every other `.md` file. Mirrors its skip rules.
+fn count_markdown_files(
+    workspace_root: &Path,
+    dir: &Path,
+    ignore: &SyncIgnore,
+) -> Result<usize, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Error reading directory {}: {}", dir.display(), e))?;
+    let dir_name = dir.file_name().and_then(|n| n.to_str());
+
+    let mut count = 0;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            if is_ignored_sync_entry(name) {
+                continue;
+            }
+        }
+
+        let symlink_metadata = path
+            .symlink_metadata()
+            .map_err(|e| format!("Error reading symlink metadata: {}", e))?;
+        if symlink_metadata.is_symlink() {
+            continue;
+        }
+        let is_dir = symlink_metadata.is_dir();
+        if ignore.is_ignored(&compute_rel_path(&path, workspace_root)?, is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            count += 1 + count_markdown_files(workspace_root, &path, ignore)?;
+        } else if path.extension().is_some_and(|ext| ext == "md")
+            && path.file_stem().and_then(|s| s.to_str()) != dir_name
+        {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
 /// Sync or create a file in database
 fn sync_or_create_file(
     conn: &rusqlite::Connection,
     workspace_root: &Path,
     file_path: &Path,
+    indent: IndentStyle,
     parent_page_id: Option<&str>,
     is_directory: bool,
     existing_pages: &mut std::collections::HashMap<String, String>,
@@ -575,7 +1484,7 @@ fn sync_or_create_file(
                 .map_err(|e| e.to_string())?;
 
             // Parse blocks from markdown
-            let markdown_blocks = markdown_to_blocks(&content, &page_id);
+            let markdown_blocks = markdown_to_blocks(&content, &page_id, indent);
             let markdown_block_ids: std::collections::HashSet<String> =
                 markdown_blocks.iter().map(|b| b.id.clone()).collect();
 
@@ -602,6 +1511,7 @@ fn sync_or_create_file(
                             [&block_id],
                         )
                         .map_err(|e| e.to_string())?;
+                        deindex_block_fts(conn, &block_id)?;
                         eprintln!("[sync_or_create_file] Deleted orphaned block: {}", block_id);
                     } else {
                         // Preserve: recent block likely still being synced to markdown
@@ -617,8 +1527,8 @@ fn sync_or_create_file(
             for block in &markdown_blocks {
                 conn.execute(
                     "INSERT OR REPLACE INTO blocks (id, page_id, parent_id, content, order_weight,
-                                        block_type, created_at, updated_at)
-                     VALUES (:id, :page_id, :parent_id, :content, :order_weight, :block_type, :created_at, :updated_at)",
+                                        block_type, language, created_at, updated_at)
+                     VALUES (:id, :page_id, :parent_id, :content, :order_weight, :block_type, :language, :created_at, :updated_at)",
                     named_params! {
                         ":id": &block.id,
                         ":page_id": &block.page_id,
@@ -626,6 +1536,7 @@ fn sync_or_create_file(
                         ":content": &block.content,
                         ":order_weight": block.order_weight,
                         ":block_type": block_type_to_string(&block.block_type),
+                        ":language": &block.language,
                         ":created_at": &block.created_at,
                         ":updated_at": &block.updated_at
                     },
@@ -636,6 +1547,20 @@ fn sync_or_create_file(
                 index_block_fts(&conn, &block.id, &page_id, &block.content)?;
             }
 
+            save_page_frontmatter(conn, &page_id, &content)?;
+            if let Some(title) = slug_page_title(file_name, &content) {
+                conn.execute(
+                    "UPDATE pages SET title = ? WHERE id = ?",
+                    [&title, &page_id],
+                )
+                .map_err(|e| e.to_string())?;
+                page_path_service::update_page_path(conn, &page_id, &rel_path)
+                    .map_err(|e| format!("Failed to update page path: {}", e))?;
+            }
+            record_sync_changed_blocks(conn, &page_id, &markdown_blocks)?;
+            // The file changed outside the editor, so undo snapshots no longer apply.
+            block_history::clear_page_history(conn, &page_id)?;
+
             *synced_pages += 1;
             *synced_blocks += markdown_blocks.len();
         }
@@ -648,14 +1573,18 @@ fn sync_or_create_file(
     let content = fs::read_to_string(file_path).map_err(|e| e.to_string())?;
     let page_id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
+    let title = slug_page_title(file_name, &content).unwrap_or_else(|| file_name.to_string());
 
-    // Store relative path in DB (P0 requirement)
-    conn.execute(
+    // Store relative path in DB (P0 requirement).
+    // Insert only if no live page has this path yet: another sync running concurrently
+    // may have created it after `existing_pages` was loaded.
+    let inserted = conn.execute(
         "INSERT INTO pages (id, title, parent_id, file_path, is_directory, file_mtime, file_size, created_at, updated_at)
-         VALUES (:id, :title, :parent_id, :file_path, :is_directory, :file_mtime, :file_size, :created_at, :updated_at)",
+         SELECT :id, :title, :parent_id, :file_path, :is_directory, :file_mtime, :file_size, :created_at, :updated_at
+         WHERE NOT EXISTS (SELECT 1 FROM pages WHERE file_path = :file_path AND is_deleted = 0)",
         named_params! {
             ":id": &page_id,
-            ":title": file_name,
+            ":title": &title,
             ":parent_id": parent_page_id,
             ":file_path": &rel_path,
             ":is_directory": if is_directory { 1 } else { 0 },
@@ -667,18 +1596,41 @@ fn sync_or_create_file(
     )
     .map_err(|e| e.to_string())?;
 
+    if inserted == 0 {
+        let existing_id: String = conn
+            .query_row(
+                "SELECT id FROM pages WHERE file_path = ? AND is_deleted = 0",
+                [&rel_path],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        println!("Page created concurrently: {} -> {}", file_name, existing_id);
+        existing_pages.insert(rel_path, existing_id);
+        return sync_or_create_file(
+            conn,
+            workspace_root,
+            file_path,
+            indent,
+            parent_page_id,
+            is_directory,
+            existing_pages,
+            synced_pages,
+            synced_blocks,
+        );
+    }
+
     // Update page_paths
     page_path_service::update_page_path(conn, &page_id, &rel_path)
         .map_err(|e| format!("Failed to update page path: {}", e))?;
 
     // Parse and create blocks
-    let blocks = markdown_to_blocks(&content, &page_id);
+    let blocks = markdown_to_blocks(&content, &page_id, indent);
 
     for block in &blocks {
         conn.execute(
             "INSERT INTO blocks (id, page_id, parent_id, content, order_weight,
-                                block_type, created_at, updated_at)
-             VALUES (:id, :page_id, :parent_id, :content, :order_weight, :block_type, :created_at, :updated_at)",
+                                block_type, language, created_at, updated_at)
+             VALUES (:id, :page_id, :parent_id, :content, :order_weight, :block_type, :language, :created_at, :updated_at)",
             named_params! {
                 ":id": &block.id,
                 ":page_id": &block.page_id,
@@ -686,6 +1638,7 @@ fn sync_or_create_file(
                 ":content": &block.content,
                 ":order_weight": &block.order_weight,
                 ":block_type": block_type_to_string(&block.block_type),
+                ":language": &block.language,
                 ":created_at": &block.created_at,
                 ":updated_at": &block.updated_at
             },
@@ -696,12 +1649,71 @@ fn sync_or_create_file(
         index_block_fts(&conn, &block.id, &page_id, &block.content)?;
     }
 
+    save_page_frontmatter(conn, &page_id, &content)?;
+    record_sync_changed_blocks(conn, &page_id, &blocks)?;
+
     *synced_pages += 1;
     *synced_blocks += blocks.len();
 
     Ok(page_id)
 }
 
+/// Display title kept in the frontmatter of a slug-named file (`my-page.md` with
+/// `title: My Page`). None when the file has no title there or its name is not a
+/// slug of it, in which case the file stem is the title.
+fn slug_page_title(file_stem: &str, content: &str) -> Option<String> {
+    frontmatter_value(content, TITLE_FRONTMATTER_KEY)
+        .filter(|title| title != file_stem && is_slug_stem(file_stem, title))
+}
+
+/// Record blocks created or replaced during the current sync
+fn record_sync_changed_blocks(
+    conn: &rusqlite::Connection,
+    page_id: &str,
+    blocks: &[crate::models::block::Block],
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("INSERT OR REPLACE INTO sync_changed_blocks (block_id, page_id) VALUES (?, ?)")
+        .map_err(|e| e.to_string())?;
+
+    for block in blocks {
+        stmt.execute([&block.id, page_id])
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Load (block_id, page_id, content) for every block recorded by the last sync
+fn load_sync_changed_blocks(
+    conn: &rusqlite::Connection,
+) -> Result<Vec<(String, String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.page_id, b.content
+             FROM sync_changed_blocks s
+             JOIN blocks b ON b.id = s.block_id
+             ORDER BY b.page_id, b.order_key",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
+
+/// Get ids of the blocks created or replaced by the most recent sync
+#[tauri::command]
+pub fn get_last_sync_changed_blocks(workspace_path: String) -> Result<Vec<String>, String> {
+    let conn = open_workspace_db(&workspace_path)?;
+    let changed = load_sync_changed_blocks(&conn)?;
+    Ok(changed.into_iter().map(|(block_id, _, _)| block_id).collect())
+}
+
 /// Incremental sync: currently unified to use the filesystem-driven sync engine
 /// for consistent directory-note semantics (Dir/Dir.md is the directory page's content source).
 ///
@@ -720,7 +1732,7 @@ pub fn sync_workspace_incremental(workspace_path: String) -> Result<MigrationRes
     );
 
     // Reuse the same engine as full sync (no DB wipe here).
-    sync_workspace(workspace_path)
+    sync_workspace_with_progress(&workspace_path, None, &mut |_| {})
 }
 
 /// Full reindex: delete all and rebuild from files
@@ -733,9 +1745,62 @@ pub fn sync_workspace_incremental(workspace_path: String) -> Result<MigrationRes
 /// To avoid duplicate "directory note" pages (Dir/Dir.md appearing as its own page),
 /// full reindex should use the filesystem-driven sync, which treats Dir/Dir.md
 /// as the content source for the directory page (Notion-like).
+///
+/// Runs off the main thread so `cancel_reindex` can stop it between files. A
+/// cancelled reindex keeps the pages synced so far and returns the cancellation error.
 #[tauri::command]
-pub fn reindex_workspace(workspace_path: String) -> Result<MigrationResult, String> {
-    let conn = open_workspace_db(&workspace_path)?;
+pub async fn reindex_workspace(workspace_path: String) -> Result<MigrationResult, String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    reindex_cancel_flags()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(workspace_path.clone(), cancel.clone());
+
+    let result = {
+        let workspace_path = workspace_path.clone();
+        let cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || run_reindex(&workspace_path, &cancel))
+            .await
+            .map_err(|e| format!("Reindex task failed: {e}"))
+    };
+
+    // Only drop our own flag; a newer reindex may have registered its own
+    if let Ok(mut flags) = reindex_cancel_flags().lock() {
+        if flags
+            .get(&workspace_path)
+            .is_some_and(|flag| Arc::ptr_eq(flag, &cancel))
+        {
+            flags.remove(&workspace_path);
+        }
+    }
+
+    result?
+}
+
+/// Ask a running `reindex_workspace` to stop before its next file.
+/// Returns false if no reindex is running for the workspace.
+#[tauri::command]
+pub fn cancel_reindex(workspace_path: String) -> Result<bool, String> {
+    let flags = reindex_cancel_flags().lock().map_err(|e| e.to_string())?;
+    match flags.get(&workspace_path) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+static REINDEX_CANCEL_FLAGS: OnceLock<Mutex<std::collections::HashMap<String, Arc<AtomicBool>>>> =
+    OnceLock::new();
+
+fn reindex_cancel_flags() -> &'static Mutex<std::collections::HashMap<String, Arc<AtomicBool>>> {
+    REINDEX_CANCEL_FLAGS.get_or_init(|| Mutex::new(std::collections::HashMap::new()))
+}
+
+/// Wipe and rebuild the index; stops between files once `cancel` is set
+fn run_reindex(workspace_path: &str, cancel: &AtomicBool) -> Result<MigrationResult, String> {
+    let conn = open_workspace_db(workspace_path)?;
 
     println!(
         "[reindex_workspace] Starting full reindex for: {}",
@@ -750,7 +1815,7 @@ pub fn reindex_workspace(workspace_path: String) -> Result<MigrationResult, Stri
 
     // Rebuild from filesystem using the canonical, filesystem-driven sync.
     // This ensures directory-notes (Dir/Dir.md) do not become duplicate pages.
-    let result = sync_workspace(workspace_path.clone())?;
+    let result = sync_workspace_with_progress(workspace_path, Some(cancel), &mut |_| {})?;
 
     println!(
         "[reindex_workspace] Complete: {} pages indexed",
@@ -771,8 +1836,12 @@ pub fn reindex_workspace(workspace_path: String) -> Result<MigrationResult, Stri
 
 #[tauri::command]
 pub async fn close_workspace() -> Result<(), String> {
-    // Current implementation doesn't need to do anything server-side
-    // The frontend just clears the state
+    // The frontend clears its own state; only background maintenance needs stopping
+    crate::commands::db::stop_all_auto_optimize();
+    crate::services::file_watcher::unwatch_workspace();
+    if let Ok(mut open) = open_workspace_slot().lock() {
+        *open = None;
+    }
     Ok(())
 }
 
@@ -811,3 +1880,335 @@ pub fn reveal_in_finder(path: String) -> Result<bool, String> {
 
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_reads_slug_page_title_from_frontmatter() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_slug_{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+        fs::write(
+            temp_dir.join("launch-plan.md"),
+            "---\ntitle: Launch Plan\n---\n- Goals\n  ID::g1\n",
+        )
+        .unwrap();
+        // A title the file name is not a slug of is ignored
+        fs::write(
+            temp_dir.join("notes.md"),
+            "---\ntitle: Meeting Notes\n---\n- Agenda\n  ID::a1\n",
+        )
+        .unwrap();
+
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+        let conn = open_workspace_db(&path_str).unwrap();
+        let title_of = |file_path: &str| -> (String, String) {
+            conn.query_row(
+                "SELECT p.title, pp.path_text FROM pages p JOIN page_paths pp ON pp.page_id = p.id
+                 WHERE p.file_path = ?",
+                [file_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+        let expected = ("Launch Plan".to_string(), "Launch Plan".to_string());
+        assert_eq!(title_of("launch-plan.md"), expected);
+        assert_eq!(title_of("notes.md").0, "notes");
+
+        // An external edit of the title is picked up on the next sync
+        fs::write(
+            temp_dir.join("launch-plan.md"),
+            "---\ntitle: \"Launch: Plan\"\n---\n- Goals\n  ID::g1\n",
+        )
+        .unwrap();
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+        assert_eq!(title_of("launch-plan.md").0, "Launch: Plan");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_records_only_changed_blocks() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_sync_{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        let alpha_id = Uuid::new_v4().to_string();
+        let beta_id = Uuid::new_v4().to_string();
+        fs::write(
+            temp_dir.join("A.md"),
+            format!("- Alpha\n  ID::{}\n", alpha_id),
+        )
+        .unwrap();
+        fs::write(temp_dir.join("B.md"), format!("- Beta\n  ID::{}\n", beta_id)).unwrap();
+
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+        let mut first = get_last_sync_changed_blocks(path_str.clone()).unwrap();
+        first.sort();
+        let mut expected = vec![alpha_id.clone(), beta_id.clone()];
+        expected.sort();
+        assert_eq!(first, expected);
+
+        // Externally edit A.md only (size changes, so it is reindexed)
+        fs::write(
+            temp_dir.join("A.md"),
+            format!("- Alpha edited, see [[B]]\n  ID::{}\n", alpha_id),
+        )
+        .unwrap();
+
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+        let second = get_last_sync_changed_blocks(path_str.clone()).unwrap();
+        assert_eq!(second, vec![alpha_id.clone()]);
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        let fts_content: String = conn
+            .query_row(
+                "SELECT content FROM blocks_fts WHERE block_id = ?",
+                [&alpha_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(fts_content, "Alpha edited, see [[B]]");
+
+        let resolved_links: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM wiki_links WHERE from_block_id = ? AND to_page_id IS NOT NULL",
+                [&alpha_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(resolved_links, 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_reports_progress_per_file() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_progress_{}", Uuid::new_v4()));
+        fs::create_dir_all(temp_dir.join("Projects")).unwrap();
+        fs::create_dir_all(temp_dir.join("node_modules")).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        fs::write(temp_dir.join("A.md"), "- Alpha\n").unwrap();
+        fs::write(temp_dir.join("notes.txt"), "not markdown").unwrap();
+        fs::write(temp_dir.join("Projects").join("Plan.md"), "- Plan\n").unwrap();
+        fs::write(temp_dir.join("node_modules").join("Skip.md"), "- Skip\n").unwrap();
+
+        let mut events = Vec::new();
+        sync_workspace_with_progress(&path_str, None, &mut |progress| {
+            events.push(progress.clone())
+        })
+        .unwrap();
+
+        // Projects/Projects.md is auto-created and synced as the folder note
+        let files: Vec<&str> = events.iter().map(|e| e.current_file.as_str()).collect();
+        assert_eq!(
+            files,
+            vec!["Projects/Projects.md", "Projects/Plan.md", "A.md"]
+        );
+        assert!(events.iter().all(|e| e.total == 3));
+        assert_eq!(
+            events.iter().map(|e| e.processed).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_detect_external_changes_reports_edited_and_missing_files() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_external_{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        fs::write(temp_dir.join("Same.md"), "- Same\n").unwrap();
+        fs::write(temp_dir.join("Edited.md"), "- Edited\n").unwrap();
+        fs::write(temp_dir.join("Gone.md"), "- Gone\n").unwrap();
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        assert!(find_external_changes(&conn, &temp_dir).unwrap().is_empty());
+
+        let edited = "- Edited elsewhere\n";
+        fs::write(temp_dir.join("Edited.md"), edited).unwrap();
+        fs::remove_file(temp_dir.join("Gone.md")).unwrap();
+
+        let changes = find_external_changes(&conn, &temp_dir).unwrap();
+        let paths: Vec<&str> = changes.iter().map(|c| c.file_path.as_str()).collect();
+        assert_eq!(paths, vec!["Edited.md", "Gone.md"]);
+        assert_eq!(changes[0].current_size, Some(edited.len() as i64));
+        assert!(changes[0].current_mtime.is_some());
+        assert_eq!(changes[1].current_mtime, None);
+        assert_eq!(changes[1].current_size, None);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_single_file_reindexes_only_external_changes() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_single_{}", Uuid::new_v4()));
+        fs::create_dir_all(temp_dir.join("Projects")).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        fs::write(temp_dir.join("A.md"), "- Alpha\n").unwrap();
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+        let conn = open_workspace_db(&path_str).unwrap();
+
+        // Unchanged since the last sync (as after the app's own writes): nothing to do
+        assert_eq!(sync_single_file(&path_str, "A.md").unwrap(), None);
+
+        // A new file lands under its folder note's page
+        fs::write(temp_dir.join("Projects").join("Plan.md"), "- Plan [[A]]\n").unwrap();
+        let synced = sync_single_file(&path_str, "Projects/Plan.md")
+            .unwrap()
+            .unwrap();
+        assert!(!synced.deleted);
+        let (parent_path, content): (String, String) = conn
+            .query_row(
+                "SELECT parent.file_path, b.content FROM pages p
+                 JOIN pages parent ON parent.id = p.parent_id
+                 JOIN blocks b ON b.page_id = p.id
+                 WHERE p.id = ?",
+                [&synced.page_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(parent_path, "Projects/Projects.md");
+        assert_eq!(content, "Plan [[A]]");
+        let links: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM wiki_links WHERE from_page_id = ?",
+                [&synced.page_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(links, 1);
+
+        fs::remove_file(temp_dir.join("Projects").join("Plan.md")).unwrap();
+        let removed = sync_single_file(&path_str, "Projects/Plan.md")
+            .unwrap()
+            .unwrap();
+        assert!(removed.deleted);
+        assert_eq!(removed.page_id, synced.page_id);
+        assert_eq!(
+            sync_single_file(&path_str, "Projects/Plan.md").unwrap(),
+            None
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_skips_oxinotignore_matches() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_ignore_{}", Uuid::new_v4()));
+        let archive_dir = temp_dir.join("archive").join("2020");
+        fs::create_dir_all(&archive_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        fs::write(temp_dir.join(".oxinotignore"), "archive/**\n*.tmp.md\n").unwrap();
+        fs::write(temp_dir.join("A.md"), "- Alpha\n").unwrap();
+        fs::write(temp_dir.join("draft.tmp.md"), "- Draft\n").unwrap();
+        fs::write(archive_dir.join("Old.md"), "- Old\n").unwrap();
+
+        let mut events = Vec::new();
+        sync_workspace_with_progress(&path_str, None, &mut |progress| {
+            events.push(progress.clone())
+        })
+        .unwrap();
+
+        let files: Vec<&str> = events.iter().map(|e| e.current_file.as_str()).collect();
+        assert_eq!(files, vec!["A.md"]);
+        assert!(events.iter().all(|e| e.total == 1));
+        // The ignored directory was not walked, so no folder notes were created in it
+        assert!(!temp_dir.join("archive").join("archive.md").exists());
+        assert!(!archive_dir.join("2020.md").exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_dry_run_reports_changes_without_writing() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_dry_run_{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        fs::write(temp_dir.join("Kept.md"), "- Kept\n").unwrap();
+        fs::write(temp_dir.join("Edited.md"), "- Edited\n").unwrap();
+        fs::write(temp_dir.join("Moved.md"), "- Moved\n").unwrap();
+        sync_workspace_with_progress(&path_str, None, &mut |_| {}).unwrap();
+
+        fs::write(temp_dir.join("Edited.md"), "- Edited, now longer\n").unwrap();
+        fs::remove_file(temp_dir.join("Moved.md")).unwrap();
+        fs::write(temp_dir.join("New.md"), "- New\n").unwrap();
+        fs::create_dir_all(temp_dir.join("Projects")).unwrap();
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        let pages_before: i64 = conn
+            .query_row("SELECT COUNT(*) FROM pages", [], |row| row.get(0))
+            .unwrap();
+
+        let plan = plan_workspace_sync(&conn, &temp_dir).unwrap();
+        let paths = |entries: &[SyncPlanEntry]| {
+            entries
+                .iter()
+                .map(|e| e.file_path.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paths(&plan.added), vec!["Projects/Projects.md", "New.md"]);
+        assert_eq!(paths(&plan.updated), vec!["Edited.md"]);
+        assert_eq!(paths(&plan.deleted), vec!["Moved.md"]);
+        assert!(plan.deleted[0].page_id.is_some());
+        assert_eq!(plan.missing_folder_notes, vec!["Projects/Projects.md"]);
+
+        // Nothing was written: same pages, no folder note created
+        let pages_after: i64 = conn
+            .query_row("SELECT COUNT(*) FROM pages", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(pages_after, pages_before);
+        assert!(!temp_dir.join("Projects").join("Projects.md").exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_cancelled_sync_stops_before_next_file() {
+        let temp_dir = std::env::temp_dir().join(format!("oxinot_test_cancel_{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path_str = temp_dir.to_string_lossy().to_string();
+
+        for name in ["A", "B", "C"] {
+            fs::write(
+                temp_dir.join(format!("{}.md", name)),
+                format!("- {}\n", name),
+            )
+            .unwrap();
+        }
+
+        let cancel = AtomicBool::new(false);
+        let mut synced = Vec::new();
+        let err = sync_workspace_with_progress(&path_str, Some(&cancel), &mut |progress| {
+            synced.push(progress.current_file.clone());
+            cancel.store(true, Ordering::SeqCst);
+        })
+        .unwrap_err();
+
+        assert!(err.starts_with("Operation cancelled"), "{}", err);
+        assert_eq!(synced, vec!["A.md"]);
+
+        let conn = open_workspace_db(&path_str).unwrap();
+        let titles: Vec<String> = conn
+            .prepare("SELECT title FROM pages ORDER BY title")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(titles, vec!["A"]);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
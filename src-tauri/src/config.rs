@@ -9,5 +9,27 @@ pub const METADATA_DIR_NAME: &str = ".oxinot";
 /// Database filename within the metadata directory
 pub const WORKSPACE_DB_FILENAME: &str = "outliner.db";
 
+/// How long a workspace DB connection waits for another writer (the file watcher,
+/// background optimization) to release its lock before failing with SQLITE_BUSY
+pub const DB_BUSY_TIMEOUT_MS: u64 = 5000;
+
 /// Settings filename within the metadata directory
 pub const SETTINGS_FILENAME: &str = "settings.json";
+
+/// Page templates directory within the metadata directory
+pub const TEMPLATES_DIR_NAME: &str = "templates";
+
+/// Trash directory (deleted pages) within the metadata directory
+pub const TRASH_DIR_NAME: &str = "trash";
+
+/// Trash manifest filename within the trash directory
+pub const TRASH_MANIFEST_FILENAME: &str = "manifest.json";
+
+/// Days a deleted page stays restorable before it is purged from the trash
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Sync exclusion patterns (`.gitignore` syntax) at the workspace root
+pub const SYNC_IGNORE_FILENAME: &str = ".oxinotignore";
+
+/// Block metadata key types within the metadata directory
+pub const METADATA_SCHEMA_FILENAME: &str = "metadata_schema.json";
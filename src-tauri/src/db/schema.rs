@@ -28,7 +28,8 @@ CREATE TABLE IF NOT EXISTS blocks (
     page_id TEXT NOT NULL,
     parent_id TEXT,  -- NULL = 페이지의 루트 레벨 블록
     content TEXT NOT NULL DEFAULT '',
-    order_weight REAL NOT NULL,  -- Fractional Indexing용
+    order_weight REAL NOT NULL,  -- 이전 Fractional Indexing 값 (정렬에는 order_key 사용)
+    order_key TEXT,  -- 문자열 Fractional Indexing 키 (형제 정렬 기준, 재조정 불필요)
     is_collapsed INTEGER DEFAULT 0,
     block_type TEXT DEFAULT 'bullet',  -- 'bullet' | 'code' | 'fence'
     language TEXT,  -- 코드 블록의 언어
@@ -43,6 +44,27 @@ CREATE TABLE IF NOT EXISTS blocks (
 CREATE INDEX IF NOT EXISTS idx_blocks_page ON blocks(page_id);
 CREATE INDEX IF NOT EXISTS idx_blocks_parent ON blocks(parent_id);
 CREATE INDEX IF NOT EXISTS idx_blocks_order ON blocks(page_id, parent_id, order_weight);
+CREATE INDEX IF NOT EXISTS idx_blocks_order_key ON blocks(page_id, parent_id, order_key);
+
+-- order_key 없이 저장된 블록은 order_weight에서 순서를 보존하는 키를 만든다:
+-- 고정 폭 십진수(정수 15자리 + 소수 15자리)에서 끝의 0을 제거. 0 이하는 가장 작은 키.
+CREATE TRIGGER IF NOT EXISTS blocks_order_key_insert AFTER INSERT ON blocks
+WHEN NEW.order_key IS NULL
+BEGIN
+    UPDATE blocks SET order_key = COALESCE(NULLIF(rtrim(replace(
+        printf('%031.15f', MIN(MAX(NEW.order_weight, 0), 999999999999999)), '.', ''), '0'), ''),
+        '000000000000000000000000000000V')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS blocks_order_key_update AFTER UPDATE OF order_key ON blocks
+WHEN NEW.order_key IS NULL
+BEGIN
+    UPDATE blocks SET order_key = COALESCE(NULLIF(rtrim(replace(
+        printf('%031.15f', MIN(MAX(NEW.order_weight, 0), 999999999999999)), '.', ''), '0'), ''),
+        '000000000000000000000000000000V')
+    WHERE id = NEW.id;
+END;
 
 -- 블록 참조 (백링크, Phase 2에서 구현)
 CREATE TABLE IF NOT EXISTS block_refs (
@@ -74,6 +96,18 @@ CREATE INDEX IF NOT EXISTS idx_block_metadata_block ON block_metadata(block_id);
 CREATE INDEX IF NOT EXISTS idx_block_metadata_key ON block_metadata(key);
 CREATE INDEX IF NOT EXISTS idx_block_metadata_key_value ON block_metadata(key, value);
 
+-- 페이지 메타데이터 (페이지 파일 앞머리의 YAML frontmatter)
+CREATE TABLE IF NOT EXISTS page_metadata (
+    page_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+
+    PRIMARY KEY (page_id, key),
+    FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_metadata_key_value ON page_metadata(key, value);
+
 -- FTS: 링크 제안/검색을 위한 블록 검색 인덱스 (content + anchor id + path 캐시)
 -- NOTE: 이 테이블은 파생 데이터이며, 리빌드/리인덱싱 시 재생성될 수 있음.
 -- anchor_id는 마크다운 파일에만 숨겨 저장되는 "ID::<uuid>"에서 추출되어 blocks.id와 일치하도록 유지된다.
@@ -94,12 +128,16 @@ CREATE VIRTUAL TABLE IF NOT EXISTS blocks_fts USING fts5(
 CREATE TABLE IF NOT EXISTS page_paths (
     page_id TEXT PRIMARY KEY,
     path_text TEXT NOT NULL,
+    nfc_basename TEXT,                 -- 마지막 경로 요소의 NFC 형태 (정규화 일치 조회용)
+    folded_basename TEXT,              -- NFC 후 소문자화 (대소문자 무시 조회용)
     updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
 
     FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
 );
 
 CREATE INDEX IF NOT EXISTS idx_page_paths_text ON page_paths(path_text);
+CREATE INDEX IF NOT EXISTS idx_page_paths_nfc_basename ON page_paths(nfc_basename);
+CREATE INDEX IF NOT EXISTS idx_page_paths_folded_basename ON page_paths(folded_basename);
 
 -- 블록 경로 캐시 (블록 링크 제안에서 표시용: "A/B/C > X > Y")
 -- 실제 링크 삽입은 (())에 UUID를 쓰는 정책이므로, path_text는 검색/표시에만 사용.
@@ -142,6 +180,67 @@ CREATE INDEX IF NOT EXISTS idx_wiki_links_target_path ON wiki_links(target_path)
 CREATE INDEX IF NOT EXISTS idx_wiki_links_from_page ON wiki_links(from_page_id);
 CREATE INDEX IF NOT EXISTS idx_wiki_links_from_block ON wiki_links(from_block_id);
 CREATE INDEX IF NOT EXISTS idx_wiki_links_type ON wiki_links(link_type);
+
+-- 태그 인덱스 (Tags). 블록 본문의 #tag, #nested/tag, #[[multi word]]
+-- NOTE: 태그는 소문자로 저장된다. 블록 인덱싱(index_block_links) 때마다 다시 채워진다.
+CREATE TABLE IF NOT EXISTS block_tags (
+    block_id TEXT NOT NULL,
+    page_id TEXT NOT NULL,
+    tag TEXT NOT NULL,                 -- lowercased, without '#': "project/alpha"
+
+    PRIMARY KEY (block_id, tag),
+    FOREIGN KEY (block_id) REFERENCES blocks(id) ON DELETE CASCADE,
+    FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_tags_tag ON block_tags(tag);
+CREATE INDEX IF NOT EXISTS idx_block_tags_page ON block_tags(page_id);
+
+-- 블록 예약 날짜 인덱스. 본문의 SCHEDULED::YYYY-MM-DD / @YYYY-MM-DD 중 가장 이른 날짜
+-- NOTE: 파생 데이터. block_metadata와 달리 마크다운으로 직렬화되지 않으며,
+-- 블록 인덱싱(index_block_links) 때마다 다시 채워진다.
+CREATE TABLE IF NOT EXISTS block_schedule (
+    block_id TEXT PRIMARY KEY,
+    scheduled_date TEXT NOT NULL,      -- "YYYY-MM-DD"
+
+    FOREIGN KEY (block_id) REFERENCES blocks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_block_schedule_date ON block_schedule(scheduled_date);
+
+-- 마지막 동기화에서 생성/교체된 블록 (링크/FTS 인덱싱 범위를 변경분으로 한정)
+-- NOTE: 파생 데이터. sync_workspace 시작 시 비워지고 해당 동기화 결과로 다시 채워진다.
+CREATE TABLE IF NOT EXISTS sync_changed_blocks (
+    block_id TEXT PRIMARY KEY,
+    page_id TEXT NOT NULL,
+    synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (block_id) REFERENCES blocks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_changed_blocks_page ON sync_changed_blocks(page_id);
+
+-- 블록 변경 이력 (undo용). 각 항목은 되돌리기에 필요한 역연산을 JSON으로 저장한다:
+-- 복원할 블록 스냅샷(content, parent_id, order_weight, metadata 등)과 삭제할 블록 ID 목록.
+-- NOTE: 항목 수는 워크스페이스마다 상한이 있으며, 오래된 항목부터 정리된다.
+CREATE TABLE IF NOT EXISTS block_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    operation TEXT NOT NULL,           -- 'create' | 'update' | 'delete' | 'move' | 'merge'
+    page_id TEXT NOT NULL,
+    inverse TEXT NOT NULL,             -- JSON: { "restore": [...], "remove": [...] }
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
+);
+
+-- 최근 검색어 (같은 검색어는 한 행만 유지하고 시각/결과 수를 갱신)
+CREATE TABLE IF NOT EXISTS search_history (
+    query TEXT PRIMARY KEY,
+    result_count INTEGER NOT NULL,
+    searched_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_history_searched_at ON search_history(searched_at);
 "#;
 
 /// Initialize the database schema
@@ -162,6 +261,178 @@ pub fn init_schema(conn: &rusqlite::Connection) -> Result<(), rusqlite::Error> {
         conn.execute("DROP TABLE IF EXISTS blocks_fts", [])?;
     }
 
+    let has_tag_index = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE name = 'block_tags' AND type = 'table'",
+            [],
+            |_| Ok(()),
+        )
+        .is_ok();
+
+    let has_schedule_index = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE name = 'block_schedule' AND type = 'table'",
+            [],
+            |_| Ok(()),
+        )
+        .is_ok();
+
+    // page_paths gained lookup key columns; add them before SCHEMA_SQL indexes them
+    let missing_path_keys = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE name = 'page_paths' AND type = 'table'",
+            [],
+            |row| Ok(!row.get::<_, String>(0)?.contains("nfc_basename")),
+        )
+        .unwrap_or(false);
+    if missing_path_keys {
+        conn.execute_batch(
+            "ALTER TABLE page_paths ADD COLUMN nfc_basename TEXT;
+             ALTER TABLE page_paths ADD COLUMN folded_basename TEXT;",
+        )?;
+    }
+
+    // blocks gained string order keys; add the column before SCHEMA_SQL indexes it
+    let missing_order_keys = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE name = 'blocks' AND type = 'table'",
+            [],
+            |row| Ok(!row.get::<_, String>(0)?.contains("order_key")),
+        )
+        .unwrap_or(false);
+    if missing_order_keys {
+        conn.execute("ALTER TABLE blocks ADD COLUMN order_key TEXT", [])?;
+    }
+
     conn.execute_batch(SCHEMA_SQL)?;
+
+    // Clearing the keys makes blocks_order_key_update derive each one from its
+    // REAL weight, so existing sibling order carries over unchanged
+    if missing_order_keys {
+        conn.execute("UPDATE blocks SET order_key = NULL", [])?;
+    }
+
+    if missing_path_keys {
+        crate::services::page_path_service::backfill_path_keys(conn)?;
+    }
+
+    // Databases created before the tag index existed: fill it once from block content
+    if !has_tag_index {
+        let tx = conn.unchecked_transaction()?;
+        crate::services::wiki_link_index::reindex_all_tags(&tx)?;
+        tx.commit()?;
+    }
+
+    // Scheduled dates used to be stored as `scheduledDate` block metadata, which
+    // leaked into page files; move them to the derived index
+    if !has_schedule_index {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM block_metadata WHERE key = 'scheduledDate'", [])?;
+        crate::services::wiki_link_index::reindex_all_schedules(&tx)?;
+        tx.commit()?;
+    }
+
+    if !ensure_unique_page_paths(conn)? {
+        eprintln!(
+            "[init_schema] Duplicate page file paths found; run dedupe_pages_by_path to repair"
+        );
+    }
     Ok(())
 }
+
+/// Enforce at most one live page per file path with a partial unique index.
+/// Returns false, leaving the index absent, while duplicate rows still exist
+/// (databases written before the index existed); deduplicating and calling this
+/// again installs it.
+pub fn ensure_unique_page_paths(conn: &rusqlite::Connection) -> Result<bool, rusqlite::Error> {
+    let result = conn.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_pages_file_path_unique
+         ON pages(file_path) WHERE file_path IS NOT NULL AND is_deleted = 0",
+    );
+
+    match result {
+        Ok(()) => Ok(true),
+        Err(rusqlite::Error::SqliteFailure(e, _))
+            if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_order_key_migration_preserves_sibling_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        // A blocks table from before order keys existed
+        conn.execute_batch(
+            "CREATE TABLE blocks (
+                id TEXT PRIMARY KEY,
+                page_id TEXT NOT NULL,
+                parent_id TEXT,
+                content TEXT NOT NULL DEFAULT '',
+                order_weight REAL NOT NULL,
+                is_collapsed INTEGER DEFAULT 0,
+                block_type TEXT DEFAULT 'bullet',
+                language TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            INSERT INTO blocks (id, page_id, order_weight) VALUES
+                ('zero', 'p', 0.0), ('tiny', 'p', 0.000001), ('half', 'p', 0.5),
+                ('one', 'p', 1.0), ('between', 'p', 1.25), ('two', 'p', 2.0),
+                ('ten', 'p', 10.0), ('huge', 'p', 123456789.5);",
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+
+        let ids: Vec<String> = conn
+            .prepare("SELECT id FROM blocks ORDER BY order_key")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            ids,
+            vec!["zero", "tiny", "half", "one", "between", "two", "ten", "huge"]
+        );
+
+        let keys: Vec<String> = conn
+            .prepare("SELECT order_key FROM blocks")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(keys
+            .iter()
+            .all(|key| crate::utils::fractional_index::is_valid_key(key)));
+
+        // Rows written without a key get one derived the same way
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, order_weight) VALUES ('late', 'p', 1.5)",
+            [],
+        )
+        .unwrap();
+        let late: String = conn
+            .query_row("SELECT order_key FROM blocks WHERE id = 'late'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let between: String = conn
+            .query_row(
+                "SELECT order_key FROM blocks WHERE id = 'between'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(between < late);
+    }
+}
@@ -61,11 +61,20 @@ pub enum OxinotError {
     #[error("Invalid state: {0}")]
     InvalidState(String),
 
+    #[error("Validation error: {0}")]
+    Validation(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Database corruption detected: {0}")]
+    Corruption(String),
 }
 
 impl OxinotError {
@@ -109,6 +118,11 @@ impl OxinotError {
         OxinotError::InvalidState(msg.into())
     }
 
+    /// Create a validation error for rejected user input.
+    pub fn validation<S: Into<String>>(msg: S) -> Self {
+        OxinotError::Validation(msg.into())
+    }
+
     /// Create a configuration error.
     pub fn config<S: Into<String>>(msg: S) -> Self {
         OxinotError::Config(msg.into())
@@ -118,6 +132,21 @@ impl OxinotError {
     pub fn internal<S: Into<String>>(msg: S) -> Self {
         OxinotError::Internal(msg.into())
     }
+
+    /// Create an error for an operation stopped at the user's request.
+    pub fn cancelled<S: Into<String>>(msg: S) -> Self {
+        OxinotError::Cancelled(msg.into())
+    }
+
+    /// Create an error for integrity problems left for an explicit repair.
+    pub fn corruption<S: Into<String>>(msg: S) -> Self {
+        OxinotError::Corruption(msg.into())
+    }
+
+    /// Whether a command error message came from a `Corruption` error.
+    pub fn is_corruption(message: &str) -> bool {
+        message.starts_with("Database corruption detected:")
+    }
 }
 
 /// Result type alias for Oxinot operations.
@@ -164,6 +193,19 @@ mod tests {
         assert!(err.to_string().contains("not under workspace root"));
     }
 
+    #[test]
+    fn test_corruption_error_display() {
+        let err = OxinotError::corruption("FOREIGN KEY constraint failed");
+        assert_eq!(
+            err.to_string(),
+            "Database corruption detected: FOREIGN KEY constraint failed"
+        );
+        assert!(OxinotError::is_corruption(&err.to_string()));
+        assert!(!OxinotError::is_corruption(
+            &OxinotError::database("FOREIGN KEY constraint failed").to_string()
+        ));
+    }
+
     #[test]
     fn test_io_error_conversion() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file not found");
@@ -11,7 +11,10 @@ pub mod models;
 pub mod services;
 pub mod utils;
 
-use utils::path::{validate_filename, validate_no_path_traversal, validate_workspace_containment};
+use utils::path::{
+    ensure_rename_target_free, validate_filename, validate_no_path_traversal,
+    validate_workspace_containment,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileSystemItem {
@@ -123,7 +126,7 @@ async fn write_file(file_path: String, content: String) -> Result<bool, String>
     // Validate input - reject absolute paths and path traversal
     validate_no_path_traversal(&file_path, "file_path")?;
 
-    tokio_fs::write(&file_path, content)
+    utils::page_sync::write_file_atomic(Path::new(&file_path), &content)
         .await
         .map_err(|e| format!("Error writing file: {}", e))?;
     Ok(true)
@@ -326,6 +329,7 @@ async fn rename_path(old_path: String, new_name: String) -> Result<String, Strin
         .parent()
         .ok_or_else(|| "Cannot get parent directory".to_string())?;
     let new_path = parent.join(&new_name);
+    ensure_rename_target_free(old, &new_path)?;
 
     tokio_fs::rename(old, &new_path)
         .await
@@ -334,6 +338,192 @@ async fn rename_path(old_path: String, new_name: String) -> Result<String, Strin
     Ok(new_path.to_string_lossy().to_string())
 }
 
+/// Rename a page file or page directory from the file tree and update the DB to match:
+/// the page's path and title, links pointing at it (and, for a directory, at the pages
+/// inside it), and the pages whose links were rewritten. A directory's folder note is
+/// renamed along with it. `old_path` may be absolute or relative to the workspace;
+/// returns the new path in the same form.
+#[tauri::command]
+async fn rename_path_with_db(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    old_path: String,
+    new_name: String,
+) -> Result<String, String> {
+    validate_no_path_traversal(&workspace_path, "workspace_path")?;
+    let old_rel = workspace_relative(&workspace_path, &old_path);
+    validate_workspace_containment(&workspace_path, &old_rel)?;
+    validate_filename(&new_name)?;
+
+    let old = Path::new(&workspace_path).join(&old_rel);
+    let new_path = old
+        .parent()
+        .ok_or_else(|| "Cannot get parent directory".to_string())?
+        .join(&new_name);
+    ensure_rename_target_free(&old, &new_path)?;
+    let is_directory = old.is_dir();
+
+    let mut renames = vec![(old.clone(), new_path.clone())];
+    if is_directory {
+        // Check the folder note up front so a clash can't leave the directory renamed
+        // without it
+        let old_note_name = format!("{}.md", dir_name(&old));
+        let new_note_name = format!("{}.md", dir_name(&new_path));
+        if old.join(&old_note_name).exists() && old_note_name != new_note_name {
+            ensure_rename_target_free(&old.join(&old_note_name), &old.join(&new_note_name))?;
+            renames.push((new_path.join(&old_note_name), new_path.join(&new_note_name)));
+        }
+    }
+
+    let new_rel = match old_rel.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/{}", dir, new_name),
+        None => new_name.clone(),
+    };
+
+    apply_path_change(&app, &workspace_path, &renames, |tx| {
+        commands::page::record_path_rename(tx, &workspace_path, &old_rel, &new_rel, is_directory)
+    })
+    .await?;
+
+    let renamed = Path::new(&old_path).with_file_name(&new_name);
+    Ok(renamed.to_string_lossy().to_string())
+}
+
+/// Move a page file or page directory into `target_parent_path` and update the DB to
+/// match: the moved pages' paths, the moved page's parent, and path-qualified links to
+/// them. Moving a folder note (`Dir/Dir.md`) moves its whole directory. Paths may be
+/// absolute or relative to the workspace; returns the moved path in the same form as
+/// `target_parent_path`, pointing at the folder note when that is what was passed.
+#[tauri::command]
+async fn move_path_with_db(
+    app: tauri::AppHandle,
+    workspace_path: String,
+    source_path: String,
+    target_parent_path: String,
+) -> Result<String, String> {
+    validate_no_path_traversal(&workspace_path, "workspace_path")?;
+    let mut source_rel = workspace_relative(&workspace_path, &source_path);
+    let target_rel = workspace_relative(&workspace_path, &target_parent_path);
+    validate_workspace_containment(&workspace_path, &source_rel)?;
+    if !target_rel.is_empty() {
+        validate_workspace_containment(&workspace_path, &target_rel)?;
+    }
+
+    let workspace = Path::new(&workspace_path);
+    let mut folder_note = None;
+    if let Some((dir, file)) = source_rel.rsplit_once('/') {
+        let stem = file.strip_suffix(".md").unwrap_or(file);
+        if dir.rsplit('/').next() == Some(stem) {
+            folder_note = Some(file.to_string());
+            source_rel = dir.to_string();
+        }
+    }
+    let source = workspace.join(&source_rel);
+    let is_directory = source.is_dir();
+    let name = dir_name(&source);
+
+    let target_parent = workspace.join(&target_rel);
+    if !target_parent.is_dir() {
+        return Err("Target parent directory does not exist".to_string());
+    }
+    let new_rel = if target_rel.is_empty() {
+        name.clone()
+    } else {
+        format!("{}/{}", target_rel, name)
+    };
+    if is_directory && format!("{}/", new_rel).starts_with(&format!("{}/", source_rel)) {
+        return Err("Cannot move a directory into itself".to_string());
+    }
+    let new_path = workspace.join(&new_rel);
+    ensure_rename_target_free(&source, &new_path)?;
+
+    apply_path_change(&app, &workspace_path, &[(source, new_path)], |tx| {
+        commands::page::record_path_move(tx, &workspace_path, &source_rel, &new_rel, is_directory)
+    })
+    .await?;
+
+    let mut moved = Path::new(&target_parent_path).join(&name);
+    if let Some(file) = folder_note {
+        moved.push(file);
+    }
+    Ok(moved.to_string_lossy().to_string())
+}
+
+/// Workspace-relative, `/`-separated form of a path that may be absolute
+fn workspace_relative(workspace_path: &str, path: &str) -> String {
+    Path::new(path)
+        .strip_prefix(workspace_path)
+        .unwrap_or(Path::new(path))
+        .to_string_lossy()
+        .replace('\\', "/")
+        .trim_matches('/')
+        .to_string()
+}
+
+/// Apply a file-tree change: record it in the DB inside a transaction, perform the
+/// renames on disk, and commit only once they all succeeded, so the tree and the DB
+/// never disagree. Then re-render the pages whose links were rewritten.
+async fn apply_path_change<F>(
+    app: &tauri::AppHandle,
+    workspace_path: &str,
+    renames: &[(PathBuf, PathBuf)],
+    record: F,
+) -> Result<(), String>
+where
+    F: FnOnce(&rusqlite::Transaction) -> Result<commands::page::WikiLinkRewriteResult, String>,
+{
+    let conn = commands::workspace::open_workspace_db(workspace_path)?;
+    let conn_mutex = std::sync::Mutex::new(conn);
+    let result = {
+        let mut conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let result = record(&tx)?;
+        rename_all(renames)?;
+        if let Err(e) = tx.commit() {
+            undo_renames(renames);
+            return Err(e.to_string());
+        }
+        result
+    };
+
+    for page_id in &result.touched_page_ids {
+        utils::page_sync::sync_page_to_markdown(&conn_mutex, workspace_path, page_id).await?;
+        utils::events::emit_page_changed(app, workspace_path, page_id);
+    }
+    utils::events::emit_workspace_changed(app, workspace_path);
+    Ok(())
+}
+
+/// Perform `renames` in order; on failure, undo the ones already done
+fn rename_all(renames: &[(PathBuf, PathBuf)]) -> Result<(), String> {
+    for (done, (from, to)) in renames.iter().enumerate() {
+        if let Err(e) = std::fs::rename(from, to) {
+            undo_renames(&renames[..done]);
+            return Err(format!("Error renaming '{}': {}", from.display(), e));
+        }
+    }
+    Ok(())
+}
+
+fn undo_renames(renames: &[(PathBuf, PathBuf)]) {
+    for (from, to) in renames.iter().rev() {
+        if let Err(e) = std::fs::rename(to, from) {
+            log::error!(
+                "[apply_path_change] Failed to restore '{}' from '{}': {}",
+                from.display(),
+                to.display(),
+                e
+            );
+        }
+    }
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
 #[tauri::command]
 async fn move_path(source_path: String, target_parent_path: String) -> Result<String, String> {
     // Validate inputs - reject absolute paths and path traversal
@@ -351,6 +541,7 @@ async fn move_path(source_path: String, target_parent_path: String) -> Result<St
     }
 
     let new_path = target_parent.join(file_name);
+    ensure_rename_target_free(source, &new_path)?;
 
     tokio_fs::rename(source, &new_path)
         .await
@@ -445,6 +636,12 @@ pub fn run() {
         .setup(|app| {
             // No global DB - each command will open workspace-specific DB as needed
 
+            // Reindexes files edited outside the app once a workspace is synced
+            services::file_watcher::start(app.handle().clone());
+
+            // Autosave commits for workspaces that enable auto_commit_interval_secs
+            services::auto_commit::start();
+
             if cfg!(debug_assertions) {
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
@@ -475,6 +672,8 @@ pub fn run() {
             delete_path,
             delete_path_with_db,
             rename_path,
+            rename_path_with_db,
+            move_path_with_db,
             move_path,
             convert_file_to_directory,
             get_path_info,
@@ -484,30 +683,70 @@ pub fn run() {
             commands::block::get_page_blocks_metadata,
             commands::block::get_page_blocks_root,
             commands::block::get_page_blocks_children,
+            commands::block::get_page_outline,
             commands::block::get_page_blocks_complete,
             commands::block::create_block,
             commands::block::create_blocks_batch,
             commands::block::update_block,
+            commands::block::replace_in_block,
             commands::block::delete_block,
             commands::block::move_block,
             commands::block::indent_block,
             commands::block::outdent_block,
             commands::block::toggle_collapse,
             commands::block::merge_blocks,
+            commands::block::undo_last_operation,
+            commands::block::swap_blocks,
+            commands::block::reorder_siblings,
+            commands::block::merge_into_parent,
+            commands::block::group_blocks_under_new_parent,
+            commands::block::set_sync_strategy_debug,
+            commands::block::debug_order_weights,
+            commands::block::preview_insert_position,
             // Block search/navigation commands
             commands::block::search_blocks,
             commands::block::resolve_block_path,
+            commands::block::resolve_block_alias,
+            commands::block::get_alias_conflicts,
+            commands::block::find_broken_block_embeds,
             commands::block::get_block,
             commands::block::get_blocks,
             commands::block::get_block_ancestors,
             commands::block::get_block_subtree,
+            commands::block::copy_block_subtree_to_clipboard_markdown,
+            commands::block::parse_markdown_preview,
+            commands::block::detect_code_language,
+            commands::block::import_csv_as_blocks,
+            commands::block::paste_markdown_as_blocks,
+            commands::block::validate_block_metadata,
             // Page commands
             commands::page::get_pages,
+            commands::page::get_pages_by_metadata,
+            commands::page::get_recent_pages,
+            commands::page::quick_switch_search,
             commands::page::create_page,
             commands::page::update_page_title,
             commands::page::delete_page,
+            commands::page::merge_pages,
+            commands::page::duplicate_page,
+            commands::page::list_soft_deleted_pages,
+            commands::page::restore_soft_deleted_page,
+            commands::page::list_templates,
+            commands::page::create_page_from_template,
+            commands::page::get_or_create_daily_note,
             commands::page::get_page,
+            commands::page::export_page_mermaid,
+            commands::page::export_page_to_html,
+            commands::page::export_page_to_plain_markdown,
+            commands::page::get_page_stats,
+            commands::page::get_workspace_page_stats,
+            commands::page::normalize_block_marker_layout,
+            commands::page::replace_page_content,
+            commands::page::reconcile_title_filename,
+            commands::page::reconcile_all,
+            commands::page::rewrite_wiki_links_for_page_path_change,
             commands::page::get_page_tree,
+            commands::page::get_page_tree_children,
             commands::page::convert_page_to_directory,
             commands::page::move_page,
             commands::page::convert_directory_to_file,
@@ -515,19 +754,54 @@ pub fn run() {
             // Workspace commands
             commands::workspace::initialize_workspace,
             commands::workspace::sync_workspace,
+            commands::workspace::sync_workspace_dry_run,
+            commands::workspace::set_auto_commit,
+            commands::workspace::set_auto_repair_on_read,
+            commands::workspace::set_file_naming,
+            commands::workspace::detect_external_changes,
             commands::workspace::sync_workspace_incremental,
             commands::workspace::reindex_workspace,
+            commands::workspace::cancel_reindex,
+            commands::workspace::get_last_sync_changed_blocks,
+            commands::workspace::set_sanitization_rules,
+            commands::workspace::set_trailing_newline_policy,
+            commands::workspace::set_indent_style,
+            commands::workspace::normalize_trailing_newlines,
             // DB maintenance commands
             commands::db::vacuum_db,
             commands::db::optimize_db,
             commands::db::repair_db,
+            commands::db::validate_workspace_integrity,
             commands::db::get_fts_stats,
             commands::db::rebuild_fts_index,
             commands::db::verify_fts_index,
             commands::db::optimize_fts_index,
             commands::db::rebuild_page_fts_index,
+            commands::db::validate_metadata_json,
+            commands::db::fix_metadata_json,
+            commands::db::get_page_size_divergence,
+            commands::db::trim_trailing_empty_blocks,
+            commands::db::enable_auto_optimize,
+            commands::db::disable_auto_optimize,
+            commands::db::dedupe_pages_by_path,
+            commands::db::find_phantom_blocks,
+            commands::db::fix_phantom_blocks,
+            commands::db::run_readonly_query,
             // Search commands
             commands::search::search_content,
+            commands::search::get_tag_view,
+            commands::search::search_with_context,
+            commands::search::get_all_tags,
+            commands::search::get_blocks_by_tag,
+            commands::search::search_by_metadata,
+            commands::search::record_search_history,
+            commands::search::get_search_history,
+            commands::search::clear_search_history,
+            // Journal commands
+            commands::journal::get_journal_entries,
+            // Stats commands
+            commands::stats::get_word_frequency,
+            commands::stats::export_metadata_index,
             // Git commands
             commands::git::git_init,
             commands::git::git_is_repo,
@@ -536,21 +810,46 @@ pub fn run() {
             commands::git::git_push,
             commands::git::git_pull,
             commands::git::git_log,
+            commands::git::git_diff,
+            commands::git::git_stash,
+            commands::git::git_stash_list,
+            commands::git::git_stash_pop,
             commands::git::git_get_remote_url,
             commands::git::git_set_remote_url,
             commands::git::git_remove_remote,
             commands::workspace::close_workspace,
             commands::workspace::reveal_in_finder,
             commands::wiki_link::get_page_backlinks,
+            commands::wiki_link::get_page_outbound_links,
+            commands::wiki_link::get_block_references,
+            commands::wiki_link::get_page_backlinks_with_context,
             commands::wiki_link::get_broken_links,
+            commands::wiki_link::get_orphan_pages,
             commands::wiki_link::reindex_wiki_links,
+            commands::wiki_link::resolve_page_link,
+            commands::wiki_link::get_block_resolved,
+            commands::wiki_link::get_embedded_blocks,
+            // Embed commands
+            commands::embed::resolve_embed,
+            commands::embed::resolve_embeds_batch,
+            commands::embed::clear_embed_cache,
             // Graph commands
             commands::graph::get_graph_data,
             commands::graph::get_page_graph_data,
+            commands::graph::get_local_graph,
             // Query commands
             commands::query::execute_query_macro,
+            commands::live_query::register_live_query,
+            commands::live_query::unregister_live_query,
             // TODO commands
             commands::todo::query_todos,
+            commands::todo::get_task_agenda,
+            commands::todo::get_scheduled_blocks,
+            commands::todo::set_block_schedule,
+            // Trash commands
+            commands::trash::list_trash,
+            commands::trash::restore_from_trash,
+            commands::trash::empty_trash,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
@@ -9,6 +9,9 @@ pub struct Block {
     pub parent_id: Option<String>,
     pub content: String,
     pub order_weight: f64,
+    /// String fractional index that orders siblings; empty for blocks not stored yet
+    #[serde(default)]
+    pub order_key: String,
     pub is_collapsed: bool,
     pub block_type: BlockType,
     pub language: Option<String>,
@@ -18,6 +21,18 @@ pub struct Block {
     pub metadata: HashMap<String, String>,
 }
 
+impl Block {
+    /// Sibling order: by order key, or by weight when either block has no key yet
+    /// (blocks parsed from markdown before they are stored)
+    pub fn cmp_position(&self, other: &Block) -> std::cmp::Ordering {
+        if self.order_key.is_empty() || other.order_key.is_empty() {
+            self.order_weight.total_cmp(&other.order_weight)
+        } else {
+            self.order_key.cmp(&other.order_key)
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BlockType {
     #[serde(rename = "bullet")]
@@ -30,6 +45,9 @@ pub enum BlockType {
     AiPrompt,
     #[serde(rename = "ai-response")]
     AiResponse,
+    /// Markdown heading; the level (1-6) is stored in `language`
+    #[serde(rename = "heading")]
+    Heading,
 }
 
 impl Default for BlockType {
@@ -48,6 +66,7 @@ pub fn string_to_block_type(s: &str) -> BlockType {
         "fence" => BlockType::Fence,
         "ai-prompt" => BlockType::AiPrompt,
         "ai-response" => BlockType::AiResponse,
+        "heading" => BlockType::Heading,
         _ => BlockType::Bullet,
     }
 }
@@ -71,6 +90,8 @@ pub struct UpdateBlockRequest {
     pub block_type: Option<BlockType>,
     pub language: Option<String>,
     pub metadata: Option<HashMap<String, String>>,
+    /// Set a task bullet's checkbox state without touching its content
+    pub checked: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -7,6 +7,9 @@ pub struct GraphNode {
     pub node_type: String, // "page" or "block"
     pub page_id: String,
     pub block_id: Option<String>,
+    /// Set when clustering was requested; nodes sharing an id form one cluster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster_id: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +18,10 @@ pub struct GraphEdge {
     pub target: String,
     pub relation_type: String,
     pub is_embed: bool,
+    /// Number of distinct blocks linking from `source` to `target`
+    pub weight: usize,
+    /// Whether `target` also links back to `source`
+    pub is_bidirectional: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,3 +29,22 @@ pub struct GraphData {
     pub nodes: Vec<GraphNode>,
     pub edges: Vec<GraphEdge>,
 }
+
+/// Neighborhood of a focus page returned by `get_local_graph`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalGraphData {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// True when the node cap cut off pages within the requested depth
+    pub truncated: bool,
+}
+
+/// How `get_graph_data` groups nodes into clusters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterMode {
+    /// Pages reachable from each other through links, in either direction
+    Components,
+    /// Densely linked groups found by label propagation
+    LabelPropagation,
+}
@@ -21,6 +21,49 @@ pub struct QueryFilter {
     pub depth: Option<DepthRange>,
     pub limit: Option<u32>,
     pub sort: Option<SortType>,
+    /// Reduce the matching blocks to a number or a grouped table
+    pub aggregate: Option<Aggregate>,
+}
+
+/// `count`, `sum:key` or `avg:key`, optionally per `group:key` value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AggregateFunction {
+    /// Number of blocks; with a key, only blocks that have that metadata
+    Count(Option<String>),
+    /// Sum of a metadata key's numeric values
+    Sum(String),
+    /// Mean of a metadata key's numeric values
+    Avg(String),
+}
+
+/// Output of an aggregate query
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AggregateResult {
+    /// A single number; None for the average of no numeric values
+    Value {
+        value: Option<f64>,
+        row_count: usize,
+    },
+    /// One row per distinct `group_key` value; blocks without it form the None group
+    Table {
+        group_key: String,
+        rows: Vec<AggregateRow>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AggregateRow {
+    pub group: Option<String>,
+    pub value: Option<f64>,
+    /// Blocks that contributed to `value`
+    pub row_count: usize,
 }
 
 /// FROM clause - specifies which pages to search in
@@ -50,6 +93,29 @@ pub enum SortType {
     Numeric123,
     /// By creation date descending (newest first)
     Numeric321,
+    /// `sort:key [asc|desc]`: by a block timestamp or a metadata value
+    By { key: SortKey, descending: bool },
+}
+
+/// What a `sort:key` clause orders by
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SortKey {
+    CreatedAt,
+    UpdatedAt,
+    /// A `key::value` metadata entry; numeric values compare as numbers
+    Metadata(String),
+}
+
+impl SortKey {
+    /// `created_at` and `updated_at` (case-insensitive) are the block timestamps;
+    /// any other name is a metadata key
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "created_at" => SortKey::CreatedAt,
+            "updated_at" => SortKey::UpdatedAt,
+            _ => SortKey::Metadata(name.to_string()),
+        }
+    }
 }
 
 impl SortType {
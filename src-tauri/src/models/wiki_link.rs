@@ -1,3 +1,4 @@
+use crate::services::page_path_service::PathMatchType;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,3 +29,102 @@ pub struct BacklinkGroup {
     pub page_title: String,
     pub blocks: Vec<BacklinkBlock>,
 }
+
+/// A block referencing a page, with enough surrounding text for a preview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklinkContext {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub content: String,
+    pub parent_id: Option<String>,
+    /// None for a root-level block
+    pub parent_content: Option<String>,
+    /// Page path + block path, as in `search_blocks`
+    pub full_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedLink {
+    pub raw_target: String,
+    pub target_path: String,
+    pub alias: Option<String>,
+    pub heading: Option<String>,
+    pub block_ref: Option<String>,
+    pub is_embed: bool,
+    pub link_type: String,
+    /// None when the target does not resolve to a page
+    pub to_page_id: Option<String>,
+    /// Workspace-relative file of the target page
+    pub to_file_path: Option<String>,
+}
+
+/// A distinct target linked from a page, with how many links point at it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboundLink {
+    /// None for a broken target
+    pub to_page_id: Option<String>,
+    pub page_title: Option<String>,
+    pub file_path: Option<String>,
+    /// Normalized target as written in the links; when they spell it differently, the
+    /// lexicographically smallest spelling
+    pub target_path: String,
+    pub count: usize,
+}
+
+/// A block mentioning a page, with the page the block lives on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockMention {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_title: String,
+    pub content: String,
+}
+
+/// Blocks referring to a page: through a wiki link, or by its title in plain text
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockReferences {
+    pub linked: Vec<BlockMention>,
+    pub unlinked: Vec<BlockMention>,
+}
+
+/// Everything a page links to, split into resolved pages and broken targets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageOutboundLinks {
+    pub resolved: Vec<OutboundLink>,
+    pub broken: Vec<OutboundLink>,
+}
+
+/// A link target resolved to a page, and how loosely it had to be matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedPageLink {
+    pub page_id: String,
+    pub match_type: PathMatchType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedBlock {
+    pub block_id: String,
+    pub page_id: String,
+    /// Raw block content, unchanged
+    pub content: String,
+    pub links: Vec<ResolvedLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockEmbedder {
+    pub block_id: String,
+    pub page_id: String,
+    pub page_title: String,
+    /// `block_ref` for `((uuid))`, `embed` for `![[page#^anchor]]`
+    pub embed_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedBlock {
+    pub block_id: String,
+    pub page_id: String,
+    pub content: String,
+    pub embedder_count: usize,
+    pub embedders: Vec<BlockEmbedder>,
+}
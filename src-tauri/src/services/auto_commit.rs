@@ -0,0 +1,179 @@
+//! Background task that periodically commits the open workspace's changes to git.
+//!
+//! `start` (called from `run()`'s setup) spawns one thread for the app's lifetime.
+//! Every tick it looks up the workspace open in the app and its
+//! `auto_commit_interval_secs` setting; once that interval has passed since the last
+//! autosave it commits everything with an "oxinot autosave <timestamp>" message.
+//! Nothing is committed while the working tree is clean or a merge is unresolved, and
+//! the commit is only pushed when `auto_commit_push` is also set.
+
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::commands::git::{git_commit, git_push, git_status, has_unresolved_merge};
+use crate::commands::workspace::{current_workspace_path, load_auto_commit_settings};
+
+/// How often the task wakes up to check whether an autosave is due
+const AUTO_COMMIT_TICK: Duration = Duration::from_secs(15);
+
+static AUTO_COMMIT_STARTED: OnceLock<()> = OnceLock::new();
+
+/// Start the autosave thread. Calling it again has no effect.
+pub fn start() {
+    if AUTO_COMMIT_STARTED.set(()).is_ok() {
+        std::thread::spawn(run_auto_commit_loop);
+    }
+}
+
+fn run_auto_commit_loop() {
+    // Workspace and time of its last autosave; the interval restarts when the
+    // open workspace changes
+    let mut last_run: Option<(String, Instant)> = None;
+
+    loop {
+        std::thread::sleep(AUTO_COMMIT_TICK);
+
+        let Some(workspace_path) = current_workspace_path() else {
+            continue;
+        };
+        let (Some(interval_secs), push) = load_auto_commit_settings(&workspace_path) else {
+            continue;
+        };
+
+        let due = match &last_run {
+            Some((path, at)) if *path == workspace_path => {
+                at.elapsed() >= Duration::from_secs(interval_secs)
+            }
+            _ => {
+                last_run = Some((workspace_path.clone(), Instant::now()));
+                false
+            }
+        };
+        if !due {
+            continue;
+        }
+
+        last_run = Some((workspace_path.clone(), Instant::now()));
+        match autosave(&workspace_path, push) {
+            Ok(Some(hash)) => println!("[auto_commit] Autosaved {} as {}", workspace_path, hash),
+            Ok(None) => {}
+            Err(e) => eprintln!("[auto_commit] Autosave of {} failed: {}", workspace_path, e),
+        }
+    }
+}
+
+/// Commit all changes in `workspace_path`, pushing afterwards if `push` is set.
+/// Returns the new commit hash, or None when the workspace is not a git repository,
+/// has nothing to commit, or is in the middle of a conflicted merge (committing then
+/// would record the conflict markers).
+pub fn autosave(workspace_path: &str, push: bool) -> Result<Option<String>, String> {
+    tauri::async_runtime::block_on(async {
+        let status = git_status(workspace_path.to_string()).await?;
+        if !status.is_repo || !status.has_changes {
+            return Ok(None);
+        }
+        if has_unresolved_merge(Path::new(workspace_path)).await? {
+            return Ok(None);
+        }
+
+        let message = format!(
+            "oxinot autosave {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        let result = git_commit(workspace_path.to_string(), message).await?;
+        if !result.success {
+            return Err(result.message);
+        }
+
+        if push && result.commit_hash.is_some() {
+            git_push(workspace_path.to_string()).await?;
+        }
+        Ok(result.commit_hash)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use uuid::Uuid;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("failed to run git");
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_autosave_commits_only_when_there_are_changes() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_autosave_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let workspace = temp_dir.to_string_lossy().to_string();
+
+        // Not a repository yet: nothing to do
+        assert_eq!(autosave(&workspace, false).unwrap(), None);
+
+        git(&temp_dir, &["init", "-q"]);
+        git(&temp_dir, &["config", "user.name", "Tester"]);
+        git(&temp_dir, &["config", "user.email", "tester@example.com"]);
+        std::fs::write(temp_dir.join("Notes.md"), "- first\n").unwrap();
+
+        let hash = autosave(&workspace, false)
+            .unwrap()
+            .expect("changes are committed");
+        assert_eq!(git(&temp_dir, &["rev-parse", "HEAD"]), hash);
+        assert!(git(&temp_dir, &["log", "-1", "--format=%s"]).starts_with("oxinot autosave "));
+
+        // Clean tree: no empty autosave commit
+        assert_eq!(autosave(&workspace, false).unwrap(), None);
+        assert_eq!(git(&temp_dir, &["rev-list", "--count", "HEAD"]), "1");
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+
+    #[test]
+    fn test_autosave_skips_conflicted_merge() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("oxinot_test_autosave_{}", Uuid::new_v4()));
+        let origin = temp_dir.join("origin");
+        let clone = temp_dir.join("clone");
+        std::fs::create_dir_all(&origin).unwrap();
+        let commit = |dir: &Path, content: &str| {
+            std::fs::write(dir.join("A.md"), content).unwrap();
+            git(dir, &["add", "-A"]);
+            git(dir, &["commit", "-q", "-m", content]);
+        };
+
+        git(&origin, &["init", "-q"]);
+        git(&origin, &["config", "user.name", "Alice"]);
+        git(&origin, &["config", "user.email", "alice@example.com"]);
+        commit(&origin, "- one\n");
+        git(&temp_dir, &["clone", "-q", "origin", "clone"]);
+        git(&clone, &["config", "user.name", "Bob"]);
+        git(&clone, &["config", "user.email", "bob@example.com"]);
+        commit(&origin, "- from origin\n");
+        commit(&clone, "- from clone\n");
+
+        let pulled = Command::new("git")
+            .args(["pull", "-q", "--no-rebase"])
+            .current_dir(&clone)
+            .output()
+            .unwrap();
+        assert!(!pulled.status.success());
+        let head = git(&clone, &["rev-parse", "HEAD"]);
+
+        let workspace = clone.to_string_lossy().to_string();
+        assert_eq!(autosave(&workspace, true).unwrap(), None);
+        assert_eq!(git(&clone, &["rev-parse", "HEAD"]), head);
+        let content = std::fs::read_to_string(clone.join("A.md")).unwrap();
+        assert!(content.contains("<<<<<<<"));
+
+        std::fs::remove_dir_all(&temp_dir).ok();
+    }
+}
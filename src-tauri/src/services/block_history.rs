@@ -0,0 +1,356 @@
+use crate::commands::block::{deindex_block_fts, index_block_fts};
+use crate::services::wiki_link_index;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// History entries kept per workspace; older ones are dropped as new ones are recorded
+pub const MAX_HISTORY_ENTRIES: i64 = 200;
+
+/// A block row as it was before an operation, with its metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockSnapshot {
+    pub id: String,
+    pub page_id: String,
+    pub parent_id: Option<String>,
+    pub content: String,
+    pub order_weight: f64,
+    /// Absent in entries journaled before string order keys; restored rows then
+    /// derive their key from `order_weight`
+    #[serde(default)]
+    pub order_key: Option<String>,
+    pub is_collapsed: bool,
+    /// Stored `block_type` string, kept verbatim
+    pub block_type: String,
+    pub language: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub metadata: HashMap<String, String>,
+}
+
+/// The inverse of a recorded operation: blocks to put back as they were (parents
+/// before children) and blocks the operation created, to remove again
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InverseOperation {
+    pub restore: Vec<BlockSnapshot>,
+    pub remove: Vec<String>,
+}
+
+/// What `undo_last` reverted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+    pub operation: String,
+    pub page_id: String,
+    pub restored_block_ids: Vec<String>,
+    pub removed_block_ids: Vec<String>,
+}
+
+/// Snapshot the given blocks, in the order given. Unknown IDs are skipped.
+pub fn snapshot_blocks(
+    conn: &Connection,
+    block_ids: &[&str],
+) -> Result<Vec<BlockSnapshot>, String> {
+    let mut snapshots = Vec::with_capacity(block_ids.len());
+
+    for block_id in block_ids {
+        let snapshot = conn
+            .query_row(
+                "SELECT id, page_id, parent_id, content, order_weight, is_collapsed,
+                        COALESCE(block_type, 'bullet'), language, created_at, updated_at,
+                        order_key
+                 FROM blocks WHERE id = ?",
+                [block_id],
+                |row| {
+                    Ok(BlockSnapshot {
+                        id: row.get(0)?,
+                        page_id: row.get(1)?,
+                        parent_id: row.get(2)?,
+                        content: row.get(3)?,
+                        order_weight: row.get(4)?,
+                        order_key: row.get(10)?,
+                        is_collapsed: row.get::<_, Option<i32>>(5)?.unwrap_or(0) != 0,
+                        block_type: row.get(6)?,
+                        language: row.get(7)?,
+                        created_at: row.get(8)?,
+                        updated_at: row.get(9)?,
+                        metadata: HashMap::new(),
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        let Some(mut snapshot) = snapshot else {
+            continue;
+        };
+
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM block_metadata WHERE block_id = ?")
+            .map_err(|e| e.to_string())?;
+        snapshot.metadata = stmt
+            .query_map([block_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<HashMap<String, String>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        snapshots.push(snapshot);
+    }
+
+    Ok(snapshots)
+}
+
+/// Append an operation's inverse to the journal, then drop entries beyond
+/// `MAX_HISTORY_ENTRIES`, oldest first
+pub fn record_operation(
+    conn: &Connection,
+    operation: &str,
+    page_id: &str,
+    inverse: &InverseOperation,
+) -> Result<(), String> {
+    let inverse_json = serde_json::to_string(inverse).map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO block_history (operation, page_id, inverse) VALUES (?, ?, ?)",
+        params![operation, page_id, inverse_json],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "DELETE FROM block_history
+         WHERE id NOT IN (SELECT id FROM block_history ORDER BY id DESC LIMIT ?)",
+        [MAX_HISTORY_ENTRIES],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Drop the journal entries of a page. Commands that change a page's blocks without
+/// recording an operation (reordering, merging pages, file imports, repairs) call this,
+/// since undoing an older entry would restore its snapshots over their changes.
+pub fn clear_page_history(conn: &Connection, page_id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM block_history WHERE page_id = ?", [page_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Drop every journal entry, for changes that are not scoped to known pages
+pub fn clear_history(conn: &Connection) -> Result<(), String> {
+    conn.execute("DELETE FROM block_history", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Revert the most recent journal entry and remove it from the journal, in one
+/// transaction. Errors when the journal is empty.
+pub fn undo_last(conn: &mut Connection) -> Result<UndoResult, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let entry: Option<(i64, String, String, String)> = tx
+        .query_row(
+            "SELECT id, operation, page_id, inverse FROM block_history ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let Some((entry_id, operation, page_id, inverse_json)) = entry else {
+        return Err("Nothing to undo: the block history is empty".to_string());
+    };
+
+    let inverse: InverseOperation = serde_json::from_str(&inverse_json)
+        .map_err(|e| format!("Corrupt block history entry {}: {}", entry_id, e))?;
+
+    for block_id in &inverse.remove {
+        tx.execute("DELETE FROM blocks WHERE id = ?", [block_id])
+            .map_err(|e| e.to_string())?;
+        deindex_block_fts(&tx, block_id)?;
+    }
+
+    for snapshot in &inverse.restore {
+        tx.execute(
+            "INSERT INTO blocks (id, page_id, parent_id, content, order_weight,
+                                 is_collapsed, block_type, language, created_at, updated_at,
+                                 order_key)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET
+                page_id = excluded.page_id,
+                parent_id = excluded.parent_id,
+                content = excluded.content,
+                order_weight = excluded.order_weight,
+                order_key = excluded.order_key,
+                is_collapsed = excluded.is_collapsed,
+                block_type = excluded.block_type,
+                language = excluded.language,
+                updated_at = excluded.updated_at",
+            params![
+                &snapshot.id,
+                &snapshot.page_id,
+                &snapshot.parent_id,
+                &snapshot.content,
+                snapshot.order_weight,
+                snapshot.is_collapsed as i32,
+                &snapshot.block_type,
+                &snapshot.language,
+                &snapshot.created_at,
+                &snapshot.updated_at,
+                &snapshot.order_key,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        tx.execute(
+            "DELETE FROM block_metadata WHERE block_id = ?",
+            [&snapshot.id],
+        )
+        .map_err(|e| e.to_string())?;
+        for (key, value) in &snapshot.metadata {
+            tx.execute(
+                "INSERT INTO block_metadata (id, block_id, key, value) VALUES (?, ?, ?, ?)",
+                params![Uuid::new_v4().to_string(), &snapshot.id, key, value],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        index_block_fts(&tx, &snapshot.id, &snapshot.page_id, &snapshot.content)?;
+        wiki_link_index::index_block_links(&tx, &snapshot.id, &snapshot.content, &snapshot.page_id)
+            .map_err(|e| e.to_string())?;
+    }
+
+    tx.execute("DELETE FROM block_history WHERE id = ?", [entry_id])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(UndoResult {
+        operation,
+        page_id,
+        restored_block_ids: inverse.restore.into_iter().map(|s| s.id).collect(),
+        removed_block_ids: inverse.remove,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::init_schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, file_path) VALUES ('p', 'Page', 'Page.md');
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                 VALUES ('a', 'p', NULL, 'alpha', 1.0);
+             INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                 VALUES ('b', 'p', 'a', 'beta', 1.0);
+             INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m', 'a', 'status', 'todo');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn content(conn: &Connection, id: &str) -> Option<String> {
+        conn.query_row("SELECT content FROM blocks WHERE id = ?", [id], |row| {
+            row.get(0)
+        })
+        .optional()
+        .unwrap()
+    }
+
+    fn record(conn: &Connection, operation: &str, restore_ids: &[&str], remove: Vec<String>) {
+        let restore = snapshot_blocks(conn, restore_ids).unwrap();
+        record_operation(conn, operation, "p", &InverseOperation { restore, remove }).unwrap();
+    }
+
+    #[test]
+    fn test_undo_restores_update_delete_and_create() {
+        let mut conn = setup();
+
+        record(&conn, "update_block", &["a"], Vec::new());
+        conn.execute("UPDATE blocks SET content = 'changed' WHERE id = 'a'", [])
+            .unwrap();
+
+        record(&conn, "delete_block", &["a", "b"], Vec::new());
+        conn.execute("UPDATE blocks SET parent_id = NULL WHERE id = 'b'", [])
+            .unwrap();
+        conn.execute("DELETE FROM blocks WHERE id = 'a'", [])
+            .unwrap();
+
+        conn.execute(
+            "INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('c', 'p', 'new', 2.0)",
+            [],
+        )
+        .unwrap();
+        record(&conn, "create_block", &[], vec!["c".to_string()]);
+
+        let undone = undo_last(&mut conn).unwrap();
+        assert_eq!(undone.operation, "create_block");
+        assert_eq!(content(&conn, "c"), None);
+
+        let undone = undo_last(&mut conn).unwrap();
+        assert_eq!(undone.operation, "delete_block");
+        assert_eq!(content(&conn, "a").as_deref(), Some("changed"));
+        let parent: Option<String> = conn
+            .query_row("SELECT parent_id FROM blocks WHERE id = 'b'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(parent.as_deref(), Some("a"));
+        let status: String = conn
+            .query_row(
+                "SELECT value FROM block_metadata WHERE block_id = 'a' AND key = 'status'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(status, "todo");
+
+        let undone = undo_last(&mut conn).unwrap();
+        assert_eq!(undone.operation, "update_block");
+        assert_eq!(content(&conn, "a").as_deref(), Some("alpha"));
+
+        let err = undo_last(&mut conn).unwrap_err();
+        assert!(err.contains("Nothing to undo"), "{}", err);
+    }
+
+    #[test]
+    fn test_unjournaled_change_clears_page_history() {
+        let mut conn = setup();
+        conn.execute(
+            "INSERT INTO pages (id, title, file_path) VALUES ('q', 'Other', 'Other.md')",
+            [],
+        )
+        .unwrap();
+
+        record(&conn, "update_block", &["a"], Vec::new());
+        conn.execute("UPDATE blocks SET content = 'edited' WHERE id = 'a'", [])
+            .unwrap();
+        record_operation(&conn, "create_block", "q", &InverseOperation::default()).unwrap();
+
+        // e.g. a file import rewrote the block; undo must not bring back 'alpha' over it
+        conn.execute("UPDATE blocks SET content = 'from file' WHERE id = 'a'", [])
+            .unwrap();
+        clear_page_history(&conn, "p").unwrap();
+
+        let undone = undo_last(&mut conn).unwrap();
+        assert_eq!(undone.page_id, "q");
+        assert!(undo_last(&mut conn).is_err());
+        assert_eq!(content(&conn, "a").as_deref(), Some("from file"));
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let conn = setup();
+        for _ in 0..MAX_HISTORY_ENTRIES + 5 {
+            record(&conn, "update_block", &["a"], Vec::new());
+        }
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM block_history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, MAX_HISTORY_ENTRIES);
+    }
+}
@@ -1,24 +1,89 @@
 use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tokio::fs;
 
+use crate::commands::page::save_page_frontmatter;
+use crate::commands::workspace::load_file_naming;
 use crate::models::page::Page;
 use crate::services::path_validator::PathValidator;
+use crate::utils::markdown::{frontmatter_value, set_frontmatter_value};
+
+/// Frontmatter key holding the display title of a slug-named page, which its file
+/// name no longer carries
+pub(crate) const TITLE_FRONTMATTER_KEY: &str = "title";
+
+/// How file names are derived from page titles, set per workspace.
+/// The page keeps its title either way; only the name on disk differs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileNaming {
+    /// The title itself, with characters invalid in file names replaced (`My Page.md`)
+    #[default]
+    Title,
+    /// A lowercase, dash-separated slug of the title (`my-page.md`)
+    Slug,
+}
+
+impl FileNaming {
+    /// File stem for a page titled `title`, before any collision suffix
+    pub fn file_stem(&self, title: &str) -> String {
+        match self {
+            FileNaming::Title => sanitize_filename(title),
+            FileNaming::Slug => slugify(title),
+        }
+    }
+
+    /// Whether `stem` is what this naming gives `title`, allowing a `-N` collision suffix
+    /// on slugs
+    pub fn matches_stem(&self, stem: &str, title: &str) -> bool {
+        match self {
+            FileNaming::Title => stem == sanitize_filename(title),
+            FileNaming::Slug => is_slug_stem(stem, title),
+        }
+    }
+}
 
 pub struct FileSyncService {
     workspace_path: PathBuf,
     path_validator: PathValidator,
+    file_naming: FileNaming,
 }
 
 impl FileSyncService {
     pub fn new(workspace_path: impl Into<PathBuf>) -> Self {
         let workspace = workspace_path.into();
         let path_validator = PathValidator::new(workspace.clone());
+        let file_naming = load_file_naming(&workspace.to_string_lossy());
         Self {
             workspace_path: workspace,
             path_validator,
+            file_naming,
+        }
+    }
+
+    /// File stem for a new or renamed page in `dir`. Slugs of different titles can
+    /// coincide, so under `Slug` a taken name gets the first free `-2`, `-3`, ... suffix;
+    /// `current` is the page's own stem and never counts as taken. Under `Title` an
+    /// existing file is left for the caller to report.
+    fn file_stem_in(&self, dir: &Path, title: &str, current: Option<&str>) -> String {
+        let base = self.file_naming.file_stem(title);
+        if self.file_naming == FileNaming::Title {
+            return base;
+        }
+
+        let taken = |stem: &str| {
+            current != Some(stem)
+                && (dir.join(format!("{}.md", stem)).exists() || dir.join(stem).exists())
+        };
+        if !taken(&base) {
+            return base;
         }
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|stem| !taken(stem))
+            .unwrap_or(base)
     }
 
     /// Compute workspace-relative path from absolute path.
@@ -55,16 +120,17 @@ impl FileSyncService {
 
         let mut full_path = self.workspace_path.clone();
 
+        let naming = self.file_naming;
         if page.is_directory {
             for part in &path_parts {
-                full_path = full_path.join(sanitize_filename(part));
+                full_path = full_path.join(naming.file_stem(part));
             }
-            Ok(full_path.join(format!("{}.md", sanitize_filename(&page.title))))
+            Ok(full_path.join(format!("{}.md", naming.file_stem(&page.title))))
         } else {
             for part in &path_parts[..path_parts.len() - 1] {
-                full_path = full_path.join(sanitize_filename(part));
+                full_path = full_path.join(naming.file_stem(part));
             }
-            Ok(full_path.join(format!("{}.md", sanitize_filename(&page.title))))
+            Ok(full_path.join(format!("{}.md", naming.file_stem(&page.title))))
         }
     }
 
@@ -119,7 +185,7 @@ impl FileSyncService {
             self.workspace_path.clone()
         };
 
-        let file_name = format!("{}.md", sanitize_filename(title));
+        let file_name = format!("{}.md", self.file_stem_in(&parent_dir, title, None));
         let abs_file_path = parent_dir.join(file_name);
 
         if abs_file_path.exists() {
@@ -132,7 +198,11 @@ impl FileSyncService {
                 .map_err(|e| format!("Failed to create parent directory: {}", e))?;
         }
 
-        fs::write(&abs_file_path, "")
+        let initial_content = match self.file_naming {
+            FileNaming::Title => String::new(),
+            FileNaming::Slug => set_frontmatter_value("", TITLE_FRONTMATTER_KEY, title),
+        };
+        fs::write(&abs_file_path, initial_content)
             .await
             .map_err(|e| format!("Failed to create file: {}", e))?;
 
@@ -169,9 +239,15 @@ impl FileSyncService {
 
         let parent = old_abs_path.parent().ok_or("Cannot get parent directory")?;
 
+        let old_stem = old_abs_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid file name")?;
+        let new_stem = self.file_stem_in(parent, new_title, Some(old_stem));
+
         if page.is_directory {
-            let old_dir = parent.join(old_abs_path.file_stem().ok_or("Invalid file name")?);
-            let new_dir = parent.join(sanitize_filename(new_title));
+            let old_dir = parent.join(old_stem);
+            let new_dir = parent.join(&new_stem);
 
             if old_dir.exists() {
                 fs::rename(&old_dir, &new_dir)
@@ -179,7 +255,7 @@ impl FileSyncService {
                     .map_err(|e| format!("Failed to rename directory: {}", e))?;
             }
 
-            let new_file_path = new_dir.join(format!("{}.md", sanitize_filename(new_title)));
+            let new_file_path = new_dir.join(format!("{}.md", new_stem));
             if old_abs_path.exists() {
                 let old_file_in_dir =
                     new_dir.join(old_abs_path.file_name().ok_or("Invalid file name")?);
@@ -190,17 +266,45 @@ impl FileSyncService {
                 }
             }
 
+            self.write_title_frontmatter(conn_mutex, page_id, &new_file_path, new_title)
+                .await?;
             self.compute_rel_path(&new_file_path).await
         } else {
-            let new_path = parent.join(format!("{}.md", sanitize_filename(new_title)));
+            let new_path = parent.join(format!("{}.md", new_stem));
             fs::rename(&old_abs_path, &new_path)
                 .await
                 .map_err(|e| format!("Failed to rename file: {}", e))?;
 
+            self.write_title_frontmatter(conn_mutex, page_id, &new_path, new_title)
+                .await?;
             self.compute_rel_path(&new_path).await
         }
     }
 
+    /// Record a renamed page's title in its file's frontmatter when the file is
+    /// slug-named, or already carries a title from an earlier slug name, so sync can
+    /// read it back
+    async fn write_title_frontmatter(
+        &self,
+        conn_mutex: &Mutex<Connection>,
+        page_id: &str,
+        abs_path: &Path,
+        title: &str,
+    ) -> Result<(), String> {
+        let content = fs::read_to_string(abs_path).await.unwrap_or_default();
+        let has_title = frontmatter_value(&content, TITLE_FRONTMATTER_KEY).is_some();
+        if self.file_naming == FileNaming::Title && !has_title {
+            return Ok(());
+        }
+
+        let content = set_frontmatter_value(&content, TITLE_FRONTMATTER_KEY, title);
+        fs::write(abs_path, &content)
+            .await
+            .map_err(|e| format!("Failed to write title: {}", e))?;
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        save_page_frontmatter(&conn, page_id, &content)
+    }
+
     /// Move a page to a new parent
     /// Returns workspace-relative path (P0 requirement)
     pub async fn move_page_file(
@@ -239,7 +343,12 @@ impl FileSyncService {
         };
 
         let new_parent_dir = new_parent_dir.ok_or("Cannot determine new parent directory")?;
-        let new_abs_path = new_parent_dir.join(format!("{}.md", sanitize_filename(&page.title)));
+        let current_stem = old_abs_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .filter(|_| old_abs_path.parent() == Some(new_parent_dir.as_path()));
+        let new_stem = self.file_stem_in(&new_parent_dir, &page.title, current_stem);
+        let new_abs_path = new_parent_dir.join(format!("{}.md", new_stem));
 
         fs::create_dir_all(&new_parent_dir)
             .await
@@ -464,24 +573,144 @@ impl FileSyncService {
     }
 }
 
-/// Sanitize filename by removing invalid characters
-fn sanitize_filename(name: &str) -> String {
-    name.chars()
+/// Device names Windows reserves regardless of extension or case
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Sanitize filename by removing invalid characters. Trailing dots and spaces, which
+/// Windows strips or rejects, are trimmed, and reserved device names get a `_` suffix,
+/// so the same vault can be created on every OS.
+pub(crate) fn sanitize_filename(name: &str) -> String {
+    let replaced: String = name
+        .chars()
         .map(|c| match c {
             '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
             _ => c,
         })
-        .collect()
+        .collect();
+    let trimmed = replaced.trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() {
+        "untitled".to_string()
+    } else {
+        avoid_reserved_name(trimmed)
+    }
+}
+
+/// Whether `stem` is `title` with only a Windows reserved name made safe (`CON_` for
+/// "CON"), so the page can still be linked by its title
+pub(crate) fn is_reserved_name_stem(stem: &str, title: &str) -> bool {
+    stem != title && stem == avoid_reserved_name(title)
+}
+
+/// Append `_` to the part before the first dot when Windows reserves it (`CON.md` is
+/// as unusable as `CON`)
+fn avoid_reserved_name(name: &str) -> String {
+    let (base, rest) = match name.find('.') {
+        Some(dot) => name.split_at(dot),
+        None => (name, ""),
+    };
+    let reserved = WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| base.trim_end().eq_ignore_ascii_case(reserved));
+    if reserved {
+        format!("{}_{}", base, rest)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Lowercase slug of a title: letters and digits kept (any script), every other run
+/// of characters collapsed to a single `-`
+pub(crate) fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        avoid_reserved_name(slug)
+    }
+}
+
+/// Whether `stem` is the slug of `title`, possibly with a `-N` collision suffix
+pub(crate) fn is_slug_stem(stem: &str, title: &str) -> bool {
+    let slug = slugify(title);
+    stem == slug
+        || stem.strip_prefix(slug.as_str()).is_some_and(|rest| {
+            rest.strip_prefix('-')
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slugify_and_slug_stems() {
+        assert_eq!(slugify("My Page"), "my-page");
+        let slug = slugify("  Rust: Ownership & Borrowing! ");
+        assert_eq!(slug, "rust-ownership-borrowing");
+        assert_eq!(slugify("회의 노트 2024"), "회의-노트-2024");
+        assert_eq!(slugify("???"), "untitled");
+
+        assert!(is_slug_stem("my-page", "My Page"));
+        assert!(is_slug_stem("my-page-2", "My Page"));
+        assert!(!is_slug_stem("my-page-", "My Page"));
+        assert!(!is_slug_stem("my-pages", "My Page"));
+        assert!(!is_slug_stem("My Page", "My Page"));
+        assert!(FileNaming::Title.matches_stem("My Page", "My Page"));
+    }
+
+    #[test]
+    fn test_slug_names_get_numeric_suffix_on_collision() {
+        let dir = std::env::temp_dir().join(format!("oxinot_test_slug_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("my-page.md"), "").unwrap();
+        std::fs::write(dir.join("my-page-2.md"), "").unwrap();
+
+        let mut service = FileSyncService::new(&dir);
+        service.file_naming = FileNaming::Slug;
+        assert_eq!(service.file_stem_in(&dir, "My Page!", None), "my-page-3");
+        assert_eq!(
+            service.file_stem_in(&dir, "My Page", Some("my-page")),
+            "my-page"
+        );
+        assert_eq!(service.file_stem_in(&dir, "Other", None), "other");
+
+        service.file_naming = FileNaming::Title;
+        assert_eq!(service.file_stem_in(&dir, "My Page", None), "My Page");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("Hello World"), "Hello World");
         assert_eq!(sanitize_filename("Test:File*Name?"), "Test_File_Name_");
         assert_eq!(sanitize_filename("Path/To/File"), "Path_To_File");
     }
+
+    #[test]
+    fn test_sanitize_filename_avoids_windows_reserved_names() {
+        assert_eq!(sanitize_filename("CON.md"), "CON_.md");
+        assert_eq!(sanitize_filename("aux"), "aux_");
+        assert_eq!(sanitize_filename("Com1 "), "Com1_");
+        assert_eq!(sanitize_filename("name. "), "name");
+        assert_eq!(sanitize_filename("Console"), "Console");
+        assert_eq!(sanitize_filename("nul.tar.gz"), "nul_.tar.gz");
+        assert_eq!(sanitize_filename("..."), "untitled");
+        assert_eq!(sanitize_filename(" . "), "untitled");
+        assert!(is_reserved_name_stem("CON_", "CON"));
+        assert!(!is_reserved_name_stem("Console", "Console"));
+        assert_eq!(slugify("Con"), "con_");
+    }
 }
@@ -0,0 +1,193 @@
+//! Background file watcher that keeps the index in sync with edits made outside the app.
+//!
+//! `start` (called from `run()`'s setup) spawns one worker thread for the app's
+//! lifetime. `watch_workspace` points a `notify` watcher at a workspace root; its raw
+//! events are forwarded to the worker, which waits for them to settle and then
+//! reindexes each changed markdown file with `sync_single_file`. Files the app wrote
+//! itself already match `pages.file_mtime`/`file_size` and are skipped there, so the
+//! app's own writes do not loop back into a reindex.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::workspace::{sync_single_file, syncable_markdown_path, SyncedFile};
+use crate::utils::events::emit_page_changed;
+use crate::utils::sync_ignore::SyncIgnore;
+
+/// Events arriving within this window of each other are handled as one batch
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Changed paths reported by the watcher of a workspace
+struct WatchEvent {
+    workspace_path: String,
+    paths: Vec<PathBuf>,
+}
+
+struct FileWatcher {
+    events: Sender<WatchEvent>,
+    /// The workspace being watched; dropping the watcher stops it
+    active: Option<(String, RecommendedWatcher)>,
+}
+
+static FILE_WATCHER: OnceLock<Mutex<FileWatcher>> = OnceLock::new();
+
+/// Start the worker that reindexes changed files and emits `file-changed`
+/// (`SyncedFile`) for each of them. Calling it again has no effect.
+pub fn start(app: AppHandle) {
+    if FILE_WATCHER.get().is_some() {
+        return;
+    }
+
+    let (events, receiver) = mpsc::channel();
+    let watcher = FileWatcher {
+        events,
+        active: None,
+    };
+    if FILE_WATCHER.set(Mutex::new(watcher)).is_ok() {
+        std::thread::spawn(move || run_watch_loop(&app, receiver));
+    }
+}
+
+/// Watch `workspace_path` for external changes, replacing the previously watched
+/// workspace. Does nothing if `start` has not been called.
+pub fn watch_workspace(workspace_path: &str) -> Result<(), String> {
+    let Some(state) = FILE_WATCHER.get() else {
+        return Ok(());
+    };
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    if state
+        .active
+        .as_ref()
+        .is_some_and(|(path, _)| path == workspace_path)
+    {
+        return Ok(());
+    }
+
+    let events = state.events.clone();
+    let tagged_path = workspace_path.to_string();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            // Reads do not change anything worth reindexing
+            Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+            Ok(event) => {
+                let _ = events.send(WatchEvent {
+                    workspace_path: tagged_path.clone(),
+                    paths: event.paths,
+                });
+            }
+            Err(e) => eprintln!("[file_watcher] Watch error: {}", e),
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    watcher
+        .watch(Path::new(workspace_path), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", workspace_path, e))?;
+
+    println!("[file_watcher] Watching workspace: {}", workspace_path);
+    state.active = Some((workspace_path.to_string(), watcher));
+    Ok(())
+}
+
+/// Stop watching the current workspace, if any
+pub fn unwatch_workspace() {
+    if let Some(state) = FILE_WATCHER.get() {
+        if let Ok(mut state) = state.lock() {
+            state.active = None;
+        }
+    }
+}
+
+fn run_watch_loop(app: &AppHandle, receiver: Receiver<WatchEvent>) {
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        loop {
+            match receiver.recv_timeout(WATCH_DEBOUNCE) {
+                Ok(event) => batch.push(event),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        for (workspace_path, rel_paths) in changed_markdown_files(batch) {
+            for rel_path in rel_paths {
+                match sync_single_file(&workspace_path, &rel_path) {
+                    Ok(Some(synced)) => notify_file_changed(app, &workspace_path, &synced),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("[file_watcher] Failed to sync {}: {}", rel_path, e),
+                }
+            }
+        }
+    }
+}
+
+fn notify_file_changed(app: &AppHandle, workspace_path: &str, synced: &SyncedFile) {
+    println!(
+        "[file_watcher] Reindexed externally changed file: {}",
+        synced.file_path
+    );
+    let _ = app.emit("file-changed", synced);
+    emit_page_changed(app, workspace_path, &synced.page_id);
+}
+
+/// Workspace-relative markdown paths touched by a batch of events, deduplicated and
+/// sorted per workspace; paths that sync would not visit are dropped
+fn changed_markdown_files(batch: Vec<WatchEvent>) -> HashMap<String, Vec<String>> {
+    let mut changed: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut ignores: HashMap<String, SyncIgnore> = HashMap::new();
+
+    for event in batch {
+        let workspace_root = Path::new(&event.workspace_path);
+        let ignore = ignores
+            .entry(event.workspace_path.clone())
+            .or_insert_with(|| SyncIgnore::load(workspace_root));
+        for path in &event.paths {
+            if let Some(rel_path) = syncable_markdown_path(workspace_root, path, ignore) {
+                changed
+                    .entry(event.workspace_path.clone())
+                    .or_default()
+                    .insert(rel_path);
+            }
+        }
+    }
+
+    changed
+        .into_iter()
+        .map(|(workspace_path, paths)| {
+            let mut paths: Vec<String> = paths.into_iter().collect();
+            paths.sort();
+            (workspace_path, paths)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_markdown_files_filters_and_dedups() {
+        let root = std::env::temp_dir().join("oxinot_watch_filter");
+        let event = |paths: &[&str]| WatchEvent {
+            workspace_path: root.to_string_lossy().to_string(),
+            paths: paths.iter().map(|p| root.join(p)).collect(),
+        };
+
+        let changed = changed_markdown_files(vec![
+            event(&["Notes.md", ".oxinot/outliner.db", ".oxinot/outliner.db-wal"]),
+            event(&["Projects/Plan.md", "Notes.md", "image.png"]),
+            event(&["node_modules/pkg/README.md"]),
+        ]);
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(
+            changed[&root.to_string_lossy().to_string()],
+            vec!["Notes.md", "Projects/Plan.md"]
+        );
+    }
+}
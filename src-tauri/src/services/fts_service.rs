@@ -11,8 +11,10 @@ impl FtsService {
         page_id: &str,
         content: &str,
     ) -> Result<(), String> {
+        // blocks_fts has no unique key on block_id, so OR REPLACE would add a duplicate
+        Self::deindex_block(conn, block_id)?;
         conn.execute(
-            "INSERT OR REPLACE INTO blocks_fts (block_id, page_id, content, anchor_id, path_text)
+            "INSERT INTO blocks_fts (block_id, page_id, content, anchor_id, path_text)
              VALUES (?, ?, ?, ?, ?)",
             params![block_id, page_id, content, block_id, ""],
         )
@@ -131,7 +133,7 @@ impl FtsService {
                  JOIN pages p ON b.page_id = p.id
                  WHERE blocks_fts MATCH ?1
                  AND p.is_deleted = 0
-                 ORDER BY rank, p.title COLLATE NOCASE, b.order_weight
+                 ORDER BY rank, p.title COLLATE NOCASE, b.order_key
                  LIMIT ?2",
             )
             .map_err(|e| e.to_string())?;
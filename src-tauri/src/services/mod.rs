@@ -1,4 +1,7 @@
+pub mod auto_commit;
+pub mod block_history;
 pub mod file_sync;
+pub mod file_watcher;
 pub mod fts_service;
 pub mod page_path_service;
 pub mod path_validator;
@@ -1,22 +1,211 @@
+use crate::services::file_sync::{is_reserved_name_stem, is_slug_stem, sanitize_filename};
+use crate::services::wiki_link_index::resolve_link_target;
 use crate::utils::path::normalize_page_path;
-use rusqlite::{params, Connection};
+use icu_normalizer::ComposingNormalizerBorrowed;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Deepest page nesting followed when building a title path
+const MAX_PARENT_DEPTH: usize = 64;
+
+/// How a link target or block path segment was matched, from strictest to loosest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathMatchType {
+    /// Byte-for-byte equal
+    Exact,
+    /// Equal after Unicode NFC normalization (e.g. precomposed vs decomposed accents)
+    Normalized,
+    /// Equal after NFC normalization and lowercasing
+    CaseInsensitive,
+}
+
+/// Unicode NFC form of `text`, so `é` typed precomposed and decomposed compare equal
+pub fn nfc(text: &str) -> String {
+    ComposingNormalizerBorrowed::new_nfc()
+        .normalize(text)
+        .into_owned()
+}
+
+/// Key for fallback matching: the NFC form, lowercased when `case_insensitive`
+pub fn match_key(text: &str, case_insensitive: bool) -> String {
+    let normalized = nfc(text.trim());
+    if case_insensitive {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
+
+/// Resolve a normalized link target to a page id. The exact lookup of
+/// `resolve_link_target` runs first; when it fails the paths are compared by NFC form,
+/// then (with `case_insensitive`) also ignoring case. Returns how the page was matched.
+pub fn resolve_page_path(
+    conn: &Connection,
+    target_path: &str,
+    case_insensitive: bool,
+) -> Result<Option<(String, PathMatchType)>, rusqlite::Error> {
+    if let Some(page_id) = resolve_link_target(conn, target_path)? {
+        return Ok(Some((page_id, PathMatchType::Exact)));
+    }
+    // An ASCII target is already in NFC, so only case folding could still find it
+    if target_path.is_ascii() && !case_insensitive {
+        return Ok(None);
+    }
+
+    let mut modes = vec![(false, PathMatchType::Normalized)];
+    if case_insensitive {
+        modes.push((true, PathMatchType::CaseInsensitive));
+    }
+    for (fold_case, match_type) in modes {
+        let target = match_key(target_path, fold_case);
+        // Any match shares the target's basename, so only those rows are keyed. Rows
+        // written before the key columns existed have none and are always examined.
+        let column = if fold_case {
+            "folded_basename"
+        } else {
+            "nfc_basename"
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT path_text, page_id FROM page_paths
+             WHERE {column} = ? OR {column} IS NULL
+             ORDER BY path_text"
+        ))?;
+        let keyed: Vec<(String, String)> = stmt
+            .query_map([basename_of(&target)], |row| {
+                Ok((match_key(&row.get::<_, String>(0)?, fold_case), row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        if let Some(page_id) = find_keyed_path(&keyed, &target) {
+            return Ok(Some((page_id.to_string(), match_type)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Same priority as `resolve_link_target`: full path, then a path ending in the
+/// target's basename, then a top-level page named like the basename. Ties go to the
+/// first entry, so callers list paths in `path_text` order.
+fn find_keyed_path<'a>(keyed: &'a [(String, String)], target: &str) -> Option<&'a str> {
+    let basename = basename_of(target);
+    let suffix = format!("/{}", basename);
+
+    keyed
+        .iter()
+        .find(|(path, _)| path == target)
+        .or_else(|| keyed.iter().find(|(path, _)| path.ends_with(&suffix)))
+        .or_else(|| keyed.iter().find(|(path, _)| path == basename))
+        .map(|(_, page_id)| page_id.as_str())
+}
+
+/// Last segment of a page path
+fn basename_of(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Store the fallback lookup keys of paths written before `page_paths` had them
+pub fn backfill_path_keys(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let mut stmt =
+        conn.prepare("SELECT page_id, path_text FROM page_paths WHERE nfc_basename IS NULL")?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    for (page_id, path) in rows {
+        conn.execute(
+            "UPDATE page_paths SET nfc_basename = ?, folded_basename = ? WHERE page_id = ?",
+            params![
+                basename_of(&match_key(&path, false)),
+                basename_of(&match_key(&path, true)),
+                page_id
+            ],
+        )?;
+    }
+    Ok(())
+}
 
 pub fn update_page_path(
     conn: &Connection,
     page_id: &str,
     file_path: &str,
 ) -> Result<(), rusqlite::Error> {
-    // Normalize path using shared utility
-    let path_str = normalize_page_path(file_path);
+    // Normalize path using shared utility; slug-named files and reserved names made safe
+    // are linked by title instead
+    let path_str = match slug_title_path(conn, page_id, file_path)? {
+        Some(path) => path,
+        None => normalize_page_path(file_path),
+    };
 
     conn.execute(
-        "INSERT OR REPLACE INTO page_paths (page_id, path_text, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)",
-        params![page_id, path_str],
+        "INSERT OR REPLACE INTO page_paths (page_id, path_text, nfc_basename, folded_basename, updated_at)
+         VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)",
+        params![
+            page_id,
+            path_str,
+            basename_of(&match_key(&path_str, false)),
+            basename_of(&match_key(&path_str, true))
+        ],
     )?;
 
     Ok(())
 }
 
+/// Path of titles for a page stored under a slug of its title (`my-page.md` for
+/// "My Page") or under a reserved name made safe (`CON_.md` for "CON"), so
+/// `[[My Page]]` keeps resolving. Built from the titles of the page's ancestors the way
+/// title-named files nest: `Parent/Child`, and `Dir/Dir` for a directory page. None
+/// when the file is named after the title as is.
+fn slug_title_path(
+    conn: &Connection,
+    page_id: &str,
+    file_path: &str,
+) -> Result<Option<String>, rusqlite::Error> {
+    let normalized = normalize_page_path(file_path);
+    let stem = normalized.rsplit('/').next().unwrap_or(&normalized);
+
+    let page: Option<(String, Option<String>, bool)> = conn
+        .query_row(
+            "SELECT title, parent_id, is_directory FROM pages WHERE id = ?",
+            params![page_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i32>(2)? != 0)),
+        )
+        .optional()?;
+    let Some((title, mut parent_id, is_directory)) = page else {
+        return Ok(None);
+    };
+    let slug_named = stem != sanitize_filename(&title) && is_slug_stem(stem, &title);
+    if !slug_named && !is_reserved_name_stem(stem, &title) {
+        return Ok(None);
+    }
+
+    let mut segments = vec![title.clone()];
+    if is_directory {
+        segments.push(title);
+    }
+    // Bounded walk in case of a parent cycle
+    for _ in 0..MAX_PARENT_DEPTH {
+        let Some(id) = parent_id else {
+            break;
+        };
+        let parent: Option<(String, Option<String>)> = conn
+            .query_row(
+                "SELECT title, parent_id FROM pages WHERE id = ?",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((parent_title, next)) = parent else {
+            break;
+        };
+        segments.push(parent_title);
+        parent_id = next;
+    }
+    segments.reverse();
+
+    Ok(Some(segments.join("/")))
+}
+
 pub fn remove_page_path(conn: &Connection, page_id: &str) -> Result<(), rusqlite::Error> {
     conn.execute("DELETE FROM page_paths WHERE page_id = ?", params![page_id])?;
     Ok(())
@@ -54,3 +243,136 @@ pub fn migrate_populate_page_paths(conn: &Connection) -> Result<(), rusqlite::Er
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_page_path_falls_back_to_nfc_then_case() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES ('cafe', 'Cafe'), ('notes', 'Notes');",
+        )
+        .unwrap();
+        // Decomposed "é" as written by macOS file systems
+        update_page_path(&conn, "cafe", "Places/Cafe\u{301}.md").unwrap();
+        update_page_path(&conn, "notes", "Notes.md").unwrap();
+
+        let exact = resolve_page_path(&conn, "Places/Cafe\u{301}", false).unwrap();
+        assert_eq!(exact, Some(("cafe".to_string(), PathMatchType::Exact)));
+
+        let composed = resolve_page_path(&conn, "Places/Caf\u{e9}", false).unwrap();
+        assert_eq!(
+            composed,
+            Some(("cafe".to_string(), PathMatchType::Normalized))
+        );
+        let basename = resolve_page_path(&conn, "Caf\u{e9}", false).unwrap();
+        assert_eq!(
+            basename,
+            Some(("cafe".to_string(), PathMatchType::Normalized))
+        );
+
+        assert_eq!(resolve_page_path(&conn, "CAF\u{c9}", false).unwrap(), None);
+        let folded = resolve_page_path(&conn, "CAF\u{c9}", true).unwrap();
+        assert_eq!(
+            folded,
+            Some(("cafe".to_string(), PathMatchType::CaseInsensitive))
+        );
+
+        assert_eq!(resolve_page_path(&conn, "Missing", true).unwrap(), None);
+    }
+
+    #[test]
+    fn test_resolve_page_path_keys_and_ties() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title) VALUES
+                ('b', 'Cafe'), ('a', 'Cafe'), ('top', 'Cafe'), ('legacy', 'Resume');
+             INSERT INTO page_paths (page_id, path_text) VALUES ('legacy', 'Old/Re\u{301}sume\u{301}');",
+        )
+        .unwrap();
+        update_page_path(&conn, "b", "Zed/Cafe\u{301}.md").unwrap();
+        update_page_path(&conn, "a", "Alpha/Cafe\u{301}.md").unwrap();
+        update_page_path(&conn, "top", "Cafe\u{301}.md").unwrap();
+
+        let keys: (String, String) = conn
+            .query_row(
+                "SELECT nfc_basename, folded_basename FROM page_paths WHERE page_id = 'b'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(keys, ("Caf\u{e9}".to_string(), "caf\u{e9}".to_string()));
+
+        let resolved = resolve_page_path(&conn, "Caf\u{e9}", false).unwrap();
+        assert_eq!(
+            resolved,
+            Some(("top".to_string(), PathMatchType::Normalized))
+        );
+
+        // A nested path beats the top-level page and the first path in order wins,
+        // breaking the tie the same way the exact lookup does
+        let resolved = resolve_page_path(&conn, "Other/Caf\u{e9}", false).unwrap();
+        assert_eq!(resolved, Some(("a".to_string(), PathMatchType::Normalized)));
+        let exact = resolve_link_target(&conn, "Other/Cafe\u{301}").unwrap();
+        assert_eq!(exact.as_deref(), Some("a"));
+
+        // Rows without keys still resolve, and the backfill fills them in
+        let legacy = resolve_page_path(&conn, "R\u{e9}sum\u{e9}", false).unwrap();
+        assert_eq!(legacy.map(|(id, _)| id).as_deref(), Some("legacy"));
+        backfill_path_keys(&conn).unwrap();
+        let unkeyed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM page_paths WHERE nfc_basename IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(unkeyed, 0);
+    }
+
+    #[test]
+    fn test_slug_named_pages_resolve_by_title() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO pages (id, title, is_directory) VALUES ('proj', 'Big Projects', 1);
+             INSERT INTO pages (id, title, parent_id) VALUES
+                ('plan', 'Launch Plan', 'proj'), ('misc', 'misc', NULL);",
+        )
+        .unwrap();
+        update_page_path(&conn, "proj", "big-projects/big-projects.md").unwrap();
+        update_page_path(&conn, "plan", "big-projects/launch-plan-2.md").unwrap();
+        update_page_path(&conn, "misc", "misc.md").unwrap();
+
+        let path_of = |id: &str| -> String {
+            conn.query_row(
+                "SELECT path_text FROM page_paths WHERE page_id = ?",
+                [id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(path_of("proj"), "Big Projects/Big Projects");
+        assert_eq!(path_of("plan"), "Big Projects/Launch Plan");
+        assert_eq!(path_of("misc"), "misc");
+
+        let resolved = resolve_page_path(&conn, "Launch Plan", false).unwrap();
+        assert_eq!(resolved, Some(("plan".to_string(), PathMatchType::Exact)));
+    }
+
+    #[test]
+    fn test_reserved_name_pages_resolve_by_title() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_schema(&conn).unwrap();
+        conn.execute_batch("INSERT INTO pages (id, title) VALUES ('con', 'CON');")
+            .unwrap();
+        update_page_path(&conn, "con", "CON_.md").unwrap();
+
+        let resolved = resolve_page_path(&conn, "CON", false).unwrap();
+        assert_eq!(resolved, Some(("con".to_string(), PathMatchType::Exact)));
+    }
+}
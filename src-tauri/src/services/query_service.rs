@@ -1,7 +1,30 @@
 use crate::models::query::*;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 
 /// Parse a query macro string (the content inside {{ }})
+///
+/// Grammar (keywords are case-insensitive, clauses may come in any order):
+///
+/// ```text
+/// QUERY: FROM [path] [path/*] ...     required; page paths, `*` wildcards
+///        LIKE "text"                  block content contains text
+///        DEPTH n | DEPTH min..max     nesting level (root blocks are 0)
+///        SORT RANDOM|ABC|CBA|123|321  content or creation order
+///        sort:key [asc|desc]          created_at, updated_at or a metadata key
+///        LIMIT n | limit:n            at most n results, taken after sorting
+///        count | count:key            number of blocks (having metadata key)
+///        sum:key | avg:key            total / mean of a numeric metadata key
+///        group:key                    one aggregate row per value of key
+/// ```
+///
+/// `sort:key` orders metadata values numerically when both are numbers and
+/// lexically otherwise; blocks without the key come last. Default is ascending.
+/// An aggregate is computed over every matching block (LIMIT is ignored), and
+/// `sum`/`avg` skip values that are not numbers. A `|` may separate the filter
+/// from the aggregate for readability: `FROM [Movies/*] | avg:rating`.
 pub fn parse_query_macro(input: &str) -> Result<QueryMacro, QueryError> {
     let trimmed = input.trim();
 
@@ -43,6 +66,7 @@ fn parse_query_filter(input: &str) -> Result<QueryFilter, QueryError> {
     let depth = parse_depth_clause(query_part)?;
     let limit = parse_limit_clause(query_part)?;
     let sort = parse_sort_clause(query_part)?;
+    let aggregate = parse_aggregate_clause(query_part)?;
 
     Ok(QueryFilter {
         from,
@@ -50,6 +74,7 @@ fn parse_query_filter(input: &str) -> Result<QueryFilter, QueryError> {
         depth,
         limit,
         sort,
+        aggregate,
     })
 }
 
@@ -126,9 +151,9 @@ fn parse_depth_clause(input: &str) -> Result<Option<DepthRange>, QueryError> {
     }
 }
 
-/// Parse LIMIT clause: LIMIT 10
+/// Parse LIMIT clause: LIMIT 10 or limit:10
 fn parse_limit_clause(input: &str) -> Result<Option<u32>, QueryError> {
-    let re = Regex::new(r"(?i)LIMIT\s+(\d+)").map_err(|_| QueryError::new("Regex error"))?;
+    let re = Regex::new(r"(?i)LIMIT[\s:]\s*(\d+)").map_err(|_| QueryError::new("Regex error"))?;
 
     if let Some(captures) = re.captures(input) {
         let limit = captures
@@ -142,8 +167,20 @@ fn parse_limit_clause(input: &str) -> Result<Option<u32>, QueryError> {
     }
 }
 
-/// Parse SORT clause: SORT RANDOM|ABC|CBA|123|321
+/// Parse SORT clause: SORT RANDOM|ABC|CBA|123|321 or sort:key [asc|desc]
 fn parse_sort_clause(input: &str) -> Result<Option<SortType>, QueryError> {
+    let key_re = Regex::new(r"(?i)SORT:\s*([\w.-]+)(?:\s+(ASC|DESC)\b)?")
+        .map_err(|_| QueryError::new("Regex error"))?;
+    if let Some(captures) = key_re.captures(input) {
+        let descending = captures
+            .get(2)
+            .is_some_and(|dir| dir.as_str().eq_ignore_ascii_case("desc"));
+        return Ok(Some(SortType::By {
+            key: SortKey::from_name(&captures[1]),
+            descending,
+        }));
+    }
+
     let re = Regex::new(r"(?i)SORT\s+(\w+)").map_err(|_| QueryError::new("Regex error"))?;
 
     if let Some(captures) = re.captures(input) {
@@ -156,6 +193,169 @@ fn parse_sort_clause(input: &str) -> Result<Option<SortType>, QueryError> {
     }
 }
 
+/// Parse aggregate clauses: count, count:key, sum:key, avg:key and group:key.
+/// Quoted LIKE text and bracketed FROM paths are ignored, so a page named "count"
+/// does not turn the query into an aggregate.
+fn parse_aggregate_clause(input: &str) -> Result<Option<Aggregate>, QueryError> {
+    let literals =
+        Regex::new(r#""[^"]*"|\[[^\]]*\]"#).map_err(|_| QueryError::new("Regex error"))?;
+    let clauses = literals.replace_all(input, " ");
+
+    let function_re = Regex::new(r"(?i)\b(COUNT|SUM|AVG)\b(?::\s*([\w.-]+))?")
+        .map_err(|_| QueryError::new("Regex error"))?;
+    let Some(captures) = function_re.captures(&clauses) else {
+        return Ok(None);
+    };
+    let name = captures[1].to_uppercase();
+    let key = captures.get(2).map(|m| m.as_str().to_string());
+    let function = match (name.as_str(), key) {
+        ("COUNT", key) => AggregateFunction::Count(key),
+        ("SUM", Some(key)) => AggregateFunction::Sum(key),
+        ("AVG", Some(key)) => AggregateFunction::Avg(key),
+        _ => {
+            return Err(QueryError::new(format!(
+                "{} needs a metadata key, e.g. {}:rating",
+                name,
+                name.to_lowercase()
+            )))
+        }
+    };
+
+    let group_re =
+        Regex::new(r"(?i)\bGROUP:\s*([\w.-]+)").map_err(|_| QueryError::new("Regex error"))?;
+    let group_by = group_re.captures(&clauses).map(|c| c[1].to_string());
+
+    Ok(Some(Aggregate { function, group_by }))
+}
+
+/// Apply `aggregate` to the metadata of the matching blocks
+pub fn aggregate_metadata(
+    aggregate: &Aggregate,
+    rows: &[&HashMap<String, String>],
+) -> AggregateResult {
+    let Some(group_key) = &aggregate.group_by else {
+        let (value, row_count) = aggregate_values(&aggregate.function, rows);
+        return AggregateResult::Value { value, row_count };
+    };
+
+    let mut groups: BTreeMap<Option<String>, Vec<&HashMap<String, String>>> = BTreeMap::new();
+    for metadata in rows {
+        groups
+            .entry(metadata.get(group_key).cloned())
+            .or_default()
+            .push(metadata);
+    }
+
+    let mut table: Vec<AggregateRow> = groups
+        .into_iter()
+        .map(|(group, members)| {
+            let (value, row_count) = aggregate_values(&aggregate.function, &members);
+            AggregateRow {
+                group,
+                value,
+                row_count,
+            }
+        })
+        .collect();
+    table.sort_by(|a, b| compare_sort_values(a.group.as_deref(), b.group.as_deref(), false));
+
+    AggregateResult::Table {
+        group_key: group_key.clone(),
+        rows: table,
+    }
+}
+
+/// The aggregate's value over `rows` and how many rows it counted
+fn aggregate_values(
+    function: &AggregateFunction,
+    rows: &[&HashMap<String, String>],
+) -> (Option<f64>, usize) {
+    let numbers = |key: &str| -> Vec<f64> {
+        rows.iter()
+            .filter_map(|metadata| metadata.get(key)?.trim().parse::<f64>().ok())
+            .filter(|number| number.is_finite())
+            .collect()
+    };
+
+    match function {
+        AggregateFunction::Count(None) => (Some(rows.len() as f64), rows.len()),
+        AggregateFunction::Count(Some(key)) => {
+            let count = rows.iter().filter(|m| m.contains_key(key)).count();
+            (Some(count as f64), count)
+        }
+        AggregateFunction::Sum(key) => {
+            let values = numbers(key);
+            (Some(values.iter().sum()), values.len())
+        }
+        AggregateFunction::Avg(key) => {
+            let values = numbers(key);
+            let mean =
+                (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+            (mean, values.len())
+        }
+    }
+}
+
+/// A metadata value as `compare_sort_values` orders it; variants are in sort order
+enum SortValue {
+    Number(f64),
+    Date(NaiveDateTime),
+    Text(String),
+}
+
+impl SortValue {
+    fn parse(value: &str) -> Self {
+        if let Ok(number) = value.parse::<f64>() {
+            return SortValue::Number(number);
+        }
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .or_else(|| {
+                DateTime::parse_from_rfc3339(value)
+                    .ok()
+                    .map(|dt| dt.naive_utc())
+            });
+        match date {
+            Some(date) => SortValue::Date(date),
+            None => SortValue::Text(value.to_lowercase()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            SortValue::Number(_) => 0,
+            SortValue::Date(_) => 1,
+            SortValue::Text(_) => 2,
+        }
+    }
+}
+
+/// Order two values of a `sort:key` metadata sort. Numbers come first, then dates
+/// (`YYYY-MM-DD` or RFC 3339), then text, each ordered within its kind (text
+/// case-insensitively); values that tie, like `1` and `1.0`, fall back to their raw
+/// text so the order is total. Missing values sort last in either direction.
+pub fn compare_sort_values(a: Option<&str>, b: Option<&str>, descending: bool) -> Ordering {
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a.trim(), b.trim()),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (None, None) => return Ordering::Equal,
+    };
+    let ordering = match (SortValue::parse(a), SortValue::parse(b)) {
+        (SortValue::Number(x), SortValue::Number(y)) => x.total_cmp(&y),
+        (SortValue::Date(x), SortValue::Date(y)) => x.cmp(&y),
+        (SortValue::Text(x), SortValue::Text(y)) => x.cmp(&y),
+        (x, y) => x.rank().cmp(&y.rank()),
+    }
+    .then_with(|| a.cmp(b));
+    if descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
 /// Check if a page path matches a pattern (supports * wildcard)
 pub fn matches_path_pattern(pattern: &str, path: &str) -> bool {
     if pattern == "*" {
@@ -253,6 +453,196 @@ mod tests {
         assert_eq!(depth.max, 5);
     }
 
+    #[test]
+    fn test_parse_sort_key_and_limit_colon_clauses() {
+        let input = r#"QUERY: FROM [Movies/*] sort:rating desc limit:5"#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(
+            filter.sort,
+            Some(SortType::By {
+                key: SortKey::Metadata("rating".to_string()),
+                descending: true,
+            })
+        );
+        assert_eq!(filter.limit, Some(5));
+
+        let input = r#"QUERY: FROM [*] SORT:updated_at LIMIT 3"#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(
+            filter.sort,
+            Some(SortType::By {
+                key: SortKey::UpdatedAt,
+                descending: false,
+            })
+        );
+        assert_eq!(filter.limit, Some(3));
+
+        let input = r#"QUERY: FROM [*] sort:Created_At ASC"#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(
+            filter.sort,
+            Some(SortType::By {
+                key: SortKey::CreatedAt,
+                descending: false,
+            })
+        );
+
+        // The keyword form is unchanged
+        let input = r#"QUERY: FROM [*] SORT 321"#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(filter.sort, Some(SortType::Numeric321));
+    }
+
+    #[test]
+    fn test_parse_aggregate_clauses() {
+        let input = r#"QUERY: FROM [Movies/*] | avg:rating group:genre"#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(
+            filter.aggregate,
+            Some(Aggregate {
+                function: AggregateFunction::Avg("rating".to_string()),
+                group_by: Some("genre".to_string()),
+            })
+        );
+
+        let input = r#"QUERY: FROM [*] COUNT"#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(
+            filter.aggregate,
+            Some(Aggregate {
+                function: AggregateFunction::Count(None),
+                group_by: None,
+            })
+        );
+
+        // Words inside LIKE text or FROM paths are not clauses
+        let input = r#"QUERY: FROM [count/*] LIKE "sum of parts""#;
+        let filter = parse_query_macro(input).unwrap().query_filter;
+        assert_eq!(filter.aggregate, None);
+
+        assert!(parse_query_macro(r#"QUERY: FROM [*] sum"#).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_metadata_skips_non_numeric_values() {
+        let movie = |rating: &str, genre: Option<&str>| {
+            let mut metadata = HashMap::from([("rating".to_string(), rating.to_string())]);
+            if let Some(genre) = genre {
+                metadata.insert("genre".to_string(), genre.to_string());
+            }
+            metadata
+        };
+        let movies = [
+            movie("5", Some("drama")),
+            movie("4", Some("comedy")),
+            movie("unrated", Some("drama")),
+            movie("3", Some("drama")),
+            movie("2", None),
+        ];
+        let rows: Vec<&HashMap<String, String>> = movies.iter().collect();
+
+        let avg = |group_by: Option<&str>| Aggregate {
+            function: AggregateFunction::Avg("rating".to_string()),
+            group_by: group_by.map(str::to_string),
+        };
+        assert_eq!(
+            aggregate_metadata(&avg(None), &rows),
+            AggregateResult::Value {
+                value: Some(3.5),
+                row_count: 4,
+            }
+        );
+
+        let AggregateResult::Table { rows: table, .. } =
+            aggregate_metadata(&avg(Some("genre")), &rows)
+        else {
+            panic!("expected a table");
+        };
+        let summary: Vec<(Option<&str>, Option<f64>, usize)> = table
+            .iter()
+            .map(|row| (row.group.as_deref(), row.value, row.row_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("comedy"), Some(4.0), 1),
+                (Some("drama"), Some(4.0), 2),
+                (None, Some(2.0), 1),
+            ]
+        );
+
+        let count = Aggregate {
+            function: AggregateFunction::Count(Some("genre".to_string())),
+            group_by: None,
+        };
+        assert_eq!(
+            aggregate_metadata(&count, &rows),
+            AggregateResult::Value {
+                value: Some(4.0),
+                row_count: 4,
+            }
+        );
+        let avg_of_missing = Aggregate {
+            function: AggregateFunction::Avg("missing".to_string()),
+            group_by: None,
+        };
+        assert_eq!(
+            aggregate_metadata(&avg_of_missing, &rows),
+            AggregateResult::Value {
+                value: None,
+                row_count: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_compare_sort_values() {
+        let mut values = vec![Some("10"), None, Some("9"), Some("apple"), Some("2.5")];
+        values.sort_by(|a, b| compare_sort_values(*a, *b, false));
+        assert_eq!(
+            values,
+            vec![Some("2.5"), Some("9"), Some("10"), Some("apple"), None]
+        );
+
+        let mut numbers = vec![Some("10"), None, Some("9"), Some("2.5")];
+        numbers.sort_by(|a, b| compare_sort_values(*a, *b, true));
+        assert_eq!(numbers, vec![Some("10"), Some("9"), Some("2.5"), None]);
+    }
+
+    #[test]
+    fn test_compare_sort_values_orders_mixed_kinds_totally() {
+        let expected = vec![
+            Some("-3"),
+            Some("1"),
+            Some("1.0"),
+            Some("2023-12-31"),
+            Some("2024-01-01"),
+            Some("2024-01-01T00:00:00Z"),
+            Some("2024-01-01T09:30:00+09:00"),
+            Some("2024-02-01"),
+            Some("Apple"),
+            Some("apple"),
+            Some("banana"),
+            None,
+        ];
+
+        // Every rotation of the input sorts the same way
+        for shift in 0..expected.len() {
+            let mut values = expected.clone();
+            values.rotate_left(shift);
+            values.reverse();
+            values.sort_by(|a, b| compare_sort_values(*a, *b, false));
+            assert_eq!(values, expected, "shift {}", shift);
+        }
+
+        let mut descending = expected.clone();
+        descending.sort_by(|a, b| compare_sort_values(*a, *b, true));
+        let mut reversed: Vec<_> = expected[..expected.len() - 1].to_vec();
+        reversed.reverse();
+        reversed.push(None);
+        assert_eq!(descending, reversed);
+    }
+
     #[test]
     fn test_matches_path_pattern() {
         assert!(matches_path_pattern("*", "any/path"));
@@ -1,32 +1,58 @@
-use crate::services::wiki_link_parser::parse_wiki_links;
+use crate::services::page_path_service;
+use crate::services::wiki_link_parser::{parse_scheduled_dates, parse_tags, parse_wiki_links};
 use rusqlite::{named_params, Connection, OptionalExtension};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-fn resolve_link_target(
+/// Block metadata key declaring a block alias (`alias:: name`), linkable as `[[name]]`
+pub const ALIAS_METADATA_KEY: &str = "alias";
+
+/// Find the block declaring `alias`. Aliases are not unique; the earliest created
+/// block wins.
+pub fn resolve_block_alias(
+    conn: &Connection,
+    alias: &str,
+) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT b.id FROM block_metadata m
+         JOIN blocks b ON b.id = m.block_id
+         WHERE m.key = :key AND TRIM(m.value) = :alias
+         ORDER BY b.created_at, b.id
+         LIMIT 1",
+        named_params! { ":key": ALIAS_METADATA_KEY, ":alias": alias.trim() },
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Resolve a normalized link target to a page id via `page_paths`
+pub fn resolve_link_target(
     conn: &Connection,
     target_path: &str,
 ) -> Result<Option<String>, rusqlite::Error> {
     let target_basename = target_path.split('/').last().unwrap_or(target_path);
-    let pattern = format!("%/{}", target_basename);
+    let suffix = format!("/{}", target_basename);
 
-    // Single query with priority: exact match > pattern match > basename match
+    // Single query with priority: exact match > nested basename match > top-level
+    // basename match, ties broken by path so every resolver picks the same page
     let mut stmt = conn.prepare(
         "SELECT page_id FROM page_paths
          WHERE path_text = :target_path
-         UNION
-         SELECT page_id FROM page_paths
-         WHERE path_text LIKE :pattern AND path_text != :target_path
-         UNION
-         SELECT page_id FROM page_paths
-         WHERE path_text = :target_basename
+            OR substr(path_text, -length(:suffix)) = :suffix
+            OR path_text = :target_basename
+         ORDER BY CASE
+                WHEN path_text = :target_path THEN 0
+                WHEN path_text = :target_basename THEN 2
+                ELSE 1
+            END,
+            path_text
          LIMIT 1",
     )?;
 
     stmt.query_row(
         named_params! {
             ":target_path": target_path,
-            ":pattern": pattern,
+            ":suffix": suffix,
             ":target_basename": target_basename
         },
         |row| row.get(0),
@@ -45,6 +71,13 @@ pub fn index_block_links(
         "DELETE FROM wiki_links WHERE from_block_id = :block_id",
         named_params! { ":block_id": block_id },
     )?;
+    conn.execute(
+        "DELETE FROM block_refs WHERE from_block_id = :block_id AND ref_type = 'alias'",
+        named_params! { ":block_id": block_id },
+    )?;
+
+    index_block_tags(conn, block_id, block_content, page_id)?;
+    index_block_schedule(conn, block_id, block_content)?;
 
     // 2. Parse new links
     let links = parse_wiki_links(block_content);
@@ -65,9 +98,18 @@ pub fn index_block_links(
     )?;
 
     for link in links {
-        let to_page_id: Option<String> = resolve_link_target(&conn, &link.target_path)?;
+        let to_page_id: Option<String> =
+            page_path_service::resolve_page_path(conn, &link.target_path, false)?
+                .map(|(page_id, _)| page_id);
 
-        if to_page_id.is_none() {
+        // Pages take precedence; an unresolved target may still name a block alias
+        let alias_block_id = match to_page_id {
+            Some(_) => None,
+            None => resolve_block_alias(conn, &link.target_path)?,
+        };
+        if let Some(to_block_id) = &alias_block_id {
+            insert_alias_ref(conn, block_id, to_block_id)?;
+        } else if to_page_id.is_none() {
             eprintln!(
                 "[index_block_links] Unresolved link '{}' in block {} from page {}",
                 link.target_path, block_id, page_id
@@ -94,35 +136,179 @@ pub fn index_block_links(
     Ok(())
 }
 
+/// Replace the `block_tags` rows of a block with the tags in `block_content`.
+/// Tags are stored lowercased and once per block; nested tags keep their full
+/// path (`#project/alpha` is stored as `project/alpha`).
+pub fn index_block_tags(
+    conn: &Connection,
+    block_id: &str,
+    block_content: &str,
+    page_id: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM block_tags WHERE block_id = :block_id",
+        named_params! { ":block_id": block_id },
+    )?;
+
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO block_tags (block_id, page_id, tag)
+         VALUES (:block_id, :page_id, :tag)",
+    )?;
+    for tag in parse_tags(block_content) {
+        stmt.execute(named_params! {
+            ":block_id": block_id,
+            ":page_id": page_id,
+            ":tag": tag.to_lowercase(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Replace the `block_schedule` row of a block with the earliest valid date
+/// scheduled in `block_content`, or remove it when there is none
+pub fn index_block_schedule(
+    conn: &Connection,
+    block_id: &str,
+    block_content: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM block_schedule WHERE block_id = :block_id",
+        named_params! { ":block_id": block_id },
+    )?;
+
+    let Some(date) = parse_scheduled_dates(block_content).into_iter().min() else {
+        return Ok(());
+    };
+    conn.execute(
+        "INSERT INTO block_schedule (block_id, scheduled_date)
+         VALUES (:block_id, :scheduled_date)",
+        named_params! {
+            ":block_id": block_id,
+            ":scheduled_date": date.format("%Y-%m-%d").to_string(),
+        },
+    )?;
+    Ok(())
+}
+
+/// Rebuild `block_schedule` for every block. Runs inside the caller's transaction, if any.
+pub fn reindex_all_schedules(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM block_schedule", [])?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, content FROM blocks WHERE content LIKE '%@%' OR content LIKE '%SCHEDULED::%'",
+    )?;
+    let blocks = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (block_id, content) in blocks {
+        index_block_schedule(conn, &block_id, &content)?;
+    }
+    Ok(())
+}
+
+/// Rebuild `block_tags` for every block. Runs inside the caller's transaction, if any.
+pub fn reindex_all_tags(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM block_tags", [])?;
+
+    let mut stmt =
+        conn.prepare("SELECT id, page_id, content FROM blocks WHERE content LIKE '%#%'")?;
+    let blocks = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (block_id, page_id, content) in blocks {
+        index_block_tags(conn, &block_id, &content, &page_id)?;
+    }
+    Ok(())
+}
+
+/// Record that `from_block_id` links to the block aliased by one of its `[[...]]` targets
+fn insert_alias_ref(
+    conn: &Connection,
+    from_block_id: &str,
+    to_block_id: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO block_refs (id, from_block_id, to_block_id, ref_type)
+         VALUES (:id, :from_block_id, :to_block_id, 'alias')",
+        named_params! {
+            ":id": Uuid::new_v4().to_string(),
+            ":from_block_id": from_block_id,
+            ":to_block_id": to_block_id,
+        },
+    )?;
+    Ok(())
+}
+
 pub fn reindex_all_links(conn: &mut Connection) -> Result<(), rusqlite::Error> {
     let tx = conn.transaction()?;
 
     // 1. Pre-load all page paths into memory for O(1) resolution
     // This avoids N+1 DB queries (or 3N queries due to UNION) when resolving targets.
+    // Same priority and tie-breaking as `resolve_link_target`: full path, then a
+    // nested path ending in the basename, then a top-level page named like it; paths
+    // load in order and the first one keeps each key.
     let mut path_map: HashMap<String, String> = HashMap::new();
-    let mut basename_map: HashMap<String, String> = HashMap::new();
+    let mut nested_basename_map: HashMap<String, String> = HashMap::new();
+    let mut nfc_path_map: HashMap<String, String> = HashMap::new();
+    let mut nfc_nested_basename_map: HashMap<String, String> = HashMap::new();
 
     {
-        let mut stmt = tx.prepare("SELECT path_text, page_id FROM page_paths")?;
+        let mut stmt = tx.prepare("SELECT path_text, page_id FROM page_paths ORDER BY path_text")?;
         let rows = stmt.query_map([], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
         })?;
 
         for row in rows {
             let (path, page_id) = row?;
-            // Map full path -> page_id
-            path_map.insert(path.clone(), page_id.clone());
-
-            // Map basename -> page_id (for fuzzy matching)
-            // Note: If multiple paths share the same basename, the last one loaded wins.
-            // This is acceptable behavior for fuzzy matching where ambiguity exists.
-            let basename = path.split('/').last().unwrap_or(&path).to_string();
-            basename_map.insert(basename, page_id);
+            // NFC-keyed fallbacks so decomposed and precomposed accents resolve alike
+            let normalized = page_path_service::nfc(&path);
+            if let Some((_, basename)) = path.rsplit_once('/') {
+                nested_basename_map
+                    .entry(basename.to_string())
+                    .or_insert_with(|| page_id.clone());
+            }
+            if let Some((_, basename)) = normalized.rsplit_once('/') {
+                nfc_nested_basename_map
+                    .entry(basename.to_string())
+                    .or_insert_with(|| page_id.clone());
+            }
+            path_map.entry(path).or_insert_with(|| page_id.clone());
+            nfc_path_map.entry(normalized).or_insert(page_id);
+        }
+    }
+
+    // Block aliases, earliest created block first so it wins on duplicates
+    let mut alias_map: HashMap<String, String> = HashMap::new();
+    {
+        let mut stmt = tx.prepare(
+            "SELECT TRIM(m.value), b.id FROM block_metadata m
+             JOIN blocks b ON b.id = m.block_id
+             WHERE m.key = :key
+             ORDER BY b.created_at, b.id",
+        )?;
+        let rows = stmt.query_map(named_params! { ":key": ALIAS_METADATA_KEY }, |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        for row in rows {
+            let (alias, block_id) = row?;
+            alias_map.entry(alias).or_insert(block_id);
         }
     }
 
     // 2. Clear existing links
     tx.execute("DELETE FROM wiki_links", [])?;
+    tx.execute("DELETE FROM block_refs WHERE ref_type = 'alias'", [])?;
 
     let batch_size = 1000;
     let mut offset = 0;
@@ -172,10 +358,24 @@ pub fn reindex_all_links(conn: &mut Connection) -> Result<(), rusqlite::Error> {
                     
                     let to_page_id: Option<String> = path_map
                         .get(&link.target_path)
-                        .or_else(|| basename_map.get(target_basename))
-                        .cloned();
+                        .or_else(|| nested_basename_map.get(target_basename))
+                        .or_else(|| path_map.get(target_basename))
+                        .cloned()
+                        .or_else(|| {
+                            let normalized = page_path_service::nfc(&link.target_path);
+                            let normalized_basename =
+                                normalized.split('/').next_back().unwrap_or(&normalized);
+                            nfc_path_map
+                                .get(&normalized)
+                                .or_else(|| nfc_nested_basename_map.get(normalized_basename))
+                                .or_else(|| nfc_path_map.get(normalized_basename))
+                                .cloned()
+                        });
 
                     if to_page_id.is_none() {
+                        if let Some(to_block_id) = alias_map.get(&link.target_path) {
+                            insert_alias_ref(&tx, &block_id, to_block_id)?;
+                        }
                         // eprintln!(
                         //     "[reindex_all_links] Unresolved link '{}' in block {} from page {}",
                         //     link.target_path, block_id, page_id
@@ -203,6 +403,9 @@ pub fn reindex_all_links(conn: &mut Connection) -> Result<(), rusqlite::Error> {
         offset += batch_size;
     }
 
+    reindex_all_tags(&tx)?;
+    reindex_all_schedules(&tx)?;
+
     tx.commit()?;
     Ok(())
 }
@@ -1,14 +1,28 @@
 use crate::utils::path::normalize_page_path;
+use chrono::NaiveDate;
 use regex::Regex;
+use std::collections::HashSet;
 use std::ops::Range;
 use std::sync::OnceLock;
 
 static WIKI_LINK_REGEX: OnceLock<Regex> = OnceLock::new();
+static TAG_REGEX: OnceLock<Regex> = OnceLock::new();
+static SCHEDULED_DATE_REGEX: OnceLock<Regex> = OnceLock::new();
 
 fn get_wiki_link_regex() -> &'static Regex {
     WIKI_LINK_REGEX.get_or_init(|| Regex::new(r"(!?)\[\[([^\]]+)\]\]").unwrap())
 }
 
+fn get_tag_regex() -> &'static Regex {
+    TAG_REGEX.get_or_init(|| Regex::new(r"(?:^|[^\w&/])#(?:\[\[([^\]]+)\]\]|([\w/-]+))").unwrap())
+}
+
+fn get_scheduled_date_regex() -> &'static Regex {
+    SCHEDULED_DATE_REGEX.get_or_init(|| {
+        Regex::new(r"(?i)(?:\bscheduled::[ \t]*|(?:^|[^\w@])@)(\d{4}-\d{2}-\d{2})\b").unwrap()
+    })
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedLink {
     pub target_path: String,
@@ -92,6 +106,92 @@ pub fn parse_wiki_links(content: &str) -> Vec<ParsedLink> {
     links
 }
 
+/// Rewrite links whose inner text is in `raw_targets` so their target's last path
+/// segment becomes `new_name`, keeping any folder prefix, `#heading`/`#^block` suffix
+/// and `|alias`. Links inside code are left untouched.
+pub fn rewrite_link_targets(
+    content: &str,
+    raw_targets: &HashSet<String>,
+    new_name: &str,
+) -> String {
+    rewrite_link_targets_with(content, raw_targets, |target| match target.rfind('/') {
+        Some(idx) => format!("{}/{}", &target[..idx], new_name),
+        None => new_name.to_string(),
+    })
+}
+
+/// Rewrite the target of links whose inner text is in `raw_targets` with `rewrite`,
+/// keeping any `#heading`/`#^block` suffix and `|alias`. Links inside code are left
+/// untouched.
+pub fn rewrite_link_targets_with(
+    content: &str,
+    raw_targets: &HashSet<String>,
+    rewrite: impl Fn(&str) -> String,
+) -> String {
+    let ignored_ranges = get_ignored_ranges(content);
+    let regex = get_wiki_link_regex();
+    let mut result = String::with_capacity(content.len());
+    let mut last_end = 0;
+
+    for cap in regex.captures_iter(content) {
+        let whole = cap.get(0).unwrap();
+        let inner = cap.get(2).unwrap();
+        if !raw_targets.contains(inner.as_str())
+            || ignored_ranges.iter().any(|r| r.contains(&whole.start()))
+        {
+            continue;
+        }
+
+        let inner_text = inner.as_str();
+        let target_end = inner_text.find(['#', '|']).unwrap_or(inner_text.len());
+        let rewritten_target = rewrite(&inner_text[..target_end]);
+
+        result.push_str(&content[last_end..inner.start()]);
+        result.push_str(&rewritten_target);
+        result.push_str(&inner_text[target_end..]);
+        last_end = inner.end();
+    }
+
+    result.push_str(&content[last_end..]);
+    result
+}
+
+/// Tags (`#tag`, `#nested/tag`, `#[[multi word]]`) in content, as written, in order.
+/// Tags inside inline or fenced code are ignored.
+pub fn parse_tags(content: &str) -> Vec<String> {
+    let ignored_ranges = get_ignored_ranges(content);
+
+    get_tag_regex()
+        .captures_iter(content)
+        .filter_map(|cap| {
+            let tag = cap.get(1).or_else(|| cap.get(2))?;
+            if ignored_ranges.iter().any(|r| r.contains(&tag.start())) {
+                return None;
+            }
+            let tag = tag.as_str().trim();
+            (!tag.is_empty()).then(|| tag.to_string())
+        })
+        .collect()
+}
+
+/// Dates a block is scheduled for, written as `SCHEDULED::YYYY-MM-DD` or inline
+/// `@YYYY-MM-DD`, in order. Strings that are not real dates (`@2024-02-30`) and
+/// dates inside inline or fenced code are skipped.
+pub fn parse_scheduled_dates(content: &str) -> Vec<NaiveDate> {
+    let ignored_ranges = get_ignored_ranges(content);
+
+    get_scheduled_date_regex()
+        .captures_iter(content)
+        .filter_map(|cap| {
+            let date = cap.get(1)?;
+            if ignored_ranges.iter().any(|r| r.contains(&date.start())) {
+                return None;
+            }
+            NaiveDate::parse_from_str(date.as_str(), "%Y-%m-%d").ok()
+        })
+        .collect()
+}
+
 fn get_ignored_ranges(content: &str) -> Vec<Range<usize>> {
     let mut ranges = Vec::new();
     let bytes = content.as_bytes();
@@ -222,4 +322,38 @@ mod tests {
         assert_eq!(links[1].target_path, "Link B");
         assert_eq!(links[1].alias, Some("Alias".to_string()));
     }
+
+    #[test]
+    fn test_rewrite_link_targets() {
+        let targets: HashSet<String> = ["my-page", "Folder/my-page#^b1|see"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let content = "[[my-page]], ![[Folder/my-page#^b1|see]], [[other]], `[[my-page]]`";
+
+        assert_eq!(
+            rewrite_link_targets(content, &targets, "My Page"),
+            "[[My Page]], ![[Folder/My Page#^b1|see]], [[other]], `[[my-page]]`"
+        );
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = parse_tags("#proj and #proj/sub, #[[Reading List]] `#code` a#b &#39; #");
+        assert_eq!(tags, vec!["proj", "proj/sub", "Reading List"]);
+    }
+
+    #[test]
+    fn test_parse_scheduled_dates() {
+        let date = |d| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let dates = parse_scheduled_dates(
+            "Call @2024-06-03 SCHEDULED::2024-06-01 scheduled:: 2024-07-01 \
+             @2024-02-30 mail@2024-01-01 `@2024-05-05`",
+        );
+        assert_eq!(
+            dates,
+            vec![date("2024-06-03"), date("2024-06-01"), date("2024-07-01")]
+        );
+        assert!(parse_scheduled_dates("@tomorrow SCHEDULED::soon").is_empty());
+    }
 }
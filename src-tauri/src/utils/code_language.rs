@@ -0,0 +1,220 @@
+/// A line-level hint that a snippet is written in some language
+enum Signal {
+    Starts(&'static str),
+    Contains(&'static str),
+}
+
+impl Signal {
+    fn matches(&self, line: &str) -> bool {
+        match self {
+            Signal::Starts(prefix) => line.starts_with(prefix),
+            Signal::Contains(needle) => line.contains(needle),
+        }
+    }
+}
+
+const SIGNALS: &[(&str, &[Signal])] = &[
+    (
+        "rust",
+        &[
+            Signal::Starts("fn "),
+            Signal::Starts("pub fn "),
+            Signal::Starts("use std::"),
+            Signal::Starts("impl "),
+            Signal::Starts("let mut "),
+            Signal::Contains("println!("),
+            Signal::Contains("&self"),
+            Signal::Contains("::new("),
+        ],
+    ),
+    (
+        "python",
+        &[
+            Signal::Starts("def "),
+            Signal::Starts("elif "),
+            Signal::Starts("from "),
+            Signal::Starts("import "),
+            Signal::Contains("self."),
+            Signal::Contains("print("),
+            Signal::Contains("__init__"),
+        ],
+    ),
+    (
+        "javascript",
+        &[
+            Signal::Starts("function "),
+            Signal::Starts("const "),
+            Signal::Starts("export "),
+            Signal::Contains("=> "),
+            Signal::Contains("console.log("),
+            Signal::Contains("document."),
+            Signal::Contains("require("),
+        ],
+    ),
+    (
+        "go",
+        &[
+            Signal::Starts("package "),
+            Signal::Starts("func "),
+            Signal::Starts("import ("),
+            Signal::Contains(":= "),
+            Signal::Contains("fmt."),
+        ],
+    ),
+    (
+        "sql",
+        &[
+            Signal::Starts("SELECT "),
+            Signal::Starts("INSERT INTO "),
+            Signal::Starts("CREATE TABLE "),
+            Signal::Starts("UPDATE "),
+            Signal::Starts("FROM "),
+            Signal::Starts("WHERE "),
+            Signal::Contains(" FROM "),
+        ],
+    ),
+    (
+        "bash",
+        &[
+            Signal::Starts("echo "),
+            Signal::Starts("sudo "),
+            Signal::Starts("cd "),
+            Signal::Starts("npm "),
+            Signal::Starts("cargo "),
+            Signal::Starts("git "),
+        ],
+    ),
+];
+
+/// Type annotations that turn a JavaScript guess into TypeScript
+const TYPESCRIPT_SIGNALS: &[Signal] = &[
+    Signal::Starts("interface "),
+    Signal::Starts("export interface "),
+    Signal::Starts("type "),
+    Signal::Contains(": string"),
+    Signal::Contains(": number"),
+    Signal::Contains(": boolean"),
+];
+
+/// Distinct signals a language needs before it is suggested
+const MIN_SIGNALS: usize = 2;
+
+/// Guess the language of a code snippet for syntax highlighting.
+///
+/// Deliberately conservative: shebangs, JSON and HTML are recognised by shape; other
+/// languages need at least two distinct keyword signals and a strict lead over every
+/// other language. Returns `None` when unsure.
+pub fn detect_code_language(code: &str) -> Option<&'static str> {
+    let code = code.trim();
+    if code.is_empty() {
+        return None;
+    }
+    if let Some(language) = detect_by_shape(code) {
+        return Some(language);
+    }
+
+    let lines: Vec<&str> = code
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let mut scores: Vec<(&'static str, usize)> = SIGNALS
+        .iter()
+        .map(|(language, signals)| (*language, count_signals(signals, &lines)))
+        .collect();
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+    let (language, score) = scores[0];
+    if score < MIN_SIGNALS || scores.get(1).is_some_and(|(_, next)| *next == score) {
+        return None;
+    }
+
+    match language {
+        // Python has no braces or semicolons; plenty of them means a C-like language
+        "python" if brace_density(&lines) > 0.25 => None,
+        "javascript" if count_signals(TYPESCRIPT_SIGNALS, &lines) > 0 => Some("typescript"),
+        _ => Some(language),
+    }
+}
+
+fn detect_by_shape(code: &str) -> Option<&'static str> {
+    if let Some(shebang) = code.lines().next().and_then(|l| l.strip_prefix("#!")) {
+        let interpreter = shebang.split_whitespace().last()?;
+        let interpreter = interpreter.split('/').next_back()?;
+        return match interpreter {
+            "sh" | "bash" | "zsh" => Some("bash"),
+            name if name.starts_with("python") => Some("python"),
+            "node" => Some("javascript"),
+            _ => None,
+        };
+    }
+    if (code.starts_with('{') || code.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(code).is_ok()
+    {
+        return Some("json");
+    }
+    if code.starts_with('<') && code.ends_with('>') && (code.contains("</") || code.contains("/>"))
+    {
+        return Some("html");
+    }
+    None
+}
+
+fn count_signals(signals: &[Signal], lines: &[&str]) -> usize {
+    signals
+        .iter()
+        .filter(|signal| lines.iter().any(|line| signal.matches(line)))
+        .count()
+}
+
+/// Share of lines ending in `{`, `}` or `;`
+fn brace_density(lines: &[&str]) -> f64 {
+    let braced = lines
+        .iter()
+        .filter(|line| line.ends_with('{') || line.ends_with('}') || line.ends_with(';'))
+        .count();
+    braced as f64 / lines.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_common_languages() {
+        let rust = "pub fn area(&self) -> f64 {\n    let mut total = 0.0;\n    total\n}";
+        assert_eq!(detect_code_language(rust), Some("rust"));
+
+        let python = "def greet(name):\n    print(f\"hi {name}\")\n\nimport os";
+        assert_eq!(detect_code_language(python), Some("python"));
+
+        let js = "const add = (a, b) => a + b;\nconsole.log(add(1, 2));";
+        assert_eq!(detect_code_language(js), Some("javascript"));
+
+        let ts = "const add = (a: number, b: number) => a + b;\nexport default add;";
+        assert_eq!(detect_code_language(ts), Some("typescript"));
+
+        let sql = "SELECT id, title\nFROM pages\nWHERE is_deleted = 0";
+        assert_eq!(detect_code_language(sql), Some("sql"));
+
+        assert_eq!(
+            detect_code_language("#!/usr/bin/env bash\nls"),
+            Some("bash")
+        );
+        assert_eq!(detect_code_language("{\"a\": [1, 2]}"), Some("json"));
+        assert_eq!(detect_code_language("<div><b>x</b></div>"), Some("html"));
+    }
+
+    #[test]
+    fn test_stays_quiet_when_unsure() {
+        assert_eq!(detect_code_language(""), None);
+        assert_eq!(detect_code_language("hello world"), None);
+        // One signal is not enough
+        assert_eq!(detect_code_language("fn main() {}"), None);
+        // Python keywords in brace-heavy code
+        let mixed = "import foo;\nfrom bar import baz;\nif (x) {\n  y();\n}";
+        assert_eq!(detect_code_language(mixed), None);
+        assert_eq!(detect_code_language("{ not json"), None);
+    }
+}
@@ -0,0 +1,92 @@
+/// Parse CSV text into rows of fields.
+///
+/// Follows RFC 4180: fields may be quoted, quoted fields may contain commas and
+/// newlines, and `""` inside quotes is a literal quote. Both `\n` and `\r\n` end a
+/// record. Blank lines are skipped.
+pub fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut field_started = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if !field_started => {
+                in_quotes = true;
+                field_started = true;
+            }
+            ',' => {
+                row.push(std::mem::take(&mut field));
+                field_started = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                if field_started || !row.is_empty() {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                field_started = false;
+            }
+            _ => {
+                field.push(c);
+                field_started = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quoted field in CSV".to_string());
+    }
+    if field_started || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_plain_rows() {
+        let rows = parse_csv("a,b,c\n1,2,3\n").unwrap();
+        assert_eq!(rows, vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]);
+    }
+
+    #[test]
+    fn test_parse_csv_quoted_fields() {
+        let rows = parse_csv(
+            "title,note\r\n\"Heat, 1995\",\"line one\nline \"\"two\"\"\"\r\n\r\n,empty\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["title", "note"],
+                vec!["Heat, 1995", "line one\nline \"two\""],
+                vec!["", "empty"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_unterminated_quote() {
+        assert!(parse_csv("a,\"b\n").is_err());
+    }
+}
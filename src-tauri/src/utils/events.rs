@@ -4,4 +4,14 @@ use tauri::Emitter;
 /// This is called after any file system operation that modifies workspace files
 pub fn emit_workspace_changed(app: &tauri::AppHandle, workspace_path: &str) {
     let _ = app.emit("workspace-changed", workspace_path);
+    crate::commands::db::note_workspace_write(workspace_path);
+    crate::commands::live_query::notify_live_queries(workspace_path, None);
+}
+
+/// Like `emit_workspace_changed`, for a change confined to a single page.
+/// Live queries that cannot include that page are not re-run.
+pub fn emit_page_changed(app: &tauri::AppHandle, workspace_path: &str, page_id: &str) {
+    let _ = app.emit("workspace-changed", workspace_path);
+    crate::commands::db::note_workspace_write(workspace_path);
+    crate::commands::live_query::notify_live_queries(workspace_path, Some(page_id));
 }
@@ -1,3 +1,13 @@
+/// Gap between neighboring order weights below which siblings must be rebalanced
+pub const REBALANCE_EPSILON: f64 = 1e-10;
+
+/// A first sibling weighted below this is rebalanced before inserting in front of it
+pub const MIN_LEADING_WEIGHT: f64 = 1e-5;
+
+/// A last sibling weighted above this is rebalanced before appending after it; f64
+/// spacing grows with magnitude, so appends must stay well below 2^53
+pub const MAX_TRAILING_WEIGHT: f64 = 1e15;
+
 /// Calculate the middle value between two order weights
 pub fn calculate_middle(before: Option<f64>, after: Option<f64>) -> f64 {
     match (before, after) {
@@ -8,6 +18,22 @@ pub fn calculate_middle(before: Option<f64>, after: Option<f64>) -> f64 {
     }
 }
 
+/// The middle value between two order weights, or None when there is no room left:
+/// f64 precision has collapsed so the middle is not strictly between the neighbors
+/// (it would collide with one of them), the gap is below `REBALANCE_EPSILON`, or an
+/// edge weight is past `MIN_LEADING_WEIGHT` / `MAX_TRAILING_WEIGHT`. Out-of-order
+/// neighbors also yield None. Callers rebalance the siblings and try again.
+pub fn checked_middle(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    let middle = calculate_middle(before, after);
+    let has_room = match (before, after) {
+        (None, None) => true,
+        (None, Some(a)) => a >= MIN_LEADING_WEIGHT && middle > 0.0 && middle < a,
+        (Some(b), None) => b <= MAX_TRAILING_WEIGHT && middle > b,
+        (Some(b), Some(a)) => b < middle && middle < a && !needs_rebalancing(b, a),
+    };
+    (has_room && middle.is_finite()).then_some(middle)
+}
+
 /// Calculate order weights for multiple block insertions with even distribution
 pub fn calculate_between(before: Option<f64>, after: Option<f64>, count: usize) -> Vec<f64> {
     let start = before.unwrap_or(0.0);
@@ -19,7 +45,7 @@ pub fn calculate_between(before: Option<f64>, after: Option<f64>, count: usize)
 
 /// Check if rebalancing is needed due to floating point precision limits
 pub fn needs_rebalancing(before: f64, after: f64) -> bool {
-    (after - before).abs() < 1e-10
+    (after - before).abs() < REBALANCE_EPSILON
 }
 
 /// Generate a fresh set of order weights for rebalancing
@@ -27,6 +53,127 @@ pub fn rebalance_order_weights(count: usize) -> Vec<f64> {
     (1..=count).map(|i| i as f64).collect()
 }
 
+/// Digits of string order keys, in ascending byte order so that SQLite's default
+/// BINARY collation sorts keys the same way `str` comparison does
+const KEY_DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Whether `key` is a usable order key: non-empty, made of `KEY_DIGITS`, and not
+/// ending in the zero digit (nothing sorts between "1" and "10")
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && !key.ends_with('0') && key.bytes().all(|b| KEY_DIGITS.contains(&b))
+}
+
+/// A string order key strictly between `before` and `after` (`None` for an open
+/// end). Unlike f64 weights there is always room between two keys, so siblings
+/// never need rebalancing; keys just grow by a digit every few nested inserts.
+/// Appending or prepending steps the leading digits instead of halving the open
+/// range, so a long run of appends grows keys by one digit per ~30 blocks.
+/// Returns None for invalid or out-of-order neighbors.
+pub fn key_between(before: Option<&str>, after: Option<&str>) -> Option<String> {
+    if before.is_some_and(|key| !is_valid_key(key)) || after.is_some_and(|key| !is_valid_key(key)) {
+        return None;
+    }
+
+    let key = match (before, after) {
+        (Some(before), Some(after)) if before >= after => return None,
+        (Some(before), Some(after)) => midpoint(before.as_bytes(), Some(after.as_bytes())),
+        (Some(before), None) => increment(before.as_bytes()),
+        (None, Some(after)) => decrement(after.as_bytes()),
+        (None, None) => midpoint(&[], None),
+    };
+    String::from_utf8(key).ok()
+}
+
+/// `count` ascending keys strictly between `before` and `after`, spread by
+/// bisection so their length grows with log(count)
+pub fn keys_between(
+    before: Option<&str>,
+    after: Option<&str>,
+    count: usize,
+) -> Option<Vec<String>> {
+    if count == 0 {
+        return Some(Vec::new());
+    }
+    let middle = key_between(before, after)?;
+    let left = (count - 1) / 2;
+    let mut keys = keys_between(before, Some(&middle), left)?;
+    let right = keys_between(Some(&middle), after, count - 1 - left)?;
+    keys.push(middle);
+    keys.extend(right);
+    Some(keys)
+}
+
+/// The shortest key after `key`: bump its first digit below the top one, or
+/// extend an all-top key
+fn increment(key: &[u8]) -> Vec<u8> {
+    let top = KEY_DIGITS[KEY_DIGITS.len() - 1];
+    match key.iter().position(|&digit| digit != top) {
+        Some(i) => {
+            let mut next = key[..i].to_vec();
+            next.push(KEY_DIGITS[digit_value(key[i]) + 1]);
+            next
+        }
+        None => {
+            let mut next = key.to_vec();
+            next.extend(midpoint(&[], None));
+            next
+        }
+    }
+}
+
+/// A short key before `key`: lower its first non-zero digit, or go one digit
+/// deeper when that digit is already the lowest non-zero one
+fn decrement(key: &[u8]) -> Vec<u8> {
+    // Valid keys end in a non-zero digit, so there always is one
+    let i = key
+        .iter()
+        .position(|&digit| digit != KEY_DIGITS[0])
+        .unwrap_or(key.len() - 1);
+    let mut previous = key[..i].to_vec();
+    match digit_value(key[i]) {
+        value if value > 1 => previous.push(KEY_DIGITS[value - 1]),
+        _ => {
+            previous.push(KEY_DIGITS[0]);
+            previous.extend(midpoint(&[], None));
+        }
+    }
+    previous
+}
+
+fn digit_value(digit: u8) -> usize {
+    KEY_DIGITS.iter().position(|&d| d == digit).unwrap_or(0)
+}
+
+/// Digits sorting strictly between `a` (may be empty, the lowest key) and `b`
+/// (`None` for no upper bound); `a < b` and neither ends in the zero digit
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> Vec<u8> {
+    if let Some(b) = b {
+        // Shared prefix, reading missing digits of `a` as zeros
+        let shared = b
+            .iter()
+            .enumerate()
+            .take_while(|(i, &digit)| a.get(*i).copied().unwrap_or(KEY_DIGITS[0]) == digit)
+            .count();
+        if shared > 0 {
+            let mut key = b[..shared].to_vec();
+            key.extend(midpoint(a.get(shared..).unwrap_or(&[]), Some(&b[shared..])));
+            return key;
+        }
+    }
+
+    let digit_a = a.first().map_or(0, |&d| digit_value(d));
+    let digit_b = b.map_or(KEY_DIGITS.len(), |b| digit_value(b[0]));
+    if digit_b - digit_a > 1 {
+        vec![KEY_DIGITS[(digit_a + digit_b).div_ceil(2)]]
+    } else if let Some(b) = b.filter(|b| b.len() > 1) {
+        vec![b[0]]
+    } else {
+        let mut key = vec![KEY_DIGITS[digit_a]];
+        key.extend(midpoint(a.get(1..).unwrap_or(&[]), None));
+        key
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +198,26 @@ mod tests {
         assert_eq!(calculate_middle(Some(1.0), Some(2.0)), 1.5);
     }
 
+    #[test]
+    fn test_checked_middle_detects_precision_collapse() {
+        assert_eq!(checked_middle(None, None), Some(1.0));
+        assert_eq!(checked_middle(Some(1.0), Some(2.0)), Some(1.5));
+        assert_eq!(checked_middle(None, Some(4.0)), Some(2.0));
+        assert_eq!(checked_middle(Some(3.0), None), Some(4.0));
+
+        // Adjacent f64 values: the plain middle collides with a neighbor
+        let before: f64 = 1e12;
+        let after = f64::from_bits(before.to_bits() + 1);
+        let middle = calculate_middle(Some(before), Some(after));
+        assert!(middle == before || middle == after);
+        assert_eq!(checked_middle(Some(before), Some(after)), None);
+
+        assert_eq!(checked_middle(Some(1.0), Some(1.0 + 1e-12)), None);
+        assert_eq!(checked_middle(None, Some(MIN_LEADING_WEIGHT / 2.0)), None);
+        assert_eq!(checked_middle(Some(MAX_TRAILING_WEIGHT * 2.0), None), None);
+        assert_eq!(checked_middle(Some(2.0), Some(1.0)), None);
+    }
+
     #[test]
     fn test_calculate_between() {
         let result = calculate_between(Some(1.0), Some(2.0), 1);
@@ -64,6 +231,53 @@ mod tests {
         assert_eq!(result, vec![1.0, 2.0, 3.0]);
     }
 
+    #[test]
+    fn test_key_between() {
+        assert_eq!(key_between(None, None).as_deref(), Some("V"));
+        assert_eq!(key_between(Some("V"), None).as_deref(), Some("W"));
+        assert_eq!(key_between(Some("zz3"), None).as_deref(), Some("zz4"));
+        assert_eq!(key_between(Some("zz"), None).as_deref(), Some("zzV"));
+        assert_eq!(key_between(None, Some("V")).as_deref(), Some("U"));
+        assert_eq!(key_between(None, Some("1")).as_deref(), Some("0V"));
+        assert_eq!(key_between(Some("1"), Some("2")).as_deref(), Some("1V"));
+        assert_eq!(key_between(Some("1"), Some("105")).as_deref(), Some("103"));
+        assert_eq!(key_between(None, Some("01")).as_deref(), Some("00V"));
+        assert_eq!(key_between(None, Some("03")).as_deref(), Some("02"));
+
+        assert_eq!(key_between(Some("2"), Some("1")), None);
+        assert_eq!(key_between(Some("1"), Some("1")), None);
+        assert_eq!(key_between(Some("10"), None), None);
+        assert_eq!(key_between(Some(""), None), None);
+        assert_eq!(key_between(Some("a-b"), None), None);
+    }
+
+    #[test]
+    fn test_keys_never_run_out_of_room() {
+        // Repeatedly inserting right after the same key is where f64 weights collapse
+        let (low, mut high) = ("V".to_string(), "W".to_string());
+        for _ in 0..2000 {
+            let key = key_between(Some(&low), Some(&high)).unwrap();
+            assert!(low < key && key < high);
+            assert!(is_valid_key(&key));
+            high = key;
+        }
+
+        // Appending one block at a time grows keys slowly
+        let mut last = key_between(None, None).unwrap();
+        for _ in 0..1000 {
+            let key = key_between(Some(&last), None).unwrap();
+            assert!(key > last);
+            last = key;
+        }
+        assert!(last.len() <= 40);
+
+        let keys = keys_between(Some("1"), Some("2"), 100).unwrap();
+        assert_eq!(keys.len(), 100);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(keys.first().unwrap().as_str() > "1" && keys.last().unwrap().as_str() < "2");
+        assert!(keys.iter().all(|key| key.len() <= 4));
+    }
+
     #[test]
     fn test_needs_rebalancing() {
         assert!(needs_rebalancing(1.0, 1.0000000001));
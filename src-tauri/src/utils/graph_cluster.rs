@@ -0,0 +1,210 @@
+use crate::models::graph::{ClusterMode, GraphEdge, GraphNode};
+use std::collections::{BTreeSet, HashMap};
+
+/// Upper bound on label propagation rounds; most graphs settle in a handful
+const MAX_PROPAGATION_ROUNDS: usize = 20;
+
+/// Tag every node with a `cluster_id`. Links are treated as undirected and edges
+/// to nodes outside `nodes` are ignored. Cluster 0 is the largest cluster, ties broken
+/// by the position of their first node, so ids are stable for an unchanged graph.
+pub fn assign_clusters(nodes: &mut [GraphNode], edges: &[GraphEdge], mode: ClusterMode) {
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+
+    let mut neighbors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); nodes.len()];
+    for edge in edges {
+        let (Some(&a), Some(&b)) = (
+            index.get(edge.source.as_str()),
+            index.get(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        if a != b {
+            neighbors[a].insert(b);
+            neighbors[b].insert(a);
+        }
+    }
+
+    let labels = match mode {
+        ClusterMode::Components => connected_components(&neighbors),
+        ClusterMode::LabelPropagation => propagate_labels(&neighbors),
+    };
+
+    for (node, cluster_id) in nodes.iter_mut().zip(renumber_by_size(&labels)) {
+        node.cluster_id = Some(cluster_id);
+    }
+}
+
+/// Label of each node's component: the smallest node index in it
+fn connected_components(neighbors: &[BTreeSet<usize>]) -> Vec<usize> {
+    let mut labels = vec![usize::MAX; neighbors.len()];
+    for start in 0..neighbors.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+        labels[start] = start;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for &next in &neighbors[node] {
+                if labels[next] == usize::MAX {
+                    labels[next] = start;
+                    stack.push(next);
+                }
+            }
+        }
+    }
+    labels
+}
+
+/// Deterministic label propagation: nodes are visited in order and take the label
+/// with the most weight among their neighbors until nothing changes. A link weighs
+/// 1 plus the neighbors its ends share, so a lone bridge between two dense groups
+/// does not pull them together. Ties keep the current label if it is among them,
+/// otherwise the smallest label wins.
+fn propagate_labels(neighbors: &[BTreeSet<usize>]) -> Vec<usize> {
+    let weights: Vec<Vec<(usize, usize)>> = neighbors
+        .iter()
+        .map(|adjacent| {
+            adjacent
+                .iter()
+                .map(|&next| {
+                    let shared = adjacent.intersection(&neighbors[next]).count();
+                    (next, 1 + shared)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut labels: Vec<usize> = (0..neighbors.len()).collect();
+    for _ in 0..MAX_PROPAGATION_ROUNDS {
+        let mut changed = false;
+        for node in 0..neighbors.len() {
+            let mut totals: HashMap<usize, usize> = HashMap::new();
+            for &(next, weight) in &weights[node] {
+                *totals.entry(labels[next]).or_default() += weight;
+            }
+            let Some(&max) = totals.values().max() else {
+                continue;
+            };
+            let current = labels[node];
+            if totals.get(&current) == Some(&max) {
+                continue;
+            }
+            let best = totals
+                .iter()
+                .filter(|(_, &total)| total == max)
+                .map(|(&label, _)| label)
+                .min()
+                .unwrap_or(current);
+            labels[node] = best;
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    labels
+}
+
+/// Map raw labels to 0.. ordered by cluster size (largest first), then first node
+fn renumber_by_size(labels: &[usize]) -> Vec<usize> {
+    let mut clusters: Vec<(usize, usize, usize)> = Vec::new(); // (label, size, first node)
+    let mut position: HashMap<usize, usize> = HashMap::new();
+    for (node, &label) in labels.iter().enumerate() {
+        match position.get(&label) {
+            Some(&i) => clusters[i].1 += 1,
+            None => {
+                position.insert(label, clusters.len());
+                clusters.push((label, 1, node));
+            }
+        }
+    }
+    clusters.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
+
+    let ids: HashMap<usize, usize> = clusters
+        .iter()
+        .enumerate()
+        .map(|(id, (label, _, _))| (*label, id))
+        .collect();
+    labels.iter().map(|label| ids[label]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            label: id.to_string(),
+            node_type: "page".to_string(),
+            page_id: id.to_string(),
+            block_id: None,
+            cluster_id: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> GraphEdge {
+        GraphEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            relation_type: "page_link".to_string(),
+            is_embed: false,
+            weight: 1,
+            is_bidirectional: false,
+        }
+    }
+
+    fn cluster_ids(nodes: &[GraphNode]) -> Vec<usize> {
+        nodes.iter().map(|n| n.cluster_id.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_components_are_numbered_by_size() {
+        let mut nodes: Vec<GraphNode> = ["a", "b", "c", "d", "e", "f"]
+            .into_iter()
+            .map(node)
+            .collect();
+        let edges = vec![
+            edge("a", "b"),
+            edge("c", "d"),
+            edge("e", "d"),
+            edge("f", "f"),
+            edge("a", "missing"),
+        ];
+
+        assign_clusters(&mut nodes, &edges, ClusterMode::Components);
+        assert_eq!(cluster_ids(&nodes), vec![1, 1, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn test_label_propagation_splits_loosely_joined_groups() {
+        let mut nodes: Vec<GraphNode> = ["a1", "a2", "a3", "a4", "b1", "b2", "b3", "b4"]
+            .into_iter()
+            .map(node)
+            .collect();
+        // Two 4-cliques joined by a single bridge a4 - b1
+        let mut edges = Vec::new();
+        for group in [["a1", "a2", "a3", "a4"], ["b1", "b2", "b3", "b4"]] {
+            for i in 0..group.len() {
+                for j in i + 1..group.len() {
+                    edges.push(edge(group[i], group[j]));
+                }
+            }
+        }
+        edges.push(edge("a4", "b1"));
+
+        let mut components = nodes.clone();
+        assign_clusters(&mut components, &edges, ClusterMode::Components);
+        assert!(cluster_ids(&components).iter().all(|&id| id == 0));
+
+        assign_clusters(&mut nodes, &edges, ClusterMode::LabelPropagation);
+        let ids = cluster_ids(&nodes);
+        assert!(ids[..4].iter().all(|&id| id == ids[0]));
+        assert!(ids[4..].iter().all(|&id| id == ids[4]));
+        assert_ne!(ids[0], ids[4]);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::models::block::{Block, BlockType};
+use crate::utils::path::normalize_page_path;
+use crate::utils::UrlValidator;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Render a page as a standalone HTML document.
+///
+/// Blocks become nested `<ul>`/`<li>` lists in order_weight order; a block with
+/// children wraps them in `<details>`, left closed when the block is collapsed.
+/// `page_anchors` maps exported pages (see `page_anchor_key`) to the element id they
+/// are rendered under: `[[wiki]]` links to those pages become `#id` anchors, while
+/// links to pages outside the export are kept as plain text.
+pub fn blocks_to_html(
+    title: &str,
+    anchor: &str,
+    blocks: &[Block],
+    page_anchors: &HashMap<String, String>,
+) -> String {
+    let mut children_map: HashMap<Option<String>, Vec<&Block>> = HashMap::new();
+    for block in blocks {
+        children_map
+            .entry(block.parent_id.clone())
+            .or_default()
+            .push(block);
+    }
+    for children in children_map.values_mut() {
+        children.sort_by(|a, b| a.cmp_position(b));
+    }
+
+    let title = escape_html(title);
+    let mut output = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    output.push_str(&format!("<title>{}</title>\n</head>\n<body>\n", title));
+    output.push_str(&format!(
+        "<article id=\"{}\">\n<h1>{}</h1>\n",
+        escape_html(anchor),
+        title
+    ));
+    render_list(&children_map, None, page_anchors, &mut output);
+    output.push_str("</article>\n</body>\n</html>\n");
+    output
+}
+
+/// Key under which a page is looked up when resolving `[[target]]`: the
+/// normalized, case-folded page path or title
+pub fn page_anchor_key(target: &str) -> String {
+    normalize_page_path(target).to_lowercase()
+}
+
+fn render_list(
+    children_map: &HashMap<Option<String>, Vec<&Block>>,
+    parent_id: Option<String>,
+    page_anchors: &HashMap<String, String>,
+    output: &mut String,
+) {
+    let Some(children) = children_map.get(&parent_id) else {
+        return;
+    };
+
+    output.push_str("<ul>\n");
+    for block in children {
+        let body = render_block_body(block, page_anchors);
+        if children_map.contains_key(&Some(block.id.clone())) {
+            let open = if block.is_collapsed { "" } else { " open" };
+            output.push_str(&format!(
+                "<li><details{}><summary>{}</summary>\n",
+                open, body
+            ));
+            render_list(children_map, Some(block.id.clone()), page_anchors, output);
+            output.push_str("</details></li>\n");
+        } else {
+            output.push_str(&format!("<li>{}</li>\n", body));
+        }
+    }
+    output.push_str("</ul>\n");
+}
+
+fn render_block_body(block: &Block, page_anchors: &HashMap<String, String>) -> String {
+    match block.block_type {
+        BlockType::Code => {
+            let class = block
+                .language
+                .as_deref()
+                .filter(|lang| !lang.is_empty())
+                .map(|lang| format!(" class=\"language-{}\"", escape_html(lang)))
+                .unwrap_or_default();
+            format!(
+                "<pre><code{}>{}</code></pre>",
+                class,
+                escape_html(&block.content)
+            )
+        }
+        BlockType::Fence => format!("<pre>{}</pre>", escape_html(&block.content)),
+        BlockType::Heading => {
+            let level = block
+                .language
+                .as_deref()
+                .and_then(|l| l.parse::<u8>().ok())
+                .unwrap_or(1)
+                .clamp(1, 6);
+            format!(
+                "<h{}>{}</h{}>",
+                level,
+                render_inline(&block.content, page_anchors),
+                level
+            )
+        }
+        _ => block
+            .content
+            .lines()
+            .map(|line| render_inline(line, page_anchors))
+            .collect::<Vec<_>>()
+            .join("<br>"),
+    }
+}
+
+/// Convert inline markdown (code, wiki links, links, bold, italic) to HTML, escaping
+/// everything else
+pub fn render_inline(text: &str, page_anchors: &HashMap<String, String>) -> String {
+    static INLINE_RE: OnceLock<Regex> = OnceLock::new();
+    let inline_re = INLINE_RE.get_or_init(|| {
+        Regex::new(concat!(
+            r"`(?P<code>[^`]+)`",
+            r"|!?\[\[(?P<target>[^\]|]+)(?:\|(?P<alias>[^\]]*))?\]\]",
+            // One level of balanced parentheses, as in `Rust_(language)`
+            r"|\[(?P<text>[^\]]+)\]\((?P<url>(?:[^()\s]|\([^()\s]*\))+)\)",
+            r"|\*\*(?P<bold>.+?)\*\*",
+            r"|\*(?P<star>[^*\s][^*]*)\*",
+            r"|\b_(?P<under>[^_]+)_\b",
+        ))
+        .expect("valid inline markdown regex")
+    });
+
+    let mut output = String::new();
+    let mut last = 0;
+    for caps in inline_re.captures_iter(text) {
+        let whole = caps.get(0).expect("match");
+        output.push_str(&escape_html(&text[last..whole.start()]));
+        output.push_str(&render_inline_match(&caps, page_anchors));
+        last = whole.end();
+    }
+    output.push_str(&escape_html(&text[last..]));
+    output
+}
+
+fn render_inline_match(caps: &Captures, page_anchors: &HashMap<String, String>) -> String {
+    if let Some(code) = caps.name("code") {
+        return format!("<code>{}</code>", escape_html(code.as_str()));
+    }
+    if let Some(target) = caps.name("target") {
+        let label = caps.name("alias").unwrap_or(target).as_str();
+        return match page_anchors.get(&page_anchor_key(target.as_str())) {
+            Some(anchor) => format!(
+                "<a href=\"#{}\">{}</a>",
+                escape_html(anchor),
+                escape_html(label)
+            ),
+            None => escape_html(label),
+        };
+    }
+    if let (Some(text), Some(url)) = (caps.name("text"), caps.name("url")) {
+        let text = render_inline(text.as_str(), page_anchors);
+        return if is_safe_href(url.as_str()) {
+            format!("<a href=\"{}\">{}</a>", escape_html(url.as_str()), text)
+        } else {
+            text
+        };
+    }
+    if let Some(bold) = caps.name("bold") {
+        return format!(
+            "<strong>{}</strong>",
+            render_inline(bold.as_str(), page_anchors)
+        );
+    }
+    let italic = caps.name("star").or_else(|| caps.name("under"));
+    match italic {
+        Some(italic) => format!("<em>{}</em>", render_inline(italic.as_str(), page_anchors)),
+        None => escape_html(&caps[0]),
+    }
+}
+
+/// Links with a scheme must pass `UrlValidator`; relative links are always allowed
+fn is_safe_href(url: &str) -> bool {
+    let has_scheme = url
+        .split_once(':')
+        .is_some_and(|(scheme, _)| !scheme.is_empty() && !scheme.contains(['/', '?', '#']));
+    !has_scheme || UrlValidator::validate(url).is_ok()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, parent_id: Option<&str>, content: &str, order_weight: f64) -> Block {
+        Block {
+            id: id.to_string(),
+            page_id: "page".to_string(),
+            parent_id: parent_id.map(|p| p.to_string()),
+            content: content.to_string(),
+            order_weight,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Bullet,
+            language: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_html_hierarchy_collapse_and_code() {
+        let mut collapsed = block("a", None, "Parent", 1.0);
+        collapsed.is_collapsed = true;
+        let mut code = block("c", None, "if a < b {}", 2.0);
+        code.block_type = BlockType::Code;
+        code.language = Some("rust".to_string());
+        let blocks = vec![code, block("a1", Some("a"), "Child", 1.0), collapsed];
+
+        let html = blocks_to_html("Notes", "notes", &blocks, &HashMap::new());
+
+        assert!(html.contains("<article id=\"notes\">\n<h1>Notes</h1>\n"));
+        assert!(html.contains(
+            "<ul>\n\
+             <li><details><summary>Parent</summary>\n\
+             <ul>\n<li>Child</li>\n</ul>\n\
+             </details></li>\n\
+             <li><pre><code class=\"language-rust\">if a &lt; b {}</code></pre></li>\n\
+             </ul>\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_inline_markdown_and_escaping() {
+        let anchors = HashMap::from([(page_anchor_key("Projects/Plan"), "plan".to_string())]);
+
+        assert_eq!(
+            render_inline("**bold** and *it* and _em_ in snake_case_name", &anchors),
+            "<strong>bold</strong> and <em>it</em> and <em>em</em> in snake_case_name"
+        );
+        assert_eq!(
+            render_inline("See [[projects/plan|the plan]] and [[Elsewhere]]", &anchors),
+            "See <a href=\"#plan\">the plan</a> and Elsewhere"
+        );
+        assert_eq!(
+            render_inline("[site](https://example.com/?a=1&b=2) `<b>`", &anchors),
+            "<a href=\"https://example.com/?a=1&amp;b=2\">site</a> <code>&lt;b&gt;</code>"
+        );
+        // Script URLs and raw HTML never reach the output as markup
+        assert_eq!(
+            render_inline("[x](javascript:alert(1)) <script>\"</script>", &anchors),
+            "x &lt;script&gt;&quot;&lt;/script&gt;"
+        );
+        assert_eq!(
+            render_inline(
+                "[Rust](https://en.wikipedia.org/wiki/Rust_(language)).",
+                &anchors
+            ),
+            "<a href=\"https://en.wikipedia.org/wiki/Rust_(language)\">Rust</a>."
+        );
+    }
+}
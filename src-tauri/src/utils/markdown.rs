@@ -1,15 +1,19 @@
 use crate::commands::block::block_type_to_string;
 use crate::models::block::{Block, BlockType};
 use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use uuid::Uuid;
 
 /// I4 Migration Strategy: Canonical markdown format
-/// Current implementation uses bullet-only format (Option A from handoff notes):
-/// - All blocks serialize as "- content" (bullets)
-/// - Headings (# ) are parsed into bullet blocks for backward compatibility
-/// - This avoids heading loss when round-tripping through parser/serializer
-/// - Future: implement BlockType::Heading for proper heading support (Option B)
+/// Blocks serialize as bullets ("- content"), except headings (Option B from handoff notes):
+/// - Heading blocks serialize as "## content" at the block's indent, level 1-6, always on
+///   one line (content line breaks become spaces)
+/// - The heading level is stored in the block's `language` field ("1".."6")
+/// - Heading lines are parsed back into `BlockType::Heading` blocks at the same depth and
+///   take the same hidden ID marker / metadata lines as bullets
 ///
 /// Hidden Block IDs (Logseq-style, internal only)
 /// - When serializing, we append a hidden marker line directly under each bullet block:
@@ -26,10 +30,34 @@ use uuid::Uuid;
 /// - Metadata lines are consumed during parsing and stored in block.metadata HashMap
 /// - During serialization, metadata is written after the ID marker line
 /// - Metadata lines are not shown to users in the UI (like ID markers)
+///
+/// Task bullets
+/// - "- [ ] content" / "- [x] content" parse into a bullet with `checked::false` / `checked::true`
+///   in its metadata; the checkbox prefix is not part of the content
+/// - The checked state is serialized only as the prefix, never as a metadata line
+/// - A plain bullet whose content itself starts with "[ ]" or "[x]" is escaped as "- \[ ] ..."
+///
+/// Multi-line content
+/// - Content lines after the first are written at the bullet's indent, before the ID marker
+/// - A content line that looks like a bullet ("- item") or heading ("# item") is escaped as
+///   "\- item" / "\# item" so it is not reparsed as a separate block; parsing removes the escape
+///
+/// Code and fence blocks
+/// - Code blocks serialize as a "```lang" fence and fence blocks as "///" delimiters, with every
+///   content line at the block's indent; parsing turns each region back into one block, keeping
+///   blank lines and the language
+/// - The hidden ID marker and metadata lines follow the closing fence at the block's body indent
+///
+/// Page frontmatter
+/// - A leading YAML block between "---" lines holds page-level metadata; it is not parsed
+///   into blocks, and rewriting a page file from its blocks keeps the file's frontmatter
 
 const ID_MARKER_PREFIX: &str = "ID::";
 const METADATA_PATTERN: &str = "::";
 
+/// Metadata key holding a task bullet's checkbox state ("true" / "false")
+pub const CHECKED_METADATA_KEY: &str = "checked";
+
 fn is_id_marker_line(trimmed: &str) -> bool {
     trimmed.starts_with(ID_MARKER_PREFIX) && trimmed[ID_MARKER_PREFIX.len()..].trim().len() > 0
 }
@@ -85,14 +113,585 @@ pub fn sanitize_content_for_markdown(content: &str) -> String {
         if trimmed.starts_with(ID_MARKER_PREFIX) {
             out.push('\u{200B}');
         }
+        // A later line reading "- item", "# item" or opening a ``` / /// fence would be reparsed
+        // as a separate block; escape it as "\- item" (adding one more backslash to
+        // already-escaped lines so this round-trips).
+        if i > 0 && is_block_start_like(trimmed) {
+            out.push_str(&line[..line.len() - trimmed.len()]);
+            out.push('\\');
+            out.push_str(trimmed);
+            continue;
+        }
         out.push_str(line);
     }
     out
 }
 
+/// "- item", "# item" or a ``` / /// fence line, optionally preceded by backslashes
+/// from escaping
+fn is_block_start_like(trimmed: &str) -> bool {
+    let unescaped = trimmed.trim_start_matches('\\');
+    unescaped.starts_with("- ")
+        || parse_heading_line(unescaped).is_some()
+        || unescaped.starts_with("```")
+        || unescaped.starts_with("///")
+}
+
+/// Split a heading line ("## Title") into its level and text.
+/// Requires 1-6 `#` followed by a space (or nothing), so "#tag" is not a heading.
+fn parse_heading_line(trimmed: &str) -> Option<(usize, &str)> {
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim()))
+}
+
+/// Split a leading task checkbox ("[ ] ", "[x] ") off bullet text
+fn split_checkbox(text: &str) -> Option<(bool, &str)> {
+    let checked = match text.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let rest = &text[3..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((checked, rest.strip_prefix(' ').unwrap_or(rest)))
+}
+
+/// Checkbox text, optionally preceded by backslashes from escaping
+fn is_checkbox_like(text: &str) -> bool {
+    split_checkbox(text.trim_start_matches('\\')).is_some()
+}
+
+/// Checkbox state stored in block metadata, if the block is a task
+pub(crate) fn checked_state(metadata: &HashMap<String, String>) -> Option<bool> {
+    metadata
+        .get(CHECKED_METADATA_KEY)
+        .map(|value| parse_checked_value(value))
+}
+
+/// Interpret a stored `checked` metadata value
+pub(crate) fn parse_checked_value(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("true")
+}
+
+/// First line of a bullet: "- content", or "- [ ] content" / "- [x] content" for tasks.
+/// Plain content that starts like a checkbox gets one more leading backslash.
+pub(crate) fn bullet_first_line(checked: Option<bool>, first: &str) -> String {
+    match checked {
+        Some(true) => format!("- [x] {}", first),
+        Some(false) => format!("- [ ] {}", first),
+        None if is_checkbox_like(first) => format!("- \\{}", first),
+        None => format!("- {}", first),
+    }
+}
+
+/// Heading level stored in a block's `language` field, defaulting to 1
+fn heading_level(block: &Block) -> usize {
+    block
+        .language
+        .as_deref()
+        .and_then(|l| l.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, 6)
+}
+
+/// Undo the escaping `sanitize_content_for_markdown` applies to a continuation line
+fn unescape_continuation_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('\\') && is_block_start_like(trimmed) {
+        format!("{}{}", &line[..line.len() - trimmed.len()], &trimmed[1..])
+    } else {
+        line.to_string()
+    }
+}
+
+/// Optional, per-workspace content clean-up rules applied when a block is saved.
+/// All rules are off by default. Code and fence blocks are never modified, and
+/// fenced/inline code inside bullet content is left untouched.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SanitizationRules {
+    /// Strip trailing spaces/tabs from every line
+    pub trim_trailing_whitespace: bool,
+    /// Replace typographic quotes (‘ ’ “ ”) with their ASCII equivalents
+    pub normalize_smart_quotes: bool,
+    /// Collapse runs of blank lines into a single blank line
+    pub collapse_blank_lines: bool,
+}
+
+impl SanitizationRules {
+    pub fn is_empty(&self) -> bool {
+        !self.trim_trailing_whitespace && !self.normalize_smart_quotes && !self.collapse_blank_lines
+    }
+}
+
+/// Apply workspace sanitization rules to block content before it is stored.
+pub fn apply_sanitization_rules(
+    content: &str,
+    rules: &SanitizationRules,
+    block_type: &BlockType,
+) -> String {
+    if rules.is_empty() || matches!(block_type, BlockType::Code | BlockType::Fence) {
+        return content.to_string();
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_code_fence = false;
+    let mut previous_blank = false;
+
+    for line in content.split('\n') {
+        let is_fence_marker = line.trim_start().starts_with("```");
+        if in_code_fence || is_fence_marker {
+            if is_fence_marker {
+                in_code_fence = !in_code_fence;
+            }
+            lines.push(line.to_string());
+            previous_blank = false;
+            continue;
+        }
+
+        let mut out = if rules.normalize_smart_quotes {
+            normalize_smart_quotes_outside_code(line)
+        } else {
+            line.to_string()
+        };
+        if rules.trim_trailing_whitespace {
+            out.truncate(out.trim_end_matches([' ', '\t']).len());
+        }
+
+        let is_blank = out.trim().is_empty();
+        if rules.collapse_blank_lines && is_blank && previous_blank {
+            continue;
+        }
+        previous_blank = is_blank;
+        lines.push(out);
+    }
+
+    lines.join("\n")
+}
+
+/// Replace smart quotes, skipping inline `code` spans
+fn normalize_smart_quotes_outside_code(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_inline_code = false;
+    for c in line.chars() {
+        if c == '`' {
+            in_inline_code = !in_inline_code;
+        }
+        match c {
+            '\u{2018}' | '\u{2019}' if !in_inline_code => out.push('\''),
+            '\u{201C}' | '\u{201D}' if !in_inline_code => out.push('"'),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// How nesting is written in page files: `width` spaces per level (2 by default),
+/// or one tab per level when `use_tabs` is set
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndentStyle {
+    pub width: usize,
+    pub use_tabs: bool,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            use_tabs: false,
+        }
+    }
+}
+
+impl IndentStyle {
+    /// Number of indent characters making up one level
+    pub fn unit_len(&self) -> usize {
+        if self.use_tabs {
+            1
+        } else {
+            self.width.max(1)
+        }
+    }
+
+    /// `len` indent characters: tabs or spaces
+    pub fn whitespace(&self, len: usize) -> String {
+        if self.use_tabs {
+            "\t".repeat(len)
+        } else {
+            " ".repeat(len)
+        }
+    }
+
+    /// Leading whitespace for a line at `depth`
+    pub fn indent(&self, depth: usize) -> String {
+        self.whitespace(depth * self.unit_len())
+    }
+
+    /// Nesting depth of a line. A tab counts as a full level in either style, so files
+    /// mixing tabs and spaces still nest the way they look.
+    pub fn depth_of(&self, line: &str) -> usize {
+        let width = self.width.max(1);
+        let columns: usize = line
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { width } else { 1 })
+            .sum();
+        columns / width
+    }
+
+    /// Style a page file is actually indented with, read from its first nested bullet
+    /// or `ID::` line (outside code and fence regions): one tab per level if it starts
+    /// with a tab, otherwise its run of spaces. Files written before the workspace
+    /// setting changed keep parsing the way they look; `fallback` applies when
+    /// nothing in the file is nested.
+    pub fn detect(content: &str, fallback: IndentStyle) -> IndentStyle {
+        let (_, body) = split_frontmatter(content);
+        let mut fence: Option<&str> = None;
+        for line in body.lines() {
+            let trimmed = line.trim_start();
+            if let Some(marker) = fence {
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+                continue;
+            }
+            if trimmed.starts_with("```") || trimmed.starts_with("///") {
+                fence = Some(&trimmed[..3]);
+                continue;
+            }
+            if trimmed.len() == line.len()
+                || !(trimmed.starts_with("- ") || is_id_marker_line(trimmed))
+            {
+                continue;
+            }
+
+            return if line.starts_with('\t') {
+                IndentStyle {
+                    use_tabs: true,
+                    ..fallback
+                }
+            } else {
+                IndentStyle {
+                    width: line.len() - line.trim_start_matches(' ').len(),
+                    use_tabs: false,
+                }
+            };
+        }
+        fallback
+    }
+}
+
 /// Convert blocks to markdown string
-pub fn blocks_to_markdown(blocks: &[Block]) -> String {
-    // Group by parent
+pub fn blocks_to_markdown(blocks: &[Block], indent: IndentStyle) -> String {
+    let children_map = group_children(blocks);
+
+    let mut output = String::new();
+    render_blocks(&children_map, None, 0, indent, true, &mut output);
+
+    output
+}
+
+/// Convert blocks to markdown for sharing: same nesting and code fences as
+/// `blocks_to_markdown`, but without the hidden `ID::` and `key::value` lines.
+/// With `frontmatter`, block metadata is collected into a YAML frontmatter block
+/// instead; a key set to different values on several blocks lists each value once.
+pub fn blocks_to_plain_markdown(
+    blocks: &[Block],
+    indent: IndentStyle,
+    frontmatter: bool,
+) -> String {
+    let children_map = group_children(blocks);
+
+    let mut body = String::new();
+    render_blocks(&children_map, None, 0, indent, false, &mut body);
+
+    if !frontmatter {
+        return body;
+    }
+
+    // Collect in document order so repeated keys list their values as they appear
+    let mut entries: Vec<(String, Vec<String>)> = Vec::new();
+    collect_metadata(&children_map, None, &mut entries);
+    if entries.is_empty() {
+        return body;
+    }
+
+    let mut output = String::from("---\n");
+    for (key, values) in &entries {
+        let key = yaml_scalar(key);
+        if let [value] = values.as_slice() {
+            output.push_str(&format!("{}: {}\n", key, yaml_scalar(value)));
+        } else {
+            output.push_str(&format!("{}:\n", key));
+            for value in values {
+                output.push_str(&format!("  - {}\n", yaml_scalar(value)));
+            }
+        }
+    }
+    output.push_str("---\n\n");
+    output.push_str(&body);
+    output
+}
+
+/// Convert the subtree rooted at `root_id` to plain markdown (no `ID::` or metadata
+/// lines), with the root at depth 0. `blocks` holds the root and its descendants;
+/// collapsed blocks keep all of their children.
+pub fn subtree_to_plain_markdown(blocks: &[Block], root_id: &str, indent: IndentStyle) -> String {
+    let Some(root) = blocks.iter().find(|block| block.id == root_id) else {
+        return String::new();
+    };
+    let mut children_map = group_children(blocks);
+    // Keep only the root at the top level, whatever else shares its parent
+    children_map.insert(root.parent_id.clone(), vec![root]);
+
+    let mut output = String::new();
+    render_blocks(
+        &children_map,
+        root.parent_id.clone(),
+        0,
+        indent,
+        false,
+        &mut output,
+    );
+    output
+}
+
+fn collect_metadata(
+    children_map: &HashMap<Option<String>, Vec<&Block>>,
+    parent_id: Option<String>,
+    entries: &mut Vec<(String, Vec<String>)>,
+) {
+    let Some(children) = children_map.get(&parent_id) else {
+        return;
+    };
+
+    for block in children {
+        let mut keys: Vec<&String> = block
+            .metadata
+            .keys()
+            .filter(|key| key.as_str() != CHECKED_METADATA_KEY)
+            .collect();
+        keys.sort();
+        for key in keys {
+            let value = &block.metadata[key];
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some((_, values)) if !values.contains(value) => values.push(value.clone()),
+                Some(_) => {}
+                None => entries.push((key.clone(), vec![value.clone()])),
+            }
+        }
+        collect_metadata(children_map, Some(block.id.clone()), entries);
+    }
+}
+
+/// A YAML scalar: plain when unambiguous, otherwise double-quoted
+fn yaml_scalar(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.' | '/'))
+        && !value.starts_with([' ', '-'])
+        && !value.ends_with(' ')
+        && !matches!(
+            value.to_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "null" | "on" | "off" | "~"
+        )
+        && value.parse::<f64>().is_err();
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Split a leading YAML frontmatter block off page markdown. The file must start with
+/// a `---` line and the block runs to the next `---` line; the returned frontmatter
+/// includes both delimiter lines. Without a closing line the whole text is the body.
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut end = content.len() - rest.len();
+    for line in rest.split_inclusive('\n') {
+        end += line.len();
+        if line.trim_end() == "---" {
+            return (Some(&content[..end]), &content[end..]);
+        }
+    }
+    (None, content)
+}
+
+/// Read the `key: value` pairs of a frontmatter block from `split_frontmatter`, in
+/// file order. Quoted scalars are unquoted; a list (`key:` followed by `- item` lines,
+/// or `[a, b]`) is stored as a JSON array of strings. Comments, nested maps and
+/// other YAML are skipped.
+pub fn parse_frontmatter(frontmatter: &str) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut pending_list: Option<(String, Vec<String>)> = None;
+
+    fn flush(entries: &mut Vec<(String, String)>, pending: Option<(String, Vec<String>)>) {
+        if let Some((key, items)) = pending.filter(|(_, items)| !items.is_empty()) {
+            entries.push((key, serde_json::json!(items).to_string()));
+        }
+    }
+
+    for line in frontmatter.lines().skip(1) {
+        let trimmed = line.trim();
+        if trimmed == "---" {
+            break;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with([' ', '\t', '-']) {
+            if let (Some((_, items)), Some(item)) =
+                (pending_list.as_mut(), trimmed.strip_prefix("- "))
+            {
+                items.push(unquote_yaml(item.trim()));
+            }
+            continue;
+        }
+
+        flush(&mut entries, pending_list.take());
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = unquote_yaml(key.trim());
+        let value = value.trim();
+        if value.is_empty() {
+            pending_list = Some((key, Vec::new()));
+        } else if let Some(inline) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let items: Vec<String> = inline
+                .split(',')
+                .map(|item| unquote_yaml(item.trim()))
+                .filter(|item| !item.is_empty())
+                .collect();
+            entries.push((key, serde_json::json!(items).to_string()));
+        } else {
+            entries.push((key, unquote_yaml(value)));
+        }
+    }
+    flush(&mut entries, pending_list);
+
+    entries
+}
+
+/// Value of `key` in the page's frontmatter, as `parse_frontmatter` reads it
+pub fn frontmatter_value(content: &str, key: &str) -> Option<String> {
+    let frontmatter = split_frontmatter(content).0?;
+    parse_frontmatter(frontmatter)
+        .into_iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+/// Frontmatter block for `key: value` entries as `parse_frontmatter` returns them
+/// (JSON arrays become lists), delimiters included; None without entries
+pub fn render_frontmatter(entries: &[(String, String)]) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+    let mut output = String::from("---\n");
+    for (key, value) in entries {
+        let items = value
+            .starts_with('[')
+            .then(|| serde_json::from_str::<Vec<String>>(value).ok())
+            .flatten();
+        match items {
+            Some(items) => {
+                output.push_str(&format!("{}:\n", yaml_scalar(key)));
+                for item in items {
+                    output.push_str(&format!("  - {}\n", yaml_scalar(&item)));
+                }
+            }
+            None => output.push_str(&format!("{}: {}\n", yaml_scalar(key), yaml_scalar(value))),
+        }
+    }
+    output.push_str("---\n");
+    Some(output)
+}
+
+/// Page markdown with `frontmatter` (from `split_frontmatter` or `render_frontmatter`)
+/// in front of `body`
+pub fn prepend_frontmatter(frontmatter: Option<&str>, body: &str) -> String {
+    match frontmatter {
+        Some(frontmatter) if frontmatter.ends_with('\n') => format!("{}{}", frontmatter, body),
+        Some(frontmatter) => format!("{}\n{}", frontmatter, body),
+        None => body.to_string(),
+    }
+}
+
+/// Set `key` to `value` in the page's frontmatter. An existing `key:` line (and any
+/// list items under it) is replaced in place, otherwise the key is appended; a page
+/// without frontmatter gets a new block. The rest of the file is left as is.
+pub fn set_frontmatter_value(content: &str, key: &str, value: &str) -> String {
+    let entry = format!("{}: {}\n", yaml_scalar(key), yaml_scalar(value));
+    let (Some(frontmatter), body) = split_frontmatter(content) else {
+        return format!("---\n{}---\n{}", entry, content);
+    };
+
+    let mut lines: Vec<&str> = frontmatter.split_inclusive('\n').collect();
+    let closing = lines.pop().unwrap_or("---\n");
+    let mut output = String::from(lines[0]);
+    let mut replaced = false;
+    let mut in_entry = false;
+    for line in &lines[1..] {
+        let nested = line.starts_with([' ', '\t', '-']);
+        if in_entry && nested {
+            continue;
+        }
+        in_entry = !nested
+            && line
+                .split_once(':')
+                .is_some_and(|(k, _)| unquote_yaml(k.trim()) == key);
+        if !in_entry {
+            output.push_str(line);
+        } else if !replaced {
+            output.push_str(&entry);
+            replaced = true;
+        }
+    }
+    if !replaced {
+        output.push_str(&entry);
+    }
+    output.push_str(closing);
+    if !closing.ends_with('\n') {
+        output.push('\n');
+    }
+    output.push_str(body);
+    output
+}
+
+/// Inverse of `yaml_scalar` for single- and double-quoted scalars
+fn unquote_yaml(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        value[1..value.len() - 1]
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\")
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].replace("''", "'")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Blocks grouped by parent, each group in sibling order
+fn group_children(blocks: &[Block]) -> HashMap<Option<String>, Vec<&Block>> {
     let mut children_map: HashMap<Option<String>, Vec<&Block>> = HashMap::new();
 
     for block in blocks {
@@ -102,25 +701,21 @@ pub fn blocks_to_markdown(blocks: &[Block]) -> String {
             .push(block);
     }
 
-    // Sort each group by order_weight
+    // Sort each group by sibling position
     for children in children_map.values_mut() {
-        children.sort_by(|a, b| {
-            a.order_weight
-                .partial_cmp(&b.order_weight)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        children.sort_by(|a, b| a.cmp_position(b));
     }
 
-    let mut output = String::new();
-    render_blocks(&children_map, None, 0, &mut output);
-
-    output
+    children_map
 }
 
+/// `with_markers` writes each block's hidden `ID::`, `block_type::` and metadata lines
 fn render_blocks(
     children_map: &HashMap<Option<String>, Vec<&Block>>,
     parent_id: Option<String>,
     depth: usize,
+    style: IndentStyle,
+    with_markers: bool,
     output: &mut String,
 ) {
     let Some(children) = children_map.get(&parent_id) else {
@@ -128,25 +723,25 @@ fn render_blocks(
     };
 
     for block in children {
-        let indent = "  ".repeat(depth);
+        let indent = style.indent(depth);
+        let body_indent = style.indent(depth + 1);
 
         match block.block_type {
-            BlockType::Bullet => {
-                output.push_str(&format!(
-                    "{}- {}\n",
-                    indent,
-                    sanitize_content_for_markdown(&block.content)
-                ));
-                // Hidden ID marker line (same indent level body)
-                output.push_str(&format!("{}  {}{}\n", indent, ID_MARKER_PREFIX, block.id));
-
-                // Metadata lines (after ID marker)
-                let mut metadata_keys: Vec<&String> = block.metadata.keys().collect();
-                metadata_keys.sort(); // Sort for consistent output
-                for key in metadata_keys {
-                    if let Some(value) = block.metadata.get(key) {
-                        output.push_str(&format!("{}  {}::{}\n", indent, key, value));
-                    }
+            BlockType::Bullet | BlockType::Heading => {
+                if matches!(block.block_type, BlockType::Heading) {
+                    let marker = "#".repeat(heading_level(block));
+                    let content = block.content.lines().collect::<Vec<_>>().join(" ");
+                    push_block_content(output, &indent, &content, |first| {
+                        format!("{} {}", marker, first)
+                    });
+                } else {
+                    let checked = checked_state(&block.metadata);
+                    push_block_content(output, &indent, &block.content, |first| {
+                        bullet_first_line(checked, first)
+                    });
+                }
+                if with_markers {
+                    push_marker_lines(output, &body_indent, block);
                 }
             }
             BlockType::Code => {
@@ -156,6 +751,9 @@ fn render_blocks(
                     output.push_str(&format!("{}{}\n", indent, line));
                 }
                 output.push_str(&format!("{}```\n", indent));
+                if with_markers {
+                    push_marker_lines(output, &body_indent, block);
+                }
             }
             BlockType::Fence => {
                 output.push_str(&format!("{}///\n", indent));
@@ -163,43 +761,280 @@ fn render_blocks(
                     output.push_str(&format!("{}{}\n", indent, line));
                 }
                 output.push_str(&format!("{}///\n", indent));
+                if with_markers {
+                    push_marker_lines(output, &body_indent, block);
+                }
             }
             BlockType::AiPrompt | BlockType::AiResponse => {
-                output.push_str(&format!(
-                    "{}- {}\n",
-                    indent,
-                    sanitize_content_for_markdown(&block.content)
-                ));
-                output.push_str(&format!("{}  {}{}\n", indent, ID_MARKER_PREFIX, block.id));
-                output.push_str(&format!(
-                    "{}  block_type::{}\n",
-                    indent,
-                    block_type_to_string(&block.block_type)
-                ));
-                let mut metadata_keys: Vec<&String> = block.metadata.keys().collect();
-                metadata_keys.sort();
-                for key in metadata_keys {
-                    if let Some(value) = block.metadata.get(key) {
-                        output.push_str(&format!("{}  {}::{}\n", indent, key, value));
+                push_block_content(output, &indent, &block.content, |first| {
+                    bullet_first_line(None, first)
+                });
+                if with_markers {
+                    output.push_str(&format!(
+                        "{}{}{}\n",
+                        body_indent, ID_MARKER_PREFIX, block.id
+                    ));
+                    output.push_str(&format!(
+                        "{}block_type::{}\n",
+                        body_indent,
+                        block_type_to_string(&block.block_type)
+                    ));
+                    let mut metadata_keys: Vec<&String> = block.metadata.keys().collect();
+                    metadata_keys.sort();
+                    for key in metadata_keys {
+                        if let Some(value) = block.metadata.get(key) {
+                            output.push_str(&format!("{}{}::{}\n", body_indent, key, value));
+                        }
                     }
                 }
             }
         }
 
         // Render children
-        render_blocks(children_map, Some(block.id.clone()), depth + 1, output);
+        render_blocks(
+            children_map,
+            Some(block.id.clone()),
+            depth + 1,
+            style,
+            with_markers,
+            output,
+        );
     }
 }
 
+/// Write the hidden `ID::` marker line and the metadata lines (sorted by key) at the
+/// block's body indent. The checked state lives in the bullet prefix, so it is skipped.
+fn push_marker_lines(output: &mut String, body_indent: &str, block: &Block) {
+    output.push_str(&format!(
+        "{}{}{}\n",
+        body_indent, ID_MARKER_PREFIX, block.id
+    ));
+
+    let mut metadata_keys: Vec<&String> = block
+        .metadata
+        .keys()
+        .filter(|key| key.as_str() != CHECKED_METADATA_KEY)
+        .collect();
+    metadata_keys.sort(); // Sort for consistent output
+    for key in metadata_keys {
+        if let Some(value) = block.metadata.get(key) {
+            output.push_str(&format!("{}{}::{}\n", body_indent, key, value));
+        }
+    }
+}
+
+/// Write the block's first line (e.g. "- content" or "## content") with any further content
+/// lines at the block's indent, which is where `markdown_to_blocks` looks for continuation lines
+fn push_block_content(
+    output: &mut String,
+    indent: &str,
+    content: &str,
+    first_line: impl FnOnce(&str) -> String,
+) {
+    let sanitized = sanitize_content_for_markdown(content);
+    let mut lines = sanitized.lines();
+    output.push_str(&format!(
+        "{}{}\n",
+        indent,
+        first_line(lines.next().unwrap_or(""))
+    ));
+    for line in lines {
+        output.push_str(&format!("{}{}\n", indent, line));
+    }
+}
+
+/// Normalize the hidden marker layout under each bullet so `markdown_to_blocks`
+/// associates every marker with its block.
+///
+/// Hand edits can leave a `key::value` line above the `ID::` marker or at the wrong
+/// indent, which makes the parser silently drop the metadata. For every bullet this
+/// keeps the content's continuation lines and rewrites the marker lines after them
+/// as: `ID::` marker first, then metadata, all at the bullet's body indent. Only lines
+/// indented past the bullet count as its markers. Bullets that carry metadata but no
+/// marker get a fresh ID so the metadata has something to attach to.
+/// Code (```) and fence (///) regions are copied verbatim. `style` is only a fallback
+/// for `IndentStyle::detect`.
+pub fn normalize_marker_layout(content: &str, style: IndentStyle) -> String {
+    let style = IndentStyle::detect(content, style);
+    let (frontmatter, content) = split_frontmatter(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut fence: Option<&str> = None;
+    let mut i = 0usize;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("///") {
+            fence = Some(&trimmed[..3]);
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        }
+
+        out.push(line.to_string());
+        i += 1;
+
+        if !trimmed.starts_with("- ") && parse_heading_line(trimmed).is_none() {
+            continue;
+        }
+
+        // Multi-line content comes first, exactly as `markdown_to_blocks` folds it in
+        let bullet_indent = line.len() - trimmed.len();
+        while i < lines.len() && is_continuation_line(lines[i], bullet_indent) {
+            out.push(lines[i].to_string());
+            i += 1;
+        }
+
+        // Then gather the marker/metadata lines under this bullet, whatever their order
+        // or depth. A line at or left of the bullet's own indent does not belong to it.
+        let mut id_line: Option<String> = None;
+        let mut metadata_lines: Vec<String> = Vec::new();
+
+        while i < lines.len() {
+            let body = lines[i];
+            let body_trimmed = body.trim_start();
+            if body.len() - body_trimmed.len() <= bullet_indent {
+                break;
+            }
+            if is_id_marker_line(body_trimmed) {
+                if id_line.is_none() {
+                    id_line = Some(body_trimmed.trim_end().to_string());
+                }
+            } else if is_metadata_line(body_trimmed) {
+                metadata_lines.push(body_trimmed.trim_end().to_string());
+            } else {
+                break;
+            }
+            i += 1;
+        }
+
+        if id_line.is_none() && metadata_lines.is_empty() {
+            continue;
+        }
+
+        let indent = format!("{}{}", &line[..bullet_indent], style.indent(1));
+        let id_line = id_line.unwrap_or_else(|| format!("{}{}", ID_MARKER_PREFIX, Uuid::new_v4()));
+        out.push(format!("{}{}", indent, id_line));
+        for meta in metadata_lines {
+            out.push(format!("{}{}", indent, meta));
+        }
+    }
+
+    let mut normalized = out.join("\n");
+    if content.ends_with('\n') {
+        normalized.push('\n');
+    }
+    match frontmatter {
+        Some(frontmatter) => format!("{}{}", frontmatter, normalized),
+        None => normalized,
+    }
+}
+
+/// Whether `line` continues the content of a bullet at `bullet_indent`: a plain line at
+/// or right of the bullet that does not start a block, fence or marker
+fn is_continuation_line(line: &str, bullet_indent: usize) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty()
+        && line.len() - trimmed.len() >= bullet_indent
+        && !trimmed.starts_with("- ")
+        && parse_heading_line(trimmed).is_none()
+        && !trimmed.starts_with("```")
+        && !trimmed.starts_with("///")
+        && !is_id_marker_line(trimmed)
+        && !is_metadata_line(trimmed)
+}
+
+/// Remove every `ID::` marker line outside code (```) and fence (///) regions, so
+/// the blocks parsed from the result all get fresh IDs. Metadata lines are kept; run
+/// `normalize_marker_layout` afterwards to give them a marker to attach to.
+pub fn strip_id_markers(content: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("///") {
+            fence = Some(&trimmed[..3]);
+        } else if is_id_marker_line(trimmed) {
+            continue;
+        }
+        out.push(line);
+    }
+
+    let mut stripped = out.join("\n");
+    if content.ends_with('\n') {
+        stripped.push('\n');
+    }
+    stripped
+}
+
+/// Block content as a reader sees it, one line per visible line: `ID::` markers,
+/// metadata lines, code fence lines and `((block refs))` are dropped, wiki links and
+/// markdown links show their alias (or target) text, and heading, quote, checkbox and
+/// inline emphasis/code markers are removed
+pub fn plain_text(content: &str) -> String {
+    static LINK_RE: OnceLock<Regex> = OnceLock::new();
+    static MARKUP_RE: OnceLock<Regex> = OnceLock::new();
+    let link_re = LINK_RE.get_or_init(|| {
+        Regex::new(r"!?\[\[([^\]|]*)(?:\|([^\]]*))?\]\]").expect("valid wiki link regex")
+    });
+    let markup_re = MARKUP_RE.get_or_init(|| {
+        Regex::new(
+            r"\(\([^)]*\)\)|!?\[([^\]]*)\]\([^)]*\)|^(?:#{1,6}\s+|>\s*|\[[ xX]\]\s+)|\*\*|__|~~|`",
+        )
+        .expect("valid inline markup regex")
+    });
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with("```")
+                && !is_id_marker_line(line)
+                && !is_metadata_line(line)
+        })
+        .map(|line| {
+            let line = link_re.replace_all(line, |caps: &regex::Captures| {
+                let shown = caps.get(2).or_else(|| caps.get(1));
+                shown.map_or("", |m| m.as_str()).to_string()
+            });
+            markup_re.replace_all(&line, "$1").into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Parse markdown file to blocks
-/// Handles both bullet format (- ) and heading format (# ) for backward compatibility (I4)
-/// Headings are converted to root-level bullets to maintain canonical format.
-/// This ensures files with heading format can be read but will be serialized as bullets.
+/// Handles both bullet format (- ) and heading format (# ) (I4).
+/// Headings become `BlockType::Heading` blocks at their indent depth, with the level
+/// stored in `language`, so they serialize back as the same heading line. A heading is
+/// exactly one line; only bullets collect continuation lines.
 ///
 /// Hidden ID markers:
 /// - Lines like "  ID::<uuid>" (aligned to the bullet's indent level) are consumed as metadata
 ///   for the preceding bullet block and are NOT imported as blocks.
-pub fn markdown_to_blocks(content: &str, page_id: &str) -> Vec<Block> {
+///
+/// Depth is measured in levels of the style the file was written with, found by
+/// `IndentStyle::detect`; `indent` (the workspace setting) is used when nothing is
+/// nested. A leading frontmatter block is skipped.
+pub fn markdown_to_blocks(content: &str, page_id: &str, indent: IndentStyle) -> Vec<Block> {
+    let indent = IndentStyle::detect(content, indent);
+    let (_, content) = split_frontmatter(content);
     let mut blocks = Vec::new();
     let mut parent_stack: Vec<(String, usize)> = Vec::new();
     let mut order_counter: f64 = 1.0;
@@ -211,7 +1046,7 @@ pub fn markdown_to_blocks(content: &str, page_id: &str) -> Vec<Block> {
     while i < lines.len() {
         let line = lines[i];
         let trimmed = line.trim_start();
-        let depth = (line.len() - trimmed.len()) / 2;
+        let depth = indent.depth_of(line);
 
         if trimmed.is_empty() {
             i += 1;
@@ -230,30 +1065,9 @@ pub fn markdown_to_blocks(content: &str, page_id: &str) -> Vec<Block> {
             continue;
         }
 
-        // Skip heading lines - they are not part of the bullet-based canonical format
-        // Headings are parsed into their content for backward compatibility
-        if trimmed.starts_with('#') {
-            // Extract heading content (# Title -> Title)
-            let heading_content = trimmed.trim_start_matches('#').trim_start().to_string();
-
-            if !heading_content.is_empty() {
-                // Treat heading as a root-level bullet (depth=0, no parent)
-                let block = Block {
-                    id: Uuid::new_v4().to_string(),
-                    page_id: page_id.to_string(),
-                    parent_id: None,
-                    content: heading_content,
-                    order_weight: order_counter,
-                    is_collapsed: false,
-                    block_type: BlockType::Bullet,
-                    language: None,
-                    created_at: Utc::now().to_rfc3339(),
-                    updated_at: Utc::now().to_rfc3339(),
-                    metadata: HashMap::new(),
-                };
-                blocks.push(block);
-                order_counter += 1.0;
-            }
+        // Empty headings ("#", "## ") carry nothing to import
+        let heading = parse_heading_line(trimmed);
+        if matches!(heading, Some((_, ""))) {
             i += 1;
             continue;
         }
@@ -269,61 +1083,104 @@ pub fn markdown_to_blocks(content: &str, page_id: &str) -> Vec<Block> {
 
         let parent_id = parent_stack.last().map(|(id, _)| id.clone());
 
+        // Code (```lang) and fence (///) regions become one block each, verbatim, followed
+        // by the same hidden ID marker / metadata lines as a bullet
+        if let Some((block_type, language)) = parse_fence_opening(trimmed) {
+            let fence_indent = line.len() - trimmed.len();
+            let closing = if matches!(block_type, BlockType::Code) {
+                "```"
+            } else {
+                "///"
+            };
+            let mut body: Vec<&str> = Vec::new();
+            i += 1;
+            while i < lines.len() && lines[i].trim() != closing {
+                body.push(strip_indent(lines[i], fence_indent));
+                i += 1;
+            }
+            // `i` is on the closing fence (an unclosed fence runs to the end)
+            let (explicit_id, metadata) = parse_marker_lines(&lines, &mut i, indent, depth + 1);
+
+            let block = Block {
+                id: explicit_id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+                page_id: page_id.to_string(),
+                parent_id,
+                content: body.join("\n"),
+                order_weight: order_counter,
+                order_key: String::new(),
+                is_collapsed: false,
+                block_type,
+                language,
+                created_at: Utc::now().to_rfc3339(),
+                updated_at: Utc::now().to_rfc3339(),
+                metadata,
+            };
+
+            order_counter += 1.0;
+            parent_stack.push((block.id.clone(), depth));
+            blocks.push(block);
+
+            i += 1;
+            continue;
+        }
+
         // Strip leading bullet if present (bullet format)
         // Non-bullet lines are treated as-is (for backward compatibility with mixed formats)
-        let content_text = if trimmed.starts_with("- ") {
-            trimmed[2..].to_string()
+        let is_bullet = trimmed.starts_with("- ");
+        let mut checked: Option<bool> = None;
+        let mut content_text = if let Some((_, text)) = heading {
+            text.to_string()
+        } else if is_bullet {
+            let text = &trimmed[2..];
+            if let Some((state, rest)) = split_checkbox(text) {
+                checked = Some(state);
+                rest.to_string()
+            } else if text.starts_with('\\') && is_checkbox_like(text) {
+                text[1..].to_string()
+            } else {
+                text.to_string()
+            }
         } else {
             trimmed.to_string()
         };
 
-        // Optional: consume an immediate ID marker line at the same logical depth.
-        // We serialize as: "<indent>- content" then "<indent>  ID::<uuid>"
-        let mut explicit_id: Option<String> = None;
-        let mut metadata: HashMap<String, String> = HashMap::new();
-
-        // Check for body-indented lines (ID marker and metadata) at depth+1
-        let body_indent = depth + 1;
-
-        if i + 1 < lines.len() {
-            let next_line = lines[i + 1];
-            let next_trimmed = next_line.trim_start();
-            let next_depth = (next_line.len() - next_trimmed.len()) / 2;
-
-            // The ID line should be "body-indented" under the bullet (depth+1)
-            if next_depth == body_indent && is_id_marker_line(next_trimmed) {
-                explicit_id = parse_id_marker(next_trimmed);
-                i += 1; // consume marker line
-
-                // After ID marker, consume any metadata lines at the same body indent level
-                while i + 1 < lines.len() {
-                    let meta_line = lines[i + 1];
-                    let meta_trimmed = meta_line.trim_start();
-                    let meta_depth = (meta_line.len() - meta_trimmed.len()) / 2;
-
-                    if meta_depth == body_indent && is_metadata_line(meta_trimmed) {
-                        if let Some((key, value)) = parse_metadata_line(meta_trimmed) {
-                            metadata.insert(key, value);
-                            i += 1; // consume metadata line
-                        } else {
-                            break;
-                        }
-                    } else {
-                        break;
-                    }
+        // Multi-line bullet content: following plain lines at or right of the bullet's
+        // indent, up to the ID marker (escaped "\- " lines are content, not children)
+        if is_bullet {
+            let bullet_indent = line.len() - trimmed.len();
+            while i + 1 < lines.len() {
+                let next_line = lines[i + 1];
+                if !is_continuation_line(next_line, bullet_indent) {
+                    break;
                 }
+                content_text.push('\n');
+                content_text.push_str(&unescape_continuation_line(&next_line[bullet_indent..]));
+                i += 1;
             }
         }
 
+        // Optional: consume an immediate ID marker line at the same logical depth.
+        // We serialize as: "<indent>- content" then "<indent>  ID::<uuid>"
+        let (explicit_id, mut metadata) = parse_marker_lines(&lines, &mut i, indent, depth + 1);
+
+        if let Some(state) = checked {
+            metadata.insert(CHECKED_METADATA_KEY.to_string(), state.to_string());
+        }
+
         let block = Block {
             id: explicit_id.unwrap_or_else(|| Uuid::new_v4().to_string()),
             page_id: page_id.to_string(),
             parent_id,
             content: content_text,
             order_weight: order_counter,
+            order_key: String::new(),
             is_collapsed: false,
-            block_type: BlockType::Bullet,
-            language: None,
+            block_type: if heading.is_some() {
+                BlockType::Heading
+            } else {
+                BlockType::Bullet
+            },
+            language: heading.map(|(level, _)| level.to_string()),
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
             metadata,
@@ -339,10 +1196,131 @@ pub fn markdown_to_blocks(content: &str, page_id: &str) -> Vec<Block> {
     blocks
 }
 
+/// Consume the hidden `ID::` marker line directly after `lines[*i]` and the metadata
+/// lines following it, all at `body_depth`. Leaves `*i` on the last consumed line.
+fn parse_marker_lines(
+    lines: &[&str],
+    i: &mut usize,
+    indent: IndentStyle,
+    body_depth: usize,
+) -> (Option<String>, HashMap<String, String>) {
+    let mut metadata = HashMap::new();
+
+    // The ID line should be "body-indented" under the block (depth+1)
+    let explicit_id = match lines.get(*i + 1) {
+        Some(next_line) if indent.depth_of(next_line) == body_depth => {
+            parse_id_marker(next_line.trim_start())
+        }
+        _ => None,
+    };
+    if explicit_id.is_none() {
+        return (None, metadata);
+    }
+    *i += 1; // consume marker line
+
+    // After ID marker, consume any metadata lines at the same body indent level
+    while let Some(meta_line) = lines.get(*i + 1) {
+        if indent.depth_of(meta_line) != body_depth {
+            break;
+        }
+        let Some((key, value)) = parse_metadata_line(meta_line.trim_start()) else {
+            break;
+        };
+        metadata.insert(key, value);
+        *i += 1; // consume metadata line
+    }
+
+    (explicit_id, metadata)
+}
+
+/// `Some((Code, lang))` for a "```lang" line, `Some((Fence, None))` for a "///" line
+fn parse_fence_opening(trimmed: &str) -> Option<(BlockType, Option<String>)> {
+    if let Some(lang) = trimmed.strip_prefix("```") {
+        let lang = lang.trim();
+        let language = (!lang.is_empty()).then(|| lang.to_string());
+        return Some((BlockType::Code, language));
+    }
+    (trimmed.trim_end() == "///").then_some((BlockType::Fence, None))
+}
+
+/// Remove up to `width` bytes of leading whitespace, the indent `render_blocks` added
+fn strip_indent(line: &str, width: usize) -> &str {
+    let start = line
+        .char_indices()
+        .find(|&(idx, c)| idx >= width || !c.is_whitespace())
+        .map_or(line.len(), |(idx, _)| idx);
+    &line[start..]
+}
+
+/// A parsed block with its children nested, for previewing markdown before import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockPreviewNode {
+    pub id: String,
+    pub content: String,
+    pub block_type: BlockType,
+    pub language: Option<String>,
+    pub metadata: HashMap<String, String>,
+    pub children: Vec<BlockPreviewNode>,
+}
+
+/// Parse markdown into a nested block tree without touching the database.
+/// IDs come from embedded `ID::` markers where present, otherwise they are fresh.
+pub fn markdown_to_block_tree(content: &str, indent: IndentStyle) -> Vec<BlockPreviewNode> {
+    let blocks = markdown_to_blocks(content, "preview", indent);
+
+    let mut children_of: HashMap<Option<String>, Vec<Block>> = HashMap::new();
+    for block in blocks {
+        children_of
+            .entry(block.parent_id.clone())
+            .or_default()
+            .push(block);
+    }
+
+    fn build(
+        parent_id: Option<String>,
+        children_of: &mut HashMap<Option<String>, Vec<Block>>,
+    ) -> Vec<BlockPreviewNode> {
+        let mut children = children_of.remove(&parent_id).unwrap_or_default();
+        children.sort_by(|a, b| a.cmp_position(b));
+        children
+            .into_iter()
+            .map(|block| BlockPreviewNode {
+                children: build(Some(block.id.clone()), children_of),
+                id: block.id,
+                content: block.content,
+                block_type: block.block_type,
+                language: block.language,
+                metadata: block.metadata,
+            })
+            .collect()
+    }
+
+    build(None, &mut children_of)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A bullet block with fixed timestamps and no metadata
+    fn test_block(id: &str, parent: Option<&str>, content: &str, order_weight: f64) -> Block {
+        Block {
+            id: id.to_string(),
+            page_id: "test-page".to_string(),
+            parent_id: parent.map(|p| p.to_string()),
+            content: content.to_string(),
+            order_weight,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Bullet,
+            language: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            metadata: HashMap::new(),
+        }
+    }
+
     #[test]
     fn test_metadata_parsing() {
         let markdown = r#"- Fight Club review
@@ -353,7 +1331,7 @@ mod tests {
   cast::{"에드워드 노튼": "나레이터", "브래드 피트": "타일러 더든"}
 "#;
 
-        let blocks = markdown_to_blocks(markdown, "test-page");
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
         assert_eq!(blocks.len(), 1);
 
         let block = &blocks[0];
@@ -382,6 +1360,7 @@ mod tests {
             parent_id: None,
             content: "Movie review".to_string(),
             order_weight: 1.0,
+            order_key: String::new(),
             is_collapsed: false,
             block_type: BlockType::Bullet,
             language: None,
@@ -390,7 +1369,7 @@ mod tests {
             metadata,
         };
 
-        let markdown = blocks_to_markdown(&[block]);
+        let markdown = blocks_to_markdown(&[block], IndentStyle::default());
 
         // Check that markdown contains metadata lines
         assert!(markdown.contains("- Movie review"));
@@ -410,15 +1389,15 @@ mod tests {
 "#;
 
         // Parse
-        let blocks = markdown_to_blocks(original_markdown, "test-page");
+        let blocks = markdown_to_blocks(original_markdown, "test-page", IndentStyle::default());
         assert_eq!(blocks.len(), 1);
         assert_eq!(blocks[0].metadata.len(), 3);
 
         // Serialize
-        let serialized = blocks_to_markdown(&blocks);
+        let serialized = blocks_to_markdown(&blocks, IndentStyle::default());
 
         // Parse again
-        let blocks2 = markdown_to_blocks(&serialized, "test-page");
+        let blocks2 = markdown_to_blocks(&serialized, "test-page", IndentStyle::default());
         assert_eq!(blocks2.len(), 1);
         assert_eq!(blocks2[0].id, "roundtrip-id");
         assert_eq!(blocks2[0].metadata, blocks[0].metadata);
@@ -434,7 +1413,7 @@ mod tests {
     tag::important
 "#;
 
-        let blocks = markdown_to_blocks(markdown, "test-page");
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
         assert_eq!(blocks.len(), 2);
 
         let parent = blocks.iter().find(|b| b.id == "parent-id").unwrap();
@@ -454,7 +1433,7 @@ mod tests {
   list::[1, 2, 3, 4]
 "#;
 
-        let blocks = markdown_to_blocks(markdown, "test-page");
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
         assert_eq!(blocks.len(), 1);
 
         let block = &blocks[0];
@@ -474,7 +1453,7 @@ mod tests {
   ID::no-meta-id
 "#;
 
-        let blocks = markdown_to_blocks(markdown, "test-page");
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
         assert_eq!(blocks.len(), 1);
         assert_eq!(blocks[0].metadata.len(), 0);
     }
@@ -489,10 +1468,642 @@ mod tests {
   ID::next-id
 "#;
 
-        let blocks = markdown_to_blocks(markdown, "test-page");
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
         // Should only have 2 blocks, not 4 (metadata lines should not become blocks)
         assert_eq!(blocks.len(), 2);
         assert_eq!(blocks[0].id, "block-id");
         assert_eq!(blocks[1].id, "next-id");
     }
+
+    #[test]
+    fn test_sanitization_trims_trailing_whitespace() {
+        let rules = SanitizationRules {
+            trim_trailing_whitespace: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            apply_sanitization_rules("foo   ", &rules, &BlockType::Bullet),
+            "foo"
+        );
+        assert_eq!(
+            apply_sanitization_rules("foo   ", &SanitizationRules::default(), &BlockType::Bullet),
+            "foo   "
+        );
+    }
+
+    #[test]
+    fn test_sanitization_leaves_code_untouched() {
+        let rules = SanitizationRules {
+            trim_trailing_whitespace: true,
+            normalize_smart_quotes: true,
+            collapse_blank_lines: true,
+        };
+        let code = "let s = \u{201C}x\u{201D};   \n\n\n  return s;  ";
+
+        assert_eq!(
+            apply_sanitization_rules(code, &rules, &BlockType::Code),
+            code
+        );
+        assert_eq!(
+            apply_sanitization_rules(code, &rules, &BlockType::Fence),
+            code
+        );
+
+        // Fenced and inline code inside a bullet are preserved as well
+        let bullet = "\u{201C}quoted\u{201D}  \n```\nkeep   \n\n\n```\n`\u{2018}x\u{2019}`";
+        assert_eq!(
+            apply_sanitization_rules(bullet, &rules, &BlockType::Bullet),
+            "\"quoted\"\n```\nkeep   \n\n\n```\n`\u{2018}x\u{2019}`"
+        );
+    }
+
+    #[test]
+    fn test_sanitization_collapses_blank_lines() {
+        let rules = SanitizationRules {
+            collapse_blank_lines: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            apply_sanitization_rules("a\n\n\n\nb", &rules, &BlockType::Bullet),
+            "a\n\nb"
+        );
+    }
+
+    #[test]
+    fn test_normalize_marker_layout_moves_metadata_after_id() {
+        let markdown =
+            "- Task\n  status::done\n  ID::task-id\n    priority::A\n- Next\n  ID::next-id\n";
+
+        // Before normalization the misplaced metadata is lost
+        let before = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
+        assert!(before
+            .iter()
+            .all(|b| b.id != "task-id" || b.metadata.is_empty()));
+
+        let normalized = normalize_marker_layout(markdown, IndentStyle::default());
+        assert_eq!(
+            normalized,
+            "- Task\n  ID::task-id\n  status::done\n  priority::A\n- Next\n  ID::next-id\n"
+        );
+
+        let blocks = markdown_to_blocks(&normalized, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].id, "task-id");
+        assert_eq!(blocks[0].metadata.get("status"), Some(&"done".to_string()));
+        assert_eq!(blocks[0].metadata.get("priority"), Some(&"A".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_marker_layout_handles_multi_line_content() {
+        let markdown =
+            "- First\nsecond line\n  status::done\n  ID::first-id\n- Next\n  ID::next-id\n";
+
+        let normalized = normalize_marker_layout(markdown, IndentStyle::default());
+        assert_eq!(
+            normalized,
+            "- First\nsecond line\n  ID::first-id\n  status::done\n- Next\n  ID::next-id\n"
+        );
+
+        let blocks = markdown_to_blocks(&normalized, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].id, "first-id");
+        assert_eq!(blocks[0].content, "First\nsecond line");
+        assert_eq!(blocks[0].metadata.get("status"), Some(&"done".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_marker_layout_leaves_column_zero_metadata_alone() {
+        let markdown = "- A\n  ID::a\nk::v\n- B\n  ID::b\n";
+        assert_eq!(
+            normalize_marker_layout(markdown, IndentStyle::default()),
+            markdown
+        );
+    }
+
+    #[test]
+    fn test_normalize_marker_layout_keeps_canonical_and_code_untouched() {
+        let canonical = "- A\n  ID::a\n  k::v\n  - B\n    ID::b\n```\nx::y\n```\n";
+        assert_eq!(
+            normalize_marker_layout(canonical, IndentStyle::default()),
+            canonical
+        );
+    }
+
+    #[test]
+    fn test_strip_id_markers_keeps_metadata_and_code() {
+        let markdown = "- A\n  ID::a\n  k::v\n  - B\n    ID::b\n```\nID::kept\n```\n";
+        let stripped = strip_id_markers(markdown);
+        assert_eq!(stripped, "- A\n  k::v\n  - B\n```\nID::kept\n```\n");
+
+        let blocks = markdown_to_blocks(
+            &normalize_marker_layout(&stripped, IndentStyle::default()),
+            "test-page",
+            IndentStyle::default(),
+        );
+        assert_eq!(blocks[1].content, "B");
+        assert!(blocks.iter().all(|b| b.id != "a" && b.id != "b"));
+        assert_eq!(blocks[0].metadata.get("k"), Some(&"v".to_string()));
+    }
+
+    #[test]
+    fn test_markdown_to_block_tree_nests_children_and_keeps_ids() {
+        let markdown = "- Parent\n  ID::parent-id\n  status::todo\n  - Child\n    ID::child-id\n    - Grandchild\n- Sibling\n";
+
+        let tree = markdown_to_block_tree(markdown, IndentStyle::default());
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].id, "parent-id");
+        assert_eq!(tree[0].content, "Parent");
+        assert_eq!(tree[0].metadata.get("status"), Some(&"todo".to_string()));
+        assert_eq!(tree[0].children.len(), 1);
+        assert_eq!(tree[0].children[0].id, "child-id");
+        assert_eq!(tree[0].children[0].children[0].content, "Grandchild");
+        assert!(tree[0].children[0].children[0].children.is_empty());
+        assert_eq!(tree[1].content, "Sibling");
+        assert!(tree[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_multiline_content_with_bullet_like_line_roundtrips() {
+        let block = Block {
+            id: "multi-id".to_string(),
+            page_id: "test-page".to_string(),
+            parent_id: None,
+            content: "Shopping notes\n- not a child\n\\- already escaped".to_string(),
+            order_weight: 1.0,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Bullet,
+            language: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+            metadata: HashMap::new(),
+        };
+        let child = Block {
+            id: "child-id".to_string(),
+            parent_id: Some("multi-id".to_string()),
+            content: "Real child".to_string(),
+            ..block.clone()
+        };
+
+        let markdown = blocks_to_markdown(&[block.clone(), child], IndentStyle::default());
+        assert!(markdown.contains("\n\\- not a child\n"));
+
+        let blocks = markdown_to_blocks(&markdown, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].id, "multi-id");
+        assert_eq!(blocks[0].content, block.content);
+        assert_eq!(blocks[1].content, "Real child");
+        assert_eq!(blocks[1].parent_id, Some("multi-id".to_string()));
+    }
+
+    #[test]
+    fn test_bullet_with_fenced_code_roundtrips() {
+        let content = "Example:\n```rust\nfn main() {}\n```\n/// note\n\\```escaped";
+        let block = test_block("x", None, content, 1.0);
+
+        let markdown = blocks_to_markdown(&[block], IndentStyle::default());
+        assert!(markdown.contains("\n\\```rust\n"));
+        assert!(markdown.contains("\n\\/// note\n"));
+
+        let blocks = markdown_to_blocks(&markdown, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].id, "x");
+        assert!(matches!(blocks[0].block_type, BlockType::Bullet));
+        assert_eq!(blocks[0].content, content);
+    }
+
+    #[test]
+    fn test_heading_roundtrips_with_level_and_id() {
+        let heading = Block {
+            id: "heading-id".to_string(),
+            page_id: "test-page".to_string(),
+            parent_id: None,
+            content: "Project Plan".to_string(),
+            order_weight: 1.0,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Heading,
+            language: Some("2".to_string()),
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+            metadata: HashMap::new(),
+        };
+        let child = Block {
+            id: "child-id".to_string(),
+            parent_id: Some("heading-id".to_string()),
+            content: "First step\n# not a heading".to_string(),
+            block_type: BlockType::Bullet,
+            language: None,
+            ..heading.clone()
+        };
+
+        let markdown = blocks_to_markdown(&[heading, child], IndentStyle::default());
+        assert!(markdown.starts_with("## Project Plan\n  ID::heading-id\n"));
+        assert!(markdown.contains("\n  \\# not a heading\n"));
+
+        let blocks = markdown_to_blocks(&markdown, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].id, "heading-id");
+        assert!(matches!(blocks[0].block_type, BlockType::Heading));
+        assert_eq!(blocks[0].language.as_deref(), Some("2"));
+        assert_eq!(blocks[0].content, "Project Plan");
+        assert_eq!(blocks[1].parent_id, Some("heading-id".to_string()));
+        assert_eq!(blocks[1].content, "First step\n# not a heading");
+
+        // A heading is one line: the plain line after it is its own block
+        let blocks = markdown_to_blocks(
+            "# Intro\nSome text\n- item\n",
+            "test-page",
+            IndentStyle::default(),
+        );
+        assert_eq!(blocks.len(), 3);
+        assert!(matches!(blocks[0].block_type, BlockType::Heading));
+        assert_eq!(blocks[0].content, "Intro");
+        assert_eq!(blocks[1].content, "Some text");
+        assert_eq!(blocks[1].parent_id, None);
+        assert_eq!(blocks[2].content, "item");
+        assert_eq!(blocks[2].parent_id, None);
+
+        // ...so multi-line heading content is written back on a single line
+        let wrapped = Block {
+            block_type: BlockType::Heading,
+            ..test_block("wrapped", None, "Two\nlines", 1.0)
+        };
+        let markdown = blocks_to_markdown(&[wrapped], IndentStyle::default());
+        assert!(markdown.starts_with("# Two lines\n  ID::wrapped\n"));
+
+        // "#tag" lines are plain content, not headings
+        let blocks = markdown_to_blocks("#tag line\n", "test-page", IndentStyle::default());
+        assert!(matches!(blocks[0].block_type, BlockType::Bullet));
+        assert_eq!(blocks[0].content, "#tag line");
+    }
+
+    #[test]
+    fn test_code_blocks_roundtrip() {
+        let bullet = Block {
+            id: "parent-id".to_string(),
+            page_id: "test-page".to_string(),
+            parent_id: None,
+            content: "Snippets".to_string(),
+            order_weight: 1.0,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Bullet,
+            language: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+            metadata: HashMap::new(),
+        };
+        let code = Block {
+            id: "code-id".to_string(),
+            parent_id: Some("parent-id".to_string()),
+            content: "fn main() {\n    let x = 1;\n\n\n    println!(\"{}\", x);\n}".to_string(),
+            block_type: BlockType::Code,
+            language: Some("rust".to_string()),
+            metadata: HashMap::from([("source".to_string(), "playground".to_string())]),
+            ..bullet.clone()
+        };
+        let after = Block {
+            id: "after-id".to_string(),
+            content: "After".to_string(),
+            order_weight: 2.0,
+            ..bullet.clone()
+        };
+
+        for style in [
+            IndentStyle::default(),
+            IndentStyle {
+                width: 4,
+                use_tabs: true,
+            },
+        ] {
+            let markdown =
+                blocks_to_markdown(&[bullet.clone(), code.clone(), after.clone()], style);
+            let blocks = markdown_to_blocks(&markdown, "test-page", style);
+            assert_eq!(blocks.len(), 3, "{}", markdown);
+
+            assert_eq!(blocks[1].id, "code-id");
+            assert!(matches!(blocks[1].block_type, BlockType::Code));
+            assert_eq!(blocks[1].language.as_deref(), Some("rust"));
+            assert_eq!(blocks[1].content, code.content);
+            assert_eq!(blocks[1].parent_id.as_deref(), Some("parent-id"));
+            assert_eq!(blocks[1].metadata, code.metadata);
+
+            assert_eq!(blocks[2].id, "after-id");
+            assert_eq!(blocks[2].parent_id, None);
+
+            // A second pass is stable, so resyncs never re-key the block
+            assert_eq!(blocks_to_markdown(&blocks, style), markdown);
+        }
+
+        // Editors often strip whitespace-only lines; a bare fence has no language
+        let blocks = markdown_to_blocks(
+            "- A\n  ```\n  x\n\n  y\n  ```\n",
+            "test-page",
+            IndentStyle::default(),
+        );
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].content, "x\n\ny");
+        assert_eq!(blocks[1].language, None);
+    }
+
+    #[test]
+    fn test_fence_block_serializes_identically_after_reparse() {
+        let fence = Block {
+            id: "fence-id".to_string(),
+            page_id: "test-page".to_string(),
+            parent_id: None,
+            content: "Quoted /// text\n\n- kept as is\n    indented".to_string(),
+            order_weight: 1.0,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Fence,
+            language: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+            metadata: HashMap::new(),
+        };
+        let bullet = Block {
+            id: "bullet-id".to_string(),
+            content: "Next".to_string(),
+            order_weight: 2.0,
+            block_type: BlockType::Bullet,
+            ..fence.clone()
+        };
+
+        let nested = Block {
+            id: "nested-fence-id".to_string(),
+            parent_id: Some("bullet-id".to_string()),
+            content: "- not a bullet\n\n  indented".to_string(),
+            ..fence.clone()
+        };
+
+        for style in [
+            IndentStyle::default(),
+            IndentStyle {
+                width: 4,
+                use_tabs: true,
+            },
+        ] {
+            let markdown =
+                blocks_to_markdown(&[fence.clone(), bullet.clone(), nested.clone()], style);
+            assert!(markdown.starts_with(&format!(
+                "///\nQuoted /// text\n\n- kept as is\n    indented\n///\n{}ID::fence-id\n",
+                style.indent(1)
+            )));
+
+            let blocks = markdown_to_blocks(&markdown, "test-page", style);
+            assert_eq!(blocks.len(), 3, "{}", markdown);
+            assert_eq!(blocks[0].id, "fence-id");
+            assert!(matches!(blocks[0].block_type, BlockType::Fence));
+            assert_eq!(blocks[0].content, fence.content);
+            assert_eq!(blocks[0].language, None);
+
+            assert_eq!(blocks[2].id, "nested-fence-id");
+            assert!(matches!(blocks[2].block_type, BlockType::Fence));
+            assert_eq!(blocks[2].content, nested.content);
+            assert_eq!(blocks[2].parent_id.as_deref(), Some("bullet-id"));
+
+            let reserialized = blocks_to_markdown(&blocks, style);
+            assert_eq!(reserialized, markdown);
+        }
+    }
+
+    #[test]
+    fn test_frontmatter_is_split_off_and_not_parsed_as_blocks() {
+        let markdown = "---\ntitle: \"Q1: plan\"\nstatus: draft\n# comment\ntags:\n  - work\n  - 'q''1'\naliases: [a, \"b\"]\n---\n- First\n  ID::b1\n";
+
+        let (frontmatter, body) = split_frontmatter(markdown);
+        let frontmatter = frontmatter.unwrap();
+        assert!(frontmatter.starts_with("---\n") && frontmatter.ends_with("---\n"));
+        assert_eq!(body, "- First\n  ID::b1\n");
+
+        let entries = parse_frontmatter(frontmatter);
+        let expected = vec![
+            ("title", "Q1: plan"),
+            ("status", "draft"),
+            ("tags", "[\"work\",\"q'1\"]"),
+            ("aliases", "[\"a\",\"b\"]"),
+        ];
+        let entries: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(entries, expected);
+
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].id, "b1");
+        assert_eq!(blocks[0].content, "First");
+
+        let normalized = normalize_marker_layout(markdown, IndentStyle::default());
+        assert_eq!(normalized, markdown);
+
+        // An unclosed block is not frontmatter
+        assert_eq!(split_frontmatter("---\na: b\n- x\n").0, None);
+    }
+
+    #[test]
+    fn test_set_frontmatter_value_replaces_or_adds_the_key() {
+        let body = "- First\n  ID::b1\n";
+        let added = set_frontmatter_value(body, "title", "Q1: plan");
+        assert_eq!(added, format!("---\ntitle: \"Q1: plan\"\n---\n{}", body));
+        assert_eq!(
+            frontmatter_value(&added, "title").as_deref(),
+            Some("Q1: plan")
+        );
+
+        let markdown = format!("---\ntitle:\n  - old\nstatus: draft\n---\n{}", body);
+        let replaced = set_frontmatter_value(&markdown, "title", "New");
+        assert_eq!(
+            replaced,
+            format!("---\ntitle: New\nstatus: draft\n---\n{}", body)
+        );
+
+        let appended = set_frontmatter_value(&replaced, "alias", "n");
+        assert_eq!(
+            appended,
+            format!("---\ntitle: New\nstatus: draft\nalias: n\n---\n{}", body)
+        );
+        assert_eq!(frontmatter_value(&appended, "missing"), None);
+    }
+
+    #[test]
+    fn test_render_frontmatter_round_trips_parsed_entries() {
+        let frontmatter = "---\nstatus: draft\ntags:\n  - a\n  - \"b: c\"\ncount: \"3\"\n---\n";
+        let entries = parse_frontmatter(frontmatter);
+        assert_eq!(render_frontmatter(&entries).as_deref(), Some(frontmatter));
+        assert_eq!(render_frontmatter(&[]), None);
+        assert_eq!(
+            prepend_frontmatter(Some("---\nk: v\n---"), "- body\n"),
+            "---\nk: v\n---\n- body\n"
+        );
+    }
+
+    #[test]
+    fn test_checkbox_bullets_roundtrip() {
+        let markdown = "- [ ] Write report\n  ID::task-open\n- [x] Send invoice\n  ID::task-done\n  due::friday\n- \\[x] not a task\n  ID::plain\n";
+
+        let blocks = markdown_to_blocks(markdown, "test-page", IndentStyle::default());
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].content, "Write report");
+        assert_eq!(
+            blocks[0].metadata.get(CHECKED_METADATA_KEY),
+            Some(&"false".to_string())
+        );
+        assert_eq!(blocks[1].content, "Send invoice");
+        assert_eq!(
+            blocks[1].metadata.get(CHECKED_METADATA_KEY),
+            Some(&"true".to_string())
+        );
+        assert_eq!(blocks[1].metadata.get("due"), Some(&"friday".to_string()));
+        assert_eq!(blocks[2].content, "[x] not a task");
+        assert!(blocks[2].metadata.is_empty());
+
+        assert_eq!(
+            blocks_to_markdown(&blocks, IndentStyle::default()),
+            markdown
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_with_each_indent_style() {
+        let mut parent = test_block("parent-id", None, "Parent\nsecond line", 1.0);
+        parent
+            .metadata
+            .insert("status".to_string(), "active".to_string());
+        let mut child = test_block("child-id", Some("parent-id"), "Child", 1.0);
+        child
+            .metadata
+            .insert(CHECKED_METADATA_KEY.to_string(), "true".to_string());
+        let grandchild = test_block("grandchild-id", Some("child-id"), "Grandchild", 1.0);
+
+        let styles = [
+            (IndentStyle::default(), "  "),
+            (
+                IndentStyle {
+                    width: 4,
+                    use_tabs: false,
+                },
+                "    ",
+            ),
+            (
+                IndentStyle {
+                    width: 4,
+                    use_tabs: true,
+                },
+                "\t",
+            ),
+        ];
+
+        for (style, unit) in styles {
+            let markdown =
+                blocks_to_markdown(&[parent.clone(), child.clone(), grandchild.clone()], style);
+            assert!(
+                markdown.contains(&format!("\n{}- [x] Child\n", unit)),
+                "{:?}: {}",
+                style,
+                markdown
+            );
+            assert!(markdown.contains(&format!("\n{}{}ID::grandchild-id\n", unit, unit.repeat(2))));
+
+            let blocks = markdown_to_blocks(&markdown, "test-page", style);
+            assert_eq!(blocks.len(), 3, "{:?}", style);
+            assert_eq!(blocks[0].id, "parent-id");
+            assert_eq!(blocks[0].content, "Parent\nsecond line");
+            assert_eq!(
+                blocks[0].metadata.get("status"),
+                Some(&"active".to_string())
+            );
+            assert_eq!(blocks[1].parent_id.as_deref(), Some("parent-id"));
+            assert_eq!(
+                blocks[1].metadata.get(CHECKED_METADATA_KEY),
+                Some(&"true".to_string())
+            );
+            assert_eq!(blocks[2].parent_id.as_deref(), Some("child-id"));
+
+            assert_eq!(blocks_to_markdown(&blocks, style), markdown);
+
+            // A file written before the setting changed still nests the way it looks
+            for setting in [IndentStyle::default(), styles[1].0, styles[2].0] {
+                assert_eq!(
+                    IndentStyle::detect(&markdown, setting).unit_len(),
+                    unit.len()
+                );
+                let reparsed = markdown_to_blocks(&markdown, "test-page", setting);
+                assert_eq!(reparsed[2].parent_id.as_deref(), Some("child-id"));
+                assert_eq!(normalize_marker_layout(&markdown, setting), markdown);
+            }
+        }
+        assert_eq!(
+            IndentStyle::detect(
+                "- Flat
+",
+                styles[1].0
+            ),
+            styles[1].0
+        );
+    }
+
+    #[test]
+    fn test_plain_markdown_drops_markers_and_keeps_code() {
+        let mut parent = test_block("parent-id", None, "Parent", 1.0);
+        parent
+            .metadata
+            .insert("status".to_string(), "active".to_string());
+        let mut child = test_block("child-id", Some("parent-id"), "Task", 1.0);
+        child
+            .metadata
+            .insert(CHECKED_METADATA_KEY.to_string(), "false".to_string());
+        child
+            .metadata
+            .insert("status".to_string(), "done: yes".to_string());
+        let mut code = test_block("code-id", Some("parent-id"), "let x = 1;\n\nx", 2.0);
+        code.block_type = BlockType::Code;
+        code.language = Some("rust".to_string());
+        let blocks = [parent, child, code];
+
+        let expected_body = "- Parent\n  - [ ] Task\n  ```rust\n  let x = 1;\n  \n  x\n  ```\n";
+        assert_eq!(
+            blocks_to_plain_markdown(&blocks, IndentStyle::default(), false),
+            expected_body
+        );
+        assert_eq!(
+            blocks_to_plain_markdown(&blocks, IndentStyle::default(), true),
+            format!(
+                "---\nstatus:\n  - active\n  - \"done: yes\"\n---\n\n{}",
+                expected_body
+            )
+        );
+    }
+
+    #[test]
+    fn test_subtree_markdown_rebases_depth() {
+        let mut root = test_block("root", Some("outer"), "Root", 1.0);
+        root.is_collapsed = true;
+        root.metadata
+            .insert("status".to_string(), "active".to_string());
+        let blocks = [
+            test_block("grandchild", Some("b"), "Grandchild", 1.0),
+            test_block("b", Some("root"), "Second", 2.0),
+            test_block("a", Some("root"), "First", 1.0),
+            root,
+        ];
+
+        assert_eq!(
+            subtree_to_plain_markdown(&blocks, "root", IndentStyle::default()),
+            "- Root\n  - First\n  - Second\n    - Grandchild\n"
+        );
+        assert_eq!(
+            subtree_to_plain_markdown(&blocks, "b", IndentStyle::default()),
+            "- Second\n  - Grandchild\n"
+        );
+        assert_eq!(
+            subtree_to_plain_markdown(&blocks, "missing", IndentStyle::default()),
+            ""
+        );
+    }
 }
@@ -0,0 +1,138 @@
+use crate::models::block::Block;
+use std::collections::HashMap;
+
+/// Maximum number of characters kept from a block's content in a node label
+const MAX_LABEL_CHARS: usize = 60;
+
+/// Convert a page's blocks to a Mermaid `mindmap` diagram.
+/// The page title becomes the root node and block nesting becomes mindmap nesting.
+/// Children are ordered by order_weight, the same way the markdown serializer walks the tree.
+pub fn blocks_to_mermaid_mindmap(title: &str, blocks: &[Block]) -> String {
+    let mut children_map: HashMap<Option<String>, Vec<&Block>> = HashMap::new();
+
+    for block in blocks {
+        children_map
+            .entry(block.parent_id.clone())
+            .or_default()
+            .push(block);
+    }
+
+    for children in children_map.values_mut() {
+        children.sort_by(|a, b| a.cmp_position(b));
+    }
+
+    let mut output = String::from("mindmap\n");
+    output.push_str(&format!("  root((\"{}\"))\n", escape_mermaid_label(title)));
+
+    let mut next_id = 0;
+    render_nodes(&children_map, None, 2, &mut next_id, &mut output);
+
+    output
+}
+
+fn render_nodes(
+    children_map: &HashMap<Option<String>, Vec<&Block>>,
+    parent_id: Option<String>,
+    depth: usize,
+    next_id: &mut usize,
+    output: &mut String,
+) {
+    let Some(children) = children_map.get(&parent_id) else {
+        return;
+    };
+
+    for block in children {
+        let indent = "  ".repeat(depth);
+        output.push_str(&format!(
+            "{}n{}[\"{}\"]\n",
+            indent,
+            next_id,
+            escape_mermaid_label(&block.content)
+        ));
+        *next_id += 1;
+
+        render_nodes(children_map, Some(block.id.clone()), depth + 1, next_id, output);
+    }
+}
+
+/// Make text safe to place inside a quoted Mermaid label.
+/// Multi-line content is flattened, long content is truncated, and characters that
+/// would terminate the label are replaced with Mermaid entity codes.
+pub fn escape_mermaid_label(text: &str) -> String {
+    let flattened = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let truncated = if flattened.chars().count() > MAX_LABEL_CHARS {
+        let mut cut: String = flattened.chars().take(MAX_LABEL_CHARS).collect();
+        cut.push('…');
+        cut
+    } else {
+        flattened
+    };
+
+    let mut escaped = String::with_capacity(truncated.len());
+    for c in truncated.chars() {
+        match c {
+            // '#' starts an entity code, so it must be escaped before anything else
+            '#' => escaped.push_str("#35;"),
+            '"' => escaped.push_str("#quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::block::BlockType;
+
+    fn block(id: &str, parent_id: Option<&str>, content: &str, order_weight: f64) -> Block {
+        Block {
+            id: id.to_string(),
+            page_id: "page".to_string(),
+            parent_id: parent_id.map(|p| p.to_string()),
+            content: content.to_string(),
+            order_weight,
+            order_key: String::new(),
+            is_collapsed: false,
+            block_type: BlockType::Bullet,
+            language: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_mindmap_hierarchy_and_escaping() {
+        let blocks = vec![
+            block("b", None, "Second", 2.0),
+            block("a", None, "First", 1.0),
+            block("a1", Some("a"), "Say \"hi\"", 1.0),
+            block("a1x", Some("a1"), "Deep", 1.0),
+        ];
+
+        let output = blocks_to_mermaid_mindmap("My Page", &blocks);
+
+        assert_eq!(
+            output,
+            "mindmap\n\
+             \x20 root((\"My Page\"))\n\
+             \x20   n0[\"First\"]\n\
+             \x20     n1[\"Say #quot;hi#quot;\"]\n\
+             \x20       n2[\"Deep\"]\n\
+             \x20   n3[\"Second\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_escape_mermaid_label() {
+        assert_eq!(escape_mermaid_label("C# notes"), "C#35; notes");
+        assert_eq!(escape_mermaid_label("line one\nline two"), "line one line two");
+
+        let long = "x".repeat(100);
+        let escaped = escape_mermaid_label(&long);
+        assert_eq!(escaped.chars().count(), MAX_LABEL_CHARS + 1);
+        assert!(escaped.ends_with('…'));
+    }
+}
@@ -0,0 +1,226 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::{METADATA_DIR_NAME, METADATA_SCHEMA_FILENAME};
+use crate::utils::natural_date::parse_natural_date;
+
+/// Declared type of a block metadata key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataType {
+    Text,
+    Number,
+    Boolean,
+    Date,
+    List,
+    Map,
+}
+
+/// Key types from the workspace's `.oxinot/metadata_schema.json`:
+///
+/// ```json
+/// { "keys": { "priority": "number", "due": "date", "tags": "list" } }
+/// ```
+///
+/// Keys the schema doesn't mention are stored as plain text, so a workspace without
+/// a schema file behaves exactly as before.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataSchema {
+    #[serde(default)]
+    pub keys: HashMap<String, MetadataType>,
+}
+
+/// A metadata value that doesn't fit its declared type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataValidationError {
+    pub key: String,
+    pub expected: MetadataType,
+    pub value: String,
+    pub message: String,
+}
+
+impl MetadataSchema {
+    /// Read the schema from the workspace; a missing file declares nothing, a
+    /// malformed one is an error so typos don't silently disable validation
+    pub fn load(workspace_root: &Path) -> Result<Self, String> {
+        let path = workspace_root
+            .join(METADATA_DIR_NAME)
+            .join(METADATA_SCHEMA_FILENAME);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid {}: {}", METADATA_SCHEMA_FILENAME, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!(
+                "Failed to read {}: {}",
+                METADATA_SCHEMA_FILENAME, e
+            )),
+        }
+    }
+
+    /// Check `metadata` against the declared key types, returning the values to
+    /// store. Values that can be read unambiguously are normalized (`Yes` becomes
+    /// `true`, `tomorrow` becomes an ISO date relative to `today`, `a, b` becomes
+    /// `["a","b"]`); anything else is reported per key.
+    pub fn apply(
+        &self,
+        metadata: &HashMap<String, String>,
+        today: NaiveDate,
+    ) -> Result<HashMap<String, String>, Vec<MetadataValidationError>> {
+        let mut coerced = HashMap::with_capacity(metadata.len());
+        let mut errors = Vec::new();
+
+        for (key, value) in metadata {
+            let Some(&expected) = self.keys.get(key) else {
+                coerced.insert(key.clone(), value.clone());
+                continue;
+            };
+            match coerce_value(expected, value, today) {
+                Some(normalized) => {
+                    coerced.insert(key.clone(), normalized);
+                }
+                None => errors.push(MetadataValidationError {
+                    key: key.clone(),
+                    expected,
+                    value: value.clone(),
+                    message: format!("{} expects {}", key, describe(expected)),
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(coerced)
+        } else {
+            errors.sort_by(|a, b| a.key.cmp(&b.key));
+            Err(errors)
+        }
+    }
+}
+
+/// One readable line for a set of validation errors
+pub fn format_validation_errors(errors: &[MetadataValidationError]) -> String {
+    let details: Vec<String> = errors
+        .iter()
+        .map(|error| format!("{} (got {:?})", error.message, error.value))
+        .collect();
+    format!("Invalid metadata: {}", details.join("; "))
+}
+
+fn coerce_value(expected: MetadataType, value: &str, today: NaiveDate) -> Option<String> {
+    let trimmed = value.trim();
+    match expected {
+        MetadataType::Text => Some(value.to_string()),
+        MetadataType::Number => trimmed
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|_| trimmed.to_string()),
+        MetadataType::Boolean => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some("true".to_string()),
+            "false" | "no" | "off" | "0" => Some("false".to_string()),
+            _ => None,
+        },
+        MetadataType::Date => parse_natural_date(trimmed, today)
+            .or_else(|| NaiveDate::parse_from_str(trimmed, "%Y/%m/%d").ok())
+            .map(|date| date.format("%Y-%m-%d").to_string()),
+        MetadataType::List => match serde_json::from_str::<serde_json::Value>(trimmed) {
+            Ok(serde_json::Value::Array(_)) => Some(trimmed.to_string()),
+            _ if trimmed.starts_with('[') || trimmed.starts_with('{') => None,
+            _ => {
+                let items: Vec<&str> = trimmed
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .collect();
+                serde_json::to_string(&items).ok()
+            }
+        },
+        MetadataType::Map => match serde_json::from_str::<serde_json::Value>(trimmed) {
+            Ok(serde_json::Value::Object(_)) => Some(trimmed.to_string()),
+            _ => None,
+        },
+    }
+}
+
+fn describe(expected: MetadataType) -> &'static str {
+    match expected {
+        MetadataType::Text => "text",
+        MetadataType::Number => "a number",
+        MetadataType::Boolean => "true or false",
+        MetadataType::Date => "a date (YYYY-MM-DD)",
+        MetadataType::List => "a list (JSON array or comma-separated)",
+        MetadataType::Map => "a JSON object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> MetadataSchema {
+        serde_json::from_str(
+            r#"{"keys": {"priority": "number", "done": "boolean", "due": "date",
+                         "tags": "list", "extra": "map", "note": "text"}}"#,
+        )
+        .unwrap()
+    }
+
+    fn metadata(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_coerces_values_to_declared_types() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let input = metadata(&[
+            ("priority", " 2.5 "),
+            ("done", "Yes"),
+            ("due", "tomorrow"),
+            ("tags", "work, urgent"),
+            ("extra", "{\"a\": 1}"),
+            ("note", " as written "),
+            ("unknown", "anything"),
+        ]);
+
+        let stored = schema().apply(&input, today).unwrap();
+        assert_eq!(stored["priority"], "2.5");
+        assert_eq!(stored["done"], "true");
+        assert_eq!(stored["due"], "2024-03-02");
+        assert_eq!(stored["tags"], "[\"work\",\"urgent\"]");
+        assert_eq!(stored["extra"], "{\"a\": 1}");
+        assert_eq!(stored["note"], " as written ");
+        assert_eq!(stored["unknown"], "anything");
+
+        let untyped = MetadataSchema::default().apply(&input, today).unwrap();
+        assert_eq!(untyped, input);
+    }
+
+    #[test]
+    fn test_reports_each_mismatched_key() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let input = metadata(&[
+            ("priority", "high"),
+            ("done", "maybe"),
+            ("due", "someday"),
+            ("tags", "{\"not\": \"a list\"}"),
+            ("extra", "[1]"),
+        ]);
+
+        let errors = schema().apply(&input, today).unwrap_err();
+        let keys: Vec<&str> = errors.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["done", "due", "extra", "priority", "tags"]);
+        assert_eq!(errors[3].expected, MetadataType::Number);
+        assert_eq!(errors[3].value, "high");
+
+        let message = format_validation_errors(&errors[3..4]);
+        assert_eq!(
+            message,
+            "Invalid metadata: priority expects a number (got \"high\")"
+        );
+    }
+}
@@ -1,8 +1,16 @@
+pub mod code_language;
+pub mod csv;
 pub mod events;
 pub mod fractional_index;
+pub mod graph_cluster;
+pub mod html;
 pub mod markdown;
+pub mod mermaid;
+pub mod metadata_schema;
+pub mod natural_date;
 pub mod page_sync;
 pub mod path;
+pub mod sync_ignore;
 pub mod url_validator;
 
 pub use url_validator::UrlValidator;
@@ -0,0 +1,98 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Resolve a date phrase relative to `today`.
+///
+/// Accepts `today`, `tomorrow`, `yesterday`, `in N days|weeks`, `next week`,
+/// a weekday (`friday`, the next one after today), `next <weekday>` (the one in the
+/// following week) and ISO dates (`2024-03-01`). Case and surrounding spaces are ignored.
+pub fn parse_natural_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let phrase = input.trim().to_lowercase();
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => today.succ_opt(),
+        ["yesterday"] => today.pred_opt(),
+        ["next", "week"] => today.checked_add_signed(Duration::weeks(1)),
+        ["in", count, unit] => {
+            let count: i64 = count.parse().ok()?;
+            let offset = match *unit {
+                "day" | "days" => Duration::try_days(count)?,
+                "week" | "weeks" => Duration::try_weeks(count)?,
+                _ => return None,
+            };
+            today.checked_add_signed(offset)
+        }
+        ["next", day] => {
+            let upcoming = next_weekday(today, parse_weekday(day)?)?;
+            // "next monday" skips the one in the current week
+            if week_start(upcoming) == week_start(today) {
+                upcoming.checked_add_signed(Duration::weeks(1))
+            } else {
+                Some(upcoming)
+            }
+        }
+        [word] => match parse_weekday(word) {
+            Some(weekday) => next_weekday(today, weekday),
+            None => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+        },
+        _ => None,
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thur" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The first `weekday` strictly after `today`
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let ahead = if ahead == 0 { 7 } else { ahead };
+    today.checked_add_signed(Duration::days(ahead as i64))
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_relative_phrases() {
+        // A Wednesday
+        let parse = |phrase| parse_natural_date(phrase, date(2024, 2, 28));
+        assert_eq!(parse("tomorrow"), Some(date(2024, 2, 29)));
+        assert_eq!(parse(" Today "), Some(date(2024, 2, 28)));
+        assert_eq!(parse("in 3 days"), Some(date(2024, 3, 2)));
+        assert_eq!(parse("in 2 weeks"), Some(date(2024, 3, 13)));
+        assert_eq!(parse("friday"), Some(date(2024, 3, 1)));
+        assert_eq!(parse("wednesday"), Some(date(2024, 3, 6)));
+        assert_eq!(parse("next monday"), Some(date(2024, 3, 4)));
+        assert_eq!(parse("next friday"), Some(date(2024, 3, 8)));
+    }
+
+    #[test]
+    fn test_parse_iso_and_invalid() {
+        let parse = |phrase| parse_natural_date(phrase, date(2024, 2, 28));
+        assert_eq!(parse("2024-03-01"), Some(date(2024, 3, 1)));
+        assert_eq!(parse("2024-02-30"), None);
+        assert_eq!(parse("someday soon"), None);
+        assert_eq!(parse("in three days"), None);
+        assert_eq!(parse(""), None);
+    }
+}
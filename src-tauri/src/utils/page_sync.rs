@@ -1,10 +1,103 @@
 use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use tokio::fs;
 
+use crate::commands::workspace::{load_indent_style, load_trailing_newline_policy};
 use crate::models::block::Block;
-use crate::utils::markdown::{blocks_to_markdown, sanitize_content_for_markdown};
+use crate::utils::markdown::{
+    blocks_to_markdown, bullet_first_line, parse_checked_value, prepend_frontmatter,
+    sanitize_content_for_markdown, split_frontmatter, IndentStyle, CHECKED_METADATA_KEY,
+};
+
+/// How page files end, set per workspace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingNewlinePolicy {
+    /// Exactly one trailing newline on non-empty files
+    #[default]
+    Always,
+    /// No trailing newline
+    Never,
+    /// Keep whether the file ended with a newline (extra blank lines are still dropped)
+    Preserve,
+}
+
+/// End `text` according to `policy`. `had_trailing_newline` is what the file on disk
+/// had before, used by `Preserve`.
+pub fn apply_trailing_newline_policy(
+    text: &str,
+    policy: TrailingNewlinePolicy,
+    had_trailing_newline: bool,
+) -> String {
+    let body = text.trim_end_matches(['\r', '\n']);
+    let wants_newline = match policy {
+        TrailingNewlinePolicy::Always => !body.is_empty(),
+        TrailingNewlinePolicy::Never => false,
+        TrailingNewlinePolicy::Preserve => had_trailing_newline && !body.is_empty(),
+    };
+
+    let mut out = body.to_string();
+    if wants_newline {
+        out.push('\n');
+    }
+    out
+}
+
+/// Rewrite one file's ending to match `policy`. `Preserve` never changes a file.
+/// Returns whether the file needed (with `dry_run`) or got a change.
+pub(crate) fn normalize_file_trailing_newline(
+    full_path: &std::path::Path,
+    policy: TrailingNewlinePolicy,
+    dry_run: bool,
+) -> Result<bool, String> {
+    if policy == TrailingNewlinePolicy::Preserve {
+        return Ok(false);
+    }
+
+    let text = std::fs::read_to_string(full_path)
+        .map_err(|e| format!("Failed to read {:?}: {}", full_path, e))?;
+    let normalized = apply_trailing_newline_policy(&text, policy, text.ends_with('\n'));
+    if normalized == text {
+        return Ok(false);
+    }
+
+    if !dry_run {
+        std::fs::write(full_path, normalized)
+            .map_err(|e| format!("Failed to write {:?}: {}", full_path, e))?;
+    }
+    Ok(true)
+}
+
+/// How a mutation reached the page file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncStrategy {
+    InsertionPatch,
+    ContentPatch,
+    DeletionPatch,
+    RelocationPatch,
+    MergePatch,
+    /// A patch bailed out (or none applied) and the whole page was re-rendered
+    FullRewrite,
+    /// The page has no file, nothing was written
+    Skipped,
+}
+
+/// Whether mutation responses report their sync strategy. Off by default so normal
+/// responses stay unchanged.
+static SYNC_STRATEGY_DEBUG: AtomicBool = AtomicBool::new(false);
+
+pub fn set_sync_strategy_debug(enabled: bool) {
+    SYNC_STRATEGY_DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+/// `strategy` if responses should report it, else `None`
+pub fn debug_sync_strategy(strategy: SyncStrategy) -> Option<SyncStrategy> {
+    SYNC_STRATEGY_DEBUG.load(Ordering::Relaxed).then_some(strategy)
+}
 
 /// Compute leading whitespace count (spaces or tabs) as "indent length".
 fn indent_len(s: &str) -> usize {
@@ -12,20 +105,30 @@ fn indent_len(s: &str) -> usize {
 }
 
 /// Serialize a Bullet block content to the exact on-disk segment used by the canonical serializer,
-/// excluding the trailing ID marker line.
-fn bullet_content_to_segment_lines(indent: &str, content: &str) -> Vec<String> {
+/// excluding the trailing ID marker line. `checked` is the task checkbox state, if any.
+fn bullet_content_to_segment_lines(
+    indent: &str,
+    checked: Option<bool>,
+    content: &str,
+) -> Vec<String> {
     let sanitized = sanitize_content_for_markdown(content);
     let content_lines: Vec<&str> = sanitized.lines().collect();
 
     let mut out: Vec<String> = Vec::new();
     let first = content_lines.first().copied().unwrap_or("");
-    out.push(format!("{}- {}", indent, first));
+    out.push(format!("{}{}", indent, bullet_first_line(checked, first)));
     for &line in content_lines.iter().skip(1) {
         out.push(format!("{}{}", indent, line));
     }
     out
 }
 
+/// Indentation to patch a page file's lines with: the file's own, which may predate
+/// the workspace setting, so patched lines line up with their neighbors.
+fn file_indent_style(workspace_path: &str, lines: &[String]) -> IndentStyle {
+    IndentStyle::detect(&lines.join("\n"), load_indent_style(workspace_path))
+}
+
 /// Read the page markdown file and return its lines + whether it had a trailing '\n'.
 async fn read_page_lines(full_path: &std::path::Path) -> Result<(Vec<String>, bool), String> {
     let file_text = fs::read_to_string(full_path)
@@ -36,19 +139,30 @@ async fn read_page_lines(full_path: &std::path::Path) -> Result<(Vec<String>, bo
     Ok((lines, had_trailing_newline))
 }
 
-/// Write lines back to the page markdown file, preserving whether it originally had a trailing '\n'
-/// when possible. (We keep a trailing newline for non-empty files.)
+/// Write lines back to the page markdown file, ending it as the workspace's
+/// trailing-newline policy asks.
 async fn write_page_lines(
+    workspace_path: &str,
     full_path: &std::path::Path,
     lines: Vec<String>,
     had_trailing_newline: bool,
 ) -> Result<(), String> {
-    let mut new_text = lines.join("\n");
-    if had_trailing_newline || !new_text.is_empty() {
-        new_text.push('\n');
-    }
+    let new_text = apply_trailing_newline_policy(
+        &lines.join("\n"),
+        load_trailing_newline_policy(workspace_path),
+        had_trailing_newline,
+    );
 
-    // Atomic write: write to temp file, sync, then rename
+    write_file_atomic(full_path, &new_text).await
+}
+
+/// Replace a file's contents without ever leaving it half-written: write a
+/// sibling `.name.tmp`, fsync it, then rename it over the target. On failure
+/// the temp file is removed and the original file is left untouched.
+pub(crate) async fn write_file_atomic(
+    full_path: &std::path::Path,
+    contents: &str,
+) -> Result<(), String> {
     let parent = full_path
         .parent()
         .ok_or_else(|| "Invalid file path: no parent directory".to_string())?;
@@ -65,26 +179,36 @@ async fn write_page_lines(
         .ok_or_else(|| "Invalid file path: no file name".to_string())?;
     let temp_path = parent.join(format!(".{}.tmp", file_name.to_string_lossy()));
 
-    // Write to temporary file
-    fs::write(&temp_path, &new_text)
-        .await
-        .map_err(|e| format!("Failed to write temporary file: {}", e))?;
+    let result = write_and_rename(&temp_path, full_path, contents).await;
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path).await;
+    }
+    result
+}
 
-    // Sync to disk
-    let temp_file = fs::File::open(&temp_path)
+async fn write_and_rename(
+    temp_path: &std::path::Path,
+    full_path: &std::path::Path,
+    contents: &str,
+) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut temp_file = fs::File::create(temp_path)
         .await
-        .map_err(|e| format!("Failed to open temporary file for sync: {}", e))?;
+        .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+    temp_file
+        .write_all(contents.as_bytes())
+        .await
+        .map_err(|e| format!("Failed to write temporary file: {}", e))?;
     temp_file
         .sync_all()
         .await
         .map_err(|e| format!("Failed to sync temporary file to disk: {}", e))?;
+    drop(temp_file);
 
-    // Atomically rename to target path
-    fs::rename(&temp_path, full_path)
+    fs::rename(temp_path, full_path)
         .await
-        .map_err(|e| format!("Failed to rename temporary file to target: {}", e))?;
-
-    Ok(())
+        .map_err(|e| format!("Failed to rename temporary file to target: {}", e))
 }
 
 /// External modification guard based on pages.file_mtime/file_size.
@@ -123,7 +247,7 @@ async fn is_safe_to_patch_file(
 }
 
 /// Update page metadata (mtime/size) after patch write.
-async fn update_page_file_metadata(
+pub(crate) async fn update_page_file_metadata(
     conn_mutex: &Mutex<Connection>,
     full_path: &std::path::Path,
     page_id: &str,
@@ -158,19 +282,23 @@ fn find_marker_idx(lines: &[String], block_id: &str) -> Option<usize> {
 }
 
 /// From a marker line index, walk upward to find the bullet-start line (`- `).
-/// Note: Marker lines are 2 spaces MORE indented than their bullet lines.
-/// Returns the start index of the segment.
-fn find_bullet_segment_start(lines: &[String], marker_idx: usize) -> Option<usize> {
+/// Note: Marker lines are one indent level MORE indented than their bullet lines.
+/// Returns the start index of the segment, or None for a code or fence block's marker.
+fn find_bullet_segment_start(
+    lines: &[String],
+    marker_idx: usize,
+    style: IndentStyle,
+) -> Option<usize> {
     if marker_idx == 0 {
         return None;
     }
 
-    // Marker is 2 spaces more indented than the bullet line
+    // Marker is one level more indented than the bullet line
     let marker_indent = indent_len(&lines[marker_idx]);
-    let bullet_indent = if marker_indent >= 2 {
-        marker_indent - 2
+    let bullet_indent = if marker_indent >= style.unit_len() {
+        marker_indent - style.unit_len()
     } else {
-        return None; // Invalid: marker should be at least 2 spaces
+        return None; // Invalid: marker should be at least one level in
     };
 
     let mut j = marker_idx;
@@ -186,9 +314,14 @@ fn find_bullet_segment_start(lines: &[String], marker_idx: usize) -> Option<usiz
             // Different indent - could be content continuation or error
             return None;
         }
-        if lines[j].trim_start().starts_with("- ") {
+        let trimmed = lines[j].trim_start();
+        if trimmed.starts_with("- ") {
             return Some(j);
         }
+        // The marker follows a code or fence block's closing fence, not a bullet
+        if trimmed.starts_with("```") || trimmed.starts_with("///") {
+            return None;
+        }
     }
 
     None
@@ -203,6 +336,7 @@ fn find_bullet_subtree_end(
     lines: &[String],
     root_start: usize,
     root_marker_idx: usize,
+    style: IndentStyle,
 ) -> Option<usize> {
     if root_start > root_marker_idx || root_marker_idx >= lines.len() {
         return None;
@@ -219,8 +353,13 @@ fn find_bullet_subtree_end(
         if trimmed.starts_with("ID::") {
             let seg_indent = indent_len(line);
             if seg_indent <= root_indent {
-                // Next sibling/ancestor segment; subtree ends just before this segment.
-                return Some(i - 1);
+                // Next sibling/ancestor segment; subtree ends just before this segment's
+                // bullet line (not its marker).
+                let seg_start = find_bullet_segment_start(lines, i, style)?;
+                if seg_start <= root_marker_idx {
+                    return None;
+                }
+                return Some(seg_start - 1);
             }
         }
 
@@ -256,9 +395,13 @@ fn collect_descendant_ids(conn: &Connection, block_id: &str) -> Result<Vec<Strin
 }
 
 
-/// Re-indent a subtree by adjusting leading spaces on each line by `indent_delta` (can be negative).
-/// This assumes indent is represented with spaces (canonical serializer uses two spaces per depth).
-fn reindent_subtree_lines(subtree_lines: &mut [String], indent_delta: isize) -> Result<(), String> {
+/// Re-indent a subtree by adjusting leading whitespace on each line by `indent_delta` (can be negative).
+/// The delta counts indent characters of the workspace's indent style (spaces, or tabs).
+fn reindent_subtree_lines(
+    subtree_lines: &mut [String],
+    indent_delta: isize,
+    style: IndentStyle,
+) -> Result<(), String> {
     if indent_delta == 0 {
         return Ok(());
     }
@@ -270,7 +413,7 @@ fn reindent_subtree_lines(subtree_lines: &mut [String], indent_delta: isize) ->
             return Err("Invalid negative indent during subtree reindent".to_string());
         }
         let trimmed = line.trim_start().to_string();
-        *line = format!("{}{}", " ".repeat(new_indent as usize), trimmed);
+        *line = format!("{}{}", style.whitespace(new_indent as usize), trimmed);
     }
 
     Ok(())
@@ -279,7 +422,7 @@ fn reindent_subtree_lines(subtree_lines: &mut [String], indent_delta: isize) ->
 /// Attempt to relocate a Bullet subtree (move/indent/outdent) using a multi-hunk patch:
 /// 1) Cut the subtree block region anchored by `ID::<block_id>`
 /// 2) Adjust indentation based on the destination parent depth (derived from sibling anchors)
-/// 3) Insert the subtree near destination siblings based on DB `order_weight`
+/// 3) Insert the subtree near destination siblings based on DB `order_key`
 ///
 /// Conservative behavior:
 /// - Only acts when the block exists in DB and is Bullet.
@@ -313,10 +456,10 @@ async fn try_patch_bullet_subtree_relocation(
     }
 
     // Must exist in DB to derive destination/ordering
-    let (parent_id, order_weight, block_type): (Option<String>, f64, String) = {
+    let (parent_id, order_key, block_type): (Option<String>, String, String) = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT parent_id, order_weight, block_type
+            "SELECT parent_id, order_key, block_type
              FROM blocks
              WHERE id = ? AND page_id = ?",
             params![moved_block_id, page_id],
@@ -337,10 +480,10 @@ async fn try_patch_bullet_subtree_relocation(
              FROM blocks
              WHERE page_id = ?
                AND parent_id IS ?
-               AND order_weight > ?
-             ORDER BY order_weight ASC
+               AND order_key > ?
+             ORDER BY order_key ASC
              LIMIT 1",
-            params![page_id, parent_id, order_weight],
+            params![page_id, parent_id, &order_key],
             |row| row.get(0),
         )
         .ok()
@@ -353,25 +496,29 @@ async fn try_patch_bullet_subtree_relocation(
              FROM blocks
              WHERE page_id = ?
                AND parent_id IS ?
-               AND order_weight < ?
-             ORDER BY order_weight DESC
+               AND order_key < ?
+             ORDER BY order_key DESC
              LIMIT 1",
-            params![page_id, parent_id, order_weight],
+            params![page_id, parent_id, &order_key],
             |row| row.get(0),
         )
         .ok()
     };
 
     let (mut lines, had_trailing_newline) = read_page_lines(&full_path).await?;
+    let indent_style = file_indent_style(workspace_path, &lines);
 
     // ---- Cut subtree from source location (anchored by moved_block_id marker) ----
     let Some(src_marker_idx) = find_marker_idx(&lines, moved_block_id) else {
         return Ok(false);
     };
-    let Some(src_start_idx) = find_bullet_segment_start(&lines, src_marker_idx) else {
+    let Some(src_start_idx) = find_bullet_segment_start(&lines, src_marker_idx, indent_style)
+    else {
         return Ok(false);
     };
-    let Some(src_end_idx) = find_bullet_subtree_end(&lines, src_start_idx, src_marker_idx) else {
+    let Some(src_end_idx) =
+        find_bullet_subtree_end(&lines, src_start_idx, src_marker_idx, indent_style)
+    else {
         return Ok(false);
     };
 
@@ -412,7 +559,7 @@ async fn try_patch_bullet_subtree_relocation(
     // ---- Determine destination indent from sibling/parent anchors ----
     // We infer desired root indent from destination siblings' marker lines (preferred), otherwise:
     // - if parent is None => root indent 0
-    // - else find parent marker indent and add +2 spaces
+    // - else find parent marker indent (one level in from the parent bullet)
     let mut dest_root_indent_opt: Option<usize> = None;
 
     if let Some(ns) = next_sibling_id.as_deref() {
@@ -440,7 +587,7 @@ async fn try_patch_bullet_subtree_relocation(
                 return Ok(false);
             };
             let parent_marker_indent = indent_len(&lines[pmi]);
-            // Child bullet indent should match parent marker indent (parent bullet indent + 2)
+            // Child bullet indent should match parent marker indent (parent bullet indent + 1 level)
             dest_root_indent_opt = Some(parent_marker_indent);
         } else {
             dest_root_indent_opt = Some(0);
@@ -451,7 +598,7 @@ async fn try_patch_bullet_subtree_relocation(
 
     // Apply reindent to the entire subtree
     let indent_delta = dest_root_indent as isize - src_root_indent as isize;
-    reindent_subtree_lines(&mut subtree_lines, indent_delta)?;
+    reindent_subtree_lines(&mut subtree_lines, indent_delta, indent_style)?;
 
     // ---- Compute insertion point in updated document lines ----
     // Insert before next sibling segment start, else after prev sibling subtree end,
@@ -460,7 +607,8 @@ async fn try_patch_bullet_subtree_relocation(
         let Some(ns_marker_idx) = find_marker_idx(&lines, ns) else {
             return Ok(false);
         };
-        let Some(ns_start_idx) = find_bullet_segment_start(&lines, ns_marker_idx) else {
+        let Some(ns_start_idx) = find_bullet_segment_start(&lines, ns_marker_idx, indent_style)
+        else {
             return Ok(false);
         };
         ns_start_idx
@@ -468,10 +616,13 @@ async fn try_patch_bullet_subtree_relocation(
         let Some(ps_marker_idx) = find_marker_idx(&lines, ps) else {
             return Ok(false);
         };
-        let Some(ps_start_idx) = find_bullet_segment_start(&lines, ps_marker_idx) else {
+        let Some(ps_start_idx) = find_bullet_segment_start(&lines, ps_marker_idx, indent_style)
+        else {
             return Ok(false);
         };
-        let Some(ps_end_idx) = find_bullet_subtree_end(&lines, ps_start_idx, ps_marker_idx) else {
+        let Some(ps_end_idx) =
+            find_bullet_subtree_end(&lines, ps_start_idx, ps_marker_idx, indent_style)
+        else {
             return Ok(false);
         };
         ps_end_idx + 1
@@ -487,7 +638,7 @@ async fn try_patch_bullet_subtree_relocation(
     // Insert relocated subtree (multi-hunk complete)
     lines.splice(insert_at..insert_at, subtree_lines);
 
-    write_page_lines(&full_path, lines, had_trailing_newline).await?;
+    write_page_lines(workspace_path, &full_path, lines, had_trailing_newline).await?;
     update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
 
     Ok(true)
@@ -539,17 +690,18 @@ async fn try_patch_bullet_block_deletion(
     }
 
     let (mut lines, had_trailing_newline) = read_page_lines(&full_path).await?;
+    let indent_style = file_indent_style(workspace_path, &lines);
 
     let Some(mi) = find_marker_idx(&lines, deleted_block_id) else {
         return Ok(false);
     };
-    let Some(si) = find_bullet_segment_start(&lines, mi) else {
+    let Some(si) = find_bullet_segment_start(&lines, mi, indent_style) else {
         return Ok(false);
     };
 
     lines.drain(si..=mi);
 
-    write_page_lines(&full_path, lines, had_trailing_newline).await?;
+    write_page_lines(workspace_path, &full_path, lines, had_trailing_newline).await?;
     update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
 
     Ok(true)
@@ -599,12 +751,15 @@ async fn try_patch_bullet_block_content(
     }
 
     // Check if block has metadata in DB. If so, fallback to full rewrite to ensure metadata is synced.
-    let has_metadata: bool = {
+    // The checkbox state is the exception: it lives in the bullet line itself, so it is
+    // carried into the patched segment instead.
+    let (has_metadata, checked): (bool, Option<String>) = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT EXISTS(SELECT 1 FROM block_metadata WHERE block_id = ?)",
-            [updated_block_id],
-            |row| row.get(0),
+            "SELECT EXISTS(SELECT 1 FROM block_metadata WHERE block_id = ?1 AND key != ?2),
+                    (SELECT value FROM block_metadata WHERE block_id = ?1 AND key = ?2)",
+            params![updated_block_id, CHECKED_METADATA_KEY],
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .map_err(|e| e.to_string())?
     };
@@ -612,8 +767,10 @@ async fn try_patch_bullet_block_content(
     if has_metadata {
         return Ok(false);
     }
+    let checked = checked.as_deref().map(parse_checked_value);
 
     let (mut lines, had_trailing_newline) = read_page_lines(&full_path).await?;
+    let indent_style = file_indent_style(workspace_path, &lines);
 
     let Some(mi) = find_marker_idx(&lines, updated_block_id) else {
         return Ok(false);
@@ -641,16 +798,18 @@ async fn try_patch_bullet_block_content(
         j += 1;
     }
 
-    let Some(si) = find_bullet_segment_start(&lines, mi) else {
+    let Some(si) = find_bullet_segment_start(&lines, mi, indent_style) else {
         return Ok(false);
     };
 
-    let indent = " ".repeat(indent_len_val);
-    let replacement = bullet_content_to_segment_lines(&indent, &content);
+    // Indent like the bullet line being replaced. Its ID marker sits one level deeper,
+    // so taking the marker's indent would shift every patched block one level right.
+    let indent = indent_style.whitespace(indent_len(&lines[si]));
+    let replacement = bullet_content_to_segment_lines(&indent, checked, &content);
 
     lines.splice(si..mi, replacement);
 
-    write_page_lines(&full_path, lines, had_trailing_newline).await?;
+    write_page_lines(workspace_path, &full_path, lines, had_trailing_newline).await?;
     update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
 
     Ok(true)
@@ -684,10 +843,10 @@ async fn try_patch_bullet_block_insertion(
     }
 
     // Fetch created block (must exist in DB)
-    let (parent_id, order_weight, block_type, content): (Option<String>, f64, String, String) = {
+    let (parent_id, order_key, block_type, content): (Option<String>, String, String, String) = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT parent_id, order_weight, block_type, content
+            "SELECT parent_id, order_key, block_type, content
              FROM blocks
              WHERE id = ? AND page_id = ?",
             params![created_block_id, page_id],
@@ -708,10 +867,10 @@ async fn try_patch_bullet_block_insertion(
              FROM blocks
              WHERE page_id = ?
                AND parent_id IS ?
-               AND order_weight > ?
-             ORDER BY order_weight ASC
+               AND order_key > ?
+             ORDER BY order_key ASC
              LIMIT 1",
-            params![page_id, parent_id, order_weight],
+            params![page_id, parent_id, &order_key],
             |row| row.get(0),
         )
         .ok()
@@ -724,16 +883,17 @@ async fn try_patch_bullet_block_insertion(
              FROM blocks
              WHERE page_id = ?
                AND parent_id IS ?
-               AND order_weight < ?
-             ORDER BY order_weight DESC
+               AND order_key < ?
+             ORDER BY order_key DESC
              LIMIT 1",
-            params![page_id, parent_id, order_weight],
+            params![page_id, parent_id, &order_key],
             |row| row.get(0),
         )
         .ok()
     };
 
     let (mut lines, had_trailing_newline) = read_page_lines(&full_path).await?;
+    let indent_style = file_indent_style(workspace_path, &lines);
 
     if find_marker_idx(&lines, created_block_id).is_some() {
         return Ok(false);
@@ -761,24 +921,29 @@ async fn try_patch_bullet_block_insertion(
     if indent_len_opt.is_none() {
         if let Some(parent_block_id) = parent_id.as_deref() {
             if let Some(parent_marker_idx) = find_marker_idx(&lines, parent_block_id) {
-                let parent_indent = indent_len(&lines[parent_marker_idx]);
-                // Child should be indented 2 more spaces than parent
-                indent_len_opt = Some(parent_indent + 2);
+                // Child bullet indent matches the parent's marker indent (one level in)
+                indent_len_opt = Some(indent_len(&lines[parent_marker_idx]));
             }
         }
     }
 
     let indent_len_val = indent_len_opt.unwrap_or(0);
-    let indent = " ".repeat(indent_len_val);
+    let indent = indent_style.whitespace(indent_len_val);
 
-    let mut insert_segment = bullet_content_to_segment_lines(&indent, &content);
-    insert_segment.push(format!("{}  ID::{}", indent, created_block_id));
+    let mut insert_segment = bullet_content_to_segment_lines(&indent, None, &content);
+    insert_segment.push(format!(
+        "{}{}ID::{}",
+        indent,
+        indent_style.indent(1),
+        created_block_id
+    ));
 
     let insert_at: usize = if let Some(ns) = next_sibling_id.as_deref() {
         let Some(ns_marker_idx) = find_marker_idx(&lines, ns) else {
             return Ok(false);
         };
-        let Some(ns_start_idx) = find_bullet_segment_start(&lines, ns_marker_idx) else {
+        let Some(ns_start_idx) = find_bullet_segment_start(&lines, ns_marker_idx, indent_style)
+        else {
             return Ok(false);
         };
         ns_start_idx
@@ -793,7 +958,164 @@ async fn try_patch_bullet_block_insertion(
 
     lines.splice(insert_at..insert_at, insert_segment);
 
-    write_page_lines(&full_path, lines, had_trailing_newline).await?;
+    write_page_lines(workspace_path, &full_path, lines, had_trailing_newline).await?;
+    update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
+
+    Ok(true)
+}
+
+/// Attempt to apply a block merge (`merged_block_id` folded into `target_block_id`) as a
+/// multi-hunk patch mirroring what `merge_blocks` did in the DB:
+/// 1) Cut the merged block's subtree, dropping its own bullet, marker and metadata lines
+/// 2) Re-indent its children under the target and append them after the target's subtree
+/// 3) Rewrite the target's content segment with its merged content
+///
+/// Conservative behavior:
+/// - The target must exist in DB as a Bullet (the merged block is already gone).
+/// - Requires on-disk file to match DB mtime/size.
+/// - The target's subtree in the patched file must hold exactly its DB descendants.
+/// - Returns `Ok(false)` on any ambiguity, allowing full rewrite fallback.
+async fn try_patch_bullet_block_merge(
+    conn_mutex: &Mutex<Connection>,
+    workspace_path: &str,
+    page_id: &str,
+    target_block_id: &str,
+    merged_block_id: &str,
+) -> Result<bool, String> {
+    let file_path: Option<String> = {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT file_path FROM pages WHERE id = ?",
+            [page_id],
+            |row| row.get(0),
+        )
+        .ok()
+    };
+
+    let Some(rel_path) = file_path else {
+        return Ok(false);
+    };
+
+    let full_path = std::path::Path::new(workspace_path).join(&rel_path);
+
+    if !is_safe_to_patch_file(conn_mutex, &full_path, page_id).await? {
+        return Ok(false);
+    }
+
+    // Merged target content + type, checkbox state and the blocks now under it
+    let (block_type, content, checked, db_descendants) = {
+        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        let (block_type, content): (String, String) = conn
+            .query_row(
+                "SELECT block_type, content FROM blocks WHERE id = ? AND page_id = ?",
+                params![target_block_id, page_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        let checked: Option<String> = conn
+            .query_row(
+                "SELECT value FROM block_metadata WHERE block_id = ? AND key = ?",
+                params![target_block_id, CHECKED_METADATA_KEY],
+                |row| row.get(0),
+            )
+            .ok();
+        let db_descendants: HashSet<String> = collect_descendant_ids(&conn, target_block_id)?
+            .into_iter()
+            .filter(|id| id != target_block_id) // exclude self
+            .collect();
+        (block_type, content, checked, db_descendants)
+    };
+
+    if block_type.to_lowercase() != "bullet" {
+        return Ok(false);
+    }
+    let checked = checked.as_deref().map(parse_checked_value);
+
+    let (mut lines, had_trailing_newline) = read_page_lines(&full_path).await?;
+    let indent_style = file_indent_style(workspace_path, &lines);
+
+    // ---- Cut the merged block's subtree ----
+    let Some(src_marker_idx) = find_marker_idx(&lines, merged_block_id) else {
+        return Ok(false);
+    };
+    let Some(src_start_idx) = find_bullet_segment_start(&lines, src_marker_idx, indent_style)
+    else {
+        return Ok(false);
+    };
+    let Some(src_end_idx) =
+        find_bullet_subtree_end(&lines, src_start_idx, src_marker_idx, indent_style)
+    else {
+        return Ok(false);
+    };
+
+    // Merging into one's own descendant has no file equivalent
+    let Some(target_marker_idx) = find_marker_idx(&lines, target_block_id) else {
+        return Ok(false);
+    };
+    if (src_start_idx..=src_end_idx).contains(&target_marker_idx) {
+        return Ok(false);
+    }
+
+    // The merged block's metadata went with it; what follows is its children
+    let src_marker_indent = indent_len(&lines[src_marker_idx]);
+    let mut children_start = src_marker_idx + 1;
+    while children_start <= src_end_idx
+        && indent_len(&lines[children_start]) == src_marker_indent
+        && crate::utils::markdown::is_metadata_line(lines[children_start].trim_start())
+    {
+        children_start += 1;
+    }
+    let mut child_lines: Vec<String> = lines[children_start..=src_end_idx].to_vec();
+
+    // Anything less indented than a child (e.g. a root code block) is not part of the subtree
+    if child_lines
+        .iter()
+        .any(|line| line.trim().is_empty() || indent_len(line) < src_marker_indent)
+    {
+        return Ok(false);
+    }
+
+    lines.drain(src_start_idx..=src_end_idx);
+
+    // ---- Append the children after the target's subtree ----
+    let Some(target_marker_idx) = find_marker_idx(&lines, target_block_id) else {
+        return Ok(false);
+    };
+    let Some(target_start_idx) = find_bullet_segment_start(&lines, target_marker_idx, indent_style)
+    else {
+        return Ok(false);
+    };
+    let Some(target_end_idx) =
+        find_bullet_subtree_end(&lines, target_start_idx, target_marker_idx, indent_style)
+    else {
+        return Ok(false);
+    };
+
+    let indent_delta = indent_len(&lines[target_marker_idx]) as isize - src_marker_indent as isize;
+    reindent_subtree_lines(&mut child_lines, indent_delta, indent_style)?;
+
+    let insert_at = target_end_idx + 1;
+    let target_subtree_end = insert_at + child_lines.len();
+    lines.splice(insert_at..insert_at, child_lines);
+
+    // If the file disagrees with the DB about what the target now holds, rewrite instead
+    let file_descendants: HashSet<String> = lines[target_marker_idx + 1..target_subtree_end]
+        .iter()
+        .filter_map(|line| {
+            let trimmed = line.trim_start();
+            trimmed.strip_prefix("ID::").map(|s| s.to_string())
+        })
+        .collect();
+    if file_descendants != db_descendants {
+        return Ok(false);
+    }
+
+    // ---- Rewrite the target's content with the merged content ----
+    let indent = indent_style.whitespace(indent_len(&lines[target_start_idx]));
+    let replacement = bullet_content_to_segment_lines(&indent, checked, &content);
+    lines.splice(target_start_idx..target_marker_idx, replacement);
+
+    write_page_lines(workspace_path, &full_path, lines, had_trailing_newline).await?;
     update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
 
     Ok(true)
@@ -805,13 +1127,13 @@ pub async fn sync_page_to_markdown_after_create(
     workspace_path: &str,
     page_id: &str,
     created_block_id: &str,
-) -> Result<(), String> {
+) -> Result<SyncStrategy, String> {
     if try_patch_bullet_block_insertion(conn_mutex, workspace_path, page_id, created_block_id)
         .await?
     {
-        return Ok(());
+        return Ok(SyncStrategy::InsertionPatch);
     }
-    sync_page_to_markdown(conn_mutex, workspace_path, page_id).await
+    sync_page_to_markdown_after_block_change(conn_mutex, workspace_path, page_id, None).await
 }
 
 /// Sync a page after a block update, attempting safe incremental content patch.
@@ -820,12 +1142,12 @@ pub async fn sync_page_to_markdown_after_update(
     workspace_path: &str,
     page_id: &str,
     updated_block_id: &str,
-) -> Result<(), String> {
+) -> Result<SyncStrategy, String> {
     if try_patch_bullet_block_content(conn_mutex, workspace_path, page_id, updated_block_id).await?
     {
-        return Ok(());
+        return Ok(SyncStrategy::ContentPatch);
     }
-    sync_page_to_markdown(conn_mutex, workspace_path, page_id).await
+    sync_page_to_markdown_after_block_change(conn_mutex, workspace_path, page_id, None).await
 }
 
 /// Sync a page after a block deletion, attempting safe incremental deletion.
@@ -834,13 +1156,13 @@ pub async fn sync_page_to_markdown_after_delete(
     workspace_path: &str,
     page_id: &str,
     deleted_block_id: &str,
-) -> Result<(), String> {
+) -> Result<SyncStrategy, String> {
     if try_patch_bullet_block_deletion(conn_mutex, workspace_path, page_id, deleted_block_id)
         .await?
     {
-        return Ok(());
+        return Ok(SyncStrategy::DeletionPatch);
     }
-    sync_page_to_markdown(conn_mutex, workspace_path, page_id).await
+    sync_page_to_markdown_after_block_change(conn_mutex, workspace_path, page_id, None).await
 }
 
 /// Sync a page after a block move/indent/outdent, attempting safe incremental relocation.
@@ -850,10 +1172,10 @@ pub async fn sync_page_to_markdown_after_move(
     workspace_path: &str,
     page_id: &str,
     moved_block_id: &str,
-) -> Result<(), String> {
+) -> Result<SyncStrategy, String> {
     // Try incremental patch first; if it fails for any reason, fall back to full rewrite
     match try_patch_bullet_subtree_relocation(conn_mutex, workspace_path, page_id, moved_block_id).await {
-        Ok(true) => return Ok(()),
+        Ok(true) => return Ok(SyncStrategy::RelocationPatch),
         Ok(false) => {
             // Patch returned false (conditions not met), fall back to full rewrite
         }
@@ -862,7 +1184,37 @@ pub async fn sync_page_to_markdown_after_move(
             eprintln!("[page_sync] Incremental patch failed, falling back to full rewrite: {}", e);
         }
     }
-    sync_page_to_markdown(conn_mutex, workspace_path, page_id).await
+    sync_page_to_markdown_after_block_change(conn_mutex, workspace_path, page_id, None).await
+}
+
+/// Sync a page after `merged_block_id` was merged into `target_block_id`, attempting a safe
+/// incremental patch. If the patch fails for any reason, falls back to full rewrite.
+pub async fn sync_page_to_markdown_after_merge(
+    conn_mutex: &Mutex<Connection>,
+    workspace_path: &str,
+    page_id: &str,
+    target_block_id: &str,
+    merged_block_id: &str,
+) -> Result<SyncStrategy, String> {
+    match try_patch_bullet_block_merge(
+        conn_mutex,
+        workspace_path,
+        page_id,
+        target_block_id,
+        merged_block_id,
+    )
+    .await
+    {
+        Ok(true) => return Ok(SyncStrategy::MergePatch),
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!(
+                "[page_sync] Merge patch failed, falling back to full rewrite: {}",
+                e
+            );
+        }
+    }
+    sync_page_to_markdown_after_block_change(conn_mutex, workspace_path, page_id, None).await
 }
 
 /// Sync a page's blocks from DB to its markdown file on disk.
@@ -884,6 +1236,7 @@ pub async fn sync_page_to_markdown_after_block_update(
 ) -> Result<(), String> {
     sync_page_to_markdown_after_block_change(conn_mutex, workspace_path, page_id, updated_block_id)
         .await
+        .map(|_| ())
 }
 
 /// Sync a page after a specific block change, allowing a targeted on-disk patch when safe.
@@ -892,7 +1245,7 @@ pub async fn sync_page_to_markdown_after_block_change(
     workspace_path: &str,
     page_id: &str,
     changed_block_id: Option<&str>,
-) -> Result<(), String> {
+) -> Result<SyncStrategy, String> {
     // Resolve file path up-front
     let file_path: Option<String> = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
@@ -905,31 +1258,87 @@ pub async fn sync_page_to_markdown_after_block_change(
     };
 
     if file_path.is_none() {
-        return Ok(()); // No file path, skip
+        return Ok(SyncStrategy::Skipped); // No file path, skip
     }
 
     if let Some(block_id) = changed_block_id {
         // Deletion patch
         if try_patch_bullet_block_deletion(conn_mutex, workspace_path, page_id, block_id).await? {
-            return Ok(());
+            return Ok(SyncStrategy::DeletionPatch);
         }
 
         // Content update patch
         if try_patch_bullet_block_content(conn_mutex, workspace_path, page_id, block_id).await? {
-            return Ok(());
+            return Ok(SyncStrategy::ContentPatch);
         }
     }
 
     // --- Full rewrite fallback (canonical behavior) ---
 
-    // Get all blocks for this page
-    let mut blocks: Vec<Block> = {
+    // Write to file
+    let full_path = std::path::Path::new(workspace_path).join(file_path.unwrap());
+
+    // Ensure parent directory exists
+    if let Some(parent) = full_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+        }
+    }
+
+    let existing = fs::read_to_string(&full_path).await.ok();
+    let markdown = {
         let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
+        render_page_file(&conn, workspace_path, page_id, existing.as_deref())?
+    };
+
+    write_file_atomic(&full_path, &markdown).await?;
+
+    update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
+
+    Ok(SyncStrategy::FullRewrite)
+}
+
+/// The exact file a full rewrite would write for a page, given the file it replaces
+pub(crate) fn render_page_file(
+    conn: &Connection,
+    workspace_path: &str,
+    page_id: &str,
+    existing: Option<&str>,
+) -> Result<String, String> {
+    let markdown = render_page_markdown(conn, page_id, load_indent_style(workspace_path))?;
+
+    // Frontmatter isn't stored as blocks: carry it over from the file being replaced
+    let frontmatter = existing.and_then(|text| split_frontmatter(text).0);
+    let markdown = prepend_frontmatter(frontmatter, &markdown);
+
+    let policy = load_trailing_newline_policy(workspace_path);
+    let had_trailing_newline = match policy {
+        TrailingNewlinePolicy::Preserve => existing
+            .map(|text| text.is_empty() || text.ends_with('\n'))
+            .unwrap_or(true),
+        _ => true,
+    };
+    Ok(apply_trailing_newline_policy(
+        &markdown,
+        policy,
+        had_trailing_newline,
+    ))
+}
+
+/// Serialize a page's current DB blocks (including metadata) to canonical markdown
+pub(crate) fn render_page_markdown(
+    conn: &Connection,
+    page_id: &str,
+    indent: IndentStyle,
+) -> Result<String, String> {
+    let mut blocks: Vec<Block> = {
         let mut stmt = conn
             .prepare(
                 "SELECT id, page_id, parent_id, content, order_weight,
-                is_collapsed, block_type, language, created_at, updated_at
-         FROM blocks WHERE page_id = ? ORDER BY order_weight",
+                is_collapsed, block_type, language, created_at, updated_at, order_key
+         FROM blocks WHERE page_id = ? ORDER BY order_key",
             )
             .map_err(|e| e.to_string())?;
 
@@ -941,6 +1350,7 @@ pub async fn sync_page_to_markdown_after_block_change(
                     parent_id: row.get(2)?,
                     content: row.get(3)?,
                     order_weight: row.get(4)?,
+                    order_key: row.get(10)?,
                     is_collapsed: row.get::<_, i32>(5)? != 0,
                     block_type: crate::models::block::string_to_block_type(
                         &row.get::<_, String>(6)?,
@@ -958,36 +1368,11 @@ pub async fn sync_page_to_markdown_after_block_change(
     };
 
     // Load metadata for all blocks
-    {
-        let conn = conn_mutex.lock().map_err(|e| e.to_string())?;
-        for block in &mut blocks {
-            let metadata = load_block_metadata_for_sync(&conn, &block.id)?;
-            block.metadata = metadata;
-        }
-    }
-
-    // Convert blocks to markdown (now includes metadata)
-    let markdown = blocks_to_markdown(&blocks);
-
-    // Write to file
-    let full_path = std::path::Path::new(workspace_path).join(file_path.unwrap());
-
-    // Ensure parent directory exists
-    if let Some(parent) = full_path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| format!("Failed to create parent directory: {}", e))?;
-        }
+    for block in &mut blocks {
+        block.metadata = load_block_metadata_for_sync(conn, &block.id)?;
     }
 
-    fs::write(&full_path, markdown)
-        .await
-        .map_err(|e| format!("Failed to write file: {}", e))?;
-
-    update_page_file_metadata(conn_mutex, &full_path, page_id).await?;
-
-    Ok(())
+    Ok(blocks_to_markdown(&blocks, indent))
 }
 
 /// Load metadata for a block (helper for page_sync)
@@ -1009,3 +1394,458 @@ fn load_block_metadata_for_sync(
 
     Ok(metadata)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp_page(text: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("oxinot_test_newline_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Page.md");
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_atomic_write_cleans_up_and_keeps_original() {
+        let path = write_temp_page("- original\n");
+        let dir = path.parent().unwrap().to_path_buf();
+        let temp_path = dir.join(".Page.md.tmp");
+
+        tauri::async_runtime::block_on(async {
+            write_file_atomic(&path, "- replaced\n").await.unwrap();
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "- replaced\n");
+            assert!(!temp_path.exists());
+
+            // A non-empty directory can't be renamed over, so the write fails
+            // after the temp file has been written
+            let blocked = dir.join("Blocked.md");
+            std::fs::create_dir_all(&blocked).unwrap();
+            std::fs::write(blocked.join("keep.md"), "- keep\n").unwrap();
+            assert!(write_file_atomic(&blocked, "- lost\n").await.is_err());
+            assert!(!dir.join(".Blocked.md.tmp").exists());
+            let kept = std::fs::read_to_string(blocked.join("keep.md")).unwrap();
+            assert_eq!(kept, "- keep\n");
+        });
+
+        // The temp file itself can't be created: the original stays intact
+        std::fs::create_dir_all(&temp_path).unwrap();
+        std::fs::write(temp_path.join("x"), "").unwrap();
+        let result = tauri::async_runtime::block_on(write_file_atomic(&path, "- lost\n"));
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- replaced\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_trailing_newline_always_adds_one() {
+        let path = write_temp_page("- a\n  ID::a");
+
+        assert!(
+            normalize_file_trailing_newline(&path, TrailingNewlinePolicy::Always, true).unwrap()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- a\n  ID::a");
+
+        assert!(
+            normalize_file_trailing_newline(&path, TrailingNewlinePolicy::Always, false).unwrap()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- a\n  ID::a\n");
+        assert!(
+            !normalize_file_trailing_newline(&path, TrailingNewlinePolicy::Always, false).unwrap()
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_trailing_newline_never_removes_all() {
+        let path = write_temp_page("- a\n  ID::a\n\n\n");
+
+        assert!(
+            normalize_file_trailing_newline(&path, TrailingNewlinePolicy::Never, false).unwrap()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- a\n  ID::a");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_trailing_newline_preserve_leaves_file_unchanged() {
+        let path = write_temp_page("- a\n  ID::a");
+
+        assert!(
+            !normalize_file_trailing_newline(&path, TrailingNewlinePolicy::Preserve, false)
+                .unwrap()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "- a\n  ID::a");
+
+        assert_eq!(
+            apply_trailing_newline_policy("- b\n", TrailingNewlinePolicy::Preserve, false),
+            "- b"
+        );
+        assert_eq!(
+            apply_trailing_newline_policy("- b", TrailingNewlinePolicy::Preserve, true),
+            "- b\n"
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_full_rewrite_keeps_frontmatter() {
+        tauri::async_runtime::block_on(async {
+            let dir = std::env::temp_dir()
+                .join(format!("oxinot_test_frontmatter_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'Body', 1.0);",
+            )
+            .unwrap();
+            let frontmatter = "---\nstatus: draft\n---\n";
+            std::fs::write(&full_path, format!("{}- Old\n  ID::b1\n", frontmatter)).unwrap();
+
+            let conn_mutex = Mutex::new(conn);
+            sync_page_to_markdown(&conn_mutex, &workspace, "p1")
+                .await
+                .unwrap();
+
+            let text = std::fs::read_to_string(&full_path).unwrap();
+            assert_eq!(text, format!("{}- Body\n  ID::b1\n", frontmatter));
+
+            std::fs::remove_dir_all(&dir).ok();
+        });
+    }
+
+    #[test]
+    fn test_update_reports_content_patch_or_full_rewrite() {
+        tauri::async_runtime::block_on(async {
+            let dir =
+                std::env::temp_dir().join(format!("oxinot_test_strategy_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'Old', 1.0);",
+            )
+            .unwrap();
+            std::fs::write(
+                &full_path,
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap(),
+            )
+            .unwrap();
+            let conn_mutex = Mutex::new(conn);
+            update_page_file_metadata(&conn_mutex, &full_path, "p1")
+                .await
+                .unwrap();
+
+            let set_content = |content: &str| {
+                conn_mutex
+                    .lock()
+                    .unwrap()
+                    .execute("UPDATE blocks SET content = ? WHERE id = 'b1'", [content])
+                    .unwrap();
+            };
+
+            set_content("New");
+            let strategy = sync_page_to_markdown_after_update(&conn_mutex, &workspace, "p1", "b1")
+                .await
+                .unwrap();
+            assert_eq!(strategy, SyncStrategy::ContentPatch);
+            assert_eq!(
+                std::fs::read_to_string(&full_path).unwrap(),
+                "- New\n  ID::b1\n"
+            );
+
+            // Edited outside the app: mtime/size no longer match the DB
+            let mut text = std::fs::read_to_string(&full_path).unwrap();
+            text.push_str("- External\n");
+            std::fs::write(&full_path, text).unwrap();
+
+            set_content("Newer");
+            let strategy = sync_page_to_markdown_after_update(&conn_mutex, &workspace, "p1", "b1")
+                .await
+                .unwrap();
+            assert_eq!(strategy, SyncStrategy::FullRewrite);
+            assert_eq!(
+                std::fs::read_to_string(&full_path).unwrap(),
+                "- Newer\n  ID::b1\n"
+            );
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_content_patch_keeps_nested_indentation() {
+        tauri::async_runtime::block_on(async {
+            let dir =
+                std::env::temp_dir().join(format!("oxinot_test_nested_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'Parent', 1.0);
+                 INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                    VALUES ('b2', 'p1', 'b1', 'Child', 1.0);",
+            )
+            .unwrap();
+            std::fs::write(
+                &full_path,
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap(),
+            )
+            .unwrap();
+            let conn_mutex = Mutex::new(conn);
+            update_page_file_metadata(&conn_mutex, &full_path, "p1")
+                .await
+                .unwrap();
+
+            conn_mutex
+                .lock()
+                .unwrap()
+                .execute("UPDATE blocks SET content = 'Kid' WHERE id = 'b2'", [])
+                .unwrap();
+            let strategy = sync_page_to_markdown_after_update(&conn_mutex, &workspace, "p1", "b2")
+                .await
+                .unwrap();
+            assert_eq!(strategy, SyncStrategy::ContentPatch);
+            // The child stays at its own level, not at its ID marker's
+            assert_eq!(
+                std::fs::read_to_string(&full_path).unwrap(),
+                "- Parent\n  ID::b1\n  - Kid\n    ID::b2\n"
+            );
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_content_patch_keeps_checkbox_prefix() {
+        tauri::async_runtime::block_on(async {
+            let dir =
+                std::env::temp_dir().join(format!("oxinot_test_checkbox_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'Buy milk', 1.0);
+                 INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m1', 'b1', 'checked', 'true');",
+            )
+            .unwrap();
+            std::fs::write(
+                &full_path,
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap(),
+            )
+            .unwrap();
+            assert!(std::fs::read_to_string(&full_path)
+                .unwrap()
+                .starts_with("- [x] Buy milk\n  ID::b1\n"));
+            let conn_mutex = Mutex::new(conn);
+            update_page_file_metadata(&conn_mutex, &full_path, "p1")
+                .await
+                .unwrap();
+
+            conn_mutex
+                .lock()
+                .unwrap()
+                .execute(
+                    "UPDATE blocks SET content = 'Buy oat milk' WHERE id = 'b1'",
+                    [],
+                )
+                .unwrap();
+            let strategy = sync_page_to_markdown_after_update(&conn_mutex, &workspace, "p1", "b1")
+                .await
+                .unwrap();
+            assert_eq!(strategy, SyncStrategy::ContentPatch);
+            assert_eq!(
+                std::fs::read_to_string(&full_path).unwrap(),
+                "- [x] Buy oat milk\n  ID::b1\n"
+            );
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_insertion_patch_follows_tab_indent_style() {
+        tauri::async_runtime::block_on(async {
+            let dir =
+                std::env::temp_dir().join(format!("oxinot_test_tabs_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+            let style = IndentStyle {
+                width: 4,
+                use_tabs: true,
+            };
+            crate::commands::workspace::set_indent_style(workspace.clone(), style).unwrap();
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'Parent', 1.0);",
+            )
+            .unwrap();
+            std::fs::write(
+                &full_path,
+                render_page_markdown(&conn, "p1", style).unwrap(),
+            )
+            .unwrap();
+            let conn_mutex = Mutex::new(conn);
+            update_page_file_metadata(&conn_mutex, &full_path, "p1")
+                .await
+                .unwrap();
+
+            conn_mutex
+                .lock()
+                .unwrap()
+                .execute(
+                    "INSERT INTO blocks (id, page_id, parent_id, content, order_weight)
+                     VALUES ('b2', 'p1', 'b1', 'Child', 1.0)",
+                    [],
+                )
+                .unwrap();
+            let strategy = sync_page_to_markdown_after_create(&conn_mutex, &workspace, "p1", "b2")
+                .await
+                .unwrap();
+            assert_eq!(strategy, SyncStrategy::InsertionPatch);
+            assert_eq!(
+                std::fs::read_to_string(&full_path).unwrap(),
+                "- Parent\n\tID::b1\n\t- Child\n\t\tID::b2\n"
+            );
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_merge_patch_matches_full_rewrite() {
+        tauri::async_runtime::block_on(async {
+            let dir =
+                std::env::temp_dir().join(format!("oxinot_test_merge_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'Target', 1.0);
+                 INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('c1', 'p1', 'b1', 'Kept child', 1.0);
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b2', 'p1', ' merged', 2.0);
+                 INSERT INTO block_metadata (id, block_id, key, value) VALUES ('m1', 'b2', 'status', 'done');
+                 INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('c2', 'p1', 'b2', 'Moved child', 1.0);
+                 INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('g2', 'p1', 'c2', 'Grandchild', 1.0);
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b3', 'p1', 'After', 3.0);",
+            )
+            .unwrap();
+            std::fs::write(
+                &full_path,
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap(),
+            )
+            .unwrap();
+            let conn_mutex = Mutex::new(conn);
+            update_page_file_metadata(&conn_mutex, &full_path, "p1")
+                .await
+                .unwrap();
+
+            // What merge_blocks does in the DB
+            conn_mutex
+                .lock()
+                .unwrap()
+                .execute_batch(
+                    "UPDATE blocks SET parent_id = 'b1', order_weight = 2.0, order_key = NULL WHERE id = 'c2';
+                     UPDATE blocks SET content = 'Target merged' WHERE id = 'b1';
+                     DELETE FROM block_metadata WHERE block_id = 'b2';
+                     DELETE FROM blocks WHERE id = 'b2';",
+                )
+                .unwrap();
+
+            let strategy =
+                sync_page_to_markdown_after_merge(&conn_mutex, &workspace, "p1", "b1", "b2")
+                    .await
+                    .unwrap();
+            assert_eq!(strategy, SyncStrategy::MergePatch);
+
+            let expected = {
+                let conn = conn_mutex.lock().unwrap();
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap()
+            };
+            assert_eq!(std::fs::read_to_string(&full_path).unwrap(), expected);
+            assert!(expected.contains("- Target merged\n  ID::b1\n  - Kept child\n"));
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_relocation_patch_leaves_next_sibling_intact() {
+        tauri::async_runtime::block_on(async {
+            let dir =
+                std::env::temp_dir().join(format!("oxinot_test_move_{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let workspace = dir.to_string_lossy().to_string();
+            let full_path = dir.join("Page.md");
+
+            let conn = Connection::open_in_memory().unwrap();
+            crate::db::schema::init_schema(&conn).unwrap();
+            conn.execute_batch(
+                "INSERT INTO pages (id, title, file_path) VALUES ('p1', 'Page', 'Page.md');
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b1', 'p1', 'One', 1.0);
+                 INSERT INTO blocks (id, page_id, parent_id, content, order_weight) VALUES ('c1', 'p1', 'b1', 'Child', 1.0);
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b2', 'p1', 'Two', 2.0);
+                 INSERT INTO blocks (id, page_id, content, order_weight) VALUES ('b3', 'p1', 'Three', 3.0);",
+            )
+            .unwrap();
+            std::fs::write(
+                &full_path,
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap(),
+            )
+            .unwrap();
+            let conn_mutex = Mutex::new(conn);
+            update_page_file_metadata(&conn_mutex, &full_path, "p1")
+                .await
+                .unwrap();
+
+            conn_mutex
+                .lock()
+                .unwrap()
+                .execute("UPDATE blocks SET order_weight = 2.5, order_key = NULL WHERE id = 'b1'", [])
+                .unwrap();
+            let strategy = sync_page_to_markdown_after_move(&conn_mutex, &workspace, "p1", "b1")
+                .await
+                .unwrap();
+            assert_eq!(strategy, SyncStrategy::RelocationPatch);
+
+            let expected = {
+                let conn = conn_mutex.lock().unwrap();
+                render_page_markdown(&conn, "p1", IndentStyle::default()).unwrap()
+            };
+            assert_eq!(std::fs::read_to_string(&full_path).unwrap(), expected);
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        });
+    }
+}
@@ -10,7 +10,7 @@
 //! - Workspace containment validation
 //! - Filename sanitization
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Normalize a file path to standard format.
 ///
@@ -180,6 +180,23 @@ pub fn validate_filename(filename: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// `fs::rename` silently replaces an existing file, so refuse up front. Renaming onto
+/// the same entry (a case-only rename on a case-insensitive filesystem) is allowed.
+pub fn ensure_rename_target_free(old: &Path, new_path: &Path) -> Result<(), String> {
+    if !new_path.exists() {
+        return Ok(());
+    }
+    let same_entry = match (old.canonicalize(), new_path.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    if same_entry {
+        Ok(())
+    } else {
+        Err(format!("'{}' already exists", new_path.display()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -301,4 +318,21 @@ mod tests {
         assert!(validate_no_path_traversal("a/b/c/file.md", "path").is_ok());
         assert!(validate_no_path_traversal("file.md", "path").is_ok());
     }
+
+    #[test]
+    fn test_ensure_rename_target_free() {
+        let dir = std::env::temp_dir().join(format!("oxinot_test_rename_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = dir.join("Notes.md");
+        let other = dir.join("Other.md");
+        std::fs::write(&old, "- a\n").unwrap();
+        std::fs::write(&other, "- b\n").unwrap();
+
+        assert!(ensure_rename_target_free(&old, &dir.join("Fresh.md")).is_ok());
+        assert!(ensure_rename_target_free(&old, &other).is_err());
+        // The entry itself, as a case-insensitive filesystem resolves "notes.md"
+        assert!(ensure_rename_target_free(&old, &old).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
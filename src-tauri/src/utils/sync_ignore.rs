@@ -0,0 +1,180 @@
+use crate::config::SYNC_IGNORE_FILENAME;
+use std::path::Path;
+
+/// One line of `.oxinotignore`
+#[derive(Debug, Clone)]
+struct IgnorePattern {
+    glob: String,
+    /// `!pattern`: re-include an entry an earlier pattern excluded
+    negated: bool,
+    /// `pattern/`: only matches directories
+    dir_only: bool,
+    /// Contains a `/` (other than a trailing one): matched against the whole
+    /// workspace-relative path instead of just the entry name
+    anchored: bool,
+}
+
+/// Exclusion patterns from the workspace's `.oxinotignore`, in `.gitignore` syntax:
+/// `#` comments, `*` and `?` within a path segment, `**` across segments, a trailing
+/// `/` for directories only and a leading `!` to re-include. The last matching
+/// pattern wins.
+///
+/// `dir/**` also matches `dir` itself, so the whole directory is skipped instead of
+/// being walked only to ignore everything inside it.
+#[derive(Debug, Clone, Default)]
+pub struct SyncIgnore {
+    patterns: Vec<IgnorePattern>,
+}
+
+impl SyncIgnore {
+    /// Read `.oxinotignore` from the workspace root; a missing or unreadable file
+    /// ignores nothing
+    pub fn load(workspace_root: &Path) -> Self {
+        std::fs::read_to_string(workspace_root.join(SYNC_IGNORE_FILENAME))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let glob = line.trim_start_matches('/');
+                (!glob.is_empty()).then(|| IgnorePattern {
+                    glob: glob.to_string(),
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether the entry at `rel_path` (workspace-relative, `/`-separated) is excluded
+    pub fn is_ignored(&self, rel_path: &str, is_dir: bool) -> bool {
+        let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+
+        let mut ignored = false;
+        for pattern in &self.patterns {
+            if pattern.dir_only && !is_dir {
+                continue;
+            }
+            let subject = if pattern.anchored { rel_path } else { name };
+            let matched = glob_match(&pattern.glob, subject)
+                || (is_dir
+                    && pattern
+                        .glob
+                        .strip_suffix("/**")
+                        .is_some_and(|dir| glob_match(dir, subject)));
+            if matched {
+                ignored = !pattern.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Match `text` against a glob where `*` and `?` stay within one `/`-separated
+/// segment and `**` spans any number of segments
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    match_from(&pattern, &text)
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            // Zero segments, or skip segments one `/` at a time
+            match_from(rest, text)
+                || text
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c == '/')
+                    .any(|(i, _)| match_from(rest, &text[i + 1..]))
+        }
+        ['*', '*'] => true,
+        ['*', rest @ ..] => {
+            let segment_len = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+            (0..=segment_len).any(|skip| match_from(rest, &text[skip..]))
+        }
+        ['?', rest @ ..] => match text {
+            [c, text_rest @ ..] if *c != '/' => match_from(rest, text_rest),
+            _ => false,
+        },
+        [p, rest @ ..] => match text {
+            [c, text_rest @ ..] if c == p => match_from(rest, text_rest),
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.tmp.md", "draft.tmp.md"));
+        assert!(!glob_match("*.tmp.md", "notes/draft.tmp.md"));
+        assert!(glob_match("archive/**", "archive/2020/old.md"));
+        assert!(!glob_match("archive/**", "archived/old.md"));
+        assert!(glob_match("**/drafts", "drafts"));
+        assert!(glob_match("**/drafts", "a/b/drafts"));
+        assert!(glob_match("a/**/b.md", "a/b.md"));
+        assert!(glob_match("a/**/b.md", "a/x/y/b.md"));
+        assert!(glob_match("page-?.md", "page-1.md"));
+        assert!(!glob_match("page-?.md", "page-10.md"));
+    }
+
+    #[test]
+    fn test_is_ignored_follows_gitignore_rules() {
+        let ignore = SyncIgnore::parse(
+            "# attachments and old stuff\n\
+             archive/**\n\
+             *.tmp.md\n\
+             assets/\n\
+             /Scratch.md\n\
+             !keep.tmp.md\n",
+        );
+
+        // `dir/**` excludes the directory itself so it is never walked
+        assert!(ignore.is_ignored("archive", true));
+        assert!(ignore.is_ignored("archive/old.md", false));
+        assert!(!ignore.is_ignored("notes/archive", true));
+
+        // Name-only patterns match at any depth
+        assert!(ignore.is_ignored("draft.tmp.md", false));
+        assert!(ignore.is_ignored("notes/deep/draft.tmp.md", false));
+        assert!(!ignore.is_ignored("notes/keep.tmp.md", false));
+
+        // Trailing `/` only matches directories
+        assert!(ignore.is_ignored("projects/assets", true));
+        assert!(!ignore.is_ignored("assets", false));
+
+        // Leading `/` anchors to the workspace root
+        assert!(ignore.is_ignored("Scratch.md", false));
+        assert!(!ignore.is_ignored("notes/Scratch.md", false));
+
+        assert!(!ignore.is_ignored("notes/today.md", false));
+        assert!(SyncIgnore::parse("\n# nothing\n").is_empty());
+    }
+}
@@ -13,6 +13,7 @@ interface EmbeddedBlock {
   parent_id: string | null;
   content: string;
   order_weight: number;
+  order_key: string;
   is_collapsed: boolean;
 }
 
@@ -57,6 +58,7 @@ export const EmbeddedBlockCard: React.FC<EmbeddedBlockCardProps> = ({
             null,
           content: (b.content ?? "").toString(),
           order_weight: Number(b.orderWeight ?? b.order_weight ?? 0),
+          order_key: String(b.orderKey ?? b.order_key ?? ""),
           is_collapsed: !!(b.isCollapsed ?? b.is_collapsed),
         }));
 
@@ -103,6 +105,9 @@ export const EmbeddedBlockCard: React.FC<EmbeddedBlockCardProps> = ({
     childMap[key].sort((a, b) => {
       const ba = byId[a];
       const bb = byId[b];
+      if (ba?.order_key && bb?.order_key && ba.order_key !== bb.order_key) {
+        return ba.order_key < bb.order_key ? -1 : 1;
+      }
       return (ba?.order_weight ?? 0) - (bb?.order_weight ?? 0);
     });
   }
@@ -13,6 +13,7 @@ interface EmbeddedBlock {
   parent_id: string | null;
   content: string;
   order_weight: number;
+  order_key: string;
   is_collapsed: boolean;
 }
 
@@ -100,6 +101,7 @@ export const EmbeddedPageCard: React.FC<EmbeddedPageCardProps> = ({
             null,
           content: (b.content ?? "").toString(),
           order_weight: Number(b.orderWeight ?? b.order_weight ?? 0),
+          order_key: String(b.orderKey ?? b.order_key ?? ""),
           is_collapsed: !!(b.isCollapsed ?? b.is_collapsed),
         }));
 
@@ -146,6 +148,9 @@ export const EmbeddedPageCard: React.FC<EmbeddedPageCardProps> = ({
     childMap[key].sort((a, b) => {
       const ba = byId[a];
       const bb = byId[b];
+      if (ba?.order_key && bb?.order_key && ba.order_key !== bb.order_key) {
+        return ba.order_key < bb.order_key ? -1 : 1;
+      }
       return (ba?.order_weight ?? 0) - (bb?.order_weight ?? 0);
     });
   }
@@ -30,6 +30,8 @@ interface GraphEdge extends d3.SimulationLinkDatum<GraphNode> {
   target: string | GraphNode;
   relation_type: string;
   is_embed: boolean;
+  weight: number;
+  is_bidirectional: boolean;
 }
 
 interface GraphData {
@@ -93,6 +95,8 @@ export function GraphViewModal({
             target: string;
             relation_type: string;
             is_embed: boolean;
+            weight: number;
+            is_bidirectional: boolean;
           }>;
         }>("get_page_graph_data", {
           workspacePath,
@@ -121,6 +125,8 @@ export function GraphViewModal({
             target: string;
             relation_type: string;
             is_embed: boolean;
+            weight: number;
+            is_bidirectional: boolean;
           }>;
         }>("get_graph_data", {
           workspacePath,
@@ -210,7 +216,10 @@ export function GraphViewModal({
           ? "var(--color-text-secondary)"
           : "var(--color-text-tertiary)",
       )
-      .attr("stroke-width", (d) => (d.is_embed ? 2 : 1))
+      .attr(
+        "stroke-width",
+        (d) => (d.is_embed ? 2 : 1) + Math.log2(Math.max(d.weight, 1)),
+      )
       .attr("opacity", 0.6);
 
     // Create nodes
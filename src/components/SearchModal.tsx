@@ -119,6 +119,15 @@ export function SearchModal({ opened, onClose }: SearchModalProps) {
   }, [pages, collapsed]);
 
   const handleResultClick = (result: SearchResult) => {
+    if (workspacePath) {
+      invoke("record_search_history", {
+        workspacePath,
+        query,
+        resultCount: results.length,
+      }).catch((error) => {
+        console.error("Failed to record search history:", error);
+      });
+    }
     setCurrentPageId(result.page_id);
     showPage(result.page_id);
     onClose();
@@ -37,7 +37,6 @@ export const useGitManagement = (
   const remoteUrl = useGitStore((state) => state.remoteUrl);
   const autoCommitEnabled = useGitStore((state) => state.autoCommitEnabled);
   const autoCommitInterval = useGitStore((state) => state.autoCommitInterval);
-  const autoCommit = useGitStore((state) => state.autoCommit);
 
   const addError = useErrorStore((state) => state.addError);
   const [gitMenuOpen, setGitMenuOpen] = useState(false);
@@ -93,22 +92,6 @@ export const useGitManagement = (
     };
   }, [workspacePath, isGitRepo, checkGitStatus]);
 
-  // Auto-commit on configured interval
-  useEffect(() => {
-    if (!workspacePath || !autoCommitEnabled) return;
-
-    const intervalId = setInterval(
-      () => {
-        autoCommit(workspacePath).catch((error) => {
-          console.error("[useGitManagement] Auto-commit failed:", error);
-        });
-      },
-      autoCommitInterval * 60 * 1000,
-    );
-
-    return () => clearInterval(intervalId);
-  }, [workspacePath, autoCommitEnabled, autoCommitInterval, autoCommit]);
-
   const handleGitCommit = async () => {
     if (!workspacePath || !hasGitChanges) return;
 
@@ -168,8 +151,17 @@ export const useGitManagement = (
     if (!workspacePath || !remoteUrl) return;
 
     try {
-      await gitPull(workspacePath);
-      showToast({ message: "Changes pulled from remote", type: "success" });
+      const result = await gitPull(workspacePath);
+      if (result.outcome === "Conflicts") {
+        addError("Merge conflict detected: Resolve manually and commit", {
+          type: "warning",
+          details: [result.message, ...result.conflicted_files].join("\n"),
+        });
+      } else if (result.outcome === "UpToDate") {
+        showToast({ message: "Already up to date", type: "info" });
+      } else {
+        showToast({ message: "Changes pulled from remote", type: "success" });
+      }
     } catch (error) {
       const errorMessage =
         error instanceof Error ? error.message : "Unknown error occurred";
@@ -1,4 +1,5 @@
 import { useErrorStore } from "@/stores/errorStore";
+import { useGitStore } from "@/stores/gitStore";
 import { usePageStore } from "@/stores/pageStore";
 import { useWorkspaceStore } from "@/stores/workspaceStore";
 import { invoke } from "@tauri-apps/api/core";
@@ -77,6 +78,7 @@ export const useWorkspaceInitializer = (
         );
 
         await loadPages();
+        await useGitStore.getState().loadAutoCommitSettings(workspacePath);
 
         setIsInitialized(true);
         setShowMigration(false);
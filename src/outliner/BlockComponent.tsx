@@ -44,6 +44,7 @@ import {
   type BlockData,
   useBlockContent,
   useBlockIsCollapsed,
+  useBlockLanguage,
   useBlockMetadata,
   useBlockStore,
   useBlockType,
@@ -106,6 +107,7 @@ export const BlockComponent: React.FC<BlockComponentProps> = memo(
 
     const blockContent = useBlockContent(blockId);
     const blockType = useBlockType(blockId);
+    const blockLanguage = useBlockLanguage(blockId);
     const isCollapsed = useBlockIsCollapsed(blockId);
     const childrenIds = useChildrenIds(blockId);
     const hasChildren = childrenIds.length > 0;
@@ -118,6 +120,11 @@ export const BlockComponent: React.FC<BlockComponentProps> = memo(
     const isAiPrompt = blockType === "ai-prompt";
     const isAiResponse = blockType === "ai-response";
     const isSubpageHeader = blockType === "subpage-header";
+    // Heading blocks keep their level (1-6) in `language`
+    const headingLevel =
+      blockType === "heading"
+        ? Math.min(Math.max(Number(blockLanguage) || 1, 1), 6)
+        : null;
     const isAiLocked = useIsBlockLocked(blockId);
 
     // DnD sortable hook for block reordering
@@ -2440,7 +2447,11 @@ export const BlockComponent: React.FC<BlockComponentProps> = memo(
 
             {/* Content Editor */}
             <div
-              className="block-content-wrapper"
+              className={
+                headingLevel
+                  ? `block-content-wrapper block-heading block-heading-${headingLevel}`
+                  : "block-content-wrapper"
+              }
               style={{ position: "relative" }}
             >
               {isAiResponse ? (
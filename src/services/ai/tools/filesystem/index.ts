@@ -203,12 +203,16 @@ Notes:
   }),
   isDangerous: false,
   requiresApproval: true,
-  execute: async ({ oldPath, newName }) => {
+  execute: async ({ oldPath, newName }, context) => {
     console.log(`[rename_file] Renaming ${oldPath} to ${newName}`);
 
     try {
-      // Rename using the API (which takes oldPath and newName)
-      const data = await tauriAPI.renamePath(oldPath, newName);
+      // Rename through the DB-aware API so page paths and links follow the file
+      const data = await tauriAPI.renamePathWithDb(
+        context.workspacePath,
+        oldPath,
+        newName,
+      );
       const success = !!data;
 
       // Construct new path for the event payload
@@ -265,12 +269,16 @@ Notes:
   }),
   isDangerous: false,
   requiresApproval: true,
-  execute: async ({ sourcePath, destinationPath }) => {
+  execute: async ({ sourcePath, destinationPath }, context) => {
     console.log(`[move_file] Moving ${sourcePath} to ${destinationPath}`);
 
     try {
-      // Move using the API (which takes sourcePath and targetParentPath)
-      const data = await tauriAPI.movePath(sourcePath, destinationPath);
+      // Move through the DB-aware API so page paths and links follow the file
+      const data = await tauriAPI.movePathWithDb(
+        context.workspacePath,
+        sourcePath,
+        destinationPath,
+      );
       const success = !!data;
 
       if (success) {
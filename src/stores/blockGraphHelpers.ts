@@ -29,6 +29,21 @@ export function getInsertBelowTarget(
   };
 }
 
+/**
+ * Sibling order: by orderKey, or by orderWeight when either block has no key
+ * (optimistic blocks the backend has not stored yet).
+ */
+export function compareBlockPosition(
+  a: BlockData | undefined,
+  b: BlockData | undefined,
+): number {
+  if (a?.orderKey && b?.orderKey) {
+    if (a.orderKey === b.orderKey) return 0;
+    return a.orderKey < b.orderKey ? -1 : 1;
+  }
+  return (a?.orderWeight ?? 0) - (b?.orderWeight ?? 0);
+}
+
 /**
  * Efficiently updates childrenMap based on updated and deleted blocks.
  * Avoids O(N) full traversal by only processing affected parents.
@@ -108,19 +123,18 @@ export function updateChildrenMap(
   }
 
   // 3. Re-sort children for affected parents
-  // Create a temporary map of updated weights for faster lookup during sort
-  const updatedWeights = new Map(
-    updatedBlocks.map((b) => [b.id, b.orderWeight]),
-  );
+  // Create a temporary map of updated blocks for faster lookup during sort
+  const updatedById = new Map(updatedBlocks.map((b) => [b.id, b]));
 
   for (const parentKey of affectedParentIds) {
     const list = childrenMap[parentKey];
     if (list) {
-      list.sort((a, b) => {
-        const wA = updatedWeights.get(a) ?? blocksById[a]?.orderWeight ?? 0;
-        const wB = updatedWeights.get(b) ?? blocksById[b]?.orderWeight ?? 0;
-        return wA - wB;
-      });
+      list.sort((a, b) =>
+        compareBlockPosition(
+          updatedById.get(a) ?? blocksById[a],
+          updatedById.get(b) ?? blocksById[b],
+        ),
+      );
     }
   }
 }
@@ -145,11 +159,11 @@ export function normalizeBlocks(blocks: BlockData[]): {
     childrenMap[parentKey].push(block.id);
   }
 
-  // Sort by orderWeight
+  // Sort by position among siblings
   for (const key of Object.keys(childrenMap)) {
-    childrenMap[key].sort((a, b) => {
-      return blocksById[a].orderWeight - blocksById[b].orderWeight;
-    });
+    childrenMap[key].sort((a, b) =>
+      compareBlockPosition(blocksById[a], blocksById[b]),
+    );
   }
 
   return { blocksById, childrenMap };
@@ -19,8 +19,18 @@ export interface BlockData {
   parentId: string | null;
   content: string;
   orderWeight: number;
+  /** Sibling order key; absent on optimistic blocks the backend has not stored yet */
+  orderKey?: string;
   isCollapsed: boolean;
-  blockType: "bullet" | "code" | "fence" | "ai-prompt" | "ai-response" | "subpage-header";
+  blockType:
+    | "bullet"
+    | "code"
+    | "fence"
+    | "heading"
+    | "ai-prompt"
+    | "ai-response"
+    | "subpage-header";
+  /** Code language, or the level ("1"-"6") of a heading block */
   language?: string;
   createdAt: string;
   updatedAt: string;
@@ -1428,6 +1438,13 @@ export const useBlockMetadata = (id: string) =>
 export const useBlockType = (id: string) =>
   useBlockStore((state) => state.blocksById[id]?.blockType, shallow);
 
+/**
+ * Get block language only (code language, or heading level).
+ * Re-renders only when THIS block's language changes.
+ */
+export const useBlockLanguage = (id: string) =>
+  useBlockStore((state) => state.blocksById[id]?.language, shallow);
+
 /**
  * Get block status only (synced/syncing/error/optimistic).
  * Re-renders only when THIS block's sync status changes.
@@ -169,7 +169,9 @@ export const usePageStore = createWithEqualityFn<PageStore>()(
         }
 
         // Incremental update: get updated page from backend
-        const updatedPage = await invoke<PageData>("update_page_title", {
+        const { touchedPageIds, ...updatedPage } = await invoke<
+          PageData & { touchedPageIds: string[] }
+        >("update_page_title", {
           workspacePath,
           request: { id, title },
         });
@@ -178,6 +180,12 @@ export const usePageStore = createWithEqualityFn<PageStore>()(
         set((state) => {
           state.pagesById[id] = updatedPage;
         });
+
+        // Reload the open page only if its links to the renamed page were rewritten
+        const { currentPageId, loadPage } = useBlockStore.getState();
+        if (currentPageId && touchedPageIds.includes(currentPageId)) {
+          await loadPage(currentPageId);
+        }
       } catch (error) {
         // Rollback on error
         set((state) => {
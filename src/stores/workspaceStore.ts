@@ -40,6 +40,7 @@ interface WorkspaceState {
   createNewDirectory: (parentPath: string, dirName: string) => Promise<void>;
   deleteItem: (path: string) => Promise<void>;
   renameItem: (oldPath: string, newName: string) => Promise<void>;
+  moveItem: (sourcePath: string, targetParentPath: string) => Promise<void>;
   setFileContent: (content: string) => void;
   setError: (error: string | null) => void;
   clearError: () => void;
@@ -295,24 +296,17 @@ export const useWorkspaceStore = createWithEqualityFn<WorkspaceState>()(
           const { workspacePath } = get();
           if (!workspacePath) throw new Error("No workspace selected");
 
-          const newPath = await tauriAPI.renamePath(oldPath, newName);
+          // The backend updates the page row and rewrites wiki links in the same call,
+          // and refuses to overwrite an existing file.
+          const newPath = await tauriAPI.renamePathWithDb(
+            workspacePath,
+            oldPath,
+            newName,
+          );
 
           const { currentFile, currentPath } = get();
           const isRenamingCurrentFile = currentFile === oldPath;
 
-          // Delegate wiki link rewriting to backend for consistency and transactional safety.
-          // This prevents race conditions and performance issues from frontend file traversal.
-          try {
-            await tauriAPI.rewriteWikiLinksForPagePathChange(
-              workspacePath,
-              oldPath,
-              newPath,
-            );
-          } catch (e) {
-            console.warn("[renameItem] Failed to rewrite wiki links:", e);
-            // Don't fail the entire rename operation if link rewriting fails
-          }
-
           // Keep app state in sync with the renamed file path/content
           if (isRenamingCurrentFile) {
             const content = await tauriAPI.readFile(newPath);
@@ -342,6 +336,48 @@ export const useWorkspaceStore = createWithEqualityFn<WorkspaceState>()(
         }
       },
 
+      moveItem: async (sourcePath: string, targetParentPath: string) => {
+        try {
+          set({ isLoading: true, error: null });
+
+          const { workspacePath } = get();
+          if (!workspacePath) throw new Error("No workspace selected");
+
+          // The backend moves the page rows and rewrites path-qualified links in the
+          // same call, and refuses to overwrite an existing file.
+          const newPath = await tauriAPI.movePathWithDb(
+            workspacePath,
+            sourcePath,
+            targetParentPath,
+          );
+
+          const { currentFile, currentPath } = get();
+          if (currentFile === sourcePath) {
+            const content = await tauriAPI.readFile(newPath);
+            set({ currentFile: newPath, fileContent: content });
+          } else if (currentFile) {
+            try {
+              const content = await tauriAPI.readFile(currentFile);
+              set({ fileContent: content });
+            } catch (e) {
+              console.warn("[moveItem] Failed to reload current file:", e);
+            }
+          }
+
+          if (currentPath) {
+            await get().loadDirectory(currentPath);
+          }
+        } catch (err) {
+          const errorMessage =
+            err instanceof Error ? err.message : "Failed to move item";
+          set({ error: errorMessage });
+          console.error("Error moving item:", err);
+          throw err;
+        } finally {
+          set({ isLoading: false });
+        }
+      },
+
       setFileContent: (content: string) => {
         set({ fileContent: content });
       },
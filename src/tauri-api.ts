@@ -94,8 +94,9 @@ export interface QueryResultBlock {
   parentId: string | null;
   content: string;
   orderWeight: number;
+  orderKey: string;
   isCollapsed: boolean;
-  blockType: "bullet" | "code" | "fence";
+  blockType: "bullet" | "code" | "fence" | "heading";
   language?: string;
   createdAt: string;
   updatedAt: string;
@@ -103,10 +104,23 @@ export interface QueryResultBlock {
   pagePath: string;
 }
 
+export interface QueryAggregateRow {
+  group: string | null;
+  value: number | null;
+  row_count: number;
+}
+
+/** Result of a `count` / `sum:key` / `avg:key` query, optionally grouped */
+export type QueryAggregate =
+  | { type: "value"; value: number | null; row_count: number }
+  | { type: "table"; group_key: string; rows: QueryAggregateRow[] };
+
 export interface QueryResult {
   blocks: QueryResultBlock[];
   totalCount: number;
   error?: string;
+  /** Set for aggregate queries, which return no blocks */
+  aggregate?: QueryAggregate | null;
 }
 
 export interface SearchResult {
@@ -117,6 +131,13 @@ export interface SearchResult {
   content: string;
   snippet: string;
   rank: number;
+  score: number;
+}
+
+export interface SearchHistoryEntry {
+  query: string;
+  resultCount: number;
+  searchedAt: string;
 }
 
 export const tauriAPI = {
@@ -180,6 +201,35 @@ export const tauriAPI = {
     }
   },
 
+  recordSearchHistory: async (
+    workspacePath: string,
+    query: string,
+    resultCount: number,
+  ): Promise<void> => {
+    validatePath(workspacePath, "workspacePath");
+    return await invoke<void>("record_search_history", {
+      workspacePath,
+      query,
+      resultCount,
+    });
+  },
+
+  getSearchHistory: async (
+    workspacePath: string,
+    limit?: number,
+  ): Promise<SearchHistoryEntry[]> => {
+    validatePath(workspacePath, "workspacePath");
+    return await invoke<SearchHistoryEntry[]>("get_search_history", {
+      workspacePath,
+      limit,
+    });
+  },
+
+  clearSearchHistory: async (workspacePath: string): Promise<void> => {
+    validatePath(workspacePath, "workspacePath");
+    return await invoke<void>("clear_search_history", { workspacePath });
+  },
+
   // Wiki Link Index
   getPageBacklinks: async (
     workspacePath: string,
@@ -253,6 +303,21 @@ export const tauriAPI = {
     return await invoke<string>("rename_path", { oldPath, newName });
   },
 
+  renamePathWithDb: async (
+    workspacePath: string,
+    oldPath: string,
+    newName: string,
+  ): Promise<string> => {
+    validatePath(workspacePath, "workspacePath");
+    validatePath(oldPath, "oldPath");
+    validateFileName(newName);
+    return await invoke<string>("rename_path_with_db", {
+      workspacePath,
+      oldPath,
+      newName,
+    });
+  },
+
   movePath: async (
     sourcePath: string,
     targetParentPath: string,
@@ -262,6 +327,21 @@ export const tauriAPI = {
     return await invoke<string>("move_path", { sourcePath, targetParentPath });
   },
 
+  movePathWithDb: async (
+    workspacePath: string,
+    sourcePath: string,
+    targetParentPath: string,
+  ): Promise<string> => {
+    validatePath(workspacePath, "workspacePath");
+    validatePath(sourcePath, "sourcePath");
+    validatePath(targetParentPath, "targetParentPath");
+    return await invoke<string>("move_path_with_db", {
+      workspacePath,
+      sourcePath,
+      targetParentPath,
+    });
+  },
+
   convertFileToDirectory: async (filePath: string): Promise<string> => {
     validatePath(filePath, "filePath");
     return await invoke<string>("convert_file_to_directory", { filePath });
@@ -277,15 +357,18 @@ export const tauriAPI = {
     workspacePath: string,
     fromPath: string,
     toPath: string,
-  ): Promise<number> => {
+  ): Promise<{ updatedCount: number; touchedPageIds: string[] }> => {
     validatePath(workspacePath, "workspacePath");
     validatePath(fromPath, "fromPath");
     validatePath(toPath, "toPath");
-    return await invoke<number>("rewrite_wiki_links_for_page_path_change", {
-      workspacePath,
-      fromPath,
-      toPath,
-    });
+    return await invoke<{ updatedCount: number; touchedPageIds: string[] }>(
+      "rewrite_wiki_links_for_page_path_change",
+      {
+        workspacePath,
+        fromPath,
+        toPath,
+      },
+    );
   },
 
   // Workspace sync operations
@@ -299,6 +382,34 @@ export const tauriAPI = {
     );
   },
 
+  syncWorkspaceDryRun: async (
+    workspacePath: string,
+  ): Promise<{
+    added: { file_path: string; page_id: string | null }[];
+    updated: { file_path: string; page_id: string | null }[];
+    deleted: { file_path: string; page_id: string | null }[];
+    missing_folder_notes: string[];
+  }> => {
+    validatePath(workspacePath, "workspacePath");
+    return await invoke("sync_workspace_dry_run", { workspacePath });
+  },
+
+  detectExternalChanges: async (
+    workspacePath: string,
+  ): Promise<
+    {
+      page_id: string;
+      file_path: string;
+      recorded_mtime: number;
+      recorded_size: number;
+      current_mtime: number | null;
+      current_size: number | null;
+    }[]
+  > => {
+    validatePath(workspacePath, "workspacePath");
+    return await invoke("detect_external_changes", { workspacePath });
+  },
+
   reindexWorkspace: async (
     workspacePath: string,
   ): Promise<{ pages: number; blocks: number }> => {
@@ -309,6 +420,11 @@ export const tauriAPI = {
     );
   },
 
+  cancelReindex: async (workspacePath: string): Promise<boolean> => {
+    validatePath(workspacePath, "workspacePath");
+    return await invoke<boolean>("cancel_reindex", { workspacePath });
+  },
+
   // DB Maintenance
   vacuumDb: async (workspacePath: string): Promise<void> => {
     validatePath(workspacePath, "workspacePath");